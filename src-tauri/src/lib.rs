@@ -1,15 +1,58 @@
+use tauri::Manager;
+use tauri_plugin_autostart::MacosLauncher;
+
 pub mod scan;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let app_state = scan::state::AppState::new();
-
     tauri::Builder::default()
+        // Must come first so a second launch exits before setting anything else up
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            if !args.iter().any(|arg| arg == scan::agent::MINIMIZED_ARG) {
+                scan::tray::show_main_window(app);
+            }
+            scan::shell::handle_launch_args(app, args, std::path::Path::new(&cwd));
+        }))
         .plugin(tauri_plugin_opener::init())
-        .manage(app_state)
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            MacosLauncher::LaunchAgent,
+            Some(vec![scan::agent::MINIMIZED_ARG]),
+        ))
+        .on_window_event(scan::tray::on_window_event)
+        .setup(|app| {
+            let app_state = match app.path().app_data_dir() {
+                Ok(data_dir) => scan::state::AppState::with_data_dir(data_dir),
+                Err(_) => scan::state::AppState::new(),
+            };
+            scan::stale::spawn_stale_watcher(app.handle().clone(), app_state.clone());
+            scan::rules::spawn_rule_watcher(app.handle().clone(), app_state.clone());
+            scan::watchdog::spawn_watchdog(app_state.clone());
+            let run_in_tray = app_state.settings().agent.run_in_tray;
+            app.manage(scan::tray::TrayState::default());
+            app.manage(scan::shell::LaunchRequest::default());
+            let api_server = scan::api::ApiServer::default();
+            // A taken port shouldn't keep the app from starting; the settings page reports it
+            let _ = api_server.apply(&app_state.settings().api, &app_state);
+            app.manage(api_server);
+            app.manage(app_state.clone());
+            scan::agent::spawn_agent(app.handle().clone(), app_state);
+            if run_in_tray {
+                scan::tray::create_tray(app.handle())?;
+                if std::env::args().any(|arg| arg == scan::agent::MINIMIZED_ARG) {
+                    scan::tray::hide_main_window(app.handle());
+                }
+            }
+            scan::volumes::spawn_root_watcher(app.handle().clone());
+            let cwd = std::env::current_dir().unwrap_or_default();
+            scan::shell::handle_launch_args(app.handle(), std::env::args(), &cwd);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             scan::commands::start_scan,
+            scan::commands::get_default_options,
             scan::commands::cancel_scan,
+            scan::commands::resume_scan,
             scan::commands::get_scan_result,
             scan::commands::list_roots,
             scan::commands::open_in_explorer,
@@ -18,7 +61,116 @@ pub fn run() {
             scan::commands::get_file_safety_level,
             scan::commands::get_file_details,
             scan::commands::smart_delete,
-            scan::commands::bulk_smart_delete
+            scan::commands::bulk_smart_delete,
+            scan::commands::delete_matching,
+            scan::commands::request_close_confirmation,
+            scan::commands::retry_after_close,
+            scan::commands::get_hook_config,
+            scan::commands::get_delete_throttle,
+            scan::commands::set_delete_throttle,
+            scan::commands::get_developer_mode,
+            scan::commands::set_developer_mode,
+            scan::commands::get_audit_log,
+            scan::commands::schedule_delete_on_reboot,
+            scan::commands::get_reboot_deletions,
+            scan::commands::request_acl_fix_confirmation,
+            scan::commands::delete_with_acl_fix,
+            scan::commands::get_junk_candidates,
+            scan::commands::find_duplicates,
+            scan::commands::resolve_duplicate_group,
+            scan::commands::inspect_archive,
+            scan::commands::extract_selected,
+            scan::commands::estimate_compressibility,
+            scan::commands::apply_ntfs_compression,
+            scan::commands::get_cow_usage,
+            scan::commands::explain_missing_space,
+            scan::commands::get_root_breakdown,
+            scan::commands::get_shadow_storage,
+            scan::commands::request_shadow_resize_confirmation,
+            scan::commands::resize_shadow_storage,
+            scan::commands::check_trash_capacity,
+            scan::commands::validate_rule_pack,
+            scan::commands::reload_rule_packs,
+            scan::commands::get_agent_settings,
+            scan::commands::set_agent_settings,
+            scan::commands::get_agent_status,
+            scan::commands::get_notification_settings,
+            scan::commands::set_notification_settings,
+            scan::commands::take_scan_request,
+            scan::commands::get_context_menu_registered,
+            scan::commands::set_context_menu_registered,
+            scan::commands::export_scan,
+            scan::commands::get_api_settings,
+            scan::commands::get_api_token,
+            scan::commands::set_api_settings,
+            scan::commands::get_node_report,
+            scan::commands::get_usage_by_owner,
+            scan::commands::get_policies,
+            scan::commands::set_policies,
+            scan::commands::preview_policy,
+            scan::commands::run_policy_now,
+            scan::commands::get_policy_runs,
+            scan::commands::get_bookmarks,
+            scan::commands::add_bookmark,
+            scan::commands::remove_bookmark,
+            scan::commands::rescan_bookmarks,
+            scan::commands::set_node_tag,
+            scan::commands::get_tags_for_scan,
+            scan::commands::get_age_histogram,
+            scan::commands::get_size_distribution,
+            scan::commands::get_extension_stats_for,
+            scan::commands::find_high_entropy_files,
+            scan::commands::get_executable_inventory,
+            scan::commands::set_baseline,
+            scan::commands::get_baselines,
+            scan::commands::remove_baseline,
+            scan::commands::compare_to_baseline,
+            scan::commands::get_growth_report,
+            scan::commands::get_drift_thresholds,
+            scan::commands::set_drift_thresholds,
+            scan::commands::get_vm_disks,
+            scan::commands::compact_vm_disk,
+            scan::commands::get_browser_storage,
+            scan::commands::get_app_data_usage,
+            scan::commands::get_shader_caches,
+            scan::commands::find_documents,
+            scan::commands::get_memory_usage,
+            scan::commands::release_scan,
+            scan::commands::get_spill_settings,
+            scan::commands::set_spill_settings,
+            scan::commands::search_nodes,
+            scan::commands::get_children,
+            scan::commands::get_top_files,
+            scan::commands::find_covering_scan,
+            scan::commands::run_scan_benchmark,
+            scan::commands::get_incomplete_operations,
+            scan::commands::recover_operation,
+            scan::commands::export_settings,
+            scan::commands::import_settings,
+            scan::commands::get_message_catalog,
+            scan::commands::get_home_overview,
+            scan::commands::get_dashboard_summary,
+            scan::commands::analyze_component_store,
+            scan::commands::request_component_cleanup_confirmation,
+            scan::commands::start_component_cleanup,
+            scan::commands::find_crash_dumps,
+            scan::commands::check_operation_feasibility,
+            scan::commands::preview_bulk_delete,
+            scan::commands::get_offline_files_cache,
+            scan::commands::start_inventory_scan,
+            scan::commands::watch_node_size,
+            scan::commands::stop_node_watch,
+            scan::commands::list_trash_items,
+            scan::commands::restore_trash_items,
+            scan::commands::request_trash_purge_confirmation,
+            scan::commands::purge_trash_items,
+            scan::commands::analyze_windows_stores,
+            scan::commands::request_store_cleanup_confirmation,
+            scan::commands::clean_windows_store_items,
+            scan::commands::get_hash_cache_stats,
+            scan::commands::purge_hash_cache,
+            scan::commands::find_similar,
+            scan::commands::archive_then_delete
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Well-known SID of the local Administrators group
+#[cfg(target_os = "windows")]
+const ADMINISTRATORS_SID: &str = "*S-1-5-32-544";
+
+/// One ownership/permission change made before an assisted delete
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AclChange {
+    pub step: String,
+    pub path: String,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// Whether the process runs with administrator rights
+#[cfg(target_os = "windows")]
+pub fn is_elevated() -> bool {
+    unsafe { windows_sys::Win32::UI::Shell::IsUserAnAdmin() != 0 }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+/// Arguments for takeown. `/D` is left out: its answer letter is translated on
+/// non-English Windows, and with stdin closed takeown skips folders it can't list
+/// rather than failing, leaving icacls to report what it still can't reach.
+#[cfg(any(target_os = "windows", test))]
+fn take_ownership_args(path: &str, recursive: bool) -> Vec<&str> {
+    let mut args = vec!["/F", path, "/A"];
+    if recursive {
+        args.push("/R");
+    }
+    args
+}
+
+/// Take ownership of `path` for the Administrators group and grant it full control,
+/// recursively for directories. Stops at the first failed step.
+#[cfg(target_os = "windows")]
+pub fn fix_permissions(path: &Path) -> Vec<AclChange> {
+    use std::process::{Command, Stdio};
+
+    let path_str = path.to_string_lossy().to_string();
+    let recursive = path.is_dir();
+
+    let mut takeown = Command::new("takeown");
+    takeown.args(take_ownership_args(&path_str, recursive)).stdin(Stdio::null());
+
+    let grant = format!("{}:F", ADMINISTRATORS_SID);
+    let mut icacls = Command::new("icacls");
+    icacls.args([path_str.as_str(), "/grant", grant.as_str()]);
+    if recursive {
+        icacls.args(["/T", "/C"]);
+    }
+
+    let mut changes = Vec::new();
+    for (step, mut command) in [("take_ownership", takeown), ("grant_full_control", icacls)] {
+        let change = match command.output() {
+            Ok(output) if output.status.success() => AclChange {
+                step: step.to_string(),
+                path: path_str.clone(),
+                success: true,
+                message: None,
+            },
+            Ok(output) => AclChange {
+                step: step.to_string(),
+                path: path_str.clone(),
+                success: false,
+                message: Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            },
+            Err(e) => AclChange {
+                step: step.to_string(),
+                path: path_str.clone(),
+                success: false,
+                message: Some(e.to_string()),
+            },
+        };
+        let failed = !change.success;
+        changes.push(change);
+        if failed {
+            break;
+        }
+    }
+    changes
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn fix_permissions(path: &Path) -> Vec<AclChange> {
+    vec![AclChange {
+        step: "take_ownership".to_string(),
+        path: path.to_string_lossy().to_string(),
+        success: false,
+        message: Some("Permission-fix deletion is only supported on Windows".to_string()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_ownership_does_not_answer_prompts_in_one_language() {
+        assert_eq!(take_ownership_args("C:\\x", false), ["/F", "C:\\x", "/A"]);
+        let recursive = take_ownership_args("C:\\x", true);
+        assert!(recursive.contains(&"/R"));
+        assert!(!recursive.contains(&"/D"));
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::scan::audit::{AuditAction, AuditOutcome, AuditRange};
+use crate::scan::commands::spawn_scan;
+use crate::scan::events::EventRoute;
+use crate::scan::model::{ScanHandle, ScanOptions};
+use crate::scan::policy;
+use crate::scan::runwindow::{RunWindow, WindowBlocker};
+use crate::scan::state::AppState;
+use crate::scan::tray;
+use crate::scan::volumes::list_root_entries;
+
+pub const EVENT_LOW_SPACE: &str = "agent://low_space";
+/// Passed by the autostart entry so the app starts hidden in the tray
+pub const MINIMIZED_ARG: &str = "--minimized";
+const AGENT_INTERVAL: Duration = Duration::from_secs(60);
+const HOUR_MILLIS: u64 = 60 * 60 * 1000;
+/// Enough audit entries to cover the cleanups since any recent scan
+const FREED_AUDIT_LIMIT: usize = 100_000;
+
+/// Background agent settings, persisted with the other settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AgentSettings {
+    /// Keep running in the system tray after the window closes
+    #[serde(default)]
+    pub run_in_tray: bool,
+    /// Start minimized to the tray at login
+    #[serde(default)]
+    pub autostart: bool,
+    #[serde(default)]
+    pub scheduled_scan: Option<ScheduledScan>,
+    /// Warn when a fixed volume's free space drops below this percentage
+    #[serde(default)]
+    pub low_space_percent: Option<u8>,
+    /// When scheduled scans and cleanup policies may start; any time when unset
+    #[serde(default)]
+    pub run_window: Option<RunWindow>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledScan {
+    pub root_path: String,
+    pub every_hours: u32,
+    #[serde(default)]
+    pub options: ScanOptions,
+    /// Unix millis of the last scheduled run, kept across restarts
+    #[serde(default)]
+    pub last_run_at: Option<u64>,
+}
+
+impl ScheduledScan {
+    fn is_due(&self, now: u64) -> bool {
+        self.every_hours > 0
+            && self
+                .last_run_at
+                .is_none_or(|last| now.saturating_sub(last) >= u64::from(self.every_hours) * HOUR_MILLIS)
+    }
+}
+
+/// The most recently finished scan
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LastScan {
+    pub scan_id: String,
+    pub root_path: String,
+    pub total_bytes: u64,
+    pub finished_at: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LowSpacePayload {
+    pub mount_point: String,
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    pub threshold_percent: u8,
+}
+
+/// Quick stats shown in the tray menu
+#[derive(Clone, Debug, Serialize)]
+pub struct AgentStatus {
+    pub last_scan: Option<LastScan>,
+    /// Bytes deleted or trashed since the last scan finished
+    pub freed_since_last_scan: u64,
+    /// Fixed volume with the least free space, as (mount point, percent free)
+    pub lowest_free: Option<(String, u8)>,
+    /// What is holding scheduled work back right now; empty when it may run
+    pub window_blockers: Vec<WindowBlocker>,
+}
+
+pub fn agent_status(state: &AppState) -> AgentStatus {
+    let last_scan = state.last_scan();
+    let freed_since_last_scan = last_scan
+        .as_ref()
+        .map(|scan| freed_since(state, scan.finished_at))
+        .unwrap_or(0);
+    let lowest_free = list_root_entries()
+        .into_iter()
+        .filter(|root| !root.is_removable && !root.is_network && root.total_bytes > 0)
+        .map(|root| (root.path, percent_free(root.available_bytes, root.total_bytes)))
+        .min_by_key(|(_, percent)| *percent);
+    AgentStatus {
+        last_scan,
+        freed_since_last_scan,
+        lowest_free,
+        window_blockers: window_blockers(&state.settings().agent),
+    }
+}
+
+fn window_blockers(settings: &AgentSettings) -> Vec<WindowBlocker> {
+    settings.run_window.as_ref().map(RunWindow::check).unwrap_or_default()
+}
+
+/// Bytes deleted or trashed since `from` (Unix millis), per the audit log
+pub fn freed_since(state: &AppState, from: u64) -> u64 {
+    let range = AuditRange {
+        from: Some(from),
+        to: None,
+        limit: Some(FREED_AUDIT_LIMIT),
+    };
+    state
+        .read_audit(&range)
+        .unwrap_or_default()
+        .iter()
+        .filter(|entry| matches!(entry.action, AuditAction::Delete | AuditAction::Trash))
+        .filter(|entry| entry.outcome == AuditOutcome::Success)
+        .map(|entry| entry.size_bytes)
+        .sum()
+}
+
+fn percent_free(available: u64, total: u64) -> u8 {
+    (available.saturating_mul(100) / total.max(1)).min(100) as u8
+}
+
+/// Fixed volumes whose free space is under `threshold_percent`
+pub fn low_space_volumes(threshold_percent: u8) -> Vec<LowSpacePayload> {
+    list_root_entries()
+        .into_iter()
+        .filter(|root| !root.is_removable && !root.is_network && root.total_bytes > 0)
+        .filter(|root| percent_free(root.available_bytes, root.total_bytes) < threshold_percent)
+        .map(|root| LowSpacePayload {
+            mount_point: root.path,
+            available_bytes: root.available_bytes,
+            total_bytes: root.total_bytes,
+            threshold_percent,
+        })
+        .collect()
+}
+
+/// Start the scheduled scan now, unless none is configured or a scan is already running
+pub fn run_scheduled_scan(app_handle: &AppHandle, state: &AppState) -> Option<ScanHandle> {
+    let schedule = state.settings().agent.scheduled_scan?;
+    if state.has_active_scans() {
+        return None;
+    }
+    let handle = spawn_scan(
+        schedule.root_path,
+        schedule.options,
+        true,
+        EventRoute::Broadcast,
+        app_handle.clone(),
+        state.clone(),
+    );
+    let now = now_millis();
+    let _ = state.update_settings(|settings| {
+        if let Some(schedule) = settings.agent.scheduled_scan.as_mut() {
+            schedule.last_run_at = Some(now);
+        }
+    });
+    Some(handle)
+}
+
+/// Run due scheduled scans, watch free space, and keep the tray stats current
+pub fn spawn_agent(app_handle: AppHandle, state: AppState) {
+    thread::spawn(move || {
+        // Volumes already warned about, so each drop below the threshold is reported once
+        let mut warned: HashSet<String> = HashSet::new();
+        loop {
+            let settings = state.settings().agent;
+            // Due work outside the run window waits for the next check inside it
+            if window_blockers(&settings).is_empty() {
+                if settings.scheduled_scan.as_ref().is_some_and(|s| s.is_due(now_millis())) {
+                    run_scheduled_scan(&app_handle, &state);
+                }
+                policy::run_due_policies(&state);
+            }
+            match settings.low_space_percent {
+                Some(threshold) => {
+                    let low = low_space_volumes(threshold);
+                    warned.retain(|mount| low.iter().any(|volume| volume.mount_point == *mount));
+                    for volume in low {
+                        if warned.insert(volume.mount_point.clone()) {
+                            let _ = app_handle.emit(EVENT_LOW_SPACE, volume);
+                        }
+                    }
+                }
+                None => warned.clear(),
+            }
+            tray::refresh(&app_handle, &agent_status(&state));
+            thread::sleep(AGENT_INTERVAL);
+        }
+    });
+}
+
+/// Human-readable size for tray and notification text
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduled_scans_run_once_per_interval() {
+        let mut schedule = ScheduledScan {
+            root_path: "/".to_string(),
+            every_hours: 24,
+            options: ScanOptions::default(),
+            last_run_at: None,
+        };
+        assert!(schedule.is_due(0));
+        schedule.last_run_at = Some(10 * HOUR_MILLIS);
+        assert!(!schedule.is_due(33 * HOUR_MILLIS));
+        assert!(schedule.is_due(34 * HOUR_MILLIS));
+        schedule.every_hours = 0;
+        assert!(!schedule.is_due(100 * HOUR_MILLIS));
+    }
+
+    #[test]
+    fn formats_sizes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
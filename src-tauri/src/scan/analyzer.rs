@@ -0,0 +1,578 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::Metadata;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::scan::appdata::AppDataAnalyzer;
+use crate::scan::browsers::BrowserProfilesAnalyzer;
+use crate::scan::entropy::HighEntropyAnalyzer;
+use crate::scan::executables::ExecutablesAnalyzer;
+use crate::scan::extstats::ExtensionStatsTable;
+use crate::scan::images::SimilarImagesAnalyzer;
+use crate::scan::owners::OwnerUsageAnalyzer;
+use crate::scan::model::{
+    AgeBucket, AgeHistogramView, AnalyzerConfig, AnalyzerReport, CategoryStat, DirectoryAgeHistogram, NodeId, NodeKind, SizeBucket, TopFile, TreeNode,
+};
+use crate::scan::shaders::ShaderCacheAnalyzer;
+use crate::scan::tree::ScanTree;
+use crate::scan::vmdisks::VmDiskAnalyzer;
+
+pub const NO_EXTENSION_LABEL: &str = "<none>";
+pub const DEFAULT_TOP_FILES_LIMIT: usize = 100;
+
+const DAY: u64 = 24 * 60 * 60;
+
+// Age buckets as (label, upper bound in seconds); anything older falls into "older"
+const AGE_BUCKETS: &[(&str, u64)] = &[
+    ("week", 7 * DAY),
+    ("month", 30 * DAY),
+    ("quarter", 90 * DAY),
+    ("year", 365 * DAY),
+];
+const AGE_BUCKET_OLDER: &str = "older";
+const AGE_BUCKET_UNKNOWN: &str = "unknown";
+
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+const GB: u64 = 1024 * MB;
+
+// Size buckets as (label, upper bound in bytes); anything larger falls into ">10GB"
+const SIZE_BUCKETS: &[(&str, u64)] = &[
+    ("<1KB", KB),
+    ("1KB-100KB", 100 * KB),
+    ("100KB-1MB", MB),
+    ("1MB-10MB", 10 * MB),
+    ("10MB-100MB", 100 * MB),
+    ("100MB-1GB", GB),
+    ("1GB-10GB", 10 * GB),
+];
+const SIZE_BUCKET_LARGEST: &str = ">10GB";
+
+// Extension -> category mapping used by the category analyzer
+const CATEGORY_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("documents", &["doc", "docx", "pdf", "txt", "rtf", "odt", "xls", "xlsx", "ppt", "pptx", "csv", "md", "epub"]),
+    ("images", &["jpg", "jpeg", "png", "gif", "bmp", "svg", "webp", "ico", "tiff", "raw", "heic", "psd", "cr2", "nef"]),
+    ("videos", &["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "mpg", "mpeg"]),
+    ("audio", &["mp3", "wav", "flac", "aac", "ogg", "m4a", "wma", "opus"]),
+    ("archives", &["zip", "rar", "7z", "tar", "gz", "bz2", "xz", "zst", "iso", "cab"]),
+    ("code", &["js", "ts", "jsx", "tsx", "py", "rs", "go", "java", "cpp", "c", "h", "cs", "rb", "php", "swift", "kt", "html", "css", "json", "xml", "yaml", "yml", "toml"]),
+    ("executables", &["exe", "msi", "dll", "so", "dylib", "app", "dmg", "deb", "rpm", "appimage"]),
+    ("temporary", &["tmp", "temp", "bak", "old", "swp", "log", "cache", "dmp"]),
+];
+const CATEGORY_OTHER: &str = "other";
+
+/// A file as seen by analyzers while the engine walks the tree
+pub struct FileVisit<'a> {
+    pub node: &'a TreeNode,
+    pub modified: Option<SystemTime>,
+    /// The walker's metadata for the file, when it could be read
+    pub metadata: Option<&'a Metadata>,
+}
+
+/// Per-scan analysis hook driven by the engine during the walk.
+///
+/// `on_dir_complete` is called once every descendant of a directory has been
+/// visited, so the directory's `size_bytes` is final at that point.
+pub trait Analyzer: Send {
+    fn on_file(&mut self, file: &FileVisit<'_>);
+
+    fn on_dir_complete(&mut self, _dir: &TreeNode) {}
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport;
+}
+
+/// Label of the age-histogram bucket a file of `age_secs` falls into
+pub fn age_bucket_label(age_secs: Option<u64>) -> &'static str {
+    let Some(age) = age_secs else {
+        return AGE_BUCKET_UNKNOWN;
+    };
+    AGE_BUCKETS
+        .iter()
+        .find(|(_, max_age)| age < *max_age)
+        .map(|(label, _)| *label)
+        .unwrap_or(AGE_BUCKET_OLDER)
+}
+
+/// Map a lowercase extension to a coarse file category
+pub fn category_for_extension(ext: Option<&str>) -> &'static str {
+    let Some(ext) = ext else {
+        return CATEGORY_OTHER;
+    };
+    CATEGORY_EXTENSIONS
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext))
+        .map(|(category, _)| *category)
+        .unwrap_or(CATEGORY_OTHER)
+}
+
+/// The analyzers registered for a single scan
+pub struct AnalyzerSet {
+    analyzers: Vec<Box<dyn Analyzer>>,
+}
+
+impl AnalyzerSet {
+    /// Build the set for a scan. Extension stats and the size distribution are always
+    /// registered because ScanResult exposes them directly.
+    pub fn from_configs(configs: &[AnalyzerConfig]) -> Self {
+        let mut analyzers: Vec<Box<dyn Analyzer>> = vec![
+            Box::new(ExtensionStatsAnalyzer::default()),
+            Box::new(SizeDistributionAnalyzer::default()),
+        ];
+        for config in configs {
+            analyzers.push(build_analyzer(config));
+        }
+        Self { analyzers }
+    }
+
+    pub fn register(&mut self, analyzer: Box<dyn Analyzer>) {
+        self.analyzers.push(analyzer);
+    }
+
+    pub fn on_file(&mut self, file: &FileVisit<'_>) {
+        for analyzer in self.analyzers.iter_mut() {
+            analyzer.on_file(file);
+        }
+    }
+
+    pub fn on_dir_complete(&mut self, dir: &TreeNode) {
+        for analyzer in self.analyzers.iter_mut() {
+            analyzer.on_dir_complete(dir);
+        }
+    }
+
+    pub fn finalize(self) -> Vec<AnalyzerReport> {
+        self.analyzers.into_iter().map(|a| a.finalize()).collect()
+    }
+}
+
+fn build_analyzer(config: &AnalyzerConfig) -> Box<dyn Analyzer> {
+    match config {
+        AnalyzerConfig::TopFiles { limit } => Box::new(TopFilesAnalyzer::new(*limit)),
+        AnalyzerConfig::AgeHistogram => Box::new(AgeHistogramAnalyzer::new(SystemTime::now())),
+        AnalyzerConfig::CategoryStats => Box::new(CategoryStatsAnalyzer::default()),
+        AnalyzerConfig::SimilarImages { max_bytes, max_distance } => {
+            Box::new(SimilarImagesAnalyzer::new(*max_bytes, *max_distance))
+        }
+        AnalyzerConfig::OwnerUsage => Box::new(OwnerUsageAnalyzer::default()),
+        AnalyzerConfig::HighEntropy { min_bytes } => Box::new(HighEntropyAnalyzer::new(*min_bytes)),
+        AnalyzerConfig::Executables => Box::new(ExecutablesAnalyzer::default()),
+        AnalyzerConfig::VmDisks => Box::new(VmDiskAnalyzer::default()),
+        AnalyzerConfig::BrowserProfiles => Box::new(BrowserProfilesAnalyzer::default()),
+        AnalyzerConfig::AppData => Box::new(AppDataAnalyzer::default()),
+        AnalyzerConfig::ShaderCaches => Box::new(ShaderCacheAnalyzer::default()),
+    }
+}
+
+// ==========================================
+// BUILT-IN ANALYZERS
+// ==========================================
+
+#[derive(Default)]
+pub struct ExtensionStatsAnalyzer {
+    stats: ExtensionStatsTable,
+}
+
+impl Analyzer for ExtensionStatsAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        self.stats.add(file.node.file_ext.as_deref(), file.node.size_bytes);
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        AnalyzerReport::ExtensionStats {
+            stats: self.stats.into_stats(),
+        }
+    }
+}
+
+pub struct TopFilesAnalyzer {
+    limit: usize,
+    heap: BinaryHeap<Reverse<(u64, NodeId, String)>>,
+}
+
+impl TopFilesAnalyzer {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit + 1),
+        }
+    }
+}
+
+impl Analyzer for TopFilesAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        if self.limit == 0 {
+            return;
+        }
+        let size = file.node.size_bytes;
+        if self.heap.len() >= self.limit {
+            match self.heap.peek() {
+                Some(Reverse((smallest, _, _))) if *smallest >= size => return,
+                _ => {}
+            }
+        }
+        self.heap
+            .push(Reverse((size, file.node.id, file.node.path.clone())));
+        if self.heap.len() > self.limit {
+            self.heap.pop();
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let files = self
+            .heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((size_bytes, id, path))| TopFile {
+                id,
+                path,
+                size_bytes,
+            })
+            .collect();
+        AnalyzerReport::TopFiles { files }
+    }
+}
+
+pub struct AgeHistogramAnalyzer {
+    now: SystemTime,
+    buckets: Vec<AgeBucket>,
+    /// Histograms of directories still being walked, filled by their files and by
+    /// completed subdirectories
+    open: HashMap<NodeId, Vec<AgeBucket>>,
+    /// Completed subdirectories of each open directory; the root's entry ends up holding
+    /// the top-level directories
+    completed_children: HashMap<NodeId, Vec<DirectoryAgeHistogram>>,
+    top_level: Vec<DirectoryAgeHistogram>,
+}
+
+impl AgeHistogramAnalyzer {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now,
+            buckets: empty_age_buckets(),
+            open: HashMap::new(),
+            completed_children: HashMap::new(),
+            top_level: Vec::new(),
+        }
+    }
+
+    fn bucket_index(&self, modified: Option<SystemTime>) -> usize {
+        let Some(modified) = modified else {
+            return self.buckets.len() - 1;
+        };
+        // Timestamps in the future count as brand new
+        let age = self
+            .now
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        AGE_BUCKETS
+            .iter()
+            .position(|(_, max_age)| age < *max_age)
+            .unwrap_or(AGE_BUCKETS.len())
+    }
+}
+
+fn empty_age_buckets() -> Vec<AgeBucket> {
+    let mut buckets: Vec<AgeBucket> = AGE_BUCKETS
+        .iter()
+        .map(|(label, max_age)| AgeBucket::empty(label, Some(*max_age)))
+        .collect();
+    buckets.push(AgeBucket::empty(AGE_BUCKET_OLDER, None));
+    buckets.push(AgeBucket::empty(AGE_BUCKET_UNKNOWN, None));
+    buckets
+}
+
+fn add_to_bucket(bucket: &mut AgeBucket, bytes: u64, count: u64) {
+    bucket.bytes = bucket.bytes.saturating_add(bytes);
+    bucket.count = bucket.count.saturating_add(count);
+}
+
+fn merge_age_buckets(into: &mut [AgeBucket], from: &[AgeBucket]) {
+    for (target, source) in into.iter_mut().zip(from) {
+        add_to_bucket(target, source.bytes, source.count);
+    }
+}
+
+impl Analyzer for AgeHistogramAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let index = self.bucket_index(file.modified);
+        add_to_bucket(&mut self.buckets[index], file.node.size_bytes, 1);
+        if let Some(parent) = file.node.parent {
+            let dir = self.open.entry(parent).or_insert_with(empty_age_buckets);
+            add_to_bucket(&mut dir[index], file.node.size_bytes, 1);
+        }
+    }
+
+    fn on_dir_complete(&mut self, dir: &TreeNode) {
+        let buckets = self.open.remove(&dir.id).unwrap_or_else(empty_age_buckets);
+        let children = self.completed_children.remove(&dir.id).unwrap_or_default();
+        let Some(parent) = dir.parent else {
+            self.top_level = children;
+            return;
+        };
+        merge_age_buckets(self.open.entry(parent).or_insert_with(empty_age_buckets), &buckets);
+        self.completed_children.entry(parent).or_default().push(DirectoryAgeHistogram {
+            node_id: dir.id,
+            path: dir.path.clone(),
+            buckets,
+        });
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut directories = self.top_level;
+        directories.sort_by_key(|d| Reverse(d.buckets.iter().map(|b| b.bytes).sum::<u64>()));
+        AnalyzerReport::AgeHistogram {
+            buckets: self.buckets,
+            directories,
+        }
+    }
+}
+
+/// Age histogram of any node and its child directories, from the modification times
+/// stored in the tree
+pub fn age_histogram_view(tree: &ScanTree, node_id: NodeId, now: SystemTime) -> Result<AgeHistogramView, String> {
+    let node = tree.get(node_id).ok_or_else(|| format!("Node {} not found", node_id))?;
+    let subtree = |id: NodeId| {
+        let mut analyzer = AgeHistogramAnalyzer::new(now);
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let Some(node) = tree.get(id) else {
+                continue;
+            };
+            match node.kind {
+                NodeKind::Dir => stack.extend(node.children.iter().copied()),
+                NodeKind::File => {
+                    let modified = node.modified.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+                    let index = analyzer.bucket_index(modified);
+                    add_to_bucket(&mut analyzer.buckets[index], node.size_bytes, 1);
+                }
+            }
+        }
+        analyzer.buckets
+    };
+    let mut directories: Vec<DirectoryAgeHistogram> = node
+        .children
+        .iter()
+        .filter_map(|id| tree.get(*id))
+        .filter(|child| child.kind == NodeKind::Dir)
+        .map(|child| DirectoryAgeHistogram {
+            node_id: child.id,
+            path: child.path.clone(),
+            buckets: subtree(child.id),
+        })
+        .collect();
+    directories.sort_by_key(|d| Reverse(d.buckets.iter().map(|b| b.bytes).sum::<u64>()));
+    Ok(AgeHistogramView {
+        node_id,
+        buckets: subtree(node_id),
+        directories,
+    })
+}
+
+pub struct SizeDistributionAnalyzer {
+    buckets: Vec<SizeBucket>,
+}
+
+impl Default for SizeDistributionAnalyzer {
+    fn default() -> Self {
+        Self {
+            buckets: empty_size_buckets(),
+        }
+    }
+}
+
+fn empty_size_buckets() -> Vec<SizeBucket> {
+    let mut min_bytes = 0;
+    let mut buckets: Vec<SizeBucket> = Vec::with_capacity(SIZE_BUCKETS.len() + 1);
+    for (label, max_bytes) in SIZE_BUCKETS {
+        buckets.push(SizeBucket {
+            label: label.to_string(),
+            min_bytes,
+            max_bytes: Some(*max_bytes),
+            bytes: 0,
+            count: 0,
+        });
+        min_bytes = *max_bytes;
+    }
+    buckets.push(SizeBucket {
+        label: SIZE_BUCKET_LARGEST.to_string(),
+        min_bytes,
+        max_bytes: None,
+        bytes: 0,
+        count: 0,
+    });
+    buckets
+}
+
+fn add_to_size_buckets(buckets: &mut [SizeBucket], size: u64) {
+    let index = SIZE_BUCKETS
+        .iter()
+        .position(|(_, max_bytes)| size < *max_bytes)
+        .unwrap_or(SIZE_BUCKETS.len());
+    let bucket = &mut buckets[index];
+    bucket.bytes = bucket.bytes.saturating_add(size);
+    bucket.count = bucket.count.saturating_add(1);
+}
+
+impl Analyzer for SizeDistributionAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        add_to_size_buckets(&mut self.buckets, file.node.size_bytes);
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        AnalyzerReport::SizeDistribution { buckets: self.buckets }
+    }
+}
+
+/// Size distribution of the files below any node, from the scan tree
+pub fn size_distribution(tree: &ScanTree, node_id: NodeId) -> Result<Vec<SizeBucket>, String> {
+    tree.get(node_id).ok_or_else(|| format!("Node {} not found", node_id))?;
+    let mut buckets = empty_size_buckets();
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = tree.get(id) else {
+            continue;
+        };
+        match node.kind {
+            NodeKind::Dir => stack.extend(node.children.iter().copied()),
+            NodeKind::File => add_to_size_buckets(&mut buckets, node.size_bytes),
+        }
+    }
+    Ok(buckets)
+}
+
+#[derive(Default)]
+pub struct CategoryStatsAnalyzer {
+    stats: HashMap<&'static str, CategoryStat>,
+}
+
+impl Analyzer for CategoryStatsAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let category = category_for_extension(file.node.file_ext.as_deref());
+        let entry = self.stats.entry(category).or_insert(CategoryStat {
+            category: category.to_string(),
+            bytes: 0,
+            count: 0,
+        });
+        entry.bytes = entry.bytes.saturating_add(file.node.size_bytes);
+        entry.count = entry.count.saturating_add(1);
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut categories: Vec<CategoryStat> = self.stats.into_values().collect();
+        categories.sort_by_key(|c| Reverse(c.bytes));
+        AnalyzerReport::CategoryStats { categories }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_node(id: NodeId, name: &str, ext: Option<&str>, size: u64) -> TreeNode {
+        TreeNode {
+            id,
+            parent: None,
+            name: name.to_string(),
+            path: format!("/root/{}", name),
+            kind: NodeKind::File,
+            size_bytes: size,
+            file_ext: ext.map(|e| e.to_string()),
+            modified: None,
+            informational: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn top_files_keeps_largest() {
+        let mut analyzer = Box::new(TopFilesAnalyzer::new(2));
+        for (id, size) in [(1, 10), (2, 50), (3, 30), (4, 5)] {
+            let node = file_node(id, &format!("f{}", id), None, size);
+            analyzer.on_file(&FileVisit { node: &node, modified: None, metadata: None });
+        }
+        match analyzer.finalize() {
+            AnalyzerReport::TopFiles { files } => {
+                let sizes: Vec<u64> = files.iter().map(|f| f.size_bytes).collect();
+                assert_eq!(sizes, vec![50, 30]);
+            }
+            other => panic!("unexpected report: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn age_histogram_buckets_by_mtime() {
+        let now = SystemTime::now();
+        let mut analyzer = Box::new(AgeHistogramAnalyzer::new(now));
+        let node = file_node(1, "a.txt", Some("txt"), 100);
+        analyzer.on_file(&FileVisit { node: &node, modified: Some(now - Duration::from_secs(DAY)), metadata: None });
+        analyzer.on_file(&FileVisit { node: &node, modified: Some(now - Duration::from_secs(400 * DAY)), metadata: None });
+        analyzer.on_file(&FileVisit { node: &node, modified: None, metadata: None });
+        match analyzer.finalize() {
+            AnalyzerReport::AgeHistogram { buckets, .. } => {
+                let find = |label: &str| buckets.iter().find(|b| b.label == label).expect("bucket").count;
+                assert_eq!(find("week"), 1);
+                assert_eq!(find("older"), 1);
+                assert_eq!(find("unknown"), 1);
+            }
+            other => panic!("unexpected report: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn age_histogram_splits_top_level_directories() {
+        let now = SystemTime::now();
+        let mut analyzer = Box::new(AgeHistogramAnalyzer::new(now));
+        let mut root = file_node(1, "root", None, 0);
+        root.kind = NodeKind::Dir;
+        let mut photos = file_node(2, "photos", None, 0);
+        photos.kind = NodeKind::Dir;
+        photos.parent = Some(1);
+        let mut old = file_node(3, "old.jpg", Some("jpg"), 70);
+        old.parent = Some(2);
+        let mut fresh = file_node(4, "new.txt", Some("txt"), 30);
+        fresh.parent = Some(1);
+
+        analyzer.on_file(&FileVisit { node: &old, modified: Some(now - Duration::from_secs(400 * DAY)), metadata: None });
+        analyzer.on_dir_complete(&photos);
+        analyzer.on_file(&FileVisit { node: &fresh, modified: Some(now), metadata: None });
+        analyzer.on_dir_complete(&root);
+        match analyzer.finalize() {
+            AnalyzerReport::AgeHistogram { buckets, directories } => {
+                assert_eq!(buckets.iter().map(|b| b.bytes).sum::<u64>(), 100);
+                assert_eq!(directories.len(), 1);
+                assert_eq!(directories[0].node_id, 2);
+                let older = directories[0].buckets.iter().find(|b| b.label == "older").expect("bucket");
+                assert_eq!(older.bytes, 70);
+            }
+            other => panic!("unexpected report: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn size_distribution_uses_half_open_buckets() {
+        let mut analyzer = Box::new(SizeDistributionAnalyzer::default());
+        for (id, size) in [(1, 10), (2, KB), (3, 20 * GB)] {
+            let node = file_node(id, &format!("f{}", id), None, size);
+            analyzer.on_file(&FileVisit { node: &node, modified: None, metadata: None });
+        }
+        match analyzer.finalize() {
+            AnalyzerReport::SizeDistribution { buckets } => {
+                let find = |label: &str| buckets.iter().find(|b| b.label == label).expect("bucket").count;
+                assert_eq!(find("<1KB"), 1);
+                assert_eq!(find("1KB-100KB"), 1);
+                assert_eq!(find(">10GB"), 1);
+                assert_eq!(buckets[1].min_bytes, KB);
+            }
+            other => panic!("unexpected report: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn categorizes_extensions() {
+        assert_eq!(category_for_extension(Some("mp4")), "videos");
+        assert_eq!(category_for_extension(Some("docx")), "documents");
+        assert_eq!(category_for_extension(Some("xyz")), "other");
+        assert_eq!(category_for_extension(None), "other");
+    }
+}
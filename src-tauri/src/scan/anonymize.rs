@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::tree::ScanTree;
+
+/// Hex digits kept from a name's hash; enough that distinct names don't collide in
+/// any realistic tree
+const HASH_DIGITS: usize = 12;
+
+/// How names are hidden in a shared export. Structure, sizes, times, and file
+/// extensions stay as they are.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Anonymization {
+    /// Each name becomes a keyed hash, so a name repeated across the tree (such as
+    /// `node_modules`) stays recognizable as the same name. The key is random per
+    /// export, so two exports can't be matched up.
+    Hash,
+    /// Each entry gets a numbered label such as `dir-12` or `file-340`
+    Pseudonym,
+}
+
+/// Rewrites node paths for one export
+pub struct Anonymizer {
+    mode: Anonymization,
+    key: [u8; 32],
+    paths: HashMap<NodeId, String>,
+    next_label: u64,
+}
+
+impl Anonymizer {
+    pub fn new(mode: Anonymization) -> Self {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        key[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        Self {
+            mode,
+            key,
+            paths: HashMap::new(),
+            next_label: 1,
+        }
+    }
+
+    /// The node's path with every name replaced; a volume root such as `C:\` or `/`
+    /// is kept
+    pub fn path(&mut self, tree: &ScanTree, node: &TreeNode) -> String {
+        if let Some(path) = self.paths.get(&node.id) {
+            return path.clone();
+        }
+        let path = match node.parent.and_then(|id| tree.get(id)) {
+            Some(parent) => {
+                let parent_path = self.path(tree, parent);
+                let name = self.name(&node.name, node.kind);
+                let separator = if node.path.contains('\\') && !node.path.contains('/') { '\\' } else { '/' };
+                if parent_path.ends_with(['/', '\\']) {
+                    format!("{}{}", parent_path, name)
+                } else {
+                    format!("{}{}{}", parent_path, separator, name)
+                }
+            }
+            None => {
+                let mut root = PathBuf::new();
+                for component in Path::new(&node.path).components() {
+                    match component {
+                        Component::Normal(name) => root.push(self.name(&name.to_string_lossy(), NodeKind::Dir)),
+                        other => root.push(other.as_os_str()),
+                    }
+                }
+                root.to_string_lossy().to_string()
+            }
+        };
+        self.paths.insert(node.id, path.clone());
+        path
+    }
+
+    fn name(&mut self, name: &str, kind: NodeKind) -> String {
+        // Only files keep their extension; a folder's "extension" is part of its name
+        let extension = match kind {
+            NodeKind::File => Path::new(name)
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default(),
+            NodeKind::Dir => String::new(),
+        };
+        let stem = match self.mode {
+            Anonymization::Hash => blake3::keyed_hash(&self.key, name.as_bytes()).to_hex()[..HASH_DIGITS].to_string(),
+            Anonymization::Pseudonym => {
+                let label = self.next_label;
+                self.next_label += 1;
+                match kind {
+                    NodeKind::File => format!("file-{}", label),
+                    NodeKind::Dir => format!("dir-{}", label),
+                }
+            }
+        };
+        format!("{}{}", stem, extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: 1,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn hides_names_but_keeps_structure_and_extensions() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/home/anna", NodeKind::Dir, vec![2, 4]),
+                node(2, Some(1), "/home/anna/taxes", NodeKind::Dir, vec![3]),
+                node(3, Some(2), "/home/anna/taxes/return.pdf", NodeKind::File, vec![]),
+                node(4, Some(1), "/home/anna/return.pdf", NodeKind::File, vec![]),
+            ],
+            false,
+        );
+        let get = |id| tree.get(id).expect("node");
+
+        let mut hashed = Anonymizer::new(Anonymization::Hash);
+        let file = hashed.path(&tree, get(3));
+        assert!(!file.contains("anna") && !file.contains("taxes") && !file.contains("return"));
+        assert!(file.starts_with('/') && file.ends_with(".pdf"));
+        assert!(file.starts_with(&hashed.path(&tree, get(2))));
+        assert_eq!(file.rsplit('/').next(), hashed.path(&tree, get(4)).rsplit('/').next());
+
+        let mut labeled = Anonymizer::new(Anonymization::Pseudonym);
+        assert_eq!(labeled.path(&tree, get(3)), "/dir-1/dir-2/dir-3/file-4.pdf");
+        assert_eq!(labeled.path(&tree, get(4)), "/dir-1/dir-2/file-5.pdf");
+    }
+}
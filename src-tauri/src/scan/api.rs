@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::scan::agent::{freed_since, LastScan};
+use crate::scan::model::{RootEntry, ScanResult};
+use crate::scan::state::AppState;
+use crate::scan::volumes::list_root_entries;
+
+pub const DEFAULT_API_PORT: u16 = 9617;
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// Connections handled at once; more are closed until one finishes
+const MAX_CONNECTIONS: usize = 16;
+/// File in the app data dir holding the bearer token clients must send
+pub const API_TOKEN_FILE: &str = "api-token";
+
+/// Optional HTTP endpoint on localhost for monitoring tools
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_API_PORT,
+        }
+    }
+}
+
+fn default_port() -> u16 {
+    DEFAULT_API_PORT
+}
+
+/// The running local API server, if any
+#[derive(Default)]
+pub struct ApiServer(Mutex<Option<ServerHandle>>);
+
+struct ServerHandle {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl ApiServer {
+    /// Start, stop, or move the server so it matches `settings`
+    pub fn apply(&self, settings: &ApiSettings, state: &AppState) -> Result<(), String> {
+        let mut guard = self.0.lock().map_err(|e| e.to_string())?;
+        if settings.enabled && guard.as_ref().is_some_and(|server| server.port == settings.port) {
+            return Ok(());
+        }
+        if let Some(server) = guard.take() {
+            server.stop.store(true, Ordering::Relaxed);
+            // Wait for the listener to close so the port can be bound again right away
+            let _ = server.thread.join();
+        }
+        if settings.enabled {
+            *guard = Some(start_server(settings.port, state.clone())?);
+        }
+        Ok(())
+    }
+}
+
+/// The bearer token for this install, created on first use. Without a data dir the
+/// token lasts only as long as the app.
+pub fn api_token(data_dir: Option<&Path>) -> Result<String, String> {
+    let Some(dir) = data_dir else {
+        return Ok(Uuid::new_v4().simple().to_string());
+    };
+    let path = dir.join(API_TOKEN_FILE);
+    if let Ok(token) = fs::read_to_string(&path) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+    let token = Uuid::new_v4().simple().to_string();
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    file.write_all(token.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// Listen on localhost only. The endpoint exposes paths and sizes, so each request
+/// must name this server as its Host, which turns away pages rebinding a domain to
+/// 127.0.0.1, and carry the install's bearer token.
+fn start_server(port: u16, state: AppState) -> Result<ServerHandle, String> {
+    let token: Arc<str> = api_token(state.data_dir())?.into();
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = stop.clone();
+    let active = Arc::new(AtomicUsize::new(0));
+    let thread = thread::spawn(move || {
+        while !stop_flag.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if active.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                        active.fetch_sub(1, Ordering::AcqRel);
+                        continue;
+                    }
+                    // A slow client holds only its own thread, never the accept loop
+                    let (active, token, state) = (active.clone(), token.clone(), state.clone());
+                    thread::spawn(move || {
+                        let _ = handle_connection(stream, port, &token, &state);
+                        active.fetch_sub(1, Ordering::AcqRel);
+                    });
+                }
+                // WouldBlock means no client is waiting; other errors are per-connection
+                Err(_) => thread::sleep(ACCEPT_POLL),
+            }
+        }
+    });
+    Ok(ServerHandle { port, stop, thread })
+}
+
+fn handle_connection(mut stream: TcpStream, port: u16, token: &str, state: &AppState) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    let (status, content_type, body) = respond(&String::from_utf8_lossy(&request), port, token, state);
+    let challenge = if status.starts_with("401") { "WWW-Authenticate: Bearer\r\n" } else { "" };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        challenge,
+        body
+    )?;
+    stream.flush()
+}
+
+/// Status, content type, and body for a raw request
+fn respond(request: &str, port: u16, token: &str, state: &AppState) -> (&'static str, &'static str, String) {
+    const TEXT: &str = "text/plain; charset=utf-8";
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (request_line.next(), request_line.next());
+    let path = target.map(|t| t.split('?').next().unwrap_or(t));
+    let header = |name: &str| {
+        request
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    };
+
+    if !header("Host").is_some_and(|host| is_local_host(host, port)) {
+        return ("403 Forbidden", TEXT, "Forbidden\n".to_string());
+    }
+    let bearer = header("Authorization")
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+        .map(|(_, credentials)| credentials.trim());
+    if !bearer.is_some_and(|credentials| tokens_match(credentials, token)) {
+        return ("401 Unauthorized", TEXT, "Unauthorized\n".to_string());
+    }
+    match (method, path) {
+        (Some("GET"), Some("/metrics")) => {
+            let last_scan = state.last_scan();
+            let result = last_scan.as_ref().and_then(|scan| state.get_result(&scan.scan_id));
+            let freed = last_scan.as_ref().map(|scan| freed_since(state, scan.finished_at)).unwrap_or(0);
+            let body = render_metrics(&list_root_entries(), last_scan.as_ref(), result.as_ref(), freed);
+            ("200 OK", "text/plain; version=0.0.4; charset=utf-8", body)
+        }
+        (Some("GET"), _) => ("404 Not Found", TEXT, "Not found\n".to_string()),
+        _ => ("405 Method Not Allowed", TEXT, "Method not allowed\n".to_string()),
+    }
+}
+
+/// Whether a Host header names this server rather than some domain resolving to it
+fn is_local_host(host: &str, port: u16) -> bool {
+    let Some((name, host_port)) = host.rsplit_once(':') else {
+        return false;
+    };
+    host_port.parse() == Ok(port) && (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+}
+
+/// Compare without stopping at the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Metric name, help text, and the volume value it reports
+type VolumeMetric = (&'static str, &'static str, fn(&RootEntry) -> u64);
+
+/// Prometheus text exposition of per-volume space and the last scan's totals
+pub fn render_metrics(
+    volumes: &[RootEntry],
+    last_scan: Option<&LastScan>,
+    result: Option<&ScanResult>,
+    freed_since_last_scan: u64,
+) -> String {
+    let mut out = String::new();
+    let volume_metrics: [VolumeMetric; 3] = [
+        ("disksight_volume_size_bytes", "Total size of the volume", |v| v.total_bytes),
+        ("disksight_volume_free_bytes", "Space available on the volume", |v| v.available_bytes),
+        ("disksight_volume_used_bytes", "Space in use on the volume", |v| {
+            v.total_bytes.saturating_sub(v.available_bytes)
+        }),
+    ];
+    for (name, help, value) in volume_metrics {
+        write_header(&mut out, name, help);
+        for volume in volumes {
+            let _ = writeln!(
+                out,
+                "{}{{mount=\"{}\",fs=\"{}\",label=\"{}\"}} {}",
+                name,
+                escape_label(&volume.path),
+                escape_label(&volume.file_system),
+                escape_label(volume.label.as_deref().unwrap_or_default()),
+                value(volume)
+            );
+        }
+    }
+
+    if let Some(scan) = last_scan {
+        let root = escape_label(&scan.root_path);
+        let mut scan_metric = |name: &str, help: &str, value: u64| {
+            write_header(&mut out, name, help);
+            let _ = writeln!(out, "{}{{root=\"{}\"}} {}", name, root, value);
+        };
+        scan_metric("disksight_last_scan_total_bytes", "Bytes found by the last scan", scan.total_bytes);
+        if let Some(result) = result {
+            scan_metric("disksight_last_scan_files", "Files found by the last scan", result.total_files);
+            scan_metric("disksight_last_scan_dirs", "Directories found by the last scan", result.total_dirs);
+        }
+        scan_metric(
+            "disksight_last_scan_timestamp_seconds",
+            "When the last scan finished",
+            scan.finished_at / 1000,
+        );
+        scan_metric(
+            "disksight_freed_since_last_scan_bytes",
+            "Bytes deleted or trashed since the last scan",
+            freed_since_last_scan,
+        );
+    }
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_volume_gauges_with_escaped_labels() {
+        let volume = RootEntry {
+            name: "C:".to_string(),
+            path: "C:\\".to_string(),
+            total_bytes: 100,
+            available_bytes: 40,
+            file_system: "NTFS".to_string(),
+            copy_on_write: false,
+            is_removable: false,
+            is_network: false,
+            is_read_only: false,
+            label: Some("System \"main\"".to_string()),
+            device: "C:".to_string(),
+            disk_kind: "ssd".to_string(),
+            encrypted: None,
+        };
+        let text = render_metrics(&[volume], None, None, 0);
+        assert!(text.contains("# TYPE disksight_volume_free_bytes gauge"));
+        assert!(text.contains(
+            "disksight_volume_used_bytes{mount=\"C:\\\\\",fs=\"NTFS\",label=\"System \\\"main\\\"\"} 60"
+        ));
+        assert!(!text.contains("disksight_last_scan"));
+    }
+
+    #[test]
+    fn requests_need_a_local_host_and_the_bearer_token() {
+        let state = AppState::new();
+        let request = |host: &str, auth: &str, target: &str| {
+            let raw = format!("GET {} HTTP/1.1\r\nHost: {}\r\n{}\r\n", target, host, auth);
+            respond(&raw, 9617, "secret", &state).0
+        };
+        let auth = "authorization: Bearer secret\r\n";
+        assert_eq!(request("127.0.0.1:9617", auth, "/nope"), "404 Not Found");
+        assert_eq!(request("LOCALHOST:9617", auth, "/nope"), "404 Not Found");
+        assert_eq!(request("attacker.example:9617", auth, "/nope"), "403 Forbidden");
+        assert_eq!(request("127.0.0.1:80", auth, "/nope"), "403 Forbidden");
+        assert_eq!(request("127.0.0.1:9617", "", "/metrics"), "401 Unauthorized");
+        assert_eq!(request("127.0.0.1:9617", "Authorization: Bearer secreT\r\n", "/metrics"), "401 Unauthorized");
+        assert_eq!(respond("GET /metrics HTTP/1.1\r\n\r\n", 9617, "secret", &state).0, "403 Forbidden");
+    }
+
+    #[test]
+    fn the_token_is_created_once_per_install() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let token = api_token(Some(temp.path())).expect("token");
+        assert_eq!(token.len(), 32);
+        assert_eq!(api_token(Some(temp.path())).expect("reload"), token);
+        assert_ne!(api_token(None).expect("ephemeral"), token);
+    }
+}
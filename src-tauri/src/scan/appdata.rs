@@ -0,0 +1,240 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, AppDataUsage};
+
+/// Folder sequences that hold one folder per application, and whether everything
+/// below them is cache the application rebuilds
+const DATA_ROOTS: &[(&[&str], bool)] = &[
+    (&["appdata", "local", "packages"], false),
+    (&["appdata", "local"], false),
+    (&["appdata", "locallow"], false),
+    (&["appdata", "roaming"], false),
+    (&["library", "application support"], false),
+    (&["library", "caches"], true),
+    (&["library", "containers"], false),
+    (&["library", "logs"], true),
+    (&[".cache"], true),
+    (&[".config"], false),
+    (&[".local", "share"], false),
+];
+
+/// Vendor folders whose applications sit one level further down, e.g. Google/Chrome
+const VENDOR_FOLDERS: &[&str] = &[
+    "google", "microsoft", "adobe", "jetbrains", "mozilla", "apple", "nvidia", "nvidia corporation", "amd",
+    "intel", "autodesk", "unity", "epic", "bravesoftware",
+];
+
+/// Application names for well-known data folders, keyed by the lowercase folder name,
+/// or vendor and folder joined with `/`
+const KNOWN_APPS: &[(&str, &str)] = &[
+    ("google/chrome", "Google Chrome"),
+    ("google-chrome", "Google Chrome"),
+    ("microsoft/edge", "Microsoft Edge"),
+    ("microsoft-edge", "Microsoft Edge"),
+    ("microsoft edge", "Microsoft Edge"),
+    ("mozilla/firefox", "Firefox"),
+    ("firefox", "Firefox"),
+    ("bravesoftware/brave-browser", "Brave"),
+    ("chromium", "Chromium"),
+    ("com.apple.safari", "Safari"),
+    ("code", "Visual Studio Code"),
+    ("com.microsoft.vscode", "Visual Studio Code"),
+    ("microsoft/teams", "Microsoft Teams"),
+    ("microsoft/onedrive", "OneDrive"),
+    ("microsoft/outlook", "Outlook"),
+    ("slack", "Slack"),
+    ("com.tinyspeck.slackmacgap", "Slack"),
+    ("discord", "Discord"),
+    ("spotify", "Spotify"),
+    ("com.spotify.client", "Spotify"),
+    ("zoom", "Zoom"),
+    ("zoom.us", "Zoom"),
+    ("us.zoom.xos", "Zoom"),
+    ("telegram desktop", "Telegram"),
+    ("whatsapp", "WhatsApp"),
+    ("steam", "Steam"),
+    ("docker", "Docker"),
+    ("docker desktop", "Docker"),
+    ("com.docker.docker", "Docker"),
+    ("obs-studio", "OBS Studio"),
+    ("npm-cache", "npm"),
+    ("npm", "npm"),
+    ("yarn", "Yarn"),
+    ("pip", "pip"),
+    ("pnpm", "pnpm"),
+    ("go-build", "Go"),
+    ("gradle", "Gradle"),
+    ("jetbrains", "JetBrains IDEs"),
+    ("thumbnails", "Thumbnails"),
+    ("mesa_shader_cache", "Mesa shader cache"),
+    ("nvidia/dxcache", "NVIDIA shader cache"),
+    ("nvidia/glcache", "NVIDIA shader cache"),
+    ("d3dscache", "Direct3D shader cache"),
+    ("crashdumps", "Crash dumps"),
+    ("temp", "Temporary files"),
+];
+
+/// Folder names inside an application's data that hold rebuildable cache
+const CACHE_FOLDERS: &[&str] = &[
+    "cache", "caches", "cache2", "code cache", "gpucache", "shadercache", "grshadercache", "dawncache", "cacheddata",
+    "cachedextensionvsixs", "temp", "tmp", "logs", "crashpad", "crashes", "crashdumps", "service worker",
+];
+
+/// Attributes per-user application data (AppData on Windows, ~/Library on macOS, and
+/// ~/.cache, ~/.config, ~/.local/share elsewhere) to application names, with the share
+/// of each that is cache and safe to clear
+#[derive(Default)]
+pub struct AppDataAnalyzer {
+    apps: HashMap<String, AppTotals>,
+}
+
+#[derive(Default)]
+struct AppTotals {
+    folders: BTreeSet<String>,
+    bytes: u64,
+    files: u64,
+    cache_bytes: u64,
+}
+
+impl Analyzer for AppDataAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let Some(location) = locate(Path::new(&file.node.path)) else {
+            return;
+        };
+        let totals = self.apps.entry(location.app).or_default();
+        totals.folders.insert(location.folder.to_string_lossy().to_string());
+        totals.bytes += file.node.size_bytes;
+        totals.files += 1;
+        if location.cache {
+            totals.cache_bytes += file.node.size_bytes;
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut apps: Vec<AppDataUsage> = self
+            .apps
+            .into_iter()
+            .map(|(app, totals)| AppDataUsage {
+                app,
+                paths: totals.folders.into_iter().collect(),
+                total_bytes: totals.bytes,
+                files: totals.files,
+                cache_bytes: totals.cache_bytes,
+                cache_fraction: if totals.bytes == 0 {
+                    0.0
+                } else {
+                    totals.cache_bytes as f64 / totals.bytes as f64
+                },
+            })
+            .collect();
+        apps.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then_with(|| a.app.cmp(&b.app)));
+        AnalyzerReport::AppData { apps }
+    }
+}
+
+struct Location {
+    app: String,
+    /// The application's data folder
+    folder: PathBuf,
+    cache: bool,
+}
+
+/// Which application's data a file belongs to, if it lies in a per-user data folder
+fn locate(path: &Path) -> Option<Location> {
+    let components: Vec<Component> = path.components().collect();
+    let lower: Vec<String> = components
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    // The deepest root wins, so AppData/Local/Packages beats AppData/Local
+    let (end, cache_root) = DATA_ROOTS
+        .iter()
+        .filter_map(|(sequence, cache)| {
+            lower
+                .windows(sequence.len())
+                .rposition(|window| window.iter().zip(sequence.iter()).all(|(a, b)| a == b))
+                .map(|start| (start + sequence.len(), *cache))
+        })
+        .max_by_key(|(end, _)| *end)?;
+    // The file must sit inside a folder below the root
+    if lower.len() < end + 2 {
+        return None;
+    }
+    let vendor = &lower[end];
+    let app_len = if VENDOR_FOLDERS.contains(&vendor.as_str()) && lower.len() > end + 2 {
+        end + 2
+    } else {
+        end + 1
+    };
+    let key = lower[end..app_len].join("/");
+    let display = components[app_len - 1].as_os_str().to_string_lossy().to_string();
+    let app = KNOWN_APPS
+        .iter()
+        .find(|(folder, _)| *folder == key || *folder == lower[app_len - 1])
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| app_name(&display));
+    let inner = &lower[app_len..lower.len() - 1];
+    Some(Location {
+        app,
+        folder: components[..app_len].iter().collect(),
+        cache: cache_root
+            || key == "temp"
+            || inner.iter().any(|folder| CACHE_FOLDERS.contains(&folder.as_str())),
+    })
+}
+
+/// A readable name from a data folder name: the product part of a reverse-DNS bundle
+/// id or a Windows package family name, or the folder name itself
+fn app_name(folder: &str) -> String {
+    // Package family names end with `_<publisher hash>`
+    let name = match folder.rsplit_once('_') {
+        Some((name, hash)) if hash.len() == 13 && hash.chars().all(|c| c.is_ascii_alphanumeric()) => name,
+        _ => folder,
+    };
+    let parts: Vec<&str> = name.split('.').filter(|part| !part.is_empty()).collect();
+    let is_dotted_id = parts.len() >= 3 || (parts.len() == 2 && !name.contains(' ') && parts[1].len() > 4);
+    if !is_dotted_id {
+        return folder.to_string();
+    }
+    const GENERIC: &[&str] = &["app", "client", "desktop", "helper", "mac", "macos", "agent"];
+    parts
+        .iter()
+        .rev()
+        .find(|part| !GENERIC.contains(&part.to_lowercase().as_str()))
+        .map(|part| part.to_string())
+        .unwrap_or_else(|| folder.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_data_folders_to_applications() {
+        let chrome = locate(Path::new("C:/Users/me/AppData/Local/Google/Chrome/User Data/Default/Cache/f_01"))
+            .expect("chrome");
+        assert_eq!(chrome.app, "Google Chrome");
+        assert!(chrome.cache);
+        assert!(chrome.folder.ends_with("Local/Google/Chrome"));
+
+        let slack = locate(Path::new("/Users/me/Library/Application Support/Slack/storage/db")).expect("slack");
+        assert_eq!((slack.app.as_str(), slack.cache), ("Slack", false));
+        let cached = locate(Path::new("/Users/me/Library/Caches/com.spotify.client/Data/x")).expect("spotify");
+        assert_eq!((cached.app.as_str(), cached.cache), ("Spotify", true));
+        let temp = locate(Path::new("C:/Users/me/AppData/Local/Temp/setup/a.msi")).expect("temp");
+        assert_eq!((temp.app.as_str(), temp.cache), ("Temporary files", true));
+
+        let terminal = locate(Path::new(
+            "C:/Users/me/AppData/Local/Packages/Microsoft.WindowsTerminal_8wekyb3d8bbwe/LocalState/settings.json",
+        ))
+        .expect("terminal");
+        assert_eq!(terminal.app, "WindowsTerminal");
+        assert_eq!(app_name("org.gnome.Nautilus"), "Nautilus");
+        assert_eq!(app_name("Blender Foundation"), "Blender Foundation");
+
+        assert!(locate(Path::new("/home/me/.cache/loose-file")).is_none());
+        assert!(locate(Path::new("/home/me/Documents/report.pdf")).is_none());
+    }
+}
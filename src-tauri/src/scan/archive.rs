@@ -0,0 +1,543 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::scan::delete::DeleteResult;
+use crate::scan::diskimage;
+
+pub const EVENT_ARCHIVE_PROGRESS: &str = "archive://progress";
+/// Files whose archived bytes are compared with the originals during verification;
+/// every other entry is checked by size and CRC
+const SPOT_CHECKS: usize = 16;
+/// Zip entries larger than this need the zip64 extension
+const ZIP64_THRESHOLD: u64 = 0xFFFF_FFFF;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    SevenZip,
+    Tar,
+    TarGz,
+    /// ISO 9660 disc image, read natively without mounting
+    Iso,
+    /// Windows imaging file; lists its images, not their files
+    Wim,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Stored size, when the format records it per entry
+    pub compressed_bytes: Option<u64>,
+    pub is_dir: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveListing {
+    pub path: String,
+    pub format: ArchiveFormat,
+    pub archive_bytes: u64,
+    pub uncompressed_bytes: u64,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtractResult {
+    pub extracted: Vec<String>,
+    pub bytes_written: u64,
+    pub missing: Vec<String>,
+    /// Entries not extracted because a file already exists at their destination
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+/// Stage of an archive-then-delete operation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchivePhase {
+    Archiving,
+    Verifying,
+    Deleting,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveProgressPayload {
+    pub archive_path: String,
+    pub phase: ArchivePhase,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Outcome of an archive-then-delete operation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveDeleteResult {
+    pub archive_path: String,
+    pub summary: ArchiveSummary,
+    pub delete: DeleteResult,
+}
+
+/// What went into a newly written archive
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub files: u64,
+    pub folders: u64,
+    /// Uncompressed bytes of the archived files
+    pub bytes: u64,
+    pub archive_bytes: u64,
+}
+
+/// One file or folder to store, with its name inside the archive
+struct ArchiveItem {
+    source: PathBuf,
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// The top-level archive name of each selected path: its file name, with " (2)" and so
+/// on added when two selections share a name
+fn top_level_names(paths: &[PathBuf]) -> Vec<String> {
+    let mut used = HashSet::new();
+    paths
+        .iter()
+        .map(|path| {
+            let base = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "item".to_string());
+            let mut name = base.clone();
+            let mut copy = 2;
+            while !used.insert(name.to_lowercase()) {
+                let stem = Path::new(&base).file_stem().map(|stem| stem.to_string_lossy().to_string());
+                let ext = Path::new(&base).extension().map(|ext| format!(".{}", ext.to_string_lossy()));
+                name = format!("{} ({}){}", stem.unwrap_or_default(), copy, ext.unwrap_or_default());
+                copy += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+/// Every file and folder below the selected paths. Symbolic links are not followed or
+/// stored, and selections that no longer exist are passed over.
+fn archive_items(paths: &[PathBuf]) -> Result<Vec<ArchiveItem>, String> {
+    let mut items = Vec::new();
+    for (path, name) in paths.iter().zip(top_level_names(paths)) {
+        if fs::symlink_metadata(path).is_ok() {
+            add_items(path, name, &mut items)?;
+        }
+    }
+    Ok(items)
+}
+
+fn add_items(path: &Path, name: String, items: &mut Vec<ArchiveItem>) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    if !metadata.is_dir() {
+        items.push(ArchiveItem {
+            source: path.to_path_buf(),
+            name,
+            is_dir: false,
+            size: metadata.len(),
+        });
+        return Ok(());
+    }
+    items.push(ArchiveItem {
+        source: path.to_path_buf(),
+        name: format!("{}/", name),
+        is_dir: true,
+        size: 0,
+    });
+    let mut children: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()?;
+    children.sort();
+    for child in children {
+        let child_name = child.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        add_items(&child, format!("{}/{}", name, child_name), items)?;
+    }
+    Ok(())
+}
+
+/// Zip the selected paths into a new archive at `archive_path`. The archive is written
+/// beside its final name and renamed into place once complete.
+pub fn write_zip(
+    paths: &[PathBuf],
+    archive_path: &Path,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<ArchiveSummary, String> {
+    if archive_path.exists() {
+        return Err(format!("{} already exists", archive_path.display()));
+    }
+    let items = archive_items(paths)?;
+    let total: u64 = items.iter().map(|item| item.size).sum();
+    let partial = archive_path.with_extension("zip.partial");
+    let mut summary = ArchiveSummary::default();
+    let outcome = File::create(&partial)
+        .map_err(|e| e.to_string())
+        .and_then(|file| write_items(file, &items, &mut summary, |done| on_progress(done, total)))
+        .and_then(|_| fs::rename(&partial, archive_path).map_err(|e| e.to_string()));
+    if let Err(e) = outcome {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    summary.archive_bytes = fs::metadata(archive_path).map(|metadata| metadata.len()).unwrap_or(0);
+    Ok(summary)
+}
+
+fn write_items(
+    file: File,
+    items: &[ArchiveItem],
+    summary: &mut ArchiveSummary,
+    mut on_progress: impl FnMut(u64),
+) -> Result<(), String> {
+    let mut writer = zip::ZipWriter::new(BufWriter::new(file));
+    for item in items {
+        if item.is_dir {
+            writer
+                .add_directory(item.name.as_str(), zip::write::SimpleFileOptions::default())
+                .map_err(|e| e.to_string())?;
+            summary.folders += 1;
+            continue;
+        }
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(item.size >= ZIP64_THRESHOLD);
+        writer.start_file(item.name.as_str(), options).map_err(|e| e.to_string())?;
+        let mut source = File::open(&item.source).map_err(|e| format!("{}: {}", item.source.display(), e))?;
+        summary.bytes += io::copy(&mut source, &mut writer).map_err(|e| e.to_string())?;
+        summary.files += 1;
+        on_progress(summary.bytes);
+    }
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Check that the archive holds every file and folder below the selected paths as
+/// they are now: each file entry must exist with the current size and decompress
+/// without a CRC error, and a spread of them must match the originals byte for byte.
+pub fn verify_zip(paths: &[PathBuf], archive_path: &Path, mut on_progress: impl FnMut(u64, u64)) -> Result<(), String> {
+    let items = archive_items(paths)?;
+    let file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let total: u64 = items.iter().map(|item| item.size).sum();
+    let files = items.iter().filter(|item| !item.is_dir).count();
+    let spot_every = files.div_ceil(SPOT_CHECKS).max(1);
+    let mut done = 0;
+    for (index, item) in items.iter().filter(|item| !item.is_dir).enumerate() {
+        let mut entry = archive
+            .by_name(&item.name)
+            .map_err(|_| format!("{} is missing from the archive", item.source.display()))?;
+        if entry.size() != item.size {
+            return Err(format!("{} changed size since it was archived", item.source.display()));
+        }
+        // Reading an entry to the end checks its CRC
+        if index % spot_every == 0 {
+            let mut archived = blake3::Hasher::new();
+            io::copy(&mut entry, &mut archived).map_err(|e| format!("{}: {}", item.name, e))?;
+            let mut original = blake3::Hasher::new();
+            io::copy(&mut File::open(&item.source).map_err(|e| e.to_string())?, &mut original)
+                .map_err(|e| e.to_string())?;
+            if archived.finalize() != original.finalize() {
+                return Err(format!("{} differs from its archived copy", item.source.display()));
+            }
+        } else {
+            io::copy(&mut entry, &mut io::sink()).map_err(|e| format!("{}: {}", item.name, e))?;
+        }
+        done += item.size;
+        on_progress(done, total);
+    }
+    for item in items.iter().filter(|item| item.is_dir) {
+        if archive.index_for_name(&item.name).is_none() {
+            return Err(format!("{} is missing from the archive", item.source.display()));
+        }
+    }
+    Ok(())
+}
+
+pub fn detect_format(path: &Path) -> Option<ArchiveFormat> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveFormat::Zip)
+    } else if name.ends_with(".7z") {
+        Some(ArchiveFormat::SevenZip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveFormat::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveFormat::Tar)
+    } else if name.ends_with(".iso") {
+        Some(ArchiveFormat::Iso)
+    } else if name.ends_with(".wim") || name.ends_with(".esd") {
+        Some(ArchiveFormat::Wim)
+    } else {
+        None
+    }
+}
+
+/// List an archive's entries and uncompressed sizes without extracting anything
+pub fn list_archive(path: &Path) -> Result<ArchiveListing, String> {
+    let format = detect_format(path).ok_or_else(|| format!("Unsupported archive type: {}", path.display()))?;
+    let archive_bytes = fs::metadata(path).map_err(|e| e.to_string())?.len();
+
+    let entries = match format {
+        ArchiveFormat::Zip => list_zip(path)?,
+        ArchiveFormat::SevenZip => list_7z(path)?,
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => list_tar(path, format)?,
+        ArchiveFormat::Iso => diskimage::list_iso(path)?,
+        ArchiveFormat::Wim => diskimage::list_wim(path)?,
+    };
+
+    Ok(ArchiveListing {
+        path: path.to_string_lossy().to_string(),
+        format,
+        archive_bytes,
+        uncompressed_bytes: entries.iter().map(|e| e.size_bytes).sum(),
+        entries,
+    })
+}
+
+fn list_zip(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+        entries.push(ArchiveEntry {
+            name: entry.name().to_string(),
+            size_bytes: entry.size(),
+            compressed_bytes: Some(entry.compressed_size()),
+            is_dir: entry.is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+fn list_7z(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let archive = sevenz_rust::Archive::open(path).map_err(|e| e.to_string())?;
+    Ok(archive
+        .files
+        .iter()
+        .map(|entry| ArchiveEntry {
+            name: entry.name().to_string(),
+            size_bytes: entry.size(),
+            // Solid 7z blocks compress many files together, so per-entry sizes aren't meaningful
+            compressed_bytes: None,
+            is_dir: entry.is_directory(),
+        })
+        .collect())
+}
+
+fn list_tar(path: &Path, format: ArchiveFormat) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = tar::Archive::new(tar_reader(path, format)?);
+    let mut entries = Vec::new();
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let header = entry.header();
+        entries.push(ArchiveEntry {
+            name: entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string(),
+            size_bytes: header.size().unwrap_or(0),
+            compressed_bytes: None,
+            is_dir: header.entry_type().is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+fn tar_reader(path: &Path, format: ArchiveFormat) -> Result<Box<dyn Read>, String> {
+    let file = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    Ok(match format {
+        ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+/// Extract only the named entries into `dest`, keeping their relative paths.
+/// Entry names that would escape `dest` are rejected, and entries whose file already
+/// exists are reported as conflicts and left alone.
+pub fn extract_entries(path: &Path, entries: &[String], dest: &Path) -> Result<ExtractResult, String> {
+    let format = detect_format(path).ok_or_else(|| format!("Unsupported archive type: {}", path.display()))?;
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+    let mut result = ExtractResult {
+        extracted: Vec::new(),
+        bytes_written: 0,
+        missing: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    let mut write_entry = |name: &str, reader: &mut dyn Read| -> Result<(), String> {
+        let target = safe_join(dest, name)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // Never overwrite what is already at the destination
+        let mut out = match OpenOptions::new().write(true).create_new(true).open(&target) {
+            Ok(out) => out,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                // Solid and streamed formats expect each entry to be read through
+                io::copy(reader, &mut io::sink()).map_err(|e| e.to_string())?;
+                result.conflicts.push(name.to_string());
+                return Ok(());
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        result.bytes_written += io::copy(reader, &mut out).map_err(|e| e.to_string())?;
+        result.extracted.push(name.to_string());
+        Ok(())
+    };
+
+    match format {
+        ArchiveFormat::Zip => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+            for name in entries {
+                match archive.by_name(name) {
+                    Ok(mut entry) if !entry.is_dir() => write_entry(name, &mut entry)?,
+                    _ => continue,
+                }
+            }
+        }
+        ArchiveFormat::SevenZip => {
+            let mut reader = sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
+                .map_err(|e| e.to_string())?;
+            let mut failure = None;
+            reader
+                .for_each_entries(|entry, data| {
+                    if entry.is_directory() || !entries.iter().any(|n| n == entry.name()) {
+                        return Ok(true);
+                    }
+                    match write_entry(entry.name(), data) {
+                        Ok(()) => Ok(true),
+                        Err(e) => {
+                            failure = Some(e);
+                            Ok(false)
+                        }
+                    }
+                })
+                .map_err(|e| e.to_string())?;
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+            let mut archive = tar::Archive::new(tar_reader(path, format)?);
+            for entry in archive.entries().map_err(|e| e.to_string())? {
+                let mut entry = entry.map_err(|e| e.to_string())?;
+                let name = entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string();
+                if entry.header().entry_type().is_file() && entries.contains(&name) {
+                    write_entry(&name, &mut entry)?;
+                }
+            }
+        }
+        ArchiveFormat::Iso => diskimage::extract_iso(path, entries, &mut write_entry)?,
+        ArchiveFormat::Wim => {
+            return Err("Files can't be extracted from a WIM image; mount or apply it with DISM".to_string())
+        }
+    }
+
+    result.missing = entries
+        .iter()
+        .filter(|name| !result.extracted.contains(name) && !result.conflicts.contains(name))
+        .cloned()
+        .collect();
+    Ok(result)
+}
+
+/// Join an archive entry name onto `dest`, refusing absolute paths and `..` components
+fn safe_join(dest: &Path, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("Refusing to extract entry outside destination: {}", name));
+    }
+    Ok(dest.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn lists_and_extracts_selected_zip_entries() {
+        let temp = tempdir().expect("tempdir");
+        let archive_path = temp.path().join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).expect("create"));
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("docs/a.txt", options).expect("start a");
+        writer.write_all(&[b'a'; 100]).expect("write a");
+        writer.start_file("b.txt", options).expect("start b");
+        writer.write_all(b"bb").expect("write b");
+        writer.finish().expect("finish");
+
+        let listing = list_archive(&archive_path).expect("listing");
+        assert_eq!(listing.format, ArchiveFormat::Zip);
+        assert_eq!(listing.entries.len(), 2);
+        assert_eq!(listing.uncompressed_bytes, 102);
+
+        let dest = temp.path().join("out");
+        let result = extract_entries(
+            &archive_path,
+            &["docs/a.txt".to_string(), "nope.txt".to_string()],
+            &dest,
+        )
+        .expect("extract");
+        assert_eq!(result.bytes_written, 100);
+        assert_eq!(result.missing, vec!["nope.txt".to_string()]);
+        assert!(dest.join("docs").join("a.txt").exists());
+        assert!(!dest.join("b.txt").exists());
+
+        fs::write(dest.join("b.txt"), b"mine").expect("write existing");
+        let again = extract_entries(&archive_path, &["docs/a.txt".to_string(), "b.txt".to_string()], &dest)
+            .expect("extract again");
+        assert!(again.extracted.is_empty());
+        assert_eq!(again.conflicts, vec!["docs/a.txt".to_string(), "b.txt".to_string()]);
+        assert!(again.missing.is_empty());
+        assert_eq!(fs::read(dest.join("b.txt")).expect("read existing"), b"mine");
+        assert_eq!(fs::read(dest.join("docs").join("a.txt")).expect("read a").len(), 100);
+    }
+
+    #[test]
+    fn writes_and_verifies_an_archive_of_the_selection() {
+        let temp = tempdir().expect("tempdir");
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("renders")).expect("create dirs");
+        fs::create_dir_all(project.join("empty")).expect("create empty");
+        fs::write(project.join("renders").join("cut.mp4"), [7u8; 5000]).expect("write cut");
+        let other = temp.path().join("other");
+        fs::create_dir_all(&other).expect("create other");
+        fs::write(other.join("project"), b"same name").expect("write clash");
+        let paths = vec![project.clone(), other.join("project")];
+
+        let archive_path = temp.path().join("backup.zip");
+        let summary = write_zip(&paths, &archive_path, |_, _| {}).expect("write");
+        assert_eq!((summary.files, summary.folders, summary.bytes), (2, 3, 5009));
+        assert!(archive_path.exists() && !temp.path().join("backup.zip.partial").exists());
+        let listing = list_archive(&archive_path).expect("list");
+        let names: Vec<String> = listing.entries.into_iter().map(|entry| entry.name).collect();
+        assert!(names.contains(&"project/renders/cut.mp4".to_string()));
+        assert!(names.contains(&"project (2)".to_string()));
+        verify_zip(&paths, &archive_path, |_, _| {}).expect("verify");
+
+        fs::write(project.join("renders").join("cut.mp4"), [7u8; 10]).expect("rewrite");
+        assert!(verify_zip(&paths, &archive_path, |_, _| {}).is_err());
+        assert!(write_zip(&paths, &archive_path, |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn rejects_escaping_entry_names() {
+        let dest = Path::new("/tmp/out");
+        assert!(safe_join(dest, "../evil").is_err());
+        assert!(safe_join(dest, "/etc/passwd").is_err());
+        assert!(safe_join(dest, "ok/file.txt").is_ok());
+    }
+}
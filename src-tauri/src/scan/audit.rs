@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::delete::SafetyLevel;
+
+const AUDIT_FILE: &str = "audit.log";
+const DEFAULT_AUDIT_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Delete,
+    Trash,
+    Move,
+    Compress,
+    EmptyTrash,
+    AclChange,
+    ResizeShadowStorage,
+    CompactDisk,
+    /// Superseded Windows components removed with DISM
+    ComponentCleanup,
+    /// Put back from the trash
+    Restore,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failed,
+    Blocked,
+}
+
+/// How the operation was authorized
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmedBy {
+    /// AutoDelete classification, no prompt shown
+    Auto,
+    /// The user explicitly confirmed in the UI
+    User,
+    /// A cleanup policy the user set up, run on its schedule or on demand
+    Policy,
+}
+
+/// One line of the append-only audit log
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub action: AuditAction,
+    pub path: String,
+    pub size_bytes: u64,
+    pub safety_level: Option<SafetyLevel>,
+    pub confirmed_by: ConfirmedBy,
+    /// OS account that ran the app
+    pub user: String,
+    pub outcome: AuditOutcome,
+    pub message: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction, path: &str, confirmed_by: ConfirmedBy, outcome: AuditOutcome) -> Self {
+        Self {
+            timestamp: now_millis(),
+            action,
+            path: path.to_string(),
+            size_bytes: 0,
+            safety_level: None,
+            confirmed_by,
+            user: current_user(),
+            outcome,
+            message: None,
+        }
+    }
+}
+
+/// Time window (unix millis, inclusive) and paging for `get_audit_log`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuditRange {
+    #[serde(default)]
+    pub from: Option<u64>,
+    #[serde(default)]
+    pub to: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Append entries to the audit log in `dir`
+pub fn append_entries(dir: &Path, entries: &[AuditEntry]) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(AUDIT_FILE))
+        .map_err(|e| e.to_string())?;
+    let mut buffer = String::new();
+    for entry in entries {
+        buffer.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        buffer.push('\n');
+    }
+    file.write_all(buffer.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Read entries within `range`, newest first
+pub fn read_entries(dir: &Path, range: &AuditRange) -> Result<Vec<AuditEntry>, String> {
+    let file = match fs::File::open(dir.join(AUDIT_FILE)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut entries: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        // Skip lines torn by a crash mid-write rather than failing the whole read
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(&line).ok())
+        .filter(|entry| range.from.is_none_or(|from| entry.timestamp >= from))
+        .filter(|entry| range.to.is_none_or(|to| entry.timestamp <= to))
+        .collect();
+    entries.reverse();
+    entries.truncate(range.limit.unwrap_or(DEFAULT_AUDIT_LIMIT));
+    Ok(entries)
+}
+
+fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn appends_and_reads_newest_first() {
+        let temp = tempdir().expect("tempdir");
+        let mut first = AuditEntry::new(AuditAction::Trash, "/a", ConfirmedBy::Auto, AuditOutcome::Success);
+        first.timestamp = 100;
+        let mut second = AuditEntry::new(AuditAction::Delete, "/b", ConfirmedBy::User, AuditOutcome::Failed);
+        second.timestamp = 200;
+        append_entries(temp.path(), &[first]).expect("append first");
+        append_entries(temp.path(), &[second]).expect("append second");
+
+        let all = read_entries(temp.path(), &AuditRange::default()).expect("read");
+        assert_eq!(all.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), vec!["/b", "/a"]);
+
+        let ranged = read_entries(
+            temp.path(),
+            &AuditRange { from: Some(150), to: None, limit: None },
+        )
+        .expect("read range");
+        assert_eq!(ranged.len(), 1);
+        assert_eq!(ranged[0].outcome, AuditOutcome::Failed);
+    }
+
+    #[test]
+    fn missing_log_is_empty() {
+        let temp = tempdir().expect("tempdir");
+        assert!(read_entries(temp.path(), &AuditRange::default()).expect("read").is_empty());
+    }
+}
@@ -0,0 +1,677 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::scan::limits::ScanLimits;
+use crate::scan::model::ScanOptions;
+
+/// How the engine reads file sizes during a walk. The directory traversal itself is
+/// always the walker's; backends differ in how file metadata is fetched.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanBackend {
+    /// The platform fast path when there is one, else the walker
+    #[default]
+    Auto,
+    /// One stat call per file as the walker reaches it
+    Walker,
+    /// macOS getattrlistbulk: names, types, sizes, and times for a whole directory in
+    /// a few calls
+    AttrListBulk,
+    /// Linux io_uring: the statx calls for a directory's files submitted as one batch.
+    /// Kernels without io_uring statx (before 5.6, or with io_uring disabled) use the
+    /// walker.
+    IoUring,
+    /// The walker, with the files of large directories stat'ed across worker threads
+    ParallelStat,
+}
+
+impl ScanBackend {
+    /// The backend a scan with `options` actually uses. Analyzers that read platform
+    /// metadata (owners, allocated blocks) need the walker's per-file stat, and limits
+    /// that leave parallel stat a single thread leave it the walker's.
+    pub fn resolve(options: &ScanOptions) -> ScanBackend {
+        if options.analyzers.iter().any(|config| config.needs_metadata()) {
+            return ScanBackend::Walker;
+        }
+        let threads = options.limits.stat_threads(ParallelStat::default_threads());
+        if options.backend == ScanBackend::ParallelStat && threads < 2 {
+            return ScanBackend::Walker;
+        }
+        match options.backend {
+            ScanBackend::Auto if cfg!(target_os = "macos") => ScanBackend::AttrListBulk,
+            ScanBackend::Auto if cfg!(target_os = "linux") => ScanBackend::IoUring,
+            ScanBackend::Auto => ScanBackend::Walker,
+            ScanBackend::AttrListBulk if !cfg!(target_os = "macos") => ScanBackend::Walker,
+            ScanBackend::IoUring if !cfg!(target_os = "linux") => ScanBackend::Walker,
+            other => other,
+        }
+    }
+
+    /// Every backend that can run on this platform
+    pub fn available() -> Vec<ScanBackend> {
+        let mut backends = vec![ScanBackend::Walker, ScanBackend::ParallelStat];
+        if cfg!(target_os = "macos") {
+            backends.push(ScanBackend::AttrListBulk);
+        }
+        #[cfg(target_os = "linux")]
+        if uring::UringStatx::new().is_some() {
+            backends.push(ScanBackend::IoUring);
+        }
+        backends
+    }
+}
+
+/// Size and modification time of a regular file, however it was read
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EntryStat {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<&Metadata> for EntryStat {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+/// Reads the metadata of every regular file in a directory at once
+pub trait BulkStat: Send {
+    fn read_dir(&mut self, dir: &Path) -> std::io::Result<Vec<(OsString, EntryStat)>>;
+}
+
+/// The bulk reader for a resolved backend, or None when files are stat'ed one by one
+pub fn bulk_stat(backend: ScanBackend, limits: &ScanLimits) -> Option<Box<dyn BulkStat>> {
+    match backend {
+        #[cfg(target_os = "macos")]
+        ScanBackend::AttrListBulk => Some(Box::new(attrlist::AttrListBulk)),
+        #[cfg(target_os = "linux")]
+        ScanBackend::IoUring => uring::UringStatx::new().map(|reader| Box::new(reader) as Box<dyn BulkStat>),
+        ScanBackend::ParallelStat => Some(Box::new(ParallelStat::with_threads(
+            limits.stat_threads(ParallelStat::default_threads()),
+        ))),
+        _ => None,
+    }
+}
+
+/// Directories with fewer files than this are left to per-file stat by ParallelStat;
+/// spreading a handful of calls over threads costs more than it saves
+const PARALLEL_MIN_FILES: usize = 64;
+
+/// Stats the regular files of a directory on several threads at once, which pays off
+/// on network shares and spinning disks where each stat waits on a round trip
+pub struct ParallelStat {
+    threads: usize,
+}
+
+impl ParallelStat {
+    pub fn new() -> Self {
+        Self::with_threads(Self::default_threads())
+    }
+
+    pub fn with_threads(threads: usize) -> Self {
+        Self { threads: threads.max(1) }
+    }
+
+    /// One thread per core, within 2 to 8
+    pub fn default_threads() -> usize {
+        std::thread::available_parallelism().map_or(4, |n| n.get()).clamp(2, 8)
+    }
+}
+
+impl Default for ParallelStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BulkStat for ParallelStat {
+    fn read_dir(&mut self, dir: &Path) -> std::io::Result<Vec<(OsString, EntryStat)>> {
+        let names: Vec<OsString> = std::fs::read_dir(dir)?
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .map(|entry| entry.file_name())
+            .collect();
+        if names.len() < PARALLEL_MIN_FILES {
+            return Ok(Vec::new());
+        }
+        let chunk = names.len().div_ceil(self.threads);
+        let entries = std::thread::scope(|scope| {
+            let workers: Vec<_> = names
+                .chunks(chunk)
+                .map(|names| {
+                    scope.spawn(move || {
+                        names
+                            .iter()
+                            .filter_map(|name| {
+                                let metadata = std::fs::symlink_metadata(dir.join(name)).ok()?;
+                                metadata
+                                    .is_file()
+                                    .then(|| (name.clone(), EntryStat::from(&metadata)))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        });
+        Ok(entries)
+    }
+}
+
+/// Bulk-read file stats, loaded per directory the first time one of its files is
+/// asked for. Files the reader skipped (symlinks, unreadable entries) return None so
+/// the caller can stat them itself.
+pub struct StatCache {
+    reader: Box<dyn BulkStat>,
+    dirs: HashMap<PathBuf, HashMap<OsString, EntryStat>>,
+}
+
+impl StatCache {
+    pub fn new(reader: Box<dyn BulkStat>) -> Self {
+        Self {
+            reader,
+            dirs: HashMap::new(),
+        }
+    }
+
+    pub fn take(&mut self, path: &Path) -> Option<EntryStat> {
+        let (dir, name) = (path.parent()?, path.file_name()?);
+        if !self.dirs.contains_key(dir) {
+            // A directory that can't be read in bulk is remembered as empty, so its
+            // files fall back to stat without retrying the bulk read for each
+            let entries = self.reader.read_dir(dir).unwrap_or_default();
+            self.dirs.insert(dir.to_path_buf(), entries.into_iter().collect());
+        }
+        self.dirs.get_mut(dir)?.remove(name)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod attrlist {
+    use std::ffi::{CString, OsStr, OsString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{BulkStat, EntryStat};
+
+    const ATTR_CMN_ERROR: u32 = 0x2000_0000;
+    /// fsobj_type_t of a regular file
+    const VREG: u32 = 1;
+    const BUFFER_BYTES: usize = 256 * 1024;
+
+    pub struct AttrListBulk;
+
+    impl BulkStat for AttrListBulk {
+        fn read_dir(&mut self, dir: &Path) -> std::io::Result<Vec<(OsString, EntryStat)>> {
+            let c_dir = CString::new(dir.as_os_str().as_bytes())
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            // SAFETY: c_dir is a valid null-terminated path
+            let fd = unsafe { libc::open(c_dir.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let result = read_entries(fd);
+            // SAFETY: fd was opened above and is closed once
+            unsafe { libc::close(fd) };
+            result
+        }
+    }
+
+    fn read_entries(fd: libc::c_int) -> std::io::Result<Vec<(OsString, EntryStat)>> {
+        let mut request = libc::attrlist {
+            bitmapcount: libc::ATTR_BIT_MAP_COUNT,
+            reserved: 0,
+            commonattr: libc::ATTR_CMN_RETURNED_ATTRS
+                | libc::ATTR_CMN_NAME
+                | ATTR_CMN_ERROR
+                | libc::ATTR_CMN_OBJTYPE
+                | libc::ATTR_CMN_MODTIME,
+            volattr: 0,
+            dirattr: 0,
+            fileattr: libc::ATTR_FILE_DATALENGTH,
+            forkattr: 0,
+        };
+        let mut buffer = vec![0u8; BUFFER_BYTES];
+        let mut entries = Vec::new();
+        loop {
+            // SAFETY: the request and buffer outlive the call and the size passed
+            // matches the buffer
+            let count = unsafe {
+                libc::getattrlistbulk(
+                    fd,
+                    &mut request as *mut libc::attrlist as *mut libc::c_void,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                    0,
+                )
+            };
+            if count < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if count == 0 {
+                return Ok(entries);
+            }
+            let mut offset = 0usize;
+            for _ in 0..count {
+                let Some(length) = read_u32(&buffer, offset) else {
+                    break;
+                };
+                if let Some(entry) = parse_entry(&buffer[offset..(offset + length as usize).min(buffer.len())]) {
+                    entries.push(entry);
+                }
+                offset += length as usize;
+            }
+        }
+    }
+
+    /// One packed entry: length, returned attribute set, then each returned attribute
+    /// in the order getattrlist(2) documents
+    fn parse_entry(entry: &[u8]) -> Option<(OsString, EntryStat)> {
+        let returned_common = read_u32(entry, 4)?;
+        let returned_file = read_u32(entry, 16)?;
+        let mut field = 4 + std::mem::size_of::<libc::attribute_set_t>();
+
+        let mut name = None;
+        if returned_common & libc::ATTR_CMN_NAME != 0 {
+            let data_offset = read_u32(entry, field)? as i32;
+            let data_length = read_u32(entry, field + 4)? as usize;
+            let start = (field as i64 + i64::from(data_offset)) as usize;
+            // The length includes the terminating null
+            let bytes = entry.get(start..start + data_length.saturating_sub(1))?;
+            name = Some(OsStr::from_bytes(bytes).to_os_string());
+            field += std::mem::size_of::<libc::attrreference_t>();
+        }
+        if returned_common & ATTR_CMN_ERROR != 0 {
+            if read_u32(entry, field)? != 0 {
+                return None;
+            }
+            field += 4;
+        }
+        if returned_common & libc::ATTR_CMN_OBJTYPE == 0 || read_u32(entry, field)? != VREG {
+            return None;
+        }
+        field += 4;
+        let mut modified = None;
+        if returned_common & libc::ATTR_CMN_MODTIME != 0 {
+            let secs = read_i64(entry, field)?;
+            let nanos = read_i64(entry, field + 8)?;
+            modified = u64::try_from(secs)
+                .ok()
+                .map(|secs| UNIX_EPOCH + Duration::new(secs, nanos.clamp(0, 999_999_999) as u32));
+            field += std::mem::size_of::<libc::timespec>();
+        }
+        if returned_file & libc::ATTR_FILE_DATALENGTH == 0 {
+            return None;
+        }
+        let size = read_i64(entry, field)?.max(0) as u64;
+        Some((name?, EntryStat { size, modified }))
+    }
+
+    fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+        Some(u32::from_ne_bytes(buffer.get(offset..offset + 4)?.try_into().ok()?))
+    }
+
+    fn read_i64(buffer: &[u8], offset: usize) -> Option<i64> {
+        Some(i64::from_ne_bytes(buffer.get(offset..offset + 8)?.try_into().ok()?))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod uring {
+    use std::ffi::{CString, OsStr, OsString};
+    use std::mem::MaybeUninit;
+    use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use rustix::fs::{AtFlags, Mode, OFlags, Statx, StatxFlags};
+    use rustix::io::Errno;
+    use rustix::io_uring::{
+        addr_or_splice_off_in_union, io_uring_cqe, io_uring_enter, io_uring_params, io_uring_ptr,
+        io_uring_setup, io_uring_sqe, io_uring_user_data, len_union, off_or_addr2_union, op_flags_union,
+        IoringEnterFlags, IoringOp, IORING_OFF_CQ_RING, IORING_OFF_SQES, IORING_OFF_SQ_RING,
+    };
+    use rustix::mm::{mmap, munmap, MapFlags, ProtFlags};
+
+    use super::{BulkStat, EntryStat};
+
+    const RING_ENTRIES: u32 = 256;
+    /// Below this many files a directory is left to per-file stat; batching only pays
+    /// off in the maildirs and object stores this backend is for
+    const MIN_BATCH_FILES: usize = 32;
+    const S_IFMT: u16 = 0o170_000;
+    const S_IFREG: u16 = 0o100_000;
+
+    pub struct UringStatx {
+        ring: Ring,
+    }
+
+    impl UringStatx {
+        /// A reader on a fresh ring, or None when the kernel can't run statx through
+        /// io_uring
+        pub fn new() -> Option<Self> {
+            let mut ring = Ring::new(RING_ENTRIES).ok()?;
+            // Kernels before 5.6 set up the ring but reject the statx opcode
+            let root = CString::new("/").ok()?;
+            let mut probe = [MaybeUninit::<Statx>::zeroed()];
+            let results = ring.statx_batch(rustix::fs::CWD, std::slice::from_ref(&root), &mut probe).ok()?;
+            (results == [0]).then_some(Self { ring })
+        }
+    }
+
+    impl BulkStat for UringStatx {
+        fn read_dir(&mut self, dir: &Path) -> std::io::Result<Vec<(OsString, EntryStat)>> {
+            // The directory entry type comes from readdir, so only regular files are
+            // queued; symlinks and oddities are left to the walker
+            let mut names = Vec::new();
+            for entry in std::fs::read_dir(dir)?.flatten() {
+                if entry.file_type().is_ok_and(|kind| kind.is_file()) {
+                    if let Ok(name) = CString::new(entry.file_name().as_bytes()) {
+                        names.push(name);
+                    }
+                }
+            }
+            if names.len() < MIN_BATCH_FILES {
+                return Ok(Vec::new());
+            }
+
+            let dir_fd = rustix::fs::open(dir, OFlags::RDONLY | OFlags::DIRECTORY | OFlags::CLOEXEC, Mode::empty())?;
+            let mut stats = vec![MaybeUninit::<Statx>::zeroed(); names.len()];
+            let mut entries = Vec::with_capacity(names.len());
+            let batch = self.ring.entries as usize;
+            for (names, stats) in names.chunks(batch).zip(stats.chunks_mut(batch)) {
+                let results = self.ring.statx_batch(dir_fd.as_fd(), names, stats)?;
+                for ((name, stat), result) in names.iter().zip(stats.iter()).zip(results) {
+                    if result != 0 {
+                        continue;
+                    }
+                    // SAFETY: the kernel filled the buffer for every statx that succeeded
+                    let stat = unsafe { stat.assume_init_ref() };
+                    if stat.stx_mode & S_IFMT != S_IFREG {
+                        continue;
+                    }
+                    let modified = u64::try_from(stat.stx_mtime.tv_sec)
+                        .ok()
+                        .map(|secs| UNIX_EPOCH + Duration::new(secs, stat.stx_mtime.tv_nsec.min(999_999_999)));
+                    entries.push((
+                        OsStr::from_bytes(name.as_bytes()).to_os_string(),
+                        EntryStat {
+                            size: stat.stx_size,
+                            modified,
+                        },
+                    ));
+                }
+            }
+            Ok(entries)
+        }
+    }
+
+    /// One mapped region of the ring
+    struct Mapping {
+        ptr: *mut u8,
+        len: usize,
+    }
+
+    impl Mapping {
+        fn new(fd: &OwnedFd, len: usize, offset: u64) -> rustix::io::Result<Self> {
+            // SAFETY: a fresh shared mapping of the ring fd at an offset the kernel defines
+            let ptr = unsafe {
+                mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    ProtFlags::READ | ProtFlags::WRITE,
+                    MapFlags::SHARED | MapFlags::POPULATE,
+                    fd,
+                    offset,
+                )
+            }?;
+            Ok(Self { ptr: ptr.cast(), len })
+        }
+
+        /// # Safety
+        /// `offset` must lie inside the mapping and be aligned for `T`
+        unsafe fn at<T>(&self, offset: u32) -> *mut T {
+            self.ptr.add(offset as usize).cast()
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: the region was mapped in Mapping::new and is unmapped once
+            unsafe {
+                let _ = munmap(self.ptr.cast(), self.len);
+            }
+        }
+    }
+
+    /// A submission and completion ring used from one thread, one batch at a time:
+    /// every batch is fully reaped before the next is queued
+    struct Ring {
+        fd: OwnedFd,
+        entries: u32,
+        sq: Mapping,
+        cq: Mapping,
+        sqes: Mapping,
+        params: io_uring_params,
+    }
+
+    // SAFETY: the mappings are owned by the ring and only touched through &mut self
+    unsafe impl Send for Ring {}
+
+    impl Ring {
+        fn new(entries: u32) -> rustix::io::Result<Self> {
+            let mut params = io_uring_params::default();
+            // SAFETY: params is a valid, zeroed io_uring_params
+            let fd = unsafe { io_uring_setup(entries, &mut params) }?;
+            let sq_len = params.sq_off.array as usize + params.sq_entries as usize * std::mem::size_of::<u32>();
+            let cq_len =
+                params.cq_off.cqes as usize + params.cq_entries as usize * std::mem::size_of::<io_uring_cqe>();
+            let sqes_len = params.sq_entries as usize * std::mem::size_of::<io_uring_sqe>();
+            Ok(Self {
+                sq: Mapping::new(&fd, sq_len, IORING_OFF_SQ_RING)?,
+                cq: Mapping::new(&fd, cq_len, IORING_OFF_CQ_RING)?,
+                sqes: Mapping::new(&fd, sqes_len, IORING_OFF_SQES)?,
+                entries: params.sq_entries,
+                fd,
+                params,
+            })
+        }
+
+        /// statx each of `names` relative to `dir`, at most `entries` at a time, and
+        /// return each call's result: 0, or a negated errno
+        fn statx_batch(
+            &mut self,
+            dir: BorrowedFd<'_>,
+            names: &[CString],
+            stats: &mut [MaybeUninit<Statx>],
+        ) -> std::io::Result<Vec<i32>> {
+            let count = names.len().min(stats.len()).min(self.entries as usize) as u32;
+            let off = self.params.sq_off;
+            // SAFETY: the offsets come from io_uring_setup and index into the mappings
+            // sized from the same parameters. Each sqe is written before the tail that
+            // publishes it, and the names and buffers outlive every sqe the kernel takes,
+            // because unsubmitted ones are taken back and submitted ones drained below
+            unsafe {
+                let sq_tail = &*self.sq.at::<AtomicU32>(off.tail);
+                let sq_mask = *self.sq.at::<u32>(off.ring_mask);
+                let array = self.sq.at::<u32>(off.array);
+                let sqes = self.sqes.ptr.cast::<io_uring_sqe>();
+                let tail = sq_tail.load(Ordering::Relaxed);
+                for i in 0..count {
+                    let slot = tail.wrapping_add(i) & sq_mask;
+                    let sqe = io_uring_sqe {
+                        opcode: IoringOp::Statx,
+                        fd: dir.as_raw_fd(),
+                        addr_or_splice_off_in: addr_or_splice_off_in_union {
+                            addr: io_uring_ptr::new(names[i as usize].as_ptr() as *mut _),
+                        },
+                        len: len_union {
+                            len: (StatxFlags::TYPE | StatxFlags::SIZE | StatxFlags::MTIME).bits(),
+                        },
+                        off_or_addr2: off_or_addr2_union {
+                            addr2: io_uring_ptr::new(stats[i as usize].as_mut_ptr().cast()),
+                        },
+                        op_flags: op_flags_union {
+                            statx_flags: AtFlags::SYMLINK_NOFOLLOW | AtFlags::STATX_DONT_SYNC,
+                        },
+                        user_data: io_uring_user_data::from_u64(u64::from(i)),
+                        ..io_uring_sqe::default()
+                    };
+                    sqes.add(slot as usize).write(sqe);
+                    *array.add(slot as usize) = slot;
+                }
+                sq_tail.store(tail.wrapping_add(count), Ordering::Release);
+            }
+
+            let mut results = vec![-Errno::CANCELED.raw_os_error(); count as usize];
+            let (mut submitted, mut reaped) = (0u32, 0u32);
+            let mut failure = None;
+            while submitted < count {
+                // SAFETY: the ring fd is live and every queued sqe is fully written
+                let entered = unsafe {
+                    io_uring_enter(&self.fd, count - submitted, 1, IoringEnterFlags::GETEVENTS)
+                };
+                match entered {
+                    Ok(n) => submitted += n,
+                    Err(Errno::INTR) => {}
+                    Err(err) => {
+                        failure = Some(err);
+                        break;
+                    }
+                }
+                reaped += self.reap(&mut results);
+            }
+            if failure.is_some() {
+                // The kernel never looked at the unsubmitted sqes; take them back so a
+                // later batch can't hand it pointers into buffers freed after we return
+                self.unqueue();
+            }
+            self.drain(submitted - reaped, &mut results);
+            match failure {
+                Some(err) => Err(err.into()),
+                None => Ok(results),
+            }
+        }
+
+        /// Pull the sq tail back to the kernel's head, dropping every queued sqe it
+        /// hasn't consumed
+        fn unqueue(&mut self) {
+            let off = self.params.sq_off;
+            // SAFETY: as in statx_batch; without SQPOLL the kernel only reads the tail
+            // inside io_uring_enter, which this thread isn't in
+            unsafe {
+                let sq_head = &*self.sq.at::<AtomicU32>(off.head);
+                let sq_tail = &*self.sq.at::<AtomicU32>(off.tail);
+                sq_tail.store(sq_head.load(Ordering::Acquire), Ordering::Release);
+            }
+        }
+
+        /// Wait until `pending` more completions have been reaped. Every submitted
+        /// statx completes on its own, so if waiting in the kernel fails this polls
+        /// the completion ring instead of giving up while sqes still point at our
+        /// buffers
+        fn drain(&mut self, mut pending: u32, results: &mut [i32]) {
+            while pending > 0 {
+                // SAFETY: the ring fd is live and nothing new is queued
+                let waited = unsafe { io_uring_enter(&self.fd, 0, pending, IoringEnterFlags::GETEVENTS) };
+                let reaped = self.reap(results);
+                if reaped == 0 && waited.is_err() {
+                    std::thread::yield_now();
+                }
+                pending = pending.saturating_sub(reaped);
+            }
+        }
+
+        /// Move every posted completion into `results`, returning how many there were
+        fn reap(&mut self, results: &mut [i32]) -> u32 {
+            let off = self.params.cq_off;
+            // SAFETY: as in statx_batch; the kernel publishes cqes before moving the tail
+            unsafe {
+                let cq_head = &*self.cq.at::<AtomicU32>(off.head);
+                let cq_tail = &*self.cq.at::<AtomicU32>(off.tail);
+                let cq_mask = *self.cq.at::<u32>(off.ring_mask);
+                let cqes = self.cq.at::<io_uring_cqe>(off.cqes);
+                let mut head = cq_head.load(Ordering::Relaxed);
+                let tail = cq_tail.load(Ordering::Acquire);
+                let mut count = 0;
+                while head != tail {
+                    let cqe = &*cqes.add((head & cq_mask) as usize);
+                    if let Some(result) = results.get_mut(cqe.user_data.u64_() as usize) {
+                        *result = cqe.res;
+                    }
+                    head = head.wrapping_add(1);
+                    count += 1;
+                }
+                cq_head.store(head, Ordering::Release);
+                count
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::AnalyzerConfig;
+
+    struct Listed(Vec<(&'static str, u64)>);
+
+    impl BulkStat for Listed {
+        fn read_dir(&mut self, _dir: &Path) -> std::io::Result<Vec<(OsString, EntryStat)>> {
+            Ok(self
+                .0
+                .iter()
+                .map(|(name, size)| (OsString::from(name), EntryStat { size: *size, modified: None }))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn cache_hands_out_each_bulk_entry_once_and_falls_back() {
+        let mut cache = StatCache::new(Box::new(Listed(vec![("a.bin", 3), ("b.bin", 4)])));
+        assert_eq!(cache.take(Path::new("/d/a.bin")).map(|stat| stat.size), Some(3));
+        assert_eq!(cache.take(Path::new("/d/a.bin")), None);
+        assert_eq!(cache.take(Path::new("/d/link")), None);
+        assert_eq!(cache.take(Path::new("/e/b.bin")).map(|stat| stat.size), Some(4));
+
+        let owners = ScanOptions {
+            analyzers: vec![AnalyzerConfig::OwnerUsage],
+            backend: ScanBackend::AttrListBulk,
+            ..ScanOptions::default()
+        };
+        assert_eq!(ScanBackend::resolve(&owners), ScanBackend::Walker);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn io_uring_sizes_match_stat() {
+        let Some(mut reader) = bulk_stat(ScanBackend::IoUring, &ScanLimits::default()) else {
+            // Kernel without io_uring statx; scans use the walker
+            return;
+        };
+        let dir = tempfile::tempdir().expect("tempdir");
+        for i in 0..300u64 {
+            std::fs::write(dir.path().join(format!("msg{i}")), vec![0u8; i as usize]).expect("write");
+        }
+        std::fs::create_dir(dir.path().join("sub")).expect("mkdir");
+        let entries = reader.read_dir(dir.path()).expect("read_dir");
+        assert_eq!(entries.len(), 300);
+        for (name, stat) in entries {
+            let metadata = std::fs::metadata(dir.path().join(&name)).expect("metadata");
+            assert_eq!(stat, EntryStat::from(&metadata));
+        }
+    }
+}
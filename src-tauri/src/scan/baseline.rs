@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::scan::model::NodeKind;
+use crate::scan::tree::ScanTree;
+
+pub const EVENT_BASELINE_DRIFT: &str = "baseline://drift";
+const BASELINES_FILE: &str = "baselines.json";
+/// Directories smaller than this are left out of a baseline to keep it small; when one
+/// later grows, its growth is counted from zero
+const BASELINE_MIN_DIR_BYTES: u64 = 10 * 1024 * 1024;
+const GB: u64 = 1024 * 1024 * 1024;
+
+/// How much a directory must grow past its baseline size to be reported
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DriftThresholds {
+    #[serde(default = "default_min_growth_bytes")]
+    pub min_growth_bytes: u64,
+    /// Growth relative to the baseline size; directories new since the baseline
+    /// always pass
+    #[serde(default = "default_min_growth_percent")]
+    pub min_growth_percent: u32,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self {
+            min_growth_bytes: default_min_growth_bytes(),
+            min_growth_percent: default_min_growth_percent(),
+        }
+    }
+}
+
+fn default_min_growth_bytes() -> u64 {
+    GB
+}
+
+fn default_min_growth_percent() -> u32 {
+    25
+}
+
+/// Directory sizes of one scan, kept as the reference for a root
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub root_path: String,
+    pub scan_id: String,
+    pub created_at: u64,
+    pub total_bytes: u64,
+    pub dirs: HashMap<String, u64>,
+}
+
+/// A baseline without its directory sizes, for listing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaselineInfo {
+    pub root_path: String,
+    pub scan_id: String,
+    pub created_at: u64,
+    pub total_bytes: u64,
+    pub dir_count: usize,
+}
+
+impl Baseline {
+    pub fn capture(scan_id: &str, tree: &ScanTree) -> Result<Self, String> {
+        let root = tree.root().ok_or_else(|| "Scan has no root".to_string())?;
+        let dirs = tree
+            .nodes()
+            .filter(|node| node.kind == NodeKind::Dir && node.size_bytes >= BASELINE_MIN_DIR_BYTES)
+            .map(|node| (node.path.clone(), node.size_bytes))
+            .collect();
+        Ok(Self {
+            root_path: root.path.clone(),
+            scan_id: scan_id.to_string(),
+            created_at: now_millis(),
+            total_bytes: root.size_bytes,
+            dirs,
+        })
+    }
+
+    pub fn info(&self) -> BaselineInfo {
+        BaselineInfo {
+            root_path: self.root_path.clone(),
+            scan_id: self.scan_id.clone(),
+            created_at: self.created_at,
+            total_bytes: self.total_bytes,
+            dir_count: self.dirs.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DirectoryDrift {
+    pub path: String,
+    pub baseline_bytes: u64,
+    pub current_bytes: u64,
+    pub growth_bytes: u64,
+}
+
+/// Directories of a scan that grew past the thresholds since the baseline
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub root_path: String,
+    pub scan_id: String,
+    pub baseline_scan_id: String,
+    pub baseline_created_at: u64,
+    pub baseline_total_bytes: u64,
+    pub current_total_bytes: u64,
+    pub drifts: Vec<DirectoryDrift>,
+}
+
+/// Compare a scan with the baseline of its root. A directory is only reported when its
+/// growth isn't mostly explained by a subdirectory that is reported itself, so one
+/// runaway log folder shows up once rather than with every ancestor.
+pub fn compare(baseline: &Baseline, scan_id: &str, tree: &ScanTree, thresholds: &DriftThresholds) -> DriftReport {
+    let mut drifts: Vec<DirectoryDrift> = tree
+        .nodes()
+        .filter(|node| node.kind == NodeKind::Dir)
+        .filter_map(|node| {
+            let baseline_bytes = baseline.dirs.get(&node.path).copied().unwrap_or(0);
+            let growth_bytes = node.size_bytes.checked_sub(baseline_bytes)?;
+            let percent_ok = baseline_bytes == 0
+                || growth_bytes.saturating_mul(100) >= baseline_bytes.saturating_mul(u64::from(thresholds.min_growth_percent));
+            (growth_bytes >= thresholds.min_growth_bytes && percent_ok).then(|| DirectoryDrift {
+                path: node.path.clone(),
+                baseline_bytes,
+                current_bytes: node.size_bytes,
+                growth_bytes,
+            })
+        })
+        .collect();
+    let explained: Vec<bool> = drifts
+        .iter()
+        .map(|outer| {
+            drifts.iter().any(|inner| {
+                is_descendant(&inner.path, &outer.path) && inner.growth_bytes.saturating_mul(2) >= outer.growth_bytes
+            })
+        })
+        .collect();
+    let mut explained = explained.into_iter();
+    drifts.retain(|_| !explained.next().unwrap_or(false));
+    drifts.sort_by_key(|drift| std::cmp::Reverse(drift.growth_bytes));
+    DriftReport {
+        root_path: baseline.root_path.clone(),
+        scan_id: scan_id.to_string(),
+        baseline_scan_id: baseline.scan_id.clone(),
+        baseline_created_at: baseline.created_at,
+        baseline_total_bytes: baseline.total_bytes,
+        current_total_bytes: tree.root().map(|root| root.size_bytes).unwrap_or(0),
+        drifts,
+    }
+}
+
+pub fn is_descendant(path: &str, ancestor: &str) -> bool {
+    path.len() > ancestor.len()
+        && path.starts_with(ancestor)
+        && (ancestor.ends_with(['/', '\\']) || path[ancestor.len()..].starts_with(['/', '\\']))
+}
+
+/// After a scheduled scan, diff it against its root's baseline and emit
+/// `baseline://drift` when anything grew past the thresholds
+pub fn check_scheduled_scan(
+    app_handle: &AppHandle,
+    data_dir: Option<&Path>,
+    thresholds: &DriftThresholds,
+    scan_id: &str,
+    tree: &ScanTree,
+) {
+    let Some(dir) = data_dir else {
+        return;
+    };
+    let Some(root) = tree.root() else {
+        return;
+    };
+    let Some(baseline) = load_baselines(dir).into_iter().find(|b| b.root_path == root.path) else {
+        return;
+    };
+    let report = compare(&baseline, scan_id, tree, thresholds);
+    if !report.drifts.is_empty() {
+        let _ = app_handle.emit(EVENT_BASELINE_DRIFT, report);
+    }
+}
+
+pub fn load_baselines(dir: &Path) -> Vec<Baseline> {
+    fs::read_to_string(dir.join(BASELINES_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the baselines, writing to a temp file first so a crash can't truncate them
+pub fn save_baselines(dir: &Path, baselines: &[Baseline]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string(baselines).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", BASELINES_FILE));
+    fs::write(&temp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(BASELINES_FILE)).map_err(|e| e.to_string())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::{NodeId, TreeNode};
+
+    fn dir(id: NodeId, parent: Option<NodeId>, path: &str, size: u64, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind: NodeKind::Dir,
+            size_bytes: size,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn reports_the_directory_that_grew_not_its_ancestors() {
+        let nodes = vec![
+            dir(1, None, "/srv", 9 * GB, vec![2, 4]),
+            dir(2, Some(1), "/srv/app", 8 * GB, vec![3]),
+            dir(3, Some(2), "/srv/app/logs", 7 * GB, vec![]),
+            dir(4, Some(1), "/srv/data", GB, vec![]),
+        ];
+        let paths = nodes.iter().map(|n| (n.path.clone(), n.id)).collect();
+        let tree = ScanTree::new(1, nodes.into_iter().map(|n| (n.id, n)).collect(), paths);
+        let baseline = Baseline {
+            root_path: "/srv".to_string(),
+            scan_id: "base".to_string(),
+            created_at: 0,
+            total_bytes: 2 * GB,
+            dirs: HashMap::from([
+                ("/srv".to_string(), 2 * GB),
+                ("/srv/app".to_string(), GB),
+                ("/srv/data".to_string(), GB),
+            ]),
+        };
+
+        let report = compare(&baseline, "now", &tree, &DriftThresholds::default());
+        assert_eq!(report.drifts.len(), 1);
+        assert_eq!(report.drifts[0].path, "/srv/app/logs");
+        assert_eq!(report.drifts[0].baseline_bytes, 0);
+        assert_eq!(report.drifts[0].growth_bytes, 7 * GB);
+    }
+}
@@ -0,0 +1,139 @@
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::scan::backend::{bulk_stat, EntryStat, ScanBackend, StatCache};
+use crate::scan::limits::ScanLimits;
+
+/// Entries each backend walks; enough to separate the backends without walking a
+/// whole drive several times over
+pub const BENCHMARK_SAMPLE_ENTRIES: u64 = 50_000;
+
+/// How one backend did over the sample
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackendTiming {
+    pub backend: ScanBackend,
+    pub entries: u64,
+    pub files: u64,
+    pub total_bytes: u64,
+    pub wall_ms: u64,
+    pub entries_per_sec: f64,
+    /// Why the backend was ruled out: it couldn't run here, or it sized the sample
+    /// differently from the walker
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanBenchmark {
+    pub path: String,
+    pub timings: Vec<BackendTiming>,
+    /// The fastest backend that agreed with the walker, now the default for scans
+    /// that ask for Auto
+    pub best: Option<ScanBackend>,
+    pub previous_default: ScanBackend,
+}
+
+/// Time each of `backends` over the first `sample` entries below `root`. An untimed
+/// walk runs first so every backend meets the same warm directory cache, and each
+/// backend's byte total is checked against the walker's.
+pub fn run_benchmark(root: &Path, backends: &[ScanBackend], sample: u64) -> Vec<BackendTiming> {
+    walk_sample(root, ScanBackend::Walker, sample);
+    let mut timings: Vec<BackendTiming> = backends
+        .iter()
+        .map(|backend| walk_sample(root, *backend, sample))
+        .collect();
+    let reference = timings
+        .iter()
+        .find(|timing| timing.backend == ScanBackend::Walker && timing.error.is_none())
+        .map(|timing| (timing.files, timing.total_bytes));
+    if let Some((files, bytes)) = reference {
+        for timing in timings.iter_mut().filter(|timing| timing.error.is_none()) {
+            if (timing.files, timing.total_bytes) != (files, bytes) {
+                timing.error = Some(format!(
+                    "counted {} files and {} bytes where the walker counted {} and {}",
+                    timing.files, timing.total_bytes, files, bytes
+                ));
+            }
+        }
+    }
+    timings
+}
+
+/// The fastest backend that ran cleanly
+pub fn best_backend(timings: &[BackendTiming]) -> Option<ScanBackend> {
+    timings
+        .iter()
+        .filter(|timing| timing.error.is_none() && timing.entries > 0)
+        .max_by(|a, b| a.entries_per_sec.total_cmp(&b.entries_per_sec))
+        .map(|timing| timing.backend)
+}
+
+fn walk_sample(root: &Path, backend: ScanBackend, sample: u64) -> BackendTiming {
+    let mut timing = BackendTiming {
+        backend,
+        entries: 0,
+        files: 0,
+        total_bytes: 0,
+        wall_ms: 0,
+        entries_per_sec: 0.0,
+        error: None,
+    };
+    let mut stat_cache = match backend {
+        ScanBackend::Walker => None,
+        other => match bulk_stat(other, &ScanLimits::default()) {
+            Some(reader) => Some(StatCache::new(reader)),
+            None => {
+                timing.error = Some("not available on this system".to_string());
+                return timing;
+            }
+        },
+    };
+
+    let mut builder = WalkBuilder::new(root);
+    builder.standard_filters(false).hidden(false).follow_links(false);
+    let started = Instant::now();
+    for entry in builder.build().flatten().take(sample as usize) {
+        timing.entries += 1;
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let stat = stat_cache
+            .as_mut()
+            .and_then(|cache| cache.take(entry.path()))
+            .or_else(|| entry.metadata().ok().map(|metadata| EntryStat::from(&metadata)));
+        if let Some(stat) = stat {
+            timing.files += 1;
+            timing.total_bytes += stat.size;
+        }
+    }
+    let elapsed = started.elapsed();
+    timing.wall_ms = elapsed.as_millis() as u64;
+    timing.entries_per_sec = timing.entries as f64 / elapsed.as_secs_f64().max(1e-6);
+    timing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_available_backend_agrees_with_the_walker() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for sub in ["a", "b"] {
+            std::fs::create_dir(dir.path().join(sub)).expect("mkdir");
+            for i in 0..80u64 {
+                std::fs::write(dir.path().join(sub).join(format!("f{i}")), vec![1u8; i as usize]).expect("write");
+            }
+        }
+        let timings = run_benchmark(dir.path(), &ScanBackend::available(), BENCHMARK_SAMPLE_ENTRIES);
+        assert_eq!(timings.len(), ScanBackend::available().len());
+        for timing in &timings {
+            assert_eq!(timing.error, None, "{:?}", timing.backend);
+            assert_eq!((timing.entries, timing.files), (163, 160));
+            assert_eq!(timing.total_bytes, 2 * (0..80).sum::<u64>());
+        }
+        assert!(best_backend(&timings).is_some());
+        assert_eq!(best_backend(&[]), None);
+    }
+}
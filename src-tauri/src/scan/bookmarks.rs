@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+use crate::scan::engine::{run_scan, ScanError};
+use crate::scan::model::{ScanOptions, ScanResult};
+use crate::scan::tree::ScanTree;
+
+/// Largest children listed in a bookmark summary
+const TOP_CHILDREN: usize = 5;
+
+/// A folder the user tracks between full scans
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Bookmark {
+    pub path: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub added_at: u64,
+    /// Size found by the previous rescan, to show growth
+    #[serde(default)]
+    pub last_bytes: Option<u64>,
+    #[serde(default)]
+    pub last_scanned_at: Option<u64>,
+}
+
+impl Bookmark {
+    pub fn new(path: String, label: Option<String>) -> Self {
+        Self {
+            path,
+            label,
+            added_at: now_millis(),
+            last_bytes: None,
+            last_scanned_at: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookmarkChild {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// Compact result of rescanning one bookmark
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookmarkSummary {
+    pub path: String,
+    pub label: Option<String>,
+    pub scanned_at: u64,
+    pub total_bytes: u64,
+    pub total_files: u64,
+    pub total_dirs: u64,
+    /// Change since the previous rescan; None on the first one
+    pub delta_bytes: Option<i64>,
+    pub top_children: Vec<BookmarkChild>,
+    /// Set when the folder couldn't be scanned; the totals are then zero
+    pub error: Option<String>,
+}
+
+/// Scan one bookmarked folder without events and summarize it
+pub fn rescan(bookmark: &Bookmark) -> BookmarkSummary {
+    let outcome = run_scan(
+        None,
+        Uuid::new_v4().to_string(),
+        bookmark.path.clone(),
+        ScanOptions::default(),
+        Arc::new(AtomicBool::new(false)),
+    );
+    match outcome {
+        Ok((result, tree)) => summarize(bookmark, &result, &tree),
+        Err(e) => {
+            let error = match e {
+                ScanError::Canceled(_) => "Scan canceled".to_string(),
+                ScanError::Failed(message) => message,
+                ScanError::RootLost(path) => format!("{} is no longer available", path),
+            };
+            BookmarkSummary {
+                path: bookmark.path.clone(),
+                label: bookmark.label.clone(),
+                scanned_at: now_millis(),
+                total_bytes: 0,
+                total_files: 0,
+                total_dirs: 0,
+                delta_bytes: None,
+                top_children: Vec::new(),
+                error: Some(error),
+            }
+        }
+    }
+}
+
+fn summarize(bookmark: &Bookmark, result: &ScanResult, tree: &ScanTree) -> BookmarkSummary {
+    let mut top_children: Vec<BookmarkChild> = tree
+        .root()
+        .map(|root| {
+            root.children
+                .iter()
+                .filter_map(|id| tree.get(*id))
+                .map(|child| BookmarkChild {
+                    name: child.name.clone(),
+                    size_bytes: child.size_bytes,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    top_children.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.name.cmp(&b.name)));
+    top_children.truncate(TOP_CHILDREN);
+    BookmarkSummary {
+        path: bookmark.path.clone(),
+        label: bookmark.label.clone(),
+        scanned_at: now_millis(),
+        total_bytes: result.total_bytes,
+        total_files: result.total_files,
+        total_dirs: result.total_dirs,
+        delta_bytes: bookmark
+            .last_bytes
+            .map(|last| result.total_bytes as i64 - last as i64),
+        top_children,
+        error: None,
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn summary_lists_largest_children_and_growth() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("captures")).expect("create dir");
+        write(root.join("captures").join("clip.mp4"), vec![0u8; 30]).expect("write clip");
+        write(root.join("notes.txt"), vec![0u8; 10]).expect("write notes");
+
+        let mut bookmark = Bookmark::new(root.to_string_lossy().to_string(), Some("Hot".to_string()));
+        bookmark.last_bytes = Some(50);
+        let summary = rescan(&bookmark);
+
+        assert!(summary.error.is_none());
+        assert_eq!(summary.total_bytes, 40);
+        assert_eq!(summary.delta_bytes, Some(-10));
+        assert_eq!(summary.top_children[0].name, "captures");
+        assert_eq!(summary.top_children[1].size_bytes, 10);
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, BrowserProfileUsage, BrowserStorage, BrowserStorageKind};
+
+/// Folder sequences that lead to the directory holding a browser's profiles, with the
+/// browser's name. The component right after the sequence is the profile.
+const PROFILE_ROOTS: &[(&[&str], &str)] = &[
+    (&["google", "chrome", "user data"], "Chrome"),
+    (&["microsoft", "edge", "user data"], "Edge"),
+    (&["bravesoftware", "brave-browser", "user data"], "Brave"),
+    (&["chromium", "user data"], "Chromium"),
+    (&["application support", "google", "chrome"], "Chrome"),
+    (&["application support", "microsoft edge"], "Edge"),
+    (&[".config", "google-chrome"], "Chrome"),
+    (&[".config", "microsoft-edge"], "Edge"),
+    (&[".config", "chromium"], "Chromium"),
+    (&[".cache", "google-chrome"], "Chrome"),
+    (&[".cache", "microsoft-edge"], "Edge"),
+    (&[".cache", "chromium"], "Chromium"),
+    (&["firefox", "profiles"], "Firefox"),
+    (&[".mozilla", "firefox"], "Firefox"),
+    (&[".cache", "mozilla", "firefox"], "Firefox"),
+];
+
+/// Chromium folders beside the profiles, shared by all of them
+const SHARED_PROFILE: &str = "(shared)";
+
+/// Breaks browser profiles down into cache, IndexedDB, service worker, history, and
+/// other storage, so a large AppData folder can be traced to what is safe to clear
+#[derive(Default)]
+pub struct BrowserProfilesAnalyzer {
+    profiles: HashMap<(&'static str, String), ProfileTotals>,
+}
+
+#[derive(Default)]
+struct ProfileTotals {
+    roots: BTreeSet<String>,
+    storage: HashMap<BrowserStorageKind, (u64, u64, BTreeSet<String>)>,
+}
+
+impl Analyzer for BrowserProfilesAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let Some(location) = locate(Path::new(&file.node.path)) else {
+            return;
+        };
+        let profile = self.profiles.entry((location.browser, location.profile)).or_default();
+        profile.roots.insert(location.root.to_string_lossy().to_string());
+        let (bytes, files, folders) = profile.storage.entry(location.kind).or_default();
+        *bytes += file.node.size_bytes;
+        *files += 1;
+        if let Some(folder) = location.folder {
+            folders.insert(folder.to_string_lossy().to_string());
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut profiles: Vec<BrowserProfileUsage> = self
+            .profiles
+            .into_iter()
+            .map(|((browser, profile), totals)| {
+                let mut storage: Vec<BrowserStorage> = totals
+                    .storage
+                    .into_iter()
+                    .map(|(kind, (bytes, files, folders))| BrowserStorage {
+                        kind,
+                        bytes,
+                        files,
+                        safe_to_clear: kind.safe_to_clear(),
+                        paths: folders.into_iter().collect(),
+                    })
+                    .collect();
+                storage.sort_by_key(|entry| std::cmp::Reverse(entry.bytes));
+                BrowserProfileUsage {
+                    browser: browser.to_string(),
+                    profile,
+                    paths: totals.roots.into_iter().collect(),
+                    total_bytes: storage.iter().map(|entry| entry.bytes).sum(),
+                    clearable_bytes: storage.iter().filter(|entry| entry.safe_to_clear).map(|entry| entry.bytes).sum(),
+                    storage,
+                }
+            })
+            .collect();
+        profiles.sort_by_key(|profile| std::cmp::Reverse(profile.total_bytes));
+        AnalyzerReport::BrowserProfiles { profiles }
+    }
+}
+
+struct Location {
+    browser: &'static str,
+    profile: String,
+    /// Profile directory (or the shared folder for Chromium's profile-wide data)
+    root: PathBuf,
+    kind: BrowserStorageKind,
+    /// The folder that makes up the storage kind, e.g. `.../Default/Cache`
+    folder: Option<PathBuf>,
+}
+
+/// Which browser profile and storage kind a file belongs to, if any
+fn locate(path: &Path) -> Option<Location> {
+    let components: Vec<Component> = path.components().collect();
+    let lower: Vec<String> = components
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    let (end, browser) = PROFILE_ROOTS.iter().find_map(|(sequence, browser)| {
+        lower
+            .windows(sequence.len())
+            .position(|window| window.iter().zip(sequence.iter()).all(|(a, b)| a == b))
+            .map(|start| (start + sequence.len(), *browser))
+    })?;
+    // The file must sit inside a folder below the profile root
+    if lower.len() < end + 2 {
+        return None;
+    }
+    let name = &lower[end];
+    let is_profile = browser == "Firefox"
+        || name == "default"
+        || name.starts_with("profile ")
+        || name == "guest profile"
+        || name == "system profile";
+    let profile_len = if is_profile { end + 1 } else { end };
+    let root: PathBuf = components[..profile_len].iter().collect();
+    let folders = &lower[profile_len..lower.len() - 1];
+    let file_name = &lower[lower.len() - 1];
+    let (kind, depth) = if browser == "Firefox" {
+        firefox_kind(folders, file_name)
+    } else {
+        chromium_kind(folders, file_name)
+    };
+    let folder = (depth > 0).then(|| components[..profile_len + depth].iter().collect());
+    Some(Location {
+        browser,
+        profile: if is_profile {
+            components[end].as_os_str().to_string_lossy().to_string()
+        } else {
+            SHARED_PROFILE.to_string()
+        },
+        root,
+        kind,
+        folder,
+    })
+}
+
+/// Storage kind of a file from its name and the folders between its Chromium profile
+/// and itself, with how many of those folders make up the kind's directory (0 for
+/// loose files)
+fn chromium_kind(folders: &[String], file_name: &str) -> (BrowserStorageKind, usize) {
+    let Some(first) = folders.first() else {
+        let history = ["history", "visited links", "top sites", "favicons", "shortcuts", "network action predictor"]
+            .iter()
+            .any(|name| file_name == *name || file_name.strip_prefix(name).is_some_and(|rest| rest == "-journal"));
+        let kind = if history { BrowserStorageKind::History } else { BrowserStorageKind::Other };
+        return (kind, 0);
+    };
+    let kind = match first.as_str() {
+        "cache" | "code cache" | "gpucache" | "dawncache" | "dawngraphitecache" | "dawnwebgpucache"
+        | "grshadercache" | "shadercache" | "graphitedawncache" | "media cache" => BrowserStorageKind::Cache,
+        "service worker" => BrowserStorageKind::ServiceWorker,
+        "indexeddb" => BrowserStorageKind::IndexedDb,
+        "local storage" | "session storage" | "storage" | "file system" | "blob_storage" | "databases"
+        | "shared_proto_db" => BrowserStorageKind::SiteData,
+        "extensions" | "local extension settings" | "extension state" => BrowserStorageKind::Extensions,
+        _ => return (BrowserStorageKind::Other, 0),
+    };
+    (kind, 1)
+}
+
+fn firefox_kind(folders: &[String], file_name: &str) -> (BrowserStorageKind, usize) {
+    match folders {
+        [] if file_name.starts_with("places.sqlite") || file_name.starts_with("favicons.sqlite") => {
+            (BrowserStorageKind::History, 0)
+        }
+        [] => (BrowserStorageKind::Other, 0),
+        [first, ..] if matches!(first.as_str(), "cache2" | "startupcache" | "thumbnails" | "shader-cache" | "jumplistcache") => {
+            (BrowserStorageKind::Cache, 1)
+        }
+        // storage/default/<origin>/{idb,cache,ls,...}
+        [storage, scope, _origin, kind, ..] if storage == "storage" && scope != "permanent" => match kind.as_str() {
+            "idb" => (BrowserStorageKind::IndexedDb, 4),
+            "cache" => (BrowserStorageKind::ServiceWorker, 4),
+            _ => (BrowserStorageKind::SiteData, 1),
+        },
+        [first, ..] if first == "storage" => (BrowserStorageKind::SiteData, 1),
+        [first, ..] if first == "extensions" || first == "browser-extension-data" => (BrowserStorageKind::Extensions, 1),
+        _ => (BrowserStorageKind::Other, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_files_to_profiles_and_storage() {
+        let chrome = locate(Path::new(
+            "/Users/me/Library/Application Support/Google/Chrome/Profile 2/Service Worker/CacheStorage/ab/0",
+        ))
+        .expect("chrome file");
+        assert_eq!(chrome.browser, "Chrome");
+        assert_eq!(chrome.profile, "Profile 2");
+        assert_eq!(chrome.kind, BrowserStorageKind::ServiceWorker);
+        assert!(chrome.folder.expect("folder").ends_with("Profile 2/Service Worker"));
+
+        let shared = locate(Path::new("C:/Users/me/AppData/Local/Google/Chrome/User Data/ShaderCache/data_1"))
+            .expect("shared file");
+        assert_eq!(shared.profile, SHARED_PROFILE);
+        assert_eq!(shared.kind, BrowserStorageKind::Cache);
+
+        let idb = locate(Path::new(
+            "/home/me/.mozilla/firefox/x1y2.default-release/storage/default/https+++mail.example.com/idb/1.sqlite",
+        ))
+        .expect("firefox file");
+        assert_eq!(idb.browser, "Firefox");
+        assert_eq!(idb.kind, BrowserStorageKind::IndexedDb);
+        let history = locate(Path::new("/home/me/.mozilla/firefox/x1y2.default-release/places.sqlite-wal"))
+            .expect("firefox history");
+        assert_eq!(history.kind, BrowserStorageKind::History);
+        assert!(history.folder.is_none());
+
+        assert!(locate(Path::new("/home/me/.mozilla/firefox/profiles.ini")).is_none());
+    }
+}
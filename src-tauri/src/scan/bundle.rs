@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::backend::ScanBackend;
+use crate::scan::rules::{pack_files, RulePackReport};
+use crate::scan::settings::Settings;
+
+/// Version written by export_settings; bundles from a newer version are refused
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Every setting plus the user rule packs, in one file that can be carried to another
+/// machine or shared as a team cleanup policy. Policies and the agent hold the
+/// schedules, and bookmarks and hooks travel with the rest of the settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: u64,
+    pub settings: Settings,
+    #[serde(default)]
+    pub rule_packs: Vec<BundledRulePack>,
+}
+
+/// A user rule pack file, kept as written so it re-validates on the importing side
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundledRulePack {
+    pub file_name: String,
+    pub contents: serde_json::Value,
+}
+
+/// What an import changed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsImport {
+    pub exported_at: u64,
+    pub policies: usize,
+    pub bookmarks: usize,
+    /// Validation of every rule pack after the import, user packs and built-in alike
+    pub rule_packs: Vec<RulePackReport>,
+}
+
+impl SettingsBundle {
+    /// A bundle of `settings` and the packs in `rules_dir`, leaving out what only
+    /// describes this machine: the benchmarked scan backend and when scheduled scans
+    /// last ran
+    pub fn new(settings: &Settings, rules_dir: Option<&Path>) -> Result<Self, String> {
+        let mut settings = settings.clone();
+        settings.scan_backend = ScanBackend::Auto;
+        if let Some(schedule) = settings.agent.scheduled_scan.as_mut() {
+            schedule.last_run_at = None;
+        }
+        let mut rule_packs = Vec::new();
+        for path in rules_dir.map(pack_files).unwrap_or_default() {
+            let contents = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let contents = serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            rule_packs.push(BundledRulePack { file_name, contents });
+        }
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            exported_at: now_millis(),
+            settings,
+            rule_packs,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let bundle: SettingsBundle =
+            serde_json::from_str(&contents).map_err(|e| format!("Not a settings bundle: {}", e))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!(
+                "This bundle was exported by a newer version (format {}); update the app to import it",
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+
+    /// The settings to adopt in place of `current`, keeping this machine's own values
+    /// for what the bundle leaves out
+    pub fn merged_settings(&self, current: &Settings) -> Settings {
+        let mut settings = self.settings.clone();
+        settings.scan_backend = current.scan_backend;
+        if let (Some(schedule), Some(old)) = (settings.agent.scheduled_scan.as_mut(), &current.agent.scheduled_scan) {
+            if schedule.root_path == old.root_path && schedule.every_hours == old.every_hours {
+                schedule.last_run_at = old.last_run_at;
+            }
+        }
+        settings
+    }
+
+    /// Write the bundled rule packs into `rules_dir`, replacing packs of the same name
+    pub fn install_rule_packs(&self, rules_dir: &Path) -> Result<(), String> {
+        if self.rule_packs.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(rules_dir).map_err(|e| e.to_string())?;
+        for pack in &self.rule_packs {
+            // Only a bare `*.json` name, so a bundle can't write outside the rules dir
+            let name = Path::new(&pack.file_name);
+            let is_bare = name.file_name().is_some_and(|file| file == name.as_os_str());
+            if !is_bare || !name.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+                return Err(format!("Invalid rule pack name in bundle: {}", pack.file_name));
+            }
+            let contents = serde_json::to_string_pretty(&pack.contents).map_err(|e| e.to_string())?;
+            fs::write(rules_dir.join(name), contents).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::agent::ScheduledScan;
+    use tempfile::tempdir;
+
+    #[test]
+    fn bundles_round_trip_without_machine_state() {
+        let temp = tempdir().expect("tempdir");
+        let rules = temp.path().join("rules");
+        fs::create_dir(&rules).expect("mkdir");
+        fs::write(rules.join("team.json"), r#"{"name": "Team", "rules": []}"#).expect("write");
+
+        let mut settings = Settings {
+            developer_mode: true,
+            scan_backend: ScanBackend::Walker,
+            ..Settings::default()
+        };
+        settings.agent.scheduled_scan = Some(ScheduledScan {
+            root_path: "/data".to_string(),
+            every_hours: 24,
+            options: Default::default(),
+            last_run_at: Some(7),
+        });
+        let bundle = SettingsBundle::new(&settings, Some(&rules)).expect("bundle");
+        let file = temp.path().join("bundle.json");
+        bundle.save(&file).expect("save");
+
+        let loaded = SettingsBundle::load(&file).expect("load");
+        assert_eq!(loaded.settings.scan_backend, ScanBackend::Auto);
+        let merged = loaded.merged_settings(&settings);
+        assert!(merged.developer_mode);
+        assert_eq!(merged.scan_backend, ScanBackend::Walker);
+        assert_eq!(merged.agent.scheduled_scan.and_then(|schedule| schedule.last_run_at), Some(7));
+
+        let other = temp.path().join("other");
+        loaded.install_rule_packs(&other).expect("install");
+        assert!(other.join("team.json").exists());
+
+        let mut hostile = loaded;
+        hostile.rule_packs[0].file_name = "../escape.json".to_string();
+        assert!(hostile.install_rule_packs(&other).is_err());
+    }
+}
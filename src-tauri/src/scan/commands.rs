@@ -1,51 +1,328 @@
+use std::panic::{self, AssertUnwindSafe};
 use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::fs;
 
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State, WebviewWindow};
+use tauri_plugin_autostart::ManagerExt;
 use uuid::Uuid;
 
-use crate::scan::engine::{run_scan, ScanError};
+use crate::scan::engine::{is_case_insensitive, node_to_delta, normalize_root, run_scan, ScanError, MAX_PARTIAL_BATCH};
 use crate::scan::events::{
-    emit_canceled, emit_error, emit_finished, emit_started, CanceledPayload, ErrorPayload,
-    FinishedPayload, StartedPayload,
+    emit_canceled, emit_error, emit_finished, emit_partial_tree, emit_root_lost, emit_started, CanceledPayload,
+    ErrorPayload, EventRoute, FinishedPayload, PartialTreePayload, RootLostPayload, ScanEmitter, StartedPayload,
 };
-use crate::scan::model::{RootEntry, ScanHandle, ScanOptions, ScanSummary};
+use crate::scan::analyzer::{age_histogram_view, size_distribution};
+use crate::scan::model::{AgeHistogramView, AnalyzerReport, AppDataUsage, BrowserProfileUsage, ExecutableFile, HighEntropyFile, NodeId, NodeKind, OverlapDecision, OwnerUsage, RootEntry, ScanHandle, ScanOptions, ScanSummary, ShaderCacheUsage, SizeBucket, TreeNode, VmDiskImage};
 use crate::scan::state::{AppState, ScanState};
 use crate::scan::delete::{
-    SafetyLevel, DeleteResult, FileInfo, 
-    get_safety_level, get_file_info, smart_delete_file,
+    SafetyLevel, DeleteResult, ErrorPolicy, FileInfo, PathTotals, 
+    get_safety_level, get_file_info, smart_delete_file, delete_path_direct, calculate_dir_size, measure_path,
+    DeleteMode,
     emit_deleted, emit_delete_failed, DeletedPayload, DeleteFailedPayload,
 };
+use crate::scan::anonymize::Anonymization;
+use crate::scan::archive::{
+    self, extract_entries, list_archive, ArchiveDeleteResult, ArchiveFormat, ArchiveListing, ArchivePhase,
+    ArchiveProgressPayload, ExtractResult, EVENT_ARCHIVE_PROGRESS,
+};
+use crate::scan::compress::{self, CompressibilityEstimate, CompressionResult};
+use crate::scan::cow::{cow_usage, CowUsage};
+use crate::scan::crashdumps::{self, CrashDumpReport};
+use crate::scan::csc::{self, OfflineFilesCache};
+use crate::scan::documents::{find_large_documents, DocumentGroup, DEFAULT_DOCUMENT_MIN_BYTES};
+use crate::scan::export::{export_tree, ExportFormat, ExportResult};
+use crate::scan::filelist::{self, FileListFormat};
+use crate::scan::policy::{self, Policy, PolicyRun};
+use crate::scan::query::{self, NodeFilter, NodeMatch, NodeQuery, Weight, DEFAULT_QUERY_LIMIT};
+use crate::scan::reconcile::{self, RootBreakdown, SpaceReconciliation};
+use crate::scan::recycle::{self, TrashEntry, TrashFallback, TrashFit, TrashPlan};
+use crate::scan::report::{node_report, ReportFormat};
+use crate::scan::backend::ScanBackend;
+use crate::scan::benchmark::{self, ScanBenchmark, BENCHMARK_SAMPLE_ENTRIES};
+use crate::scan::hashcache::HashCacheStats;
+use crate::scan::hints;
+use crate::scan::reuse::{self, CoveringScan, ResumeSeed, ScanReuse, DEFAULT_REUSE_MAX_AGE_SECS};
+use crate::scan::roots;
+use crate::scan::rules::{self, RulePackReport};
+use crate::scan::shadow::{self, ShadowStorage};
+use crate::scan::shell::{self, LaunchRequest};
+use crate::scan::similar::{self, SimilarFile};
+use crate::scan::tags::{Annotation, TaggedNode};
+use crate::scan::throttle::{DeleteThrottle, Throttle};
+use crate::scan::vmdisks;
+use crate::scan::volumes::{list_root_entries, volume_capabilities, volume_space};
+use crate::scan::watch::{self, NodeWatch, EVENT_SIZE_CHANGED, MIN_WATCH_INTERVAL_SECS};
+use crate::scan::winstores::{self, StoreCleanupResult, WindowsStoresReport};
+use crate::scan::winsxs::{
+    self, CleanupFinishedPayload, CleanupProgressPayload, ComponentStoreAnalysis, COMPONENT_STORE,
+    EVENT_CLEANUP_FINISHED, EVENT_CLEANUP_PROGRESS,
+};
+use crate::scan::acl::{fix_permissions, is_elevated};
+use crate::scan::agent::{self, AgentSettings, AgentStatus};
+use crate::scan::growth::{self, GrowthRange, GrowthReport};
+use crate::scan::baseline::{self, Baseline, BaselineInfo, DriftReport, DriftThresholds};
+use crate::scan::bookmarks::{self, Bookmark, BookmarkSummary};
+use crate::scan::feasibility::{self, FeasibilityReport};
+use crate::scan::dashboard::{self, DashboardSummary};
+use crate::scan::extstats::{selection_stats, SelectionStats};
+use crate::scan::scandefaults::{self, DefaultOptions};
+use crate::scan::pattern::{classify_matches, descendant_paths, find_matches, PathPattern, PatternDelete, PatternMatch};
+use crate::scan::deletetime::{self, DeletePreview, DEFAULT_WIPE_PASSES};
+use crate::scan::home::{self, HomeOverview};
+use crate::scan::known_folders::{user_folders, UserFolder};
+use crate::scan::bundle::{SettingsBundle, SettingsImport};
+use crate::scan::settings::Settings;
+use crate::scan::api::{api_token, ApiServer, ApiSettings};
+use crate::scan::tray;
+use crate::scan::notify::{
+    delete_finished_body, notify, scan_finished_body, NotificationKind, NotificationSettings, LONG_DELETE,
+};
+use crate::scan::confirm::ConfirmationToken;
+use crate::scan::audit::{AuditAction, AuditEntry, AuditOutcome, AuditRange, ConfirmedBy};
+use crate::scan::duplicates::{
+    find_duplicate_groups, matches_hash, partition_group, DuplicateGroup, DuplicateResolution,
+    KeepStrategy,
+};
+use crate::scan::junk::{JunkCandidate, TreeScorer};
+use crate::scan::journal::{
+    refresh_reboot_deletions, schedule_reboot_delete, IncompleteOperation, JournalEntry, JournalOperation,
+    JournalStatus, RecoveryAction, RecoveryOutcome,
+};
+use crate::scan::locks::{close_processes, find_lock_holders, DeleteFailure};
+use crate::scan::memory::MemoryUsage;
+use crate::scan::messages::{self, Message, MessageTemplate};
+use crate::scan::spill::SpillSettings;
+use crate::scan::hooks::{
+    run_post_delete, run_pre_delete, HookConfig, HookDecision, HookItem, HookManifest, HookPhase,
+};
 
+/// Start a scan whose events go only to the calling window, or to every window when
+/// `broadcast` is set
 #[tauri::command]
 pub fn start_scan(
     root_path: String,
     options: ScanOptions,
+    broadcast: Option<bool>,
+    reuse: Option<ScanReuse>,
+    window: WebviewWindow,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<ScanHandle, String> {
+    let route = if broadcast.unwrap_or(false) {
+        EventRoute::Broadcast
+    } else {
+        EventRoute::Window(window.label().to_string())
+    };
+    if options.file_list.is_none() {
+        remember_options(&root_path, &options, state.inner());
+    }
+    if let Some(reuse) = reuse {
+        if let Some(handle) = spawn_reused_scan(&root_path, &options, &reuse, route.clone(), &app_handle, state.inner()) {
+            return Ok(handle);
+        }
+    }
+    Ok(spawn_scan(root_path, options, false, route, app_handle, state.inner().clone()))
+}
+
+/// Keep the options of a scan started by hand as the defaults for its root
+fn remember_options(root_path: &str, options: &ScanOptions, state: &AppState) {
+    let Ok(root) = normalize_root(root_path) else {
+        return;
+    };
+    let mount_point = volume_space(&root).map(|volume| volume.mount_point);
+    let _ = state.update_settings(|settings| {
+        let root = root.to_string_lossy();
+        scandefaults::remember(&mut settings.scan_defaults, &root, mount_point, options, now_millis());
+    });
+}
+
+/// The options to offer for scanning `root`: those it was last scanned with, else those
+/// of the nearest scanned folder above it or the latest root scanned on its volume
+#[tauri::command]
+pub fn get_default_options(root: String, state: State<'_, AppState>) -> Result<DefaultOptions, String> {
+    let root = normalize_root(&root)?;
+    let mount_point = volume_space(&root).map(|volume| volume.mount_point);
+    Ok(scandefaults::default_options(
+        &state.settings().scan_defaults,
+        &root.to_string_lossy(),
+        mount_point.as_deref(),
+    ))
+}
+
+/// Answer a scan from a fresh stored scan covering its root, emitting the same events
+/// as a walk. Returns None, so the caller walks instead, when no stored scan qualifies
+/// or validation finds directories changed since it ran.
+fn spawn_reused_scan(
+    root_path: &str,
+    options: &ScanOptions,
+    reuse: &ScanReuse,
+    route: EventRoute,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Option<ScanHandle> {
+    let root = normalize_root(root_path).ok()?;
+    let max_age = reuse.max_age_secs.unwrap_or(DEFAULT_REUSE_MAX_AGE_SECS);
+    let covering = reuse::find_covering_scan(state, &root, options, max_age)?;
+    let subtree = state
+        .with_tree(&covering.scan_id, |tree| reuse::extract_subtree(tree, covering.node_id))
+        .ok()??;
+    if reuse.validate && !reuse::changed_dirs(&subtree, covering.scanned_at).is_empty() {
+        return None;
+    }
+    let source = state.get_result(&covering.scan_id)?;
+
+    let scan_id = Uuid::new_v4().to_string();
+    let emitter = ScanEmitter::new(app_handle.clone(), route);
+    emit_started(
+        &emitter,
+        StartedPayload {
+            scan_id: scan_id.clone(),
+            root_path: root_path.to_string(),
+            started_at: now_millis(),
+            canonical_root: Some(covering.path.clone()).filter(|path| path != root_path),
+            overlaps: Vec::new(),
+            capabilities: volume_capabilities(&root),
+        },
+    );
+
+    let state = state.clone();
+    let options = options.clone();
+    let result_scan_id = scan_id.clone();
+    thread::spawn(move || {
+        let result = reuse::reused_result(&result_scan_id, &source, &subtree, &options);
+        let mut nodes: Vec<&TreeNode> = subtree.nodes().collect();
+        nodes.sort_unstable_by_key(|node| node.id);
+        for batch in nodes.chunks(MAX_PARTIAL_BATCH) {
+            emit_partial_tree(
+                &emitter,
+                PartialTreePayload {
+                    scan_id: result_scan_id.clone(),
+                    nodes: batch.iter().map(|node| node_to_delta(node)).collect(),
+                    updated_at: now_millis(),
+                },
+            );
+        }
+        emit_finished(
+            &emitter,
+            FinishedPayload {
+                scan_id: result_scan_id.clone(),
+                summary: ScanSummary {
+                    total_bytes: result.total_bytes,
+                    total_files: result.total_files,
+                    total_dirs: result.total_dirs,
+                    extension_stats: result.extension_stats.clone(),
+                    size_distribution: result.size_distribution.clone(),
+                },
+                root_node_id: result.root_id,
+                finished_at: now_millis(),
+            },
+        );
+        state.finish_scan(&result_scan_id, result, subtree);
+    });
+
+    Some(ScanHandle {
+        scan_id,
+        overlaps: Vec::new(),
+        reused_from: Some(covering.scan_id),
+        decision: OverlapDecision::Run,
+    })
+}
+
+/// How often a queued scan checks whether the scan it waits on has finished
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Start a scan on a background thread, emitting the usual scan events. Shared by
+/// `start_scan` and the background agent's scheduled scans.
+pub fn spawn_scan(
+    root_path: String,
+    mut options: ScanOptions,
+    scheduled: bool,
+    route: EventRoute,
+    app_handle: AppHandle,
+    state: AppState,
+) -> ScanHandle {
     let scan_id = Uuid::new_v4().to_string();
-    let scan_state = ScanState::new();
+    let emitter = ScanEmitter::new(app_handle.clone(), route);
+    let mut scan_state = ScanState::new();
+    scan_state.emitter = Some(emitter.clone());
     let started_at = scan_state
         .started_at
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis() as u64;
 
+    // Resolve the root the same way the walk will, so a scan requested through a mapped
+    // drive or a share is matched against results held under the other name
+    let canonical_root = normalize_root(&root_path).ok();
+    let decision = canonical_root
+        .as_ref()
+        .map(|root| {
+            let running = state.active_scan_roots();
+            roots::overlap_decision(roots::find_overlaps(
+                &root.to_string_lossy(),
+                running.iter().map(|(id, path)| (id.as_str(), path.as_str())),
+                is_case_insensitive(root),
+            ))
+        })
+        .unwrap_or_default();
+    if let (OverlapDecision::CarvedOut { excluded_paths, .. }, Some(root)) = (&decision, &canonical_root) {
+        let root = root.to_string_lossy();
+        options
+            .exclude_patterns
+            .extend(excluded_paths.iter().filter_map(|path| roots::carve_out_pattern(&root, path)));
+    }
+    let queued_behind = match &decision {
+        OverlapDecision::Queued { behind, .. } => Some(behind.clone()),
+        _ => None,
+    };
+    scan_state.root = canonical_root.as_ref().map(|root| root.to_string_lossy().to_string());
+
     state.insert_scan(scan_id.clone(), scan_state);
-    let state_clone = state.inner().clone();
-    let app_handle_clone = app_handle.clone();
+    let state_clone = state.clone();
+    let app_handle_clone = app_handle;
     let root_path_clone = root_path.clone();
+
+    if options.backend == ScanBackend::Auto {
+        options.backend = state.settings().scan_backend;
+    }
+    if options.size_hints.is_none() {
+        options.size_hints = canonical_root
+            .as_ref()
+            .and_then(|root| hints::hints_for_root(&state, root))
+            .map(Arc::new);
+    }
     let options_clone = options.clone();
+    // A resumed scan replaces the canceled one once it finishes
+    let resumed_from = options.resume.as_ref().map(|seed| seed.scan_id.clone());
+    let overlaps = canonical_root
+        .as_ref()
+        .map(|root| {
+            let roots = state.scan_roots();
+            roots::find_overlaps(
+                &root.to_string_lossy(),
+                roots.iter().map(|(id, path)| (id.as_str(), path.as_str())),
+                is_case_insensitive(root),
+            )
+        })
+        .unwrap_or_default();
+    let capabilities = canonical_root.as_deref().and_then(volume_capabilities);
+    let canonical_root = canonical_root
+        .map(|root| root.to_string_lossy().to_string())
+        .filter(|root| root.as_str() != root_path.trim_end_matches(['/', '\\']));
 
     emit_started(
-        &app_handle,
+        &emitter,
         StartedPayload {
             scan_id: scan_id.clone(),
             root_path: root_path.clone(),
             started_at,
+            canonical_root,
+            overlaps: overlaps.clone(),
+            capabilities,
         },
     );
 
@@ -55,26 +332,37 @@ pub fn start_scan(
             Some(flag) => flag,
             None => return,
         };
+        // Let the scan already walking these folders finish first
+        if let Some(behind) = &queued_behind {
+            while state_clone.is_scan_active(behind) && !cancel_flag.load(Ordering::Relaxed) {
+                thread::sleep(QUEUE_POLL_INTERVAL);
+            }
+        }
 
-        let result = run_scan(
-            Some(app_handle_clone.clone()),
-            scan_id_for_closure.clone(),
-            root_path_clone.clone(),
-            options_clone,
-            cancel_flag,
-        );
+        // A panic in the walker must still end the scan, or the frontend waits forever
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            run_scan(
+                Some(emitter.clone()),
+                scan_id_for_closure.clone(),
+                root_path_clone.clone(),
+                options_clone,
+                cancel_flag,
+            )
+        }))
+        .unwrap_or_else(|_| Err(ScanError::Failed("The scan stopped unexpectedly".to_string())));
 
         match result {
-            Ok(result) => {
+            Ok((result, tree)) => {
                 let summary = ScanSummary {
                     total_bytes: result.total_bytes,
                     total_files: result.total_files,
                     total_dirs: result.total_dirs,
                     extension_stats: result.extension_stats.clone(),
+                    size_distribution: result.size_distribution.clone(),
                 };
                 let result_scan_id = result.scan_id.clone();
                 emit_finished(
-                    &app_handle_clone,
+                    &emitter,
                     FinishedPayload {
                         scan_id: result_scan_id.clone(),
                         summary,
@@ -82,19 +370,61 @@ pub fn start_scan(
                         finished_at: now_millis(),
                     },
                 );
-                state_clone.finish_scan(&result_scan_id, result);
+                let (kind, title) = if scheduled {
+                    (NotificationKind::ScheduledScan, "Scheduled scan finished")
+                } else {
+                    (NotificationKind::ScanFinished, "Scan finished")
+                };
+                let body = scan_finished_body(&root_path_clone, result.total_bytes, result.total_files);
+                if scheduled {
+                    baseline::check_scheduled_scan(
+                        &app_handle_clone,
+                        state_clone.data_dir(),
+                        &state_clone.settings().drift,
+                        &result_scan_id,
+                        &tree,
+                    );
+                    let _ = growth::record_scheduled_scan(state_clone.data_dir(), &result_scan_id, &tree);
+                }
+                state_clone.finish_scan(&result_scan_id, result, tree);
+                if let Some(resumed_from) = &resumed_from {
+                    state_clone.release_scan(resumed_from);
+                }
+                notify(&app_handle_clone, &state_clone, kind, title, &body);
             }
-            Err(ScanError::Canceled) => {
-                emit_canceled(&app_handle_clone, CanceledPayload { scan_id: scan_id_for_closure.clone() });
-                state_clone.remove_scan(&scan_id_for_closure);
+            Err(ScanError::Canceled(partial)) => {
+                // What the walk found stays browsable under the scan's id
+                let partial_result = partial.is_some();
+                match partial {
+                    Some(partial) => state_clone.finish_scan(&scan_id_for_closure, partial.result, partial.tree),
+                    None => state_clone.remove_scan(&scan_id_for_closure),
+                }
+                emit_canceled(
+                    &emitter,
+                    CanceledPayload {
+                        scan_id: scan_id_for_closure.clone(),
+                        partial_result,
+                    },
+                );
             }
             Err(ScanError::Failed(message)) => {
                 emit_error(
-                    &app_handle_clone,
-                    ErrorPayload {
+                    &emitter,
+                    ErrorPayload::new(
+                        scan_id_for_closure.clone(),
+                        Message::new(messages::SCAN_FAILED, [("error", message)]),
+                        Some(root_path_clone),
+                    ),
+                );
+                state_clone.remove_scan(&scan_id_for_closure);
+            }
+            Err(ScanError::RootLost(root_path)) => {
+                emit_root_lost(
+                    &emitter,
+                    RootLostPayload {
                         scan_id: scan_id_for_closure.clone(),
-                        message,
-                        path: Some(root_path_clone),
+                        message: format!("{} is no longer available; the scan was stopped", root_path),
+                        root_path,
                     },
                 );
                 state_clone.remove_scan(&scan_id_for_closure);
@@ -102,7 +432,12 @@ pub fn start_scan(
         }
     });
 
-    Ok(ScanHandle { scan_id })
+    ScanHandle {
+        scan_id,
+        overlaps,
+        reused_from: None,
+        decision,
+    }
 }
 
 #[tauri::command]
@@ -110,6 +445,30 @@ pub fn cancel_scan(scan_id: String, state: State<'_, AppState>) -> bool {
     state.cancel_scan(&scan_id)
 }
 
+/// Pick up a canceled scan kept as a partial result: its root is walked again with the
+/// same options, but the folders it had finished are copied over instead of read. The
+/// partial result is released once the new scan finishes.
+#[tauri::command]
+pub fn resume_scan(
+    scan_id: String,
+    window: WebviewWindow,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ScanHandle, String> {
+    let result = state.get_result(&scan_id).ok_or("Scan not found")?;
+    if result.complete {
+        return Err("Only a canceled scan can be resumed".to_string());
+    }
+    let tree = state.with_tree(&scan_id, |tree| tree.clone())?;
+    let root_path = tree.root().map(|root| root.path.clone()).ok_or("Scan has no root")?;
+    let options = ScanOptions {
+        resume: Some(Arc::new(ResumeSeed::new(&scan_id, &result, tree))),
+        ..result.options
+    };
+    let route = EventRoute::Window(window.label().to_string());
+    Ok(spawn_scan(root_path, options, false, route, app_handle, state.inner().clone()))
+}
+
 #[tauri::command]
 pub fn get_scan_result(scan_id: String, state: State<'_, AppState>) -> Option<crate::scan::model::ScanResult> {
     state.get_result(&scan_id)
@@ -117,17 +476,7 @@ pub fn get_scan_result(scan_id: String, state: State<'_, AppState>) -> Option<cr
 
 #[tauri::command]
 pub fn list_roots() -> Vec<RootEntry> {
-    let disks = sysinfo::Disks::new_with_refreshed_list();
-    disks
-        .list()
-        .iter()
-        .map(|disk| RootEntry {
-            name: disk.name().to_string_lossy().to_string(),
-            path: disk.mount_point().to_string_lossy().to_string(),
-            total_bytes: disk.total_space(),
-            available_bytes: disk.available_space(),
-        })
-        .collect()
+    list_root_entries()
 }
 
 fn now_millis() -> u64 {
@@ -191,28 +540,177 @@ pub fn open_in_explorer(path: String) -> Result<(), String> {
     Ok(())
 }
 
-/// Delete a file or folder
+/// Delete a file or folder and report what was freed. With a scan id, the removed
+/// subtree is also dropped from that scan's stored tree. Protected paths are refused
+/// and ConfirmRequired ones need a token, as with `smart_delete`.
 #[tauri::command]
-pub fn delete_path(path: String, to_trash: bool) -> Result<(), String> {
-    let path = Path::new(&path);
+pub fn delete_path(
+    path: String,
+    to_trash: bool,
+    trash_fallback: Option<TrashFallback>,
+    scan_id: Option<String>,
+    confirmation_token: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DeleteResult, String> {
+    let path_obj = Path::new(&path);
     
-    if !path.exists() {
-        return Err(format!("Path does not exist: {}", path.display()));
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    
+    let safety = get_safety_level(path_obj, &state.classifier());
+    if safety == SafetyLevel::Protected {
+        emit_delete_failed(&app_handle, DeleteFailedPayload {
+            path: path.clone(),
+            reason: "Protected system file cannot be deleted".to_string(),
+        });
+        state.record_audit(&[blocked_audit_entry(&path, &safety, "Protected system file")]);
+        return Err("Cannot delete protected system file".to_string());
+    }
+    if safety == SafetyLevel::ConfirmRequired {
+        match &confirmation_token {
+            Some(token) => state.consume_delete_confirmation(token, &path, &safety)?,
+            None => return Err("Confirmation required for this file type".to_string()),
+        }
     }
     
+    let known = scanned_totals(&state, scan_id.as_deref(), &path);
+    let mut to_trash = to_trash;
     if to_trash {
-        // Move to trash/recycle bin
-        trash::delete(path).map_err(|e| e.to_string())?;
-    } else {
-        // Permanent delete
-        if path.is_dir() {
-            fs::remove_dir_all(path).map_err(|e| e.to_string())?;
-        } else {
-            fs::remove_file(path).map_err(|e| e.to_string())?;
+        let size = known.map(|t| t.bytes).unwrap_or_else(|| measure_path(path_obj).bytes);
+        match recycle::trash_plan(path_obj, size, trash_fallback.unwrap_or_default()) {
+            TrashPlan::Delete(mode) => to_trash = mode == DeleteMode::Trash,
+            TrashPlan::Quarantine => return quarantine_path(&path, known, scan_id.as_deref(), &state),
+            TrashPlan::Refuse(reason) => return Err(reason),
         }
     }
     
-    Ok(())
+    let result = delete_path_direct(path_obj, to_trash, known);
+    
+    let action = if to_trash { AuditAction::Trash } else { AuditAction::Delete };
+    let mut entry = AuditEntry::new(action, &path, ConfirmedBy::User, AuditOutcome::Success);
+    entry.size_bytes = result.bytes_freed;
+    if !result.success {
+        entry.outcome = AuditOutcome::Failed;
+        entry.message = result.errors.first().cloned();
+    }
+    state.record_audit(&[entry]);
+    
+    if !result.success {
+        let reason = result.errors.join("; ");
+        emit_delete_failed(&app_handle, DeleteFailedPayload {
+            path: path.clone(),
+            reason: reason.clone(),
+        });
+        return Err(reason);
+    }
+    
+    emit_deleted(&app_handle, DeletedPayload {
+        path: path.clone(),
+        bytes_freed: result.bytes_freed,
+        was_auto: false,
+    });
+    if let Some(scan_id) = &scan_id {
+        prune_scan_tree(&state, scan_id, &path);
+    }
+    Ok(result)
+}
+
+/// Move a path that won't fit in the trash into the volume's quarantine folder
+fn quarantine_path(
+    path: &str,
+    known: Option<PathTotals>,
+    scan_id: Option<&str>,
+    state: &AppState,
+) -> Result<DeleteResult, String> {
+    let totals = known.unwrap_or_else(|| measure_path(Path::new(path)));
+    let moved = recycle::quarantine_target(Path::new(path)).and_then(|target| {
+        let journal_id = journal_start(
+            state,
+            JournalOperation::Move {
+                from: path.to_string(),
+                to: target.to_string_lossy().to_string(),
+            },
+        );
+        let moved = recycle::move_path(Path::new(path), &target);
+        journal_finish(state, journal_id.as_deref(), moved.is_ok());
+        moved.map(|_| target)
+    });
+    let mut entry = AuditEntry::new(AuditAction::Move, path, ConfirmedBy::User, AuditOutcome::Success);
+    entry.size_bytes = totals.bytes;
+    match &moved {
+        Ok(target) => entry.message = Some(format!("Quarantined to {}", target.display())),
+        Err(e) => {
+            entry.outcome = AuditOutcome::Failed;
+            entry.message = Some(e.clone());
+        }
+    }
+    state.record_audit(&[entry]);
+    let target = moved?;
+    if let Some(scan_id) = scan_id {
+        prune_scan_tree(state, scan_id, path);
+    }
+    Ok(DeleteResult {
+        success: true,
+        files_deleted: totals.files,
+        folders_deleted: totals.folders,
+        bytes_freed: 0,
+        errors: Vec::new(),
+        was_auto_delete: false,
+        failures: Vec::new(),
+        quarantined_to: Some(target.to_string_lossy().to_string()),
+    })
+}
+
+/// Journal an operation as pending, so a crash part-way through is reported on the next
+/// launch. Returns the entry id, or None when the journal can't be written.
+fn journal_start(state: &AppState, operation: JournalOperation) -> Option<String> {
+    let entry = JournalEntry::pending(Uuid::new_v4().to_string(), operation);
+    let id = entry.id.clone();
+    state.update_journal(|journal| journal.entries.push(entry)).ok()?;
+    Some(id)
+}
+
+/// Close a journaled operation that ran to its end
+fn journal_finish(state: &AppState, id: Option<&str>, success: bool) {
+    let Some(id) = id else {
+        return;
+    };
+    let status = if success { JournalStatus::Completed } else { JournalStatus::Failed };
+    let _ = state.update_journal(|journal| {
+        if let Some(entry) = journal.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.set_status(status);
+        }
+    });
+}
+
+/// Size and entry counts of a path from a finished scan's tree, if it was scanned
+fn scanned_totals(state: &AppState, scan_id: Option<&str>, path: &str) -> Option<PathTotals> {
+    state
+        .with_tree(scan_id?, |tree| {
+            let node = tree.find_by_path(path)?;
+            let (files, folders) = tree.subtree_counts(node.id)?;
+            Some(PathTotals {
+                bytes: node.size_bytes,
+                files,
+                folders,
+            })
+        })
+        .ok()
+        .flatten()
+}
+
+/// Drop a deleted path from a finished scan's tree. An unknown scan id is ignored,
+/// since the delete itself already succeeded.
+fn prune_scan_tree(state: &AppState, scan_id: &str, path: &str) {
+    let Ok(id) = state.with_tree(scan_id, |tree| tree.find_by_path(path).map(|node| node.id)) else {
+        return;
+    };
+    let pruned = state.prune_nodes(scan_id, id.as_slice());
+    if pruned.is_ok() {
+        state.refresh_stamps(scan_id, &[path.to_string()]);
+    }
 }
 
 /// Get file/folder size
@@ -256,32 +754,59 @@ pub fn get_path_size(path: String) -> Result<u64, String> {
 
 /// Get the safety level for a file or folder
 #[tauri::command]
-pub fn get_file_safety_level(path: String) -> Result<SafetyLevel, String> {
+pub fn get_file_safety_level(path: String, state: State<'_, AppState>) -> Result<SafetyLevel, String> {
     let path = Path::new(&path);
     if !path.exists() {
         return Err(format!("Path does not exist: {}", path.display()));
     }
-    Ok(get_safety_level(path))
+    Ok(get_safety_level(path, &state.classifier()))
 }
 
-/// Get detailed file info including safety level
+/// Get detailed file info including safety level and junk score.
+/// With a scan_id the score also uses scan-wide context such as duplicates.
+/// A ConfirmRequired path comes with a one-time token for deleting it.
 #[tauri::command]
-pub fn get_file_details(path: String) -> Result<FileInfo, String> {
-    let path = Path::new(&path);
-    get_file_info(path)
+pub fn get_file_details(
+    path: String,
+    scan_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FileInfo, String> {
+    let mut info = get_file_info(Path::new(&path), &state.classifier())?;
+    if info.safety_level == SafetyLevel::ConfirmRequired {
+        info.confirmation = Some(state.issue_delete_confirmation(vec![(path.clone(), SafetyLevel::ConfirmRequired)])?);
+    }
+    if let Some(scan_id) = scan_id {
+        let junk = state.with_tree(&scan_id, |tree| {
+            tree.find_by_path(&path)
+                .map(|node| TreeScorer::new(tree).score_node(node))
+        })?;
+        if let Some(junk) = junk {
+            info.junk = junk;
+        }
+    }
+    Ok(info)
 }
 
 /// Smart delete a file or folder
-/// If force=true, skip confirmation requirement (user already confirmed)
+/// ConfirmRequired paths need a token from `get_file_details` or `preview_bulk_delete`.
+/// Items too large for the Recycle Bin follow `trash_fallback`, else the setting.
 #[tauri::command]
-pub fn smart_delete(path: String, force: bool, app_handle: AppHandle) -> Result<DeleteResult, String> {
+pub fn smart_delete(
+    path: String,
+    confirmation_token: Option<String>,
+    error_policy: Option<ErrorPolicy>,
+    scan_id: Option<String>,
+    trash_fallback: Option<TrashFallback>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DeleteResult, String> {
     let path_obj = Path::new(&path);
     
     if !path_obj.exists() {
         return Err(format!("Path does not exist: {}", path));
     }
     
-    let safety = get_safety_level(path_obj);
+    let safety = get_safety_level(path_obj, &state.classifier());
     
     // Never allow deleting protected files
     if safety == SafetyLevel::Protected {
@@ -289,16 +814,69 @@ pub fn smart_delete(path: String, force: bool, app_handle: AppHandle) -> Result<
             path: path.clone(),
             reason: "Protected system file cannot be deleted".to_string(),
         });
+        state.record_audit(&[blocked_audit_entry(&path, &safety, "Protected system file")]);
         return Err("Cannot delete protected system file".to_string());
     }
     
-    // If confirmation required but not forced, return error
-    if safety == SafetyLevel::ConfirmRequired && !force {
-        return Err("Confirmation required for this file type".to_string());
+    // If confirmation required, the caller must hold a token covering this path
+    if safety == SafetyLevel::ConfirmRequired {
+        match &confirmation_token {
+            Some(token) => state.consume_delete_confirmation(token, &path, &safety)?,
+            None => return Err("Confirmation required for this file type".to_string()),
+        }
+    }
+    
+    let settings = state.settings();
+    let hooks = state.hooks();
+    let mut hook_items = Vec::new();
+    if hooks.is_configured() {
+        hook_items.push(hook_item(path_obj, &safety));
+        let decision = run_pre_delete(&hooks, &hook_manifest(HookPhase::PreDelete, "smart_delete", &hook_items));
+        if let Some(reason) = decision.denies(&path) {
+            let reason = format!("Blocked by pre-delete hook: {}", reason);
+            emit_delete_failed(&app_handle, DeleteFailedPayload {
+                path: path.clone(),
+                reason: reason.clone(),
+            });
+            state.record_audit(&[blocked_audit_entry(&path, &safety, &reason)]);
+            return Err(reason);
+        }
     }
     
     // Perform the delete
-    match smart_delete_file(path_obj, force) {
+    let totals = scanned_totals(&state, scan_id.as_deref(), &path).unwrap_or_else(|| measure_path(path_obj));
+    let outcome = match recycle::trash_plan(path_obj, totals.bytes, trash_fallback.unwrap_or(settings.trash_fallback)) {
+        TrashPlan::Delete(mode) => smart_delete_file(
+            path_obj,
+            false,
+            &state.classifier(),
+            &mut Throttle::unlimited(),
+            error_policy.unwrap_or_default(),
+            Some(totals),
+            mode,
+        ),
+        TrashPlan::Quarantine => return quarantine_path(&path, Some(totals), scan_id.as_deref(), &state),
+        TrashPlan::Refuse(reason) => Ok(refused_delete(path_obj, reason, &safety)),
+    };
+    state.record_audit(&[delete_audit_entry(&path, &safety, &outcome)]);
+    if hooks.is_configured() {
+        for item in hook_items.iter_mut() {
+            match &outcome {
+                Ok(result) => {
+                    item.success = Some(result.success);
+                    item.error = result.errors.first().cloned();
+                }
+                Err(e) => {
+                    item.success = Some(false);
+                    item.error = Some(e.clone());
+                }
+            }
+        }
+        // Post-delete hooks are advisory; their failure doesn't change the outcome
+        let _ = run_post_delete(&hooks, &hook_manifest(HookPhase::PostDelete, "smart_delete", &hook_items));
+    }
+    
+    match outcome {
         Ok(result) => {
             if result.success {
                 emit_deleted(&app_handle, DeletedPayload {
@@ -306,6 +884,9 @@ pub fn smart_delete(path: String, force: bool, app_handle: AppHandle) -> Result<
                     bytes_freed: result.bytes_freed,
                     was_auto: result.was_auto_delete,
                 });
+                if let Some(scan_id) = &scan_id {
+                    prune_scan_tree(&state, scan_id, &path);
+                }
             } else {
                 for error in &result.errors {
                     emit_delete_failed(&app_handle, DeleteFailedPayload {
@@ -328,12 +909,58 @@ pub fn smart_delete(path: String, force: bool, app_handle: AppHandle) -> Result<
 
 /// Bulk delete multiple paths with smart safety checks
 #[tauri::command]
-pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle) -> DeleteResult {
+pub fn bulk_smart_delete(
+    paths: Vec<String>,
+    confirmation_token: Option<String>,
+    throttle: Option<DeleteThrottle>,
+    error_policy: Option<ErrorPolicy>,
+    scan_id: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> DeleteResult {
+    let batch = DeleteBatch {
+        throttle,
+        error_policy,
+        scan_id,
+        journal_id: None,
+    };
+    run_bulk_delete(paths, confirmation_token, batch, app_handle, state)
+}
+
+/// Options of one bulk delete
+struct DeleteBatch {
+    throttle: Option<DeleteThrottle>,
+    error_policy: Option<ErrorPolicy>,
+    scan_id: Option<String>,
+    /// An operation already journaled that this delete completes; without one the
+    /// delete is journaled on its own
+    journal_id: Option<String>,
+}
+
+fn run_bulk_delete(
+    paths: Vec<String>,
+    confirmation_token: Option<String>,
+    batch: DeleteBatch,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> DeleteResult {
+    let DeleteBatch {
+        throttle,
+        error_policy,
+        scan_id,
+        journal_id,
+    } = batch;
+    let classifier = state.classifier();
+    let started = Instant::now();
+    let item_count = paths.len();
     let mut total_bytes = 0u64;
     let mut total_files = 0u64;
     let mut total_folders = 0u64;
     let mut errors = Vec::new();
+    let mut failures = Vec::new();
     let mut all_auto = true;
+    let mut eligible: Vec<(String, SafetyLevel)> = Vec::new();
+    let mut audit_entries: Vec<AuditEntry> = Vec::new();
     
     for path_str in paths {
         let path = Path::new(&path_str);
@@ -343,7 +970,7 @@ pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle)
             continue;
         }
         
-        let safety = get_safety_level(path);
+        let safety = get_safety_level(path, &classifier);
         
         if safety == SafetyLevel::Protected {
             errors.push(format!("Skipped protected: {}", path_str));
@@ -351,27 +978,102 @@ pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle)
                 path: path_str.clone(),
                 reason: "Protected system file".to_string(),
             });
+            audit_entries.push(blocked_audit_entry(&path_str, &safety, "Protected system file"));
             continue;
         }
         
-        if safety == SafetyLevel::ConfirmRequired && !force {
-            errors.push(format!("Requires confirmation: {}", path_str));
+        if safety == SafetyLevel::ConfirmRequired {
             all_auto = false;
-            continue;
+            let confirmed = confirmation_token
+                .as_deref()
+                .map(|token| state.consume_delete_confirmation(token, &path_str, &safety).is_ok())
+                .unwrap_or(false);
+            if !confirmed {
+                errors.push(format!("Requires confirmation: {}", path_str));
+                continue;
+            }
         }
         
-        if safety == SafetyLevel::ConfirmRequired {
-            all_auto = false;
+        eligible.push((path_str, safety));
+    }
+    
+    // Let the pre-delete hook veto the whole batch or individual paths
+    let settings = state.settings();
+    let hooks = state.hooks();
+    let mut throttle = Throttle::new(throttle.unwrap_or(settings.delete_throttle));
+    let mut hook_items: Vec<HookItem> = Vec::new();
+    if hooks.is_configured() {
+        hook_items = eligible
+            .iter()
+            .map(|(path_str, safety)| hook_item(Path::new(path_str), safety))
+            .collect();
+        let decision = run_pre_delete(&hooks, &hook_manifest(HookPhase::PreDelete, "bulk_smart_delete", &hook_items));
+        if decision != HookDecision::Allow {
+            eligible.retain(|(path_str, safety)| match decision.denies(path_str) {
+                Some(reason) => {
+                    let reason = format!("Blocked by pre-delete hook: {}", reason);
+                    errors.push(format!("{}: {}", reason, path_str));
+                    audit_entries.push(blocked_audit_entry(path_str, safety, &reason));
+                    emit_delete_failed(&app_handle, DeleteFailedPayload {
+                        path: path_str.clone(),
+                        reason,
+                    });
+                    false
+                }
+                None => true,
+            });
+            hook_items.retain(|item| eligible.iter().any(|(p, _)| *p == item.path));
         }
+    }
+    
+    let journal_id = journal_id.or_else(|| {
+        (!eligible.is_empty())
+            .then(|| {
+                let paths = eligible.iter().map(|(path_str, _)| path_str.clone()).collect();
+                journal_start(&state, JournalOperation::BulkDelete { paths })
+            })
+            .flatten()
+    });
+    for (path_str, safety) in eligible {
+        let path = Path::new(&path_str);
+        let hook_index = hook_items.iter().position(|item| item.path == path_str);
         
-        match smart_delete_file(path, force) {
+        let totals = scanned_totals(&state, scan_id.as_deref(), &path_str).unwrap_or_else(|| measure_path(path));
+        let plan = recycle::trash_plan(path, totals.bytes, settings.trash_fallback);
+        let quarantined = plan == TrashPlan::Quarantine;
+        let outcome = match plan {
+            TrashPlan::Delete(mode) => smart_delete_file(
+                path,
+                false,
+                &classifier,
+                &mut throttle,
+                error_policy.unwrap_or_default(),
+                Some(totals),
+                mode,
+            ),
+            // The move is audited on its own and pruned from the tree below
+            TrashPlan::Quarantine => quarantine_path(&path_str, Some(totals), None, &state),
+            TrashPlan::Refuse(reason) => Ok(refused_delete(path, reason, &safety)),
+        };
+        if !quarantined {
+            audit_entries.push(delete_audit_entry(&path_str, &safety, &outcome));
+        }
+        match outcome {
             Ok(result) => {
                 total_bytes += result.bytes_freed;
                 total_files += result.files_deleted;
                 total_folders += result.folders_deleted;
+                if let Some(item) = hook_index.and_then(|i| hook_items.get_mut(i)) {
+                    item.success = Some(result.success);
+                    item.error = result.errors.first().cloned();
+                }
                 errors.extend(result.errors);
+                failures.extend(result.failures);
                 
                 if result.success {
+                    if let Some(scan_id) = &scan_id {
+                        prune_scan_tree(&state, scan_id, &path_str);
+                    }
                     emit_deleted(&app_handle, DeletedPayload {
                         path: path_str,
                         bytes_freed: result.bytes_freed,
@@ -380,6 +1082,10 @@ pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle)
                 }
             }
             Err(e) => {
+                if let Some(item) = hook_index.and_then(|i| hook_items.get_mut(i)) {
+                    item.success = Some(false);
+                    item.error = Some(e.clone());
+                }
                 errors.push(e.clone());
                 emit_delete_failed(&app_handle, DeleteFailedPayload {
                     path: path_str,
@@ -389,6 +1095,22 @@ pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle)
         }
     }
     
+    journal_finish(&state, journal_id.as_deref(), errors.is_empty());
+    if hooks.is_configured() && !hook_items.is_empty() {
+        // Post-delete hooks are advisory; their failure doesn't change the outcome
+        let _ = run_post_delete(&hooks, &hook_manifest(HookPhase::PostDelete, "bulk_smart_delete", &hook_items));
+    }
+    state.record_audit(&audit_entries);
+    if started.elapsed() >= LONG_DELETE {
+        notify(
+            &app_handle,
+            &state,
+            NotificationKind::BackgroundDelete,
+            "Cleanup finished",
+            &delete_finished_body(total_bytes, item_count, errors.len()),
+        );
+    }
+    
     DeleteResult {
         success: errors.is_empty(),
         bytes_freed: total_bytes,
@@ -396,5 +1118,2382 @@ pub fn bulk_smart_delete(paths: Vec<String>, force: bool, app_handle: AppHandle)
         folders_deleted: total_folders,
         errors,
         was_auto_delete: all_auto,
+        failures,
+        quarantined_to: None,
+    }
+}
+
+/// The result for an item left in place for being too large for the Recycle Bin
+fn refused_delete(path: &Path, reason: String, safety: &SafetyLevel) -> DeleteResult {
+    DeleteResult {
+        success: false,
+        bytes_freed: 0,
+        files_deleted: 0,
+        folders_deleted: 0,
+        errors: vec![reason.clone()],
+        was_auto_delete: *safety == SafetyLevel::AutoDelete,
+        failures: vec![DeleteFailure::wont_fit_trash(path, reason)],
+        quarantined_to: None,
+    }
+}
+
+/// Delete every node of a finished scan matching a .gitignore-style glob such as
+/// `**/*.tmp` or `Thumbs.db` whose safety level is at or below `safety_floor`, as one
+/// bulk delete. A matched folder counts at the strictest level of anything inside it.
+/// A dry run only lists the matches, with a token covering the ConfirmRequired ones for
+/// the real run; protected ones are never deleted.
+#[tauri::command]
+pub fn delete_matching(
+    scan_id: String,
+    pattern: String,
+    safety_floor: SafetyLevel,
+    dry_run: bool,
+    confirmation_token: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PatternDelete, String> {
+    let found = state.with_tree(&scan_id, |tree| {
+        let root = tree.root().map(|root| root.path.clone()).unwrap_or_default();
+        let compiled = PathPattern::new(&root, &pattern)?;
+        let found: Vec<(TreeNode, Vec<String>)> = find_matches(tree, &compiled)
+            .into_iter()
+            .map(|node| (node.clone(), descendant_paths(tree, node)))
+            .collect();
+        Ok::<_, String>(found)
+    })??;
+    let (matches, skipped) = classify_matches(&found, &safety_floor, &state.classifier());
+    let mut report = PatternDelete {
+        pattern,
+        safety_floor,
+        dry_run,
+        matched_bytes: matches.iter().map(|found| found.size_bytes).sum(),
+        skipped_count: skipped.len() as u64,
+        skipped_bytes: skipped.iter().map(|found| found.size_bytes).sum(),
+        matches,
+        confirmation: None,
+        result: None,
+    };
+    let confirm: Vec<(String, SafetyLevel)> = report
+        .matches
+        .iter()
+        .filter(|found| found.safety == SafetyLevel::ConfirmRequired)
+        .map(|found| (found.path.clone(), found.safety.clone()))
+        .collect();
+    if dry_run && !confirm.is_empty() {
+        report.confirmation = Some(state.issue_delete_confirmation(confirm)?);
+    }
+    if !dry_run && !report.matches.is_empty() {
+        let (paths, unconfirmed) = confirm_held_matches(&report.matches, confirmation_token.as_deref(), &state);
+        let batch = DeleteBatch {
+            throttle: None,
+            error_policy: None,
+            scan_id: Some(scan_id),
+            journal_id: None,
+        };
+        let mut result = run_bulk_delete(paths, confirmation_token, batch, app_handle, state);
+        if !unconfirmed.is_empty() {
+            result.success = false;
+            result.errors.extend(unconfirmed);
+        }
+        report.result = Some(result);
+    }
+    Ok(report)
+}
+
+/// Paths of a pattern delete to hand to the bulk delete, and errors for those left
+/// out. The bulk delete only checks a folder's own level, so a folder that needs
+/// confirming just for what it holds uses up its token here; one that needs it at its
+/// own level too is left for the bulk delete, so the token is used once.
+fn confirm_held_matches(
+    matches: &[PatternMatch],
+    confirmation_token: Option<&str>,
+    state: &AppState,
+) -> (Vec<String>, Vec<String>) {
+    let classifier = state.classifier();
+    let mut unconfirmed = Vec::new();
+    let paths = matches
+        .iter()
+        .filter(|found| {
+            if found.held_by.is_none() || found.safety != SafetyLevel::ConfirmRequired {
+                return true;
+            }
+            if get_safety_level(Path::new(&found.path), &classifier) == SafetyLevel::ConfirmRequired {
+                return true;
+            }
+            let confirmed = confirmation_token
+                .is_some_and(|token| state.consume_delete_confirmation(token, &found.path, &found.safety).is_ok());
+            if !confirmed {
+                unconfirmed.push(format!("Requires confirmation: {}", found.path));
+            }
+            confirmed
+        })
+        .map(|found| found.path.clone())
+        .collect();
+    (paths, unconfirmed)
+}
+
+/// Issue a short-lived token authorizing `retry_after_close` to close exactly these
+/// processes. Call this after the user confirms they may lose unsaved work; every
+/// process must hold the locked file.
+#[tauri::command]
+pub fn request_close_confirmation(
+    path: String,
+    locked_path: Option<String>,
+    pids: Vec<u32>,
+    state: State<'_, AppState>,
+) -> Result<ConfirmationToken, String> {
+    let locked = locked_path.unwrap_or(path);
+    let holders = find_lock_holders(Path::new(&locked));
+    if pids.is_empty() {
+        return Err("No processes were chosen".to_string());
+    }
+    if let Some(pid) = pids.iter().find(|pid| !holders.iter().any(|h| h.pid == **pid)) {
+        return Err(format!("Process {} does not hold {}", pid, locked));
+    }
+    state.issue_confirmation(vec![close_confirmation_key(&locked, &pids)])
+}
+
+/// What a close confirmation covers: one locked file and one set of processes
+fn close_confirmation_key(locked: &str, pids: &[u32]) -> String {
+    let mut pids = pids.to_vec();
+    pids.sort_unstable();
+    pids.dedup();
+    let pids: Vec<String> = pids.iter().map(u32::to_string).collect();
+    format!("close {} holding {}", pids.join(","), locked)
+}
+
+/// Ask the processes holding a locked file to close, then retry the delete.
+/// `locked_path` is the file reported in the failure when it differs from `path`
+/// (e.g. a locked file inside a folder being deleted). `close_token` must come from
+/// `request_close_confirmation` for these same processes; `confirmation_token` is the
+/// delete token a ConfirmRequired path needs.
+#[tauri::command]
+pub fn retry_after_close(
+    path: String,
+    locked_path: Option<String>,
+    pids: Vec<u32>,
+    close_token: String,
+    confirmation_token: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DeleteResult, String> {
+    let locked = locked_path.unwrap_or_else(|| path.clone());
+    state.take_confirmation(&close_token, &close_confirmation_key(&locked, &pids))?;
+    let holders = find_lock_holders(Path::new(&locked));
+    // Only processes that still hold the file are asked to close
+    let pids: Vec<u32> = pids
+        .into_iter()
+        .filter(|pid| holders.iter().any(|h| h.pid == *pid))
+        .collect();
+    if !pids.is_empty() {
+        close_processes(Path::new(&locked), &pids)?;
+    }
+    smart_delete(path, confirmation_token, None, None, None, app_handle, state)
+}
+
+fn delete_audit_entry(path: &str, safety: &SafetyLevel, outcome: &Result<DeleteResult, String>) -> AuditEntry {
+    let confirmed_by = if *safety == SafetyLevel::AutoDelete {
+        ConfirmedBy::Auto
+    } else {
+        ConfirmedBy::User
+    };
+    let mut entry = AuditEntry::new(AuditAction::Delete, path, confirmed_by, AuditOutcome::Failed);
+    entry.safety_level = Some(safety.clone());
+    match outcome {
+        Ok(result) if result.success => {
+            entry.outcome = AuditOutcome::Success;
+            entry.size_bytes = result.bytes_freed;
+        }
+        Ok(result) => entry.message = result.errors.first().cloned(),
+        Err(e) => entry.message = Some(e.clone()),
+    }
+    entry
+}
+
+fn blocked_audit_entry(path: &str, safety: &SafetyLevel, reason: &str) -> AuditEntry {
+    let mut entry = AuditEntry::new(AuditAction::Delete, path, ConfirmedBy::User, AuditOutcome::Blocked);
+    entry.safety_level = Some(safety.clone());
+    entry.message = Some(reason.to_string());
+    entry
+}
+
+fn hook_item(path: &Path, safety: &SafetyLevel) -> HookItem {
+    let size_bytes = if path.is_dir() {
+        calculate_dir_size(path).unwrap_or(0)
+    } else {
+        path.metadata().map(|m| m.len()).unwrap_or(0)
+    };
+    HookItem {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        safety_level: safety.clone(),
+        success: None,
+        error: None,
+    }
+}
+
+fn hook_manifest(phase: HookPhase, operation: &str, items: &[HookItem]) -> HookManifest {
+    HookManifest {
+        phase,
+        operation: operation.to_string(),
+        items: items.to_vec(),
+    }
+}
+
+// ==========================================
+// DELETE HOOK COMMANDS
+// ==========================================
+
+/// Get the pre/post delete hooks configured in the hooks file. There is no command to
+/// change them: the user edits the file by hand.
+#[tauri::command]
+pub fn get_hook_config(state: State<'_, AppState>) -> HookConfig {
+    state.hooks()
+}
+
+/// Get the default pacing applied to bulk deletes
+#[tauri::command]
+pub fn get_delete_throttle(state: State<'_, AppState>) -> DeleteThrottle {
+    state.settings().delete_throttle
+}
+
+/// Replace the default pacing applied to bulk deletes
+#[tauri::command]
+pub fn set_delete_throttle(throttle: DeleteThrottle, state: State<'_, AppState>) -> Result<DeleteThrottle, String> {
+    state
+        .update_settings(|settings| settings.delete_throttle = throttle)
+        .map(|settings| settings.delete_throttle)
+}
+
+/// Whether developer mode's reclassification of build output and VCS/IDE folders is on
+#[tauri::command]
+pub fn get_developer_mode(state: State<'_, AppState>) -> bool {
+    state.settings().developer_mode
+}
+
+/// Turn developer mode on or off and persist it
+#[tauri::command]
+pub fn set_developer_mode(enabled: bool, state: State<'_, AppState>) -> Result<bool, String> {
+    state
+        .update_settings(|settings| settings.developer_mode = enabled)
+        .map(|settings| settings.developer_mode)
+}
+
+// ==========================================
+// AUDIT LOG COMMANDS
+// ==========================================
+
+/// Read the audit log of destructive operations, newest first
+#[tauri::command]
+pub fn get_audit_log(range: Option<AuditRange>, state: State<'_, AppState>) -> Result<Vec<AuditEntry>, String> {
+    state.read_audit(&range.unwrap_or_default())
+}
+
+// ==========================================
+// DELETE-ON-REBOOT COMMANDS
+// ==========================================
+
+/// Schedule a locked file for deletion on the next reboot (Windows only)
+#[tauri::command]
+pub fn schedule_delete_on_reboot(path: String, state: State<'_, AppState>) -> Result<JournalEntry, String> {
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    let safety = get_safety_level(path_obj, &state.classifier());
+    if safety == SafetyLevel::Protected {
+        state.record_audit(&[blocked_audit_entry(&path, &safety, "Protected system file")]);
+        return Err("Cannot delete protected system file".to_string());
+    }
+    let size_bytes = path_obj.metadata().map(|m| m.len()).unwrap_or(0);
+
+    let outcome = schedule_reboot_delete(path_obj);
+    let mut audit = AuditEntry::new(AuditAction::Delete, &path, ConfirmedBy::User, AuditOutcome::Success);
+    audit.size_bytes = size_bytes;
+    audit.safety_level = Some(safety);
+    match &outcome {
+        Ok(()) => audit.message = Some("Scheduled for deletion on reboot".to_string()),
+        Err(e) => {
+            audit.outcome = AuditOutcome::Failed;
+            audit.message = Some(e.clone());
+        }
+    }
+    state.record_audit(&[audit]);
+    outcome?;
+
+    let entry = JournalEntry::pending(
+        Uuid::new_v4().to_string(),
+        JournalOperation::RebootDelete { path },
+    );
+    state.update_journal(|journal| journal.entries.push(entry.clone()))?;
+    Ok(entry)
+}
+
+/// List scheduled reboot deletions, resolving which ones completed since the last boot
+#[tauri::command]
+pub fn get_reboot_deletions(state: State<'_, AppState>) -> Result<Vec<JournalEntry>, String> {
+    let boot_time_millis = sysinfo::System::boot_time().saturating_mul(1000);
+    state.update_journal(|journal| {
+        refresh_reboot_deletions(journal, boot_time_millis);
+        journal
+            .entries
+            .iter()
+            .filter(|entry| matches!(entry.operation, JournalOperation::RebootDelete { .. }))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Bulk deletes and moves that were cut short when the app last exited
+#[tauri::command]
+pub fn get_incomplete_operations(state: State<'_, AppState>) -> Result<Vec<IncompleteOperation>, String> {
+    let entries: Vec<JournalEntry> = state.update_journal(|journal| {
+        journal
+            .entries
+            .iter()
+            .filter(|entry| entry.status == JournalStatus::Interrupted)
+            .cloned()
+            .collect()
+    })?;
+    Ok(entries.into_iter().map(IncompleteOperation::new).collect())
+}
+
+/// Resume, roll back, or dismiss an interrupted operation. Resuming a bulk delete runs
+/// the paths it had not reached through bulk_smart_delete, with the same safety checks
+/// and confirmation rules; rolling one back restores what it had trashed.
+#[tauri::command]
+pub fn recover_operation(
+    id: String,
+    action: RecoveryAction,
+    confirmation_token: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RecoveryOutcome, String> {
+    let entry = state
+        .update_journal(|journal| {
+            journal
+                .entries
+                .iter()
+                .find(|entry| entry.id == id && entry.status == JournalStatus::Interrupted)
+                .cloned()
+        })?
+        .ok_or_else(|| format!("No interrupted operation with id {}", id))?;
+    let incomplete = IncompleteOperation::new(entry);
+    let mut outcome = RecoveryOutcome::default();
+    let mut audit_entries = Vec::new();
+
+    let status = match (action, &incomplete.entry.operation) {
+        (RecoveryAction::Dismiss, _) => JournalStatus::Dismissed,
+        (_, JournalOperation::RebootDelete { .. }) => {
+            return Err("Reboot deletions resolve on restart and can't be recovered".to_string());
+        }
+        (RecoveryAction::Resume, JournalOperation::BulkDelete { .. }) => {
+            resume_bulk_delete(&incomplete, confirmation_token, &mut outcome, app_handle, state.clone())
+        }
+        // The archive is checked again before anything more is deleted
+        (RecoveryAction::Resume, JournalOperation::ArchiveDelete { paths, archive }) => {
+            let selected: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+            match archive::verify_zip(&selected, Path::new(archive), |_, _| {}) {
+                Ok(()) => resume_bulk_delete(&incomplete, confirmation_token, &mut outcome, app_handle, state.clone()),
+                Err(e) => {
+                    outcome.errors.push(format!("The archive did not verify, so nothing more was deleted: {}", e));
+                    JournalStatus::Failed
+                }
+            }
+        }
+        (RecoveryAction::RollBack, JournalOperation::BulkDelete { .. } | JournalOperation::ArchiveDelete { .. }) => {
+            match recycle::restore_from_trash(&incomplete.done) {
+                Ok(restored) => {
+                    outcome.errors = incomplete
+                        .done
+                        .iter()
+                        .filter(|path| !restored.contains(path))
+                        .map(|path| format!("Not in the trash: {}", path))
+                        .collect();
+                    outcome.restored = restored;
+                }
+                Err(e) => outcome.errors.push(e),
+            }
+            for path in &outcome.restored {
+                audit_entries.push(AuditEntry::new(AuditAction::Restore, path, ConfirmedBy::User, AuditOutcome::Success));
+            }
+            if outcome.errors.is_empty() { JournalStatus::RolledBack } else { JournalStatus::Failed }
+        }
+        // Rolling a move back is the same rename in the other direction
+        (RecoveryAction::Resume, JournalOperation::Move { from, to })
+        | (RecoveryAction::RollBack, JournalOperation::Move { to: from, from: to }) => {
+            let pending = if action == RecoveryAction::Resume { &incomplete.remaining } else { &incomplete.done };
+            let mut status = if action == RecoveryAction::Resume { JournalStatus::Completed } else { JournalStatus::RolledBack };
+            if !pending.is_empty() {
+                let mut audit = AuditEntry::new(AuditAction::Move, from, ConfirmedBy::User, AuditOutcome::Success);
+                match recycle::move_path(Path::new(from), Path::new(to)) {
+                    Ok(()) if action == RecoveryAction::Resume => outcome.completed.push(from.clone()),
+                    Ok(()) => outcome.restored.push(to.clone()),
+                    Err(e) => {
+                        audit.outcome = AuditOutcome::Failed;
+                        audit.message = Some(e.clone());
+                        outcome.errors.push(e);
+                        status = JournalStatus::Failed;
+                    }
+                }
+                audit_entries.push(audit);
+            }
+            status
+        }
+    };
+    state.record_audit(&audit_entries);
+    outcome.entry = state.update_journal(|journal| {
+        let entry = journal.entries.iter_mut().find(|entry| entry.id == id)?;
+        entry.set_status(status);
+        Some(entry.clone())
+    })?;
+    Ok(outcome)
+}
+
+/// Run the paths an interrupted bulk delete had not reached through bulk_smart_delete
+fn resume_bulk_delete(
+    incomplete: &IncompleteOperation,
+    confirmation_token: Option<String>,
+    outcome: &mut RecoveryOutcome,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> JournalStatus {
+    let result = bulk_smart_delete(
+        incomplete.remaining.clone(),
+        confirmation_token,
+        None,
+        None,
+        None,
+        app_handle,
+        state,
+    );
+    outcome.completed = incomplete
+        .remaining
+        .iter()
+        .filter(|path| !Path::new(path).exists())
+        .cloned()
+        .collect();
+    outcome.errors = result.errors;
+    if result.success { JournalStatus::Completed } else { JournalStatus::Failed }
+}
+
+// ==========================================
+// PERMISSION-FIX DELETE COMMANDS
+// ==========================================
+
+/// Issue the token `delete_with_acl_fix` needs for `path`. Call this after the user
+/// agrees to take ownership of it; any path that isn't protected can be confirmed.
+#[tauri::command]
+pub fn request_acl_fix_confirmation(path: String, state: State<'_, AppState>) -> Result<ConfirmationToken, String> {
+    issue_acl_fix_confirmation(&path, &state)
+}
+
+fn issue_acl_fix_confirmation(path: &str, state: &AppState) -> Result<ConfirmationToken, String> {
+    let path_obj = Path::new(path);
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    if get_safety_level(path_obj, &state.classifier()) == SafetyLevel::Protected {
+        return Err("Cannot delete protected system file".to_string());
+    }
+    state.issue_confirmation(vec![acl_fix_confirmation_key(path)])
+}
+
+/// What an ACL-fix confirmation covers, kept apart from delete tokens for the same path
+fn acl_fix_confirmation_key(path: &str) -> String {
+    format!("fix permissions of {}", path)
+}
+
+/// Use up the ACL-fix token for `path` and check the fix may go ahead, returning the
+/// path's safety level
+fn check_acl_fix(path: &str, confirmation_token: &str, state: &AppState) -> Result<SafetyLevel, String> {
+    let path_obj = Path::new(path);
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    state.take_confirmation(confirmation_token, &acl_fix_confirmation_key(path))?;
+    let safety = get_safety_level(path_obj, &state.classifier());
+    if safety == SafetyLevel::Protected {
+        state.record_audit(&[blocked_audit_entry(path, &safety, "Protected system file")]);
+        return Err("Cannot delete protected system file".to_string());
+    }
+    if !is_elevated() {
+        return Err("Administrator rights are required; restart DiskSight as administrator".to_string());
+    }
+    Ok(safety)
+}
+
+/// Take ownership of a path, grant delete rights, then delete it.
+/// Requires a token from `request_acl_fix_confirmation`, used up here, and an
+/// elevated process; every ACL change is audited.
+#[tauri::command]
+pub fn delete_with_acl_fix(
+    path: String,
+    confirmation_token: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DeleteResult, String> {
+    let path_obj = Path::new(&path);
+    let safety = check_acl_fix(&path, &confirmation_token, &state)?;
+
+    let changes = fix_permissions(path_obj);
+    let audit_entries: Vec<AuditEntry> = changes
+        .iter()
+        .map(|change| {
+            let outcome = if change.success { AuditOutcome::Success } else { AuditOutcome::Failed };
+            let mut entry = AuditEntry::new(AuditAction::AclChange, &change.path, ConfirmedBy::User, outcome);
+            entry.safety_level = Some(safety.clone());
+            entry.message = Some(match &change.message {
+                Some(message) => format!("{}: {}", change.step, message),
+                None => change.step.clone(),
+            });
+            entry
+        })
+        .collect();
+    state.record_audit(&audit_entries);
+
+    if let Some(failed) = changes.iter().find(|change| !change.success) {
+        let reason = format!(
+            "Failed to {}: {}",
+            failed.step.replace('_', " "),
+            failed.message.clone().unwrap_or_default()
+        );
+        emit_delete_failed(&app_handle, DeleteFailedPayload {
+            path: path.clone(),
+            reason: reason.clone(),
+        });
+        return Err(reason);
+    }
+
+    let totals = measure_path(path_obj);
+    let outcome = match recycle::trash_plan(path_obj, totals.bytes, state.settings().trash_fallback) {
+        TrashPlan::Delete(mode) => smart_delete_file(
+            path_obj,
+            true,
+            &state.classifier(),
+            &mut Throttle::unlimited(),
+            ErrorPolicy::FailFast,
+            Some(totals),
+            mode,
+        ),
+        TrashPlan::Quarantine => return quarantine_path(&path, Some(totals), None, &state),
+        TrashPlan::Refuse(reason) => Ok(refused_delete(path_obj, reason, &safety)),
+    };
+    state.record_audit(&[delete_audit_entry(&path, &safety, &outcome)]);
+    match &outcome {
+        Ok(result) if result.success => emit_deleted(&app_handle, DeletedPayload {
+            path: path.clone(),
+            bytes_freed: result.bytes_freed,
+            was_auto: false,
+        }),
+        Ok(result) => {
+            for error in &result.errors {
+                emit_delete_failed(&app_handle, DeleteFailedPayload {
+                    path: path.clone(),
+                    reason: error.clone(),
+                });
+            }
+        }
+        Err(e) => emit_delete_failed(&app_handle, DeleteFailedPayload {
+            path: path.clone(),
+            reason: e.clone(),
+        }),
+    }
+    outcome
+}
+
+// ==========================================
+// JUNK SCORE COMMANDS
+// ==========================================
+
+/// Rank the nodes of a finished scan by junk score, keeping those at or above `min_score`
+#[tauri::command]
+pub fn get_junk_candidates(
+    scan_id: String,
+    min_score: u8,
+    state: State<'_, AppState>,
+) -> Result<Vec<JunkCandidate>, String> {
+    state.with_tree(&scan_id, |tree| TreeScorer::new(tree).candidates(min_score))
+}
+
+// ==========================================
+// DUPLICATE COMMANDS
+// ==========================================
+
+/// Find files with identical content in a finished scan, largest savings first
+#[tauri::command]
+pub fn find_duplicates(
+    scan_id: String,
+    min_size: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let groups = state.update_hash_cache(|cache| {
+        state.with_tree(&scan_id, |tree| find_duplicate_groups(&scan_id, tree, min_size.unwrap_or(1), cache))
+    })??;
+    state.store_duplicate_groups(&scan_id, &groups);
+    Ok(groups)
+}
+
+/// How many file hashes duplicate search has cached between sessions
+#[tauri::command]
+pub fn get_hash_cache_stats(state: State<'_, AppState>) -> Result<HashCacheStats, String> {
+    state.hash_cache_stats()
+}
+
+/// Drop cached hashes of files that were deleted or changed, or every cached hash when
+/// `all` is set
+#[tauri::command]
+pub fn purge_hash_cache(all: Option<bool>, state: State<'_, AppState>) -> Result<HashCacheStats, String> {
+    state.update_hash_cache(|cache| cache.purge(all.unwrap_or(false)))?;
+    state.hash_cache_stats()
+}
+
+/// Files of a scan that look like other copies or renders of the file `node_id`: the
+/// same size, the same cached hash, a similar name, or the same extension in a folder of
+/// the same name. Most alike first.
+#[tauri::command]
+pub fn find_similar(
+    scan_id: String,
+    node_id: NodeId,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SimilarFile>, String> {
+    let limit = limit.unwrap_or(similar::DEFAULT_SIMILAR_LIMIT);
+    state.update_hash_cache(|cache| {
+        state.with_tree(&scan_id, |tree| similar::find_similar(tree, node_id, cache, limit))
+    })??
+}
+
+/// Keep the copies selected by `strategy` and smart-delete the rest.
+/// Copies whose content changed since the group was found are left alone.
+#[tauri::command]
+pub fn resolve_duplicate_group(
+    group_id: String,
+    strategy: KeepStrategy,
+    confirmation_token: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DuplicateResolution, String> {
+    let group = state
+        .get_duplicate_group(&group_id)
+        .ok_or_else(|| format!("Unknown duplicate group: {}", group_id))?;
+    let (keep, remove) = partition_group(&group, &strategy)?;
+
+    if !keep.iter().any(|f| matches_hash(Path::new(&f.path), &group.hash)) {
+        return Err("No kept copy still matches the group's content".to_string());
+    }
+
+    let mut result = DeleteResult {
+        success: true,
+        bytes_freed: 0,
+        files_deleted: 0,
+        folders_deleted: 0,
+        errors: Vec::new(),
+        was_auto_delete: true,
+        failures: Vec::new(),
+        quarantined_to: None,
+    };
+    let mut removed = Vec::new();
+    for file in remove {
+        if !matches_hash(Path::new(&file.path), &group.hash) {
+            result.errors.push(format!("Content changed since scan, skipped: {}", file.path));
+            continue;
+        }
+        match smart_delete(
+            file.path.clone(),
+            confirmation_token.clone(),
+            None,
+            None,
+            None,
+            app_handle.clone(),
+            state.clone(),
+        ) {
+            Ok(deleted) => {
+                if deleted.success {
+                    removed.push(file.id);
+                }
+                result.bytes_freed += deleted.bytes_freed;
+                result.files_deleted += deleted.files_deleted;
+                result.was_auto_delete &= deleted.was_auto_delete;
+                result.errors.extend(deleted.errors);
+                result.failures.extend(deleted.failures);
+            }
+            Err(e) => result.errors.push(format!("{}: {}", file.path, e)),
+        }
+    }
+    result.success = result.errors.is_empty();
+
+    state.prune_nodes(&group.scan_id, &removed)?;
+    let removed_paths: Vec<String> = group
+        .files
+        .iter()
+        .filter(|f| removed.contains(&f.id))
+        .map(|f| f.path.clone())
+        .collect();
+    state.refresh_stamps(&group.scan_id, &removed_paths);
+    state.remove_duplicate_files(&group_id, &removed);
+
+    Ok(DuplicateResolution {
+        group_id,
+        kept: keep.iter().map(|f| f.path.clone()).collect(),
+        result,
+    })
+}
+
+// ==========================================
+// ARCHIVE COMMANDS
+// ==========================================
+
+/// List the contents and uncompressed size of a zip/7z/tar(.gz) archive without extracting it
+#[tauri::command]
+pub fn inspect_archive(path: String) -> Result<ArchiveListing, String> {
+    list_archive(Path::new(&path))
+}
+
+/// Extract only the selected entries of an archive into `dest`
+#[tauri::command]
+pub fn extract_selected(path: String, entries: Vec<String>, dest: String) -> Result<ExtractResult, String> {
+    extract_entries(Path::new(&path), &entries, Path::new(&dest))
+}
+
+/// Shortest gap between two archive progress events
+const ARCHIVE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Zip the selected items into a new archive, verify it, then smart-delete the
+/// originals, journaled as one operation. Progress arrives as `archive://progress`
+/// events. Nothing is deleted unless the archive verifies; an archive that fails
+/// verification is removed again.
+#[tauri::command]
+pub fn archive_then_delete(
+    paths: Vec<String>,
+    archive_path: String,
+    confirmation_token: Option<String>,
+    scan_id: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ArchiveDeleteResult, String> {
+    let archive_file = PathBuf::from(&archive_path);
+    if archive::detect_format(&archive_file) != Some(ArchiveFormat::Zip) {
+        return Err("The archive must be a .zip file".to_string());
+    }
+    if paths.is_empty() {
+        return Err("No items were chosen".to_string());
+    }
+    let classifier = state.classifier();
+    for path in &paths {
+        let path_obj = Path::new(path);
+        if !path_obj.exists() {
+            return Err(format!("Path does not exist: {}", path));
+        }
+        if archive_file.starts_with(path_obj) {
+            return Err(format!("The archive can't be saved inside {}, which is being deleted", path));
+        }
+        match get_safety_level(path_obj, &classifier) {
+            SafetyLevel::Protected => return Err(format!("Cannot delete protected path: {}", path)),
+            SafetyLevel::ConfirmRequired => {
+                let token = confirmation_token.as_deref().ok_or_else(|| format!("Requires confirmation: {}", path))?;
+                state.check_delete_confirmation(token, path, &SafetyLevel::ConfirmRequired)?;
+            }
+            _ => {}
+        }
+    }
+
+    let journal_id = journal_start(
+        &state,
+        JournalOperation::ArchiveDelete {
+            paths: paths.clone(),
+            archive: archive_path.clone(),
+        },
+    );
+    let selected: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let emit_progress = |phase: ArchivePhase| {
+        let app_handle = app_handle.clone();
+        let archive_path = archive_path.clone();
+        let mut last = None::<Instant>;
+        move |bytes_done: u64, bytes_total: u64| {
+            if last.is_some_and(|at| at.elapsed() < ARCHIVE_PROGRESS_INTERVAL) && bytes_done < bytes_total {
+                return;
+            }
+            last = Some(Instant::now());
+            let payload = ArchiveProgressPayload {
+                archive_path: archive_path.clone(),
+                phase,
+                bytes_done,
+                bytes_total,
+            };
+            let _ = app_handle.emit(EVENT_ARCHIVE_PROGRESS, payload);
+        }
+    };
+    let verified = archive::write_zip(&selected, &archive_file, emit_progress(ArchivePhase::Archiving))
+        .and_then(|summary| {
+            archive::verify_zip(&selected, &archive_file, emit_progress(ArchivePhase::Verifying))
+                .map_err(|e| {
+                    let _ = fs::remove_file(&archive_file);
+                    format!("The archive did not verify, so nothing was deleted: {}", e)
+                })
+                .map(|_| summary)
+        });
+    let summary = match verified {
+        Ok(summary) => summary,
+        Err(e) => {
+            journal_finish(&state, journal_id.as_deref(), false);
+            return Err(e);
+        }
+    };
+
+    emit_progress(ArchivePhase::Deleting)(0, summary.bytes);
+    let batch = DeleteBatch {
+        throttle: None,
+        error_policy: None,
+        scan_id,
+        journal_id,
+    };
+    let delete = run_bulk_delete(paths, confirmation_token, batch, app_handle.clone(), state);
+    Ok(ArchiveDeleteResult {
+        archive_path,
+        summary,
+        delete,
+    })
+}
+
+// ==========================================
+// COMPRESSION COMMANDS
+// ==========================================
+
+/// Estimate how well a file would compress by zstd-compressing a few sampled blocks
+#[tauri::command]
+pub fn estimate_compressibility(path: String) -> Result<CompressibilityEstimate, String> {
+    compress::estimate_compressibility(Path::new(&path))
+}
+
+/// Compress a file or folder in place with NTFS compression instead of deleting it
+#[tauri::command]
+pub fn apply_ntfs_compression(path: String, state: State<'_, AppState>) -> Result<CompressionResult, String> {
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    let safety = get_safety_level(path_obj, &state.classifier());
+    if safety == SafetyLevel::Protected {
+        state.record_audit(&[AuditEntry {
+            action: AuditAction::Compress,
+            ..blocked_audit_entry(&path, &safety, "Protected system file")
+        }]);
+        return Err("Cannot compress protected system file".to_string());
+    }
+
+    let outcome = compress::apply_ntfs_compression(path_obj);
+    let mut entry = AuditEntry::new(
+        AuditAction::Compress,
+        &path,
+        ConfirmedBy::User,
+        if outcome.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failed },
+    );
+    entry.safety_level = Some(safety);
+    match &outcome {
+        Ok(result) => {
+            entry.size_bytes = result.before_bytes;
+            entry.message = Some(format!("{} -> {} bytes on disk", result.before_bytes, result.after_bytes));
+        }
+        Err(e) => entry.message = Some(e.clone()),
+    }
+    state.record_audit(&[entry]);
+    outcome
+}
+
+// ==========================================
+// COPY-ON-WRITE FILESYSTEM COMMANDS
+// ==========================================
+
+/// Compressed, referenced, and snapshot usage for a path on Btrfs or ZFS
+#[tauri::command]
+pub fn get_cow_usage(path: String) -> Result<CowUsage, String> {
+    cow_usage(Path::new(&path))
+}
+
+// ==========================================
+// SPACE RECONCILIATION COMMANDS
+// ==========================================
+
+/// Break down the difference between the volume's used space and what a scan found
+#[tauri::command]
+pub fn explain_missing_space(scan_id: String, state: State<'_, AppState>) -> Result<SpaceReconciliation, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    state.with_tree(&scan_id, |tree| reconcile::explain_missing_space(&result, tree))?
+}
+
+/// Split the used space of the volume holding `path` into trash, shadow copies,
+/// hibernation and paging files, and the files found by the broadest stored scan of it
+#[tauri::command]
+pub fn get_root_breakdown(path: String, state: State<'_, AppState>) -> Result<RootBreakdown, String> {
+    let path = Path::new(&path);
+    let mount_point = volume_space(path)
+        .map(|volume| volume.mount_point)
+        .ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    let scan = state
+        .scan_roots()
+        .into_iter()
+        .filter(|(_, root)| {
+            Path::new(root).starts_with(&mount_point)
+                && volume_space(Path::new(root)).is_some_and(|volume| volume.mount_point == mount_point)
+        })
+        .filter_map(|(scan_id, root)| Some((state.get_result(&scan_id)?, root.len())))
+        .min_by_key(|(result, root_len)| (*root_len, std::cmp::Reverse(result.started_at)));
+    match scan {
+        Some((result, _)) => {
+            state.with_tree(&result.scan_id, |tree| reconcile::root_breakdown(path, Some((&result, tree))))?
+        }
+        None => reconcile::root_breakdown(path, None),
+    }
+}
+
+// ==========================================
+// SHADOW COPY COMMANDS
+// ==========================================
+
+/// Shadow copy (System Restore) storage used and reserved per volume
+#[tauri::command]
+pub fn get_shadow_storage() -> Result<Vec<ShadowStorage>, String> {
+    shadow::list_shadow_storage()
+}
+
+/// Issue a short-lived token authorizing a shadow storage resize of `volume`.
+/// Call this after the user confirms that old restore points may be lost.
+#[tauri::command]
+pub fn request_shadow_resize_confirmation(
+    volume: String,
+    state: State<'_, AppState>,
+) -> Result<ConfirmationToken, String> {
+    if shadow::shadow_storage_for(&volume).is_none() {
+        return Err(format!("No shadow storage is configured for {}", volume));
+    }
+    state.issue_confirmation(vec![volume])
+}
+
+/// Change the shadow storage limit of a volume. Needs elevation and a confirmation
+/// token; shrinking below current usage deletes the oldest restore points.
+#[tauri::command]
+pub fn resize_shadow_storage(
+    volume: String,
+    max_bytes: u64,
+    confirmation_token: String,
+    state: State<'_, AppState>,
+) -> Result<ShadowStorage, String> {
+    state.validate_confirmation(&confirmation_token, &volume)?;
+    if !is_elevated() {
+        return Err("Resizing shadow storage requires running as administrator".to_string());
+    }
+    let before = shadow::shadow_storage_for(&volume);
+    let outcome = shadow::resize_shadow_storage(&volume, max_bytes);
+    let mut entry = AuditEntry::new(
+        AuditAction::ResizeShadowStorage,
+        &volume,
+        ConfirmedBy::User,
+        if outcome.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failed },
+    );
+    match &outcome {
+        Ok(after) => {
+            entry.size_bytes = before.map(|b| b.used_bytes.saturating_sub(after.used_bytes)).unwrap_or(0);
+            entry.message = Some(format!("Maximum set to {} bytes", max_bytes));
+        }
+        Err(e) => entry.message = Some(e.clone()),
+    }
+    state.record_audit(&[entry]);
+    outcome
+}
+
+// ============================================================================
+// TRASH CAPACITY COMMANDS
+// ============================================================================
+
+/// Check whether each path fits in its volume's Recycle Bin before trashing it
+#[tauri::command]
+pub fn check_trash_capacity(
+    paths: Vec<String>,
+    scan_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashFit>, String> {
+    paths
+        .iter()
+        .map(|path| {
+            let size = scanned_totals(&state, scan_id.as_deref(), path)
+                .map(|t| t.bytes)
+                .unwrap_or_else(|| measure_path(Path::new(path)).bytes);
+            recycle::trash_fit(Path::new(path), size)
+        })
+        .collect()
+}
+
+// ============================================================================
+// RULE PACK COMMANDS
+// ============================================================================
+
+/// Check a classification rule pack file for errors without installing it
+#[tauri::command]
+pub fn validate_rule_pack(path: String) -> RulePackReport {
+    rules::validate_rule_pack(Path::new(&path))
+}
+
+/// Re-read the rule packs in the user rules dir now instead of waiting for the watcher
+#[tauri::command]
+pub fn reload_rule_packs(state: State<'_, AppState>) -> Vec<RulePackReport> {
+    state.reload_rules()
+}
+
+// ============================================================================
+// BACKGROUND AGENT COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_agent_settings(state: State<'_, AppState>) -> AgentSettings {
+    state.settings().agent
+}
+
+/// Save the agent settings, showing or removing the tray icon and registering or
+/// unregistering the login item to match
+#[tauri::command]
+pub fn set_agent_settings(
+    settings: AgentSettings,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AgentSettings, String> {
+    if let Some(window) = &settings.run_window {
+        window.validate()?;
+    }
+    let autolaunch = app_handle.autolaunch();
+    if autolaunch.is_enabled().map_err(|e| e.to_string())? != settings.autostart {
+        let toggled = if settings.autostart {
+            autolaunch.enable()
+        } else {
+            autolaunch.disable()
+        };
+        toggled.map_err(|e| e.to_string())?;
+    }
+    if settings.run_in_tray {
+        tray::create_tray(&app_handle).map_err(|e| e.to_string())?;
+    } else {
+        tray::remove_tray(&app_handle);
+    }
+    let saved = state
+        .update_settings(|current| {
+            // Keep the schedule's last run unless the schedule itself changed
+            let last_run_at = current
+                .agent
+                .scheduled_scan
+                .as_ref()
+                .filter(|old| {
+                    settings
+                        .scheduled_scan
+                        .as_ref()
+                        .is_some_and(|new| new.root_path == old.root_path && new.every_hours == old.every_hours)
+                })
+                .and_then(|old| old.last_run_at);
+            current.agent = settings;
+            if let Some(schedule) = current.agent.scheduled_scan.as_mut() {
+                schedule.last_run_at = last_run_at;
+            }
+        })
+        .map(|settings| settings.agent)?;
+    tray::refresh(&app_handle, &agent::agent_status(&state));
+    Ok(saved)
+}
+
+/// Quick stats shown in the tray: last scan, space freed since, and lowest free space
+#[tauri::command]
+pub fn get_agent_status(state: State<'_, AppState>) -> AgentStatus {
+    agent::agent_status(&state)
+}
+
+// ============================================================================
+// NOTIFICATION COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_notification_settings(state: State<'_, AppState>) -> NotificationSettings {
+    state.settings().notifications
+}
+
+/// Turn native notifications on or off, overall or per event
+#[tauri::command]
+pub fn set_notification_settings(
+    settings: NotificationSettings,
+    state: State<'_, AppState>,
+) -> Result<NotificationSettings, String> {
+    state
+        .update_settings(|current| current.notifications = settings)
+        .map(|settings| settings.notifications)
+}
+
+// ============================================================================
+// SHELL INTEGRATION COMMANDS
+// ============================================================================
+
+/// The folder the app was launched to scan, if any; cleared once taken
+#[tauri::command]
+pub fn take_scan_request(request: State<'_, LaunchRequest>) -> Option<String> {
+    request.take()
+}
+
+#[tauri::command]
+pub fn get_context_menu_registered() -> bool {
+    shell::is_context_menu_registered()
+}
+
+/// Add or remove "Analyze with DiskSight" in the Explorer folder context menu
+#[tauri::command]
+pub fn set_context_menu_registered(enabled: bool) -> Result<bool, String> {
+    if enabled {
+        shell::register_context_menu()?;
+    } else {
+        shell::unregister_context_menu()?;
+    }
+    Ok(shell::is_context_menu_registered())
+}
+
+// ============================================================================
+// EXPORT COMMANDS
+// ============================================================================
+
+/// Export every node of a finished scan to a file for analysis in other tools. With
+/// `anonymize`, file and folder names are replaced so the export can be shared
+/// publicly; structure, sizes, times, and extensions are kept.
+#[tauri::command]
+pub fn export_scan(
+    scan_id: String,
+    format: ExportFormat,
+    dest_path: String,
+    filter: Option<NodeFilter>,
+    anonymize: Option<Anonymization>,
+    state: State<'_, AppState>,
+) -> Result<ExportResult, String> {
+    let query = filter.map(|filter| NodeQuery::new(filter, state.classifier())).transpose()?;
+    state.with_tree(&scan_id, |tree| {
+        export_tree(tree, format, Path::new(&dest_path), query.as_ref(), anonymize)
+    })?
+}
+
+// ============================================================================
+// LOCAL API COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_api_settings(state: State<'_, AppState>) -> ApiSettings {
+    state.settings().api
+}
+
+/// The bearer token scrapers must send to the local metrics endpoint
+#[tauri::command]
+pub fn get_api_token(state: State<'_, AppState>) -> Result<String, String> {
+    api_token(state.data_dir())
+}
+
+/// Start, stop, or move the local metrics endpoint, saving the settings only once the
+/// server matches them
+#[tauri::command]
+pub fn set_api_settings(
+    settings: ApiSettings,
+    server: State<'_, ApiServer>,
+    state: State<'_, AppState>,
+) -> Result<ApiSettings, String> {
+    server.apply(&settings, &state)?;
+    state
+        .update_settings(|current| current.api = settings)
+        .map(|settings| settings.api)
+}
+
+// ============================================================================
+// REPORT COMMANDS
+// ============================================================================
+
+/// A text, Markdown, or CSV summary of one node for the clipboard: path, size, largest
+/// children, and extension mix
+#[tauri::command]
+pub fn get_node_report(
+    scan_id: String,
+    node_id: NodeId,
+    format: ReportFormat,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    state.with_tree(&scan_id, |tree| node_report(tree, node_id, format))?
+}
+
+// ============================================================================
+// OWNERSHIP COMMANDS
+// ============================================================================
+
+/// Bytes and files per owner, largest first. Needs the scan to have run with the
+/// `owner_usage` analyzer enabled.
+#[tauri::command]
+pub fn get_usage_by_owner(scan_id: String, state: State<'_, AppState>) -> Result<Vec<OwnerUsage>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::OwnerUsage { owners } => Some(owners),
+            _ => None,
+        })
+        .ok_or_else(|| "Owner collection was not enabled for this scan".to_string())
+}
+
+// ============================================================================
+// POLICY COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_policies(state: State<'_, AppState>) -> Vec<Policy> {
+    state.settings().policies
+}
+
+/// Replace the saved policies, giving new ones an id. Each run time is kept from the
+/// saved copy so editing a policy doesn't make it due again.
+#[tauri::command]
+pub fn set_policies(mut policies: Vec<Policy>, state: State<'_, AppState>) -> Result<Vec<Policy>, String> {
+    for policy in policies.iter_mut() {
+        policy::validate_policy(policy)?;
+        if policy.id.is_empty() {
+            policy.id = Uuid::new_v4().to_string();
+        }
+    }
+    state
+        .update_settings(|settings| {
+            for policy in policies.iter_mut() {
+                if let Some(saved) = settings.policies.iter().find(|saved| saved.id == policy.id) {
+                    policy.last_run_at = saved.last_run_at;
+                }
+            }
+            settings.policies = policies;
+        })
+        .map(|settings| settings.policies)
+}
+
+/// What a policy would remove right now, without removing anything
+#[tauri::command]
+pub fn preview_policy(policy: Policy, state: State<'_, AppState>) -> Result<PolicyRun, String> {
+    policy::validate_policy(&policy)?;
+    policy::run_policy(&policy, true, &state)
+}
+
+/// Run a saved policy immediately, whether or not it is enabled or due
+#[tauri::command]
+pub fn run_policy_now(policy_id: String, state: State<'_, AppState>) -> Result<PolicyRun, String> {
+    let policy = state
+        .settings()
+        .policies
+        .into_iter()
+        .find(|policy| policy.id == policy_id)
+        .ok_or_else(|| format!("No policy with id {}", policy_id))?;
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    let run = policy::run_policy(&policy, false, &state);
+    policy::mark_ran(&state, &policy.id, started_at);
+    run
+}
+
+/// Reports of past policy runs, newest first
+#[tauri::command]
+pub fn get_policy_runs(
+    policy_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PolicyRun>, String> {
+    let Some(dir) = state.data_dir() else {
+        return Ok(Vec::new());
+    };
+    policy::read_runs(dir, policy_id.as_deref(), limit)
+}
+
+// ============================================================================
+// BOOKMARK COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_bookmarks(state: State<'_, AppState>) -> Vec<Bookmark> {
+    state.settings().bookmarks
+}
+
+/// Bookmark a folder; bookmarking it again only updates the label
+#[tauri::command]
+pub fn add_bookmark(path: String, label: Option<String>, state: State<'_, AppState>) -> Result<Vec<Bookmark>, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    state
+        .update_settings(|settings| {
+            match settings.bookmarks.iter_mut().find(|bookmark| bookmark.path == path) {
+                Some(bookmark) => bookmark.label = label,
+                None => settings.bookmarks.push(Bookmark::new(path, label)),
+            }
+        })
+        .map(|settings| settings.bookmarks)
+}
+
+#[tauri::command]
+pub fn remove_bookmark(path: String, state: State<'_, AppState>) -> Result<Vec<Bookmark>, String> {
+    state
+        .update_settings(|settings| settings.bookmarks.retain(|bookmark| bookmark.path != path))
+        .map(|settings| settings.bookmarks)
+}
+
+/// Scan just the bookmarked folders, one after another, and remember each size so the
+/// next rescan can report growth
+#[tauri::command]
+pub fn rescan_bookmarks(state: State<'_, AppState>) -> Result<Vec<BookmarkSummary>, String> {
+    let summaries: Vec<BookmarkSummary> = state.settings().bookmarks.iter().map(bookmarks::rescan).collect();
+    state.update_settings(|settings| {
+        for summary in summaries.iter().filter(|summary| summary.error.is_none()) {
+            if let Some(bookmark) = settings.bookmarks.iter_mut().find(|b| b.path == summary.path) {
+                bookmark.last_bytes = Some(summary.total_bytes);
+                bookmark.last_scanned_at = Some(summary.scanned_at);
+            }
+        }
+    })?;
+    Ok(summaries)
+}
+
+// ============================================================================
+// TAG COMMANDS
+// ============================================================================
+
+/// Set the tags and note on a path, replacing any previous ones. Returns None when both
+/// are empty and the annotation was removed.
+#[tauri::command]
+pub fn set_node_tag(
+    path: String,
+    tags: Vec<String>,
+    note: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Annotation>, String> {
+    state.update_tags(|store| store.set(&path, tags, note))
+}
+
+/// Every annotated path that is part of a finished scan, with its node id
+#[tauri::command]
+pub fn get_tags_for_scan(scan_id: String, state: State<'_, AppState>) -> Result<Vec<TaggedNode>, String> {
+    state.with_tags(|store| state.with_tree(&scan_id, |tree| store.for_tree(tree)))?
+}
+
+// ============================================================================
+// AGE HISTOGRAM COMMANDS
+// ============================================================================
+
+/// Bytes by last-modified age for the whole scan (with one histogram per top-level
+/// directory), or for any node and its subdirectories. Needs the scan to have run with
+/// the `age_histogram` analyzer.
+#[tauri::command]
+pub fn get_age_histogram(
+    scan_id: String,
+    node_id: Option<NodeId>,
+    state: State<'_, AppState>,
+) -> Result<AgeHistogramView, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    let (buckets, directories) = result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::AgeHistogram { buckets, directories } => Some((buckets, directories)),
+            _ => None,
+        })
+        .ok_or_else(|| "The age histogram was not enabled for this scan".to_string())?;
+    match node_id {
+        Some(node_id) if node_id != result.root_id => {
+            state.with_tree(&scan_id, |tree| age_histogram_view(tree, node_id, SystemTime::now()))?
+        }
+        _ => Ok(AgeHistogramView {
+            node_id: result.root_id,
+            buckets,
+            directories,
+        }),
+    }
+}
+
+// ============================================================================
+// SIZE DISTRIBUTION COMMANDS
+// ============================================================================
+
+/// Bytes and file counts by file size below one node; the whole-scan distribution is
+/// part of the scan summary
+#[tauri::command]
+pub fn get_size_distribution(
+    scan_id: String,
+    node_id: NodeId,
+    state: State<'_, AppState>,
+) -> Result<Vec<SizeBucket>, String> {
+    state.with_tree(&scan_id, |tree| size_distribution(tree, node_id))?
+}
+
+/// Extension and category breakdown of an arbitrary selection, such as the user's
+/// multi-select, given as node ids, paths, or both
+#[tauri::command]
+pub fn get_extension_stats_for(
+    scan_id: String,
+    node_ids: Option<Vec<NodeId>>,
+    paths: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<SelectionStats, String> {
+    let node_ids = node_ids.unwrap_or_default();
+    let paths = paths.unwrap_or_default();
+    if node_ids.is_empty() && paths.is_empty() {
+        return Err("Nothing is selected".to_string());
+    }
+    state.with_tree(&scan_id, |tree| selection_stats(tree, &node_ids, &paths))
+}
+
+// ============================================================================
+// HIGH ENTROPY COMMANDS
+// ============================================================================
+
+/// Large files that look encrypted or corrupted, largest first. Needs the scan to have
+/// run with the `high_entropy` analyzer; `min_size` narrows its results further.
+#[tauri::command]
+pub fn find_high_entropy_files(
+    scan_id: String,
+    min_size: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<HighEntropyFile>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    let files = result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::HighEntropy { files } => Some(files),
+            _ => None,
+        })
+        .ok_or_else(|| "High-entropy detection was not enabled for this scan".to_string())?;
+    let min_size = min_size.unwrap_or(0);
+    Ok(files.into_iter().filter(|file| file.size_bytes >= min_size).collect())
+}
+
+// ============================================================================
+// EXECUTABLE INVENTORY COMMANDS
+// ============================================================================
+
+/// Executables and installers found outside install locations, those in temp folders
+/// first. Needs the scan to have run with the `executables` analyzer.
+#[tauri::command]
+pub fn get_executable_inventory(scan_id: String, state: State<'_, AppState>) -> Result<Vec<ExecutableFile>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::Executables { files } => Some(files),
+            _ => None,
+        })
+        .ok_or_else(|| "The executable inventory was not enabled for this scan".to_string())
+}
+
+// ============================================================================
+// BASELINE COMMANDS
+// ============================================================================
+
+/// Make a finished scan the baseline for its root, replacing any earlier one.
+/// Scheduled scans of that root are then compared with it.
+#[tauri::command]
+pub fn set_baseline(scan_id: String, state: State<'_, AppState>) -> Result<BaselineInfo, String> {
+    let dir = state.data_dir().ok_or_else(|| "No data directory for baselines".to_string())?;
+    let captured = state.with_tree(&scan_id, |tree| Baseline::capture(&scan_id, tree))??;
+    let info = captured.info();
+    let mut baselines = baseline::load_baselines(dir);
+    baselines.retain(|existing| existing.root_path != captured.root_path);
+    baselines.push(captured);
+    baseline::save_baselines(dir, &baselines)?;
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_baselines(state: State<'_, AppState>) -> Result<Vec<BaselineInfo>, String> {
+    let dir = state.data_dir().ok_or_else(|| "No data directory for baselines".to_string())?;
+    Ok(baseline::load_baselines(dir).iter().map(Baseline::info).collect())
+}
+
+#[tauri::command]
+pub fn remove_baseline(root_path: String, state: State<'_, AppState>) -> Result<Vec<BaselineInfo>, String> {
+    let dir = state.data_dir().ok_or_else(|| "No data directory for baselines".to_string())?;
+    let mut baselines = baseline::load_baselines(dir);
+    baselines.retain(|existing| existing.root_path != root_path);
+    baseline::save_baselines(dir, &baselines)?;
+    Ok(baselines.iter().map(Baseline::info).collect())
+}
+
+/// Compare any finished scan with the baseline of its root, using the saved thresholds
+#[tauri::command]
+pub fn compare_to_baseline(scan_id: String, state: State<'_, AppState>) -> Result<DriftReport, String> {
+    let dir = state.data_dir().ok_or_else(|| "No data directory for baselines".to_string())?;
+    let baselines = baseline::load_baselines(dir);
+    let thresholds = state.settings().drift;
+    state.with_tree(&scan_id, |tree| {
+        let root_path = tree.root().map(|root| root.path.clone()).unwrap_or_default();
+        let found = baselines
+            .iter()
+            .find(|existing| existing.root_path == root_path)
+            .ok_or_else(|| format!("No baseline set for {}", root_path))?;
+        Ok(baseline::compare(found, &scan_id, tree, &thresholds))
+    })?
+}
+
+/// Which directories of a root grew most per day or week, from the diffs of its
+/// consecutive scheduled scans, optionally only from `since` (Unix millis) on
+#[tauri::command]
+pub fn get_growth_report(
+    root: String,
+    range: GrowthRange,
+    since: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<GrowthReport, String> {
+    let dir = state.data_dir().ok_or_else(|| "No data directory for growth history".to_string())?;
+    let history = growth::load_growth_history(dir);
+    // Scans record their canonical root, which the requested path may only alias
+    let root_path = normalize_root(&root)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok()
+        .filter(|path| history.contains_key(path))
+        .unwrap_or(root);
+    Ok(growth::growth_report(&history, &root_path, range, since))
+}
+
+#[tauri::command]
+pub fn get_drift_thresholds(state: State<'_, AppState>) -> DriftThresholds {
+    state.settings().drift
+}
+
+#[tauri::command]
+pub fn set_drift_thresholds(thresholds: DriftThresholds, state: State<'_, AppState>) -> Result<DriftThresholds, String> {
+    state
+        .update_settings(|settings| settings.drift = thresholds)
+        .map(|settings| settings.drift)
+}
+
+// ============================================================================
+// VIRTUAL DISK COMMANDS
+// ============================================================================
+
+/// VM disk images found by the scan, largest on disk first. Needs the scan to have run
+/// with the `vm_disks` analyzer.
+#[tauri::command]
+pub fn get_vm_disks(scan_id: String, state: State<'_, AppState>) -> Result<Vec<VmDiskImage>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::VmDisks { disks } => Some(disks),
+            _ => None,
+        })
+        .ok_or_else(|| "VM disk detection was not enabled for this scan".to_string())
+}
+
+/// Shrink a detached VHD/VHDX in place with Optimize-VHD
+#[tauri::command]
+pub fn compact_vm_disk(path: String, state: State<'_, AppState>) -> Result<CompressionResult, String> {
+    let path_obj = Path::new(&path);
+    if !path_obj.is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+    let safety = get_safety_level(path_obj, &state.classifier());
+    if safety == SafetyLevel::Protected {
+        state.record_audit(&[AuditEntry {
+            action: AuditAction::CompactDisk,
+            ..blocked_audit_entry(&path, &safety, "Protected system file")
+        }]);
+        return Err("Cannot compact protected system file".to_string());
+    }
+
+    let outcome = vmdisks::compact_vm_disk(path_obj);
+    let mut entry = AuditEntry::new(
+        AuditAction::CompactDisk,
+        &path,
+        ConfirmedBy::User,
+        if outcome.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failed },
+    );
+    entry.safety_level = Some(safety);
+    match &outcome {
+        Ok(result) => {
+            entry.size_bytes = result.before_bytes;
+            entry.message = Some(format!("{} -> {} bytes", result.before_bytes, result.after_bytes));
+        }
+        Err(e) => entry.message = Some(e.clone()),
+    }
+    state.record_audit(&[entry]);
+    outcome
+}
+
+// ============================================================================
+// BROWSER PROFILE COMMANDS
+// ============================================================================
+
+/// Storage of each browser profile found by the scan, split by kind, largest first.
+/// Needs the scan to have run with the `browser_profiles` analyzer.
+#[tauri::command]
+pub fn get_browser_storage(scan_id: String, state: State<'_, AppState>) -> Result<Vec<BrowserProfileUsage>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::BrowserProfiles { profiles } => Some(profiles),
+            _ => None,
+        })
+        .ok_or_else(|| "Browser profile analysis was not enabled for this scan".to_string())
+}
+
+/// Per-user application data by application, largest first, with how much of each is
+/// cache. Needs the scan to have run with the `app_data` analyzer.
+#[tauri::command]
+pub fn get_app_data_usage(scan_id: String, state: State<'_, AppState>) -> Result<Vec<AppDataUsage>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::AppData { apps } => Some(apps),
+            _ => None,
+        })
+        .ok_or_else(|| "App data analysis was not enabled for this scan".to_string())
+}
+
+/// GPU driver and Steam shader caches, largest first. They are rebuilt on the next
+/// launch, so the whole of each is safe to clear. Needs the `shader_caches` analyzer.
+#[tauri::command]
+pub fn get_shader_caches(scan_id: String, state: State<'_, AppState>) -> Result<Vec<ShaderCacheUsage>, String> {
+    let result = state
+        .get_result(&scan_id)
+        .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+    result
+        .analyzer_reports
+        .into_iter()
+        .find_map(|report| match report {
+            AnalyzerReport::ShaderCaches { caches } => Some(caches),
+            _ => None,
+        })
+        .ok_or_else(|| "Shader cache analysis was not enabled for this scan".to_string())
+}
+
+// ============================================================================
+// LARGE DOCUMENT COMMANDS
+// ============================================================================
+
+/// Office, PDF, and design files of at least `min_size` (10 MB by default), grouped by
+/// folder and age for the cleanup suggestions view
+#[tauri::command]
+pub fn find_documents(
+    scan_id: String,
+    min_size: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentGroup>, String> {
+    state.with_tree(&scan_id, |tree| {
+        find_large_documents(tree, min_size.unwrap_or(DEFAULT_DOCUMENT_MIN_BYTES))
+    })
+}
+
+// ============================================================================
+// MEMORY COMMANDS
+// ============================================================================
+
+/// Estimated memory held by each stored scan, running scans, and caches
+#[tauri::command]
+pub fn get_memory_usage(state: State<'_, AppState>) -> MemoryUsage {
+    state.memory_usage()
+}
+
+/// Free a finished scan's tree, result, and caches; its scan id stops working
+#[tauri::command]
+pub fn release_scan(scan_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    if state.release_scan(&scan_id) {
+        Ok(())
+    } else {
+        Err(format!("No finished scan with id {}", scan_id))
+    }
+}
+
+#[tauri::command]
+pub fn get_spill_settings(state: State<'_, AppState>) -> SpillSettings {
+    state.settings().spill
+}
+
+/// Applies to scans finishing from now on; trees already in memory stay there
+#[tauri::command]
+pub fn set_spill_settings(settings: SpillSettings, state: State<'_, AppState>) -> Result<SpillSettings, String> {
+    state
+        .update_settings(|current| current.spill = settings)
+        .map(|current| current.spill)
+}
+
+// ============================================================================
+// QUERY COMMANDS
+// ============================================================================
+
+/// Nodes of a finished scan matching `filter`, largest first, or with the most files
+/// first when `weight` is file count
+#[tauri::command]
+pub fn search_nodes(
+    scan_id: String,
+    filter: NodeFilter,
+    limit: Option<usize>,
+    weight: Option<Weight>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NodeMatch>, String> {
+    let query = NodeQuery::new(filter, state.classifier())?;
+    state.with_tree(&scan_id, |tree| {
+        query.search(tree, limit.unwrap_or(DEFAULT_QUERY_LIMIT), weight.unwrap_or_default())
+    })
+}
+
+/// A node's children ranked by `weight`, bytes unless given, for one treemap level
+#[tauri::command]
+pub fn get_children(
+    scan_id: String,
+    node_id: NodeId,
+    weight: Option<Weight>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NodeMatch>, String> {
+    state
+        .with_tree(&scan_id, |tree| query::children(tree, node_id, weight.unwrap_or_default()))?
+        .ok_or_else(|| "Node not found".to_string())
+}
+
+/// The largest files matching `filter`; unlike the top-files analyzer this works on any
+/// finished scan and any subset of it
+#[tauri::command]
+pub fn get_top_files(
+    scan_id: String,
+    filter: Option<NodeFilter>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NodeMatch>, String> {
+    let filter = NodeFilter {
+        kinds: vec![NodeKind::File],
+        ..filter.unwrap_or_default()
+    };
+    let query = NodeQuery::new(filter, state.classifier())?;
+    state.with_tree(&scan_id, |tree| {
+        query.search(
+            tree,
+            limit.unwrap_or(crate::scan::analyzer::DEFAULT_TOP_FILES_LIMIT),
+            Weight::Bytes,
+        )
+    })
+}
+
+// ============================================================================
+// SCAN REUSE COMMANDS
+// ============================================================================
+
+/// The newest fresh stored scan whose tree already holds `path`, which start_scan can
+/// answer from when passed `reuse`
+#[tauri::command]
+pub fn find_covering_scan(
+    path: String,
+    options: Option<ScanOptions>,
+    max_age_secs: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Option<CoveringScan>, String> {
+    let root = normalize_root(&path)?;
+    Ok(reuse::find_covering_scan(
+        &state,
+        &root,
+        &options.unwrap_or_default(),
+        max_age_secs.unwrap_or(DEFAULT_REUSE_MAX_AGE_SECS),
+    ))
+}
+
+// ============================================================================
+// BENCHMARK COMMANDS
+// ============================================================================
+
+/// Time every backend available here over a sample of `path` and make the fastest
+/// one that sized the sample correctly the default for scans that ask for Auto
+#[tauri::command]
+pub fn run_scan_benchmark(path: String, state: State<'_, AppState>) -> Result<ScanBenchmark, String> {
+    let root = normalize_root(&path)?;
+    let timings = benchmark::run_benchmark(&root, &ScanBackend::available(), BENCHMARK_SAMPLE_ENTRIES);
+    let best = benchmark::best_backend(&timings);
+    let previous_default = state.settings().scan_backend;
+    if let Some(best) = best {
+        state.update_settings(|settings| settings.scan_backend = best)?;
+    }
+    Ok(ScanBenchmark {
+        path: root.to_string_lossy().to_string(),
+        timings,
+        best,
+        previous_default,
+    })
+}
+
+// ============================================================================
+// SETTINGS BUNDLE COMMANDS
+// ============================================================================
+
+/// Write every setting and user rule pack to one JSON file at `path`
+#[tauri::command]
+pub fn export_settings(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let bundle = SettingsBundle::new(&state.settings(), state.rules_dir().as_deref())?;
+    bundle.save(Path::new(&path))
+}
+
+/// Adopt the settings and rule packs of a bundle written by export_settings. The tray,
+/// login item, and local API are switched to match, as when each is set on its own.
+#[tauri::command]
+pub fn import_settings(
+    path: String,
+    app_handle: AppHandle,
+    server: State<'_, ApiServer>,
+    state: State<'_, AppState>,
+) -> Result<SettingsImport, String> {
+    let bundle = SettingsBundle::load(Path::new(&path))?;
+    if let Some(rules_dir) = state.rules_dir() {
+        bundle.install_rule_packs(&rules_dir)?;
+    }
+    let settings = bundle.merged_settings(&state.settings());
+    server.apply(&settings.api, &state)?;
+    set_agent_settings(settings.agent.clone(), app_handle, state.clone())?;
+    let agent = state.settings().agent;
+    state.update_settings(|current| *current = Settings { agent, ..settings })?;
+    Ok(SettingsImport {
+        exported_at: bundle.exported_at,
+        policies: bundle.settings.policies.len(),
+        bookmarks: bundle.settings.bookmarks.len(),
+        rule_packs: state.reload_rules(),
+    })
+}
+
+// ============================================================================
+// MESSAGE CATALOG COMMANDS
+// ============================================================================
+
+/// Every message code the backend sends with its English template, as the source
+/// for translations
+#[tauri::command]
+pub fn get_message_catalog() -> Vec<MessageTemplate> {
+    messages::catalog()
+}
+
+// ============================================================================
+// HOME OVERVIEW COMMANDS
+// ============================================================================
+
+/// The user's profile broken down by standard folder (Documents, Pictures, Videos,
+/// Music, Downloads, Desktop, app caches) in one call. A fresh stored scan covering the
+/// profile answers it; otherwise the profile is scanned with default options and kept
+/// like any finished scan, so the landing screen can drill into it.
+#[tauri::command]
+pub fn get_home_overview(state: State<'_, AppState>) -> Result<HomeOverview, String> {
+    let (home, folders) = user_folders().ok_or("No home folder found")?;
+    let root = normalize_root(&home.to_string_lossy())?;
+    // Compare in the same resolved form as the tree's paths
+    let folders: Vec<(UserFolder, PathBuf)> = folders
+        .into_iter()
+        .map(|(folder, path)| (folder, normalize_root(&path.to_string_lossy()).unwrap_or(path)))
+        .collect();
+    let options = ScanOptions {
+        backend: state.settings().scan_backend,
+        ..ScanOptions::default()
+    };
+    if let Some(covering) = reuse::find_covering_scan(&state, &root, &options, DEFAULT_REUSE_MAX_AGE_SECS) {
+        return state.with_tree(&covering.scan_id, |tree| {
+            home::overview(&covering.scan_id, tree, &root, &folders)
+        })?;
+    }
+
+    let scan_id = Uuid::new_v4().to_string();
+    let (result, tree) = run_scan(
+        None,
+        scan_id.clone(),
+        root.to_string_lossy().to_string(),
+        options,
+        Arc::new(AtomicBool::new(false)),
+    )
+    .map_err(|e| match e {
+        ScanError::Canceled(_) => "Scan canceled".to_string(),
+        ScanError::Failed(message) => message,
+        ScanError::RootLost(path) => format!("{} is no longer available", path),
+    })?;
+    let overview = home::overview(&scan_id, &tree, &root, &folders)?;
+    state.finish_scan(&scan_id, result, tree);
+    Ok(overview)
+}
+
+/// The home screen's data in one call: the newest scan of each root, free space and
+/// its weekly trend per fixed volume, junk worth cleaning in those scans, what sits in
+/// quarantine, and the scheduled scans and policies coming up. Nothing is scanned.
+#[tauri::command]
+pub fn get_dashboard_summary(state: State<'_, AppState>) -> DashboardSummary {
+    dashboard::dashboard_summary(&state)
+}
+
+// ============================================================================
+// COMPONENT STORE COMMANDS
+// ============================================================================
+
+/// How much of the Windows component store (WinSxS) a cleanup could free. The scanner
+/// skips WinSxS because hard links make its size meaningless; DISM knows the real split.
+/// Requires running as administrator.
+#[tauri::command]
+pub fn analyze_component_store() -> Result<ComponentStoreAnalysis, String> {
+    if !is_elevated() {
+        return Err("Analyzing the component store requires running as administrator".to_string());
+    }
+    winsxs::analyze_component_store()
+}
+
+/// Issue a short-lived token authorizing a component store cleanup. Call this after the
+/// user confirms that installed updates can no longer be uninstalled afterwards.
+#[tauri::command]
+pub fn request_component_cleanup_confirmation(state: State<'_, AppState>) -> Result<ConfirmationToken, String> {
+    state.issue_confirmation(vec![COMPONENT_STORE.to_string()])
+}
+
+/// Start `Dism /StartComponentCleanup` in the background. Progress arrives as
+/// `winsxs://progress` events and the outcome, with the space freed, as `winsxs://finished`.
+#[tauri::command]
+pub fn start_component_cleanup(
+    confirmation_token: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.validate_confirmation(&confirmation_token, COMPONENT_STORE)?;
+    if !is_elevated() {
+        return Err("Cleaning up the component store requires running as administrator".to_string());
+    }
+    if !winsxs::begin_cleanup() {
+        return Err("A component store cleanup is already running".to_string());
+    }
+    let state = state.inner().clone();
+    thread::spawn(move || {
+        let before = winsxs::analyze_component_store().ok();
+        let outcome = winsxs::start_component_cleanup(|percent| {
+            let _ = app_handle.emit(EVENT_CLEANUP_PROGRESS, CleanupProgressPayload { percent });
+        });
+        let after = outcome.as_ref().ok().and_then(|_| winsxs::analyze_component_store().ok());
+        winsxs::end_cleanup();
+
+        let freed_bytes = match (&before, &after) {
+            (Some(before), Some(after)) => before.actual_size_bytes.saturating_sub(after.actual_size_bytes),
+            _ => 0,
+        };
+        let mut entry = AuditEntry::new(
+            AuditAction::ComponentCleanup,
+            COMPONENT_STORE,
+            ConfirmedBy::User,
+            if outcome.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failed },
+        );
+        entry.size_bytes = freed_bytes;
+        entry.message = outcome.as_ref().err().cloned();
+        state.record_audit(&[entry]);
+        let _ = app_handle.emit(
+            EVENT_CLEANUP_FINISHED,
+            CleanupFinishedPayload {
+                success: outcome.is_ok(),
+                error: outcome.err(),
+                before,
+                after,
+                freed_bytes,
+            },
+        );
+    });
+    Ok(())
+}
+
+// ============================================================================
+// CRASH DUMP COMMANDS
+// ============================================================================
+
+/// Memory dumps, minidumps, error reports, and application crash dumps on every
+/// mounted volume and in the user's profile, largest first. Dumps older than a week
+/// are AutoDelete; newer ones need confirming.
+#[tauri::command]
+pub fn find_crash_dumps(state: State<'_, AppState>) -> Result<CrashDumpReport, String> {
+    let volumes: Vec<PathBuf> = list_root_entries()
+        .into_iter()
+        .map(|root| PathBuf::from(root.path))
+        .collect();
+    let home = user_folders().map(|(home, _)| home);
+    let locations = crashdumps::dump_locations(&volumes, home.as_deref());
+    Ok(crashdumps::find_crash_dumps(&locations, &state.classifier()))
+}
+
+// ============================================================================
+// OPERATION PRE-CHECK COMMANDS
+// ============================================================================
+
+/// Which entries a bulk delete or move of `paths` would fail on and why: missing or
+/// protected paths, read-only attributes, missing permissions, and locked files. Checks
+/// a sample of each folder's contents unless `full` is set.
+#[tauri::command]
+pub fn check_operation_feasibility(
+    paths: Vec<String>,
+    full: Option<bool>,
+    state: State<'_, AppState>,
+) -> FeasibilityReport {
+    feasibility::check_feasibility(&paths, full.unwrap_or(false), &state.classifier())
+}
+
+/// Sizes and entry counts of `paths` with how long moving them to the trash, deleting
+/// them, or securely wiping them would take. Each volume involved is calibrated with a
+/// short benchmark the first time, or again when `recalibrate` is set. ConfirmRequired
+/// paths come with a one-time token for deleting them.
+#[tauri::command]
+pub fn preview_bulk_delete(
+    paths: Vec<String>,
+    wipe_passes: Option<u32>,
+    recalibrate: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<DeletePreview, String> {
+    let passes = wipe_passes.unwrap_or(DEFAULT_WIPE_PASSES).max(1);
+    let mut preview = state.update_delete_calibrations(|calibrations| {
+        deletetime::preview_delete(&paths, passes, recalibrate.unwrap_or(false), calibrations)
+    })?;
+    let classifier = state.classifier();
+    let confirm: Vec<(String, SafetyLevel)> = paths
+        .into_iter()
+        .filter(|path| Path::new(path).exists())
+        .map(|path| {
+            let safety = get_safety_level(Path::new(&path), &classifier);
+            (path, safety)
+        })
+        .filter(|(_, safety)| *safety == SafetyLevel::ConfirmRequired)
+        .collect();
+    if !confirm.is_empty() {
+        preview.confirmation = Some(state.issue_delete_confirmation(confirm)?);
+    }
+    Ok(preview)
+}
+
+// ============================================================================
+// OFFLINE FILES COMMANDS
+// ============================================================================
+
+/// Size of the Windows Offline Files cache (C:\Windows\CSC) with ways to shrink it, or
+/// None when Offline Files were never used. The scanner skips the Windows folder, so
+/// this is the only place the cache shows up. Requires running as administrator.
+#[tauri::command]
+pub fn get_offline_files_cache() -> Result<Option<OfflineFilesCache>, String> {
+    if !is_elevated() {
+        return Err("Reading the Offline Files cache requires running as administrator".to_string());
+    }
+    csc::offline_files_cache()
+}
+
+// ============================================================================
+// STORAGE INVENTORY COMMANDS
+// ============================================================================
+
+/// Visualize a bucket or remote from its listing (an S3 Inventory CSV or `rclone lsjson
+/// -R` output) rather than a walk. Entries land under `root_path`, given a scheme such
+/// as `s3://` so they are never taken for local files; an S3 inventory supplies its
+/// bucket when no root is given. Remote entries classify as Protected, so delete
+/// actions stay disabled for them.
+#[tauri::command]
+pub fn start_inventory_scan(
+    list_path: String,
+    format: FileListFormat,
+    root_path: Option<String>,
+    window: WebviewWindow,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ScanHandle, String> {
+    let root_path = match root_path.filter(|root| !root.trim().is_empty()) {
+        Some(root) => filelist::remote_root(&root, format),
+        None => filelist::inventory_root(Path::new(&list_path), format)?,
+    };
+    let options = ScanOptions {
+        file_list: Some(list_path),
+        file_list_format: format,
+        ..ScanOptions::default()
+    };
+    let route = EventRoute::Window(window.label().to_string());
+    Ok(spawn_scan(root_path, options, false, route, app_handle, state.inner().clone()))
+}
+
+// ============================================================================
+// SIZE WATCH COMMANDS
+// ============================================================================
+
+/// Re-measure one folder every `interval_secs` and emit `watch://size-changed` with the
+/// deltas whenever it changed, until `stop_node_watch` or the folder disappears. Much
+/// lighter than watching the filesystem, for keeping an eye on a log or cache folder.
+#[tauri::command]
+pub fn watch_node_size(
+    path: String,
+    interval_secs: u64,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<NodeWatch, String> {
+    let target = PathBuf::from(&path);
+    if !target.is_dir() {
+        return Err(format!("{} is not a folder", path));
+    }
+    let interval_secs = interval_secs.max(MIN_WATCH_INTERVAL_SECS);
+    let initial = measure_path(&target);
+    let watch_id = Uuid::new_v4().to_string();
+    let stop = state.add_watch(&watch_id);
+    let state = state.inner().clone();
+    let id = watch_id.clone();
+    thread::spawn(move || {
+        watch::run_watch(&id, &target, Duration::from_secs(interval_secs), initial, &stop, |payload| {
+            let _ = app_handle.emit(EVENT_SIZE_CHANGED, payload);
+        });
+        state.stop_watch(&id);
+    });
+    Ok(NodeWatch {
+        watch_id,
+        path,
+        interval_secs,
+        bytes: initial.bytes,
+        files: initial.files,
+    })
+}
+
+#[tauri::command]
+pub fn stop_node_watch(watch_id: String, state: State<'_, AppState>) -> bool {
+    state.stop_watch(&watch_id)
+}
+
+// ============================================================================
+// TRASH CONTENTS COMMANDS
+// ============================================================================
+
+/// What is in the trash, newest first; pass a mount point to see one volume's bin
+#[tauri::command]
+pub fn list_trash_items(mount_point: Option<String>) -> Result<Vec<TrashEntry>, String> {
+    recycle::list_trash(mount_point.as_deref())
+}
+
+/// Put the chosen trash items back at their original paths
+#[tauri::command]
+pub fn restore_trash_items(ids: Vec<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let restored = recycle::restore_trash_items(&ids)?;
+    let entries: Vec<AuditEntry> = restored
+        .iter()
+        .map(|path| AuditEntry::new(AuditAction::Restore, path, ConfirmedBy::User, AuditOutcome::Success))
+        .collect();
+    state.record_audit(&entries);
+    Ok(restored)
+}
+
+/// Issue a short-lived token authorizing the permanent removal of the chosen trash
+/// items. Call this after the user confirms.
+#[tauri::command]
+pub fn request_trash_purge_confirmation(
+    ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<ConfirmationToken, String> {
+    if ids.is_empty() {
+        return Err("No trash items were chosen".to_string());
+    }
+    state.issue_confirmation(ids)
+}
+
+/// Permanently delete the chosen trash items. Every id must be covered by the token.
+#[tauri::command]
+pub fn purge_trash_items(
+    ids: Vec<String>,
+    confirmation_token: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashEntry>, String> {
+    for id in &ids {
+        state.validate_confirmation(&confirmation_token, id)?;
+    }
+    let purged = recycle::purge_trash_items(&ids)?;
+    let entries: Vec<AuditEntry> = purged
+        .iter()
+        .map(|item| {
+            let mut entry =
+                AuditEntry::new(AuditAction::EmptyTrash, &item.original_path, ConfirmedBy::User, AuditOutcome::Success);
+            entry.size_bytes = item.size_bytes;
+            entry
+        })
+        .collect();
+    state.record_audit(&entries);
+    Ok(purged)
+}
+
+// ============================================================================
+// WINDOWS STORE COMMANDS
+// ============================================================================
+
+/// Orphaned Windows Installer packages, superseded driver packages, and unregistered
+/// fonts, with the sizes of the folders holding them. The scanner skips the Windows
+/// folder, so none of this shows up in a scan. Requires running as administrator.
+#[tauri::command]
+pub fn analyze_windows_stores() -> Result<WindowsStoresReport, String> {
+    if !is_elevated() {
+        return Err("Analyzing the Windows folder stores requires running as administrator".to_string());
+    }
+    winstores::analyze_windows_stores()
+}
+
+/// Issue a short-lived token authorizing the removal of the chosen store items. Call
+/// this after the user has seen each item's caveat.
+#[tauri::command]
+pub fn request_store_cleanup_confirmation(
+    paths: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<ConfirmationToken, String> {
+    if paths.is_empty() {
+        return Err("No items were chosen".to_string());
+    }
+    state.issue_confirmation(paths)
+}
+
+/// Remove the chosen store items. The stores are analyzed again first and only paths
+/// that are still candidates are touched, so a product reinstalled or a driver updated
+/// in the meantime is left alone.
+#[tauri::command]
+pub fn clean_windows_store_items(
+    paths: Vec<String>,
+    confirmation_token: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<StoreCleanupResult>, String> {
+    for path in &paths {
+        state.validate_confirmation(&confirmation_token, path)?;
+    }
+    if !is_elevated() {
+        return Err("Cleaning up the Windows folder stores requires running as administrator".to_string());
+    }
+    let report = winstores::analyze_windows_stores()?;
+    let mut results = Vec::new();
+    let mut audit_entries = Vec::new();
+    for path in paths {
+        let outcome = match report.items.iter().find(|item| item.path == path) {
+            Some(item) => winstores::remove_store_item(item),
+            None => Err("No longer a cleanup candidate".to_string()),
+        };
+        let mut entry = AuditEntry::new(
+            AuditAction::Delete,
+            &path,
+            ConfirmedBy::User,
+            if outcome.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failed },
+        );
+        entry.size_bytes = *outcome.as_ref().unwrap_or(&0);
+        entry.message = outcome.as_ref().err().cloned();
+        audit_entries.push(entry);
+        results.push(StoreCleanupResult {
+            path,
+            freed_bytes: *outcome.as_ref().unwrap_or(&0),
+            error: outcome.err(),
+        });
+    }
+    state.record_audit(&audit_entries);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A temp file old enough for the builtin rules to auto-delete it
+    fn old_temp_file(dir: &Path) -> String {
+        let path = dir.join("stale.tmp");
+        fs::write(&path, b"stale").expect("write");
+        let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(month_ago))
+            .expect("age file");
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn close_tokens_cover_one_set_of_processes_in_any_order() {
+        let state = AppState::new();
+        let key = close_confirmation_key("/a/locked.db", &[7, 3]);
+        let token = state.issue_confirmation(vec![key]).expect("token").token;
+        assert!(state.take_confirmation(&token, &close_confirmation_key("/a/locked.db", &[3])).is_err());
+        assert!(state.take_confirmation(&token, &close_confirmation_key("/a/locked.db", &[3, 7, 7])).is_ok());
+        assert!(state.take_confirmation(&token, &close_confirmation_key("/a/locked.db", &[3, 7])).is_err());
+    }
+
+    #[test]
+    fn acl_fix_tokens_cover_paths_that_need_no_delete_confirmation() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = old_temp_file(dir.path());
+        let state = AppState::new();
+        assert_eq!(get_safety_level(Path::new(&path), &state.classifier()), SafetyLevel::AutoDelete);
+
+        let token = issue_acl_fix_confirmation(&path, &state).expect("token");
+        // A delete token for the same path isn't an ACL-fix token
+        let delete_token = state
+            .issue_delete_confirmation(vec![(path.clone(), SafetyLevel::AutoDelete)])
+            .expect("delete token");
+        assert!(check_acl_fix(&path, &delete_token.token, &state)
+            .unwrap_err()
+            .contains("does not cover"));
+        match check_acl_fix(&path, &token.token, &state) {
+            Ok(safety) => assert_eq!(safety, SafetyLevel::AutoDelete),
+            Err(e) => assert!(e.contains("Administrator rights"), "{}", e),
+        }
+    }
+
+    #[test]
+    fn acl_fix_tokens_cannot_be_replayed() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = old_temp_file(dir.path());
+        let state = AppState::new();
+        let token = issue_acl_fix_confirmation(&path, &state).expect("token");
+
+        let first = check_acl_fix(&path, &token.token, &state);
+        if is_elevated() {
+            assert_eq!(first, Ok(SafetyLevel::AutoDelete));
+        } else {
+            assert!(first.unwrap_err().contains("Administrator rights"));
+        }
+        assert_eq!(
+            check_acl_fix(&path, &token.token, &state),
+            Err("Unknown confirmation token".to_string())
+        );
+    }
+
+    #[test]
+    fn folders_confirmed_at_their_own_level_use_their_token_once() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let folder = dir.path().join("notes");
+        fs::create_dir(&folder).expect("mkdir");
+        fs::write(folder.join("draft.txt"), b"draft").expect("write");
+        let path = folder.to_string_lossy().to_string();
+        let state = AppState::new();
+        assert_eq!(get_safety_level(&folder, &state.classifier()), SafetyLevel::ConfirmRequired);
+
+        let found = PatternMatch {
+            id: 2,
+            path: path.clone(),
+            kind: NodeKind::Dir,
+            size_bytes: 5,
+            safety: SafetyLevel::ConfirmRequired,
+            held_by: Some(folder.join("draft.txt").to_string_lossy().to_string()),
+        };
+        let token = state
+            .issue_delete_confirmation(vec![(path.clone(), SafetyLevel::ConfirmRequired)])
+            .expect("token");
+        let (paths, unconfirmed) = confirm_held_matches(&[found], Some(&token.token), &state);
+        assert_eq!((paths, unconfirmed), (vec![path.clone()], Vec::<String>::new()));
+        // Still there for the bulk delete to use
+        assert!(state
+            .consume_delete_confirmation(&token.token, &path, &SafetyLevel::ConfirmRequired)
+            .is_ok());
+    }
+
+    #[test]
+    fn acl_fix_is_never_confirmed_for_missing_paths() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let missing = dir.path().join("gone").to_string_lossy().to_string();
+        assert!(issue_acl_fix_confirmation(&missing, &AppState::new()).is_err());
     }
 }
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const SAMPLE_BYTES: usize = 64 * 1024;
+const SAMPLE_COUNT: u64 = 8;
+const ZSTD_LEVEL: i32 = 3;
+
+/// Estimated compression ratio of a file, from compressing a few evenly spaced blocks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressibilityEstimate {
+    pub path: String,
+    pub size_bytes: u64,
+    pub sampled_bytes: u64,
+    pub compressed_sample_bytes: u64,
+    /// Compressed size / original size of the sample (lower compresses better)
+    pub ratio: f64,
+    pub estimated_savings_bytes: u64,
+}
+
+/// On-disk size before and after applying filesystem compression
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressionResult {
+    pub path: String,
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub saved_bytes: u64,
+}
+
+pub fn estimate_compressibility(path: &Path) -> Result<CompressibilityEstimate, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    if size == 0 {
+        return Err(format!("File is empty: {}", path.display()));
+    }
+
+    let samples = if size <= SAMPLE_BYTES as u64 * SAMPLE_COUNT { 1 } else { SAMPLE_COUNT };
+    let stride = size / samples;
+    let mut buffer = vec![0u8; SAMPLE_BYTES];
+    let mut sampled = 0u64;
+    let mut compressed = 0u64;
+    for index in 0..samples {
+        file.seek(SeekFrom::Start(index * stride)).map_err(|e| e.to_string())?;
+        let read = read_up_to(&mut file, &mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        let packed = zstd::bulk::compress(&buffer[..read], ZSTD_LEVEL).map_err(|e| e.to_string())?;
+        sampled += read as u64;
+        // Incompressible blocks are stored raw, never larger
+        compressed += (packed.len() as u64).min(read as u64);
+    }
+
+    let ratio = if sampled == 0 { 1.0 } else { compressed as f64 / sampled as f64 };
+    Ok(CompressibilityEstimate {
+        path: path.to_string_lossy().to_string(),
+        size_bytes: size,
+        sampled_bytes: sampled,
+        compressed_sample_bytes: compressed,
+        ratio,
+        estimated_savings_bytes: (size as f64 * (1.0 - ratio)).max(0.0) as u64,
+    })
+}
+
+fn read_up_to(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file.read(&mut buffer[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+/// Space a file or directory tree actually occupies on disk, honoring NTFS compression
+#[cfg(target_os = "windows")]
+pub fn allocated_size(path: &Path) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE};
+
+    if path.is_dir() {
+        return std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| allocated_size(&e.path())).sum())
+            .unwrap_or(0);
+    }
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high: u32 = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+        return path.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    (u64::from(high) << 32) | u64::from(low)
+}
+
+/// Compress a file or directory tree in place with NTFS compression (compact.exe)
+#[cfg(target_os = "windows")]
+pub fn apply_ntfs_compression(path: &Path) -> Result<CompressionResult, String> {
+    use std::process::Command;
+
+    let before = allocated_size(path);
+    let path_str = path.to_string_lossy().to_string();
+    let mut compact = Command::new("compact");
+    compact.arg("/C").arg("/I").arg("/Q");
+    if path.is_dir() {
+        compact.arg(format!("/S:{}", path_str));
+    } else {
+        compact.arg(&path_str);
+    }
+    let output = compact.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    let after = allocated_size(path);
+    Ok(CompressionResult {
+        path: path_str,
+        before_bytes: before,
+        after_bytes: after,
+        saved_bytes: before.saturating_sub(after),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn apply_ntfs_compression(_path: &Path) -> Result<CompressionResult, String> {
+    Err("NTFS compression is only supported on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn repetitive_data_estimates_high_savings() {
+        let temp = tempdir().expect("tempdir");
+        let text = temp.path().join("log.txt");
+        write(&text, "the same line again\n".repeat(100_000)).expect("write");
+        let noise = temp.path().join("noise.bin");
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let bytes: Vec<u8> = (0..600_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        write(&noise, bytes).expect("write");
+
+        let text = estimate_compressibility(&text).expect("text estimate");
+        let noise = estimate_compressibility(&noise).expect("noise estimate");
+        assert!(text.ratio < 0.1);
+        assert!(noise.ratio > 0.9);
+        assert_eq!(text.sampled_bytes, SAMPLE_BYTES as u64 * SAMPLE_COUNT);
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+use crate::scan::delete::SafetyLevel;
+
+/// How long an issued confirmation stays valid
+pub const CONFIRMATION_TTL: Duration = Duration::from_secs(120);
+
+/// Short-lived authorization to delete specific ConfirmRequired paths
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmationToken {
+    pub token: String,
+    pub paths: Vec<String>,
+    pub expires_at: u64,
+    /// For delete tokens, the safety level of each path when it was previewed; a path
+    /// classified differently at delete time is not covered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety: Vec<SafetyLevel>,
+}
+
+#[derive(Default)]
+pub struct ConfirmationStore {
+    tokens: HashMap<String, ConfirmationToken>,
+}
+
+impl ConfirmationStore {
+    pub fn issue(&mut self, paths: Vec<String>) -> ConfirmationToken {
+        self.issue_with(paths, Vec::new())
+    }
+
+    /// Issue a delete token for previewed paths and the safety level each had
+    pub fn issue_classified(&mut self, items: Vec<(String, SafetyLevel)>) -> ConfirmationToken {
+        let (paths, safety) = items.into_iter().unzip();
+        self.issue_with(paths, safety)
+    }
+
+    fn issue_with(&mut self, paths: Vec<String>, safety: Vec<SafetyLevel>) -> ConfirmationToken {
+        let now = now_millis();
+        self.tokens.retain(|_, token| token.expires_at > now);
+        let token = ConfirmationToken {
+            token: Uuid::new_v4().to_string(),
+            paths,
+            expires_at: now + CONFIRMATION_TTL.as_millis() as u64,
+            safety,
+        };
+        self.tokens.insert(token.token.clone(), token.clone());
+        token
+    }
+
+    /// Check that `token` is live and covers `path`
+    pub fn validate(&self, token: &str, path: &str) -> Result<(), String> {
+        let Some(issued) = self.tokens.get(token) else {
+            return Err("Unknown confirmation token".to_string());
+        };
+        if issued.expires_at <= now_millis() {
+            return Err("Confirmation token has expired".to_string());
+        }
+        if !issued.paths.iter().any(|p| p == path) {
+            return Err(format!("Confirmation token does not cover {}", path));
+        }
+        Ok(())
+    }
+
+    /// Check that the delete token `token` is live and covers `path` as classified `safety`
+    pub fn validate_classified(&self, token: &str, path: &str, safety: &SafetyLevel) -> Result<(), String> {
+        self.validate(token, path)?;
+        let issued = &self.tokens[token];
+        let index = issued.paths.iter().position(|p| p == path);
+        match index.and_then(|index| issued.safety.get(index)) {
+            Some(previewed) if previewed == safety => Ok(()),
+            Some(_) => Err(format!("{} was reclassified since it was confirmed", path)),
+            None => Err(format!("Confirmation token does not cover deleting {}", path)),
+        }
+    }
+
+    /// Validate like `validate_classified` and use the token up for `path`, so it can't
+    /// be replayed; the token goes once every path it covered was used
+    pub fn consume(&mut self, token: &str, path: &str, safety: &SafetyLevel) -> Result<(), String> {
+        self.validate_classified(token, path, safety)?;
+        self.use_up(token, path)
+    }
+
+    /// Validate like `validate` and use the token up for `path`
+    pub fn take(&mut self, token: &str, path: &str) -> Result<(), String> {
+        self.validate(token, path)?;
+        self.use_up(token, path)
+    }
+
+    fn use_up(&mut self, token: &str, path: &str) -> Result<(), String> {
+        let Some(issued) = self.tokens.get_mut(token) else {
+            return Err("Unknown confirmation token".to_string());
+        };
+        if let Some(index) = issued.paths.iter().position(|p| p == path) {
+            issued.paths.remove(index);
+            if index < issued.safety.len() {
+                issued.safety.remove(index);
+            }
+        }
+        if issued.paths.is_empty() {
+            self.tokens.remove(token);
+        }
+        Ok(())
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_bound_to_paths() {
+        let mut store = ConfirmationStore::default();
+        let issued = store.issue(vec!["/data/report.pdf".to_string()]);
+        assert!(store.validate(&issued.token, "/data/report.pdf").is_ok());
+        assert!(store.validate(&issued.token, "/data/other.pdf").is_err());
+        assert!(store.validate("bogus", "/data/report.pdf").is_err());
+    }
+
+    #[test]
+    fn delete_tokens_are_bound_to_classification_and_used_once() {
+        let mut store = ConfirmationStore::default();
+        let confirm = SafetyLevel::ConfirmRequired;
+        let issued = store.issue_classified(vec![
+            ("/a".to_string(), confirm.clone()),
+            ("/b".to_string(), confirm.clone()),
+        ]);
+        assert!(store.consume(&issued.token, "/a", &SafetyLevel::Protected).is_err());
+        assert!(store.consume(&issued.token, "/a", &confirm).is_ok());
+        assert!(store.consume(&issued.token, "/a", &confirm).is_err());
+        assert!(store.consume(&issued.token, "/b", &confirm).is_ok());
+        assert!(store.tokens.is_empty());
+
+        let generic = store.issue(vec!["/c".to_string()]);
+        assert!(store.consume(&generic.token, "/c", &confirm).is_err());
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let mut store = ConfirmationStore::default();
+        let issued = store.issue(vec!["/a".to_string()]);
+        store.tokens.get_mut(&issued.token).expect("token").expires_at = 0;
+        assert!(store.validate(&issued.token, "/a").is_err());
+    }
+}
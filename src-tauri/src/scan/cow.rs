@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CowFilesystem {
+    Btrfs,
+    Zfs,
+}
+
+impl CowFilesystem {
+    pub fn detect(file_system: &str) -> Option<Self> {
+        match file_system.to_lowercase().as_str() {
+            "btrfs" => Some(Self::Btrfs),
+            "zfs" => Some(Self::Zfs),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotUsage {
+    pub name: String,
+    /// Bytes that only this snapshot still references
+    pub exclusive_bytes: u64,
+}
+
+/// Real space usage of a copy-on-write filesystem. Fields are None when the platform
+/// tools (zfs, compsize, btrfs qgroups) are unavailable or not enabled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CowUsage {
+    pub path: String,
+    pub mount_point: String,
+    pub filesystem: CowFilesystem,
+    /// Size before compression
+    pub logical_bytes: Option<u64>,
+    /// Data reachable from this path, shared extents counted once
+    pub referenced_bytes: Option<u64>,
+    /// Space taken on disk after compression
+    pub disk_bytes: Option<u64>,
+    /// Space held only by snapshots
+    pub snapshot_bytes: Option<u64>,
+    pub snapshots: Vec<SnapshotUsage>,
+}
+
+/// Find the mounted volume holding `path` as (device, mount point, filesystem)
+fn volume_for_path(path: &Path) -> Option<(String, String, String)> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| {
+            (
+                disk.name().to_string_lossy().to_string(),
+                disk.mount_point().to_string_lossy().to_string(),
+                disk.file_system().to_string_lossy().to_string(),
+            )
+        })
+}
+
+pub fn cow_usage(path: &Path) -> Result<CowUsage, String> {
+    let (device, mount_point, file_system) =
+        volume_for_path(path).ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    let filesystem = CowFilesystem::detect(&file_system)
+        .ok_or_else(|| format!("{} is on {}, not a copy-on-write filesystem", path.display(), file_system))?;
+
+    let mut usage = CowUsage {
+        path: path.to_string_lossy().to_string(),
+        mount_point: mount_point.clone(),
+        filesystem,
+        logical_bytes: None,
+        referenced_bytes: None,
+        disk_bytes: None,
+        snapshot_bytes: None,
+        snapshots: Vec::new(),
+    };
+
+    match filesystem {
+        CowFilesystem::Zfs => {
+            // The device name of a ZFS mount is its dataset
+            if let Some(out) = run("zfs", &["list", "-Hp", "-o", "used,referenced,logicalreferenced,usedbysnapshots", &device]) {
+                if let Some([used, referenced, logical, snapshots]) = parse_zfs_numbers(&out) {
+                    usage.disk_bytes = Some(used.saturating_sub(snapshots));
+                    usage.referenced_bytes = Some(referenced);
+                    usage.logical_bytes = Some(logical);
+                    usage.snapshot_bytes = Some(snapshots);
+                }
+            }
+            if let Some(out) = run("zfs", &["list", "-Hp", "-t", "snapshot", "-d", "1", "-o", "name,used", &device]) {
+                usage.snapshots = parse_zfs_snapshots(&out);
+            }
+        }
+        CowFilesystem::Btrfs => {
+            let path_str = path.to_string_lossy().to_string();
+            if let Some(out) = run("compsize", &["-b", &path_str]) {
+                if let Some((disk, uncompressed, referenced)) = parse_compsize_total(&out) {
+                    usage.disk_bytes = Some(disk);
+                    usage.logical_bytes = Some(uncompressed);
+                    usage.referenced_bytes = Some(referenced);
+                }
+            }
+            let snapshots = run("btrfs", &["subvolume", "list", "-s", &mount_point]);
+            let qgroups = run("btrfs", &["qgroup", "show", "--raw", &mount_point]);
+            if let (Some(snapshots), Some(qgroups)) = (snapshots, qgroups) {
+                let exclusive = parse_qgroup_exclusive(&qgroups);
+                usage.snapshots = parse_btrfs_snapshots(&snapshots)
+                    .into_iter()
+                    .filter_map(|(id, name)| {
+                        exclusive.get(&id).map(|bytes| SnapshotUsage { name, exclusive_bytes: *bytes })
+                    })
+                    .collect();
+                usage.snapshot_bytes = Some(usage.snapshots.iter().map(|s| s.exclusive_bytes).sum());
+            }
+        }
+    }
+    Ok(usage)
+}
+
+/// Run a tool and return its stdout, or None if it is missing or fails
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_zfs_numbers(output: &str) -> Option<[u64; 4]> {
+    let values: Vec<u64> = output
+        .lines()
+        .next()?
+        .split('\t')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    values.try_into().ok()
+}
+
+fn parse_zfs_snapshots(output: &str) -> Vec<SnapshotUsage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, used) = line.split_once('\t')?;
+            Some(SnapshotUsage {
+                name: name.to_string(),
+                exclusive_bytes: used.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// `compsize -b` ends with a "TOTAL <perc> <disk> <uncompressed> <referenced>" row
+fn parse_compsize_total(output: &str) -> Option<(u64, u64, u64)> {
+    let line = output.lines().find(|line| line.starts_with("TOTAL"))?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    Some((fields.get(2)?.parse().ok()?, fields.get(3)?.parse().ok()?, fields.get(4)?.parse().ok()?))
+}
+
+/// Subvolume id -> path from `btrfs subvolume list -s`
+fn parse_btrfs_snapshots(output: &str) -> Vec<(u64, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != "ID" {
+                return None;
+            }
+            let id = fields.next()?.parse().ok()?;
+            let path = line.rsplit_once(" path ")?.1.to_string();
+            Some((id, path))
+        })
+        .collect()
+}
+
+/// Subvolume id -> exclusive bytes from `btrfs qgroup show --raw` level-0 groups
+fn parse_qgroup_exclusive(output: &str) -> HashMap<u64, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let id = fields.first()?.strip_prefix("0/")?.parse().ok()?;
+            Some((id, fields.get(2)?.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zfs_listings() {
+        assert_eq!(parse_zfs_numbers("500\t300\t900\t200\n"), Some([500, 300, 900, 200]));
+        assert_eq!(parse_zfs_numbers("500\t-\t900\t200\n"), None);
+        let snapshots = parse_zfs_snapshots("tank/home@daily\t4096\ntank/home@weekly\t81920\n");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].exclusive_bytes, 81920);
+    }
+
+    #[test]
+    fn parses_btrfs_tools() {
+        let compsize = "Processed 10 files, 4 regular extents (4 refs), 6 inline.\n\
+            Type       Perc     Disk Usage   Uncompressed Referenced\n\
+            TOTAL       40%         4000        10000        12000\n\
+            zstd        40%         4000        10000        12000\n";
+        assert_eq!(parse_compsize_total(compsize), Some((4000, 10000, 12000)));
+
+        let subvolumes = "ID 260 gen 40 cgen 38 top level 5 otime 2024-03-01 10:00:00 path snapshots/home 2024\n";
+        assert_eq!(parse_btrfs_snapshots(subvolumes), vec![(260, "snapshots/home 2024".to_string())]);
+
+        let qgroups = "qgroupid         rfer         excl\n--------         ----         ----\n0/5          16384        16384\n0/260      1048576       524288\n";
+        let exclusive = parse_qgroup_exclusive(qgroups);
+        assert_eq!(exclusive.get(&260), Some(&524288));
+        assert_eq!(exclusive.len(), 2);
+    }
+}
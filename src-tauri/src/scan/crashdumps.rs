@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::scan::delete::{get_safety_level, measure_path, SafetyLevel};
+use crate::scan::rules::Classifier;
+
+/// Crash dump locations relative to a volume root
+const VOLUME_LOCATIONS: &[&str] = &[
+    "Windows/MEMORY.DMP",
+    "Windows/Minidump",
+    "Windows/LiveKernelReports",
+    "ProgramData/Microsoft/Windows/WER/ReportArchive",
+    "ProgramData/Microsoft/Windows/WER/ReportQueue",
+    "Library/Logs/DiagnosticReports",
+    "var/crash",
+];
+
+/// Crash dump locations relative to the user's profile
+const HOME_LOCATIONS: &[&str] = &[
+    "AppData/Local/CrashDumps",
+    "AppData/Local/Microsoft/Windows/WER/ReportArchive",
+    "AppData/Local/Microsoft/Windows/WER/ReportQueue",
+    "Library/Logs/DiagnosticReports",
+];
+
+/// Folder sequences whose contents are crash dumps or reports, and their kind
+const DUMP_FOLDERS: &[(&[&str], CrashDumpKind)] = &[
+    (&["windows", "minidump"], CrashDumpKind::Minidump),
+    (&["windows", "livekernelreports"], CrashDumpKind::KernelReport),
+    (&["wer", "reportarchive"], CrashDumpKind::ErrorReport),
+    (&["wer", "reportqueue"], CrashDumpKind::ErrorReport),
+    (&["logs", "diagnosticreports"], CrashDumpKind::ErrorReport),
+    (&["var", "crash"], CrashDumpKind::AppCrashDump),
+    (&["crashdumps"], CrashDumpKind::AppCrashDump),
+];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashDumpKind {
+    /// The full or kernel memory dump Windows writes after a bugcheck (MEMORY.DMP)
+    SystemDump,
+    Minidump,
+    /// Live kernel reports written for hangs and driver timeouts
+    KernelReport,
+    /// Windows Error Reporting and macOS diagnostic reports
+    ErrorReport,
+    /// Dumps applications write for their own crashes
+    AppCrashDump,
+}
+
+/// One dump file or report folder
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrashDump {
+    pub path: String,
+    pub kind: CrashDumpKind,
+    pub size_bytes: u64,
+    pub files: u64,
+    /// Seconds since the Unix epoch
+    pub modified: Option<u64>,
+    /// Old dumps are AutoDelete; recent ones still need confirming in case a crash is
+    /// being investigated
+    pub level: SafetyLevel,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CrashDumpReport {
+    /// Largest first
+    pub dumps: Vec<CrashDump>,
+    pub total_bytes: u64,
+    pub auto_delete_bytes: u64,
+}
+
+/// The kind of crash dump at `path`: MEMORY.DMP in a Windows folder, or anything
+/// inside a dump or error report folder
+pub fn crash_dump_kind(path: &Path) -> Option<CrashDumpKind> {
+    let lower: Vec<String> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    if lower.len() >= 2 && lower[lower.len() - 1] == "memory.dmp" && lower[lower.len() - 2] == "windows" {
+        return Some(CrashDumpKind::SystemDump);
+    }
+    DUMP_FOLDERS.iter().find_map(|(sequence, kind)| {
+        // Only entries below the folder; the folder itself stays
+        let end = lower
+            .windows(sequence.len())
+            .position(|window| window.iter().zip(sequence.iter()).all(|(a, b)| a == b))?
+            + sequence.len();
+        (lower.len() > end).then_some(*kind)
+    })
+}
+
+/// Where to look for dumps on the given volumes and in the user's profile
+pub fn dump_locations(volumes: &[PathBuf], home: Option<&Path>) -> Vec<PathBuf> {
+    let mut locations: Vec<PathBuf> = volumes
+        .iter()
+        .flat_map(|volume| VOLUME_LOCATIONS.iter().map(move |location| volume.join(location)))
+        .chain(home.into_iter().flat_map(|home| HOME_LOCATIONS.iter().map(move |location| home.join(location))))
+        .collect();
+    locations.sort();
+    locations.dedup();
+    locations
+}
+
+/// Collect the dumps in `locations`: a location that is a file is one dump, and every
+/// entry of a location folder is one dump or report
+pub fn find_crash_dumps(locations: &[PathBuf], classifier: &Classifier) -> CrashDumpReport {
+    let mut dumps: Vec<CrashDump> = locations
+        .iter()
+        .flat_map(|location| -> Vec<PathBuf> {
+            if location.is_dir() {
+                fs::read_dir(location)
+                    .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+                    .unwrap_or_default()
+            } else if location.is_file() {
+                vec![location.clone()]
+            } else {
+                Vec::new()
+            }
+        })
+        .filter_map(|path| {
+            let kind = crash_dump_kind(&path)?;
+            let totals = measure_path(&path);
+            Some(CrashDump {
+                path: path.to_string_lossy().to_string(),
+                kind,
+                size_bytes: totals.bytes,
+                files: totals.files,
+                modified: fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|age| age.as_secs()),
+                level: get_safety_level(&path, classifier),
+            })
+        })
+        .collect();
+    dumps.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.path.cmp(&b.path)));
+    CrashDumpReport {
+        total_bytes: dumps.iter().map(|dump| dump.size_bytes).sum(),
+        auto_delete_bytes: dumps
+            .iter()
+            .filter(|dump| dump.level == SafetyLevel::AutoDelete)
+            .map(|dump| dump.size_bytes)
+            .sum(),
+        dumps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn recognizes_dump_locations() {
+        assert_eq!(crash_dump_kind(Path::new("C:/Windows/MEMORY.DMP")), Some(CrashDumpKind::SystemDump));
+        assert_eq!(
+            crash_dump_kind(Path::new("C:/Windows/Minidump/101524-9031-01.dmp")),
+            Some(CrashDumpKind::Minidump)
+        );
+        assert_eq!(
+            crash_dump_kind(Path::new("C:/ProgramData/Microsoft/Windows/WER/ReportArchive/AppCrash_x/Report.wer")),
+            Some(CrashDumpKind::ErrorReport)
+        );
+        assert_eq!(crash_dump_kind(Path::new("C:/Windows/Minidump")), None);
+        assert_eq!(crash_dump_kind(Path::new("C:/Users/me/Documents/memory.dmp")), None);
+    }
+
+    #[test]
+    fn collects_dumps_across_volumes() {
+        let volume = tempdir().expect("tempdir");
+        let minidumps = volume.path().join("Windows/Minidump");
+        fs::create_dir_all(&minidumps).expect("mkdir");
+        fs::write(minidumps.join("a.dmp"), vec![0u8; 300]).expect("write");
+        fs::write(volume.path().join("Windows/MEMORY.DMP"), vec![0u8; 1000]).expect("write");
+        let report_dir = volume.path().join("ProgramData/Microsoft/Windows/WER/ReportQueue/AppCrash_1");
+        fs::create_dir_all(&report_dir).expect("mkdir");
+        fs::write(report_dir.join("Report.wer"), vec![0u8; 20]).expect("write");
+
+        let locations = dump_locations(&[volume.path().to_path_buf()], None);
+        let report = find_crash_dumps(&locations, &Classifier::builtin(false));
+        let kinds: Vec<CrashDumpKind> = report.dumps.iter().map(|dump| dump.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![CrashDumpKind::SystemDump, CrashDumpKind::Minidump, CrashDumpKind::ErrorReport]
+        );
+        assert_eq!(report.total_bytes, 1320);
+        // Fresh dumps may still be needed, so nothing is auto-deleted yet
+        assert!(report.dumps.iter().all(|dump| dump.level == SafetyLevel::ConfirmRequired));
+        assert_eq!(report.auto_delete_bytes, 0);
+    }
+}
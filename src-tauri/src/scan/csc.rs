@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The Offline Files store below the Windows folder, readable only by SYSTEM
+pub const CSC_FOLDER: &str = "CSC";
+
+/// Ways to reclaim the Offline Files cache; its files can't be deleted directly
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CscGuidance {
+    /// Sync Center > Manage offline files > Disk Usage sets a smaller limit
+    LowerCacheLimit,
+    /// The `FormatDatabase` value under the CSC service parameters empties the cache on
+    /// the next restart; changes not yet synced are lost
+    ResetCache,
+    /// Turning Offline Files off stops the cache from growing again
+    DisableOfflineFiles,
+}
+
+/// Space held by Windows Offline Files (Client Side Caching), which the scanner never
+/// sees because it skips the Windows folder
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OfflineFilesCache {
+    pub path: String,
+    pub bytes: u64,
+    pub files: u64,
+    /// False when some folders couldn't be read, so the totals are a lower bound
+    pub complete: bool,
+    pub guidance: Vec<CscGuidance>,
+}
+
+/// Measure the Offline Files cache, or None when the machine has none. Enables the
+/// backup privilege first so the SYSTEM-only folders can be listed; needs an elevated
+/// process.
+#[cfg(target_os = "windows")]
+pub fn offline_files_cache() -> Result<Option<OfflineFilesCache>, String> {
+    let windows = std::env::var_os("SystemRoot").ok_or_else(|| "The Windows folder could not be found".to_string())?;
+    let path = Path::new(&windows).join(CSC_FOLDER);
+    if !path.exists() {
+        return Ok(None);
+    }
+    if !enable_backup_privilege() {
+        return Err("Reading the Offline Files cache requires running as administrator".to_string());
+    }
+    Ok(Some(measure_cache(&path)))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn offline_files_cache() -> Result<Option<OfflineFilesCache>, String> {
+    Err("Offline Files only exist on Windows".to_string())
+}
+
+/// Turn on SeBackupPrivilege for the process, which lets directory listings bypass
+/// folder permissions
+#[cfg(target_os = "windows")]
+fn enable_backup_privilege() -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_SUCCESS, HANDLE, LUID};
+    use windows_sys::Win32::Security::{
+        AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES, SE_PRIVILEGE_ENABLED,
+        TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let name: Vec<u16> = "SeBackupPrivilege".encode_utf16().chain(Some(0)).collect();
+    let mut token: HANDLE = std::ptr::null_mut();
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY, &mut token) } == 0 {
+        return false;
+    }
+    let mut luid = LUID { LowPart: 0, HighPart: 0 };
+    let enabled = unsafe { LookupPrivilegeValueW(std::ptr::null(), name.as_ptr(), &mut luid) } != 0 && {
+        let privileges = TOKEN_PRIVILEGES {
+            PrivilegeCount: 1,
+            Privileges: [LUID_AND_ATTRIBUTES {
+                Luid: luid,
+                Attributes: SE_PRIVILEGE_ENABLED,
+            }],
+        };
+        // Succeeds without assigning anything when the token lacks the privilege
+        unsafe {
+            AdjustTokenPrivileges(token, 0, &privileges, 0, std::ptr::null_mut(), std::ptr::null_mut()) != 0
+                && GetLastError() == ERROR_SUCCESS
+        }
+    };
+    unsafe { CloseHandle(token) };
+    enabled
+}
+
+/// Total the cache folder, noting whether every folder could be listed
+pub fn measure_cache(path: &Path) -> OfflineFilesCache {
+    let mut totals = (0, 0, true);
+    add_folder(path, &mut totals);
+    let (bytes, files, complete) = totals;
+    OfflineFilesCache {
+        path: path.to_string_lossy().to_string(),
+        bytes,
+        files,
+        complete,
+        guidance: if bytes == 0 {
+            Vec::new()
+        } else {
+            vec![
+                CscGuidance::LowerCacheLimit,
+                CscGuidance::ResetCache,
+                CscGuidance::DisableOfflineFiles,
+            ]
+        },
+    }
+}
+
+fn add_folder(path: &Path, totals: &mut (u64, u64, bool)) {
+    let Ok(entries) = fs::read_dir(path) else {
+        totals.2 = false;
+        return;
+    };
+    for entry in entries {
+        let Ok(entry) = entry else {
+            totals.2 = false;
+            continue;
+        };
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => add_folder(&entry.path(), totals),
+            Ok(metadata) => {
+                totals.0 += metadata.len();
+                totals.1 += 1;
+            }
+            Err(_) => totals.2 = false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_the_cache_and_offers_guidance_when_not_empty() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let cache = measure_cache(dir.path());
+        assert_eq!((cache.bytes, cache.files, cache.complete), (0, 0, true));
+        assert!(cache.guidance.is_empty());
+
+        let share = dir.path().join("namespace").join("server").join("share");
+        fs::create_dir_all(&share).expect("create share");
+        fs::write(share.join("report.docx"), [0u8; 300]).expect("write");
+        fs::write(dir.path().join("namespace").join("desktop.ini"), [0u8; 20]).expect("write");
+        let cache = measure_cache(dir.path());
+        assert_eq!((cache.bytes, cache.files, cache.complete), (320, 2, true));
+        assert_eq!(cache.guidance.len(), 3);
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::agent::{agent_status, LastScan};
+use crate::scan::baseline::is_descendant;
+use crate::scan::delete::{measure_path, PathTotals};
+use crate::scan::growth::{load_growth_history, GrowthHistory};
+use crate::scan::junk::{JunkCandidate, TreeScorer};
+use crate::scan::model::RootEntry;
+use crate::scan::recycle::QUARANTINE_DIR;
+use crate::scan::runwindow::WindowBlocker;
+use crate::scan::state::AppState;
+use crate::scan::volumes::list_root_entries;
+
+/// Junk score a node needs to be suggested on the home screen
+const SUGGESTION_MIN_SCORE: u8 = 70;
+/// Suggestions listed per scan; the counts cover all of them
+const SUGGESTIONS_PER_SCAN: usize = 5;
+/// Growth over this span sets a volume's trend
+const TREND_MILLIS: u64 = 7 * 24 * 60 * 60 * 1000;
+const HOUR_MILLIS: u64 = 60 * 60 * 1000;
+
+/// Free space of a fixed volume and how fast its scanned roots have been growing
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VolumeTrend {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    /// Summed growth of the roots on the volume over the last week, from scheduled
+    /// scans; None when none of them has two yet
+    pub week_delta_bytes: Option<i64>,
+    /// Days until the volume fills at the last week's rate, when it is growing
+    pub days_until_full: Option<u64>,
+}
+
+/// High-scoring junk in the newest scan of a root
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupSuggestion {
+    pub scan_id: String,
+    pub root_path: String,
+    pub candidates: u64,
+    pub bytes: u64,
+    /// The largest candidates
+    pub top: Vec<JunkCandidate>,
+}
+
+/// What sits in the quarantine folder of one volume
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantineUsage {
+    pub mount_point: String,
+    pub path: String,
+    pub totals: PathTotals,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Scan,
+    Policy,
+}
+
+/// A scheduled scan or an enabled policy with an interval
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub kind: JobKind,
+    /// Root path of a scan, name of a policy
+    pub name: String,
+    /// Id of a policy
+    #[serde(default)]
+    pub id: Option<String>,
+    pub every_hours: u32,
+    pub last_run_at: Option<u64>,
+    /// Unix millis the job is next due; it may wait longer for the run window
+    pub next_run_at: u64,
+}
+
+/// Everything the home screen shows, gathered in one call
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DashboardSummary {
+    pub generated_at: u64,
+    /// Newest finished scan held of each root, newest first
+    pub latest_scans: Vec<LastScan>,
+    /// Bytes deleted or trashed since the last scan finished
+    pub freed_since_last_scan: u64,
+    pub volumes: Vec<VolumeTrend>,
+    pub cleanup: Vec<CleanupSuggestion>,
+    pub quarantine: Vec<QuarantineUsage>,
+    /// Soonest due first
+    pub scheduled: Vec<ScheduledJob>,
+    /// What is holding scheduled work back right now
+    pub window_blockers: Vec<WindowBlocker>,
+}
+
+pub fn dashboard_summary(state: &AppState) -> DashboardSummary {
+    let now = now_millis();
+    let latest_scans = state.latest_scans();
+    let status = agent_status(state);
+    let roots: Vec<RootEntry> = list_root_entries()
+        .into_iter()
+        .filter(|root| !root.is_removable && !root.is_network && root.total_bytes > 0)
+        .collect();
+    let history = state.data_dir().map(load_growth_history).unwrap_or_default();
+    let cleanup = latest_scans
+        .iter()
+        .filter_map(|scan| {
+            let candidates = state
+                .with_tree(&scan.scan_id, |tree| TreeScorer::new(tree).candidates(SUGGESTION_MIN_SCORE))
+                .ok()?;
+            suggestion(scan, candidates)
+        })
+        .collect();
+    DashboardSummary {
+        generated_at: now,
+        freed_since_last_scan: status.freed_since_last_scan,
+        volumes: volume_trends(&roots, &history, now),
+        cleanup,
+        quarantine: quarantine_usage(&roots),
+        scheduled: scheduled_jobs(state, now),
+        window_blockers: status.window_blockers,
+        latest_scans,
+    }
+}
+
+fn suggestion(scan: &LastScan, mut candidates: Vec<JunkCandidate>) -> Option<CleanupSuggestion> {
+    if candidates.is_empty() {
+        return None;
+    }
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.size_bytes));
+    let count = candidates.len() as u64;
+    let bytes = candidates.iter().map(|candidate| candidate.size_bytes).sum();
+    candidates.truncate(SUGGESTIONS_PER_SCAN);
+    Some(CleanupSuggestion {
+        scan_id: scan.scan_id.clone(),
+        root_path: scan.root_path.clone(),
+        candidates: count,
+        bytes,
+        top: candidates,
+    })
+}
+
+/// Pair each volume with the growth recorded for the roots on it
+pub fn volume_trends(roots: &[RootEntry], history: &GrowthHistory, now: u64) -> Vec<VolumeTrend> {
+    let since = now.saturating_sub(TREND_MILLIS);
+    roots
+        .iter()
+        .map(|root| {
+            let week_delta_bytes = history
+                .iter()
+                .filter(|(path, _)| mount_point_of(roots, path) == Some(root.path.as_str()))
+                .flat_map(|(_, growth)| growth.steps.iter().filter(|step| step.to_at >= since))
+                .map(|step| step.total_delta_bytes)
+                .reduce(|sum, delta| sum + delta);
+            let days_until_full = week_delta_bytes
+                .filter(|delta| *delta > 0)
+                .map(|delta| root.available_bytes.saturating_mul(7) / delta as u64);
+            VolumeTrend {
+                mount_point: root.path.clone(),
+                total_bytes: root.total_bytes,
+                available_bytes: root.available_bytes,
+                week_delta_bytes,
+                days_until_full,
+            }
+        })
+        .collect()
+}
+
+/// The deepest mount point holding `path`
+fn mount_point_of<'a>(roots: &'a [RootEntry], path: &str) -> Option<&'a str> {
+    roots
+        .iter()
+        .map(|root| root.path.as_str())
+        .filter(|mount| *mount == path || is_descendant(path, mount))
+        .max_by_key(|mount| mount.len())
+}
+
+fn quarantine_usage(roots: &[RootEntry]) -> Vec<QuarantineUsage> {
+    roots
+        .iter()
+        .filter_map(|root| {
+            let dir = Path::new(&root.path).join(QUARANTINE_DIR);
+            dir.is_dir().then(|| QuarantineUsage {
+                mount_point: root.path.clone(),
+                path: dir.to_string_lossy().to_string(),
+                totals: measure_path(&dir),
+            })
+        })
+        .collect()
+}
+
+fn scheduled_jobs(state: &AppState, now: u64) -> Vec<ScheduledJob> {
+    let settings = state.settings();
+    let next = |every_hours: u32, last_run_at: Option<u64>| {
+        last_run_at.map_or(now, |last| last + u64::from(every_hours) * HOUR_MILLIS)
+    };
+    let mut jobs: Vec<ScheduledJob> = settings
+        .agent
+        .scheduled_scan
+        .iter()
+        .filter(|scan| scan.every_hours > 0)
+        .map(|scan| ScheduledJob {
+            kind: JobKind::Scan,
+            name: scan.root_path.clone(),
+            id: None,
+            every_hours: scan.every_hours,
+            last_run_at: scan.last_run_at,
+            next_run_at: next(scan.every_hours, scan.last_run_at),
+        })
+        .collect();
+    jobs.extend(
+        settings
+            .policies
+            .iter()
+            .filter(|policy| policy.enabled && policy.every_hours > 0)
+            .map(|policy| ScheduledJob {
+                kind: JobKind::Policy,
+                name: policy.name.clone(),
+                id: Some(policy.id.clone()),
+                every_hours: policy.every_hours,
+                last_run_at: policy.last_run_at,
+                next_run_at: next(policy.every_hours, policy.last_run_at),
+            }),
+    );
+    jobs.sort_by_key(|job| job.next_run_at);
+    jobs
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::growth::{GrowthStep, RootGrowth};
+
+    const GB: u64 = 1024 * 1024 * 1024;
+    const DAY: u64 = 24 * HOUR_MILLIS;
+
+    fn volume(path: &str, available_bytes: u64) -> RootEntry {
+        RootEntry {
+            name: path.to_string(),
+            path: path.to_string(),
+            total_bytes: 100 * GB,
+            available_bytes,
+            file_system: "ext4".to_string(),
+            copy_on_write: false,
+            is_removable: false,
+            is_network: false,
+            is_read_only: false,
+            label: None,
+            device: String::new(),
+            disk_kind: "ssd".to_string(),
+            encrypted: None,
+        }
+    }
+
+    fn step(to_at: u64, delta: u64) -> GrowthStep {
+        GrowthStep {
+            from_scan_id: String::new(),
+            to_scan_id: String::new(),
+            from_at: to_at - DAY,
+            to_at,
+            total_delta_bytes: delta as i64,
+            dirs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn trends_sum_last_week_of_roots_on_each_volume() {
+        let roots = [volume("/", 50 * GB), volume("/data", 14 * GB)];
+        let now = 30 * DAY;
+        let mut history = GrowthHistory::new();
+        history.insert(
+            "/data/projects".to_string(),
+            RootGrowth {
+                last: None,
+                steps: vec![step(20 * DAY, 9 * GB), step(25 * DAY, GB), step(29 * DAY, GB)],
+            },
+        );
+        history.insert(
+            "/home".to_string(),
+            RootGrowth {
+                last: None,
+                steps: vec![step(29 * DAY, 0)],
+            },
+        );
+        let trends = volume_trends(&roots, &history, now);
+        assert_eq!(trends[0].week_delta_bytes, Some(0));
+        assert_eq!(trends[0].days_until_full, None);
+        assert_eq!(trends[1].week_delta_bytes, Some(2 * GB as i64));
+        assert_eq!(trends[1].days_until_full, Some(49));
+        assert_eq!(volume_trends(&roots, &GrowthHistory::new(), now)[1].week_delta_bytes, None);
+    }
+}
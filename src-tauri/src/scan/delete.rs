@@ -4,6 +4,16 @@ use std::path::Path;
 use std::time::{Duration, SystemTime};
 use tauri::{AppHandle, Emitter};
 
+use crate::scan::confirm::ConfirmationToken;
+use crate::scan::filelist::is_remote;
+use crate::scan::junk::{score_path, JunkScore};
+use crate::scan::known_folders::is_known_folder_or_ancestor;
+use crate::scan::locks::DeleteFailure;
+use crate::scan::messages::Message;
+use crate::scan::rules::{Classification, Classifier, EntryFacts, RuleSet};
+use crate::scan::throttle::{DeleteThrottle, Throttle};
+use crate::scan::sysfiles::{is_system_file, system_file_info, SystemFileInfo};
+
 // ==========================================
 // SAFETY LEVEL CLASSIFICATION
 // ==========================================
@@ -12,10 +22,30 @@ use tauri::{AppHandle, Emitter};
 #[serde(rename_all = "snake_case")]
 pub enum SafetyLevel {
     AutoDelete,       // No warning - safe to delete
+    ReclaimableBuild, // Developer mode: build output that can be regenerated
     ConfirmRequired,  // Show warning dialog
     Protected,        // Never delete (system files)
 }
 
+/// Size and entry counts of a path, measured before deleting it
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PathTotals {
+    pub bytes: u64,
+    pub files: u64,
+    pub folders: u64,
+}
+
+/// What a recursive delete does when an entry can't be removed
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Stop at the first failure
+    #[default]
+    FailFast,
+    /// Skip failed entries, delete everything else, and report every failure
+    Continue,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeleteResult {
     pub success: bool,
@@ -24,6 +54,11 @@ pub struct DeleteResult {
     pub folders_deleted: u64,
     pub errors: Vec<String>,
     pub was_auto_delete: bool,
+    #[serde(default)]
+    pub failures: Vec<DeleteFailure>,
+    /// Where the item was moved when it was quarantined instead of deleted
+    #[serde(default)]
+    pub quarantined_to: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -33,179 +68,52 @@ pub struct FileInfo {
     pub size_bytes: u64,
     pub safety_level: SafetyLevel,
     pub is_dir: bool,
+    #[serde(default)]
+    pub junk: JunkScore,
+    /// Set for paging, hibernation, and swap files
+    #[serde(default)]
+    pub system_file: Option<SystemFileInfo>,
+    /// The rule or heuristic behind `safety_level`
+    #[serde(default)]
+    pub safety_reason: Option<String>,
+    #[serde(default)]
+    pub safety_reason_message: Option<Message>,
+    /// How to regenerate a ReclaimableBuild folder after deleting it
+    #[serde(default)]
+    pub regenerate_hint: Option<String>,
+    /// Token to pass when deleting a ConfirmRequired path, once the user confirmed
+    #[serde(default)]
+    pub confirmation: Option<ConfirmationToken>,
 }
 
-// Auto-delete extensions (safe to delete without confirmation)
-const AUTO_DELETE_EXTENSIONS: &[&str] = &[
-    // Temp files
-    "tmp", "temp", "bak", "old", "swp", "swo",
-    // Logs
-    "log", "logs",
-    // Cache
-    "cache",
-    // Windows junk
-    "thumbs.db", "desktop.ini", "ehthumbs.db", "ehthumbs_vista.db",
-    // macOS junk
-    "ds_store",
-    // Thumbnails
-    "thumb", "thumbcache",
-    // Build artifacts
-    "pdb", "ilk", "obj", "o", "a", "lib", "exp",
-    // Package lock files (usually regenerated)
-    "pyc", "pyo", "__pycache__",
-    // Editor backups
-    "bak~", "~",
-];
-
-// Auto-delete file names (exact match, case-insensitive)
-const AUTO_DELETE_NAMES: &[&str] = &[
-    "thumbs.db",
-    "desktop.ini",
-    "ehthumbs.db",
-    "ehthumbs_vista.db",
-    ".ds_store",
-    "npm-debug.log",
-    "yarn-error.log",
-    "yarn-debug.log",
-    ".npmrc",
-    ".yarnrc",
-    "debug.log",
-    "error.log",
-    "access.log",
-];
-
-// Auto-delete folder names (these folders are safe to delete)
-const AUTO_DELETE_FOLDERS: &[&str] = &[
-    // Caches
-    ".cache",
-    "__pycache__",
-    ".pytest_cache",
-    ".mypy_cache",
-    "node_modules",
-    ".npm",
-    ".yarn",
-    ".pnpm",
-    // Build outputs
-    "dist",
-    "build",
-    "out",
-    "target",
-    ".next",
-    ".nuxt",
-    ".turbo",
-    // IDE/Editor
-    ".idea",
-    ".vscode",
-    ".vs",
-    // Version control (untracked)
-    ".git",
-    ".svn",
-    ".hg",
-    // Temp
-    "tmp",
-    "temp",
-    ".tmp",
-    ".temp",
-    // Logs
-    "logs",
-    "log",
-];
-
-// Protected paths (NEVER delete)
-const PROTECTED_PATHS: &[&str] = &[
-    "windows",
-    "system32",
-    "syswow64",
-    "program files",
-    "program files (x86)",
-    "programdata",
-    "users",
-    "documents",
-    "pictures",
-    "videos",
-    "music",
-    "downloads",
-    "desktop",
-    "appdata",
-    "boot",
-    "recovery",
-    "system volume information",
-];
-
-// Important/protected extensions (require confirmation)
-const IMPORTANT_EXTENSIONS: &[&str] = &[
-    // Documents
-    "doc", "docx", "pdf", "txt", "rtf", "odt", "xls", "xlsx", "ppt", "pptx",
-    // Media
-    "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm",
-    "mp3", "wav", "flac", "aac", "ogg", "m4a", "wma",
-    "jpg", "jpeg", "png", "gif", "bmp", "svg", "webp", "ico", "tiff", "raw",
-    // Code
-    "js", "ts", "jsx", "tsx", "py", "rs", "go", "java", "cpp", "c", "cs", "rb", "php", "swift", "kt",
-    "html", "css", "scss", "sass", "less",
-    // Config
-    "json", "xml", "yaml", "yml", "toml", "ini", "cfg", "conf",
-    // Archives
-    "zip", "rar", "7z", "tar", "gz", "bz2", "xz",
-    // Databases
-    "db", "sqlite", "sql", "mdb",
-    // Executables
-    "exe", "msi", "app", "dmg", "deb", "rpm",
-];
+/// Whether a path is a system or user folder that must never be deleted: an OS known
+/// folder (or a folder containing one), or a top-level location from the built-in rules.
+/// User rule packs can add protection but never remove it.
+pub fn is_protected_path(path: &Path) -> bool {
+    is_system_file(path)
+        || is_known_folder_or_ancestor(path)
+        || RuleSet::builtin().protected_match(path).is_some()
+}
 
-/// Get the safety level for a file or folder
-pub fn get_safety_level(path: &Path) -> SafetyLevel {
-    let path_str = path.to_string_lossy().to_lowercase();
-    
-    // Check if path is protected
-    for protected in PROTECTED_PATHS {
-        if path_str.contains(protected) {
-            // Check if it's a direct system path
-            let parts: Vec<&str> = path_str.split(['/', '\\']).collect();
-            if parts.len() <= 3 && parts.iter().any(|p| p == protected) {
-                return SafetyLevel::Protected;
-            }
-        }
+/// Classify a path by the active rules, falling back to age and size heuristics
+fn classify(path: &Path, classifier: &Classifier) -> Classification {
+    if is_remote(path) {
+        return Classification {
+            level: SafetyLevel::Protected,
+            reason: Some("Remote object listed from a storage inventory".to_string()),
+            hint: None,
+        };
     }
-    
-    // Check file name
-    if let Some(name) = path.file_name() {
-        let name_lower = name.to_string_lossy().to_lowercase();
-        
-        // Check auto-delete names
-        for auto_name in AUTO_DELETE_NAMES {
-            if name_lower == *auto_name {
-                return SafetyLevel::AutoDelete;
-            }
-        }
-        
-        // Check if it's a folder
-        if path.is_dir() {
-            for auto_folder in AUTO_DELETE_FOLDERS {
-                if name_lower == *auto_folder {
-                    return SafetyLevel::AutoDelete;
-                }
-            }
-        }
+    if is_protected_path(path) {
+        return Classification {
+            level: SafetyLevel::Protected,
+            reason: Some("System file or folder".to_string()),
+            hint: None,
+        };
     }
     
-    // Check extension
-    if let Some(ext) = path.extension() {
-        let ext_lower = ext.to_string_lossy().to_lowercase();
-        
-        // Check auto-delete extensions
-        for auto_ext in AUTO_DELETE_EXTENSIONS {
-            if ext_lower == *auto_ext {
-                return SafetyLevel::AutoDelete;
-            }
-        }
-        
-        // Check important extensions
-        for imp_ext in IMPORTANT_EXTENSIONS {
-            if ext_lower == *imp_ext {
-                return SafetyLevel::ConfirmRequired;
-            }
-        }
+    if let Some(classification) = classifier.classify(path, &EntryFacts::of(path)) {
+        return classification;
     }
     
     // Check file age and size for heuristic (old large files more likely junk)
@@ -216,18 +124,31 @@ pub fn get_safety_level(path: &Path) -> SafetyLevel {
                 // Files > 100MB and older than 30 days
                 if size > 100 * 1024 * 1024 && age > Duration::from_secs(30 * 24 * 60 * 60) {
                     // Still require confirmation for unknown types
-                    return SafetyLevel::ConfirmRequired;
+                    return Classification {
+                        level: SafetyLevel::ConfirmRequired,
+                        reason: Some("Large file not modified in 30 days".to_string()),
+                        hint: None,
+                    };
                 }
             }
         }
     }
     
     // Default: require confirmation for unknown types
-    SafetyLevel::ConfirmRequired
+    Classification {
+        level: SafetyLevel::ConfirmRequired,
+        reason: None,
+        hint: None,
+    }
+}
+
+/// Get the safety level for a file or folder
+pub fn get_safety_level(path: &Path, classifier: &Classifier) -> SafetyLevel {
+    classify(path, classifier).level
 }
 
 /// Get file info with safety level
-pub fn get_file_info(path: &Path) -> Result<FileInfo, String> {
+pub fn get_file_info(path: &Path, classifier: &Classifier) -> Result<FileInfo, String> {
     if !path.exists() {
         return Err(format!("Path does not exist: {}", path.display()));
     }
@@ -239,19 +160,26 @@ pub fn get_file_info(path: &Path) -> Result<FileInfo, String> {
         metadata.len()
     };
     
+    let classification = classify(path, classifier);
     Ok(FileInfo {
         path: path.to_string_lossy().to_string(),
         name: path.file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| path.to_string_lossy().to_string()),
         size_bytes: size,
-        safety_level: get_safety_level(path),
+        safety_level: classification.level,
+        safety_reason_message: classification.reason.as_deref().map(Message::rule_reason),
+        safety_reason: classification.reason,
         is_dir: path.is_dir(),
+        junk: score_path(path),
+        system_file: system_file_info(path),
+        regenerate_hint: classification.hint,
+        confirmation: None,
     })
 }
 
 /// Calculate directory size recursively
-fn calculate_dir_size(path: &Path) -> Result<u64, std::io::Error> {
+pub fn calculate_dir_size(path: &Path) -> Result<u64, std::io::Error> {
     let mut size = 0;
     if path.is_dir() {
         for entry in fs::read_dir(path)? {
@@ -267,13 +195,31 @@ fn calculate_dir_size(path: &Path) -> Result<u64, std::io::Error> {
     Ok(size)
 }
 
-/// Delete a file with smart safety checks
-pub fn smart_delete_file(path: &Path, force: bool) -> Result<DeleteResult, String> {
+/// Where a smart delete sends what it removes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeleteMode {
+    /// Into the trash and nowhere else. A folder the trash refuses as a whole goes entry
+    /// by entry, so locked files are reported while the rest still moves.
+    Trash,
+    Permanent,
+}
+
+/// Delete a file with smart safety checks, paced by `throttle`. `known` totals (e.g. from
+/// a scan tree) spare a walk of the subtree before it is deleted.
+pub fn smart_delete_file(
+    path: &Path,
+    force: bool,
+    classifier: &Classifier,
+    throttle: &mut Throttle,
+    policy: ErrorPolicy,
+    known: Option<PathTotals>,
+    mode: DeleteMode,
+) -> Result<DeleteResult, String> {
     if !path.exists() {
         return Err(format!("Path does not exist: {}", path.display()));
     }
     
-    let safety_level = get_safety_level(path);
+    let safety_level = get_safety_level(path, classifier);
     
     // Never delete protected files
     if safety_level == SafetyLevel::Protected && !force {
@@ -283,98 +229,214 @@ pub fn smart_delete_file(path: &Path, force: bool) -> Result<DeleteResult, Strin
     // Check if confirmation is required
     let was_auto_delete = safety_level == SafetyLevel::AutoDelete;
     
-    let size = if path.is_file() {
-        path.metadata().map(|m| m.len()).unwrap_or(0)
-    } else {
-        calculate_dir_size(path).unwrap_or(0)
-    };
+    let totals = known.unwrap_or_else(|| measure_path(path));
+    let size = totals.bytes;
     
     // Perform deletion
+    let mut failures = Vec::new();
     let result = if path.is_dir() {
-        delete_folder_recursive_internal(path)
+        // Trashing a folder moves it in one step, so report the contents counted beforehand
+        if mode == DeleteMode::Trash && trash::delete(path).is_ok() {
+            throttle.record(1, 0);
+            Ok((totals.files, totals.folders))
+        } else {
+            delete_folder_contents(path, throttle, policy, mode, &mut failures)
+        }
     } else {
-        delete_single_file(path)
+        delete_single_file(path, throttle, mode)
     };
     
     match result {
-        Ok((files, folders)) => Ok(DeleteResult {
-            success: true,
-            bytes_freed: size,
-            files_deleted: files,
-            folders_deleted: folders,
-            errors: vec![],
-            was_auto_delete,
-        }),
-        Err(e) => Ok(DeleteResult {
-            success: false,
-            bytes_freed: 0,
-            files_deleted: 0,
-            folders_deleted: 0,
-            errors: vec![e],
-            was_auto_delete,
-        }),
+        Ok((files, folders)) => {
+            // Whatever survived a partial delete was not freed
+            let remaining = if failures.is_empty() { 0 } else { calculate_dir_size(path).unwrap_or(0) };
+            Ok(DeleteResult {
+                success: failures.is_empty(),
+                bytes_freed: size.saturating_sub(remaining),
+                files_deleted: files,
+                folders_deleted: folders,
+                errors: failures.iter().map(describe_failure).collect(),
+                was_auto_delete,
+                failures,
+                quarantined_to: None,
+            })
+        }
+        Err(failure) => {
+            failures.push(failure);
+            Ok(DeleteResult {
+                success: false,
+                bytes_freed: 0,
+                files_deleted: 0,
+                folders_deleted: 0,
+                errors: failures.iter().map(describe_failure).collect(),
+                was_auto_delete,
+                failures,
+                quarantined_to: None,
+            })
+        }
+    }
+}
+
+/// Human-readable error for a failed path, naming lock holders when known
+fn describe_failure(failure: &DeleteFailure) -> String {
+    failure
+        .localized
+        .as_ref()
+        .map_or_else(|| failure.message.clone(), |message| message.text.clone())
+}
+
+/// Delete a single file, to the trash or permanently
+fn delete_single_file(path: &Path, throttle: &mut Throttle, mode: DeleteMode) -> Result<(u64, u64), DeleteFailure> {
+    let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+    match mode {
+        DeleteMode::Trash => trash::delete(path).map_err(|e| DeleteFailure::from_trash(path, e.to_string()))?,
+        DeleteMode::Permanent => fs::remove_file(path).map_err(|e| DeleteFailure::from_io(path, &e))?,
     }
+    throttle.record(1, size);
+    Ok((1, 0))
 }
 
-/// Delete a single file
-fn delete_single_file(path: &Path) -> Result<(u64, u64), String> {
-    // Try to move to trash first
-    match trash::delete(path) {
-        Ok(_) => Ok((1, 0)),
-        Err(_) => {
-            // Fallback to permanent delete
-            fs::remove_file(path).map_err(|e| e.to_string())?;
-            Ok((1, 0))
+/// Delete a folder recursively. Under `ErrorPolicy::Continue`, entries that can't be
+/// removed are collected in `failures` and the rest of the tree is still deleted.
+fn delete_folder_recursive_internal(
+    path: &Path,
+    throttle: &mut Throttle,
+    policy: ErrorPolicy,
+    mode: DeleteMode,
+    failures: &mut Vec<DeleteFailure>,
+) -> Result<(u64, u64), DeleteFailure> {
+    // Try to move to trash first (handles the whole folder), counting what it holds
+    // beforehand as the top-level path does
+    if mode == DeleteMode::Trash {
+        let totals = measure_path(path);
+        if trash::delete(path).is_ok() {
+            throttle.record(1, 0);
+            return Ok((totals.files, totals.folders));
         }
     }
+    delete_folder_contents(path, throttle, policy, mode, failures)
 }
 
-/// Delete a folder recursively
-fn delete_folder_recursive_internal(path: &Path) -> Result<(u64, u64), String> {
+/// Manually delete a folder's entries and then the folder itself
+fn delete_folder_contents(
+    path: &Path,
+    throttle: &mut Throttle,
+    policy: ErrorPolicy,
+    mode: DeleteMode,
+    failures: &mut Vec<DeleteFailure>,
+) -> Result<(u64, u64), DeleteFailure> {
     let mut files_deleted = 0u64;
     let mut folders_deleted = 0u64;
-    
-    // Try to move to trash first (handles the whole folder)
-    match trash::delete(path) {
-        Ok(_) => {
-            // Count items (approximate)
-            folders_deleted = 1;
-            Ok((files_deleted, folders_deleted))
-        }
-        Err(_) => {
-            // Fallback to manual recursive delete
-            if path.is_dir() {
-                for entry in fs::read_dir(path).map_err(|e| e.to_string())? {
-                    let entry = entry.map_err(|e| e.to_string())?;
-                    let entry_path = entry.path();
-                    
-                    if entry_path.is_dir() {
-                        let (f, d) = delete_folder_recursive_internal(&entry_path)?;
-                        files_deleted += f;
-                        folders_deleted += d;
-                    } else {
-                        fs::remove_file(&entry_path).map_err(|e| e.to_string())?;
-                        files_deleted += 1;
-                    }
+    if !path.is_dir() {
+        return Ok((0, 0));
+    }
+    let failed_before = failures.len();
+    for entry in fs::read_dir(path).map_err(|e| DeleteFailure::from_io(path, &e))? {
+        let outcome = entry
+            .map_err(|e| DeleteFailure::from_io(path, &e))
+            .and_then(|entry| {
+                let entry_path = entry.path();
+                if entry_path.is_dir() {
+                    return delete_folder_recursive_internal(&entry_path, throttle, policy, mode, failures);
                 }
-                fs::remove_dir(path).map_err(|e| e.to_string())?;
-                folders_deleted += 1;
+                delete_single_file(&entry_path, throttle, mode)
+            });
+        match outcome {
+            Ok((f, d)) => {
+                files_deleted += f;
+                folders_deleted += d;
             }
-            Ok((files_deleted, folders_deleted))
+            Err(failure) if policy == ErrorPolicy::Continue => failures.push(failure),
+            Err(failure) => return Err(failure),
         }
     }
+    // A folder still holding failed entries can't be removed; those entries are already reported
+    if failures.len() > failed_before {
+        return Ok((files_deleted, folders_deleted));
+    }
+    fs::remove_dir(path).map_err(|e| DeleteFailure::from_io(path, &e))?;
+    folders_deleted += 1;
+    Ok((files_deleted, folders_deleted))
+}
+
+/// Delete a path the user picked explicitly, to the trash or permanently, without
+/// safety classification. What is removed is counted up front so it can be reported.
+pub fn delete_path_direct(path: &Path, to_trash: bool, known: Option<PathTotals>) -> DeleteResult {
+    let totals = known.unwrap_or_else(|| measure_path(path));
+    let outcome = if to_trash {
+        trash::delete(path).map_err(|e| DeleteFailure::other(path, e.to_string()))
+    } else if path.is_dir() {
+        fs::remove_dir_all(path).map_err(|e| DeleteFailure::from_io(path, &e))
+    } else {
+        fs::remove_file(path).map_err(|e| DeleteFailure::from_io(path, &e))
+    };
+    match outcome {
+        Ok(()) => DeleteResult {
+            success: true,
+            bytes_freed: totals.bytes,
+            files_deleted: totals.files,
+            folders_deleted: totals.folders,
+            errors: vec![],
+            was_auto_delete: false,
+            failures: vec![],
+            quarantined_to: None,
+        },
+        Err(failure) => DeleteResult {
+            success: false,
+            bytes_freed: 0,
+            files_deleted: 0,
+            folders_deleted: 0,
+            errors: vec![describe_failure(&failure)],
+            was_auto_delete: false,
+            failures: vec![failure],
+            quarantined_to: None,
+        },
+    }
+}
+
+/// Total bytes, files, and folders (including `path` itself) under a path
+pub fn measure_path(path: &Path) -> PathTotals {
+    if !path.is_dir() {
+        return PathTotals {
+            bytes: path.metadata().map(|m| m.len()).unwrap_or(0),
+            files: 1,
+            folders: 0,
+        };
+    }
+    let mut totals = PathTotals {
+        bytes: 0,
+        files: 0,
+        folders: 1,
+    };
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let inner = measure_path(&entry.path());
+            totals.bytes += inner.bytes;
+            totals.files += inner.files;
+            totals.folders += inner.folders;
+        }
+    }
+    totals
 }
 
 /// Bulk delete multiple paths
-pub fn bulk_delete(paths: Vec<&Path>, skip_confirm: bool) -> DeleteResult {
+pub fn bulk_delete(
+    paths: Vec<&Path>,
+    skip_confirm: bool,
+    classifier: &Classifier,
+    limits: DeleteThrottle,
+    policy: ErrorPolicy,
+) -> DeleteResult {
+    let mut throttle = Throttle::new(limits);
     let mut total_bytes = 0u64;
     let mut total_files = 0u64;
     let mut total_folders = 0u64;
     let mut errors = Vec::new();
+    let mut failures = Vec::new();
     let mut all_auto = true;
     
     for path in paths {
-        let safety = get_safety_level(path);
+        let safety = get_safety_level(path, classifier);
         
         if safety == SafetyLevel::Protected {
             errors.push(format!("Skipped protected: {}", path.display()));
@@ -391,12 +453,13 @@ pub fn bulk_delete(paths: Vec<&Path>, skip_confirm: bool) -> DeleteResult {
             all_auto = false;
         }
         
-        match smart_delete_file(path, false) {
+        match smart_delete_file(path, false, classifier, &mut throttle, policy, None, DeleteMode::Trash) {
             Ok(result) => {
                 total_bytes += result.bytes_freed;
                 total_files += result.files_deleted;
                 total_folders += result.folders_deleted;
                 errors.extend(result.errors);
+                failures.extend(result.failures);
             }
             Err(e) => {
                 errors.push(e);
@@ -411,6 +474,8 @@ pub fn bulk_delete(paths: Vec<&Path>, skip_confirm: bool) -> DeleteResult {
         folders_deleted: total_folders,
         errors,
         was_auto_delete: all_auto,
+        failures,
+        quarantined_to: None,
     }
 }
 
@@ -438,3 +503,50 @@ pub fn emit_deleted(app_handle: &AppHandle, payload: DeletedPayload) {
 pub fn emit_delete_failed(app_handle: &AppHandle, payload: DeleteFailedPayload) {
     let _ = app_handle.emit("delete://failed", payload);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn developer_mode_reclassifies_project_folders() {
+        let temp = tempdir().expect("tempdir");
+        let git = temp.path().join(".git");
+        let modules = temp.path().join("node_modules");
+        fs::create_dir(&git).expect("mkdir");
+        fs::create_dir(&modules).expect("mkdir");
+
+        let standard = Classifier::builtin(false);
+        let developer = Classifier::builtin(true);
+        assert_eq!(get_safety_level(&git, &standard), SafetyLevel::AutoDelete);
+        assert_eq!(get_safety_level(&git, &developer), SafetyLevel::ConfirmRequired);
+        assert_eq!(get_safety_level(&modules, &developer), SafetyLevel::ReclaimableBuild);
+        let info = get_file_info(&modules, &developer).expect("info");
+        assert!(info.regenerate_hint.is_some_and(|hint| hint.contains("npm install")));
+    }
+
+    #[test]
+    fn entry_by_entry_trashing_counts_nested_folders() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().join("project");
+        let deeper = root.join("sub").join("deeper");
+        fs::create_dir_all(&deeper).expect("mkdir");
+        fs::write(root.join("a.txt"), b"a").expect("write");
+        fs::write(root.join("sub").join("b.txt"), b"b").expect("write");
+        fs::write(deeper.join("c.txt"), b"c").expect("write");
+
+        let mut failures = Vec::new();
+        let counts = delete_folder_contents(
+            &root,
+            &mut Throttle::unlimited(),
+            ErrorPolicy::Continue,
+            DeleteMode::Trash,
+            &mut failures,
+        )
+        .expect("delete");
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(counts, (3, 3));
+        assert!(!root.exists());
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::scan::confirm::ConfirmationToken;
+use crate::scan::delete::{measure_path, PathTotals};
+use crate::scan::volumes::volume_space;
+
+/// Small files created, moved, and removed to time per-entry costs
+const CALIBRATION_FILES: usize = 200;
+const CALIBRATION_FILE_BYTES: usize = 4096;
+/// Data written and flushed to time overwrite throughput
+const CALIBRATION_WRITE_CHUNKS: usize = 8;
+const CALIBRATION_CHUNK_BYTES: usize = 1024 * 1024;
+/// Calibrations older than this are measured again
+const CALIBRATION_MAX_AGE_SECS: u64 = 24 * 60 * 60;
+/// Share of a permanent delete's per-entry cost a trash move spends on entries below
+/// the moved item: Windows sizes what enters the Recycle Bin, and freedesktop trash
+/// records folder sizes
+const TRASH_WALK_SHARE: f64 = 0.1;
+pub const DEFAULT_WIPE_PASSES: u32 = 1;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMethod {
+    Trash,
+    Permanent,
+    /// Overwrite every byte before removing the file
+    SecureWipe,
+}
+
+/// Per-entry and throughput costs measured on one volume
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteCalibration {
+    pub mount_point: String,
+    pub delete_secs_per_entry: f64,
+    /// Moving one item aside and writing its trash record
+    pub trash_secs_per_item: f64,
+    pub write_bytes_per_sec: f64,
+    pub measured_at: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteTimeEstimate {
+    pub method: DeleteMethod,
+    pub seconds: f64,
+}
+
+/// What deleting some paths involves and roughly how long each method would take
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeletePreview {
+    pub totals: PathTotals,
+    /// Paths that exist, as a trash move handles each as one item
+    pub items: u64,
+    pub estimates: Vec<DeleteTimeEstimate>,
+    pub wipe_passes: u32,
+    /// One per volume the paths are on
+    pub calibrations: Vec<DeleteCalibration>,
+    /// Paths that are missing or on a volume that couldn't be calibrated; they are
+    /// left out of the estimates
+    pub errors: Vec<String>,
+    /// Token covering the ConfirmRequired paths, to pass to the delete once confirmed
+    #[serde(default)]
+    pub confirmation: Option<ConfirmationToken>,
+}
+
+/// Time per-entry deletes, trash moves, and flushed writes in a scratch folder inside
+/// `dir`, which is removed afterwards
+pub fn calibrate(dir: &Path, mount_point: &str) -> Result<DeleteCalibration, String> {
+    let work = dir.join(format!(".disksight-calibration-{}", std::process::id()));
+    fs::create_dir(&work).map_err(|e| format!("Can't calibrate in {}: {}", dir.display(), e))?;
+    let measured = run_calibration(&work);
+    let _ = fs::remove_dir_all(&work);
+    let (delete_secs_per_entry, trash_secs_per_item, write_bytes_per_sec) = measured.map_err(|e| e.to_string())?;
+    Ok(DeleteCalibration {
+        mount_point: mount_point.to_string(),
+        delete_secs_per_entry,
+        trash_secs_per_item,
+        write_bytes_per_sec,
+        measured_at: now_secs(),
+    })
+}
+
+fn run_calibration(work: &Path) -> io::Result<(f64, f64, f64)> {
+    let held = work.join("held");
+    fs::create_dir(&held)?;
+    let names: Vec<String> = (0..CALIBRATION_FILES).map(|index| format!("f{}", index)).collect();
+    for name in &names {
+        fs::write(work.join(name), [0u8; CALIBRATION_FILE_BYTES])?;
+    }
+    let started = Instant::now();
+    for name in &names {
+        fs::rename(work.join(name), held.join(name))?;
+        fs::write(held.join(format!("{}.info", name)), b"[Trash Info]")?;
+    }
+    let trash = started.elapsed().as_secs_f64() / CALIBRATION_FILES as f64;
+
+    let started = Instant::now();
+    for name in &names {
+        fs::remove_file(held.join(name))?;
+    }
+    let delete = started.elapsed().as_secs_f64() / CALIBRATION_FILES as f64;
+
+    let chunk = vec![0xA5u8; CALIBRATION_CHUNK_BYTES];
+    let mut file = File::create(work.join("wipe"))?;
+    let started = Instant::now();
+    for _ in 0..CALIBRATION_WRITE_CHUNKS {
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()?;
+    let written = (CALIBRATION_WRITE_CHUNKS * CALIBRATION_CHUNK_BYTES) as f64;
+    let throughput = written / started.elapsed().as_secs_f64().max(f64::EPSILON);
+    Ok((delete, trash, throughput))
+}
+
+/// Seconds `method` would take on `items` paths holding `totals`
+pub fn estimate_seconds(
+    totals: &PathTotals,
+    items: u64,
+    calibration: &DeleteCalibration,
+    method: DeleteMethod,
+    wipe_passes: u32,
+) -> f64 {
+    let entries = (totals.files + totals.folders) as f64;
+    let permanent = entries * calibration.delete_secs_per_entry;
+    match method {
+        DeleteMethod::Permanent => permanent,
+        DeleteMethod::Trash => items as f64 * calibration.trash_secs_per_item + permanent * TRASH_WALK_SHARE,
+        DeleteMethod::SecureWipe => {
+            let overwrite = totals.bytes as f64 * wipe_passes as f64;
+            permanent + overwrite / calibration.write_bytes_per_sec.max(1.0)
+        }
+    }
+}
+
+/// Measure `paths` and estimate each delete method, calibrating every volume involved
+/// that has no recent calibration in `calibrations` (or all of them when `recalibrate`)
+pub fn preview_delete(
+    paths: &[String],
+    wipe_passes: u32,
+    recalibrate: bool,
+    calibrations: &mut HashMap<String, DeleteCalibration>,
+) -> DeletePreview {
+    let mut errors = Vec::new();
+    // Totals and item count per volume
+    let mut volumes: HashMap<String, (PathTotals, u64)> = HashMap::new();
+    for path_str in paths {
+        let path = Path::new(path_str);
+        if !path.exists() {
+            errors.push(format!("Path does not exist: {}", path_str));
+            continue;
+        }
+        let Some(volume) = volume_space(path) else {
+            errors.push(format!("No volume found for {}", path_str));
+            continue;
+        };
+        if !calibrations
+            .get(&volume.mount_point)
+            .is_some_and(|calibration| !recalibrate && now_secs() < calibration.measured_at + CALIBRATION_MAX_AGE_SECS)
+        {
+            let dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+            match calibrate(dir, &volume.mount_point) {
+                Ok(calibration) => {
+                    calibrations.insert(volume.mount_point.clone(), calibration);
+                }
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            }
+        }
+        let measured = measure_path(path);
+        let (totals, items) = volumes.entry(volume.mount_point).or_default();
+        totals.bytes += measured.bytes;
+        totals.files += measured.files;
+        totals.folders += measured.folders;
+        *items += 1;
+    }
+
+    let used: Vec<DeleteCalibration> = volumes
+        .keys()
+        .filter_map(|mount_point| calibrations.get(mount_point).cloned())
+        .collect();
+    let estimates = [DeleteMethod::Trash, DeleteMethod::Permanent, DeleteMethod::SecureWipe]
+        .into_iter()
+        .map(|method| DeleteTimeEstimate {
+            method,
+            seconds: used
+                .iter()
+                .map(|calibration| {
+                    let (totals, items) = &volumes[&calibration.mount_point];
+                    estimate_seconds(totals, *items, calibration, method, wipe_passes)
+                })
+                .sum(),
+        })
+        .collect();
+    let totals = volumes.values().fold(PathTotals::default(), |sum, (totals, _)| PathTotals {
+        bytes: sum.bytes + totals.bytes,
+        files: sum.files + totals.files,
+        folders: sum.folders + totals.folders,
+    });
+    DeletePreview {
+        totals,
+        items: volumes.values().map(|(_, items)| items).sum(),
+        estimates,
+        wipe_passes,
+        calibrations: used,
+        errors,
+        confirmation: None,
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibrates_and_scales_estimates_by_method() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let measured = calibrate(dir.path(), "/").expect("calibrate");
+        assert!(measured.write_bytes_per_sec > 0.0);
+        assert_eq!(fs::read_dir(dir.path()).expect("list").count(), 0);
+
+        let calibration = DeleteCalibration {
+            delete_secs_per_entry: 0.001,
+            trash_secs_per_item: 0.01,
+            write_bytes_per_sec: 100_000_000.0,
+            ..measured
+        };
+        let totals = PathTotals {
+            bytes: 1_000_000_000,
+            files: 9_000,
+            folders: 1_000,
+        };
+        let seconds = |method, passes| estimate_seconds(&totals, 2, &calibration, method, passes);
+        assert!((seconds(DeleteMethod::Permanent, 1) - 10.0).abs() < 1e-9);
+        assert!((seconds(DeleteMethod::Trash, 1) - 1.02).abs() < 1e-9);
+        assert!((seconds(DeleteMethod::SecureWipe, 3) - 40.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Slowest directories kept in a profiled scan's result
+pub const SLOW_DIRS_KEPT: usize = 25;
+
+/// Time a profiled scan spent on one directory's own entries: opening and listing it
+/// and reading each file's metadata, but not walking its subdirectories
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DirTiming {
+    pub path: String,
+    pub millis: u64,
+    /// Entries listed directly in the directory
+    pub entries: u64,
+}
+
+/// Charges the time between walker entries to the directory being read
+pub struct DirProfiler {
+    spent: HashMap<String, Duration>,
+    current: Option<String>,
+    last: Instant,
+}
+
+impl Default for DirProfiler {
+    fn default() -> Self {
+        Self {
+            spent: HashMap::new(),
+            current: None,
+            last: Instant::now(),
+        }
+    }
+}
+
+impl DirProfiler {
+    /// Charge the time since the last call to the directory then being read, and start
+    /// charging `dir`. A walker yields a folder just before opening it and a file after
+    /// listing it, so callers pass a folder itself and a file's parent.
+    pub fn enter(&mut self, dir: &Path) {
+        self.charge();
+        self.current = Some(dir.to_string_lossy().to_string());
+    }
+
+    /// Drop the time since the last call, such as a pause between scan phases
+    pub fn restart_clock(&mut self) {
+        self.last = Instant::now();
+    }
+
+    fn charge(&mut self) {
+        let now = Instant::now();
+        if let Some(current) = &self.current {
+            *self.spent.entry(current.clone()).or_default() += now - self.last;
+        }
+        self.last = now;
+    }
+
+    /// The `limit` directories that took longest, slowest first, with entry counts
+    /// from `entries`
+    pub fn slowest(mut self, limit: usize, entries: impl Fn(&str) -> u64) -> Vec<DirTiming> {
+        self.charge();
+        let mut spent: Vec<(String, Duration)> = self.spent.into_iter().collect();
+        spent.sort_by_key(|(path, time)| (Reverse(*time), path.clone()));
+        spent
+            .into_iter()
+            .take(limit)
+            .map(|(path, time)| DirTiming {
+                entries: entries(&path),
+                millis: time.as_millis() as u64,
+                path,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_time_to_the_directory_being_read() {
+        let mut profiler = DirProfiler::default();
+        profiler.enter(Path::new("/fast"));
+        profiler.enter(Path::new("/slow"));
+        std::thread::sleep(Duration::from_millis(30));
+        profiler.enter(Path::new("/fast"));
+        let slowest = profiler.slowest(1, |path| path.len() as u64);
+        assert_eq!(slowest.len(), 1);
+        assert_eq!(slowest[0].path, "/slow");
+        assert!(slowest[0].millis >= 30);
+        assert_eq!(slowest[0].entries, 5);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::scan::archive::ArchiveEntry;
+
+const SECTOR: u64 = 2048;
+/// Volume descriptors start after the 32 KB system area
+const FIRST_DESCRIPTOR: u64 = 16;
+/// Descriptors read before giving up on finding the terminator
+const MAX_DESCRIPTORS: u64 = 64;
+/// Directory records of one directory; guards against loops in a corrupt image
+const MAX_DIRECTORY_BYTES: u64 = 64 * 1024 * 1024;
+const WIM_MAGIC: &[u8; 8] = b"MSWIM\0\0\0";
+const WIM_HEADER_BYTES: usize = 208;
+/// The XML data resource header: 7 bytes of stored size, a flags byte, the offset,
+/// and the original size
+const WIM_XML_RESOURCE: usize = 72;
+/// Largest XML data read; real images have a few KB per edition
+const MAX_WIM_XML_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A file or folder in an ISO 9660 image, with the sectors that hold it
+struct IsoEntry {
+    name: String,
+    is_dir: bool,
+    /// (first sector, bytes); files over 4 GB span several
+    extents: Vec<(u64, u64)>,
+}
+
+impl IsoEntry {
+    fn size(&self) -> u64 {
+        self.extents.iter().map(|(_, len)| len).sum()
+    }
+}
+
+/// List an ISO 9660 image, using the Joliet tree (long Unicode names) when present
+pub fn list_iso(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    Ok(iso_entries(path)?
+        .into_iter()
+        .map(|entry| ArchiveEntry {
+            size_bytes: if entry.is_dir { 0 } else { entry.size() },
+            name: entry.name,
+            compressed_bytes: None,
+            is_dir: entry.is_dir,
+        })
+        .collect())
+}
+
+/// Hand each named file entry of an ISO image to `write_entry`; other names are ignored
+pub fn extract_iso(
+    path: &Path,
+    names: &[String],
+    mut write_entry: impl FnMut(&str, &mut dyn Read) -> Result<(), String>,
+) -> Result<(), String> {
+    let wanted: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    for entry in iso_entries(path)? {
+        if entry.is_dir || !wanted.contains(entry.name.as_str()) {
+            continue;
+        }
+        let mut parts: Vec<Box<dyn Read>> = Vec::new();
+        for (sector, len) in &entry.extents {
+            let mut part = file.try_clone().map_err(|e| e.to_string())?;
+            part.seek(SeekFrom::Start(sector * SECTOR)).map_err(|e| e.to_string())?;
+            parts.push(Box::new(part.take(*len)));
+        }
+        let mut reader = parts
+            .into_iter()
+            .fold(Box::new(io::empty()) as Box<dyn Read>, |joined, part| Box::new(joined.chain(part)));
+        write_entry(&entry.name, &mut reader)?;
+    }
+    Ok(())
+}
+
+fn iso_entries(path: &Path) -> Result<Vec<IsoEntry>, String> {
+    let mut image = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    let mut primary = None;
+    let mut joliet = None;
+    let mut has_udf = false;
+    for index in FIRST_DESCRIPTOR..FIRST_DESCRIPTOR + MAX_DESCRIPTORS {
+        let mut descriptor = [0u8; SECTOR as usize];
+        if read_at(&mut image, index * SECTOR, &mut descriptor).is_err() {
+            break;
+        }
+        match (&descriptor[1..6], descriptor[0]) {
+            (b"CD001", 1) if primary.is_none() => primary = Some(root_record(&descriptor)),
+            (b"CD001", 2) if is_joliet(&descriptor) => joliet = Some(root_record(&descriptor)),
+            (b"CD001", 255) => {}
+            (b"NSR02", _) | (b"NSR03", _) => has_udf = true,
+            (b"CD001", _) | (b"BEA01", _) | (b"TEA01", _) => {}
+            _ => break,
+        }
+    }
+    let (root, unicode) = match (joliet, primary) {
+        (Some(root), _) => (root, true),
+        (None, Some(root)) => (root, false),
+        (None, None) => return Err(format!("{} is not an ISO 9660 image", path.display())),
+    };
+    let mut entries = Vec::new();
+    let mut visited = HashSet::new();
+    read_directory(&mut image, root, "", unicode, &mut entries, &mut visited)?;
+    // Windows install media put everything in UDF and leave a README in ISO 9660
+    if has_udf && entries.iter().filter(|entry| !entry.is_dir).count() <= 1 {
+        return Err("This image keeps its files in a UDF file system, which can't be listed".to_string());
+    }
+    Ok(entries)
+}
+
+/// Joliet supplementary descriptors announce UCS-2 with an escape sequence
+fn is_joliet(descriptor: &[u8]) -> bool {
+    matches!(&descriptor[88..91], b"%/@" | b"%/C" | b"%/E")
+}
+
+/// (first sector, bytes) of the root directory from a volume descriptor
+fn root_record(descriptor: &[u8]) -> (u64, u64) {
+    let record = &descriptor[156..190];
+    (le_u32(&record[2..6]), le_u32(&record[10..14]))
+}
+
+fn read_directory(
+    image: &mut BufReader<File>,
+    (sector, len): (u64, u64),
+    prefix: &str,
+    unicode: bool,
+    entries: &mut Vec<IsoEntry>,
+    visited: &mut HashSet<u64>,
+) -> Result<(), String> {
+    if !visited.insert(sector) || len > MAX_DIRECTORY_BYTES {
+        return Ok(());
+    }
+    let mut data = vec![0u8; len as usize];
+    read_at(image, sector * SECTOR, &mut data).map_err(|e| e.to_string())?;
+    let mut subdirectories = Vec::new();
+    let mut offset = 0usize;
+    // A file over 4 GB is several records of the same name, all but the last flagged
+    let mut pending: Option<IsoEntry> = None;
+    while offset < data.len() {
+        let record_len = data[offset] as usize;
+        if record_len == 0 {
+            // Records never cross a sector boundary; the rest of this sector is padding
+            offset = (offset / SECTOR as usize + 1) * SECTOR as usize;
+            continue;
+        }
+        let Some(record) = data.get(offset..offset + record_len).filter(|record| record.len() >= 33) else {
+            break;
+        };
+        offset += record_len;
+        let name_len = record[32] as usize;
+        let Some(raw_name) = record.get(33..33 + name_len) else {
+            continue;
+        };
+        // The "." and ".." entries
+        if name_len == 1 && raw_name[0] <= 1 {
+            continue;
+        }
+        let flags = record[25];
+        let is_dir = flags & 0x02 != 0;
+        let extent = (le_u32(&record[2..6]), le_u32(&record[10..14]));
+        let name = format!("{}{}", prefix, decode_name(raw_name, unicode));
+        match &mut pending {
+            Some(entry) if entry.name == name => entry.extents.push(extent),
+            _ => {
+                entries.extend(pending.take());
+                pending = Some(IsoEntry {
+                    name: if is_dir { format!("{}/", name) } else { name.clone() },
+                    is_dir,
+                    extents: vec![extent],
+                });
+            }
+        }
+        if is_dir {
+            entries.extend(pending.take());
+            subdirectories.push((extent, format!("{}/", name)));
+        } else if flags & 0x80 == 0 {
+            entries.extend(pending.take());
+        }
+    }
+    entries.extend(pending);
+    for (extent, prefix) in subdirectories {
+        read_directory(image, extent, &prefix, unicode, entries, visited)?;
+    }
+    Ok(())
+}
+
+/// ISO 9660 names carry a ";1" version and a trailing "." when there is no extension;
+/// Joliet names are UCS-2 big-endian
+fn decode_name(raw: &[u8], unicode: bool) -> String {
+    let name = if unicode {
+        let units: Vec<u16> = raw.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(raw).to_string()
+    };
+    let name = name.split(';').next().unwrap_or_default();
+    name.strip_suffix('.').unwrap_or(name).to_string()
+}
+
+/// List the images in a WIM or ESD file as one entry each, with the size and counts
+/// recorded in the file's XML data. The files inside live in compressed metadata, so
+/// they are not listed.
+pub fn list_wim(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut header = [0u8; WIM_HEADER_BYTES];
+    file.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if &header[..8] != WIM_MAGIC {
+        return Err(format!("{} is not a WIM image", path.display()));
+    }
+    let resource = &header[WIM_XML_RESOURCE..WIM_XML_RESOURCE + 24];
+    let offset = le_u64(&resource[8..16]);
+    let size = le_u64(&resource[16..24]).min(MAX_WIM_XML_BYTES);
+    let mut xml = vec![0u8; size as usize];
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut xml).map_err(|e| e.to_string())?;
+    let units: Vec<u16> = xml.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    Ok(parse_wim_xml(String::from_utf16_lossy(&units).trim_start_matches('\u{feff}')))
+}
+
+/// One entry per `<IMAGE>` element of the WIM XML data
+fn parse_wim_xml(xml: &str) -> Vec<ArchiveEntry> {
+    xml.split("<IMAGE ")
+        .skip(1)
+        .map(|image| {
+            let image = image.split("</IMAGE>").next().unwrap_or(image);
+            let index = image
+                .split("INDEX=\"")
+                .nth(1)
+                .and_then(|rest| rest.split('"').next())
+                .unwrap_or("?");
+            let name = xml_value(image, "DISPLAYNAME").or_else(|| xml_value(image, "NAME"));
+            let files = xml_value(image, "FILECOUNT").unwrap_or_default();
+            let dirs = xml_value(image, "DIRCOUNT").unwrap_or_default();
+            ArchiveEntry {
+                name: format!(
+                    "Image {}: {} ({} files, {} folders)/",
+                    index,
+                    name.unwrap_or_else(|| "unnamed".to_string()),
+                    files,
+                    dirs
+                ),
+                size_bytes: xml_value(image, "TOTALBYTES").and_then(|bytes| bytes.parse().ok()).unwrap_or(0),
+                compressed_bytes: None,
+                is_dir: true,
+            }
+        })
+        .collect()
+}
+
+/// Text of the first `<TAG>` directly in `xml`
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find("</")? + start;
+    Some(xml[start..end].trim().to_string()).filter(|value| !value.is_empty())
+}
+
+fn read_at(image: &mut BufReader<File>, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    image.seek(SeekFrom::Start(offset))?;
+    image.read_exact(buf)
+}
+
+fn le_u32(bytes: &[u8]) -> u64 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A directory record pointing at `sector`
+    fn record(name: &[u8], sector: u32, len: u32, flags: u8) -> Vec<u8> {
+        let mut record = vec![0u8; 33];
+        record[2..6].copy_from_slice(&sector.to_le_bytes());
+        record[10..14].copy_from_slice(&len.to_le_bytes());
+        record[25] = flags;
+        record[32] = name.len() as u8;
+        record.extend_from_slice(name);
+        if record.len() % 2 == 1 {
+            record.push(0);
+        }
+        record[0] = record.len() as u8;
+        record
+    }
+
+    #[test]
+    fn lists_and_extracts_iso_files() {
+        let mut image = vec![0u8; 24 * SECTOR as usize];
+        let put = |image: &mut Vec<u8>, at: u64, bytes: &[u8]| {
+            let at = at as usize;
+            image[at..at + bytes.len()].copy_from_slice(bytes);
+        };
+        let mut primary = vec![1u8];
+        primary.extend_from_slice(b"CD001");
+        put(&mut image, 16 * SECTOR, &primary);
+        put(&mut image, 16 * SECTOR + 156, &record(&[0], 20, SECTOR as u32, 2));
+        put(&mut image, 17 * SECTOR, &[255, b'C', b'D', b'0', b'0', b'1']);
+
+        let mut root = record(&[0], 20, SECTOR as u32, 2);
+        root.extend(record(&[1], 20, SECTOR as u32, 2));
+        root.extend(record(b"MOVIES", 21, SECTOR as u32, 2));
+        root.extend(record(b"README.TXT;1", 22, 5, 0));
+        put(&mut image, 20 * SECTOR, &root);
+        put(&mut image, 21 * SECTOR, &record(b"CLIP.;1", 23, 3, 0));
+        put(&mut image, 22 * SECTOR, b"hello");
+        put(&mut image, 23 * SECTOR, b"abc");
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("disc.iso");
+        fs::write(&path, image).expect("write image");
+        let entries: Vec<(String, u64)> =
+            list_iso(&path).expect("list").into_iter().map(|entry| (entry.name, entry.size_bytes)).collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("MOVIES/".to_string(), 0),
+                ("README.TXT".to_string(), 5),
+                ("MOVIES/CLIP".to_string(), 3),
+            ]
+        );
+
+        let mut extracted = Vec::new();
+        extract_iso(&path, &["MOVIES/CLIP".to_string()], |name, reader| {
+            let mut data = String::new();
+            reader.read_to_string(&mut data).map_err(|e| e.to_string())?;
+            extracted.push((name.to_string(), data));
+            Ok(())
+        })
+        .expect("extract");
+        assert_eq!(extracted, vec![("MOVIES/CLIP".to_string(), "abc".to_string())]);
+    }
+
+    #[test]
+    fn reads_image_sizes_from_wim_xml() {
+        let xml = "<WIM><TOTALBYTES>5000</TOTALBYTES><IMAGE INDEX=\"1\"><DIRCOUNT>20</DIRCOUNT>\
+            <FILECOUNT>90</FILECOUNT><TOTALBYTES>4000</TOTALBYTES><NAME>Windows 11 Pro</NAME></IMAGE>\
+            <IMAGE INDEX=\"2\"><TOTALBYTES>1000</TOTALBYTES></IMAGE></WIM>";
+        let images = parse_wim_xml(xml);
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].name, "Image 1: Windows 11 Pro (90 files, 20 folders)/");
+        assert_eq!((images[0].size_bytes, images[1].size_bytes), (4000, 1000));
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::analyzer::age_bucket_label;
+use crate::scan::junk::{JunkCandidate, TreeScorer};
+use crate::scan::model::{NodeId, NodeKind};
+use crate::scan::tree::ScanTree;
+
+pub const DEFAULT_DOCUMENT_MIN_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Office, PDF, design, and saved-mail formats that pile up on work drives
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "docm", "xls", "xlsx", "xlsm", "xlsb", "ppt", "pptx", "pptm", "odt", "ods", "odp", "rtf",
+    "pages", "numbers", "key", "pdf", "psd", "psb", "ai", "indd", "vsd", "vsdx", "pub", "msg", "eml",
+];
+
+/// Large documents sharing a parent folder and age bucket
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DocumentGroup {
+    pub folder_id: NodeId,
+    pub folder_path: String,
+    /// Age bucket label as used by the age histogram ("month", "year", "older", ...)
+    pub age: String,
+    pub total_bytes: u64,
+    /// The documents, scored like any other cleanup suggestion, largest first
+    pub documents: Vec<JunkCandidate>,
+}
+
+/// Documents of at least `min_bytes`, grouped by parent folder and age, biggest group
+/// first
+pub fn find_large_documents(tree: &ScanTree, min_bytes: u64) -> Vec<DocumentGroup> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let scorer = TreeScorer::new(tree);
+    let mut groups: HashMap<(NodeId, &'static str), Vec<JunkCandidate>> = HashMap::new();
+    for node in tree.nodes() {
+        if node.kind != NodeKind::File
+            || node.size_bytes < min_bytes
+            || !node.file_ext.as_deref().is_some_and(|ext| DOCUMENT_EXTENSIONS.contains(&ext))
+        {
+            continue;
+        }
+        let Some(parent) = node.parent else {
+            continue;
+        };
+        let junk = scorer.score_node(node);
+        let age = age_bucket_label(node.modified.map(|modified| now.saturating_sub(modified)));
+        groups.entry((parent, age)).or_default().push(JunkCandidate {
+            id: node.id,
+            path: node.path.clone(),
+            name: node.name.clone(),
+            kind: node.kind,
+            size_bytes: node.size_bytes,
+            score: junk.score,
+            reasons: junk.reasons,
+            reason_messages: junk.reason_messages,
+        });
+    }
+    let mut groups: Vec<DocumentGroup> = groups
+        .into_iter()
+        .filter_map(|((folder_id, age), mut documents)| {
+            let folder = tree.get(folder_id)?;
+            documents.sort_by_key(|document| std::cmp::Reverse(document.size_bytes));
+            Some(DocumentGroup {
+                folder_id,
+                folder_path: folder.path.clone(),
+                age: age.to_string(),
+                total_bytes: documents.iter().map(|document| document.size_bytes).sum(),
+                documents,
+            })
+        })
+        .collect();
+    groups.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then_with(|| a.folder_path.cmp(&b.folder_path)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::TreeNode;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, modified: Option<u64>) -> TreeNode {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        TreeNode {
+            id,
+            parent,
+            file_ext: name.rsplit_once('.').map(|(_, ext)| ext.to_string()),
+            name,
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            modified,
+            informational: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn groups_large_documents_by_folder_and_age() {
+        const MB: u64 = 1024 * 1024;
+        let nodes = vec![
+            node(1, None, "/work", NodeKind::Dir, 0, None),
+            node(2, Some(1), "/work/deck.pptx", NodeKind::File, 40 * MB, Some(0)),
+            node(3, Some(1), "/work/scan.pdf", NodeKind::File, 20 * MB, Some(0)),
+            node(4, Some(1), "/work/notes.pdf", NodeKind::File, MB, Some(0)),
+            node(5, Some(1), "/work/movie.mp4", NodeKind::File, 90 * MB, Some(0)),
+            node(6, Some(1), "/work/today.psd", NodeKind::File, 15 * MB, None),
+        ];
+        let paths = nodes.iter().map(|n| (n.path.clone(), n.id)).collect();
+        let tree = ScanTree::new(1, nodes.into_iter().map(|n| (n.id, n)).collect(), paths);
+
+        let groups = find_large_documents(&tree, DEFAULT_DOCUMENT_MIN_BYTES);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].age, "older");
+        assert_eq!(groups[0].total_bytes, 60 * MB);
+        assert_eq!(groups[0].documents[0].name, "deck.pptx");
+        assert_eq!(groups[1].age, "unknown");
+    }
+}
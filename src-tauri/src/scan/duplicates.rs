@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::scan::delete::DeleteResult;
+use crate::scan::hashcache::{file_stamp, HashCache, HashKind};
+use crate::scan::model::{NodeId, NodeKind};
+use crate::scan::tree::ScanTree;
+
+/// Bytes hashed in the first pass; only files whose prefixes collide are fully hashed
+const PREFIX_HASH_BYTES: u64 = 64 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub id: NodeId,
+    pub path: String,
+    pub modified: Option<u64>,
+}
+
+/// Files of a finished scan with identical content
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub id: String,
+    pub scan_id: String,
+    pub hash: String,
+    pub size_bytes: u64,
+    pub files: Vec<DuplicateFile>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that would be freed by keeping a single copy
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size_bytes * (self.files.len() as u64).saturating_sub(1)
+    }
+}
+
+/// Which copies of a duplicate group survive a resolution
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KeepStrategy {
+    KeepNewest,
+    KeepOldest,
+    /// Keep every copy under `prefix`
+    KeepInPathPrefix { prefix: String },
+    KeepList { paths: Vec<String> },
+}
+
+/// Outcome of resolving a duplicate group
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateResolution {
+    pub group_id: String,
+    pub kept: Vec<String>,
+    pub result: DeleteResult,
+}
+
+/// Group the files of a scan by content, ignoring files smaller than `min_size`.
+/// Hashes of files unchanged since an earlier search come from `cache`.
+pub fn find_duplicate_groups(
+    scan_id: &str,
+    tree: &ScanTree,
+    min_size: u64,
+    cache: &mut HashCache,
+) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<DuplicateFile>> = HashMap::new();
+    for node in tree.nodes() {
+        if node.kind == NodeKind::File && node.size_bytes >= min_size.max(1) {
+            by_size.entry(node.size_bytes).or_default().push(DuplicateFile {
+                id: node.id,
+                path: node.path.clone(),
+                modified: node.modified,
+            });
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (size, files) in by_size.into_iter().filter(|(_, files)| files.len() > 1) {
+        for candidates in split_by_hash(files, HashKind::Prefix, cache) {
+            // Small files were hashed completely in the first pass
+            let full = if size <= PREFIX_HASH_BYTES {
+                vec![candidates]
+            } else {
+                split_by_hash(candidates, HashKind::Full, cache)
+            };
+            for mut files in full {
+                let hash = match cached_hash(&files[0].path, HashKind::Full, cache) {
+                    Ok(hash) => hash,
+                    Err(_) => continue,
+                };
+                files.sort_by(|a, b| a.path.cmp(&b.path));
+                groups.push(DuplicateGroup {
+                    id: Uuid::new_v4().to_string(),
+                    scan_id: scan_id.to_string(),
+                    hash,
+                    size_bytes: size,
+                    files,
+                });
+            }
+        }
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_bytes()));
+    groups
+}
+
+/// Split `files` into sets sharing a hash, dropping singletons and unreadable files
+fn split_by_hash(files: Vec<DuplicateFile>, kind: HashKind, cache: &mut HashCache) -> Vec<Vec<DuplicateFile>> {
+    let mut by_hash: HashMap<String, Vec<DuplicateFile>> = HashMap::new();
+    for file in files {
+        if let Ok(hash) = cached_hash(&file.path, kind, cache) {
+            by_hash.entry(hash).or_default().push(file);
+        }
+    }
+    by_hash.into_values().filter(|files| files.len() > 1).collect()
+}
+
+/// Whether `path` still holds content with the given full hash
+pub fn matches_hash(path: &Path, hash: &str) -> bool {
+    hash_file(path, None).is_ok_and(|current| current == hash)
+}
+
+/// The hash from the cache when the file is unchanged, otherwise read and cached
+fn cached_hash(path: &str, kind: HashKind, cache: &mut HashCache) -> io::Result<String> {
+    let limit = match kind {
+        HashKind::Prefix => Some(PREFIX_HASH_BYTES),
+        HashKind::Full => None,
+    };
+    let Some(stamp) = file_stamp(Path::new(path)) else {
+        return hash_file(Path::new(path), limit);
+    };
+    if let Some(hash) = cache.get(path, stamp, kind) {
+        return Ok(hash);
+    }
+    let hash = hash_file(Path::new(path), limit)?;
+    // A prefix that covers the whole file is its full hash too
+    if kind == HashKind::Prefix && stamp.0 <= PREFIX_HASH_BYTES {
+        cache.insert(path, stamp, HashKind::Full, hash.clone());
+    }
+    cache.insert(path, stamp, kind, hash.clone());
+    Ok(hash)
+}
+
+fn hash_file(path: &Path, limit: Option<u64>) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    match limit {
+        Some(limit) => io::copy(&mut file.take(limit), &mut hasher)?,
+        None => io::copy(&mut io::BufReader::new(file), &mut hasher)?,
+    };
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Split a group into (kept, removed) according to `strategy`.
+/// Fails rather than selecting every copy for removal.
+pub fn partition_group<'a>(
+    group: &'a DuplicateGroup,
+    strategy: &KeepStrategy,
+) -> Result<(Vec<&'a DuplicateFile>, Vec<&'a DuplicateFile>), String> {
+    let keep: Vec<&DuplicateFile> = match strategy {
+        KeepStrategy::KeepNewest => group
+            .files
+            .iter()
+            .max_by_key(|f| f.modified.unwrap_or(0))
+            .into_iter()
+            .collect(),
+        KeepStrategy::KeepOldest => group
+            .files
+            .iter()
+            .min_by_key(|f| f.modified.unwrap_or(u64::MAX))
+            .into_iter()
+            .collect(),
+        KeepStrategy::KeepInPathPrefix { prefix } => group
+            .files
+            .iter()
+            .filter(|f| Path::new(&f.path).starts_with(prefix))
+            .collect(),
+        KeepStrategy::KeepList { paths } => {
+            if let Some(unknown) = paths.iter().find(|p| !group.files.iter().any(|f| &f.path == *p)) {
+                return Err(format!("{} is not part of this duplicate group", unknown));
+            }
+            group.files.iter().filter(|f| paths.contains(&f.path)).collect()
+        }
+    };
+    if keep.is_empty() {
+        return Err("Strategy would not keep any copy of this file".to_string());
+    }
+    let remove = group
+        .files
+        .iter()
+        .filter(|f| !keep.iter().any(|k| k.id == f.id))
+        .collect();
+    Ok((keep, remove))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::TreeNode;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    fn group(modified: &[Option<u64>]) -> DuplicateGroup {
+        DuplicateGroup {
+            id: "g".to_string(),
+            scan_id: "s".to_string(),
+            hash: "h".to_string(),
+            size_bytes: 10,
+            files: modified
+                .iter()
+                .enumerate()
+                .map(|(i, m)| DuplicateFile {
+                    id: i as NodeId,
+                    path: format!("/data/{}/copy.bin", i),
+                    modified: *m,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn strategies_always_keep_a_copy() {
+        let g = group(&[Some(5), Some(9), None]);
+
+        let (keep, remove) = partition_group(&g, &KeepStrategy::KeepNewest).expect("newest");
+        assert_eq!(keep[0].id, 1);
+        assert_eq!(remove.len(), 2);
+
+        let (keep, _) = partition_group(&g, &KeepStrategy::KeepOldest).expect("oldest");
+        assert_eq!(keep[0].id, 0);
+
+        let prefix = KeepStrategy::KeepInPathPrefix { prefix: "/data/2".to_string() };
+        assert_eq!(partition_group(&g, &prefix).expect("prefix").0[0].id, 2);
+
+        let nowhere = KeepStrategy::KeepInPathPrefix { prefix: "/elsewhere".to_string() };
+        assert!(partition_group(&g, &nowhere).is_err());
+        assert!(partition_group(&g, &KeepStrategy::KeepList { paths: vec![] }).is_err());
+        let stranger = KeepStrategy::KeepList { paths: vec!["/tmp/x".to_string()] };
+        assert!(partition_group(&g, &stranger).is_err());
+    }
+
+    #[test]
+    fn groups_files_by_content() {
+        let temp = tempdir().expect("tempdir");
+        let mut nodes = HashMap::new();
+        let mut index = HashMap::new();
+        for (id, name, body) in [(2, "a", "same"), (3, "b", "same"), (4, "c", "diff")] {
+            let path = temp.path().join(name);
+            write(&path, body).expect("write");
+            let path = path.to_string_lossy().to_string();
+            index.insert(path.clone(), id);
+            nodes.insert(id, TreeNode {
+                id,
+                parent: Some(1),
+                name: name.to_string(),
+                path,
+                kind: NodeKind::File,
+                size_bytes: 4,
+                file_ext: None,
+                modified: None,
+                informational: false,
+                children: Vec::new(),
+            });
+        }
+        let tree = ScanTree::new(1, nodes, index);
+
+        let mut cache = HashCache::default();
+        let groups = find_duplicate_groups("scan", &tree, 1, &mut cache);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].files.len(), 2);
+        assert_eq!(groups[0].wasted_bytes(), 4);
+
+        let misses = cache.stats(0).misses;
+        assert_eq!(find_duplicate_groups("scan", &tree, 1, &mut cache).len(), 1);
+        assert_eq!(cache.stats(0).misses, misses);
+    }
+}
@@ -1,24 +1,49 @@
 use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ignore::WalkBuilder;
-use tauri::AppHandle;
 
+use crate::scan::analyzer::{AnalyzerSet, FileVisit};
+use crate::scan::backend::{bulk_stat, EntryStat, ParallelStat, ScanBackend, StatCache};
+use crate::scan::dirtiming::{DirProfiler, SLOW_DIRS_KEPT};
+use crate::scan::errors::{ErrorThrottle, ScanErrorKind, ERROR_ROLLUP_INTERVAL};
+use crate::scan::filelist;
+use crate::scan::limits::{LimitAction, MemoryCap, LARGE_FILES_NAME, SMALL_FILES_NAME};
 use crate::scan::events::{
-    emit_error, emit_partial_tree, emit_progress, ErrorPayload, PartialTreePayload,
-    ProgressPayload,
+    emit_error, emit_error_rollup, emit_partial_tree, emit_progress, emit_skipped, ErrorPayload,
+    ErrorRollupPayload, PartialTreePayload, ProgressPayload, ScanEmitter, SkippedPayload,
 };
+use crate::scan::messages::{self, Message};
+use crate::scan::reuse::ResumeSeed;
+use crate::scan::safemode::{SafeReader, SAFE_MODE_IO_TIMEOUT};
 use crate::scan::model::{
-    ExtensionStat, NodeId, NodeKind, ScanOptions, ScanResult, TreeNode, TreeNodeDelta,
+    AnalyzerReport, ExtensionStat, NodeId, NodeKind, ScanOptions, ScanResult, SizeBucket, SkipReason,
+    TreeNode, TreeNodeDelta,
 };
+use crate::scan::skips::{exclude_matcher, SkipTally};
+use crate::scan::roots;
+use crate::scan::sysfiles::{system_file_size, SYSTEM_FILES};
+use crate::scan::tree::{PathIndex, ScanTree};
 
 const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
 const PARTIAL_INTERVAL: Duration = Duration::from_millis(100);
-const MAX_PARTIAL_BATCH: usize = 10000;
-const NO_EXTENSION_LABEL: &str = "<none>";
+const SKIPPED_INTERVAL: Duration = Duration::from_secs(1);
+/// How often to confirm the scan root still exists, so an unplugged drive ends the scan
+/// instead of leaving a truncated tree
+const ROOT_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+pub const MAX_PARTIAL_BATCH: usize = 10000;
+
+/// `ProgressPayload::phase` values. Two-phase scans report `structure` while listing
+/// directories and `sizing` while stat'ing files; single-pass scans report `walking`.
+pub const PHASE_WALKING: &str = "walking";
+pub const PHASE_STRUCTURE: &str = "structure";
+pub const PHASE_SIZING: &str = "sizing";
+pub const PHASE_FINALIZING: &str = "finalizing";
 
 // Directories to skip for faster scanning (Windows system folders and heavy dirs)
 const SKIP_DIRS: &[&str] = &[
@@ -61,8 +86,18 @@ const SKIP_DIRS: &[&str] = &[
 
 #[derive(Debug)]
 pub enum ScanError {
-    Canceled,
+    /// Carries what the walk had found, unless it was canceled before reading anything
+    Canceled(Option<Box<PartialScan>>),
     Failed(String),
+    /// The scan root disappeared mid-scan, e.g. a removed USB drive or deleted folder
+    RootLost(String),
+}
+
+/// The result and tree of a canceled scan, flagged incomplete
+#[derive(Debug)]
+pub struct PartialScan {
+    pub result: ScanResult,
+    pub tree: ScanTree,
 }
 
 /// Check if a directory name should be skipped (system folders)
@@ -76,8 +111,15 @@ pub fn normalize_root(root_path: &str) -> Result<PathBuf, String> {
         let cwd = std::env::current_dir().map_err(|e| e.to_string())?;
         path = cwd.join(path);
     }
-    if let Ok(canon) = path.canonicalize() {
-        path = canon;
+    // Canonicalizing resolves subst and mapped drive letters to the folder or share
+    // behind them, so one location scans under one name however it was typed
+    match path.canonicalize() {
+        Ok(canon) => path = PathBuf::from(roots::strip_verbatim(&canon.to_string_lossy())),
+        Err(_) => {
+            if let Some(target) = roots::resolve_drive_mapping(&path) {
+                path = target;
+            }
+        }
     }
     if !path.exists() {
         return Err("Root path does not exist".to_string());
@@ -85,18 +127,48 @@ pub fn normalize_root(root_path: &str) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+/// The root as typed, when it differs from its canonical form (a mapped drive letter,
+/// subst drive, or symlinked path), so results can show both
+pub fn root_aliases(requested: &str, canonical: &Path) -> Vec<String> {
+    let requested = requested.trim_end_matches(['/', '\\']);
+    let canonical = canonical.to_string_lossy();
+    if !Path::new(requested).is_absolute() || requested == canonical.trim_end_matches(['/', '\\']) {
+        Vec::new()
+    } else {
+        vec![requested.to_string()]
+    }
+}
+
 pub fn run_scan(
-    app_handle: Option<AppHandle>,
+    emitter: Option<ScanEmitter>,
     scan_id: String,
     root_path: String,
     options: ScanOptions,
     cancel_flag: Arc<AtomicBool>,
-) -> Result<ScanResult, ScanError> {
+) -> Result<(ScanResult, ScanTree), ScanError> {
+    let analyzers = AnalyzerSet::from_configs(&options.analyzers);
+    run_scan_with_analyzers(emitter, scan_id, root_path, options, cancel_flag, analyzers)
+}
+
+/// Run a scan driving a caller-provided analyzer set
+pub fn run_scan_with_analyzers(
+    emitter: Option<ScanEmitter>,
+    scan_id: String,
+    root_path: String,
+    options: ScanOptions,
+    cancel_flag: Arc<AtomicBool>,
+    mut analyzers: AnalyzerSet,
+) -> Result<(ScanResult, ScanTree), ScanError> {
+    if let Some(list) = options.file_list.clone() {
+        return run_file_list_scan(emitter, scan_id, root_path, Path::new(&list), options, cancel_flag, analyzers);
+    }
+    let started_at = now_millis();
     let root = normalize_root(&root_path).map_err(ScanError::Failed)?;
     let mut nodes: HashMap<NodeId, TreeNode> = HashMap::with_capacity(50_000);
-    let mut path_map: HashMap<String, NodeId> = HashMap::with_capacity(50_000);
+    let mut path_map = PathIndex::with_capacity(50_000, is_case_insensitive(&root));
     let mut changed_nodes: HashSet<NodeId> = HashSet::with_capacity(5_000);
-    let mut extension_stats: HashMap<String, ExtensionStat> = HashMap::with_capacity(200);
+    // Directories still being walked, as (depth, id), used to signal completion to analyzers
+    let mut open_dirs: Vec<(usize, NodeId)> = Vec::with_capacity(64);
 
     let node_counter = AtomicU64::new(1);
     let root_id = next_node_id(&node_counter);
@@ -117,10 +189,12 @@ pub fn run_scan(
             kind: NodeKind::Dir,
             size_bytes: 0,
             file_ext: None,
+            modified: None,
+            informational: false,
             children: Vec::new(),
         },
     );
-    path_map.insert(root_path_str.clone(), root_id);
+    path_map.insert(&root_path_str, root_id);
     changed_nodes.insert(root_id);
 
     let mut visited_entries: u64 = 0;
@@ -130,6 +204,10 @@ pub fn run_scan(
 
     let mut last_progress_emit = Instant::now();
     let mut last_partial_emit = Instant::now();
+    let mut last_skipped_emit = Instant::now();
+    let mut last_error_rollup = Instant::now();
+    let mut error_throttle = ErrorThrottle::default();
+    let mut last_root_check = Instant::now();
     let mut current_path = root_path_str.clone();
 
     let mut builder = WalkBuilder::new(&root);
@@ -146,33 +224,150 @@ pub fn run_scan(
     builder.git_exclude(false);
     builder.ignore(false); // Don't use .ignore files
     builder.standard_filters(false); // Disable all standard filters for speed
+    // Visit the historically largest siblings first; the two-phase sizing pass follows
+    // this walk order too
+    if let Some(hints) = options.size_hints.clone().filter(|hints| !hints.is_empty()) {
+        builder.sort_by_file_path(move |a, b| hints.compare(a, b));
+    }
     
-    // Filter to skip system directories
-    builder.filter_entry(|entry| {
-        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+    // Filter to skip system directories, remembering them for space reconciliation, and
+    // entries matching the exclude patterns
+    let excludes = exclude_matcher(&root, &options.exclude_patterns).map_err(ScanError::Failed)?;
+    let skipped_paths: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let skipped_sink = skipped_paths.clone();
+    let skips: Arc<Mutex<SkipTally>> = Arc::new(Mutex::new(SkipTally::default()));
+    let skips_sink = skips.clone();
+    // Finished folders of a resumed scan, as (path, id in the old tree), waiting to be
+    // copied over
+    let grafts: Arc<Mutex<Vec<(PathBuf, NodeId)>>> = Arc::new(Mutex::new(Vec::new()));
+    let grafts_sink = grafts.clone();
+    let resume = options.resume.clone();
+    // Safe mode routes every stat and listing through a reader with a timeout
+    let safe_reader = options
+        .safe_mode
+        .then(|| Arc::new(Mutex::new(SafeReader::new(SAFE_MODE_IO_TIMEOUT))));
+    let safe_filter = safe_reader.clone();
+    builder.filter_entry(move |entry| {
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir && entry.depth() > 0 {
+            if let Some(id) = resume.as_ref().and_then(|seed| seed.finished_dir(entry.path())) {
+                if let Ok(mut grafts) = grafts_sink.lock() {
+                    grafts.push((entry.path().to_path_buf(), id));
+                    return false;
+                }
+            }
+        }
+        if is_dir {
             if let Some(name) = entry.file_name().to_str() {
                 // Skip system directories
                 if should_skip_dir(name) {
+                    if let Ok(mut skipped) = skipped_sink.lock() {
+                        skipped.push(entry.path().to_string_lossy().to_string());
+                    }
+                    if let Ok(mut skips) = skips_sink.lock() {
+                        skips.record(SkipReason::SystemSkip, entry.path());
+                    }
                     return false;
                 }
             }
         }
+        let excluded = entry.depth() > 0
+            && excludes
+                .as_ref()
+                .is_some_and(|matcher| matcher.matched(entry.path(), is_dir).is_ignore());
+        if excluded {
+            if let Ok(mut skips) = skips_sink.lock() {
+                skips.record(SkipReason::ExcludedPattern, entry.path());
+            }
+            return false;
+        }
+        if let Some(reader) = &safe_filter {
+            let readable = reader.lock().map_or(true, |mut reader| {
+                if is_dir && entry.depth() > 0 {
+                    reader.can_list(entry.path())
+                } else {
+                    !reader.is_blacklisted(entry.path())
+                }
+            });
+            if !readable {
+                if let Ok(mut skips) = skips_sink.lock() {
+                    skips.record(SkipReason::Unreadable, entry.path());
+                }
+                return false;
+            }
+        }
         true
     });
+    let mut error_paths: Vec<String> = Vec::new();
+    // Files found by the structure pass of a two-phase scan, waiting to be stat'ed
+    let mut pending_files: Vec<PathBuf> = Vec::new();
+    let walk_phase = if options.two_phase { PHASE_STRUCTURE } else { PHASE_WALKING };
+    // Bulk backends read whole directories at once, which safe mode can't time out
+    let mut stat_cache = if options.safe_mode {
+        None
+    } else {
+        bulk_stat(ScanBackend::resolve(&options), &options.limits).map(StatCache::new)
+    };
+    let mut limit_actions = Vec::new();
+    let stat_threads = options.limits.stat_threads(ParallelStat::default_threads());
+    if options.backend == ScanBackend::ParallelStat && stat_threads < ParallelStat::default_threads() {
+        limit_actions.push(LimitAction::ReducedThreads {
+            threads: stat_threads as u32,
+        });
+    }
+    // Past the memory cap small files are counted per folder instead of kept as nodes
+    let mut memory_cap = MemoryCap::new(&options.limits);
+    let mut profiler = options.profile_dirs.then(DirProfiler::default);
     
     let mut walker = builder.build();
+    let mut canceled = false;
 
     while let Some(entry) = walker.next() {
         // Check cancellation every 5000 entries for better performance
         if visited_entries % 5000 == 0 && cancel_flag.load(Ordering::Relaxed) {
-            return Err(ScanError::Canceled);
+            canceled = true;
+            break;
+        }
+        // Folders skipped since the last entry are copied in while their parent is
+        // still open
+        if let Some(seed) = &options.resume {
+            let (files, dirs, bytes) = graft_finished_dirs(
+                seed,
+                &grafts,
+                &mut nodes,
+                &mut path_map,
+                &mut changed_nodes,
+                &node_counter,
+                &mut analyzers,
+            );
+            total_files += files;
+            total_dirs += dirs;
+            visited_bytes_approx = visited_bytes_approx.saturating_add(bytes);
         }
         match entry {
             Ok(entry) => {
                 let path = entry.path();
                 visited_entries += 1;
-
+                if let Some(emitter) = &emitter {
+                    emitter.pulse().advance(visited_entries);
+                }
                 let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                if let Some(profiler) = profiler.as_mut() {
+                    // The walker opens a folder right after yielding it
+                    profiler.enter(if is_dir { path } else { path.parent().unwrap_or(path) });
+                }
+
+                // Directories can't complete before the sizing pass in two-phase mode;
+                // they are all closed at the end instead
+                if !options.two_phase {
+                    close_completed_dirs(&mut open_dirs, entry.depth(), &nodes, &mut analyzers);
+                }
+
+                // The same entry reached again under other casing (through a symlink or
+                // subst drive on a case-insensitive filesystem) is already counted
+                if path != root.as_path() && path_map.contains(&path.to_string_lossy()) {
+                    continue;
+                }
                 if is_dir {
                     let dir_id = ensure_dir_node(&mut nodes, &mut path_map, &mut changed_nodes, path, &node_counter);
                     
@@ -185,104 +380,248 @@ pub fn run_scan(
                             parent.children.push(dir_id);
                         }
                     }
+                    open_dirs.push((entry.depth(), dir_id));
+                } else if options.two_phase {
+                    // Structure first: files are stat'ed in the sizing pass
+                    pending_files.push(path.to_path_buf());
                 } else {
-                    // For files, use metadata from entry if available (faster)
-                    let size = entry.metadata()
-                        .map(|m| m.len())
-                        .unwrap_or(0); // Skip error logging for speed
-                    
-                    if size == 0 {
-                        continue; // Skip empty or unreadable files
-                    }
-                    
-                    visited_bytes_approx = visited_bytes_approx.saturating_add(size);
-
-                    let parent_id = parent_id_for_path(&path_map, path);
-                    let file_id = ensure_file_node(
+                    let added = size_file(
                         &mut nodes,
                         &mut path_map,
                         &mut changed_nodes,
-                        path,
-                        parent_id,
                         &node_counter,
-                        size,
+                        &mut analyzers,
+                        SizedFile {
+                            path,
+                            entry: Some(&entry),
+                            stat_cache: stat_cache.as_mut(),
+                            safe_reader: safe_reader.as_deref(),
+                            memory_cap: &mut memory_cap,
+                            options: &options,
+                        },
                     );
-                    total_files += 1;
-
-                    // Add to parent's children (walker doesn't yield duplicates)
-                    if let Some(parent_id) = parent_id {
-                        if let Some(parent) = nodes.get_mut(&parent_id) {
-                            parent.children.push(file_id);
-                        }
-                    }
-
-                    if let Some(ext) = extract_extension(path) {
-                        let entry = extension_stats
-                            .entry(ext.clone())
-                            .or_insert(ExtensionStat {
-                                ext,
-                                bytes: 0,
-                                count: 0,
-                            });
-                        entry.bytes = entry.bytes.saturating_add(size);
-                        entry.count = entry.count.saturating_add(1);
-                    } else {
-                        let entry = extension_stats
-                            .entry(NO_EXTENSION_LABEL.to_string())
-                            .or_insert(ExtensionStat {
-                                ext: NO_EXTENSION_LABEL.to_string(),
-                                bytes: 0,
-                                count: 0,
-                            });
-                        entry.bytes = entry.bytes.saturating_add(size);
-                        entry.count = entry.count.saturating_add(1);
+                    if let Some(size) = added {
+                        total_files += 1;
+                        visited_bytes_approx = visited_bytes_approx.saturating_add(size);
                     }
-
-                    increment_ancestor_sizes(&mut nodes, parent_id, size, &mut changed_nodes);
                 }
 
                 // Only emit progress/partial updates every 2000 entries to reduce overhead
                 if visited_entries % 2000 == 0 {
                     current_path = path.to_string_lossy().to_string();
                     maybe_emit_progress(
-                        &app_handle,
-                        &scan_id,
-                        visited_entries,
-                        visited_bytes_approx,
-                        &current_path,
+                        &emitter,
                         &mut last_progress_emit,
-                        "walking",
+                        ProgressPayload {
+                            scan_id: scan_id.clone(),
+                            visited_entries,
+                            visited_bytes_approx,
+                            current_path: current_path.clone(),
+                            phase: walk_phase.to_string(),
+                            files_sized: None,
+                            files_total: None,
+                        },
                     );
                     maybe_emit_partial(
-                        &app_handle,
+                        &emitter,
                         &scan_id,
                         &nodes,
                         &mut changed_nodes,
                         &mut last_partial_emit,
                     );
+                    if last_skipped_emit.elapsed() >= SKIPPED_INTERVAL {
+                        emit_skipped_update(&emitter, &scan_id, &skips);
+                        last_skipped_emit = Instant::now();
+                    }
+                    if last_error_rollup.elapsed() >= ERROR_ROLLUP_INTERVAL {
+                        emit_error_rollup_update(&emitter, &scan_id, &mut error_throttle);
+                        last_error_rollup = Instant::now();
+                    }
+                    if last_root_check.elapsed() >= ROOT_CHECK_INTERVAL {
+                        if !root.exists() {
+                            return Err(ScanError::RootLost(root_path_str));
+                        }
+                        last_root_check = Instant::now();
+                    }
                 }
             }
             Err(err) => {
-                let error_path: Option<String> = None;
-                emit_error_optional(&app_handle, &scan_id, &err.to_string(), error_path);
+                // A vanished root makes every remaining read fail; stop rather than
+                // reporting each one
+                if !root.exists() {
+                    return Err(ScanError::RootLost(root_path_str));
+                }
+                if let (Some(profiler), Some(path)) = (profiler.as_mut(), walk_error_path(&err)) {
+                    profiler.enter(path);
+                }
+                let error_path = walk_error_path(&err).map(|p| p.to_string_lossy().to_string());
+                if let Some(path) = &error_path {
+                    error_paths.push(path.clone());
+                    let denied = err
+                        .io_error()
+                        .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied);
+                    if denied {
+                        if let Ok(mut skips) = skips.lock() {
+                            skips.record(SkipReason::Permission, Path::new(path));
+                        }
+                    }
+                }
+                let message = err.to_string();
+                if error_throttle.record(ScanErrorKind::of(&err), error_path.as_deref(), &message) {
+                    emit_error_optional(&emitter, &scan_id, &message, error_path);
+                }
+                if last_error_rollup.elapsed() >= ERROR_ROLLUP_INTERVAL {
+                    emit_error_rollup_update(&emitter, &scan_id, &mut error_throttle);
+                    last_error_rollup = Instant::now();
+                }
+            }
+        }
+    }
+
+    if let Some(seed) = &options.resume {
+        let (files, dirs, bytes) = graft_finished_dirs(
+            seed,
+            &grafts,
+            &mut nodes,
+            &mut path_map,
+            &mut changed_nodes,
+            &node_counter,
+            &mut analyzers,
+        );
+        total_files += files;
+        total_dirs += dirs;
+        visited_bytes_approx = visited_bytes_approx.saturating_add(bytes);
+    }
+    canceled |= cancel_flag.load(Ordering::Relaxed);
+    // The walker just stops yielding when the root goes away between reads
+    if !canceled && !root.exists() {
+        return Err(ScanError::RootLost(root_path_str));
+    }
+
+    if options.two_phase && !canceled {
+        // Hand the UI the complete folder structure before sizing starts
+        while emit_partial_batch(&emitter, &scan_id, &nodes, &mut changed_nodes) {}
+        let files_total = pending_files.len() as u64;
+        if let Some(profiler) = profiler.as_mut() {
+            profiler.restart_clock();
+        }
+        for (index, path) in pending_files.iter().enumerate() {
+            let files_sized = index as u64;
+            if files_sized % 2000 == 0 {
+                if cancel_flag.load(Ordering::Relaxed) {
+                    canceled = true;
+                    break;
+                }
+                // The first update of the pass goes out unthrottled so the phase change
+                // shows at once
+                if files_sized == 0 {
+                    last_progress_emit = Instant::now()
+                        .checked_sub(PROGRESS_INTERVAL)
+                        .unwrap_or(last_progress_emit);
+                }
+                maybe_emit_progress(
+                    &emitter,
+                    &mut last_progress_emit,
+                    ProgressPayload {
+                        scan_id: scan_id.clone(),
+                        visited_entries,
+                        visited_bytes_approx,
+                        current_path: path.to_string_lossy().to_string(),
+                        phase: PHASE_SIZING.to_string(),
+                        files_sized: Some(files_sized),
+                        files_total: Some(files_total),
+                    },
+                );
+                maybe_emit_partial(&emitter, &scan_id, &nodes, &mut changed_nodes, &mut last_partial_emit);
+                if last_root_check.elapsed() >= ROOT_CHECK_INTERVAL {
+                    if !root.exists() {
+                        return Err(ScanError::RootLost(root_path_str));
+                    }
+                    last_root_check = Instant::now();
+                }
+            }
+            if let Some(profiler) = profiler.as_mut() {
+                profiler.enter(path.parent().unwrap_or(path));
+            }
+            let added = size_file(
+                &mut nodes,
+                &mut path_map,
+                &mut changed_nodes,
+                &node_counter,
+                &mut analyzers,
+                SizedFile {
+                    path,
+                    entry: None,
+                    stat_cache: stat_cache.as_mut(),
+                    safe_reader: safe_reader.as_deref(),
+                    memory_cap: &mut memory_cap,
+                    options: &options,
+                },
+            );
+            if let Some(size) = added {
+                total_files += 1;
+                visited_bytes_approx = visited_bytes_approx.saturating_add(size);
+            }
+            visited_entries += 1;
+            if let Some(emitter) = &emitter {
+                emitter.pulse().advance(visited_entries);
             }
         }
+        current_path = root_path_str.clone();
+    }
+    if canceled && visited_entries == 0 {
+        return Err(ScanError::Canceled(None));
+    }
+    if let Some(emitter) = &emitter {
+        emitter.pulse().set_finalizing();
     }
 
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Err(ScanError::Canceled);
+    // Whatever is still open when a walk is canceled wasn't read to the end; in a
+    // two-phase scan that is every folder
+    let incomplete_dirs: Vec<String> = if canceled {
+        open_dirs
+            .iter()
+            .filter_map(|(_, id)| nodes.get(id).map(|node| node.path.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    close_completed_dirs(&mut open_dirs, 0, &nodes, &mut analyzers);
+
+    if root.parent().is_none() {
+        total_files += add_system_file_nodes(&mut nodes, &mut path_map, &root, root_id, &node_counter);
     }
 
     recompute_dir_sizes(&mut nodes);
     changed_nodes.extend(nodes.keys().copied());
-    if app_handle.is_some() {
-        while emit_partial_batch(&app_handle, &scan_id, &nodes, &mut changed_nodes) {}
+    if emitter.is_some() {
+        while emit_partial_batch(&emitter, &scan_id, &nodes, &mut changed_nodes) {}
+        emit_skipped_update(&emitter, &scan_id, &skips);
+        emit_error_rollup_update(&emitter, &scan_id, &mut error_throttle);
         let _ = Instant::now(); // Mark as end of partial emissions
     }
 
+    let slow_dirs = profiler
+        .map(|profiler| {
+            profiler.slowest(SLOW_DIRS_KEPT, |path| {
+                path_map
+                    .get(path)
+                    .and_then(|id| nodes.get(&id))
+                    .map_or(0, |node| node.children.len() as u64)
+            })
+        })
+        .unwrap_or_default();
     let total_bytes = nodes.get(&root_id).map(|n| n.size_bytes).unwrap_or(0);
-    let mut extension_stats_vec: Vec<ExtensionStat> = extension_stats.into_values().collect();
-    extension_stats_vec.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+    let mut extension_stats: Vec<ExtensionStat> = Vec::new();
+    let mut size_distribution: Vec<SizeBucket> = Vec::new();
+    let mut analyzer_reports: Vec<AnalyzerReport> = Vec::new();
+    for report in analyzers.finalize() {
+        match report {
+            AnalyzerReport::ExtensionStats { stats } => extension_stats = stats,
+            AnalyzerReport::SizeDistribution { buckets } => size_distribution = buckets,
+            other => analyzer_reports.push(other),
+        }
+    }
 
     let result = ScanResult {
         scan_id,
@@ -290,20 +629,440 @@ pub fn run_scan(
         total_bytes,
         total_files,
         total_dirs,
-        extension_stats: extension_stats_vec,
+        extension_stats,
+        size_distribution,
+        analyzer_reports,
+        skipped_paths: skipped_paths.lock().map(|s| s.clone()).unwrap_or_default(),
+        error_paths,
+        skipped: skips.lock().map(|s| s.groups()).unwrap_or_default(),
+        error_groups: error_throttle.groups(),
+        root_aliases: root_aliases(&root_path, &root),
+        started_at,
+        options: ScanOptions {
+            size_hints: None,
+            resume: None,
+            ..options
+        },
+        reused_from: None,
+        complete: !canceled,
+        incomplete_dirs,
+        unreadable_regions: safe_reader
+            .and_then(|reader| reader.lock().ok().map(|mut reader| reader.take_regions()))
+            .unwrap_or_default(),
+        limit_actions: limit_actions.into_iter().chain(memory_cap.action()).collect(),
+        slow_dirs,
     };
+    if canceled {
+        let tree = ScanTree::with_index(root_id, nodes, path_map);
+        return Err(ScanError::Canceled(Some(Box::new(PartialScan { result, tree }))));
+    }
 
-    if let Some(handle) = app_handle {
+    if let Some(emitter) = emitter {
         let payload = ProgressPayload {
             scan_id: result.scan_id.clone(),
             visited_entries,
             visited_bytes_approx,
             current_path,
-            phase: "finalizing".to_string(),
+            phase: PHASE_FINALIZING.to_string(),
+            files_sized: None,
+            files_total: None,
         };
-        emit_progress(&handle, payload);
+        emit_progress(&emitter, payload);
+    }
+    Ok((result, ScanTree::with_index(root_id, nodes, path_map)))
+}
+
+/// Build the tree from a file list instead of walking: every listed file lands below
+/// `root_path` with the size the list gives, and the folders between are created as
+/// needed. Nothing is read from the disk, so the root doesn't have to exist.
+fn run_file_list_scan(
+    emitter: Option<ScanEmitter>,
+    scan_id: String,
+    root_path: String,
+    list: &Path,
+    options: ScanOptions,
+    cancel_flag: Arc<AtomicBool>,
+    mut analyzers: AnalyzerSet,
+) -> Result<(ScanResult, ScanTree), ScanError> {
+    let started_at = now_millis();
+    let file = std::fs::File::open(list)
+        .map_err(|e| ScanError::Failed(format!("Could not open the file list {}: {}", list.display(), e)))?;
+    let root_path_str = filelist::list_root(&root_path);
+    let case_insensitive = Path::new(&root_path_str).is_dir() && is_case_insensitive(Path::new(&root_path_str));
+    let mut nodes: HashMap<NodeId, TreeNode> = HashMap::with_capacity(50_000);
+    let mut path_map = PathIndex::with_capacity(50_000, case_insensitive);
+    let mut changed_nodes: HashSet<NodeId> = HashSet::with_capacity(5_000);
+    let node_counter = AtomicU64::new(1);
+    let root_id = next_node_id(&node_counter);
+    nodes.insert(
+        root_id,
+        TreeNode {
+            id: root_id,
+            parent: None,
+            name: root_path_str.clone(),
+            path: root_path_str.clone(),
+            kind: NodeKind::Dir,
+            size_bytes: 0,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children: Vec::new(),
+        },
+    );
+    path_map.insert(&root_path_str, root_id);
+    changed_nodes.insert(root_id);
+
+    let mut visited_entries: u64 = 0;
+    let mut visited_bytes_approx: u64 = 0;
+    let mut total_files: u64 = 0;
+    let mut total_dirs: u64 = 1;
+    let mut last_progress_emit = Instant::now();
+    let mut last_partial_emit = Instant::now();
+    let mut last_error_rollup = Instant::now();
+    let mut error_throttle = ErrorThrottle::default();
+    let mut canceled = false;
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        if index % 5000 == 0 && cancel_flag.load(Ordering::Relaxed) {
+            canceled = true;
+            break;
+        }
+        let line = line.map_err(|e| ScanError::Failed(format!("Could not read the file list: {}", e)))?;
+        let listed = match filelist::parse_entry(&line, options.file_list_format) {
+            Ok(Some(listed)) => listed,
+            Ok(None) => continue,
+            Err(message) => {
+                let message = format!("Line {} of the file list was skipped: {}", index + 1, message);
+                if error_throttle.record(ScanErrorKind::Io, None, &message) {
+                    emit_error_optional(&emitter, &scan_id, &message, None);
+                }
+                continue;
+            }
+        };
+        visited_entries += 1;
+        let Some(path) = filelist::resolve(&root_path_str, &listed.path) else {
+            let message = format!("{} is outside {}", listed.path, root_path_str);
+            if error_throttle.record(ScanErrorKind::NotFound, Some(&listed.path), &message) {
+                emit_error_optional(&emitter, &scan_id, &message, Some(listed.path.clone()));
+            }
+            continue;
+        };
+        // A path listed twice is counted once
+        if path_map.contains(&path) {
+            continue;
+        }
+        let path = PathBuf::from(path);
+        if let Some(parent) = path.parent() {
+            total_dirs += ensure_dir_chain(&mut nodes, &mut path_map, &mut changed_nodes, parent, &node_counter);
+        }
+        let meta = FileMeta {
+            stat: EntryStat {
+                size: listed.size,
+                modified: listed.modified.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            },
+            metadata: None,
+        };
+        let added = add_file_node(
+            &mut nodes,
+            &mut path_map,
+            &mut changed_nodes,
+            &node_counter,
+            &mut analyzers,
+            &path,
+            Some(meta),
+        );
+        if let Some(size) = added {
+            total_files += 1;
+            visited_bytes_approx = visited_bytes_approx.saturating_add(size);
+        }
+        if visited_entries % 2000 == 0 {
+            if let Some(emitter) = &emitter {
+                emitter.pulse().advance(visited_entries);
+            }
+            maybe_emit_progress(
+                &emitter,
+                &mut last_progress_emit,
+                ProgressPayload {
+                    scan_id: scan_id.clone(),
+                    visited_entries,
+                    visited_bytes_approx,
+                    current_path: path.to_string_lossy().to_string(),
+                    phase: PHASE_WALKING.to_string(),
+                    files_sized: None,
+                    files_total: None,
+                },
+            );
+            maybe_emit_partial(&emitter, &scan_id, &nodes, &mut changed_nodes, &mut last_partial_emit);
+            if last_error_rollup.elapsed() >= ERROR_ROLLUP_INTERVAL {
+                emit_error_rollup_update(&emitter, &scan_id, &mut error_throttle);
+                last_error_rollup = Instant::now();
+            }
+        }
+    }
+    if canceled && visited_entries == 0 {
+        return Err(ScanError::Canceled(None));
+    }
+    if let Some(emitter) = &emitter {
+        emitter.pulse().set_finalizing();
+    }
+
+    // A list comes in no particular order, so folders only complete once it is read,
+    // deepest first
+    let mut dirs: Vec<(usize, NodeId)> = nodes
+        .values()
+        .filter(|node| node.kind == NodeKind::Dir)
+        .map(|node| (Path::new(&node.path).components().count(), node.id))
+        .collect();
+    dirs.sort_by_key(|(depth, _)| std::cmp::Reverse(*depth));
+    for (_, id) in dirs {
+        if let Some(node) = nodes.get(&id) {
+            analyzers.on_dir_complete(node);
+        }
+    }
+    recompute_dir_sizes(&mut nodes);
+    changed_nodes.extend(nodes.keys().copied());
+    if emitter.is_some() {
+        while emit_partial_batch(&emitter, &scan_id, &nodes, &mut changed_nodes) {}
+        emit_error_rollup_update(&emitter, &scan_id, &mut error_throttle);
+    }
+
+    let mut extension_stats: Vec<ExtensionStat> = Vec::new();
+    let mut size_distribution: Vec<SizeBucket> = Vec::new();
+    let mut analyzer_reports: Vec<AnalyzerReport> = Vec::new();
+    for report in analyzers.finalize() {
+        match report {
+            AnalyzerReport::ExtensionStats { stats } => extension_stats = stats,
+            AnalyzerReport::SizeDistribution { buckets } => size_distribution = buckets,
+            other => analyzer_reports.push(other),
+        }
+    }
+    let result = ScanResult {
+        scan_id,
+        root_id,
+        total_bytes: nodes.get(&root_id).map(|n| n.size_bytes).unwrap_or(0),
+        total_files,
+        total_dirs,
+        extension_stats,
+        size_distribution,
+        analyzer_reports,
+        skipped_paths: Vec::new(),
+        error_paths: Vec::new(),
+        skipped: Vec::new(),
+        error_groups: error_throttle.groups(),
+        root_aliases: Vec::new(),
+        started_at,
+        options: ScanOptions {
+            size_hints: None,
+            resume: None,
+            ..options
+        },
+        reused_from: None,
+        complete: !canceled,
+        incomplete_dirs: Vec::new(),
+        unreadable_regions: Vec::new(),
+        limit_actions: Vec::new(),
+        slow_dirs: Vec::new(),
+    };
+    let tree = ScanTree::with_index(root_id, nodes, path_map);
+    if canceled {
+        return Err(ScanError::Canceled(Some(Box::new(PartialScan { result, tree }))));
+    }
+    if let Some(emitter) = emitter {
+        emit_progress(
+            &emitter,
+            ProgressPayload {
+                scan_id: result.scan_id.clone(),
+                visited_entries,
+                visited_bytes_approx,
+                current_path: root_path_str,
+                phase: PHASE_FINALIZING.to_string(),
+                files_sized: None,
+                files_total: None,
+            },
+        );
+    }
+    Ok((result, tree))
+}
+
+/// Add the folders from `dir` up to the first one already in the tree, returning how
+/// many were added. Nothing is added when no ancestor is in the tree.
+fn ensure_dir_chain(
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    changed_nodes: &mut HashSet<NodeId>,
+    dir: &Path,
+    counter: &AtomicU64,
+) -> u64 {
+    let mut missing = Vec::new();
+    let mut current = Some(dir);
+    while let Some(path) = current {
+        if path_map.contains(&path.to_string_lossy()) {
+            break;
+        }
+        missing.push(path);
+        current = path.parent();
+    }
+    if current.is_none() {
+        return 0;
+    }
+    for path in missing.iter().rev() {
+        let id = ensure_dir_node(nodes, path_map, changed_nodes, path, counter);
+        if let Some(parent) = parent_id_for_path(path_map, path).and_then(|parent| nodes.get_mut(&parent)) {
+            parent.children.push(id);
+        }
+    }
+    missing.len() as u64
+}
+
+/// Pop every open directory at or below `depth` and notify analyzers that it is complete.
+/// The walker is depth-first, so reaching an entry at `depth` means all deeper open
+/// directories have been fully visited.
+fn close_completed_dirs(
+    open_dirs: &mut Vec<(usize, NodeId)>,
+    depth: usize,
+    nodes: &HashMap<NodeId, TreeNode>,
+    analyzers: &mut AnalyzerSet,
+) {
+    while let Some(&(dir_depth, dir_id)) = open_dirs.last() {
+        if dir_depth < depth {
+            break;
+        }
+        open_dirs.pop();
+        if let Some(node) = nodes.get(&dir_id) {
+            analyzers.on_dir_complete(node);
+        }
+    }
+}
+
+/// Copy the finished folders a resumed scan skipped since the last call, with
+/// everything below them, from the canceled scan's tree, replaying their files through
+/// the analyzers. Returns the files, folders and bytes added.
+fn graft_finished_dirs(
+    seed: &ResumeSeed,
+    grafts: &Mutex<Vec<(PathBuf, NodeId)>>,
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    changed_nodes: &mut HashSet<NodeId>,
+    counter: &AtomicU64,
+    analyzers: &mut AnalyzerSet,
+) -> (u64, u64, u64) {
+    let pending = match grafts.lock() {
+        Ok(mut grafts) if !grafts.is_empty() => std::mem::take(&mut *grafts),
+        _ => return (0, 0, 0),
+    };
+    let (mut files, mut dirs, mut bytes) = (0, 0, 0);
+    for (path, old_id) in pending {
+        let Some(parent_id) = parent_id_for_path(path_map, &path) else {
+            continue;
+        };
+        let size = seed.tree().get(old_id).map(|node| node.size_bytes).unwrap_or(0);
+        // (old id, new parent) enters a node; (new id, None) leaves a folder once its
+        // descendants are in, as a walk would complete it
+        let mut stack: Vec<(NodeId, Option<NodeId>)> = vec![(old_id, Some(parent_id))];
+        while let Some((id, new_parent)) = stack.pop() {
+            let Some(new_parent) = new_parent else {
+                if let Some(node) = nodes.get(&id) {
+                    analyzers.on_dir_complete(node);
+                }
+                continue;
+            };
+            let Some(old) = seed.tree().get(id) else {
+                continue;
+            };
+            let new_id = next_node_id(counter);
+            let node = TreeNode {
+                id: new_id,
+                parent: Some(new_parent),
+                children: Vec::new(),
+                ..old.clone()
+            };
+            if let Some(parent) = nodes.get_mut(&new_parent) {
+                parent.children.push(new_id);
+            }
+            path_map.insert(&node.path, new_id);
+            changed_nodes.insert(new_id);
+            match node.kind {
+                NodeKind::File => {
+                    files += 1;
+                    let modified = node.modified.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+                    analyzers.on_file(&FileVisit {
+                        node: &node,
+                        modified,
+                        metadata: None,
+                    });
+                }
+                NodeKind::Dir => {
+                    dirs += 1;
+                    stack.push((new_id, None));
+                    stack.extend(old.children.iter().map(|child| (*child, Some(new_id))));
+                }
+            }
+            nodes.insert(new_id, node);
+        }
+        increment_ancestor_sizes(nodes, Some(parent_id), size, changed_nodes);
+        bytes += size;
+    }
+    (files, dirs, bytes)
+}
+
+/// Paging and hibernation files often fail to stat during the walk. Add them (or flag the
+/// existing nodes) as informational entries so the volume root accounts for their space.
+/// Returns how many new file nodes were added.
+fn add_system_file_nodes(
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    root: &Path,
+    root_id: NodeId,
+    counter: &AtomicU64,
+) -> u64 {
+    let mut added = 0;
+    for name in SYSTEM_FILES {
+        let path = root.join(name);
+        let path_str = path.to_string_lossy().to_string();
+        if let Some(node) = path_map.get(&path_str).and_then(|id| nodes.get_mut(&id)) {
+            node.informational = true;
+            continue;
+        }
+        let Some(size) = system_file_size(&path).filter(|size| *size > 0) else {
+            continue;
+        };
+        let id = next_node_id(counter);
+        nodes.insert(
+            id,
+            TreeNode {
+                id,
+                parent: Some(root_id),
+                name: name.to_string(),
+                path: path_str.clone(),
+                kind: NodeKind::File,
+                size_bytes: size,
+                file_ext: extract_extension(&path),
+                modified: None,
+                informational: true,
+                children: Vec::new(),
+            },
+        );
+        path_map.insert(&path_str, id);
+        if let Some(root_node) = nodes.get_mut(&root_id) {
+            root_node.children.push(id);
+        }
+        added += 1;
+    }
+    added
+}
+
+/// The path a walk error refers to, if any
+fn walk_error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => walk_error_path(err),
+        ignore::Error::Loop { child, .. } => Some(child),
+        _ => None,
     }
-    Ok(result)
+}
+
+fn safe_stat(reader: &Mutex<SafeReader>, path: &Path, follow_symlinks: bool) -> Option<Metadata> {
+    reader.lock().ok()?.stat(path, follow_symlinks)
 }
 
 fn next_node_id(counter: &AtomicU64) -> NodeId {
@@ -312,13 +1071,13 @@ fn next_node_id(counter: &AtomicU64) -> NodeId {
 
 fn ensure_dir_node(
     nodes: &mut HashMap<NodeId, TreeNode>,
-    path_map: &mut HashMap<String, NodeId>,
+    path_map: &mut PathIndex,
     changed_nodes: &mut HashSet<NodeId>,
     path: &Path,
     counter: &AtomicU64,
 ) -> NodeId {
     let path_str = path.to_string_lossy().to_string();
-    if let Some(id) = path_map.get(&path_str).copied() {
+    if let Some(id) = path_map.get(&path_str) {
         return id;
     }
     let name = path
@@ -338,17 +1097,221 @@ fn ensure_dir_node(
             kind: NodeKind::Dir,
             size_bytes: 0,
             file_ext: None,
+            modified: None,
+            informational: false,
             children: Vec::new(),
         },
     );
-    path_map.insert(path_str, id);
+    path_map.insert(&path_str, id);
     changed_nodes.insert(id);
     id
 }
 
+/// A file's size and time, with the full metadata when it came from a stat call
+struct FileMeta<'a> {
+    stat: EntryStat,
+    metadata: Option<&'a Metadata>,
+}
+
+fn file_meta(bulk: Option<EntryStat>, metadata: Option<&Metadata>) -> Option<FileMeta<'_>> {
+    match bulk {
+        Some(stat) => Some(FileMeta { stat, metadata: None }),
+        None => metadata.map(|metadata| FileMeta {
+            stat: EntryStat::from(metadata),
+            metadata: Some(metadata),
+        }),
+    }
+}
+
+/// Add a file to the tree with its size and modification time, feed it to the
+/// analyzers, and grow its ancestors. Returns the size, or None for empty and
+/// unreadable files, which are left out.
+fn add_file_node(
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    changed_nodes: &mut HashSet<NodeId>,
+    counter: &AtomicU64,
+    analyzers: &mut AnalyzerSet,
+    path: &Path,
+    meta: Option<FileMeta<'_>>,
+) -> Option<u64> {
+    let size = meta.as_ref().map(|meta| meta.stat.size).unwrap_or(0);
+    if size == 0 {
+        return None;
+    }
+    let modified = meta.as_ref().and_then(|meta| meta.stat.modified);
+    let metadata = meta.and_then(|meta| meta.metadata);
+    let parent_id = parent_id_for_path(path_map, path);
+    let file_id = ensure_file_node(nodes, path_map, changed_nodes, path, parent_id, counter, size);
+
+    // Add to parent's children (walker doesn't yield duplicates)
+    if let Some(parent) = parent_id.and_then(|id| nodes.get_mut(&id)) {
+        parent.children.push(file_id);
+    }
+    if let Some(node) = nodes.get_mut(&file_id) {
+        node.modified = modified
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        analyzers.on_file(&FileVisit { node, modified, metadata });
+    }
+    increment_ancestor_sizes(nodes, parent_id, size, changed_nodes);
+    Some(size)
+}
+
+/// The folder entry a file of `size` is counted into instead of getting a node: files
+/// at or above the scan's size ceiling, and small files once past the memory cap
+fn fold_into(
+    size_ceiling: Option<u64>,
+    memory_cap: &mut MemoryCap,
+    node_count: usize,
+    size: u64,
+) -> Option<&'static str> {
+    if size == 0 {
+        return None;
+    }
+    if size_ceiling.is_some_and(|ceiling| size >= ceiling) {
+        return Some(LARGE_FILES_NAME);
+    }
+    if memory_cap.should_fold(node_count, size) {
+        memory_cap.record_folded(size);
+        return Some(SMALL_FILES_NAME);
+    }
+    None
+}
+
+/// A file to count into the folder entry named `into`
+/// A file to size and add to the tree, and how to read it
+struct SizedFile<'a> {
+    path: &'a Path,
+    /// The walker's entry, whose metadata is used when nothing else read the file; the
+    /// sizing pass of a two-phase scan has none and stats the path
+    entry: Option<&'a ignore::DirEntry>,
+    stat_cache: Option<&'a mut StatCache>,
+    safe_reader: Option<&'a Mutex<SafeReader>>,
+    memory_cap: &'a mut MemoryCap,
+    options: &'a ScanOptions,
+}
+
+/// Read a file's size and add it to the tree, folded into its folder's small or large
+/// files entry when the scan asks. Only stats when a bulk backend didn't already read
+/// the file; errors aren't logged, for speed. Returns the size like `add_file_node`.
+fn size_file(
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    changed_nodes: &mut HashSet<NodeId>,
+    counter: &AtomicU64,
+    analyzers: &mut AnalyzerSet,
+    file: SizedFile<'_>,
+) -> Option<u64> {
+    let SizedFile {
+        path,
+        entry,
+        stat_cache,
+        safe_reader,
+        memory_cap,
+        options,
+    } = file;
+    let bulk = stat_cache.and_then(|cache| cache.take(path));
+    let metadata = match (&bulk, safe_reader, entry) {
+        (Some(_), _, _) => None,
+        (None, Some(reader), _) => safe_stat(reader, path, options.follow_symlinks),
+        (None, None, Some(entry)) => entry.metadata().ok(),
+        (None, None, None) if options.follow_symlinks => std::fs::metadata(path).ok(),
+        (None, None, None) => std::fs::symlink_metadata(path).ok(),
+    };
+    let meta = file_meta(bulk, metadata.as_ref());
+    let size = meta.as_ref().map_or(0, |meta| meta.stat.size);
+    match (meta, fold_into(options.size_ceiling, memory_cap, nodes.len(), size)) {
+        (Some(meta), Some(into)) => fold_file(
+            nodes,
+            path_map,
+            changed_nodes,
+            counter,
+            analyzers,
+            FoldedFile { path, meta, into },
+        ),
+        (meta, _) => add_file_node(nodes, path_map, changed_nodes, counter, analyzers, path, meta),
+    }
+}
+
+struct FoldedFile<'a> {
+    path: &'a Path,
+    meta: FileMeta<'a>,
+    into: &'static str,
+}
+
+/// Count a file into its folder's "<small files>" or "<large files>" entry instead of
+/// giving it a node. Analyzers still see the file, under the entry's id. Returns the
+/// size like `add_file_node`.
+fn fold_file(
+    nodes: &mut HashMap<NodeId, TreeNode>,
+    path_map: &mut PathIndex,
+    changed_nodes: &mut HashSet<NodeId>,
+    counter: &AtomicU64,
+    analyzers: &mut AnalyzerSet,
+    file: FoldedFile<'_>,
+) -> Option<u64> {
+    let FoldedFile { path, meta, into } = file;
+    let size = meta.stat.size;
+    let parent_id = parent_id_for_path(path_map, path);
+    let folded_path = path.parent()?.join(into).to_string_lossy().to_string();
+    let folded_id = match path_map.get(&folded_path) {
+        Some(id) => id,
+        None => {
+            let id = next_node_id(counter);
+            nodes.insert(
+                id,
+                TreeNode {
+                    id,
+                    parent: parent_id,
+                    name: into.to_string(),
+                    path: folded_path.clone(),
+                    kind: NodeKind::File,
+                    size_bytes: 0,
+                    file_ext: None,
+                    modified: None,
+                    informational: true,
+                    children: Vec::new(),
+                },
+            );
+            path_map.insert(&folded_path, id);
+            if let Some(parent) = parent_id.and_then(|id| nodes.get_mut(&id)) {
+                parent.children.push(id);
+            }
+            id
+        }
+    };
+    let modified = meta.stat.modified;
+    let seconds = modified.and_then(|m| m.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs());
+    if let Some(folded) = nodes.get_mut(&folded_id) {
+        folded.size_bytes += size;
+        folded.modified = folded.modified.max(seconds);
+    }
+    let visit = TreeNode {
+        id: folded_id,
+        parent: parent_id,
+        name: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        kind: NodeKind::File,
+        size_bytes: size,
+        file_ext: extract_extension(path),
+        modified: seconds,
+        informational: false,
+        children: Vec::new(),
+    };
+    analyzers.on_file(&FileVisit {
+        node: &visit,
+        modified,
+        metadata: meta.metadata,
+    });
+    changed_nodes.insert(folded_id);
+    increment_ancestor_sizes(nodes, parent_id, size, changed_nodes);
+    Some(size)
+}
+
 fn ensure_file_node(
     nodes: &mut HashMap<NodeId, TreeNode>,
-    path_map: &mut HashMap<String, NodeId>,
+    path_map: &mut PathIndex,
     changed_nodes: &mut HashSet<NodeId>,
     path: &Path,
     parent_id: Option<NodeId>,
@@ -356,7 +1319,7 @@ fn ensure_file_node(
     size: u64,
 ) -> NodeId {
     let path_str = path.to_string_lossy().to_string();
-    if let Some(id) = path_map.get(&path_str).copied() {
+    if let Some(id) = path_map.get(&path_str) {
         if let Some(node) = nodes.get_mut(&id) {
             node.size_bytes = size;
             changed_nodes.insert(id);
@@ -380,18 +1343,70 @@ fn ensure_file_node(
             kind: NodeKind::File,
             size_bytes: size,
             file_ext: ext,
+            modified: None,
+            informational: false,
             children: Vec::new(),
         },
     );
-    path_map.insert(path_str, id);
+    path_map.insert(&path_str, id);
     changed_nodes.insert(id);
     id
 }
 
-fn parent_id_for_path(path_map: &HashMap<String, NodeId>, path: &Path) -> Option<NodeId> {
-    path.parent()
-        .and_then(|p| path_map.get(&p.to_string_lossy().to_string()))
-        .copied()
+fn parent_id_for_path(path_map: &PathIndex, path: &Path) -> Option<NodeId> {
+    path.parent().and_then(|p| path_map.get(&p.to_string_lossy()))
+}
+
+/// Whether the filesystem holding `root` ignores case in names. Looks up a component of
+/// the root (or failing that, an entry inside it) with its case flipped and checks that
+/// it resolves to the same file; falls back to the platform default.
+pub fn is_case_insensitive(root: &Path) -> bool {
+    let candidates = root.ancestors().map(Path::to_path_buf).chain(
+        std::fs::read_dir(root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .take(16),
+    );
+    for path in candidates {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let flipped: String = name
+            .chars()
+            .map(|c| {
+                if c.is_lowercase() {
+                    c.to_uppercase().next().unwrap_or(c)
+                } else {
+                    c.to_lowercase().next().unwrap_or(c)
+                }
+            })
+            .collect();
+        if flipped == name {
+            continue;
+        }
+        let other = path.with_file_name(flipped);
+        return match (std::fs::symlink_metadata(&path), std::fs::symlink_metadata(&other)) {
+            (Ok(original), Ok(flipped)) => same_file(&original, &flipped),
+            (Ok(_), Err(_)) => false,
+            // The original vanished; try the next candidate
+            _ => continue,
+        };
+    }
+    cfg!(any(target_os = "windows", target_os = "macos"))
+}
+
+#[cfg(unix)]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Without stable file ids in std, matching type, size, and times is the best check
+#[cfg(not(unix))]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    a.file_type() == b.file_type() && a.len() == b.len() && a.modified().ok() == b.modified().ok()
 }
 
 fn increment_ancestor_sizes(
@@ -447,33 +1462,18 @@ fn recompute_dir_sizes(nodes: &mut HashMap<NodeId, TreeNode>) {
     }
 }
 
-fn maybe_emit_progress(
-    app_handle: &Option<AppHandle>,
-    scan_id: &str,
-    visited_entries: u64,
-    visited_bytes_approx: u64,
-    current_path: &str,
-    last_emit: &mut Instant,
-    phase: &str,
-) {
+fn maybe_emit_progress(emitter: &Option<ScanEmitter>, last_emit: &mut Instant, payload: ProgressPayload) {
     if last_emit.elapsed() < PROGRESS_INTERVAL {
         return;
     }
-    if let Some(handle) = app_handle {
-        let payload = ProgressPayload {
-            scan_id: scan_id.to_string(),
-            visited_entries,
-            visited_bytes_approx,
-            current_path: current_path.to_string(),
-            phase: phase.to_string(),
-        };
-        emit_progress(handle, payload);
+    if let Some(emitter) = emitter {
+        emit_progress(emitter, payload);
         *last_emit = Instant::now();
     }
 }
 
 fn maybe_emit_partial(
-    app_handle: &Option<AppHandle>,
+    emitter: &Option<ScanEmitter>,
     scan_id: &str,
     nodes: &HashMap<NodeId, TreeNode>,
     changed_nodes: &mut HashSet<NodeId>,
@@ -482,13 +1482,13 @@ fn maybe_emit_partial(
     if last_emit.elapsed() < PARTIAL_INTERVAL {
         return;
     }
-    if emit_partial_batch(app_handle, scan_id, nodes, changed_nodes) {
+    if emit_partial_batch(emitter, scan_id, nodes, changed_nodes) {
         *last_emit = Instant::now();
     }
 }
 
 fn emit_partial_batch(
-    app_handle: &Option<AppHandle>,
+    emitter: &Option<ScanEmitter>,
     scan_id: &str,
     nodes: &HashMap<NodeId, TreeNode>,
     changed_nodes: &mut HashSet<NodeId>,
@@ -496,7 +1496,7 @@ fn emit_partial_batch(
     if changed_nodes.is_empty() {
         return false;
     }
-    if let Some(handle) = app_handle {
+    if let Some(emitter) = emitter {
         let mut deltas = Vec::new();
         let mut count = 0usize;
         let mut ids: Vec<NodeId> = changed_nodes.drain().collect();
@@ -512,7 +1512,7 @@ fn emit_partial_batch(
             }
         }
         emit_partial_tree(
-            handle,
+            emitter,
             PartialTreePayload {
                 scan_id: scan_id.to_string(),
                 nodes: deltas,
@@ -524,7 +1524,23 @@ fn emit_partial_batch(
     false
 }
 
-fn node_to_delta(node: &TreeNode) -> TreeNodeDelta {
+fn emit_skipped_update(emitter: &Option<ScanEmitter>, scan_id: &str, skips: &Mutex<SkipTally>) {
+    let Some(emitter) = emitter else {
+        return;
+    };
+    let update = skips.lock().ok().and_then(|mut skips| skips.take_update());
+    if let Some(groups) = update {
+        emit_skipped(
+            emitter,
+            SkippedPayload {
+                scan_id: scan_id.to_string(),
+                groups,
+            },
+        );
+    }
+}
+
+pub fn node_to_delta(node: &TreeNode) -> TreeNodeDelta {
     TreeNodeDelta {
         id: node.id,
         parent: node.parent,
@@ -533,24 +1549,38 @@ fn node_to_delta(node: &TreeNode) -> TreeNodeDelta {
         kind: node.kind,
         size_bytes: node.size_bytes,
         file_ext: node.file_ext.clone(),
+        informational: node.informational,
+    }
+}
+
+fn emit_error_rollup_update(emitter: &Option<ScanEmitter>, scan_id: &str, throttle: &mut ErrorThrottle) {
+    let Some(emitter) = emitter else {
+        return;
+    };
+    if let Some((groups, suppressed)) = throttle.take_rollup() {
+        emit_error_rollup(
+            emitter,
+            ErrorRollupPayload {
+                scan_id: scan_id.to_string(),
+                groups,
+                suppressed,
+            },
+        );
     }
 }
 
 fn emit_error_optional(
-    app_handle: &Option<AppHandle>,
+    emitter: &Option<ScanEmitter>,
     scan_id: &str,
     message: &str,
     path: Option<String>,
 ) {
-    if let Some(handle) = app_handle {
-        emit_error(
-            handle,
-            ErrorPayload {
-                scan_id: scan_id.to_string(),
-                message: message.to_string(),
-                path,
-            },
+    if let Some(emitter) = emitter {
+        let localized = Message::new(
+            messages::SCAN_READ_FAILED,
+            [("error", message.to_string()), ("path", path.clone().unwrap_or_default())],
         );
+        emit_error(emitter, ErrorPayload::new(scan_id.to_string(), localized, path));
     }
 }
 
@@ -565,6 +1595,7 @@ fn now_millis() -> u64 {
 mod tests {
     use super::*;
     use std::fs::{create_dir_all, write};
+    use crate::scan::limits::ScanLimits;
     use tempfile::tempdir;
 
     #[test]
@@ -576,7 +1607,7 @@ mod tests {
         write(root.join("a.txt"), vec![0u8; 5]).expect("write a");
         write(subdir.join("b.bin"), vec![0u8; 7]).expect("write b");
 
-        let result = run_scan(
+        let (result, tree) = run_scan(
             None,
             "test-scan".to_string(),
             root.to_string_lossy().to_string(),
@@ -587,6 +1618,117 @@ mod tests {
 
         assert_eq!(result.total_bytes, 12);
         assert_eq!(result.total_files, 2);
+        let sub = tree
+            .find_by_path(&subdir.canonicalize().expect("canonical").to_string_lossy())
+            .expect("stored sub dir");
+        assert_eq!(sub.size_bytes, 7);
+    }
+
+    struct CompletedDirs(Arc<std::sync::Mutex<Vec<(String, u64)>>>);
+
+    impl crate::scan::analyzer::Analyzer for CompletedDirs {
+        fn on_file(&mut self, _file: &FileVisit<'_>) {}
+
+        fn on_dir_complete(&mut self, dir: &TreeNode) {
+            self.0.lock().unwrap().push((dir.name.clone(), dir.size_bytes));
+        }
+
+        fn finalize(self: Box<Self>) -> AnalyzerReport {
+            AnalyzerReport::TopFiles { files: Vec::new() }
+        }
+    }
+
+    #[test]
+    fn analyzers_see_completed_dirs_with_final_sizes() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("outer").join("inner")).expect("create dirs");
+        write(root.join("outer").join("inner").join("a.bin"), vec![0u8; 3]).expect("write a");
+        write(root.join("outer").join("b.bin"), vec![0u8; 4]).expect("write b");
+
+        let completed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut analyzers = AnalyzerSet::from_configs(&[]);
+        analyzers.register(Box::new(CompletedDirs(completed.clone())));
+
+        run_scan_with_analyzers(
+            None,
+            "test-analyzers".to_string(),
+            root.to_string_lossy().to_string(),
+            ScanOptions::default(),
+            Arc::new(AtomicBool::new(false)),
+            analyzers,
+        )
+        .expect("scan result");
+
+        let completed = completed.lock().unwrap();
+        let names: Vec<&str> = completed.iter().map(|(name, _)| name.as_str()).collect();
+        let inner = names.iter().position(|n| *n == "inner").expect("inner completed");
+        let outer = names.iter().position(|n| *n == "outer").expect("outer completed");
+        assert!(inner < outer);
+        assert_eq!(completed.len(), 3);
+        assert_eq!(completed[outer].1, 7);
+    }
+
+    #[test]
+    fn two_phase_scan_sizes_files_after_the_structure() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("outer").join("inner")).expect("create dirs");
+        write(root.join("outer").join("inner").join("a.bin"), vec![0u8; 3]).expect("write a");
+        write(root.join("outer").join("b.bin"), vec![0u8; 4]).expect("write b");
+        write(root.join("empty.txt"), Vec::new()).expect("write empty");
+
+        let completed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut analyzers = AnalyzerSet::from_configs(&[]);
+        analyzers.register(Box::new(CompletedDirs(completed.clone())));
+        let options = ScanOptions {
+            two_phase: true,
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan_with_analyzers(
+            None,
+            "test-two-phase".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+            analyzers,
+        )
+        .expect("scan result");
+
+        assert_eq!((result.total_bytes, result.total_files, result.total_dirs), (7, 2, 3));
+        assert_eq!(result.extension_stats.iter().map(|stat| stat.count).sum::<u64>(), 2);
+        let outer = tree
+            .find_by_path(&root.join("outer").canonicalize().expect("canonical").to_string_lossy())
+            .expect("outer dir");
+        assert_eq!(outer.children.len(), 2);
+        let completed = completed.lock().unwrap();
+        assert_eq!(completed.len(), 3);
+        assert!(completed.iter().any(|(name, size)| name == "outer" && *size == 7));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn detects_case_sensitive_filesystem() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path().join("Mixed");
+        create_dir_all(&root).expect("create dir");
+        write(root.join("Notes.txt"), b"x").expect("write");
+        assert!(!is_case_insensitive(&root));
+
+        // A second folder differing only in case is a separate node, not a coalesced one
+        create_dir_all(temp.path().join("mixed")).expect("create lower dir");
+        write(temp.path().join("mixed").join("notes.txt"), b"yy").expect("write lower");
+        let (result, tree) = run_scan(
+            None,
+            "test-case".to_string(),
+            temp.path().to_string_lossy().to_string(),
+            ScanOptions::default(),
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan result");
+        assert_eq!(result.total_files, 2);
+        assert_eq!(result.total_bytes, 3);
+        assert!(!tree.case_insensitive());
     }
 
     #[test]
@@ -610,6 +1752,214 @@ mod tests {
             cancel,
         );
 
-        assert!(matches!(result, Err(ScanError::Canceled)));
+        assert!(matches!(result, Err(ScanError::Canceled(None))));
+    }
+
+    #[test]
+    fn memory_cap_folds_small_files_per_folder() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("logs")).expect("create logs");
+        write(root.join("logs/a.log"), vec![0u8; 4]).expect("write a");
+        write(root.join("logs/b.log"), vec![0u8; 6]).expect("write b");
+        let options = ScanOptions {
+            limits: ScanLimits {
+                max_memory_bytes: Some(1),
+                ..ScanLimits::default()
+            },
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan(
+            None,
+            "capped".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+        assert_eq!((result.total_files, result.total_bytes), (2, 10));
+        let logs = format!("{}/logs", tree.root().expect("root").path);
+        let folded = tree
+            .find_by_path(&format!("{}/{}", logs, SMALL_FILES_NAME))
+            .expect("folded entry");
+        assert_eq!((folded.size_bytes, folded.informational), (10, true));
+        assert!(tree.find_by_path(&format!("{}/a.log", logs)).is_none());
+        assert!(matches!(
+            result.limit_actions.as_slice(),
+            [LimitAction::AggregatedSmallFiles { files: 2, bytes: 10, .. }]
+        ));
+    }
+
+    #[test]
+    fn size_ceiling_counts_large_files_without_listing_them() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        write(root.join("note.txt"), vec![0u8; 5]).expect("write note");
+        write(root.join("movie.mkv"), vec![0u8; 500]).expect("write movie");
+        write(root.join("show.mkv"), vec![0u8; 300]).expect("write show");
+        let options = ScanOptions {
+            size_ceiling: Some(100),
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan(
+            None,
+            "ceiling".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+        assert_eq!((result.total_files, result.total_bytes), (3, 805));
+        let root_node = tree.root().expect("root");
+        let names: Vec<&str> = root_node
+            .children
+            .iter()
+            .filter_map(|id| tree.get(*id))
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"note.txt") && names.contains(&LARGE_FILES_NAME));
+        let large = tree
+            .find_by_path(&format!("{}/{}", root_node.path, LARGE_FILES_NAME))
+            .expect("large entry");
+        assert_eq!(large.size_bytes, 800);
+    }
+
+    #[test]
+    fn profiled_scan_times_each_directory() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("logs")).expect("mkdir");
+        for name in ["a.log", "b.log", "c.log"] {
+            write(root.join("logs").join(name), b"x").expect("write log");
+        }
+        let options = ScanOptions {
+            profile_dirs: true,
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan(
+            None,
+            "profiled".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+        let logs = tree.find_by_path(&root.join("logs").to_string_lossy()).expect("logs");
+        let timing = result.slow_dirs.iter().find(|dir| dir.path == logs.path).expect("logs timed");
+        assert_eq!(timing.entries, 3);
+        assert!(run_scan(
+            None,
+            "plain".to_string(),
+            root.to_string_lossy().to_string(),
+            ScanOptions::default(),
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan")
+        .0
+        .slow_dirs
+        .is_empty());
+    }
+
+    #[test]
+    fn resumed_scan_copies_finished_folders() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("done/inner")).expect("create done");
+        create_dir_all(root.join("open")).expect("create open");
+        write(root.join("done/inner/a.bin"), vec![0u8; 7]).expect("write a");
+        write(root.join("open/b.bin"), vec![0u8; 3]).expect("write b");
+        let (mut partial, tree) = run_scan(
+            None,
+            "partial".to_string(),
+            root.to_string_lossy().to_string(),
+            ScanOptions::default(),
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+        // As if the walk had been canceled inside `open`
+        let root_path = tree.root().expect("root").path.clone();
+        partial.complete = false;
+        partial.incomplete_dirs = vec![root_path.clone(), format!("{}/open", root_path)];
+
+        // Changes inside the finished folder go unseen; the unfinished one is walked
+        write(root.join("done/inner/late.bin"), vec![0u8; 100]).expect("write late");
+        write(root.join("open/c.bin"), vec![0u8; 5]).expect("write c");
+        let options = ScanOptions {
+            resume: Some(Arc::new(ResumeSeed::new("partial", &partial, tree))),
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan(
+            None,
+            "resumed".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("resume");
+
+        assert!(result.complete);
+        assert_eq!((result.total_bytes, result.total_files, result.total_dirs), (15, 3, 4));
+        let inner = tree.find_by_path(&format!("{}/done/inner", root_path)).expect("inner");
+        assert_eq!(inner.size_bytes, 7);
+        assert!(tree.find_by_path(&format!("{}/done/inner/a.bin", root_path)).is_some());
+    }
+
+    #[test]
+    fn excluded_entries_are_counted_not_scanned() {
+        let temp = tempdir().expect("tempdir");
+        let root = temp.path();
+        create_dir_all(root.join("cache")).expect("create cache");
+        write(root.join("cache/blob.bin"), vec![0u8; 9]).expect("write blob");
+        write(root.join("keep.txt"), vec![0u8; 4]).expect("write keep");
+        write(root.join("disk.iso"), vec![0u8; 6]).expect("write iso");
+
+        let options = ScanOptions {
+            exclude_patterns: vec!["cache/".to_string(), "*.iso".to_string()],
+            ..ScanOptions::default()
+        };
+        let (result, _) = run_scan(
+            None,
+            "test-exclude".to_string(),
+            root.to_string_lossy().to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+
+        assert_eq!(result.total_bytes, 4);
+        let excluded = result
+            .skipped
+            .iter()
+            .find(|group| group.reason == SkipReason::ExcludedPattern)
+            .expect("excluded group");
+        assert_eq!(excluded.count, 2);
+    }
+
+    #[test]
+    fn file_list_scan_builds_the_tree_without_the_disk() {
+        let temp = tempdir().expect("tempdir");
+        let list = temp.path().join("inventory.txt");
+        write(
+            &list,
+            "100\tphotos/2023/a.jpg\n50\tphotos/2023/b.jpg\nphotos/c.png\t30\n\nnot a line\n100\tphotos/2023/a.jpg\n",
+        )
+        .expect("write list");
+        let options = ScanOptions {
+            file_list: Some(list.to_string_lossy().to_string()),
+            ..ScanOptions::default()
+        };
+        let (result, tree) = run_scan(
+            None,
+            "test-list".to_string(),
+            "s3://bucket".to_string(),
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .expect("scan");
+
+        assert_eq!((result.total_bytes, result.total_files, result.total_dirs), (180, 3, 3));
+        assert_eq!(tree.find_by_path("s3://bucket/photos/2023").map(|node| node.size_bytes), Some(150));
+        assert_eq!(result.error_groups.iter().map(|group| group.count).sum::<u64>(), 1);
     }
 }
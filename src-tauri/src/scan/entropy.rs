@@ -0,0 +1,164 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, HighEntropyFile};
+
+pub const DEFAULT_ENTROPY_MIN_BYTES: u64 = 16 * 1024 * 1024;
+/// Bits per byte above which a sample looks random; compressed media usually stays
+/// just under it and is recognized by its header anyway
+const RANDOM_THRESHOLD: f64 = 7.99;
+const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Leading bytes of formats that are compressed or encrypted by design, so high entropy
+/// is expected. Offsets are where the magic starts.
+const KNOWN_HEADERS: &[(usize, &[u8])] = &[
+    (0, b"PK\x03\x04"),
+    (0, b"7z\xBC\xAF\x27\x1C"),
+    (0, b"Rar!"),
+    (0, b"\x1F\x8B"),
+    (0, b"BZh"),
+    (0, b"\xFD7zXZ\x00"),
+    (0, b"\x28\xB5\x2F\xFD"),
+    (0, b"MSCF"),
+    (0, b"\xFF\xD8\xFF"),
+    (0, b"\x89PNG"),
+    (0, b"GIF8"),
+    (0, b"RIFF"),
+    (0, b"OggS"),
+    (0, b"fLaC"),
+    (0, b"ID3"),
+    (0, b"\x1A\x45\xDF\xA3"),
+    (0, b"%PDF"),
+    (0, b"MZ"),
+    (0, b"\x7FELF"),
+    (0, b"SQLite format 3"),
+    (4, b"ftyp"),
+    // VHDX and VMDK images wrap guest data that is often encrypted itself
+    (0, b"vhdxfile"),
+    (0, b"KDMV"),
+];
+
+/// Flags large files whose start, middle, and end all look like random data and that
+/// carry no known header: likely encrypted containers (VeraCrypt has no header) or
+/// files overwritten by ransomware
+pub struct HighEntropyAnalyzer {
+    min_bytes: u64,
+    found: Vec<HighEntropyFile>,
+}
+
+impl HighEntropyAnalyzer {
+    pub fn new(min_bytes: u64) -> Self {
+        Self {
+            min_bytes,
+            found: Vec::new(),
+        }
+    }
+}
+
+impl Analyzer for HighEntropyAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let node = file.node;
+        if node.size_bytes < self.min_bytes {
+            return;
+        }
+        if let Some(entropy) = sample_entropy(Path::new(&node.path), node.size_bytes) {
+            self.found.push(HighEntropyFile {
+                id: node.id,
+                path: node.path.clone(),
+                size_bytes: node.size_bytes,
+                entropy,
+            });
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut files = self.found;
+        files.sort_by_key(|f| std::cmp::Reverse(f.size_bytes));
+        AnalyzerReport::HighEntropy { files }
+    }
+}
+
+/// The lowest entropy of three samples when all of them look random and the file has
+/// no known header, otherwise None
+fn sample_entropy(path: &Path, size: u64) -> Option<f64> {
+    let mut file = File::open(path).ok()?;
+    let mut buffer = vec![0u8; SAMPLE_BYTES];
+    let mut lowest = f64::MAX;
+    let last = size.saturating_sub(SAMPLE_BYTES as u64);
+    for (index, offset) in [0, size / 2, last].into_iter().enumerate() {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let read = read_up_to(&mut file, &mut buffer).ok()?;
+        let sample = &buffer[..read];
+        if index == 0 && has_known_header(sample) {
+            return None;
+        }
+        let entropy = shannon_entropy(sample);
+        if entropy < RANDOM_THRESHOLD {
+            return None;
+        }
+        lowest = lowest.min(entropy);
+    }
+    Some(lowest)
+}
+
+fn read_up_to(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+fn has_known_header(sample: &[u8]) -> bool {
+    KNOWN_HEADERS
+        .iter()
+        .any(|(offset, magic)| sample.get(*offset..offset + magic.len()) == Some(*magic))
+}
+
+/// Shannon entropy in bits per byte (0 for constant data, 8 for uniform random)
+fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0u64; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_separates_random_from_structured_data() {
+        // xorshift gives bytes close enough to uniform for a 64 KB sample
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let random: Vec<u8> = (0..SAMPLE_BYTES)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect();
+        assert!(shannon_entropy(&random) > RANDOM_THRESHOLD);
+        assert!(shannon_entropy(&vec![b'a'; 1024]) < 0.01);
+        assert!(has_known_header(b"PK\x03\x04rest"));
+        assert!(has_known_header(b"\x00\x00\x00\x18ftypmp42"));
+    }
+}
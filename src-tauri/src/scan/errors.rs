@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// How often errors held back from individual events are sent as a rollup
+pub const ERROR_ROLLUP_INTERVAL: Duration = Duration::from_secs(1);
+/// Errors sent as their own event per scan, each the first of its group; everything
+/// after that only reaches the frontend in rollups
+const INDIVIDUAL_ERROR_LIMIT: u32 = 20;
+/// Paths kept per group
+const SAMPLE_LIMIT: usize = 5;
+
+/// Broad cause of a read error, for grouping
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    PermissionDenied,
+    NotFound,
+    TimedOut,
+    /// A symlink cycle
+    Loop,
+    Io,
+}
+
+impl ScanErrorKind {
+    pub fn of(err: &ignore::Error) -> Self {
+        match err {
+            ignore::Error::Loop { .. } => ScanErrorKind::Loop,
+            ignore::Error::WithPath { err, .. }
+            | ignore::Error::WithDepth { err, .. }
+            | ignore::Error::WithLineNumber { err, .. } => Self::of(err),
+            _ => match err.io_error().map(io::Error::kind) {
+                Some(io::ErrorKind::PermissionDenied) => ScanErrorKind::PermissionDenied,
+                Some(io::ErrorKind::NotFound) => ScanErrorKind::NotFound,
+                Some(io::ErrorKind::TimedOut) => ScanErrorKind::TimedOut,
+                _ => ScanErrorKind::Io,
+            },
+        }
+    }
+}
+
+/// Errors of one kind under one parent directory
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ErrorGroup {
+    pub kind: ScanErrorKind,
+    /// Folder holding the failing entries; empty when the errors carried no path
+    pub parent: String,
+    pub count: u64,
+    /// The first error's message
+    pub message: String,
+    pub sample: Vec<String>,
+}
+
+/// Per-scan accounting of read errors. A flaky share can fail tens of thousands of
+/// reads; only the first error of each group (up to a limit) is sent on its own, and
+/// the rest go out as periodic rollups of the groups that grew.
+#[derive(Default)]
+pub struct ErrorThrottle {
+    groups: Vec<ErrorGroup>,
+    index: HashMap<(ScanErrorKind, String), usize>,
+    /// Groups that grew since the last rollup
+    changed: Vec<usize>,
+    individual_sent: u32,
+    /// Errors not sent as their own event
+    suppressed: u64,
+}
+
+impl ErrorThrottle {
+    /// Count an error; true when it should also be sent as its own event
+    pub fn record(&mut self, kind: ScanErrorKind, path: Option<&str>, message: &str) -> bool {
+        let parent = path
+            .and_then(|path| Path::new(path).parent())
+            .map(|parent| parent.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (index, is_new) = match self.index.get(&(kind, parent.clone())) {
+            Some(index) => (*index, false),
+            None => {
+                self.groups.push(ErrorGroup {
+                    kind,
+                    parent: parent.clone(),
+                    count: 0,
+                    message: message.to_string(),
+                    sample: Vec::new(),
+                });
+                self.index.insert((kind, parent), self.groups.len() - 1);
+                (self.groups.len() - 1, true)
+            }
+        };
+        let group = &mut self.groups[index];
+        group.count += 1;
+        if let Some(path) = path.filter(|_| group.sample.len() < SAMPLE_LIMIT) {
+            group.sample.push(path.to_string());
+        }
+
+        if is_new && self.individual_sent < INDIVIDUAL_ERROR_LIMIT {
+            self.individual_sent += 1;
+            return true;
+        }
+        self.suppressed += 1;
+        if !self.changed.contains(&index) {
+            self.changed.push(index);
+        }
+        false
+    }
+
+    /// The groups that grew since the last call, with their running totals, and the
+    /// number of errors held back so far; None when nothing was held back since
+    pub fn take_rollup(&mut self) -> Option<(Vec<ErrorGroup>, u64)> {
+        if self.changed.is_empty() {
+            return None;
+        }
+        let groups = std::mem::take(&mut self.changed)
+            .into_iter()
+            .map(|index| self.groups[index].clone())
+            .collect();
+        Some((groups, self.suppressed))
+    }
+
+    /// Every group seen during the scan, largest first
+    pub fn groups(&self) -> Vec<ErrorGroup> {
+        let mut groups = self.groups.clone();
+        groups.sort_by_key(|group| std::cmp::Reverse(group.count));
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_the_first_of_each_group_and_rolls_up_the_rest() {
+        let mut throttle = ErrorThrottle::default();
+        let sent: Vec<bool> = (0..100)
+            .map(|i| {
+                throttle.record(
+                    ScanErrorKind::TimedOut,
+                    Some(&format!("/mnt/share/mail/{}", i)),
+                    "timed out",
+                )
+            })
+            .collect();
+        assert_eq!(sent.iter().filter(|sent| **sent).count(), 1);
+        assert!(throttle.record(ScanErrorKind::PermissionDenied, Some("/mnt/share/private/x"), "denied"));
+
+        let (groups, suppressed) = throttle.take_rollup().expect("rollup");
+        assert_eq!(suppressed, 99);
+        assert_eq!(groups.len(), 1);
+        assert_eq!((groups[0].parent.as_str(), groups[0].count), ("/mnt/share/mail", 100));
+        assert_eq!(groups[0].sample.len(), SAMPLE_LIMIT);
+        assert!(throttle.take_rollup().is_none());
+
+        for i in 0..(INDIVIDUAL_ERROR_LIMIT + 5) {
+            throttle.record(ScanErrorKind::Io, Some(&format!("/d{}/f", i)), "io");
+        }
+        assert_eq!(throttle.individual_sent, INDIVIDUAL_ERROR_LIMIT);
+        assert_eq!(throttle.groups().first().map(|group| group.count), Some(100));
+    }
+}
@@ -1,20 +1,37 @@
 use serde::Serialize;
+use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 
-use crate::scan::model::{NodeId, ScanSummary, TreeNodeDelta};
+use crate::scan::errors::ErrorGroup;
+use crate::scan::messages::Message;
+use crate::scan::model::{NodeId, ScanOverlap, ScanSummary, SkipGroup, TreeNodeDelta};
+use crate::scan::volumes::VolumeCapabilities;
+use crate::scan::watchdog::ScanPulse;
 
 pub const EVENT_STARTED: &str = "scan://started";
 pub const EVENT_PROGRESS: &str = "scan://progress";
 pub const EVENT_PARTIAL_TREE: &str = "scan://partial-tree";
 pub const EVENT_FINISHED: &str = "scan://finished";
 pub const EVENT_ERROR: &str = "scan://error";
+pub const EVENT_ERROR_ROLLUP: &str = "scan://error-rollup";
 pub const EVENT_CANCELED: &str = "scan://canceled";
+pub const EVENT_HEARTBEAT: &str = "scan://heartbeat";
+pub const EVENT_STALLED: &str = "scan://stalled";
+pub const EVENT_SKIPPED: &str = "scan://skipped";
+pub const EVENT_ROOT_LOST: &str = "scan://root_lost";
 
 #[derive(Clone, Debug, Serialize)]
 pub struct StartedPayload {
     pub scan_id: String,
     pub root_path: String,
     pub started_at: u64,
+    /// The root after resolving mapped drives, subst, and symlinks, when that differs
+    /// from `root_path`
+    pub canonical_root: Option<String>,
+    /// Stored scans covering part of the same location
+    pub overlaps: Vec<ScanOverlap>,
+    /// What the scanned volume supports; None when no mounted volume holds the root
+    pub capabilities: Option<VolumeCapabilities>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -24,6 +41,9 @@ pub struct ProgressPayload {
     pub visited_bytes_approx: u64,
     pub current_path: String,
     pub phase: String,
+    /// Files stat'ed so far and files found, during the sizing pass of a two-phase scan
+    pub files_sized: Option<u64>,
+    pub files_total: Option<u64>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -46,35 +66,159 @@ pub struct ErrorPayload {
     pub scan_id: String,
     pub message: String,
     pub path: Option<String>,
+    /// `message` as a code and params for the frontend to translate
+    pub localized: Message,
+}
+
+impl ErrorPayload {
+    pub fn new(scan_id: String, localized: Message, path: Option<String>) -> Self {
+        Self {
+            scan_id,
+            message: localized.text.clone(),
+            path,
+            localized,
+        }
+    }
+}
+
+/// Errors held back from individual scan://error events since the last rollup
+#[derive(Clone, Debug, Serialize)]
+pub struct ErrorRollupPayload {
+    pub scan_id: String,
+    /// Groups that grew since the last rollup, with their totals so far
+    pub groups: Vec<ErrorGroup>,
+    /// Errors not sent individually during the scan so far
+    pub suppressed: u64,
 }
 
 #[derive(Clone, Debug, Serialize)]
 pub struct CanceledPayload {
     pub scan_id: String,
+    /// Whether what the scan found was kept, under the same id and flagged incomplete
+    pub partial_result: bool,
+}
+
+/// The scanned folder or drive went away mid-scan; the scan is abandoned
+#[derive(Clone, Debug, Serialize)]
+pub struct RootLostPayload {
+    pub scan_id: String,
+    pub root_path: String,
+    pub message: String,
+}
+
+/// Running totals of what the scan has left out so far, sent as they change
+#[derive(Clone, Debug, Serialize)]
+pub struct SkippedPayload {
+    pub scan_id: String,
+    pub groups: Vec<SkipGroup>,
+}
+
+/// Sent every few seconds while a scan runs, so the frontend can tell a slow scan from
+/// a dead one
+#[derive(Clone, Debug, Serialize)]
+pub struct HeartbeatPayload {
+    pub scan_id: String,
+    pub phase: String,
+    pub visited_entries: u64,
+    pub elapsed_ms: u64,
+    /// Time since the scan last visited an entry or changed phase
+    pub idle_ms: u64,
+}
+
+/// A scan that has made no progress for too long. The scan keeps running and may
+/// recover; heartbeats show if it does.
+#[derive(Clone, Debug, Serialize)]
+pub struct StalledPayload {
+    pub scan_id: String,
+    pub phase: String,
+    pub visited_entries: u64,
+    pub idle_ms: u64,
+    pub message: String,
+}
+
+/// Which windows receive a scan's events
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum EventRoute {
+    /// Every window, for scans no window owns such as scheduled scans
+    #[default]
+    Broadcast,
+    /// Only the window with this label, usually the one that started the scan
+    Window(String),
+}
+
+/// App handle plus the route a scan's events take, so windows scanning different drives
+/// don't receive each other's updates
+#[derive(Clone)]
+pub struct ScanEmitter {
+    handle: AppHandle,
+    route: EventRoute,
+    pulse: Arc<ScanPulse>,
 }
 
-pub fn emit_started(handle: &AppHandle, payload: StartedPayload) {
-    let _ = handle.emit(EVENT_STARTED, payload);
+impl ScanEmitter {
+    pub fn new(handle: AppHandle, route: EventRoute) -> Self {
+        Self {
+            handle,
+            route,
+            pulse: Arc::new(ScanPulse::default()),
+        }
+    }
+
+    /// Liveness counters the engine updates and the watchdog reads
+    pub fn pulse(&self) -> &ScanPulse {
+        &self.pulse
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let _ = match &self.route {
+            EventRoute::Broadcast => self.handle.emit(event, payload),
+            EventRoute::Window(label) => self.handle.emit_to(label.as_str(), event, payload),
+        };
+    }
 }
 
-pub fn emit_progress(handle: &AppHandle, payload: ProgressPayload) {
-    let _ = handle.emit(EVENT_PROGRESS, payload);
+pub fn emit_started(emitter: &ScanEmitter, payload: StartedPayload) {
+    emitter.emit(EVENT_STARTED, payload);
 }
 
-pub fn emit_partial_tree(handle: &AppHandle, payload: PartialTreePayload) {
-    let _ = handle.emit(EVENT_PARTIAL_TREE, payload);
+pub fn emit_progress(emitter: &ScanEmitter, payload: ProgressPayload) {
+    emitter.emit(EVENT_PROGRESS, payload);
 }
 
-pub fn emit_finished(handle: &AppHandle, payload: FinishedPayload) {
-    let _ = handle.emit(EVENT_FINISHED, payload);
+pub fn emit_partial_tree(emitter: &ScanEmitter, payload: PartialTreePayload) {
+    emitter.emit(EVENT_PARTIAL_TREE, payload);
 }
 
-pub fn emit_error(handle: &AppHandle, payload: ErrorPayload) {
-    let _ = handle.emit(EVENT_ERROR, payload);
+pub fn emit_finished(emitter: &ScanEmitter, payload: FinishedPayload) {
+    emitter.emit(EVENT_FINISHED, payload);
 }
 
-pub fn emit_canceled(handle: &AppHandle, payload: CanceledPayload) {
-    let _ = handle.emit(EVENT_CANCELED, payload);
+pub fn emit_error(emitter: &ScanEmitter, payload: ErrorPayload) {
+    emitter.emit(EVENT_ERROR, payload);
+}
+
+pub fn emit_error_rollup(emitter: &ScanEmitter, payload: ErrorRollupPayload) {
+    emitter.emit(EVENT_ERROR_ROLLUP, payload);
+}
+
+pub fn emit_canceled(emitter: &ScanEmitter, payload: CanceledPayload) {
+    emitter.emit(EVENT_CANCELED, payload);
+}
+
+pub fn emit_root_lost(emitter: &ScanEmitter, payload: RootLostPayload) {
+    emitter.emit(EVENT_ROOT_LOST, payload);
+}
+
+pub fn emit_skipped(emitter: &ScanEmitter, payload: SkippedPayload) {
+    emitter.emit(EVENT_SKIPPED, payload);
+}
+
+pub fn emit_heartbeat(emitter: &ScanEmitter, payload: HeartbeatPayload) {
+    emitter.emit(EVENT_HEARTBEAT, payload);
+}
+
+pub fn emit_stalled(emitter: &ScanEmitter, payload: StalledPayload) {
+    emitter.emit(EVENT_STALLED, payload);
 }
 
 #[cfg(test)]
@@ -88,6 +232,9 @@ mod tests {
             scan_id: "scan-1".to_string(),
             root_path: "C:/".to_string(),
             started_at: 123,
+            canonical_root: None,
+            overlaps: Vec::new(),
+            capabilities: None,
         };
         let progress = ProgressPayload {
             scan_id: "scan-1".to_string(),
@@ -95,6 +242,8 @@ mod tests {
             visited_bytes_approx: 1024,
             current_path: "C:/".to_string(),
             phase: "walking".to_string(),
+            files_sized: None,
+            files_total: None,
         };
         let partial = PartialTreePayload {
             scan_id: "scan-1".to_string(),
@@ -112,17 +261,19 @@ mod tests {
                     bytes: 1024,
                     count: 1,
                 }],
+                size_distribution: Vec::new(),
             },
             root_node_id: 1,
             finished_at: 789,
         };
-        let error = ErrorPayload {
-            scan_id: "scan-1".to_string(),
-            message: "oops".to_string(),
-            path: None,
-        };
+        let error = ErrorPayload::new(
+            "scan-1".to_string(),
+            Message::new(crate::scan::messages::SCAN_FAILED, [("error", "oops".to_string())]),
+            None,
+        );
         let canceled = CanceledPayload {
             scan_id: "scan-1".to_string(),
+            partial_result: true,
         };
 
         let _ = serde_json::to_string(&started).expect("started serialize");
@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, ExecutableFile, SignatureInfo};
+
+/// Executables and installer packages worth listing
+const EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "msi", "msix", "appx", "dmg", "pkg", "appimage", "deb", "rpm"];
+
+/// Folders where installed programs belong; executables inside them are expected
+const INSTALL_FOLDERS: &[&str] = &[
+    "program files",
+    "program files (x86)",
+    "windows",
+    "windowsapps",
+    "applications",
+    "usr",
+    "opt",
+    "snap",
+    "flatpak",
+];
+
+/// Folder names that mark temporary locations, where binaries deserve a closer look
+const TEMP_FOLDERS: &[&str] = &["temp", "tmp", "inetcache", "temporary internet files"];
+
+/// More files than this skip the signature check, which spawns a process per batch or
+/// per file
+const SIGNATURE_CHECK_LIMIT: usize = 500;
+
+/// Lists executables and installers outside install locations, with their signer, so
+/// old installers can be cleared out and unsigned binaries in temp folders spotted
+#[derive(Default)]
+pub struct ExecutablesAnalyzer {
+    found: Vec<ExecutableFile>,
+}
+
+impl Analyzer for ExecutablesAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let node = file.node;
+        let Some(ext) = node.file_ext.as_deref() else {
+            return;
+        };
+        if !EXECUTABLE_EXTENSIONS.contains(&ext) {
+            return;
+        }
+        let path = Path::new(&node.path);
+        if has_folder(path, INSTALL_FOLDERS) {
+            return;
+        }
+        self.found.push(ExecutableFile {
+            id: node.id,
+            path: node.path.clone(),
+            size_bytes: node.size_bytes,
+            ext: ext.to_string(),
+            modified: node.modified,
+            in_temp: has_folder(path, TEMP_FOLDERS),
+            signature: None,
+        });
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut files = self.found;
+        if files.len() <= SIGNATURE_CHECK_LIMIT {
+            let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+            let signatures = query_signatures(&paths);
+            for (file, signature) in files.iter_mut().zip(signatures) {
+                file.signature = signature;
+            }
+        }
+        // Binaries in temp folders first, then the biggest
+        files.sort_by(|a, b| b.in_temp.cmp(&a.in_temp).then_with(|| b.size_bytes.cmp(&a.size_bytes)));
+        AnalyzerReport::Executables { files }
+    }
+}
+
+/// Whether any parent folder of `path` has one of `names` (case-insensitive)
+fn has_folder(path: &Path, names: &[&str]) -> bool {
+    path.parent().is_some_and(|parent| {
+        parent.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy().to_lowercase();
+            names.contains(&name.as_str())
+        })
+    })
+}
+
+/// Authenticode status and signer of each file, in order, in a single PowerShell run
+#[cfg(target_os = "windows")]
+fn query_signatures(paths: &[&str]) -> Vec<Option<SignatureInfo>> {
+    use std::process::Command;
+
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    let list = std::env::temp_dir().join(format!("disksight-signatures-{}.txt", std::process::id()));
+    if std::fs::write(&list, paths.join("\n")).is_err() {
+        return vec![None; paths.len()];
+    }
+    let script = format!(
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8; \
+         Get-Content -LiteralPath '{}' -Encoding UTF8 | ForEach-Object {{ \
+         $s = Get-AuthenticodeSignature -LiteralPath $_; \
+         \"$([string]$s.Status)`t$($s.SignerCertificate.Subject)\" }}",
+        list.display().to_string().replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .output();
+    let _ = std::fs::remove_file(&list);
+    let Ok(output) = output else {
+        return vec![None; paths.len()];
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    paths
+        .iter()
+        .map(|_| lines.next().and_then(parse_signature_line))
+        .collect()
+}
+
+/// Code signing authority from `codesign`, which reports on stderr
+#[cfg(target_os = "macos")]
+fn query_signatures(paths: &[&str]) -> Vec<Option<SignatureInfo>> {
+    use std::process::Command;
+
+    paths
+        .iter()
+        .map(|path| {
+            let output = Command::new("codesign").args(["-dv", "--verbose=2", path]).output().ok()?;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let publisher = stderr
+                .lines()
+                .find_map(|line| line.strip_prefix("Authority="))
+                .map(str::to_string);
+            Some(SignatureInfo {
+                signed: output.status.success(),
+                status: if output.status.success() { "valid" } else { "not_signed" }.to_string(),
+                publisher,
+            })
+        })
+        .collect()
+}
+
+/// Linux packages and AppImages carry no platform signature to check
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn query_signatures(paths: &[&str]) -> Vec<Option<SignatureInfo>> {
+    vec![None; paths.len()]
+}
+
+/// Parse "Status<TAB>Subject" as printed by the Authenticode script
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_signature_line(line: &str) -> Option<SignatureInfo> {
+    let (status, subject) = line.split_once('\t')?;
+    let status = status.trim();
+    if status.is_empty() {
+        return None;
+    }
+    Some(SignatureInfo {
+        signed: status == "Valid",
+        status: snake_case(status),
+        publisher: common_name(subject),
+    })
+}
+
+/// "HashMismatch" -> "hash_mismatch"
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn snake_case(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 4);
+    for (i, c) in value.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// The CN of a certificate subject such as `CN="Contoso, Ltd.", O=Contoso, C=US`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn common_name(subject: &str) -> Option<String> {
+    let start = subject.find("CN=")? + 3;
+    let rest = &subject[start..];
+    let name = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split(',').next()?,
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_install_folders_and_flags_temp() {
+        assert!(has_folder(Path::new("C:/Program Files/App/app.exe"), INSTALL_FOLDERS));
+        assert!(!has_folder(Path::new("C:/Users/me/Downloads/setup.exe"), INSTALL_FOLDERS));
+        assert!(has_folder(Path::new("C:/Users/me/AppData/Local/Temp/x.exe"), TEMP_FOLDERS));
+
+        let info = parse_signature_line("Valid\tCN=\"Contoso, Ltd.\", O=Contoso, C=US").expect("signature");
+        assert!(info.signed);
+        assert_eq!(info.publisher.as_deref(), Some("Contoso, Ltd."));
+        let unsigned = parse_signature_line("NotSigned\t").expect("signature");
+        assert!(!unsigned.signed);
+        assert_eq!(unsigned.status, "not_signed");
+        assert_eq!(unsigned.publisher, None);
+    }
+}
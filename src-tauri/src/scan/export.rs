@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use crate::scan::anonymize::{Anonymization, Anonymizer};
+use crate::scan::model::{NodeKind, TreeNode};
+use crate::scan::query::NodeQuery;
+use crate::scan::tree::ScanTree;
+
+/// Rows buffered before a row group is written, which bounds export memory
+const ROW_GROUP_ROWS: usize = 64 * 1024;
+
+/// One row per node. Times are Unix millis so DuckDB and Polars read them as timestamps.
+const PARQUET_SCHEMA: &str = "
+message disksight_scan {
+    required binary path (STRING);
+    optional binary parent (STRING);
+    required int64 size;
+    optional binary ext (STRING);
+    optional int64 mtime (TIMESTAMP(MILLIS, true));
+    required binary kind (STRING);
+}
+";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Parquet,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportResult {
+    pub path: String,
+    pub format: ExportFormat,
+    pub rows: u64,
+    pub bytes_written: u64,
+    /// How names were hidden, for an export meant for sharing
+    pub anonymized: Option<Anonymization>,
+}
+
+/// Write the nodes of a finished scan to `dest` in the given format: all of them, or
+/// only those matching `query`, with names hidden when `anonymize` is set
+pub fn export_tree(
+    tree: &ScanTree,
+    format: ExportFormat,
+    dest: &Path,
+    query: Option<&NodeQuery>,
+    anonymize: Option<Anonymization>,
+) -> Result<ExportResult, String> {
+    let nodes: Box<dyn Iterator<Item = &TreeNode>> = match query {
+        Some(query) => Box::new(query.matching(tree).into_iter().map(|(node, _)| node)),
+        None => Box::new(tree.nodes()),
+    };
+    let rows = match format {
+        ExportFormat::Parquet => write_parquet(tree, nodes, dest, anonymize.map(Anonymizer::new))?,
+    };
+    Ok(ExportResult {
+        path: dest.to_string_lossy().to_string(),
+        format,
+        rows,
+        bytes_written: fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+        anonymized: anonymize,
+    })
+}
+
+fn write_parquet<'a>(
+    tree: &'a ScanTree,
+    nodes: impl Iterator<Item = &'a TreeNode>,
+    dest: &Path,
+    mut anonymizer: Option<Anonymizer>,
+) -> Result<u64, String> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA).map_err(|e| e.to_string())?);
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(ROW_GROUP_ROWS)
+            .build(),
+    );
+    let file = File::create(dest).map_err(|e| e.to_string())?;
+    let mut writer = SerializedFileWriter::new(BufWriter::new(file), schema, props).map_err(|e| e.to_string())?;
+
+    let mut batch = RowBatch::default();
+    let mut rows = 0u64;
+    for node in nodes {
+        let parent = node.parent.and_then(|id| tree.get(id));
+        match anonymizer.as_mut() {
+            Some(anonymizer) => {
+                let path = anonymizer.path(tree, node);
+                let parent = parent.map(|parent| anonymizer.path(tree, parent));
+                batch.push(node, &path, parent.as_deref());
+            }
+            None => batch.push(node, &node.path, parent.map(|p| p.path.as_str())),
+        }
+        if batch.len() >= ROW_GROUP_ROWS {
+            rows += batch.write_to(&mut writer).map_err(|e| e.to_string())?;
+        }
+    }
+    if batch.len() > 0 {
+        rows += batch.write_to(&mut writer).map_err(|e| e.to_string())?;
+    }
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Column buffers for one row group. Optional columns keep definition levels (1 present,
+/// 0 null) alongside only the present values, as Parquet stores them.
+#[derive(Default)]
+struct RowBatch {
+    path: Vec<ByteArray>,
+    parent: Vec<ByteArray>,
+    parent_def: Vec<i16>,
+    size: Vec<i64>,
+    ext: Vec<ByteArray>,
+    ext_def: Vec<i16>,
+    mtime: Vec<i64>,
+    mtime_def: Vec<i16>,
+    kind: Vec<ByteArray>,
+}
+
+impl RowBatch {
+    fn push(&mut self, node: &TreeNode, path: &str, parent: Option<&str>) {
+        self.path.push(ByteArray::from(path));
+        push_optional(&mut self.parent, &mut self.parent_def, parent.map(ByteArray::from));
+        self.size.push(node.size_bytes as i64);
+        push_optional(&mut self.ext, &mut self.ext_def, node.file_ext.as_deref().map(ByteArray::from));
+        push_optional(
+            &mut self.mtime,
+            &mut self.mtime_def,
+            node.modified.map(|secs| secs as i64 * 1000),
+        );
+        let kind = match node.kind {
+            NodeKind::File => "file",
+            NodeKind::Dir => "dir",
+        };
+        self.kind.push(ByteArray::from(kind));
+    }
+
+    fn len(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Write the buffered rows as one row group and start a new batch
+    fn write_to<W: Write + Send>(&mut self, writer: &mut SerializedFileWriter<W>) -> parquet::errors::Result<u64> {
+        let rows = self.len() as u64;
+        let mut group = writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = group.next_column()? {
+            match index {
+                0 => column.typed::<ByteArrayType>().write_batch(&self.path, None, None)?,
+                1 => column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&self.parent, Some(&self.parent_def), None)?,
+                2 => column.typed::<Int64Type>().write_batch(&self.size, None, None)?,
+                3 => column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&self.ext, Some(&self.ext_def), None)?,
+                4 => column
+                    .typed::<Int64Type>()
+                    .write_batch(&self.mtime, Some(&self.mtime_def), None)?,
+                _ => column.typed::<ByteArrayType>().write_batch(&self.kind, None, None)?,
+            };
+            column.close()?;
+            index += 1;
+        }
+        group.close()?;
+        *self = Self::default();
+        Ok(rows)
+    }
+}
+
+fn push_optional<T>(values: &mut Vec<T>, levels: &mut Vec<i16>, value: Option<T>) {
+    match value {
+        Some(value) => {
+            values.push(value);
+            levels.push(1);
+        }
+        None => levels.push(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_columns_store_only_present_values() {
+        let node = |path: &str, ext: Option<&str>, modified: Option<u64>| TreeNode {
+            id: 1,
+            parent: None,
+            name: String::new(),
+            path: path.to_string(),
+            kind: NodeKind::File,
+            size_bytes: 10,
+            file_ext: ext.map(str::to_string),
+            modified,
+            informational: false,
+            children: Vec::new(),
+        };
+        let mut batch = RowBatch::default();
+        batch.push(&node("/data", None, None), "/data", None);
+        batch.push(&node("/data/a.txt", Some("txt"), Some(5)), "/data/a.txt", Some("/data"));
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.parent_def, vec![0, 1]);
+        assert_eq!(batch.parent.len(), 1);
+        assert_eq!(batch.ext_def, vec![0, 1]);
+        assert_eq!(batch.mtime, vec![5000]);
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::scan::analyzer::{Analyzer, CategoryStatsAnalyzer, FileVisit, NO_EXTENSION_LABEL};
+use crate::scan::model::{AnalyzerReport, CategoryStat, ExtensionStat, NodeId, NodeKind, TreeNode};
+use crate::scan::tree::ScanTree;
+
+/// Bytes and file counts per extension, kept current as files are added and removed.
+/// The engine fills it while walking; deletes subtract what they removed, so a stored
+/// result never needs recomputing from its tree.
+#[derive(Clone, Debug, Default)]
+pub struct ExtensionStatsTable {
+    stats: HashMap<String, ExtensionStat>,
+}
+
+impl ExtensionStatsTable {
+    pub fn from_stats(stats: &[ExtensionStat]) -> Self {
+        Self {
+            stats: stats.iter().map(|stat| (stat.ext.clone(), stat.clone())).collect(),
+        }
+    }
+
+    pub fn add(&mut self, ext: Option<&str>, bytes: u64) {
+        let ext = ext.unwrap_or(NO_EXTENSION_LABEL);
+        let entry = self.stats.entry(ext.to_string()).or_insert_with(|| ExtensionStat {
+            ext: ext.to_string(),
+            bytes: 0,
+            count: 0,
+        });
+        entry.bytes = entry.bytes.saturating_add(bytes);
+        entry.count = entry.count.saturating_add(1);
+    }
+
+    /// Drop an extension once its last file is gone
+    pub fn remove(&mut self, ext: Option<&str>, bytes: u64) {
+        let ext = ext.unwrap_or(NO_EXTENSION_LABEL);
+        let Some(entry) = self.stats.get_mut(ext) else {
+            return;
+        };
+        entry.bytes = entry.bytes.saturating_sub(bytes);
+        entry.count = entry.count.saturating_sub(1);
+        if entry.count == 0 {
+            self.stats.remove(ext);
+        }
+    }
+
+    /// Subtract the files among `nodes`, such as a removed subtree
+    pub fn remove_nodes<'a>(&mut self, nodes: impl IntoIterator<Item = &'a TreeNode>) {
+        for node in nodes.into_iter().filter(|node| node.kind == NodeKind::File) {
+            self.remove(node.file_ext.as_deref(), node.size_bytes);
+        }
+    }
+
+    /// Largest first
+    pub fn into_stats(self) -> Vec<ExtensionStat> {
+        let mut stats: Vec<ExtensionStat> = self.stats.into_values().collect();
+        stats.sort_by_key(|stat| Reverse(stat.bytes));
+        stats
+    }
+}
+
+/// Extension and category breakdown of the files in a selection of nodes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelectionStats {
+    pub total_bytes: u64,
+    pub total_files: u64,
+    /// Largest first
+    pub extensions: Vec<ExtensionStat>,
+    /// Largest first
+    pub categories: Vec<CategoryStat>,
+    /// Selected ids or paths that aren't in the scan
+    pub missing: Vec<String>,
+}
+
+/// Break down the files below the nodes selected by id or by path. A node selected
+/// along with one of its ancestors is counted once.
+pub fn selection_stats(tree: &ScanTree, ids: &[NodeId], paths: &[String]) -> SelectionStats {
+    let mut missing = Vec::new();
+    let mut ids = ids.to_vec();
+    for path in paths {
+        match tree.find_by_path(path) {
+            Some(node) => ids.push(node.id),
+            None => missing.push(path.clone()),
+        }
+    }
+    let selected: HashSet<NodeId> = ids.iter().copied().collect();
+    let mut table = ExtensionStatsTable::default();
+    let mut categories = Box::new(CategoryStatsAnalyzer::default());
+    let mut total_files = 0u64;
+    let mut total_bytes = 0u64;
+    let mut counted = HashSet::new();
+    for id in ids {
+        if tree.get(id).is_none() {
+            missing.push(id.to_string());
+            continue;
+        }
+        if has_selected_ancestor(tree, id, &selected) || !counted.insert(id) {
+            continue;
+        }
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let Some(node) = tree.get(id) else {
+                continue;
+            };
+            match node.kind {
+                NodeKind::Dir => stack.extend(node.children.iter().copied()),
+                NodeKind::File => {
+                    table.add(node.file_ext.as_deref(), node.size_bytes);
+                    categories.on_file(&FileVisit {
+                        node,
+                        modified: None,
+                        metadata: None,
+                    });
+                    total_files += 1;
+                    total_bytes = total_bytes.saturating_add(node.size_bytes);
+                }
+            }
+        }
+    }
+    let categories = match categories.finalize() {
+        AnalyzerReport::CategoryStats { categories } => categories,
+        _ => Vec::new(),
+    };
+    SelectionStats {
+        total_bytes,
+        total_files,
+        extensions: table.into_stats(),
+        categories,
+        missing,
+    }
+}
+
+fn has_selected_ancestor(tree: &ScanTree, id: NodeId, selected: &HashSet<NodeId>) -> bool {
+    let mut current = tree.get(id).and_then(|node| node.parent);
+    while let Some(parent) = current {
+        if selected.contains(&parent) {
+            return true;
+        }
+        current = tree.get(parent).and_then(|node| node.parent);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(ext: Option<&str>, size: u64) -> TreeNode {
+        TreeNode {
+            id: 1,
+            parent: None,
+            name: "f".to_string(),
+            path: "/f".to_string(),
+            kind: NodeKind::File,
+            size_bytes: size,
+            file_ext: ext.map(str::to_string),
+            modified: None,
+            informational: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn removing_files_applies_deltas() {
+        let mut table = ExtensionStatsTable::default();
+        table.add(Some("mp4"), 700);
+        table.add(Some("mp4"), 300);
+        table.add(Some("txt"), 10);
+        table.add(None, 5);
+
+        let mut table = ExtensionStatsTable::from_stats(&table.into_stats());
+        table.remove_nodes(&[file(Some("mp4"), 300), file(Some("txt"), 10)]);
+        let stats: Vec<(String, u64, u64)> = table
+            .into_stats()
+            .into_iter()
+            .map(|stat| (stat.ext, stat.bytes, stat.count))
+            .collect();
+        assert_eq!(
+            stats,
+            vec![("mp4".to_string(), 700, 1), (NO_EXTENSION_LABEL.to_string(), 5, 1)]
+        );
+    }
+
+    #[test]
+    fn selection_counts_overlapping_nodes_once() {
+        let node = |id: NodeId, parent: Option<NodeId>, kind: NodeKind, ext: Option<&str>, size: u64, children| TreeNode {
+            id,
+            parent,
+            kind,
+            size_bytes: size,
+            file_ext: ext.map(str::to_string),
+            path: format!("/d/{}", id),
+            children,
+            ..file(None, 0)
+        };
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, NodeKind::Dir, None, 1010, vec![2, 4]),
+                node(2, Some(1), NodeKind::Dir, None, 800, vec![3]),
+                node(3, Some(2), NodeKind::File, Some("mkv"), 800, vec![]),
+                node(4, Some(1), NodeKind::File, Some("txt"), 200, vec![]),
+            ],
+            false,
+        );
+        let paths = ["/d/4".to_string(), "/d/2".to_string(), "/x".to_string()];
+        let stats = selection_stats(&tree, &[2, 3, 9], &paths);
+        assert_eq!((stats.total_files, stats.total_bytes), (2, 1000));
+        assert_eq!(stats.categories[0].category, "videos");
+        assert_eq!(stats.categories[0].bytes, 800);
+        assert_eq!(stats.extensions.len(), 2);
+        assert_eq!(stats.missing, vec!["/x".to_string(), "9".to_string()]);
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::scan::delete::{get_safety_level, SafetyLevel};
+use crate::scan::rules::Classifier;
+
+/// Entries examined per requested path when not checking everything
+pub const SAMPLE_ENTRIES: usize = 2_000;
+/// Blocked entries listed in a report; the count covers the rest
+const MAX_LISTED: usize = 500;
+
+/// Why an entry would fail to be deleted or moved
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    Missing,
+    /// Protected by the safety rules, so bulk operations skip it
+    Protected,
+    /// Has the read-only attribute, which makes Windows refuse to delete it
+    ReadOnly,
+    /// No permission to remove it or the entries inside it
+    AccessDenied,
+    /// Held open by another process without delete sharing
+    Locked,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockedEntry {
+    pub path: String,
+    pub reason: BlockReason,
+    pub message: String,
+}
+
+/// What a bulk delete or move of some paths would run into
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FeasibilityReport {
+    pub checked_entries: u64,
+    /// False when only a sample of each folder's contents was checked
+    pub complete: bool,
+    /// Up to 500 blocked entries, in the order they were found
+    pub blocked: Vec<BlockedEntry>,
+    pub blocked_count: u64,
+    /// Requested paths with at least one blocked entry at or below them
+    pub failing_paths: Vec<String>,
+}
+
+/// Check that every path, and everything below it, could be removed. Unless `full`,
+/// only the first `SAMPLE_ENTRIES` entries below each path are looked at.
+pub fn check_feasibility(paths: &[String], full: bool, classifier: &Classifier) -> FeasibilityReport {
+    check_with_budget(paths, (!full).then_some(SAMPLE_ENTRIES), classifier)
+}
+
+fn check_with_budget(paths: &[String], budget: Option<usize>, classifier: &Classifier) -> FeasibilityReport {
+    let mut report = FeasibilityReport {
+        complete: true,
+        ..Default::default()
+    };
+    for path_str in paths {
+        let path = Path::new(path_str);
+        let before = report.blocked_count;
+        if fs::symlink_metadata(path).is_err() {
+            block(&mut report, path, BlockReason::Missing, "Path does not exist".to_string());
+        } else if get_safety_level(path, classifier) == SafetyLevel::Protected {
+            block(&mut report, path, BlockReason::Protected, "Protected system file".to_string());
+        } else {
+            if let Some(parent) = path.parent().filter(|parent| !dir_writable(parent)) {
+                let message = format!("No permission to remove entries from {}", parent.display());
+                block(&mut report, path, BlockReason::AccessDenied, message);
+            }
+            let mut remaining = budget;
+            check_tree(path, &mut remaining, &mut report);
+        }
+        if report.blocked_count > before {
+            report.failing_paths.push(path_str.clone());
+        }
+    }
+    report
+}
+
+/// Check `path` and, depth first, its contents until the budget runs out
+fn check_tree(path: &Path, remaining: &mut Option<usize>, report: &mut FeasibilityReport) {
+    if let Some(left) = remaining {
+        if *left == 0 {
+            report.complete = false;
+            return;
+        }
+        *left -= 1;
+    }
+    report.checked_entries += 1;
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return;
+    };
+    if let Some((reason, message)) = entry_blocker(path, &metadata) {
+        block(report, path, reason, message);
+    }
+    if !metadata.is_dir() {
+        return;
+    }
+    if !dir_writable(path) {
+        block(report, path, BlockReason::AccessDenied, "No permission to remove the entries inside".to_string());
+    }
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                check_tree(&entry.path(), remaining, report);
+            }
+        }
+        Err(err) => block(report, path, BlockReason::AccessDenied, err.to_string()),
+    }
+}
+
+fn block(report: &mut FeasibilityReport, path: &Path, reason: BlockReason, message: String) {
+    report.blocked_count += 1;
+    if report.blocked.len() < MAX_LISTED {
+        report.blocked.push(BlockedEntry {
+            path: path.to_string_lossy().to_string(),
+            reason,
+            message,
+        });
+    }
+}
+
+/// Open the entry for deletion the way a delete would, without deleting it
+#[cfg(target_os = "windows")]
+fn entry_blocker(path: &Path, metadata: &fs::Metadata) -> Option<(BlockReason, String)> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        DELETE, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, FILE_SHARE_DELETE, FILE_SHARE_READ,
+        FILE_SHARE_WRITE,
+    };
+
+    use crate::scan::locks::is_sharing_violation;
+
+    if metadata.permissions().readonly() {
+        return Some((BlockReason::ReadOnly, "Read-only attribute is set".to_string()));
+    }
+    let opened = fs::OpenOptions::new()
+        .access_mode(DELETE)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path);
+    match opened {
+        Ok(_) => None,
+        Err(err) if is_sharing_violation(&err) => Some((BlockReason::Locked, err.to_string())),
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+            Some((BlockReason::AccessDenied, err.to_string()))
+        }
+        Err(_) => None,
+    }
+}
+
+/// Unix checks permissions on the containing folder, not on the entry itself
+#[cfg(not(target_os = "windows"))]
+fn entry_blocker(_path: &Path, _metadata: &fs::Metadata) -> Option<(BlockReason, String)> {
+    None
+}
+
+/// Whether entries can be removed from `dir`
+#[cfg(target_os = "linux")]
+fn dir_writable(dir: &Path) -> bool {
+    use rustix::fs::{access, Access};
+    access(dir, Access::WRITE_OK | Access::EXEC_OK).is_ok()
+}
+
+#[cfg(target_os = "macos")]
+fn dir_writable(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(dir) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::access(dir.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+/// Windows decides per entry, which `entry_blocker` probes
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn dir_writable(_dir: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_missing_paths_and_sampling() {
+        let temp = tempdir().expect("tempdir");
+        let folder = temp.path().join("photos");
+        fs::create_dir(&folder).expect("mkdir");
+        for index in 0..5 {
+            fs::write(folder.join(format!("{}.jpg", index)), b"x").expect("write");
+        }
+        let missing = temp.path().join("gone.txt").to_string_lossy().to_string();
+        let paths = vec![folder.to_string_lossy().to_string(), missing.clone()];
+        let classifier = Classifier::builtin(false);
+
+        let full = check_with_budget(&paths, None, &classifier);
+        assert!(full.complete);
+        assert_eq!(full.checked_entries, 6);
+        assert_eq!(full.failing_paths, vec![missing]);
+        assert_eq!(full.blocked[0].reason, BlockReason::Missing);
+
+        let sampled = check_with_budget(&paths, Some(3), &classifier);
+        assert!(!sampled.complete);
+        assert_eq!(sampled.checked_entries, 3);
+    }
+}
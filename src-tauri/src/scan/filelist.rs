@@ -0,0 +1,395 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// How the lines of a file list are laid out
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileListFormat {
+    /// Sizes and paths, one file per line (see `parse_line`)
+    #[default]
+    Plain,
+    /// An Amazon S3 Inventory CSV report
+    S3Inventory,
+    /// The output of `rclone lsjson -R`
+    RcloneJson,
+}
+
+/// One file read from a file list instead of the disk
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListedFile {
+    pub path: String,
+    pub size: u64,
+    /// Seconds since the Unix epoch
+    pub modified: Option<u64>,
+}
+
+/// Parse one line of a file list. Lines are `size<TAB>path` or `path<TAB>size`, with an
+/// optional third tab-separated field holding the modification time in seconds since
+/// the epoch; `size path` separated by spaces (as `du` and `find -printf '%s %p'`
+/// print) works too. Blank lines and `#` comments give None.
+pub fn parse_line(line: &str) -> Result<Option<ListedFile>, String> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let fields: Vec<&str> = line.split('\t').collect();
+    let (size, path, rest) = match fields.as_slice() {
+        [first, second, rest @ ..] => match (parse_number(first), parse_number(second)) {
+            (Some(size), _) => (size, *second, rest),
+            (None, Some(size)) => (size, *first, rest),
+            (None, None) => return Err(format!("no size in \"{}\"", line)),
+        },
+        [only] => {
+            let (size, path) = only
+                .trim_start()
+                .split_once(char::is_whitespace)
+                .and_then(|(size, path)| Some((parse_number(size)?, path.trim_start())))
+                .ok_or_else(|| format!("no size in \"{}\"", line))?;
+            (size, path, &[][..])
+        }
+        [] => return Ok(None),
+    };
+    if path.is_empty() {
+        return Err(format!("no path in \"{}\"", line));
+    }
+    let modified = match rest.first() {
+        Some(field) => Some(parse_number(field).ok_or_else(|| format!("invalid modification time \"{}\"", field))?),
+        None => None,
+    };
+    Ok(Some(ListedFile {
+        path: path.to_string(),
+        size,
+        modified,
+    }))
+}
+
+/// Parse one line of a list in `format`
+pub fn parse_entry(line: &str, format: FileListFormat) -> Result<Option<ListedFile>, String> {
+    match format {
+        FileListFormat::Plain => parse_line(line),
+        FileListFormat::S3Inventory => parse_s3_inventory_line(line),
+        FileListFormat::RcloneJson => parse_rclone_line(line),
+    }
+}
+
+/// One row of an S3 Inventory CSV: bucket, URL-encoded key, then the optional version
+/// columns, size, and last-modified date, followed by whatever else the report was set
+/// up with. Only current versions count; delete markers and folder placeholders are
+/// skipped.
+fn parse_s3_inventory_line(line: &str) -> Result<Option<ListedFile>, String> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let fields = csv_fields(line);
+    let [_, key, rest @ ..] = fields.as_slice() else {
+        return Err(format!("not an inventory row: \"{}\"", line));
+    };
+    let key = percent_decode(key);
+    let booleans: Vec<bool> = rest
+        .iter()
+        .take_while(|field| parse_number(field).is_none())
+        .filter_map(|field| field.parse::<bool>().ok())
+        .collect();
+    // With versioning the report carries IsLatest and IsDeleteMarker before the size
+    if let [is_latest, is_delete_marker] = booleans.as_slice() {
+        if !is_latest || *is_delete_marker {
+            return Ok(None);
+        }
+    }
+    if key.ends_with('/') {
+        return Ok(None);
+    }
+    let size_index = rest
+        .iter()
+        .position(|field| parse_number(field).is_some())
+        .ok_or_else(|| format!("no size for {}", key))?;
+    Ok(Some(ListedFile {
+        path: key,
+        size: parse_number(&rest[size_index]).unwrap_or(0),
+        modified: rest[size_index + 1..].iter().find_map(|field| parse_timestamp(field)),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RcloneEntry {
+    path: String,
+    #[serde(default)]
+    size: i64,
+    #[serde(default)]
+    mod_time: Option<String>,
+    #[serde(default)]
+    is_dir: bool,
+}
+
+/// One object of `rclone lsjson` output, which prints the array one object per line
+fn parse_rclone_line(line: &str) -> Result<Option<ListedFile>, String> {
+    let line = line.trim().trim_end_matches(',');
+    if line.is_empty() || line == "[" || line == "]" {
+        return Ok(None);
+    }
+    let entry: RcloneEntry = serde_json::from_str(line).map_err(|e| format!("not an rclone entry: {}", e))?;
+    if entry.is_dir {
+        return Ok(None);
+    }
+    Ok(Some(ListedFile {
+        path: entry.path,
+        // rclone reports -1 when the remote doesn't know the size
+        size: entry.size.max(0) as u64,
+        modified: entry.mod_time.as_deref().and_then(parse_timestamp),
+    }))
+}
+
+/// The root an inventory's entries go under when none is given: `s3://<bucket>` from
+/// the first row of an S3 inventory. Other formats don't name their source.
+pub fn inventory_root(list: &Path, format: FileListFormat) -> Result<String, String> {
+    if format != FileListFormat::S3Inventory {
+        return Err("Name the remote or folder the list was taken from".to_string());
+    }
+    let file = File::open(list).map_err(|e| format!("Could not open {}: {}", list.display(), e))?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| csv_fields(&line).into_iter().next())
+        .filter(|bucket| !bucket.is_empty())
+        .map(|bucket| format!("s3://{}", bucket))
+        .ok_or_else(|| format!("{} has no inventory rows", list.display()))
+}
+
+/// A given root for remote entries, with a scheme added when it has none, so the
+/// entries can never be mistaken for local paths
+pub fn remote_root(root: &str, format: FileListFormat) -> String {
+    match format {
+        _ if has_scheme(root) => root.to_string(),
+        FileListFormat::Plain => root.to_string(),
+        FileListFormat::S3Inventory => format!("s3://{}", root.trim_start_matches('/')),
+        FileListFormat::RcloneJson => format!("rclone://{}", root),
+    }
+}
+
+/// Whether a path names a remote object (`s3://bucket/key`) rather than a local file;
+/// these only exist in trees built from inventories and can't be deleted from here
+pub fn is_remote(path: &Path) -> bool {
+    path.to_str().is_some_and(has_scheme)
+}
+
+fn has_scheme(path: &str) -> bool {
+    path.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Split a CSV row, honouring quoted fields with doubled quotes inside
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches(['\r', '\n']).chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Decode `%XX` escapes (and `+` for spaces) as S3 Inventory writes keys
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, bytes[index]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (None, b'+') => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Seconds since the epoch from an RFC 3339 timestamp such as
+/// `2024-03-01T12:30:00.000Z` or `2024-03-01T12:30:00+02:00`
+pub fn parse_timestamp(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, time) = text.split_once(['T', ' '])?;
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || time.len() < 8 {
+        return None;
+    }
+    let clock: Vec<i64> = time[..8].split(':').filter_map(|part| part.parse().ok()).collect();
+    let [hour, minute, second] = clock.as_slice() else {
+        return None;
+    };
+    // Skip fractional seconds, then read the zone
+    let zone = time[8..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match zone {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = if zone.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = zone.get(1..)?.split_once(':').unwrap_or((zone.get(1..3)?, zone.get(3..)?));
+            sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60)
+        }
+    };
+    // Days from the civil date (proleptic Gregorian), after Howard Hinnant
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    u64::try_from(secs).ok()
+}
+
+fn parse_number(field: &str) -> Option<u64> {
+    let field = field.trim();
+    if field.is_empty() || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    field.parse().ok()
+}
+
+/// The path the root node of a list-built tree gets: the requested root without
+/// trailing separators, except where they are the whole root (`/`, `C:\`)
+pub fn list_root(root: &str) -> String {
+    let base = root.trim_end_matches(['/', '\\']);
+    if base.is_empty() {
+        root.chars().next().map(String::from).unwrap_or_else(|| "/".to_string())
+    } else if base.ends_with(':') && base.len() == 2 {
+        format!("{}{}", base, separator(root))
+    } else {
+        base.to_string()
+    }
+}
+
+/// Where a listed path goes in the tree rooted at `root` (as returned by `list_root`).
+/// Relative paths are taken from the root; absolute ones must lie below it. None for
+/// paths outside the root or climbing out of it with `..`.
+pub fn resolve(root: &str, listed: &str) -> Option<String> {
+    let base = root.trim_end_matches(['/', '\\']);
+    let relative = if is_absolute(listed) {
+        let rest = listed.strip_prefix(base)?;
+        if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+            return None;
+        }
+        rest
+    } else {
+        listed
+    };
+    let parts: Vec<&str> = relative
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    if parts.is_empty() || parts.contains(&"..") {
+        return None;
+    }
+    let separator = separator(root);
+    Some(format!("{}{}{}", base, separator, parts.join(&separator.to_string())))
+}
+
+fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with(['/', '\\'])
+        || path.contains("://")
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Backslash for roots written Windows-style, forward slash otherwise
+fn separator(root: &str) -> char {
+    if root.contains('\\') && !root.contains('/') {
+        '\\'
+    } else {
+        '/'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines_and_places_paths_below_the_root() {
+        let file = |path: &str, size, modified| ListedFile {
+            path: path.to_string(),
+            size,
+            modified,
+        };
+        assert_eq!(parse_line("120\tphotos/a.jpg"), Ok(Some(file("photos/a.jpg", 120, None))));
+        assert_eq!(
+            parse_line("photos/b c.jpg\t99\t1700000000"),
+            Ok(Some(file("photos/b c.jpg", 99, Some(1_700_000_000))))
+        );
+        assert_eq!(parse_line("4096  ./docs/notes 2.txt"), Ok(Some(file("./docs/notes 2.txt", 4096, None))));
+        assert_eq!(parse_line("   "), Ok(None));
+        assert_eq!(parse_line("# inventory"), Ok(None));
+        assert!(parse_line("photos/a.jpg").is_err());
+        assert!(parse_line("a\tb").is_err());
+
+        assert_eq!(list_root("s3://bucket/"), "s3://bucket");
+        assert_eq!(list_root("/"), "/");
+        assert_eq!(list_root(r"D:\"), r"D:\");
+        assert_eq!(resolve("s3://bucket", "./photos//a.jpg").as_deref(), Some("s3://bucket/photos/a.jpg"));
+        assert_eq!(resolve("/backup", "/backup/home/x").as_deref(), Some("/backup/home/x"));
+        assert_eq!(resolve("/", "/etc/hosts").as_deref(), Some("/etc/hosts"));
+        assert_eq!(resolve(r"D:\", r"D:\Media\film.mkv").as_deref(), Some(r"D:\Media\film.mkv"));
+        assert_eq!(resolve("/backup", "/backups/x"), None);
+        assert_eq!(resolve("/backup", "../etc/passwd"), None);
+    }
+
+    #[test]
+    fn reads_s3_inventories_and_rclone_listings() {
+        let row = r#""photos","2023/summer%20trip/a+b.jpg","3mxD","true","false","2048","2024-03-01T12:30:00.000Z","STANDARD""#;
+        assert_eq!(
+            parse_entry(row, FileListFormat::S3Inventory),
+            Ok(Some(ListedFile {
+                path: "2023/summer trip/a b.jpg".to_string(),
+                size: 2048,
+                modified: Some(1_709_296_200),
+            }))
+        );
+        let delete_marker = r#""photos","old.jpg","9xQ","true","true","","2024-03-01T12:30:00.000Z""#;
+        assert_eq!(parse_entry(delete_marker, FileListFormat::S3Inventory), Ok(None));
+        let unversioned = r#""photos","raw/b.cr2","10","2024-03-01T14:30:00+02:00""#;
+        assert_eq!(
+            parse_entry(unversioned, FileListFormat::S3Inventory).map(|file| file.map(|file| file.modified)),
+            Ok(Some(Some(1_709_296_200)))
+        );
+
+        let line = r#"{"Path":"docs/a.pdf","Name":"a.pdf","Size":77,"ModTime":"2024-03-01T12:30:00.5Z","IsDir":false},"#;
+        assert_eq!(
+            parse_entry(line, FileListFormat::RcloneJson).map(|file| file.map(|file| (file.path, file.size))),
+            Ok(Some(("docs/a.pdf".to_string(), 77)))
+        );
+        assert_eq!(parse_entry(r#"{"Path":"docs","Size":-1,"IsDir":true},"#, FileListFormat::RcloneJson), Ok(None));
+        assert_eq!(parse_entry("[", FileListFormat::RcloneJson), Ok(None));
+
+        assert_eq!(remote_root("gdrive:Photos", FileListFormat::RcloneJson), "rclone://gdrive:Photos");
+        assert!(is_remote(Path::new("s3://photos/a.jpg")));
+        assert!(!is_remote(Path::new("/home/me/http://notes")));
+        assert!(!is_remote(Path::new("C:/Users")));
+    }
+}
@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::baseline::is_descendant;
+use crate::scan::model::NodeKind;
+use crate::scan::tree::ScanTree;
+
+const GROWTH_FILE: &str = "growth-history.json";
+/// Directories below this size aren't remembered between runs; one that crosses it
+/// counts as growing from zero
+const GROWTH_MIN_DIR_BYTES: u64 = 1024 * 1024;
+/// Changes smaller than this are left out of a step
+const GROWTH_MIN_DELTA_BYTES: u64 = 1024 * 1024;
+/// Largest changes kept per step
+const MAX_STEP_DIRS: usize = 200;
+/// Steps kept per root, about a year of daily scans
+const MAX_STEPS: usize = 400;
+/// Directories listed per period in a report
+const REPORT_TOP_DIRS: usize = 10;
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// Directory sizes of the last scheduled scan of a root, to diff the next one against
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrowthSnapshot {
+    pub scan_id: String,
+    pub taken_at: u64,
+    pub total_bytes: u64,
+    pub dirs: HashMap<String, u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DirGrowth {
+    pub path: String,
+    /// Negative when the directory shrank
+    pub delta_bytes: i64,
+    /// Size at the end of the step or period
+    pub bytes: u64,
+}
+
+/// What changed between two consecutive scheduled scans of a root
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrowthStep {
+    pub from_scan_id: String,
+    pub to_scan_id: String,
+    pub from_at: u64,
+    pub to_at: u64,
+    pub total_delta_bytes: i64,
+    pub dirs: Vec<DirGrowth>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RootGrowth {
+    pub last: Option<GrowthSnapshot>,
+    pub steps: Vec<GrowthStep>,
+}
+
+/// Growth steps by root path
+pub type GrowthHistory = HashMap<String, RootGrowth>;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GrowthRange {
+    Day,
+    Week,
+}
+
+impl GrowthRange {
+    fn millis(self) -> u64 {
+        match self {
+            GrowthRange::Day => DAY_MILLIS,
+            GrowthRange::Week => 7 * DAY_MILLIS,
+        }
+    }
+
+    /// Start of the period holding `at`; weeks start on Monday, and the Unix epoch was
+    /// a Thursday
+    fn start_of(self, at: u64) -> u64 {
+        let offset = match self {
+            GrowthRange::Day => 0,
+            GrowthRange::Week => 4 * DAY_MILLIS,
+        };
+        at.saturating_sub(offset) / self.millis() * self.millis() + offset
+    }
+}
+
+/// Growth over one day or week, from the steps that ended in it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrowthPeriod {
+    /// Start of the period in Unix millis, at a UTC midnight
+    pub start: u64,
+    pub end: u64,
+    pub scans: u64,
+    pub total_delta_bytes: i64,
+    /// Directories that grew most, without ancestors whose growth they mostly explain
+    pub top_dirs: Vec<DirGrowth>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrowthReport {
+    pub root_path: String,
+    pub range: GrowthRange,
+    /// Oldest first
+    pub periods: Vec<GrowthPeriod>,
+}
+
+fn snapshot(scan_id: &str, tree: &ScanTree) -> Option<GrowthSnapshot> {
+    let root = tree.root()?;
+    Some(GrowthSnapshot {
+        scan_id: scan_id.to_string(),
+        taken_at: now_millis(),
+        total_bytes: root.size_bytes,
+        dirs: tree
+            .nodes()
+            .filter(|node| node.kind == NodeKind::Dir && node.size_bytes >= GROWTH_MIN_DIR_BYTES)
+            .map(|node| (node.path.clone(), node.size_bytes))
+            .collect(),
+    })
+}
+
+fn diff(previous: &GrowthSnapshot, current: &GrowthSnapshot) -> GrowthStep {
+    let paths: HashSet<&String> = current.dirs.keys().chain(previous.dirs.keys()).collect();
+    let mut dirs: Vec<DirGrowth> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let before = previous.dirs.get(path).copied().unwrap_or(0);
+            let after = current.dirs.get(path).copied().unwrap_or(0);
+            let delta_bytes = after as i64 - before as i64;
+            (delta_bytes.unsigned_abs() >= GROWTH_MIN_DELTA_BYTES).then(|| DirGrowth {
+                path: path.clone(),
+                delta_bytes,
+                bytes: after,
+            })
+        })
+        .collect();
+    dirs.sort_by(|a, b| b.delta_bytes.unsigned_abs().cmp(&a.delta_bytes.unsigned_abs()).then(a.path.cmp(&b.path)));
+    dirs.truncate(MAX_STEP_DIRS);
+    GrowthStep {
+        from_scan_id: previous.scan_id.clone(),
+        to_scan_id: current.scan_id.clone(),
+        from_at: previous.taken_at,
+        to_at: current.taken_at,
+        total_delta_bytes: current.total_bytes as i64 - previous.total_bytes as i64,
+        dirs,
+    }
+}
+
+/// Diff a finished scheduled scan with the previous one of its root and remember both
+/// the step and the new sizes. The first scan of a root only records its sizes.
+pub fn record_scan(history: &mut GrowthHistory, scan_id: &str, tree: &ScanTree) -> Option<GrowthStep> {
+    let current = snapshot(scan_id, tree)?;
+    let root_path = tree.root()?.path.clone();
+    let root = history.entry(root_path).or_default();
+    let step = root.last.as_ref().map(|previous| diff(previous, &current));
+    if let Some(step) = &step {
+        root.steps.push(step.clone());
+        let excess = root.steps.len().saturating_sub(MAX_STEPS);
+        root.steps.drain(..excess);
+    }
+    root.last = Some(current);
+    step
+}
+
+/// Sum the steps of a root into days or weeks, from `since` (Unix millis) on
+pub fn growth_report(history: &GrowthHistory, root_path: &str, range: GrowthRange, since: Option<u64>) -> GrowthReport {
+    let mut periods: Vec<(u64, u64, i64, HashMap<String, DirGrowth>)> = Vec::new();
+    let steps = history.get(root_path).map(|root| root.steps.as_slice()).unwrap_or_default();
+    for step in steps.iter().filter(|step| since.is_none_or(|since| step.to_at >= since)) {
+        let start = range.start_of(step.to_at);
+        if periods.last().is_none_or(|(last, ..)| *last != start) {
+            periods.push((start, 0, 0, HashMap::new()));
+        }
+        let Some((_, scans, total, dirs)) = periods.last_mut() else {
+            continue;
+        };
+        *scans += 1;
+        *total += step.total_delta_bytes;
+        for growth in &step.dirs {
+            let entry = dirs.entry(growth.path.clone()).or_insert_with(|| DirGrowth {
+                path: growth.path.clone(),
+                delta_bytes: 0,
+                bytes: 0,
+            });
+            entry.delta_bytes += growth.delta_bytes;
+            entry.bytes = growth.bytes;
+        }
+    }
+    GrowthReport {
+        root_path: root_path.to_string(),
+        range,
+        periods: periods
+            .into_iter()
+            .map(|(start, scans, total_delta_bytes, dirs)| GrowthPeriod {
+                start,
+                end: start + range.millis(),
+                scans,
+                total_delta_bytes,
+                top_dirs: top_growth(dirs.into_values().collect()),
+            })
+            .collect(),
+    }
+}
+
+/// The directories that grew most, skipping any whose growth is mostly one listed
+/// subdirectory's, so a runaway folder shows up once rather than with every ancestor
+fn top_growth(mut dirs: Vec<DirGrowth>) -> Vec<DirGrowth> {
+    dirs.retain(|dir| dir.delta_bytes > 0);
+    let explained: Vec<bool> = dirs
+        .iter()
+        .map(|outer| {
+            dirs.iter()
+                .any(|inner| is_descendant(&inner.path, &outer.path) && inner.delta_bytes * 2 >= outer.delta_bytes)
+        })
+        .collect();
+    let mut explained = explained.into_iter();
+    dirs.retain(|_| !explained.next().unwrap_or(false));
+    dirs.sort_by(|a, b| b.delta_bytes.cmp(&a.delta_bytes).then(a.path.cmp(&b.path)));
+    dirs.truncate(REPORT_TOP_DIRS);
+    dirs
+}
+
+pub fn load_growth_history(dir: &Path) -> GrowthHistory {
+    fs::read_to_string(dir.join(GROWTH_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the history, writing to a temp file first so a crash can't truncate it
+pub fn save_growth_history(dir: &Path, history: &GrowthHistory) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string(history).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", GROWTH_FILE));
+    fs::write(&temp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(GROWTH_FILE)).map_err(|e| e.to_string())
+}
+
+/// Record a finished scheduled scan in the growth history of the data dir
+pub fn record_scheduled_scan(data_dir: Option<&Path>, scan_id: &str, tree: &ScanTree) -> Result<(), String> {
+    let Some(dir) = data_dir else {
+        return Ok(());
+    };
+    let mut history = load_growth_history(dir);
+    record_scan(&mut history, scan_id, tree);
+    save_growth_history(dir, &history)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::{NodeId, TreeNode};
+
+    const MB: u64 = 1024 * 1024;
+
+    fn tree(sizes: &[(&str, u64)]) -> ScanTree {
+        let nodes: Vec<TreeNode> = sizes
+            .iter()
+            .enumerate()
+            .map(|(index, (path, size))| TreeNode {
+                id: index as NodeId + 1,
+                parent: (index > 0).then_some(1),
+                name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                path: path.to_string(),
+                kind: NodeKind::Dir,
+                size_bytes: *size,
+                file_ext: None,
+                modified: None,
+                informational: false,
+                children: Vec::new(),
+            })
+            .collect();
+        ScanTree::from_nodes(1, nodes, false)
+    }
+
+    #[test]
+    fn sums_consecutive_scans_into_periods() {
+        let mut history = GrowthHistory::new();
+        let runs = [
+            tree(&[("/srv", 100 * MB), ("/srv/logs", 10 * MB), ("/srv/cache", 50 * MB)]),
+            tree(&[("/srv", 130 * MB), ("/srv/logs", 40 * MB), ("/srv/cache", 50 * MB)]),
+            tree(&[("/srv", 125 * MB), ("/srv/logs", 45 * MB), ("/srv/cache", 40 * MB)]),
+        ];
+        assert!(record_scan(&mut history, "a", &runs[0]).is_none());
+        let step = record_scan(&mut history, "b", &runs[1]).expect("step");
+        assert_eq!(step.total_delta_bytes, 30 * MB as i64);
+        record_scan(&mut history, "c", &runs[2]).expect("step");
+        // The two steps ended on consecutive days of the same week
+        let steps = &mut history.get_mut("/srv").expect("root").steps;
+        steps[0].to_at = 8 * DAY_MILLIS;
+        steps[1].to_at = 9 * DAY_MILLIS + 1;
+
+        let days = growth_report(&history, "/srv", GrowthRange::Day, None);
+        assert_eq!(days.periods.len(), 2);
+        assert_eq!(days.periods[1].start, 9 * DAY_MILLIS);
+        let weeks = growth_report(&history, "/srv", GrowthRange::Week, None);
+        assert_eq!(weeks.periods.len(), 1);
+        assert_eq!(weeks.periods[0].total_delta_bytes, 25 * MB as i64);
+        // /srv grew by less than /srv/logs, which explains it
+        assert_eq!(
+            weeks.periods[0].top_dirs,
+            vec![DirGrowth {
+                path: "/srv/logs".to_string(),
+                delta_bytes: 35 * MB as i64,
+                bytes: 45 * MB,
+            }]
+        );
+        assert!(growth_report(&history, "/srv", GrowthRange::Day, Some(10 * DAY_MILLIS)).periods.is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const HASH_CACHE_FILE: &str = "hash-cache.json";
+
+/// Hashes of one file, valid while its size and modification time are unchanged
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CachedHash {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch
+    pub modified_ns: u64,
+    /// Hash of the leading bytes duplicate search compares first
+    pub prefix: Option<String>,
+    pub full: Option<String>,
+}
+
+/// Which hash of a file is wanted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashKind {
+    Prefix,
+    Full,
+}
+
+/// File hashes kept between sessions, so repeated duplicate searches only read files
+/// that are new or changed
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HashCache {
+    entries: HashMap<String, CachedHash>,
+    #[serde(skip)]
+    hits: u64,
+    #[serde(skip)]
+    misses: u64,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HashCacheStats {
+    pub entries: u64,
+    /// Entries holding a full hash
+    pub full_hashes: u64,
+    /// Content covered by full hashes, i.e. what a repeat search doesn't read again
+    pub covered_bytes: u64,
+    /// Size of the cache on disk
+    pub file_bytes: u64,
+    /// Lookups answered from the cache since the app started
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Size and modification time of a file, as the cache keys validity on them
+pub fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len(), modified.as_nanos() as u64))
+}
+
+impl HashCache {
+    /// The cached hash of `path` when the file still has the given stamp
+    pub fn get(&mut self, path: &str, stamp: (u64, u64), kind: HashKind) -> Option<String> {
+        let hash = self
+            .entries
+            .get(path)
+            .filter(|entry| (entry.size, entry.modified_ns) == stamp)
+            .and_then(|entry| match kind {
+                HashKind::Prefix => entry.prefix.clone(),
+                HashKind::Full => entry.full.clone(),
+            });
+        if hash.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        hash
+    }
+
+    /// Remember a hash; a changed stamp drops the hashes of the old content
+    pub fn insert(&mut self, path: &str, stamp: (u64, u64), kind: HashKind, hash: String) {
+        let entry = self.entries.entry(path.to_string()).or_insert_with(|| CachedHash {
+            size: stamp.0,
+            modified_ns: stamp.1,
+            prefix: None,
+            full: None,
+        });
+        if (entry.size, entry.modified_ns) != stamp {
+            *entry = CachedHash {
+                size: stamp.0,
+                modified_ns: stamp.1,
+                prefix: None,
+                full: None,
+            };
+        }
+        match kind {
+            HashKind::Prefix => entry.prefix = Some(hash),
+            HashKind::Full => entry.full = Some(hash),
+        }
+        self.dirty = true;
+    }
+
+    /// Drop entries for files that are gone or changed, or everything when `all` is
+    /// set. Returns how many were dropped.
+    pub fn purge(&mut self, all: bool) -> usize {
+        let before = self.entries.len();
+        if all {
+            self.entries.clear();
+        } else {
+            self.entries
+                .retain(|path, entry| file_stamp(Path::new(path)) == Some((entry.size, entry.modified_ns)));
+        }
+        let dropped = before - self.entries.len();
+        self.dirty |= dropped > 0;
+        dropped
+    }
+
+    pub fn stats(&self, file_bytes: u64) -> HashCacheStats {
+        let full: Vec<&CachedHash> = self.entries.values().filter(|entry| entry.full.is_some()).collect();
+        HashCacheStats {
+            entries: self.entries.len() as u64,
+            full_hashes: full.len() as u64,
+            covered_bytes: full.iter().map(|entry| entry.size).sum(),
+            file_bytes,
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+pub fn load_hash_cache(dir: &Path) -> HashCache {
+    fs::read_to_string(dir.join(HASH_CACHE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the cache if it changed, writing to a temp file first so a crash can't
+/// truncate it
+pub fn save_hash_cache(dir: &Path, cache: &mut HashCache) -> Result<(), String> {
+    if !cache.dirty {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", HASH_CACHE_FILE));
+    fs::write(&temp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(HASH_CACHE_FILE)).map_err(|e| e.to_string())?;
+    cache.dirty = false;
+    Ok(())
+}
+
+/// Bytes the cache file takes on disk
+pub fn hash_cache_file_bytes(dir: &Path) -> u64 {
+    fs::metadata(dir.join(HASH_CACHE_FILE)).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_are_reused_only_for_unchanged_files() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let file = dir.path().join("movie.mkv");
+        fs::write(&file, b"frames").expect("write");
+        let path = file.to_string_lossy().to_string();
+        let stamp = file_stamp(&file).expect("stamp");
+
+        let mut cache = HashCache::default();
+        assert_eq!(cache.get(&path, stamp, HashKind::Full), None);
+        cache.insert(&path, stamp, HashKind::Full, "abc".to_string());
+        save_hash_cache(dir.path(), &mut cache).expect("save");
+
+        let mut cache = load_hash_cache(dir.path());
+        assert_eq!(cache.get(&path, stamp, HashKind::Full).as_deref(), Some("abc"));
+        assert_eq!(cache.get(&path, stamp, HashKind::Prefix), None);
+        assert_eq!(cache.get(&path, (stamp.0 + 1, stamp.1), HashKind::Full), None);
+        assert_eq!((cache.stats(0).hits, cache.stats(0).covered_bytes), (1, 6));
+
+        assert_eq!(cache.purge(false), 0);
+        fs::remove_file(&file).expect("remove");
+        assert_eq!(cache.purge(false), 1);
+        assert_eq!(cache.stats(0).entries, 0);
+    }
+}
@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::scan::baseline;
+use crate::scan::engine::is_case_insensitive;
+use crate::scan::model::NodeKind;
+use crate::scan::roots;
+use crate::scan::state::AppState;
+use crate::scan::tree::ScanTree;
+
+/// Directories below this size get no hint; among themselves they keep name order
+const HINT_MIN_DIR_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Directory sizes from an earlier scan or baseline of a root. The walker visits the
+/// historically largest siblings first, so the treemap converges on the big picture
+/// early instead of spending the first minutes in thousands of tiny folders.
+#[derive(Clone, Debug, Default)]
+pub struct SizeHints {
+    sizes: HashMap<String, u64>,
+    case_insensitive: bool,
+}
+
+impl SizeHints {
+    pub fn from_tree(tree: &ScanTree) -> Self {
+        let case_insensitive = tree.case_insensitive();
+        let sizes = tree
+            .nodes()
+            .filter(|node| node.kind == NodeKind::Dir && node.size_bytes >= HINT_MIN_DIR_BYTES)
+            .map(|node| (key(&node.path, case_insensitive), node.size_bytes))
+            .collect();
+        Self { sizes, case_insensitive }
+    }
+
+    pub fn from_sizes<'a>(sizes: impl IntoIterator<Item = (&'a str, u64)>, case_insensitive: bool) -> Self {
+        let sizes = sizes
+            .into_iter()
+            .filter(|(_, bytes)| *bytes >= HINT_MIN_DIR_BYTES)
+            .map(|(path, bytes)| (key(path, case_insensitive), bytes))
+            .collect();
+        Self { sizes, case_insensitive }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn get(&self, path: &Path) -> u64 {
+        if self.sizes.is_empty() {
+            return 0;
+        }
+        self.sizes
+            .get(&key(&path.to_string_lossy(), self.case_insensitive))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Walk order for two siblings: the larger hint first, then by path
+    pub fn compare(&self, a: &Path, b: &Path) -> Ordering {
+        self.get(b).cmp(&self.get(a)).then_with(|| a.cmp(b))
+    }
+}
+
+fn key(path: &str, case_insensitive: bool) -> String {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    if case_insensitive {
+        trimmed.to_lowercase()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Hints for a scan of `root`: the newest stored scan overlapping it, or failing that
+/// its baseline. None when neither has seen the root.
+pub fn hints_for_root(state: &AppState, root: &Path) -> Option<SizeHints> {
+    let root_str = root.to_string_lossy().to_string();
+    let case_insensitive = is_case_insensitive(root);
+    let held = state.scan_roots();
+    let newest = roots::find_overlaps(
+        &root_str,
+        held.iter().map(|(id, path)| (id.as_str(), path.as_str())),
+        case_insensitive,
+    )
+    .into_iter()
+    .filter_map(|overlap| Some((state.get_result(&overlap.scan_id)?.started_at, overlap.scan_id)))
+    .max();
+    if let Some((_, scan_id)) = newest {
+        if let Ok(hints) = state.with_tree(&scan_id, SizeHints::from_tree) {
+            if !hints.is_empty() {
+                return Some(hints);
+            }
+        }
+    }
+    let baseline = baseline::load_baselines(state.data_dir()?).into_iter().find(|baseline| {
+        !roots::find_overlaps(&root_str, [("", baseline.root_path.as_str())], case_insensitive).is_empty()
+    })?;
+    let hints = SizeHints::from_sizes(
+        baseline.dirs.iter().map(|(path, bytes)| (path.as_str(), *bytes)),
+        case_insensitive,
+    );
+    (!hints.is_empty()).then_some(hints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn larger_hinted_siblings_sort_first() {
+        let hints = SizeHints::from_sizes(
+            [("C:\\Data\\Videos", 40 * HINT_MIN_DIR_BYTES), ("C:\\Data\\Games", 90 * HINT_MIN_DIR_BYTES), ("C:\\Data\\tiny", 1)],
+            true,
+        );
+        assert_eq!(hints.len(), 2);
+        let mut siblings = vec![
+            Path::new("c:\\data\\a.txt"),
+            Path::new("c:\\data\\tiny"),
+            Path::new("c:\\data\\videos"),
+            Path::new("c:\\data\\GAMES"),
+        ];
+        siblings.sort_by(|a, b| hints.compare(a, b));
+        assert_eq!(
+            siblings,
+            vec![
+                Path::new("c:\\data\\GAMES"),
+                Path::new("c:\\data\\videos"),
+                Path::new("c:\\data\\a.txt"),
+                Path::new("c:\\data\\tiny"),
+            ]
+        );
+    }
+}
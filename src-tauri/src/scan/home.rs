@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::scan::known_folders::UserFolder;
+use crate::scan::model::NodeId;
+use crate::scan::tree::ScanTree;
+
+/// Space taken by one standard folder of the profile
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HomeCategoryUsage {
+    pub category: UserFolder,
+    pub path: String,
+    pub node_id: NodeId,
+    pub bytes: u64,
+    pub files: u64,
+    /// Relocated out of the profile folder, so not part of its total
+    pub outside_home: bool,
+}
+
+/// The user's profile broken down by standard folder, for the landing screen
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HomeOverview {
+    /// The scan the overview was read from, which can be browsed like any other
+    pub scan_id: String,
+    pub home_path: String,
+    pub total_bytes: u64,
+    pub total_files: u64,
+    /// Largest first; folders missing from the scan are left out
+    pub categories: Vec<HomeCategoryUsage>,
+    /// Profile bytes outside every listed folder
+    pub other_bytes: u64,
+}
+
+/// Summarize `home` and its standard `folders` from a tree holding them
+pub fn overview(
+    scan_id: &str,
+    tree: &ScanTree,
+    home: &Path,
+    folders: &[(UserFolder, PathBuf)],
+) -> Result<HomeOverview, String> {
+    let home_node = tree
+        .find_by_path(&home.to_string_lossy())
+        .ok_or_else(|| format!("{} is not in the scan", home.display()))?;
+    let mut categories: Vec<HomeCategoryUsage> = folders
+        .iter()
+        .filter_map(|(category, path)| {
+            let node = tree.find_by_path(&path.to_string_lossy())?;
+            Some(HomeCategoryUsage {
+                category: *category,
+                path: node.path.clone(),
+                node_id: node.id,
+                bytes: node.size_bytes,
+                files: tree.subtree_counts(node.id).map(|(files, _)| files).unwrap_or(0),
+                outside_home: !is_descendant(tree, node.id, home_node.id),
+            })
+        })
+        .collect();
+    categories.sort_by_key(|usage| std::cmp::Reverse(usage.bytes));
+    let inside: u64 = categories
+        .iter()
+        .filter(|usage| !usage.outside_home)
+        .map(|usage| usage.bytes)
+        .sum();
+    Ok(HomeOverview {
+        scan_id: scan_id.to_string(),
+        home_path: home_node.path.clone(),
+        total_bytes: home_node.size_bytes,
+        total_files: tree.subtree_counts(home_node.id).map(|(files, _)| files).unwrap_or(0),
+        other_bytes: home_node.size_bytes.saturating_sub(inside),
+        categories,
+    })
+}
+
+fn is_descendant(tree: &ScanTree, id: NodeId, ancestor: NodeId) -> bool {
+    let mut current = tree.get(id).and_then(|node| node.parent);
+    while let Some(parent) = current {
+        if parent == ancestor {
+            return true;
+        }
+        current = tree.get(parent).and_then(|node| node.parent);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::{NodeKind, TreeNode};
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn splits_the_profile_by_folder() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/", NodeKind::Dir, 175, vec![2, 7]),
+                node(2, Some(1), "/home/anna", NodeKind::Dir, 75, vec![3, 5, 6]),
+                node(3, Some(2), "/home/anna/Downloads", NodeKind::Dir, 50, vec![4]),
+                node(4, Some(3), "/home/anna/Downloads/a.iso", NodeKind::File, 50, vec![]),
+                node(5, Some(2), "/home/anna/.cache", NodeKind::Dir, 0, vec![]),
+                node(6, Some(2), "/home/anna/notes.txt", NodeKind::File, 25, vec![]),
+                node(7, Some(1), "/data/Documents", NodeKind::Dir, 100, vec![]),
+            ],
+            false,
+        );
+        let folders = vec![
+            (UserFolder::Downloads, PathBuf::from("/home/anna/Downloads")),
+            (UserFolder::AppCaches, PathBuf::from("/home/anna/.cache")),
+            (UserFolder::Documents, PathBuf::from("/data/Documents")),
+            (UserFolder::Pictures, PathBuf::from("/home/anna/Pictures")),
+        ];
+        let overview = overview("s", &tree, Path::new("/home/anna"), &folders).expect("overview");
+        assert_eq!((overview.total_bytes, overview.total_files, overview.other_bytes), (75, 2, 25));
+        let categories: Vec<(UserFolder, u64, bool)> = overview
+            .categories
+            .iter()
+            .map(|usage| (usage.category, usage.bytes, usage.outside_home))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                (UserFolder::Documents, 100, true),
+                (UserFolder::Downloads, 50, false),
+                (UserFolder::AppCaches, 0, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::scan::delete::SafetyLevel;
+
+/// Hooks run arbitrary programs, so they are only read from this file in the app data
+/// dir, which the user edits by hand, and never set from the webview
+pub const HOOKS_FILE: &str = "hooks.json";
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// External commands run around deletions
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HookConfig {
+    #[serde(default)]
+    pub pre_delete: Option<HookCommand>,
+    #[serde(default)]
+    pub post_delete: Option<HookCommand>,
+}
+
+impl HookConfig {
+    pub fn is_configured(&self) -> bool {
+        self.pre_delete.is_some() || self.post_delete.is_some()
+    }
+}
+
+/// Load the hooks from `dir`; a missing or unreadable file configures none
+pub fn load_hooks(dir: &Path) -> HookConfig {
+    fs::read_to_string(dir.join(HOOKS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HookCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_SECS
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPhase {
+    PreDelete,
+    PostDelete,
+}
+
+/// One path in the JSON manifest written to the hook's stdin
+#[derive(Clone, Debug, Serialize)]
+pub struct HookItem {
+    pub path: String,
+    pub size_bytes: u64,
+    pub safety_level: SafetyLevel,
+    /// Only set for post-delete hooks
+    pub success: Option<bool>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HookManifest {
+    pub phase: HookPhase,
+    pub operation: String,
+    pub items: Vec<HookItem>,
+}
+
+/// Optional JSON a pre-delete hook may print on stdout
+#[derive(Debug, Deserialize)]
+struct HookResponse {
+    #[serde(default = "default_allow")]
+    allow: bool,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    deny_paths: Vec<String>,
+}
+
+fn default_allow() -> bool {
+    true
+}
+
+/// A hook that exits 0 with no (or non-JSON) output allows everything
+impl Default for HookResponse {
+    fn default() -> Self {
+        Self {
+            allow: default_allow(),
+            reason: None,
+            deny_paths: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of a pre-delete hook
+#[derive(Clone, Debug, PartialEq)]
+pub enum HookDecision {
+    Allow,
+    /// Only the listed paths are vetoed
+    DenyPaths(Vec<String>, String),
+    Deny(String),
+}
+
+impl HookDecision {
+    pub fn denies(&self, path: &str) -> Option<String> {
+        match self {
+            HookDecision::Allow => None,
+            HookDecision::DenyPaths(paths, reason) => {
+                paths.iter().any(|p| p == path).then(|| reason.clone())
+            }
+            HookDecision::Deny(reason) => Some(reason.clone()),
+        }
+    }
+}
+
+/// Run the pre-delete hook (if configured). A non-zero exit, a timeout, or a failure
+/// to launch denies the whole operation; otherwise stdout may veto individual paths.
+pub fn run_pre_delete(config: &HookConfig, manifest: &HookManifest) -> HookDecision {
+    let Some(command) = &config.pre_delete else {
+        return HookDecision::Allow;
+    };
+    match run_hook(command, manifest) {
+        Ok((true, stdout)) => {
+            let response: HookResponse = serde_json::from_str(stdout.trim()).unwrap_or_default();
+            let reason = response
+                .reason
+                .unwrap_or_else(|| "Denied by pre-delete hook".to_string());
+            if !response.allow {
+                HookDecision::Deny(reason)
+            } else if !response.deny_paths.is_empty() {
+                HookDecision::DenyPaths(response.deny_paths, reason)
+            } else {
+                HookDecision::Allow
+            }
+        }
+        Ok((false, stdout)) => {
+            let message = stdout.trim();
+            if message.is_empty() {
+                HookDecision::Deny("Pre-delete hook exited with an error".to_string())
+            } else {
+                HookDecision::Deny(message.to_string())
+            }
+        }
+        Err(e) => HookDecision::Deny(format!("Pre-delete hook failed: {}", e)),
+    }
+}
+
+/// Run the post-delete hook (if configured). Failures are reported but never undo anything.
+pub fn run_post_delete(config: &HookConfig, manifest: &HookManifest) -> Result<(), String> {
+    let Some(command) = &config.post_delete else {
+        return Ok(());
+    };
+    match run_hook(command, manifest)? {
+        (true, _) => Ok(()),
+        (false, _) => Err("Post-delete hook exited with an error".to_string()),
+    }
+}
+
+/// Spawn the hook, feed it the manifest, and wait up to its timeout for it to exit and
+/// close stdout. stdin is written and stdout read on their own threads, so a hook that
+/// doesn't read its input or prints a lot can't stall past the timeout.
+/// Returns (exit success, stdout).
+fn run_hook(command: &HookCommand, manifest: &HookManifest) -> Result<(bool, String), String> {
+    let input = serde_json::to_vec(manifest).map_err(|e| e.to_string())?;
+    let mut child = Command::new(&command.program)
+        .args(&command.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let timed_out = || format!("timed out after {}s", command.timeout_secs);
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may close it early; that's not an error
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let (sender, stdout) = mpsc::channel();
+    if let Some(mut pipe) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = pipe.read_to_end(&mut output);
+            let _ = sender.send(output);
+        });
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(command.timeout_secs);
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(timed_out());
+            }
+            None => thread::sleep(HOOK_POLL_INTERVAL),
+        }
+    };
+
+    // Something the hook started may still hold stdout open
+    let output = stdout
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .map_err(|_| timed_out())?;
+    Ok((status.success(), String::from_utf8_lossy(&output).to_string()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn manifest() -> HookManifest {
+        HookManifest {
+            phase: HookPhase::PreDelete,
+            operation: "smart_delete".to_string(),
+            items: vec![HookItem {
+                path: "/tmp/a.log".to_string(),
+                size_bytes: 10,
+                safety_level: SafetyLevel::AutoDelete,
+                success: None,
+                error: None,
+            }],
+        }
+    }
+
+    fn shell_hook(script: &str) -> HookConfig {
+        HookConfig {
+            pre_delete: Some(HookCommand {
+                program: "sh".to_string(),
+                args: vec!["-c".to_string(), script.to_string()],
+                timeout_secs: 5,
+            }),
+            post_delete: None,
+        }
+    }
+
+    #[test]
+    fn no_hook_allows() {
+        assert_eq!(run_pre_delete(&HookConfig::default(), &manifest()), HookDecision::Allow);
+    }
+
+    #[test]
+    fn silent_success_allows() {
+        assert_eq!(run_pre_delete(&shell_hook("cat >/dev/null"), &manifest()), HookDecision::Allow);
+        let decision = run_pre_delete(&shell_hook("cat >/dev/null; echo looks fine"), &manifest());
+        assert_eq!(decision, HookDecision::Allow);
+    }
+
+    #[test]
+    fn non_zero_exit_denies() {
+        let decision = run_pre_delete(&shell_hook("cat >/dev/null; echo nope; exit 3"), &manifest());
+        assert_eq!(decision, HookDecision::Deny("nope".to_string()));
+    }
+
+    #[test]
+    fn large_output_is_read_while_the_hook_runs() {
+        let script = "cat >/dev/null; head -c 200000 /dev/zero | tr '\\0' a; exit 4";
+        let started = Instant::now();
+        let decision = run_pre_delete(&shell_hook(script), &manifest());
+        assert!(matches!(decision, HookDecision::Deny(message) if message.len() == 200_000));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn hooks_that_never_finish_time_out() {
+        let mut config = shell_hook("sleep 30");
+        if let Some(command) = config.pre_delete.as_mut() {
+            command.timeout_secs = 1;
+        }
+        let decision = run_pre_delete(&config, &manifest());
+        assert_eq!(decision, HookDecision::Deny("Pre-delete hook failed: timed out after 1s".to_string()));
+    }
+
+    #[test]
+    fn hooks_load_from_their_own_file() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        assert!(!load_hooks(temp.path()).is_configured());
+        let config = r#"{"pre_delete": {"program": "/usr/local/bin/check-backup", "args": ["--json"]}}"#;
+        fs::write(temp.path().join(HOOKS_FILE), config).expect("write hooks");
+        let hooks = load_hooks(temp.path());
+        let command = hooks.pre_delete.expect("pre-delete hook");
+        assert_eq!((command.program.as_str(), command.timeout_secs), ("/usr/local/bin/check-backup", 30));
+        assert!(hooks.post_delete.is_none());
+    }
+
+    #[test]
+    fn stdout_can_veto_single_paths() {
+        let script = r#"cat >/dev/null; echo '{"deny_paths": ["/tmp/a.log"], "reason": "backup pending"}'"#;
+        let decision = run_pre_delete(&shell_hook(script), &manifest());
+        assert_eq!(decision.denies("/tmp/a.log"), Some("backup pending".to_string()));
+        assert_eq!(decision.denies("/tmp/other"), None);
+    }
+}
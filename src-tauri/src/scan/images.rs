@@ -0,0 +1,205 @@
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, NodeId, SimilarImage, SimilarImageGroup};
+
+pub const DEFAULT_IMAGE_SIZE_CAP: u64 = 20 * 1024 * 1024;
+/// Max differing bits between two 64-bit hashes for images to count as near-duplicates
+pub const DEFAULT_IMAGE_DISTANCE: u32 = 6;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+const THUMBNAIL_SIZE: u32 = 128;
+
+struct HashedImage {
+    image: SimilarImage,
+    hash: u64,
+}
+
+/// Clusters photos whose difference hashes are within `max_distance` bits
+/// (burst shots, resized or re-encoded copies)
+pub struct SimilarImagesAnalyzer {
+    max_bytes: u64,
+    max_distance: u32,
+    hashed: Vec<HashedImage>,
+}
+
+impl SimilarImagesAnalyzer {
+    pub fn new(max_bytes: u64, max_distance: u32) -> Self {
+        Self {
+            max_bytes,
+            max_distance,
+            hashed: Vec::new(),
+        }
+    }
+}
+
+impl Analyzer for SimilarImagesAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let node = file.node;
+        let is_image = node
+            .file_ext
+            .as_deref()
+            .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext));
+        if !is_image || node.size_bytes > self.max_bytes {
+            return;
+        }
+        let Ok(decoded) = open_image(Path::new(&node.path)) else {
+            return;
+        };
+        self.hashed.push(HashedImage {
+            hash: dhash(&decoded),
+            image: SimilarImage {
+                id: node.id,
+                path: node.path.clone(),
+                size_bytes: node.size_bytes,
+                width: decoded.width(),
+                height: decoded.height(),
+                modified: file
+                    .modified
+                    .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs()),
+                thumbnail: None,
+            },
+        });
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let hashes: Vec<u64> = self.hashed.iter().map(|h| h.hash).collect();
+        let clusters = cluster(&hashes, self.max_distance);
+        let mut images: Vec<Option<SimilarImage>> = self.hashed.into_iter().map(|h| Some(h.image)).collect();
+
+        let mut groups: Vec<SimilarImageGroup> = clusters
+            .into_iter()
+            .map(|members| {
+                let mut members: Vec<SimilarImage> = members.into_iter().filter_map(|i| images[i].take()).collect();
+                for image in members.iter_mut() {
+                    image.thumbnail = thumbnail(Path::new(&image.path));
+                }
+                let keeper = recommended_keeper(&members);
+                let reclaimable_bytes = members
+                    .iter()
+                    .filter(|m| m.id != keeper)
+                    .map(|m| m.size_bytes)
+                    .sum();
+                SimilarImageGroup {
+                    images: members,
+                    keeper_id: keeper,
+                    reclaimable_bytes,
+                }
+            })
+            .collect();
+        groups.sort_by_key(|g| std::cmp::Reverse(g.reclaimable_bytes));
+        AnalyzerReport::SimilarImages { groups }
+    }
+}
+
+fn open_image(path: &Path) -> Result<DynamicImage, String> {
+    ImageReader::open(path)
+        .map_err(|e| e.to_string())?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .decode()
+        .map_err(|e| e.to_string())
+}
+
+/// 64-bit difference hash: one bit per horizontally adjacent pixel pair of a 9x8 grayscale image
+fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left < right);
+        }
+    }
+    hash
+}
+
+/// Single-linkage clustering by Hamming distance; returns groups of two or more indices
+fn cluster(hashes: &[u64], max_distance: u32) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..hashes.len() {
+        for j in (i + 1)..hashes.len() {
+            if (hashes[i] ^ hashes[j]).count_ones() <= max_distance {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+        }
+    }
+    let mut by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..hashes.len() {
+        let root = find(&mut parent, i);
+        by_root.entry(root).or_default().push(i);
+    }
+    by_root.into_values().filter(|members| members.len() > 1).collect()
+}
+
+/// Prefer the highest resolution, then the largest file, then the newest
+fn recommended_keeper(images: &[SimilarImage]) -> NodeId {
+    images
+        .iter()
+        .max_by_key(|i| (u64::from(i.width) * u64::from(i.height), i.size_bytes, i.modified))
+        .map(|i| i.id)
+        .unwrap_or_default()
+}
+
+/// Small JPEG preview as a data URL
+fn thumbnail(path: &Path) -> Option<String> {
+    let image = open_image(path).ok()?;
+    let preview = DynamicImage::ImageRgb8(image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8());
+    let mut bytes = Cursor::new(Vec::new());
+    preview.write_to(&mut bytes, ImageFormat::Jpeg).ok()?;
+    Some(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes.into_inner())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn gradient(width: u32, height: u32, flip: bool) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+            let v = (x * 255 / width) as u8;
+            let v = if flip { 255 - v } else { v };
+            Rgb([v, v, v])
+        }))
+    }
+
+    #[test]
+    fn resized_copies_hash_alike() {
+        let original = dhash(&gradient(640, 480, false));
+        let resized = dhash(&gradient(160, 120, false));
+        let different = dhash(&gradient(640, 480, true));
+
+        assert!((original ^ resized).count_ones() <= DEFAULT_IMAGE_DISTANCE);
+        assert!((original ^ different).count_ones() > DEFAULT_IMAGE_DISTANCE);
+    }
+
+    #[test]
+    fn clusters_only_close_hashes() {
+        let groups = cluster(&[0b0000, 0b0001, u64::MAX, 0b0011], 1);
+        assert_eq!(groups.len(), 1);
+        let mut members = groups[0].clone();
+        members.sort_unstable();
+        assert_eq!(members, vec![0, 1, 3]);
+    }
+}
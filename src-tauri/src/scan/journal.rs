@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const JOURNAL_FILE: &str = "journal.json";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStatus {
+    Pending,
+    Completed,
+    Failed,
+    /// Still pending when the app last exited; waiting for the user to resume, roll
+    /// back, or dismiss it
+    Interrupted,
+    RolledBack,
+    Dismissed,
+}
+
+/// A destructive operation that may outlive the current process
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalOperation {
+    /// Delete scheduled with MoveFileEx(MOVEFILE_DELAY_UNTIL_REBOOT)
+    RebootDelete { path: String },
+    /// A bulk delete; the paths still present when it is found interrupted were not
+    /// deleted yet
+    BulkDelete { paths: Vec<String> },
+    /// A rename, such as moving an item into quarantine
+    Move { from: String, to: String },
+    /// Items zipped into `archive`, verified, then deleted; as with a bulk delete, the
+    /// paths still present were not deleted yet
+    ArchiveDelete { paths: Vec<String>, archive: String },
+}
+
+impl JournalOperation {
+    /// Operations that finish within the process that started them. Left pending
+    /// across a restart, they were cut short by a crash.
+    fn ends_with_process(&self) -> bool {
+        !matches!(self, JournalOperation::RebootDelete { .. })
+    }
+}
+
+/// What to do with an interrupted operation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// Finish the operation
+    Resume,
+    /// Undo what was done: restore deleted items from the trash, move items back
+    RollBack,
+    /// Leave things as they are and stop reporting the operation
+    Dismiss,
+}
+
+/// An interrupted operation and how far it got
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IncompleteOperation {
+    pub entry: JournalEntry,
+    /// Paths the operation had not reached
+    pub remaining: Vec<String>,
+    /// Paths it had already deleted or moved
+    pub done: Vec<String>,
+}
+
+impl IncompleteOperation {
+    pub fn new(entry: JournalEntry) -> Self {
+        let (remaining, done) = match &entry.operation {
+            JournalOperation::RebootDelete { path } => (vec![path.clone()], Vec::new()),
+            JournalOperation::BulkDelete { paths } | JournalOperation::ArchiveDelete { paths, .. } => {
+                paths.iter().cloned().partition(|path| Path::new(path).exists())
+            }
+            JournalOperation::Move { from, to } => {
+                if Path::new(to).exists() && !Path::new(from).exists() {
+                    (Vec::new(), vec![from.clone()])
+                } else {
+                    (vec![from.clone()], Vec::new())
+                }
+            }
+        };
+        Self { entry, remaining, done }
+    }
+}
+
+/// Result of recovering an interrupted operation
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecoveryOutcome {
+    pub entry: Option<JournalEntry>,
+    /// Paths deleted or moved by resuming
+    pub completed: Vec<String>,
+    /// Paths put back by rolling back
+    pub restored: Vec<String>,
+    /// Paths that could not be resumed or put back, with the reason
+    pub errors: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub status: JournalStatus,
+    pub operation: JournalOperation,
+}
+
+impl JournalEntry {
+    pub fn pending(id: String, operation: JournalOperation) -> Self {
+        let now = now_millis();
+        Self {
+            id,
+            created_at: now,
+            updated_at: now,
+            status: JournalStatus::Pending,
+            operation,
+        }
+    }
+
+    pub fn set_status(&mut self, status: JournalStatus) {
+        self.status = status;
+        self.updated_at = now_millis();
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(default)]
+    pub entries: Vec<JournalEntry>,
+}
+
+pub fn load_journal(dir: &Path) -> Journal {
+    fs::read_to_string(dir.join(JOURNAL_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the journal, writing to a temp file first so a crash can't truncate it
+pub fn save_journal(dir: &Path, journal: &Journal) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string_pretty(journal).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", JOURNAL_FILE));
+    fs::write(&temp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(JOURNAL_FILE)).map_err(|e| e.to_string())
+}
+
+/// Mark operations left pending by an earlier run as interrupted. Called once at launch,
+/// before anything new is journaled.
+pub fn mark_interrupted(journal: &mut Journal) -> bool {
+    let mut changed = false;
+    for entry in journal.entries.iter_mut() {
+        if entry.status == JournalStatus::Pending && entry.operation.ends_with_process() {
+            entry.set_status(JournalStatus::Interrupted);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Resolve pending reboot deletions: gone means completed; still present after a
+/// reboot that happened since scheduling means the OS did not delete it.
+pub fn refresh_reboot_deletions(journal: &mut Journal, boot_time_millis: u64) -> bool {
+    let mut changed = false;
+    for entry in journal.entries.iter_mut() {
+        if entry.status != JournalStatus::Pending {
+            continue;
+        }
+        let JournalOperation::RebootDelete { path } = &entry.operation else {
+            continue;
+        };
+        if !Path::new(path).exists() {
+            entry.set_status(JournalStatus::Completed);
+            changed = true;
+        } else if boot_time_millis > entry.created_at {
+            entry.set_status(JournalStatus::Failed);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Ask the OS to delete `path` during the next boot
+#[cfg(target_os = "windows")]
+pub fn schedule_reboot_delete(path: &Path) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{MoveFileExW, MOVEFILE_DELAY_UNTIL_REBOOT};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let ok = unsafe { MoveFileExW(wide.as_ptr(), std::ptr::null(), MOVEFILE_DELAY_UNTIL_REBOOT) };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn schedule_reboot_delete(_path: &Path) -> Result<(), String> {
+    Err("Delete-on-reboot is only supported on Windows".to_string())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reboot_deletions_resolve_after_restart() {
+        let temp = tempdir().expect("tempdir");
+        let kept = temp.path().join("kept.dll");
+        fs::write(&kept, b"x").expect("write");
+
+        let mut journal = Journal::default();
+        journal.entries.push(JournalEntry::pending(
+            "gone".to_string(),
+            JournalOperation::RebootDelete { path: temp.path().join("gone.dll").to_string_lossy().to_string() },
+        ));
+        journal.entries.push(JournalEntry::pending(
+            "kept".to_string(),
+            JournalOperation::RebootDelete { path: kept.to_string_lossy().to_string() },
+        ));
+
+        // No reboot yet: the surviving file stays pending
+        assert!(refresh_reboot_deletions(&mut journal, 0));
+        assert_eq!(journal.entries[0].status, JournalStatus::Completed);
+        assert_eq!(journal.entries[1].status, JournalStatus::Pending);
+
+        refresh_reboot_deletions(&mut journal, u64::MAX);
+        assert_eq!(journal.entries[1].status, JournalStatus::Failed);
+
+        save_journal(temp.path(), &journal).expect("save");
+        assert_eq!(load_journal(temp.path()).entries.len(), 2);
+    }
+
+    #[test]
+    fn crashed_operations_are_marked_interrupted_with_progress() {
+        let temp = tempdir().expect("tempdir");
+        let left = temp.path().join("left.log");
+        fs::write(&left, b"x").expect("write");
+        let paths = vec![
+            temp.path().join("gone.log").to_string_lossy().to_string(),
+            left.to_string_lossy().to_string(),
+        ];
+
+        let mut journal = Journal::default();
+        journal.entries.push(JournalEntry::pending("bulk".to_string(), JournalOperation::BulkDelete { paths }));
+        journal.entries.push(JournalEntry::pending(
+            "reboot".to_string(),
+            JournalOperation::RebootDelete { path: left.to_string_lossy().to_string() },
+        ));
+        assert!(mark_interrupted(&mut journal));
+        assert_eq!(journal.entries[0].status, JournalStatus::Interrupted);
+        assert_eq!(journal.entries[1].status, JournalStatus::Pending);
+        assert!(!mark_interrupted(&mut journal));
+
+        let incomplete = IncompleteOperation::new(journal.entries[0].clone());
+        assert_eq!(incomplete.remaining, vec![left.to_string_lossy().to_string()]);
+        assert_eq!(incomplete.done.len(), 1);
+    }
+}
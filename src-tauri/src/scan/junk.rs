@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::analyzer::category_for_extension;
+use crate::scan::delete::is_protected_path;
+use crate::scan::messages::{self, Message};
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::tree::ScanTree;
+
+const DAY: u64 = 24 * 60 * 60;
+
+// Maximum points contributed by each signal; they add up to 100
+const LOCATION_WEIGHT: u8 = 40;
+const NAME_WEIGHT: u8 = 20;
+const AGE_WEIGHT: u8 = 20;
+const DUPLICATE_WEIGHT: u8 = 10;
+const CATEGORY_WEIGHT: u8 = 10;
+
+// Folder names that mark their contents as likely junk, as (name, share of LOCATION_WEIGHT in %)
+const JUNK_LOCATIONS: &[(&str, u8)] = &[
+    ("temp", 100),
+    ("tmp", 100),
+    (".tmp", 100),
+    (".temp", 100),
+    ("crashdumps", 100),
+    ("minidump", 100),
+    ("livekernelreports", 100),
+    ("reportarchive", 100),
+    ("reportqueue", 100),
+    ("diagnosticreports", 90),
+    ("inetcache", 90),
+    ("temporary internet files", 90),
+    ("cache", 85),
+    ("caches", 85),
+    (".cache", 85),
+    ("code cache", 85),
+    ("gpucache", 85),
+    ("grshadercache", 85),
+    ("shadercache", 85),
+    ("dxcache", 85),
+    ("dxccache", 85),
+    ("glcache", 85),
+    ("vkcache", 85),
+    ("nv_cache", 85),
+    ("d3dscache", 85),
+    ("mesa_shader_cache", 85),
+    ("dawncache", 85),
+    ("cache2", 85),
+    ("startupcache", 85),
+    ("cachestorage", 80),
+    ("scriptcache", 80),
+    ("__pycache__", 85),
+    (".pytest_cache", 85),
+    (".mypy_cache", 85),
+    ("logs", 75),
+    ("log", 75),
+    ("node_modules", 60),
+    (".next", 60),
+    (".turbo", 60),
+    ("target", 50),
+    ("build", 40),
+    ("dist", 40),
+    ("downloads", 25),
+];
+
+// Exact file names that are always junk
+const JUNK_NAMES: &[&str] = &[
+    "thumbs.db",
+    "ehthumbs.db",
+    "ehthumbs_vista.db",
+    "desktop.ini",
+    ".ds_store",
+    "npm-debug.log",
+    "yarn-error.log",
+    "yarn-debug.log",
+    "memory.dmp",
+];
+
+// Name fragments typical of stray copies and backups
+const COPY_MARKERS: &[&str] = &[" - copy", "copy of ", " (1)", " (2)", " (3)", ".bak", ".old", ".orig"];
+
+/// 0-100 estimate of how likely a node is safe-to-remove junk, with the signals behind it
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct JunkScore {
+    pub score: u8,
+    pub reasons: Vec<String>,
+    /// `reasons` as codes and params for the frontend to translate
+    #[serde(default)]
+    pub reason_messages: Vec<Message>,
+}
+
+impl JunkScore {
+    fn new(score: u8, reason_messages: Vec<Message>) -> Self {
+        Self {
+            score,
+            reasons: reason_messages.iter().map(|message| message.text.clone()).collect(),
+            reason_messages,
+        }
+    }
+}
+
+/// A node ranked by `get_junk_candidates`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JunkCandidate {
+    pub id: NodeId,
+    pub path: String,
+    pub name: String,
+    pub kind: NodeKind,
+    pub size_bytes: u64,
+    pub score: u8,
+    pub reasons: Vec<String>,
+    #[serde(default)]
+    pub reason_messages: Vec<Message>,
+}
+
+/// The facts a score is computed from, independent of where they came from
+pub struct JunkInput<'a> {
+    pub path: &'a str,
+    pub name: &'a str,
+    pub ext: Option<&'a str>,
+    pub is_dir: bool,
+    /// Newest modification time in seconds since the Unix epoch (for dirs, of any descendant)
+    pub modified: Option<u64>,
+    pub duplicated: bool,
+}
+
+pub fn score(input: &JunkInput<'_>, now_secs: u64) -> JunkScore {
+    if is_protected_path(Path::new(input.path)) {
+        return JunkScore::new(0, vec![Message::new(messages::JUNK_PROTECTED, [])]);
+    }
+
+    let mut total: u32 = 0;
+    let mut reasons = Vec::new();
+
+    if let Some((folder, share)) = junk_location(input) {
+        total += LOCATION_WEIGHT as u32 * share as u32 / 100;
+        reasons.push(Message::new(messages::JUNK_INSIDE_FOLDER, [("folder", folder.to_string())]));
+    }
+
+    let name = input.name.to_lowercase();
+    if JUNK_NAMES.contains(&name.as_str()) {
+        total += NAME_WEIGHT as u32;
+        reasons.push(Message::new(messages::JUNK_SYSTEM_FILE, []));
+    } else if name.starts_with("~$") || name.ends_with('~') {
+        total += NAME_WEIGHT as u32;
+        reasons.push(Message::new(messages::JUNK_EDITOR_FILE, []));
+    } else if COPY_MARKERS.iter().any(|marker| name.contains(marker)) {
+        total += NAME_WEIGHT as u32 * 3 / 4;
+        reasons.push(Message::new(messages::JUNK_COPY_NAME, []));
+    }
+
+    if let Some(age_days) = input.modified.map(|m| now_secs.saturating_sub(m) / DAY) {
+        let points = match age_days {
+            d if d >= 365 => AGE_WEIGHT,
+            d if d >= 180 => AGE_WEIGHT * 3 / 4,
+            d if d >= 90 => AGE_WEIGHT / 2,
+            d if d >= 30 => AGE_WEIGHT / 4,
+            _ => 0,
+        };
+        if points > 0 {
+            total += points as u32;
+            reasons.push(Message::new(messages::JUNK_NOT_MODIFIED, [("days", age_days.to_string())]));
+        }
+    }
+
+    if input.duplicated {
+        total += DUPLICATE_WEIGHT as u32;
+        reasons.push(Message::new(messages::JUNK_DUPLICATE, []));
+    }
+
+    if !input.is_dir && category_for_extension(input.ext) == "temporary" {
+        total += CATEGORY_WEIGHT as u32;
+        reasons.push(Message::new(messages::JUNK_TEMPORARY_TYPE, []));
+    }
+
+    JunkScore::new(total.min(100) as u8, reasons)
+}
+
+/// Score a path straight from the filesystem, without scan context
+pub fn score_path(path: &Path) -> JunkScore {
+    let path_str = path.to_string_lossy();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.to_string());
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    let modified = path
+        .metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    score(
+        &JunkInput {
+            path: &path_str,
+            name: &name,
+            ext: ext.as_deref(),
+            is_dir: path.is_dir(),
+            modified,
+            duplicated: false,
+        },
+        now_secs(),
+    )
+}
+
+/// Scores nodes of a stored scan tree, using tree-wide context for duplication and
+/// directory ages
+pub struct TreeScorer<'a> {
+    tree: &'a ScanTree,
+    duplicates: HashMap<(String, u64), u32>,
+    dir_modified: HashMap<NodeId, u64>,
+    now_secs: u64,
+}
+
+impl<'a> TreeScorer<'a> {
+    pub fn new(tree: &'a ScanTree) -> Self {
+        let mut duplicates: HashMap<(String, u64), u32> = HashMap::new();
+        let mut dir_modified: HashMap<NodeId, u64> = HashMap::new();
+        for node in tree.nodes().filter(|n| n.kind == NodeKind::File) {
+            *duplicates.entry((node.name.to_lowercase(), node.size_bytes)).or_default() += 1;
+            let Some(modified) = node.modified else {
+                continue;
+            };
+            let mut ancestor = node.parent;
+            while let Some(id) = ancestor {
+                let newest = dir_modified.entry(id).or_default();
+                if *newest >= modified {
+                    break;
+                }
+                *newest = modified;
+                ancestor = tree.get(id).and_then(|n| n.parent);
+            }
+        }
+        Self {
+            tree,
+            duplicates,
+            dir_modified,
+            now_secs: now_secs(),
+        }
+    }
+
+    pub fn score_node(&self, node: &TreeNode) -> JunkScore {
+        let is_dir = node.kind == NodeKind::Dir;
+        let modified = if is_dir {
+            self.dir_modified.get(&node.id).copied()
+        } else {
+            node.modified
+        };
+        let duplicated = !is_dir
+            && self
+                .duplicates
+                .get(&(node.name.to_lowercase(), node.size_bytes))
+                .is_some_and(|count| *count > 1);
+        score(
+            &JunkInput {
+                path: &node.path,
+                name: &node.name,
+                ext: node.file_ext.as_deref(),
+                is_dir,
+                modified,
+                duplicated,
+            },
+            self.now_secs,
+        )
+    }
+
+    /// Nodes scoring at least `min_score`, highest first. Once a directory qualifies its
+    /// descendants are not listed separately, so bytes are never counted twice.
+    pub fn candidates(&self, min_score: u8) -> Vec<JunkCandidate> {
+        let mut candidates = Vec::new();
+        let mut stack: Vec<NodeId> = self
+            .tree
+            .root()
+            .map(|root| root.children.clone())
+            .unwrap_or_default();
+        while let Some(id) = stack.pop() {
+            let Some(node) = self.tree.get(id) else {
+                continue;
+            };
+            let junk = self.score_node(node);
+            if junk.score >= min_score {
+                candidates.push(JunkCandidate {
+                    id: node.id,
+                    path: node.path.clone(),
+                    name: node.name.clone(),
+                    kind: node.kind,
+                    size_bytes: node.size_bytes,
+                    score: junk.score,
+                    reasons: junk.reasons,
+                    reason_messages: junk.reason_messages,
+                });
+            } else {
+                stack.extend(node.children.iter().copied());
+            }
+        }
+        candidates.sort_by(|a, b| b.score.cmp(&a.score).then(b.size_bytes.cmp(&a.size_bytes)));
+        candidates
+    }
+}
+
+fn junk_location(input: &JunkInput<'_>) -> Option<(String, u8)> {
+    let path = Path::new(input.path);
+    // A file's own name is judged by the name signal; a directory counts as a location
+    let dirs = if input.is_dir { Some(path) } else { path.parent() };
+    dirs?
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .filter_map(|c| {
+            let lower = c.to_lowercase();
+            JUNK_LOCATIONS
+                .iter()
+                .find(|(name, _)| *name == lower)
+                .map(|(_, share)| (lower, *share))
+        })
+        .max_by_key(|(_, share)| *share)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_000 * DAY;
+
+    fn input<'a>(path: &'a str, name: &'a str, ext: Option<&'a str>, modified: Option<u64>) -> JunkInput<'a> {
+        JunkInput {
+            path,
+            name,
+            ext,
+            is_dir: false,
+            modified,
+            duplicated: false,
+        }
+    }
+
+    #[test]
+    fn old_temp_files_outscore_fresh_documents() {
+        let junk = score(&input("/home/u/tmp/build.tmp", "build.tmp", Some("tmp"), Some(NOW - 400 * DAY)), NOW);
+        let doc = score(&input("/home/u/work/report.pdf", "report.pdf", Some("pdf"), Some(NOW - DAY)), NOW);
+
+        assert_eq!(junk.score, LOCATION_WEIGHT + AGE_WEIGHT + CATEGORY_WEIGHT);
+        assert_eq!(junk.reasons.len(), 3);
+        assert_eq!(doc.score, 0);
+        assert!(doc.reasons.is_empty());
+    }
+
+    #[test]
+    fn duplicated_copies_gain_points() {
+        let mut copy = input("/home/u/photos/img - Copy.jpg", "img - Copy.jpg", Some("jpg"), None);
+        let alone = score(&copy, NOW).score;
+        copy.duplicated = true;
+        assert_eq!(score(&copy, NOW).score, alone + DUPLICATE_WEIGHT);
+        assert!(alone > 0);
+    }
+}
@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// A standard folder of the user's profile
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserFolder {
+    Documents,
+    Pictures,
+    Videos,
+    Music,
+    Downloads,
+    Desktop,
+    /// Per-user application data and caches: LocalAppData, ~/Library/Caches, ~/.cache
+    AppCaches,
+}
+
+/// The user's profile folder and its standard folders, resolved the same way as
+/// `known_folders`. A folder may lie outside the profile when it was relocated.
+pub fn user_folders() -> Option<(PathBuf, Vec<(UserFolder, PathBuf)>)> {
+    let (home, folders) = resolve_user_folders()?;
+    let folders = folders.into_iter().filter(|(_, path)| *path != home).collect();
+    Some((home, folders))
+}
+
+/// User and system folders as the OS reports them, wherever they have been relocated
+/// and whatever language they are named in. Resolved once per run.
+pub fn known_folders() -> &'static [PathBuf] {
+    static FOLDERS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    FOLDERS.get_or_init(|| {
+        let mut folders = resolve_known_folders();
+        folders.sort();
+        folders.dedup();
+        folders
+    })
+}
+
+/// The OS temp folders: %TEMP%, %TMP%, and Windows\Temp on Windows; $TMPDIR, /tmp, and
+/// /var/tmp elsewhere. Resolved once per run.
+pub fn temp_folders() -> &'static [PathBuf] {
+    static FOLDERS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    FOLDERS.get_or_init(|| {
+        let mut folders: Vec<PathBuf> = ["TEMP", "TMP", "TMPDIR"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .collect();
+        folders.extend(resolve_temp_folders());
+        folders.sort();
+        folders.dedup();
+        folders
+    })
+}
+
+/// The folders a rule pack may name for an `inside_known_folder` rule
+pub fn named_known_folders(name: &str) -> Option<&'static [PathBuf]> {
+    name.eq_ignore_ascii_case("temp").then(temp_folders)
+}
+
+/// Whether `path` lies somewhere below `folder`
+pub fn is_inside(path: &Path, folder: &Path) -> bool {
+    path != folder && is_same_or_ancestor(folder, path)
+}
+
+/// Whether `path` is a known folder or contains one, so deleting it would take a known
+/// folder with it. Files inside a known folder are not covered.
+pub fn is_known_folder_or_ancestor(path: &Path) -> bool {
+    known_folders().iter().any(|folder| is_same_or_ancestor(path, folder))
+}
+
+fn is_same_or_ancestor(path: &Path, folder: &Path) -> bool {
+    let path = normalize(path);
+    let folder = normalize(folder);
+    !path.is_empty() && (folder == path || folder.starts_with(&format!("{}/", path.trim_end_matches('/'))))
+}
+
+/// Compare paths with one separator style, and case-insensitively where the filesystem is
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = if path.len() > 1 { path.trim_end_matches('/') } else { &path };
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn resolve_known_folders() -> Vec<PathBuf> {
+    use windows_sys::Win32::UI::Shell::{
+        FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_LocalAppData, FOLDERID_Music,
+        FOLDERID_Pictures, FOLDERID_Profile, FOLDERID_ProgramData, FOLDERID_ProgramFiles, FOLDERID_ProgramFilesX86,
+        FOLDERID_Public, FOLDERID_RoamingAppData, FOLDERID_System, FOLDERID_SystemX86, FOLDERID_UserProfiles,
+        FOLDERID_Videos, FOLDERID_Windows,
+    };
+
+    [
+        FOLDERID_Profile,
+        FOLDERID_UserProfiles,
+        FOLDERID_Public,
+        FOLDERID_Documents,
+        FOLDERID_Pictures,
+        FOLDERID_Videos,
+        FOLDERID_Music,
+        FOLDERID_Downloads,
+        FOLDERID_Desktop,
+        FOLDERID_RoamingAppData,
+        FOLDERID_LocalAppData,
+        FOLDERID_ProgramData,
+        FOLDERID_ProgramFiles,
+        FOLDERID_ProgramFilesX86,
+        FOLDERID_Windows,
+        FOLDERID_System,
+        FOLDERID_SystemX86,
+    ]
+    .iter()
+    .filter_map(known_folder_path)
+    .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn resolve_temp_folders() -> Vec<PathBuf> {
+    use windows_sys::Win32::UI::Shell::{FOLDERID_LocalAppData, FOLDERID_Windows};
+
+    [FOLDERID_Windows, FOLDERID_LocalAppData]
+        .iter()
+        .filter_map(known_folder_path)
+        .map(|folder| folder.join("Temp"))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn resolve_user_folders() -> Option<(PathBuf, Vec<(UserFolder, PathBuf)>)> {
+    use windows_sys::Win32::UI::Shell::{
+        FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_LocalAppData, FOLDERID_Music,
+        FOLDERID_Pictures, FOLDERID_Profile, FOLDERID_Videos,
+    };
+
+    let home = known_folder_path(&FOLDERID_Profile)?;
+    let folders = [
+        (UserFolder::Documents, FOLDERID_Documents),
+        (UserFolder::Pictures, FOLDERID_Pictures),
+        (UserFolder::Videos, FOLDERID_Videos),
+        (UserFolder::Music, FOLDERID_Music),
+        (UserFolder::Downloads, FOLDERID_Downloads),
+        (UserFolder::Desktop, FOLDERID_Desktop),
+        (UserFolder::AppCaches, FOLDERID_LocalAppData),
+    ]
+    .iter()
+    .filter_map(|(folder, id)| Some((*folder, known_folder_path(id)?)))
+    .collect();
+    Some((home, folders))
+}
+
+#[cfg(target_os = "windows")]
+fn known_folder_path(id: &windows_sys::core::GUID) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use windows_sys::Win32::System::Com::CoTaskMemFree;
+    use windows_sys::Win32::UI::Shell::SHGetKnownFolderPath;
+
+    let mut raw: windows_sys::core::PWSTR = std::ptr::null_mut();
+    let hr = unsafe { SHGetKnownFolderPath(id, 0, std::ptr::null_mut(), &mut raw) };
+    let path = if hr >= 0 && !raw.is_null() {
+        let len = (0..).take_while(|i| unsafe { *raw.add(*i) } != 0).count();
+        let wide = unsafe { std::slice::from_raw_parts(raw, len) };
+        Some(PathBuf::from(OsString::from_wide(wide)))
+    } else {
+        None
+    };
+    // The buffer is allocated even when the call fails
+    unsafe { CoTaskMemFree(raw as *const _) };
+    path
+}
+
+/// The XDG user dirs from `user-dirs.dirs`, which xdg-user-dirs localizes and users may
+/// point anywhere
+#[cfg(all(unix, not(target_os = "macos")))]
+fn resolve_known_folders() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+    let mut folders = std::fs::read_to_string(config.join("user-dirs.dirs"))
+        .map(|contents| parse_user_dirs(&contents, &home))
+        .unwrap_or_default();
+    folders.extend([home.clone(), config, home.join(".local/share")]);
+    folders
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn resolve_temp_folders() -> Vec<PathBuf> {
+    vec![PathBuf::from("/tmp"), PathBuf::from("/var/tmp")]
+}
+
+/// The XDG user dirs by name, falling back to the English defaults xdg-user-dirs
+/// creates when `user-dirs.dirs` doesn't list one
+#[cfg(all(unix, not(target_os = "macos")))]
+fn resolve_user_folders() -> Option<(PathBuf, Vec<(UserFolder, PathBuf)>)> {
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+    let entries = std::fs::read_to_string(config.join("user-dirs.dirs"))
+        .map(|contents| parse_user_dir_entries(&contents, &home))
+        .unwrap_or_default();
+    let mut folders: Vec<(UserFolder, PathBuf)> = [
+        (UserFolder::Documents, "DOCUMENTS", "Documents"),
+        (UserFolder::Pictures, "PICTURES", "Pictures"),
+        (UserFolder::Videos, "VIDEOS", "Videos"),
+        (UserFolder::Music, "MUSIC", "Music"),
+        (UserFolder::Downloads, "DOWNLOAD", "Downloads"),
+        (UserFolder::Desktop, "DESKTOP", "Desktop"),
+    ]
+    .iter()
+    .map(|(folder, key, default)| {
+        let path = entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, path)| path.clone())
+            .unwrap_or_else(|| home.join(default));
+        (*folder, path)
+    })
+    .collect();
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".cache"));
+    folders.push((UserFolder::AppCaches, cache));
+    Some((home, folders))
+}
+
+/// macOS keeps the standard folders at fixed English paths and only localizes their
+/// display names, so these match what NSSearchPathForDirectoriesInDomains returns
+#[cfg(target_os = "macos")]
+fn resolve_known_folders() -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = ["/Applications", "/Library", "/System", "/Users"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        folders.extend(
+            ["Documents", "Pictures", "Movies", "Music", "Downloads", "Desktop", "Library"]
+                .iter()
+                .map(|name| home.join(name)),
+        );
+        folders.push(home);
+    }
+    folders
+}
+
+/// /tmp and /var/tmp are links into /private
+#[cfg(target_os = "macos")]
+fn resolve_temp_folders() -> Vec<PathBuf> {
+    ["/tmp", "/var/tmp", "/private/tmp", "/private/var/tmp"]
+        .iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn resolve_user_folders() -> Option<(PathBuf, Vec<(UserFolder, PathBuf)>)> {
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    let folders = [
+        (UserFolder::Documents, "Documents"),
+        (UserFolder::Pictures, "Pictures"),
+        (UserFolder::Videos, "Movies"),
+        (UserFolder::Music, "Music"),
+        (UserFolder::Downloads, "Downloads"),
+        (UserFolder::Desktop, "Desktop"),
+        (UserFolder::AppCaches, "Library/Caches"),
+    ]
+    .iter()
+    .map(|(folder, name)| (*folder, home.join(name)))
+    .collect();
+    Some((home, folders))
+}
+
+/// Parse lines such as `XDG_DOCUMENTS_DIR="$HOME/Dokumente"`. Entries pointing at the
+/// home dir itself mean the folder is disabled.
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+fn parse_user_dirs(contents: &str, home: &Path) -> Vec<PathBuf> {
+    parse_user_dir_entries(contents, home)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// The same, keeping each folder's name: `DOCUMENTS` for `XDG_DOCUMENTS_DIR`
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+fn parse_user_dir_entries(contents: &str, home: &Path) -> Vec<(String, PathBuf)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let name = key.trim().strip_prefix("XDG_")?;
+            let name = name.strip_suffix("_DIR").unwrap_or(name).to_string();
+            let value = value.trim().trim_matches('"');
+            let path = match value.strip_prefix("$HOME") {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None => PathBuf::from(value),
+            };
+            (path.is_absolute() && path != home).then_some((name, path))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_localized_user_dirs() {
+        let home = Path::new("/home/anna");
+        let contents = "# written by xdg-user-dirs-update\nXDG_DESKTOP_DIR=\"$HOME/Schreibtisch\"\nXDG_DOCUMENTS_DIR=\"/data/Dokumente\"\nXDG_PUBLICSHARE_DIR=\"$HOME/\"\n";
+        assert_eq!(
+            parse_user_dirs(contents, home),
+            vec![PathBuf::from("/home/anna/Schreibtisch"), PathBuf::from("/data/Dokumente")]
+        );
+        assert_eq!(parse_user_dir_entries(contents, home)[1].0, "DOCUMENTS");
+    }
+
+    #[test]
+    fn ancestors_of_known_folders_are_covered() {
+        let folder = Path::new("/home/anna/Dokumente");
+        assert!(is_same_or_ancestor(Path::new("/home/anna/Dokumente/"), folder));
+        assert!(is_same_or_ancestor(Path::new("/home"), folder));
+        assert!(!is_same_or_ancestor(Path::new("/home/anna/Dokumente/report.pdf"), folder));
+        assert!(!is_same_or_ancestor(Path::new("/home/ann"), folder));
+    }
+
+    #[test]
+    fn inside_means_strictly_below() {
+        let folder = Path::new("/var/tmp");
+        assert!(is_inside(Path::new("/var/tmp/setup/a.exe"), folder));
+        assert!(!is_inside(Path::new("/var/tmp"), folder));
+        assert!(!is_inside(Path::new("/var/tmpfiles/a"), folder));
+    }
+}
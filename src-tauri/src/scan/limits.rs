@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+use crate::scan::memory::active_scan_bytes;
+
+/// Files below this size are folded into their folder's "<small files>" entry once a
+/// scan reaches its memory cap
+pub const SMALL_FILE_BYTES: u64 = 1024 * 1024;
+/// Name of the entry that holds a folder's folded small files; `<` and `>` can't appear
+/// in Windows file names
+pub const SMALL_FILES_NAME: &str = "<small files>";
+/// Name of the entry that counts a folder's files at or above a scan's size ceiling
+pub const LARGE_FILES_NAME: &str = "<large files>";
+
+/// Caps on what one scan may use, for shared servers and low-end machines. None means
+/// no cap.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScanLimits {
+    /// Threads stat'ing files at once; 1 keeps the scan on a single thread
+    #[serde(default)]
+    pub max_threads: Option<u32>,
+    /// Estimated memory of the scan's node store, past which small files stop getting
+    /// nodes of their own
+    #[serde(default)]
+    pub max_memory_bytes: Option<u64>,
+    /// File and directory handles open at once, the walker's current directory
+    /// included
+    #[serde(default)]
+    pub max_open_handles: Option<u32>,
+}
+
+impl ScanLimits {
+    /// Worker threads a parallel stat may use out of `wanted`, at least one. Each worker
+    /// holds a handle while it stats, beside the one the walker keeps.
+    pub fn stat_threads(&self, wanted: usize) -> usize {
+        let by_threads = self.max_threads.map_or(wanted, |max| max as usize);
+        let by_handles = self.max_open_handles.map_or(wanted, |max| (max as usize).saturating_sub(1));
+        wanted.min(by_threads).min(by_handles).max(1)
+    }
+}
+
+/// A way a scan held back to stay within its limits
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LimitAction {
+    /// The parallel stat backend ran on fewer threads, or on one as the plain walker
+    ReducedThreads { threads: u32 },
+    /// Past `after_entries` tree entries, small files were counted into per-folder
+    /// "<small files>" entries instead of listed
+    AggregatedSmallFiles { after_entries: u64, files: u64, bytes: u64 },
+}
+
+/// Watches a scan's node store against `max_memory_bytes` and decides which files to
+/// fold once it is reached
+#[derive(Debug, Default)]
+pub struct MemoryCap {
+    max_bytes: Option<u64>,
+    folding_since: Option<u64>,
+    files: u64,
+    bytes: u64,
+}
+
+impl MemoryCap {
+    pub fn new(limits: &ScanLimits) -> Self {
+        Self {
+            max_bytes: limits.max_memory_bytes,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a file of `size` should be folded, given the entries stored so far.
+    /// Folding never stops once started, so every folder is treated alike from then on.
+    pub fn should_fold(&mut self, node_count: usize, size: u64) -> bool {
+        let Some(max_bytes) = self.max_bytes else {
+            return false;
+        };
+        if self.folding_since.is_none() && active_scan_bytes(node_count as u64) >= max_bytes {
+            self.folding_since = Some(node_count as u64);
+        }
+        self.folding_since.is_some() && size > 0 && size < SMALL_FILE_BYTES
+    }
+
+    pub fn record_folded(&mut self, size: u64) {
+        self.files += 1;
+        self.bytes += size;
+    }
+
+    pub fn action(&self) -> Option<LimitAction> {
+        self.folding_since.map(|after_entries| LimitAction::AggregatedSmallFiles {
+            after_entries,
+            files: self.files,
+            bytes: self.bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_threads_and_folds_small_files_past_the_memory_cap() {
+        let limits = ScanLimits {
+            max_threads: Some(6),
+            max_open_handles: Some(4),
+            ..ScanLimits::default()
+        };
+        assert_eq!(limits.stat_threads(8), 3);
+        assert_eq!(ScanLimits { max_threads: Some(0), ..limits }.stat_threads(8), 1);
+        assert_eq!(ScanLimits::default().stat_threads(8), 8);
+
+        let mut cap = MemoryCap::new(&ScanLimits {
+            max_memory_bytes: Some(active_scan_bytes(100)),
+            ..ScanLimits::default()
+        });
+        assert!(!cap.should_fold(99, 10));
+        assert!(cap.should_fold(100, 10));
+        assert!(!cap.should_fold(101, SMALL_FILE_BYTES));
+        cap.record_folded(10);
+        assert_eq!(
+            cap.action(),
+            Some(LimitAction::AggregatedSmallFiles { after_entries: 100, files: 1, bytes: 10 })
+        );
+        assert!(!MemoryCap::new(&ScanLimits::default()).should_fold(usize::MAX, 1));
+    }
+}
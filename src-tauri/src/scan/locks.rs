@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+use crate::scan::messages::{self, Message};
+
+/// A process holding a handle to a file that could not be deleted
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Structured detail for a path that failed to delete
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteFailure {
+    pub path: String,
+    pub message: String,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub lock_holders: Vec<LockHolder>,
+    /// `message` with the processes holding the path, for the frontend to translate
+    #[serde(default)]
+    pub localized: Option<Box<Message>>,
+    /// Left in place for being too large for the Recycle Bin; a permanent delete or a
+    /// quarantine would remove it
+    #[serde(default)]
+    pub wont_fit_trash: bool,
+}
+
+impl DeleteFailure {
+    /// Build a failure from an IO error, identifying lock holders for sharing violations
+    pub fn from_io(path: &Path, err: &io::Error) -> Self {
+        let locked = is_sharing_violation(err);
+        let lock_holders = if locked { find_lock_holders(path) } else { Vec::new() };
+        let message = err.to_string();
+        Self {
+            path: path.to_string_lossy().to_string(),
+            localized: Some(failure_message(&message, &lock_holders)),
+            message,
+            locked,
+            lock_holders,
+            wont_fit_trash: false,
+        }
+    }
+
+    /// Build a failure for a path the trash refused, identifying lock holders of a file
+    pub fn from_trash(path: &Path, message: String) -> Self {
+        let lock_holders = if path.is_file() { find_lock_holders(path) } else { Vec::new() };
+        Self {
+            path: path.to_string_lossy().to_string(),
+            localized: Some(failure_message(&message, &lock_holders)),
+            message,
+            locked: !lock_holders.is_empty(),
+            lock_holders,
+            wont_fit_trash: false,
+        }
+    }
+
+    pub fn other(path: &Path, message: String) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            localized: Some(failure_message(&message, &[])),
+            message,
+            locked: false,
+            lock_holders: Vec::new(),
+            wont_fit_trash: false,
+        }
+    }
+
+    pub fn wont_fit_trash(path: &Path, message: String) -> Self {
+        Self {
+            wont_fit_trash: true,
+            ..Self::other(path, message)
+        }
+    }
+}
+
+fn failure_message(message: &str, holders: &[LockHolder]) -> Box<Message> {
+    if holders.is_empty() {
+        return Box::new(Message::new(messages::DELETE_FAILED, [("error", message.to_string())]));
+    }
+    let holders: Vec<String> = holders.iter().map(|h| format!("{} (pid {})", h.name, h.pid)).collect();
+    Box::new(Message::new(
+        messages::DELETE_FAILED_IN_USE,
+        [("error", message.to_string()), ("holders", holders.join(", "))],
+    ))
+}
+
+/// ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION on Windows
+pub fn is_sharing_violation(err: &io::Error) -> bool {
+    cfg!(target_os = "windows") && matches!(err.raw_os_error(), Some(32) | Some(33))
+}
+
+/// List the processes that hold `path` open (Restart Manager, Windows only)
+#[cfg(target_os = "windows")]
+pub fn find_lock_holders(path: &Path) -> Vec<LockHolder> {
+    holder_infos(path)
+        .iter()
+        .map(|info| {
+            let len = info.strAppName.iter().position(|c| *c == 0).unwrap_or(info.strAppName.len());
+            LockHolder {
+                pid: info.Process.dwProcessId,
+                name: String::from_utf16_lossy(&info.strAppName[..len]),
+            }
+        })
+        .collect()
+}
+
+/// What the Restart Manager knows about each process holding `path`
+#[cfg(target_os = "windows")]
+fn holder_infos(path: &Path) -> Vec<windows_sys::Win32::System::RestartManager::RM_PROCESS_INFO> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS};
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut holders = Vec::new();
+    let mut session: u32 = 0;
+    let mut session_key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+
+    unsafe {
+        if RmStartSession(&mut session, 0, session_key.as_mut_ptr()) != ERROR_SUCCESS {
+            return holders;
+        }
+        let files = [wide.as_ptr()];
+        if RmRegisterResources(session, 1, files.as_ptr(), 0, null(), 0, null()) == ERROR_SUCCESS {
+            let mut needed: u32 = 0;
+            let mut count: u32 = 0;
+            let mut reasons: u32 = 0;
+            let status = RmGetList(session, &mut needed, &mut count, null_mut(), &mut reasons);
+            if status == ERROR_MORE_DATA && needed > 0 {
+                let mut infos: Vec<RM_PROCESS_INFO> = vec![std::mem::zeroed(); needed as usize];
+                count = needed;
+                if RmGetList(session, &mut needed, &mut count, infos.as_mut_ptr(), &mut reasons) == ERROR_SUCCESS {
+                    infos.truncate(count as usize);
+                    holders = infos;
+                }
+            }
+        }
+        RmEndSession(session);
+    }
+    holders
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn find_lock_holders(_path: &Path) -> Vec<LockHolder> {
+    Vec::new()
+}
+
+/// Ask the given processes holding `path` to close through the Restart Manager, the way
+/// an installer does: each is sent the end-session messages and may save its work or
+/// refuse. Nothing is terminated, so a refusal is reported as an error.
+#[cfg(target_os = "windows")]
+pub fn close_processes(path: &Path, pids: &[u32]) -> Result<(), String> {
+    use std::ptr::null;
+    use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmRegisterResources, RmShutdown, RmStartSession, CCH_RM_SESSION_KEY, RM_UNIQUE_PROCESS,
+    };
+
+    // Registering the processes themselves, with their start times, keeps the session to
+    // exactly these instances rather than everything holding the file
+    let processes: Vec<RM_UNIQUE_PROCESS> = holder_infos(path)
+        .iter()
+        .filter(|info| pids.contains(&info.Process.dwProcessId))
+        .map(|info| info.Process)
+        .collect();
+    if processes.is_empty() {
+        return Ok(());
+    }
+    let mut session: u32 = 0;
+    let mut session_key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    unsafe {
+        let status = RmStartSession(&mut session, 0, session_key.as_mut_ptr());
+        if status != ERROR_SUCCESS {
+            return Err(format!(
+                "Cannot start a Restart Manager session: {}",
+                io::Error::from_raw_os_error(status as i32)
+            ));
+        }
+        let mut status = RmRegisterResources(session, 0, null(), processes.len() as u32, processes.as_ptr(), 0, null());
+        if status == ERROR_SUCCESS {
+            // No RmForceShutdown: an application that won't close is left running
+            status = RmShutdown(session, 0, None);
+        }
+        RmEndSession(session);
+        if status != ERROR_SUCCESS {
+            return Err(format!(
+                "Not every process agreed to close: {}",
+                io::Error::from_raw_os_error(status as i32)
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn close_processes(_path: &Path, _pids: &[u32]) -> Result<(), String> {
+    Err("Closing lock holders is only supported on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_name_the_processes_holding_a_file() {
+        let holders = [LockHolder { pid: 42, name: "editor.exe".to_string() }];
+        let message = failure_message("in use", &holders);
+        assert_eq!(message.code, messages::DELETE_FAILED_IN_USE);
+        assert_eq!(message.params.get("holders").map(String::as_str), Some("editor.exe (pid 42)"));
+        assert_eq!(failure_message("denied", &[]).code, messages::DELETE_FAILED);
+
+        let failure = DeleteFailure::wont_fit_trash(Path::new("big.iso"), "too large".to_string());
+        assert!(failure.wont_fit_trash && !failure.locked && failure.lock_holders.is_empty());
+        assert_eq!(failure.localized.map(|message| message.code).as_deref(), Some(messages::DELETE_FAILED));
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::scan::model::{ScanResult, TreeNode};
+
+/// Rough cost of one entry while a scan is still walking: the node, its path twice
+/// (node and index), and the walker's bookkeeping
+const ACTIVE_ENTRY_BYTES: u64 = std::mem::size_of::<TreeNode>() as u64 + 192;
+
+/// Memory held for one finished scan
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanMemory {
+    pub scan_id: String,
+    pub root_path: String,
+    pub node_count: usize,
+    pub tree_bytes: u64,
+    pub result_bytes: u64,
+    /// Change-detection stamps and remembered duplicate groups
+    pub cache_bytes: u64,
+    /// Size of the tree's node table on disk when it was spilled; `tree_bytes` then
+    /// only counts the nodes changed or cached in memory
+    pub spilled_bytes: Option<u64>,
+    pub total_bytes: u64,
+}
+
+/// Estimate for a scan that is still running, from the entries it has visited
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActiveScanMemory {
+    pub scan_id: String,
+    pub visited_entries: u64,
+    pub estimated_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheMemory {
+    pub name: String,
+    pub bytes: u64,
+}
+
+/// What the backend holds in memory, largest scans first. Sizes are estimates of heap
+/// use; `process_bytes` is the resident size reported by the OS, which also covers the
+/// allocator's slack and the webview.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    pub process_bytes: Option<u64>,
+    pub scans: Vec<ScanMemory>,
+    pub active_scans: Vec<ActiveScanMemory>,
+    pub caches: Vec<CacheMemory>,
+    /// Sum of everything listed above
+    pub tracked_bytes: u64,
+}
+
+/// Heap estimate of a stored result, from its serialized size: its bulk is strings
+/// (paths, extensions) whose JSON form is about as long as their in-memory form
+pub fn result_bytes(result: &ScanResult) -> u64 {
+    serde_json::to_vec(result)
+        .map(|json| (json.len() + std::mem::size_of::<ScanResult>()) as u64)
+        .unwrap_or(0)
+}
+
+pub fn active_scan_bytes(visited_entries: u64) -> u64 {
+    visited_entries.saturating_mul(ACTIVE_ENTRY_BYTES)
+}
+
+/// Resident memory of this process
+pub fn process_resident_bytes() -> Option<u64> {
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    system.process(pid).map(|process| process.memory())
+}
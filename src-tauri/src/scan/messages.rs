@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::scan::rules::RuleSet;
+
+// Codes for the backend strings the frontend may translate. Each has an English
+// template in CATALOG whose `{name}` placeholders are filled from the message params.
+pub const SCAN_FAILED: &str = "scan.failed";
+pub const SCAN_READ_FAILED: &str = "scan.read_failed";
+pub const DELETE_FAILED: &str = "delete.failed";
+pub const DELETE_FAILED_IN_USE: &str = "delete.failed_in_use";
+pub const JUNK_PROTECTED: &str = "junk.protected_location";
+pub const JUNK_INSIDE_FOLDER: &str = "junk.inside_folder";
+pub const JUNK_SYSTEM_FILE: &str = "junk.system_junk_file";
+pub const JUNK_EDITOR_FILE: &str = "junk.editor_lock_or_backup";
+pub const JUNK_COPY_NAME: &str = "junk.copy_or_backup_name";
+pub const JUNK_NOT_MODIFIED: &str = "junk.not_modified";
+pub const JUNK_DUPLICATE: &str = "junk.duplicate_name_and_size";
+pub const JUNK_TEMPORARY_TYPE: &str = "junk.temporary_file_type";
+/// Prefix of the codes for rule reasons; the rest is the reason text in snake case
+pub const RULE_REASON_PREFIX: &str = "rule.";
+
+const CATALOG: &[(&str, &str)] = &[
+    (SCAN_FAILED, "{error}"),
+    (SCAN_READ_FAILED, "{error}"),
+    (DELETE_FAILED, "{error}"),
+    (DELETE_FAILED_IN_USE, "{error} (in use by {holders})"),
+    (JUNK_PROTECTED, "protected system location"),
+    (JUNK_INSIDE_FOLDER, "inside '{folder}' folder"),
+    (JUNK_SYSTEM_FILE, "system-generated junk file"),
+    (JUNK_EDITOR_FILE, "editor lock or backup file"),
+    (JUNK_COPY_NAME, "name looks like a copy or backup"),
+    (JUNK_NOT_MODIFIED, "not modified in {days} days"),
+    (JUNK_DUPLICATE, "same name and size exists elsewhere"),
+    (JUNK_TEMPORARY_TYPE, "temporary file type"),
+];
+
+/// A user-facing string from the backend: a stable code and the values it mentions,
+/// so the frontend can render it in the user's language, plus the English rendering
+/// for when it doesn't. Messages wrapping an OS error keep the OS text as a param.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Message {
+    pub code: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    pub text: String,
+}
+
+impl Message {
+    pub fn new<const N: usize>(code: &str, params: [(&str, String); N]) -> Self {
+        let params: BTreeMap<String, String> =
+            params.into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+        let template = CATALOG
+            .iter()
+            .find(|(known, _)| *known == code)
+            .map_or(code, |(_, template)| *template);
+        Self {
+            code: code.to_string(),
+            text: render(template, &params),
+            params,
+        }
+    }
+
+    /// The reason a safety rule gives. Reasons are rule pack data, so the code is
+    /// derived from the English text; the built-in pack's reasons are in the catalog.
+    pub fn rule_reason(reason: &str) -> Self {
+        Self {
+            code: rule_reason_code(reason),
+            params: BTreeMap::new(),
+            text: reason.to_string(),
+        }
+    }
+}
+
+/// Replace each `{name}` in `template` with its param; unknown names stay as written
+pub fn render(template: &str, params: &BTreeMap<String, String>) -> String {
+    let mut text = template.to_string();
+    for (key, value) in params {
+        text = text.replace(&format!("{{{}}}", key), value);
+    }
+    text
+}
+
+fn rule_reason_code(reason: &str) -> String {
+    let mut slug = String::with_capacity(reason.len());
+    for c in reason.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('_') && !slug.is_empty() {
+            slug.push('_');
+        }
+    }
+    format!("{}{}", RULE_REASON_PREFIX, slug.trim_end_matches('_'))
+}
+
+/// One entry of the catalog handed to translators and the frontend
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    pub code: String,
+    pub template: String,
+}
+
+/// Every code with its English template, including the reasons of the built-in rules
+pub fn catalog() -> Vec<MessageTemplate> {
+    let mut templates: Vec<MessageTemplate> = CATALOG
+        .iter()
+        .map(|(code, template)| MessageTemplate {
+            code: code.to_string(),
+            template: template.to_string(),
+        })
+        .collect();
+    let builtin = RuleSet::builtin();
+    let mut reasons: Vec<&str> = builtin.reasons().collect();
+    reasons.sort_unstable();
+    reasons.dedup();
+    templates.extend(reasons.into_iter().map(|reason| MessageTemplate {
+        code: rule_reason_code(reason),
+        template: reason.to_string(),
+    }));
+    templates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_english_from_the_catalog() {
+        let message = Message::new(JUNK_NOT_MODIFIED, [("days", "400".to_string())]);
+        assert_eq!(message.text, "not modified in 400 days");
+        assert_eq!(message.params.get("days").map(String::as_str), Some("400"));
+        assert_eq!(Message::rule_reason("Windows Update cache").code, "rule.windows_update_cache");
+
+        let catalog = catalog();
+        assert!(catalog.iter().any(|entry| entry.code == JUNK_INSIDE_FOLDER));
+        assert!(catalog.iter().any(|entry| entry.code.starts_with(RULE_REASON_PREFIX)));
+    }
+}
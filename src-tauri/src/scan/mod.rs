@@ -1,6 +1,84 @@
+pub mod acl;
+pub mod agent;
+pub mod analyzer;
+pub mod anonymize;
+pub mod api;
+pub mod appdata;
+pub mod archive;
+pub mod audit;
+pub mod backend;
+pub mod baseline;
+pub mod benchmark;
+pub mod bookmarks;
+pub mod browsers;
+pub mod bundle;
 pub mod commands;
+pub mod compress;
+pub mod confirm;
+pub mod cow;
+pub mod crashdumps;
+pub mod csc;
+pub mod dashboard;
 pub mod delete;
+pub mod deletetime;
+pub mod diskimage;
+pub mod dirtiming;
+pub mod documents;
+pub mod duplicates;
 pub mod engine;
+pub mod entropy;
+pub mod errors;
 pub mod events;
+pub mod executables;
+pub mod export;
+pub mod extstats;
+pub mod feasibility;
+pub mod filelist;
+pub mod growth;
+pub mod hashcache;
+pub mod hints;
+pub mod home;
+pub mod hooks;
+pub mod images;
+pub mod journal;
+pub mod junk;
+pub mod known_folders;
+pub mod limits;
+pub mod locks;
+pub mod memory;
+pub mod messages;
 pub mod model;
+pub mod notify;
+pub mod owners;
+pub mod pattern;
+pub mod policy;
+pub mod query;
+pub mod reconcile;
+pub mod recycle;
+pub mod report;
+pub mod roots;
+pub mod reuse;
+pub mod rules;
+pub mod runwindow;
+pub mod safemode;
+pub mod scandefaults;
+pub mod settings;
+pub mod stale;
+pub mod shaders;
+pub mod shadow;
+pub mod shell;
+pub mod similar;
+pub mod skips;
+pub mod spill;
 pub mod state;
+pub mod sysfiles;
+pub mod tags;
+pub mod throttle;
+pub mod tray;
+pub mod tree;
+pub mod vmdisks;
+pub mod volumes;
+pub mod watch;
+pub mod watchdog;
+pub mod winstores;
+pub mod winsxs;
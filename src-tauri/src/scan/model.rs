@@ -1,4 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::scan::backend::ScanBackend;
+use crate::scan::dirtiming::DirTiming;
+use crate::scan::errors::ErrorGroup;
+use crate::scan::filelist::FileListFormat;
+use crate::scan::hints::SizeHints;
+use crate::scan::limits::{LimitAction, ScanLimits};
+use crate::scan::reuse::ResumeSeed;
+use crate::scan::safemode::UnreadableRegion;
 
 pub type NodeId = u64;
 
@@ -12,6 +22,44 @@ pub struct ScanOptions {
     pub max_depth: Option<u32>,
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+    #[serde(default)]
+    pub analyzers: Vec<AnalyzerConfig>,
+    /// List every directory before stat'ing any file, so the folder structure shows
+    /// within seconds and sizes fill in during a second pass
+    #[serde(default)]
+    pub two_phase: bool,
+    /// Earlier directory sizes of the root, filled in by the backend, that order the
+    /// walk largest-first
+    #[serde(skip)]
+    pub size_hints: Option<Arc<SizeHints>>,
+    #[serde(default)]
+    pub backend: ScanBackend,
+    /// The canceled scan being resumed, whose finished folders are taken over
+    /// instead of walked again
+    #[serde(skip)]
+    pub resume: Option<Arc<ResumeSeed>>,
+    /// For failing drives: reads run under a timeout, folders that keep timing out are
+    /// given up on, and everything unreadable is listed in the result
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// Build the tree from this newline-delimited list of sizes and paths instead of
+    /// walking the disk; the scan root need not exist
+    #[serde(default)]
+    pub file_list: Option<String>,
+    #[serde(default)]
+    pub file_list_format: FileListFormat,
+    /// Caps on threads, node-store memory, and open handles; the walk degrades to stay
+    /// within them
+    #[serde(default)]
+    pub limits: ScanLimits,
+    /// Files of this size or more are only counted, into a "<large files>" entry per
+    /// folder, for light clutter-cleanup scans that skip indexing big media
+    #[serde(default)]
+    pub size_ceiling: Option<u64>,
+    /// Time each directory's listing and stats, to find what made a scan slow; the
+    /// slowest are returned in the result
+    #[serde(default)]
+    pub profile_dirs: bool,
 }
 
 impl Default for ScanOptions {
@@ -21,10 +69,81 @@ impl Default for ScanOptions {
             one_file_system: false,
             max_depth: None,
             exclude_patterns: Vec::new(),
+            analyzers: Vec::new(),
+            two_phase: false,
+            size_hints: None,
+            backend: ScanBackend::Auto,
+            resume: None,
+            safe_mode: false,
+            file_list: None,
+            file_list_format: FileListFormat::Plain,
+            limits: ScanLimits::default(),
+            size_ceiling: None,
+            profile_dirs: false,
         }
     }
 }
 
+/// Optional analyzers that can be enabled per scan (extension stats always run)
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalyzerConfig {
+    TopFiles {
+        #[serde(default = "default_top_files_limit")]
+        limit: usize,
+    },
+    AgeHistogram,
+    CategoryStats,
+    /// Perceptual-hash near-duplicate detection for photos up to `max_bytes`
+    SimilarImages {
+        #[serde(default = "default_image_size_cap")]
+        max_bytes: u64,
+        #[serde(default = "default_image_distance")]
+        max_distance: u32,
+    },
+    /// Bytes and files per file owner (uid or SID), for shared volumes
+    OwnerUsage,
+    /// Files of at least `min_bytes` that look like random data with no known header
+    HighEntropy {
+        #[serde(default = "default_entropy_min_bytes")]
+        min_bytes: u64,
+    },
+    /// Executables and installers outside install locations, with signer info
+    Executables,
+    /// Virtual machine disk images with their virtual and allocated sizes
+    VmDisks,
+    /// Chrome, Edge, and Firefox profile storage split into cache, site data, and history
+    BrowserProfiles,
+    /// Per-user application data attributed to applications, with its cache share
+    AppData,
+    /// GPU driver and Steam shader caches, sized per source
+    ShaderCaches,
+}
+
+impl AnalyzerConfig {
+    /// Whether the analyzer reads platform metadata (owner ids, allocated blocks) that
+    /// only a per-file stat provides
+    pub fn needs_metadata(&self) -> bool {
+        matches!(self, AnalyzerConfig::OwnerUsage | AnalyzerConfig::VmDisks)
+    }
+}
+
+fn default_top_files_limit() -> usize {
+    crate::scan::analyzer::DEFAULT_TOP_FILES_LIMIT
+}
+
+fn default_image_size_cap() -> u64 {
+    crate::scan::images::DEFAULT_IMAGE_SIZE_CAP
+}
+
+fn default_image_distance() -> u32 {
+    crate::scan::images::DEFAULT_IMAGE_DISTANCE
+}
+
+fn default_entropy_min_bytes() -> u64 {
+    crate::scan::entropy::DEFAULT_ENTROPY_MIN_BYTES
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum NodeKind {
@@ -41,6 +160,13 @@ pub struct TreeNode {
     pub kind: NodeKind,
     pub size_bytes: u64,
     pub file_ext: Option<String>,
+    /// Last modification time in seconds since the Unix epoch (files only)
+    #[serde(default)]
+    pub modified: Option<u64>,
+    /// Paging/hibernation/swap file, or a folder's folded small files, shown for space
+    /// accounting; never deletable
+    #[serde(default)]
+    pub informational: bool,
     pub children: Vec<NodeId>,
 }
 
@@ -53,6 +179,8 @@ pub struct TreeNodeDelta {
     pub kind: NodeKind,
     pub size_bytes: u64,
     pub file_ext: Option<String>,
+    #[serde(default)]
+    pub informational: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -62,6 +190,234 @@ pub struct ExtensionStat {
     pub count: u64,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TopFile {
+    pub id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeBucket {
+    pub label: String,
+    pub max_age_secs: Option<u64>,
+    pub bytes: u64,
+    pub count: u64,
+}
+
+impl AgeBucket {
+    pub fn empty(label: &str, max_age_secs: Option<u64>) -> Self {
+        Self {
+            label: label.to_string(),
+            max_age_secs,
+            bytes: 0,
+            count: 0,
+        }
+    }
+}
+
+/// Files whose size falls in [min_bytes, max_bytes)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub label: String,
+    pub min_bytes: u64,
+    /// None for the open-ended top bucket
+    pub max_bytes: Option<u64>,
+    pub bytes: u64,
+    pub count: u64,
+}
+
+/// Age buckets for everything below one directory
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DirectoryAgeHistogram {
+    pub node_id: NodeId,
+    pub path: String,
+    pub buckets: Vec<AgeBucket>,
+}
+
+/// Age histogram of a node plus one per child directory, for the age heatmap
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeHistogramView {
+    pub node_id: NodeId,
+    pub buckets: Vec<AgeBucket>,
+    pub directories: Vec<DirectoryAgeHistogram>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CategoryStat {
+    pub category: String,
+    pub bytes: u64,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimilarImage {
+    pub id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+    pub width: u32,
+    pub height: u32,
+    pub modified: Option<u64>,
+    /// JPEG data URL preview
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignatureInfo {
+    /// Signed and the signature verifies
+    pub signed: bool,
+    /// Platform status in snake case, e.g. "valid", "not_signed", "hash_mismatch"
+    pub status: String,
+    pub publisher: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExecutableFile {
+    pub id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+    pub ext: String,
+    pub modified: Option<u64>,
+    /// Inside a Temp or cache folder
+    pub in_temp: bool,
+    /// None when the signature wasn't checked or the platform has none
+    pub signature: Option<SignatureInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VmDiskImage {
+    pub id: NodeId,
+    pub path: String,
+    pub format: String,
+    /// Program that most likely owns the image, e.g. "WSL" or "VirtualBox"
+    pub hypervisor: String,
+    pub file_bytes: u64,
+    /// Space taken on disk; below `file_bytes` for sparse or compressed images
+    pub allocated_bytes: u64,
+    /// Capacity the guest sees, when the header could be read
+    pub virtual_bytes: Option<u64>,
+    pub modified: Option<u64>,
+    /// Whether `compact_vm_disk` can shrink it on this platform
+    pub compactable: bool,
+    pub compaction_hint: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserStorageKind {
+    Cache,
+    ServiceWorker,
+    IndexedDb,
+    History,
+    /// Local and session storage and other per-site databases
+    SiteData,
+    Extensions,
+    Other,
+}
+
+impl BrowserStorageKind {
+    /// Caches the browser rebuilds on demand; close the browser before clearing them
+    pub fn safe_to_clear(self) -> bool {
+        matches!(self, BrowserStorageKind::Cache | BrowserStorageKind::ServiceWorker)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BrowserStorage {
+    pub kind: BrowserStorageKind,
+    pub bytes: u64,
+    pub files: u64,
+    pub safe_to_clear: bool,
+    /// Folders holding this kind of storage, for clearing it
+    pub paths: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BrowserProfileUsage {
+    pub browser: String,
+    /// Profile folder name, or "(shared)" for data beside Chromium's profiles
+    pub profile: String,
+    /// Profile folders; Firefox keeps its cache apart from the rest of the profile
+    pub paths: Vec<String>,
+    pub total_bytes: u64,
+    pub clearable_bytes: u64,
+    pub storage: Vec<BrowserStorage>,
+}
+
+/// One application's per-user data across AppData, ~/Library, and the XDG folders
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppDataUsage {
+    pub app: String,
+    /// The application's data folders
+    pub paths: Vec<String>,
+    pub total_bytes: u64,
+    pub files: u64,
+    /// Bytes in cache, temp, and log folders the application rebuilds
+    pub cache_bytes: u64,
+    pub cache_fraction: f64,
+}
+
+/// One shader cache folder; drivers and games regenerate them, so all of it can go
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShaderCacheUsage {
+    /// e.g. "NVIDIA DXCache" or "Steam game 1091500"
+    pub source: String,
+    pub path: String,
+    /// Set for Steam's per-game caches
+    pub steam_app_id: Option<u32>,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighEntropyFile {
+    pub id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+    /// Lowest bits-per-byte entropy among the sampled blocks
+    pub entropy: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OwnerUsage {
+    /// Numeric uid or SID string; "unknown" when the owner couldn't be read
+    pub owner_id: String,
+    /// Account name, when the id resolves on this machine
+    pub name: Option<String>,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimilarImageGroup {
+    pub images: Vec<SimilarImage>,
+    pub keeper_id: NodeId,
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalyzerReport {
+    ExtensionStats { stats: Vec<ExtensionStat> },
+    TopFiles { files: Vec<TopFile> },
+    AgeHistogram {
+        buckets: Vec<AgeBucket>,
+        /// One histogram per top-level directory of the scan
+        #[serde(default)]
+        directories: Vec<DirectoryAgeHistogram>,
+    },
+    CategoryStats { categories: Vec<CategoryStat> },
+    SimilarImages { groups: Vec<SimilarImageGroup> },
+    OwnerUsage { owners: Vec<OwnerUsage> },
+    SizeDistribution { buckets: Vec<SizeBucket> },
+    HighEntropy { files: Vec<HighEntropyFile> },
+    Executables { files: Vec<ExecutableFile> },
+    VmDisks { disks: Vec<VmDiskImage> },
+    BrowserProfiles { profiles: Vec<BrowserProfileUsage> },
+    AppData { apps: Vec<AppDataUsage> },
+    ShaderCaches { caches: Vec<ShaderCacheUsage> },
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ScanResult {
     pub scan_id: String,
@@ -70,6 +426,77 @@ pub struct ScanResult {
     pub total_files: u64,
     pub total_dirs: u64,
     pub extension_stats: Vec<ExtensionStat>,
+    /// Bytes and file counts by file size
+    #[serde(default)]
+    pub size_distribution: Vec<SizeBucket>,
+    #[serde(default)]
+    pub analyzer_reports: Vec<AnalyzerReport>,
+    /// Directories excluded by the built-in skip list
+    #[serde(default)]
+    pub skipped_paths: Vec<String>,
+    /// Paths the walker could not read (usually access denied)
+    #[serde(default)]
+    pub error_paths: Vec<String>,
+    /// Everything left out of the scan, counted by reason
+    #[serde(default)]
+    pub skipped: Vec<SkipGroup>,
+    /// Read errors grouped by kind and folder; `error_paths` lists every failing path
+    #[serde(default)]
+    pub error_groups: Vec<ErrorGroup>,
+    /// Other names the root was requested under, such as a mapped drive letter for
+    /// a UNC share; the root node holds the canonical path
+    #[serde(default)]
+    pub root_aliases: Vec<String>,
+    /// When the walk started, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub started_at: u64,
+    #[serde(default)]
+    pub options: ScanOptions,
+    /// The stored scan this result was answered from instead of walking
+    #[serde(default)]
+    pub reused_from: Option<String>,
+    /// False for a canceled scan kept so what it found can be browsed
+    #[serde(default = "default_complete")]
+    pub complete: bool,
+    /// Folders a canceled walk had not finished reading; every other folder in the
+    /// tree was read completely
+    #[serde(default)]
+    pub incomplete_dirs: Vec<String>,
+    /// Files and folders a safe-mode scan timed out on or failed to read
+    #[serde(default)]
+    pub unreadable_regions: Vec<UnreadableRegion>,
+    /// What the walk gave up to stay within the scan's limits
+    #[serde(default)]
+    pub limit_actions: Vec<LimitAction>,
+    /// Directories that took longest to read, slowest first, when the scan was profiled
+    #[serde(default)]
+    pub slow_dirs: Vec<DirTiming>,
+}
+
+fn default_complete() -> bool {
+    true
+}
+
+/// Why an entry is missing from a scan
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// On the built-in list of system and heavy folders
+    SystemSkip,
+    /// Matched one of the scan's exclude patterns
+    ExcludedPattern,
+    /// Could not be read
+    Permission,
+    /// Timed out or failed to read during a safe-mode scan
+    Unreadable,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkipGroup {
+    pub reason: SkipReason,
+    pub count: u64,
+    /// The first few paths skipped for this reason
+    pub sample: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -78,11 +505,60 @@ pub struct ScanSummary {
     pub total_files: u64,
     pub total_dirs: u64,
     pub extension_stats: Vec<ExtensionStat>,
+    #[serde(default)]
+    pub size_distribution: Vec<SizeBucket>,
+}
+
+/// How a requested scan root relates to the root of a scan already held
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RootOverlap {
+    /// Both name the same location, possibly through different drive letters or shares
+    Same,
+    /// The requested root contains the existing scan's root
+    Contains,
+    /// The requested root lies inside the existing scan's root
+    Within,
+}
+
+/// A stored scan covering part of a requested root
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanOverlap {
+    pub scan_id: String,
+    pub root_path: String,
+    pub relation: RootOverlap,
+}
+
+/// What a new scan does about scans of overlapping roots that are still running
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OverlapDecision {
+    /// Nothing running overlaps; the walk starts right away
+    #[default]
+    Run,
+    /// A running scan already covers this root, so the walk waits for it to finish
+    /// rather than reading the same folders at the same time
+    Queued { behind: String, root_path: String },
+    /// Running scans cover folders inside this root; those folders are left out of
+    /// this scan and can be browsed in the scans listed
+    CarvedOut {
+        scans: Vec<ScanOverlap>,
+        excluded_paths: Vec<String>,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ScanHandle {
     pub scan_id: String,
+    /// Stored scans covering part of the same location
+    #[serde(default)]
+    pub overlaps: Vec<ScanOverlap>,
+    /// Set when the scan was answered from this stored scan
+    #[serde(default)]
+    pub reused_from: Option<String>,
+    /// How the scan gets along with running scans of overlapping roots
+    #[serde(default)]
+    pub decision: OverlapDecision,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -91,4 +567,27 @@ pub struct RootEntry {
     pub path: String,
     pub total_bytes: u64,
     pub available_bytes: u64,
+    #[serde(default)]
+    pub file_system: String,
+    /// Btrfs/ZFS volumes, whose logical sizes can differ widely from real usage
+    #[serde(default)]
+    pub copy_on_write: bool,
+    #[serde(default)]
+    pub is_removable: bool,
+    #[serde(default)]
+    pub is_network: bool,
+    #[serde(default)]
+    pub is_read_only: bool,
+    /// User-facing volume label, when one is set
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Device path or drive letter backing the volume
+    #[serde(default)]
+    pub device: String,
+    /// "hdd", "ssd" or "unknown"
+    #[serde(default)]
+    pub disk_kind: String,
+    /// BitLocker / dm-crypt status; None when it can't be determined
+    #[serde(default)]
+    pub encrypted: Option<bool>,
 }
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::scan::agent::format_bytes;
+use crate::scan::state::AppState;
+
+const MAIN_WINDOW: &str = "main";
+/// Deletes running at least this long count as background work worth a notification
+pub const LONG_DELETE: Duration = Duration::from_secs(10);
+
+/// Which native notifications to show; all on by default
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NotificationSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub scan_finished: bool,
+    #[serde(default = "default_true")]
+    pub scheduled_scan: bool,
+    #[serde(default = "default_true")]
+    pub background_delete: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scan_finished: true,
+            scheduled_scan: true,
+            background_delete: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotificationKind {
+    ScanFinished,
+    ScheduledScan,
+    BackgroundDelete,
+}
+
+impl NotificationSettings {
+    pub fn allows(&self, kind: NotificationKind) -> bool {
+        self.enabled
+            && match kind {
+                NotificationKind::ScanFinished => self.scan_finished,
+                NotificationKind::ScheduledScan => self.scheduled_scan,
+                NotificationKind::BackgroundDelete => self.background_delete,
+            }
+    }
+}
+
+/// Show a native notification if the settings allow it. Manual scans only notify while
+/// the window is in the background, since the result is already on screen otherwise.
+pub fn notify(app: &AppHandle, state: &AppState, kind: NotificationKind, title: &str, body: &str) {
+    if !state.settings().notifications.allows(kind) {
+        return;
+    }
+    if kind == NotificationKind::ScanFinished && main_window_focused(app) {
+        return;
+    }
+    let _ = app.notification().builder().title(title).body(body).show();
+}
+
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window(MAIN_WINDOW)
+        .is_some_and(|window| window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false))
+}
+
+pub fn scan_finished_body(root_path: &str, total_bytes: u64, total_files: u64) -> String {
+    format!("{}: {} in {} files", root_path, format_bytes(total_bytes), total_files)
+}
+
+pub fn delete_finished_body(bytes_freed: u64, items: usize, failed: usize) -> String {
+    match failed {
+        0 => format!("Freed {} from {} items", format_bytes(bytes_freed), items),
+        _ => format!(
+            "Freed {} from {} items; {} could not be deleted",
+            format_bytes(bytes_freed),
+            items - failed.min(items),
+            failed
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_event_toggles_respect_the_master_switch() {
+        let mut settings: NotificationSettings = serde_json::from_str("{}").expect("defaults");
+        assert!(settings.allows(NotificationKind::BackgroundDelete));
+        settings.scan_finished = false;
+        assert!(!settings.allows(NotificationKind::ScanFinished));
+        assert!(settings.allows(NotificationKind::ScheduledScan));
+        settings.enabled = false;
+        assert!(!settings.allows(NotificationKind::ScheduledScan));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, OwnerUsage};
+
+/// Raw owner identity as read during the walk: a uid on Unix, SID bytes on Windows.
+/// Names are only looked up once per distinct owner when the scan finishes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum OwnerKey {
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    Uid(u32),
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    Sid(Vec<u8>),
+    Unknown,
+}
+
+/// Totals bytes and files per owner. Opt-in because reading owners costs an extra
+/// security query per file on Windows.
+#[derive(Default)]
+pub struct OwnerUsageAnalyzer {
+    usage: HashMap<OwnerKey, (u64, u64)>,
+}
+
+impl Analyzer for OwnerUsageAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let key = owner_key(file).unwrap_or(OwnerKey::Unknown);
+        let entry = self.usage.entry(key).or_default();
+        entry.0 = entry.0.saturating_add(file.node.size_bytes);
+        entry.1 = entry.1.saturating_add(1);
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut owners: Vec<OwnerUsage> = self
+            .usage
+            .into_iter()
+            .map(|(key, (bytes, files))| {
+                let (owner_id, name) = describe_owner(&key);
+                OwnerUsage {
+                    owner_id,
+                    name,
+                    bytes,
+                    files,
+                }
+            })
+            .collect();
+        owners.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.owner_id.cmp(&b.owner_id)));
+        AnalyzerReport::OwnerUsage { owners }
+    }
+}
+
+/// Owner names resolved so far, shared across scans since accounts rarely change
+fn name_cache() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The printable owner id and its account name, if it can be resolved
+fn describe_owner(key: &OwnerKey) -> (String, Option<String>) {
+    let owner_id = match key {
+        OwnerKey::Uid(uid) => uid.to_string(),
+        OwnerKey::Sid(sid) => sid_string(sid).unwrap_or_else(|| "unknown".to_string()),
+        OwnerKey::Unknown => return ("unknown".to_string(), None),
+    };
+    if let Some(name) = name_cache().lock().ok().and_then(|cache| cache.get(&owner_id).cloned()) {
+        return (owner_id, name);
+    }
+    let name = match key {
+        OwnerKey::Uid(uid) => lookup_uid(*uid),
+        OwnerKey::Sid(sid) => lookup_sid(sid),
+        OwnerKey::Unknown => None,
+    };
+    if let Ok(mut cache) = name_cache().lock() {
+        cache.insert(owner_id.clone(), name.clone());
+    }
+    (owner_id, name)
+}
+
+#[cfg(unix)]
+fn owner_key(file: &FileVisit<'_>) -> Option<OwnerKey> {
+    use std::os::unix::fs::MetadataExt;
+    file.metadata.map(|m| OwnerKey::Uid(m.uid()))
+}
+
+#[cfg(target_os = "windows")]
+fn owner_key(file: &FileVisit<'_>) -> Option<OwnerKey> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows_sys::Win32::Security::{GetLengthSid, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID};
+
+    let wide: Vec<u16> = std::ffi::OsStr::new(&file.node.path).encode_wide().chain(Some(0)).collect();
+    let mut owner: PSID = std::ptr::null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            &mut owner,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut descriptor,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    // The owner SID points into the descriptor, so copy it out before freeing
+    let sid = (!owner.is_null()).then(|| {
+        let len = unsafe { GetLengthSid(owner) } as usize;
+        unsafe { std::slice::from_raw_parts(owner as *const u8, len) }.to_vec()
+    });
+    unsafe { LocalFree(descriptor as _) };
+    sid.map(OwnerKey::Sid)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn owner_key(_file: &FileVisit<'_>) -> Option<OwnerKey> {
+    None
+}
+
+/// Look the uid up in /etc/passwd. Directory-service accounts that aren't listed there
+/// keep their numeric id.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn lookup_uid(uid: u32) -> Option<String> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    parse_passwd(&passwd).remove(&uid)
+}
+
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn parse_passwd(contents: &str) -> HashMap<u32, String> {
+    contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn sid_string(sid: &[u8]) -> Option<String> {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::ConvertSidToStringSidW;
+
+    let mut raw: windows_sys::core::PWSTR = std::ptr::null_mut();
+    let ok = unsafe { ConvertSidToStringSidW(sid.as_ptr() as _, &mut raw) };
+    if ok == 0 || raw.is_null() {
+        return None;
+    }
+    let len = (0..).take_while(|i| unsafe { *raw.add(*i) } != 0).count();
+    let text = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(raw, len) });
+    unsafe { LocalFree(raw as _) };
+    Some(text)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn sid_string(_sid: &[u8]) -> Option<String> {
+    None
+}
+
+/// DOMAIN\user for a SID, or None for deleted accounts and unreachable domains
+#[cfg(target_os = "windows")]
+fn lookup_sid(sid: &[u8]) -> Option<String> {
+    use windows_sys::Win32::Security::{LookupAccountSidW, SID_NAME_USE};
+
+    let mut name = [0u16; 256];
+    let mut domain = [0u16; 256];
+    let mut name_len = name.len() as u32;
+    let mut domain_len = domain.len() as u32;
+    let mut use_kind: SID_NAME_USE = 0;
+    let ok = unsafe {
+        LookupAccountSidW(
+            std::ptr::null(),
+            sid.as_ptr() as _,
+            name.as_mut_ptr(),
+            &mut name_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            &mut use_kind,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    let name = String::from_utf16_lossy(&name[..name_len as usize]);
+    let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+    Some(if domain.is_empty() { name } else { format!("{}\\{}", domain, name) })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn lookup_sid(_sid: &[u8]) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_passwd_entries_and_skips_comments() {
+        let passwd = "# local users\nroot:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/zsh\nbroken\n";
+        let users = parse_passwd(passwd);
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(users.get(&1000).map(String::as_str), Some("alice"));
+        assert_eq!(users.len(), 2);
+    }
+}
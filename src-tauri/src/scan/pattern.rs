@@ -0,0 +1,231 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::scan::confirm::ConfirmationToken;
+use crate::scan::delete::{get_safety_level, DeleteResult, SafetyLevel};
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::rules::Classifier;
+use crate::scan::tree::ScanTree;
+
+/// A node of a scan matched by a path pattern
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PatternMatch {
+    pub id: NodeId,
+    pub path: String,
+    pub kind: NodeKind,
+    pub size_bytes: u64,
+    /// The strictest level of the node and everything below it
+    pub safety: SafetyLevel,
+    /// The entry inside a matched folder that set `safety`, when not the folder itself
+    #[serde(default)]
+    pub held_by: Option<String>,
+}
+
+/// What a pattern delete matched and, unless it was a dry run, what it removed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PatternDelete {
+    pub pattern: String,
+    pub safety_floor: SafetyLevel,
+    pub dry_run: bool,
+    /// Matches at or below the floor, largest first
+    pub matches: Vec<PatternMatch>,
+    pub matched_bytes: u64,
+    /// Matches left alone for being above the floor or protected
+    pub skipped_count: u64,
+    pub skipped_bytes: u64,
+    /// On a dry run, a token covering the ConfirmRequired matches for the real run
+    #[serde(default)]
+    pub confirmation: Option<ConfirmationToken>,
+    pub result: Option<DeleteResult>,
+}
+
+/// A glob in .gitignore syntax, relative to a scan's root and case-insensitive:
+/// `Thumbs.db` matches at any depth, `**/*.tmp` any .tmp file, `cache/` folders only
+pub struct PathPattern {
+    matcher: Gitignore,
+}
+
+impl PathPattern {
+    pub fn new(root: &str, pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.starts_with('!') || pattern.starts_with('#') {
+            return Err(format!("Not a usable pattern: {}", pattern));
+        }
+        let mut builder = GitignoreBuilder::new(root);
+        builder.case_insensitive(true).map_err(|e| e.to_string())?;
+        builder.add_line(None, pattern).map_err(|e| e.to_string())?;
+        let matcher = builder.build().map_err(|e| e.to_string())?;
+        Ok(Self { matcher })
+    }
+
+    fn matches(&self, node: &TreeNode) -> bool {
+        self.matcher.matched(&node.path, node.kind == NodeKind::Dir).is_ignore()
+    }
+}
+
+/// Nodes below the root of `tree` matching `pattern`. A matching folder stands for
+/// everything inside it, so nothing below one is listed.
+pub fn find_matches<'a>(tree: &'a ScanTree, pattern: &PathPattern) -> Vec<&'a TreeNode> {
+    let Some(root) = tree.root() else {
+        return Vec::new();
+    };
+    let mut matches = Vec::new();
+    let mut stack: Vec<NodeId> = root.children.clone();
+    while let Some(id) = stack.pop() {
+        let Some(node) = tree.get(id) else {
+            continue;
+        };
+        if pattern.matches(node) {
+            matches.push(node);
+        } else {
+            stack.extend(node.children.iter().copied());
+        }
+    }
+    matches
+}
+
+/// Paths of everything below `node` in `tree`, which goes with it when it is deleted
+pub fn descendant_paths(tree: &ScanTree, node: &TreeNode) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut stack: Vec<NodeId> = node.children.clone();
+    while let Some(id) = stack.pop() {
+        if let Some(child) = tree.get(id) {
+            paths.push(child.path.clone());
+            stack.extend(child.children.iter().copied());
+        }
+    }
+    paths
+}
+
+/// Classify matched nodes, each with the paths below it, which reads the filesystem and
+/// rule packs, and split them into those at or below `floor`, largest first, and those
+/// above it. A folder takes the strictest level found inside it, since deleting it
+/// deletes all of that; protected paths are always above the floor. Matches inside
+/// another matched folder are dropped, as that folder already covers them.
+pub fn classify_matches(
+    matches: &[(TreeNode, Vec<String>)],
+    floor: &SafetyLevel,
+    classifier: &Classifier,
+) -> (Vec<PatternMatch>, Vec<PatternMatch>) {
+    let matched: HashSet<&str> = matches.iter().map(|(node, _)| node.path.as_str()).collect();
+    let (mut within, above): (Vec<PatternMatch>, Vec<PatternMatch>) = matches
+        .iter()
+        .filter(|(node, _)| {
+            !Path::new(&node.path)
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| matched.contains(ancestor.to_string_lossy().as_ref()))
+        })
+        .map(|(node, descendants)| {
+            let mut safety = get_safety_level(Path::new(&node.path), classifier);
+            let mut held_by = None;
+            for path in descendants {
+                if safety == SafetyLevel::Protected {
+                    break;
+                }
+                let level = get_safety_level(Path::new(path), classifier);
+                if rank(&level) > rank(&safety) {
+                    safety = level;
+                    held_by = Some(path.clone());
+                }
+            }
+            PatternMatch {
+                id: node.id,
+                path: node.path.clone(),
+                kind: node.kind,
+                size_bytes: node.size_bytes,
+                safety,
+                held_by,
+            }
+        })
+        .partition(|found| found.safety != SafetyLevel::Protected && rank(&found.safety) <= rank(floor));
+    within.sort_by_key(|found| std::cmp::Reverse(found.size_bytes));
+    (within, above)
+}
+
+/// Order of the levels from safest to delete to never deleted
+fn rank(level: &SafetyLevel) -> u8 {
+    match level {
+        SafetyLevel::AutoDelete => 0,
+        SafetyLevel::ReclaimableBuild => 1,
+        SafetyLevel::ConfirmRequired => 2,
+        SafetyLevel::Protected => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: id,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn matches_globs_below_the_root_without_nesting() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/data", NodeKind::Dir, vec![2, 3, 5]),
+                node(2, Some(1), "/data/a.TMP", NodeKind::File, vec![]),
+                node(3, Some(1), "/data/photos", NodeKind::Dir, vec![4, 6]),
+                node(4, Some(3), "/data/photos/Thumbs.db", NodeKind::File, vec![]),
+                node(5, Some(1), "/data/cache", NodeKind::Dir, vec![7]),
+                node(6, Some(3), "/data/photos/b.tmp", NodeKind::File, vec![]),
+                node(7, Some(5), "/data/cache/c.tmp", NodeKind::File, vec![]),
+            ],
+            false,
+        );
+        let paths = |pattern: &str| {
+            let pattern = PathPattern::new("/data", pattern).expect("pattern");
+            let mut paths: Vec<String> = find_matches(&tree, &pattern).iter().map(|n| n.path.clone()).collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(paths("**/*.tmp"), ["/data/a.TMP", "/data/cache/c.tmp", "/data/photos/b.tmp"]);
+        assert_eq!(paths("thumbs.db"), ["/data/photos/Thumbs.db"]);
+        assert_eq!(paths("cache/"), ["/data/cache"]);
+        assert!(PathPattern::new("/data", "!keep.tmp").is_err());
+    }
+
+    #[test]
+    fn folders_take_the_strictest_level_inside_them() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let cache = temp.path().join(".cache");
+        std::fs::create_dir(&cache).expect("mkdir");
+        std::fs::write(cache.join("report.pdf"), b"pdf").expect("write");
+        std::fs::write(cache.join("x.log"), b"log").expect("write");
+        let path = |path: &Path| path.to_string_lossy().to_string();
+        let folder = node(10, None, &path(&cache), NodeKind::Dir, vec![]);
+        let nested = node(3, Some(10), &path(&cache.join("x.log")), NodeKind::File, vec![]);
+        let matches = vec![
+            (folder, vec![path(&cache.join("report.pdf")), path(&cache.join("x.log"))]),
+            (nested, Vec::new()),
+        ];
+        let classifier = Classifier::builtin(false);
+
+        let (within, above) = classify_matches(&matches, &SafetyLevel::AutoDelete, &classifier);
+        assert!(within.is_empty());
+        assert_eq!(above.len(), 1);
+        assert_eq!(above[0].safety, SafetyLevel::ConfirmRequired);
+        assert_eq!(above[0].held_by.as_deref(), Some(path(&cache.join("report.pdf")).as_str()));
+
+        let (within, above) = classify_matches(&matches, &SafetyLevel::ConfirmRequired, &classifier);
+        assert_eq!(within.len(), 1);
+        assert_eq!(within[0].id, 10);
+        assert!(above.is_empty());
+    }
+}
@@ -0,0 +1,577 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ignore::WalkBuilder;
+
+use crate::scan::audit::{AuditAction, AuditEntry, AuditOutcome, ConfirmedBy};
+use crate::scan::delete::{get_safety_level, smart_delete_file, DeleteMode, ErrorPolicy, PathTotals, SafetyLevel};
+use crate::scan::hooks::{run_post_delete, run_pre_delete, HookItem, HookManifest, HookPhase};
+use crate::scan::recycle::{self, TrashPlan};
+use crate::scan::state::AppState;
+use crate::scan::throttle::Throttle;
+
+const RUNS_FILE: &str = "policy_runs.log";
+const DEFAULT_RUN_LIMIT: usize = 50;
+/// Matches kept in a run report; the totals always cover every match
+const MATCH_SAMPLE: usize = 200;
+const DAY_SECS: u64 = 24 * 60 * 60;
+const HOUR_MILLIS: u64 = 60 * 60 * 1000;
+
+/// Where a policy looks for items to clean up
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyTarget {
+    /// Items already in the Recycle Bin / trash, aged by when they were deleted
+    RecycleBin,
+    /// Files anywhere below a folder, aged by modification time
+    Folder { path: String },
+}
+
+/// What happens to matching files in a folder; trashed items are always purged
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    #[default]
+    Trash,
+    Delete,
+}
+
+/// A cleanup rule run on a schedule, e.g. "delete Downloads files older than 90 days
+/// over 1 GB"
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Policy {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub enabled: bool,
+    pub target: PolicyTarget,
+    #[serde(default)]
+    pub action: PolicyAction,
+    #[serde(default)]
+    pub min_age_days: Option<u32>,
+    /// Per-item size threshold
+    #[serde(default)]
+    pub min_size_bytes: Option<u64>,
+    /// Zero means the policy only runs on demand
+    #[serde(default)]
+    pub every_hours: u32,
+    /// Unix millis of the last scheduled or manual run, kept across restarts
+    #[serde(default)]
+    pub last_run_at: Option<u64>,
+}
+
+impl Policy {
+    fn is_due(&self, now: u64) -> bool {
+        self.enabled
+            && self.every_hours > 0
+            && self
+                .last_run_at
+                .is_none_or(|last| now.saturating_sub(last) >= u64::from(self.every_hours) * HOUR_MILLIS)
+    }
+
+    /// Items without a known age never match an age condition
+    fn matches(&self, size_bytes: u64, age_secs: Option<u64>) -> bool {
+        let old_enough = match self.min_age_days {
+            Some(days) => age_secs.is_some_and(|age| age >= u64::from(days) * DAY_SECS),
+            None => true,
+        };
+        old_enough && self.min_size_bytes.is_none_or(|min| size_bytes >= min)
+    }
+}
+
+/// Reject policies that could never run safely
+pub fn validate_policy(policy: &Policy) -> Result<(), String> {
+    if policy.name.trim().is_empty() {
+        return Err("Policy name is required".to_string());
+    }
+    // A policy with no condition would remove everything it can reach
+    if policy.min_age_days.is_none() && policy.min_size_bytes.is_none() {
+        return Err("Policy needs a minimum age or size".to_string());
+    }
+    if let PolicyTarget::Folder { path } = &policy.target {
+        let path = Path::new(path);
+        if !path.is_absolute() {
+            return Err(format!("Policy folder must be an absolute path: {}", path.display()));
+        }
+        // The folder itself may be protected (Downloads is); each file is still checked
+        if path.parent().is_none() {
+            return Err(format!("Policy folder cannot be a drive root: {}", path.display()));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicyMatch {
+    pub path: String,
+    pub size_bytes: u64,
+    pub age_days: Option<u64>,
+}
+
+/// Report of one policy run, or of a dry-run preview
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicyRun {
+    pub policy_id: String,
+    pub policy_name: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub dry_run: bool,
+    pub matched_count: u64,
+    pub matched_bytes: u64,
+    /// The largest matches, up to a fixed sample
+    pub matches: Vec<PolicyMatch>,
+    pub removed_count: u64,
+    pub freed_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+/// Something a policy found, with what is needed to remove it
+enum Candidate {
+    File(PathBuf, SafetyLevel),
+    #[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+    Trashed(trash::TrashItem),
+}
+
+/// Find what `policy` matches and, unless `dry_run`, remove it. Every removal is
+/// written to the audit log and real runs to the policy run log.
+pub fn run_policy(policy: &Policy, dry_run: bool, state: &AppState) -> Result<PolicyRun, String> {
+    // Policies saved before a check was added are held to it too
+    validate_policy(policy)?;
+    let started_at = now_millis();
+    let mut found = match &policy.target {
+        PolicyTarget::RecycleBin => trashed_candidates(policy)?,
+        PolicyTarget::Folder { path } => folder_candidates(policy, Path::new(path), state),
+    };
+    found.sort_by_key(|(_, found)| Reverse(found.size_bytes));
+
+    let mut run = PolicyRun {
+        policy_id: policy.id.clone(),
+        policy_name: policy.name.clone(),
+        started_at,
+        finished_at: started_at,
+        dry_run,
+        matched_count: found.len() as u64,
+        matched_bytes: found.iter().map(|(_, m)| m.size_bytes).sum(),
+        matches: found.iter().take(MATCH_SAMPLE).map(|(_, m)| m.clone()).collect(),
+        removed_count: 0,
+        freed_bytes: 0,
+        errors: Vec::new(),
+    };
+    if !dry_run {
+        remove_candidates(policy, found, &mut run, state);
+        run.finished_at = now_millis();
+        if let Some(dir) = state.data_dir() {
+            append_run(dir, &run)?;
+        }
+    }
+    Ok(run)
+}
+
+/// Run every enabled policy whose interval has elapsed, recording when each ran. A run
+/// that fails outright is still logged, with its error.
+pub fn run_due_policies(state: &AppState) -> Vec<PolicyRun> {
+    let now = now_millis();
+    let due: Vec<Policy> = state
+        .settings()
+        .policies
+        .into_iter()
+        .filter(|policy| policy.is_due(now))
+        .collect();
+    let mut runs = Vec::new();
+    for policy in due {
+        let run = run_policy(&policy, false, state).unwrap_or_else(|e| failed_run(&policy, now, e, state));
+        mark_ran(state, &policy.id, now);
+        runs.push(run);
+    }
+    runs
+}
+
+/// Log a run of `policy` that stopped before removing anything
+fn failed_run(policy: &Policy, started_at: u64, error: String, state: &AppState) -> PolicyRun {
+    let run = PolicyRun {
+        policy_id: policy.id.clone(),
+        policy_name: policy.name.clone(),
+        started_at,
+        finished_at: now_millis(),
+        dry_run: false,
+        matched_count: 0,
+        matched_bytes: 0,
+        matches: Vec::new(),
+        removed_count: 0,
+        freed_bytes: 0,
+        errors: vec![error],
+    };
+    if let Some(dir) = state.data_dir() {
+        // The run log itself may be what failed; the run is still returned
+        let _ = append_run(dir, &run);
+    }
+    run
+}
+
+pub fn mark_ran(state: &AppState, policy_id: &str, at: u64) {
+    let _ = state.update_settings(|settings| {
+        if let Some(policy) = settings.policies.iter_mut().find(|p| p.id == policy_id) {
+            policy.last_run_at = Some(at);
+        }
+    });
+}
+
+/// Files below `root` matching the policy. Protected files are left out, and so are
+/// ones that need confirmation, since nobody is there to give it when a policy runs.
+fn folder_candidates(policy: &Policy, root: &Path, state: &AppState) -> Vec<(Candidate, PolicyMatch)> {
+    let classifier = state.classifier();
+    let now = SystemTime::now();
+    let mut builder = WalkBuilder::new(root);
+    builder.standard_filters(false).follow_links(false);
+    builder
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let age_secs = metadata
+                .modified()
+                .ok()
+                .map(|modified| now.duration_since(modified).unwrap_or_default().as_secs());
+            if !policy.matches(metadata.len(), age_secs) {
+                return None;
+            }
+            let path = entry.into_path();
+            let safety = get_safety_level(&path, &classifier);
+            if matches!(safety, SafetyLevel::Protected | SafetyLevel::ConfirmRequired) {
+                return None;
+            }
+            let found = PolicyMatch {
+                path: path.to_string_lossy().to_string(),
+                size_bytes: metadata.len(),
+                age_days: age_secs.map(|age| age / DAY_SECS),
+            };
+            Some((Candidate::File(path, safety), found))
+        })
+        .collect()
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn trashed_candidates(policy: &Policy) -> Result<Vec<(Candidate, PolicyMatch)>, String> {
+    use crate::scan::recycle::trashed_folder_size;
+    use trash::os_limited::{list, metadata};
+
+    let now = now_millis() / 1000;
+    let items = list().map_err(|e| e.to_string())?;
+    Ok(items
+        .into_iter()
+        .filter_map(|item| {
+            // Folders report an entry count rather than bytes, so measure them where they sit
+            let size_bytes = match metadata(&item).ok()?.size.size() {
+                Some(bytes) => bytes,
+                None => trashed_folder_size(&item).unwrap_or(0),
+            };
+            let age_secs = u64::try_from(item.time_deleted).ok().map(|deleted| now.saturating_sub(deleted));
+            if !policy.matches(size_bytes, age_secs) {
+                return None;
+            }
+            let found = PolicyMatch {
+                path: item.original_path().to_string_lossy().to_string(),
+                size_bytes,
+                age_days: age_secs.map(|age| age / DAY_SECS),
+            };
+            Some((Candidate::Trashed(item), found))
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn trashed_candidates(_policy: &Policy) -> Result<Vec<(Candidate, PolicyMatch)>, String> {
+    Err("Listing the trash is not supported on this platform".to_string())
+}
+
+fn remove_candidates(policy: &Policy, found: Vec<(Candidate, PolicyMatch)>, run: &mut PolicyRun, state: &AppState) {
+    let message = Some(format!("Policy: {}", policy.name));
+    let mut audit = Vec::new();
+    let mut files = Vec::new();
+    let mut trashed = Vec::new();
+    for (candidate, found) in found {
+        match candidate {
+            Candidate::File(path, safety) => files.push((path, safety, found)),
+            Candidate::Trashed(item) => trashed.push((item, found)),
+        }
+    }
+    if !files.is_empty() {
+        remove_files(policy, files, &message, run, state, &mut audit);
+    }
+    if !trashed.is_empty() {
+        purge_trashed(trashed, &message, run, &mut audit);
+    }
+    state.record_audit(&audit);
+}
+
+/// Remove matched files the way a manual delete does: the pre-delete hook may veto
+/// them, trashing follows the trash fallback setting, and the post-delete hook hears
+/// what happened
+fn remove_files(
+    policy: &Policy,
+    files: Vec<(PathBuf, SafetyLevel, PolicyMatch)>,
+    message: &Option<String>,
+    run: &mut PolicyRun,
+    state: &AppState,
+    audit: &mut Vec<AuditEntry>,
+) {
+    let settings = state.settings();
+    let hooks = state.hooks();
+    let classifier = state.classifier();
+    let mut hook_items: Vec<HookItem> = files
+        .iter()
+        .map(|(_, safety, found)| HookItem {
+            path: found.path.clone(),
+            size_bytes: found.size_bytes,
+            safety_level: safety.clone(),
+            success: None,
+            error: None,
+        })
+        .collect();
+    let decision = run_pre_delete(&hooks, &hook_manifest(HookPhase::PreDelete, &hook_items));
+
+    let mut attempted = Vec::new();
+    for ((path, safety, found), mut item) in files.into_iter().zip(hook_items.drain(..)) {
+        let totals = PathTotals {
+            bytes: found.size_bytes,
+            files: 1,
+            folders: 0,
+        };
+        let mut entry = AuditEntry::new(AuditAction::Delete, &found.path, ConfirmedBy::Policy, AuditOutcome::Success);
+        entry.safety_level = Some(safety.clone());
+        entry.message = message.clone();
+        if let Some(reason) = decision.denies(&found.path) {
+            entry.outcome = AuditOutcome::Blocked;
+            entry.message = Some(format!("Blocked by pre-delete hook: {}", reason));
+            run.errors.push(format!("{}: blocked by pre-delete hook: {}", found.path, reason));
+            audit.push(entry);
+            continue;
+        }
+        let plan = match policy.action {
+            PolicyAction::Trash => recycle::trash_plan(&path, found.size_bytes, settings.trash_fallback),
+            PolicyAction::Delete => TrashPlan::Delete(DeleteMode::Permanent),
+        };
+        let outcome = match plan {
+            TrashPlan::Delete(mode) => {
+                entry.action = if mode == DeleteMode::Trash { AuditAction::Trash } else { AuditAction::Delete };
+                smart_delete_file(
+                    &path,
+                    false,
+                    &classifier,
+                    &mut Throttle::unlimited(),
+                    ErrorPolicy::default(),
+                    Some(totals),
+                    mode,
+                )
+                .and_then(|result| {
+                    if result.success {
+                        Ok(result.bytes_freed)
+                    } else {
+                        Err(result.errors.join("; "))
+                    }
+                })
+            }
+            TrashPlan::Quarantine => {
+                entry.action = AuditAction::Move;
+                recycle::quarantine(&path).map(|target| {
+                    entry.message = Some(format!("Quarantined to {}", target.display()));
+                    // Quarantined files still take up space on the volume
+                    0
+                })
+            }
+            TrashPlan::Refuse(reason) => Err(reason),
+        };
+        match &outcome {
+            Ok(freed) => {
+                entry.size_bytes = *freed;
+                run.removed_count += 1;
+                run.freed_bytes += freed;
+            }
+            Err(e) => {
+                entry.outcome = AuditOutcome::Failed;
+                run.errors.push(e.clone());
+            }
+        }
+        item.success = Some(outcome.is_ok());
+        item.error = outcome.err();
+        audit.push(entry);
+        attempted.push(item);
+    }
+    if !attempted.is_empty() {
+        // Post-delete hooks are advisory; their failure doesn't change the outcome
+        let _ = run_post_delete(&hooks, &hook_manifest(HookPhase::PostDelete, &attempted));
+    }
+}
+
+fn hook_manifest(phase: HookPhase, items: &[HookItem]) -> HookManifest {
+    HookManifest {
+        phase,
+        operation: "policy".to_string(),
+        items: items.to_vec(),
+    }
+}
+
+/// Purge in one call; the platform APIs don't report which items failed
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn purge_trashed(
+    trashed: Vec<(trash::TrashItem, PolicyMatch)>,
+    message: &Option<String>,
+    run: &mut PolicyRun,
+    audit: &mut Vec<AuditEntry>,
+) {
+    let (items, found): (Vec<_>, Vec<_>) = trashed.into_iter().unzip();
+    let outcome = trash::os_limited::purge_all(items);
+    for found in found {
+        let mut entry = AuditEntry::new(AuditAction::EmptyTrash, &found.path, ConfirmedBy::Policy, AuditOutcome::Success);
+        entry.size_bytes = found.size_bytes;
+        entry.message = message.clone();
+        match &outcome {
+            Ok(()) => {
+                run.removed_count += 1;
+                run.freed_bytes += found.size_bytes;
+            }
+            Err(e) => {
+                entry.outcome = AuditOutcome::Failed;
+                entry.message = Some(e.to_string());
+            }
+        }
+        audit.push(entry);
+    }
+    if let Err(e) = outcome {
+        run.errors.push(e.to_string());
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn purge_trashed(
+    _trashed: Vec<(trash::TrashItem, PolicyMatch)>,
+    _message: &Option<String>,
+    _run: &mut PolicyRun,
+    _audit: &mut Vec<AuditEntry>,
+) {
+}
+
+fn append_run(dir: &Path, run: &PolicyRun) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut line = serde_json::to_string(run).map_err(|e| e.to_string())?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(RUNS_FILE))
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+/// Past runs, newest first, optionally for a single policy
+pub fn read_runs(dir: &Path, policy_id: Option<&str>, limit: Option<usize>) -> Result<Vec<PolicyRun>, String> {
+    let file = match fs::File::open(dir.join(RUNS_FILE)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut runs: Vec<PolicyRun> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<PolicyRun>(&line).ok())
+        .filter(|run| policy_id.is_none_or(|id| run.policy_id == id))
+        .collect();
+    runs.reverse();
+    runs.truncate(limit.unwrap_or(DEFAULT_RUN_LIMIT));
+    Ok(runs)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    fn folder_policy(path: &Path) -> Policy {
+        Policy {
+            id: "p1".to_string(),
+            name: "Old big downloads".to_string(),
+            enabled: true,
+            target: PolicyTarget::Folder {
+                path: path.to_string_lossy().to_string(),
+            },
+            action: PolicyAction::Delete,
+            min_age_days: None,
+            min_size_bytes: Some(10),
+            every_hours: 24,
+            last_run_at: None,
+        }
+    }
+
+    #[test]
+    fn age_and_size_conditions_must_both_hold() {
+        let mut policy = folder_policy(Path::new("/tmp"));
+        policy.min_age_days = Some(30);
+        assert!(policy.matches(10, Some(31 * DAY_SECS)));
+        assert!(!policy.matches(9, Some(31 * DAY_SECS)));
+        assert!(!policy.matches(10, Some(29 * DAY_SECS)));
+        assert!(!policy.matches(10, None));
+        assert!(policy.is_due(0));
+        policy.last_run_at = Some(0);
+        assert!(!policy.is_due(HOUR_MILLIS));
+    }
+
+    #[test]
+    fn policies_without_an_age_or_size_condition_are_rejected() {
+        let mut policy = folder_policy(Path::new("/tmp/downloads"));
+        assert!(validate_policy(&policy).is_ok());
+        policy.min_size_bytes = None;
+        assert!(validate_policy(&policy).is_err());
+        let state = AppState::new();
+        assert!(run_policy(&policy, true, &state).is_err());
+    }
+
+    #[test]
+    fn dry_run_previews_without_deleting_and_real_runs_are_logged() {
+        let temp = tempdir().expect("tempdir");
+        let folder = temp.path().join("downloads");
+        fs::create_dir_all(&folder).expect("create folder");
+        write(folder.join("big.bin"), vec![0u8; 20]).expect("write big");
+        write(folder.join("small.bin"), vec![0u8; 5]).expect("write small");
+        // Week-old temp files are safe to delete; recent ones need a confirmation no
+        // policy run can give, so they are left alone
+        let month_ago = SystemTime::now() - std::time::Duration::from_secs(30 * DAY_SECS);
+        for name in ["big.bin", "small.bin"] {
+            fs::File::options()
+                .write(true)
+                .open(folder.join(name))
+                .and_then(|file| file.set_modified(month_ago))
+                .expect("age file");
+        }
+        write(folder.join("recent.bin"), vec![0u8; 20]).expect("write recent");
+        let state = AppState::with_data_dir(temp.path().join("data"));
+        let policy = folder_policy(&folder);
+
+        let preview = run_policy(&policy, true, &state).expect("preview");
+        assert_eq!(preview.matched_count, 1);
+        assert_eq!(preview.matched_bytes, 20);
+        assert!(folder.join("big.bin").exists());
+
+        let run = run_policy(&policy, false, &state).expect("run");
+        assert_eq!(run.removed_count, 1);
+        assert!(!folder.join("big.bin").exists());
+        assert!(folder.join("small.bin").exists());
+        assert!(folder.join("recent.bin").exists());
+        let runs = read_runs(&temp.path().join("data"), Some("p1"), None).expect("read runs");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].freed_bytes, 20);
+    }
+}
@@ -0,0 +1,374 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::scan::delete::{get_safety_level, SafetyLevel};
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::rules::Classifier;
+use crate::scan::tree::ScanTree;
+
+/// Matches returned when a listing command doesn't pass a limit
+pub const DEFAULT_QUERY_LIMIT: usize = 1000;
+
+/// Structured node filter shared by the listing and export commands. Every field is
+/// optional and the set ones must all match; an empty filter matches every node.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NodeFilter {
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// Modification time bounds in seconds since the Unix epoch; nodes without a
+    /// modification time (directories) never match a date bound
+    #[serde(default)]
+    pub modified_after: Option<u64>,
+    #[serde(default)]
+    pub modified_before: Option<u64>,
+    #[serde(default)]
+    pub kinds: Vec<NodeKind>,
+    /// Extensions without the dot, case-insensitive
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    /// Case-insensitive substring of the node's name
+    #[serde(default)]
+    pub name_contains: Option<String>,
+    /// Depth below the scan root, which is depth 0
+    #[serde(default)]
+    pub min_depth: Option<u32>,
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// Checked last because it reads the filesystem and the rule packs
+    #[serde(default)]
+    pub safety_levels: Vec<SafetyLevel>,
+}
+
+/// What listings rank nodes by and treemaps size them by
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Weight {
+    #[default]
+    Bytes,
+    /// Files below a node, which a file counts as one. Brings out folders of millions
+    /// of tiny files that rank low by size.
+    FileCount,
+}
+
+/// A node matched by a filter
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeMatch {
+    pub id: NodeId,
+    pub path: String,
+    pub name: String,
+    pub kind: NodeKind,
+    pub size_bytes: u64,
+    pub file_ext: Option<String>,
+    pub modified: Option<u64>,
+    pub depth: u32,
+    /// Files in the node's subtree, filled in when weighting by file count
+    #[serde(default)]
+    pub file_count: Option<u64>,
+}
+
+/// A filter prepared for evaluation against one tree
+pub struct NodeQuery {
+    filter: NodeFilter,
+    extensions: Vec<String>,
+    name_contains: Option<String>,
+    classifier: Classifier,
+}
+
+impl NodeQuery {
+    pub fn new(filter: NodeFilter, classifier: Classifier) -> Result<Self, String> {
+        if let (Some(min), Some(max)) = (filter.min_size, filter.max_size) {
+            if min > max {
+                return Err("min_size is larger than max_size".to_string());
+            }
+        }
+        if let (Some(min), Some(max)) = (filter.min_depth, filter.max_depth) {
+            if min > max {
+                return Err("min_depth is larger than max_depth".to_string());
+            }
+        }
+        let extensions = filter
+            .extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        let name_contains = filter
+            .name_contains
+            .as_ref()
+            .map(|text| text.to_lowercase())
+            .filter(|text| !text.is_empty());
+        Ok(Self {
+            filter,
+            extensions,
+            name_contains,
+            classifier,
+        })
+    }
+
+    /// Every matching node with its depth. The walk starts at `path_prefix` when it
+    /// names a node, and doesn't descend past `max_depth`.
+    pub fn matching<'a>(&self, tree: &'a ScanTree) -> Vec<(&'a TreeNode, u32)> {
+        let Some(root) = tree.root() else {
+            return Vec::new();
+        };
+        let start = match self.filter.path_prefix.as_deref().and_then(|prefix| tree.find_by_path(prefix)) {
+            Some(node) => (node, depth_of(tree, node)),
+            None => (root, 0),
+        };
+        let mut matches = Vec::new();
+        let mut stack = vec![start];
+        while let Some((node, depth)) = stack.pop() {
+            if self.matches(node, depth) {
+                matches.push((node, depth));
+            }
+            if self.filter.max_depth.is_none_or(|max| depth < max) {
+                stack.extend(node.children.iter().filter_map(|id| tree.get(*id)).map(|child| (child, depth + 1)));
+            }
+        }
+        matches
+    }
+
+    /// Matches as listing rows, heaviest first, at most `limit`
+    pub fn search(&self, tree: &ScanTree, limit: usize, weight: Weight) -> Vec<NodeMatch> {
+        let matches = self.matching(tree);
+        let counts = match weight {
+            Weight::Bytes => None,
+            Weight::FileCount => Some(tree.file_counts(tree.root_id)),
+        };
+        let mut rows: Vec<NodeMatch> = matches
+            .into_iter()
+            .map(|(node, depth)| {
+                let file_count = counts.as_ref().map(|counts| counts.get(&node.id).copied().unwrap_or(0));
+                node_match(node, depth, file_count)
+            })
+            .collect();
+        sort_by_weight(&mut rows, weight);
+        rows.truncate(limit);
+        rows
+    }
+
+    fn matches(&self, node: &TreeNode, depth: u32) -> bool {
+        let filter = &self.filter;
+        if filter.min_size.is_some_and(|min| node.size_bytes < min)
+            || filter.max_size.is_some_and(|max| node.size_bytes > max)
+            || filter.min_depth.is_some_and(|min| depth < min)
+            || filter.max_depth.is_some_and(|max| depth > max)
+        {
+            return false;
+        }
+        if !filter.kinds.is_empty() && !filter.kinds.contains(&node.kind) {
+            return false;
+        }
+        if filter.modified_after.is_some() || filter.modified_before.is_some() {
+            let Some(modified) = node.modified else {
+                return false;
+            };
+            if filter.modified_after.is_some_and(|after| modified < after)
+                || filter.modified_before.is_some_and(|before| modified > before)
+            {
+                return false;
+            }
+        }
+        if !self.extensions.is_empty()
+            && !node
+                .file_ext
+                .as_ref()
+                .is_some_and(|ext| self.extensions.iter().any(|wanted| wanted.eq_ignore_ascii_case(ext)))
+        {
+            return false;
+        }
+        if let Some(prefix) = &filter.path_prefix {
+            if !is_under(&node.path, prefix) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.name_contains {
+            if !node.name.to_lowercase().contains(text.as_str()) {
+                return false;
+            }
+        }
+        filter.safety_levels.is_empty()
+            || filter
+                .safety_levels
+                .contains(&get_safety_level(Path::new(&node.path), &self.classifier))
+    }
+}
+
+/// The children of `node_id`, heaviest first, for laying out one level of a treemap
+pub fn children(tree: &ScanTree, node_id: NodeId, weight: Weight) -> Option<Vec<NodeMatch>> {
+    let node = tree.get(node_id)?;
+    let depth = depth_of(tree, node) + 1;
+    let mut rows: Vec<NodeMatch> = node
+        .children
+        .iter()
+        .filter_map(|id| tree.get(*id))
+        .map(|child| {
+            let file_count = match weight {
+                Weight::Bytes => None,
+                Weight::FileCount => tree.subtree_counts(child.id).map(|(files, _)| files),
+            };
+            node_match(child, depth, file_count)
+        })
+        .collect();
+    sort_by_weight(&mut rows, weight);
+    Some(rows)
+}
+
+fn node_match(node: &TreeNode, depth: u32, file_count: Option<u64>) -> NodeMatch {
+    NodeMatch {
+        id: node.id,
+        path: node.path.clone(),
+        name: node.name.clone(),
+        kind: node.kind,
+        size_bytes: node.size_bytes,
+        file_ext: node.file_ext.clone(),
+        modified: node.modified,
+        depth,
+        file_count,
+    }
+}
+
+/// Heaviest first by `weight`, then largest, then by path
+fn sort_by_weight(rows: &mut [NodeMatch], weight: Weight) {
+    rows.sort_by(|a, b| {
+        let by_count = match weight {
+            Weight::Bytes => std::cmp::Ordering::Equal,
+            Weight::FileCount => b.file_count.cmp(&a.file_count),
+        };
+        by_count
+            .then_with(|| b.size_bytes.cmp(&a.size_bytes))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+}
+
+/// Whether `path` is `prefix` itself or below it
+fn is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches(['/', '\\']);
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']) || prefix.is_empty())
+}
+
+fn depth_of(tree: &ScanTree, node: &TreeNode) -> u32 {
+    let mut depth = 0;
+    let mut parent = node.parent;
+    while let Some(id) = parent {
+        depth += 1;
+        parent = tree.get(id).and_then(|node| node.parent);
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::rules::RuleSet;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        TreeNode {
+            id,
+            parent,
+            file_ext: (kind == NodeKind::File)
+                .then(|| name.rsplit_once('.').map(|(_, ext)| ext.to_string()))
+                .flatten(),
+            name,
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            modified: (kind == NodeKind::File).then_some(100),
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn filters_combine_and_respect_depth() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/r", NodeKind::Dir, 60, vec![2, 5]),
+                node(2, Some(1), "/r/logs", NodeKind::Dir, 50, vec![3, 4]),
+                node(3, Some(2), "/r/logs/app.LOG", NodeKind::File, 40, vec![]),
+                node(4, Some(2), "/r/logs/old.gz", NodeKind::File, 10, vec![]),
+                node(5, Some(1), "/r/logsheet.log", NodeKind::File, 10, vec![]),
+            ],
+            false,
+        );
+        let classifier = || Classifier {
+            rules: RuleSet::builtin(),
+            developer_mode: false,
+        };
+        let query = |filter: NodeFilter| NodeQuery::new(filter, classifier()).expect("query");
+
+        let logs = query(NodeFilter {
+            extensions: vec![".LOG".to_string(), "log".to_string()],
+            path_prefix: Some("/r/logs/".to_string()),
+            ..NodeFilter::default()
+        });
+        let found = logs.search(&tree, 10, Weight::Bytes);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "/r/logs/app.LOG");
+        assert_eq!(found[0].depth, 2);
+
+        let shallow = query(NodeFilter {
+            max_depth: Some(1),
+            min_size: Some(10),
+            modified_after: Some(50),
+            ..NodeFilter::default()
+        });
+        let found = shallow.search(&tree, 10, Weight::Bytes);
+        assert_eq!(found.iter().map(|m| m.id).collect::<Vec<_>>(), vec![5]);
+
+        assert!(NodeQuery::new(
+            NodeFilter {
+                min_size: Some(2),
+                max_size: Some(1),
+                ..NodeFilter::default()
+            },
+            classifier(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn file_count_weighting_ranks_many_small_files_first() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/r", NodeKind::Dir, 103, vec![2, 3]),
+                node(2, Some(1), "/r/video", NodeKind::Dir, 100, vec![4]),
+                node(3, Some(1), "/r/node_modules", NodeKind::Dir, 3, vec![5, 6, 7]),
+                node(4, Some(2), "/r/video/a.mkv", NodeKind::File, 100, vec![]),
+                node(5, Some(3), "/r/node_modules/a.js", NodeKind::File, 1, vec![]),
+                node(6, Some(3), "/r/node_modules/b.js", NodeKind::File, 1, vec![]),
+                node(7, Some(3), "/r/node_modules/c.js", NodeKind::File, 1, vec![]),
+            ],
+            false,
+        );
+        let by_size = children(&tree, 1, Weight::Bytes).expect("children");
+        assert_eq!(by_size[0].name, "video");
+        assert_eq!(by_size[0].file_count, None);
+        let by_count = children(&tree, 1, Weight::FileCount).expect("children");
+        assert_eq!((by_count[0].name.as_str(), by_count[0].file_count), ("node_modules", Some(3)));
+
+        let dirs = NodeQuery::new(
+            NodeFilter {
+                kinds: vec![NodeKind::Dir],
+                min_depth: Some(1),
+                ..NodeFilter::default()
+            },
+            Classifier {
+                rules: RuleSet::builtin(),
+                developer_mode: false,
+            },
+        )
+        .expect("query");
+        let found = dirs.search(&tree, 10, Weight::FileCount);
+        assert_eq!(found.iter().map(|m| m.file_count).collect::<Vec<_>>(), vec![Some(3), Some(1)]);
+        assert_eq!(tree.file_counts(1).get(&1), Some(&4));
+    }
+}
@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::scan::delete::calculate_dir_size;
+use crate::scan::model::ScanResult;
+use crate::scan::recycle::trash_usage;
+use crate::scan::shadow::shadow_storage_for;
+use crate::scan::sysfiles::{system_file_info, system_file_size, SystemFileKind, SYSTEM_FILES};
+use crate::scan::tree::ScanTree;
+use crate::scan::volumes::{cluster_size, volume_space, VolumeSpace};
+
+/// Approximate MFT record size per file or directory on NTFS
+const NTFS_RECORD_BYTES: u64 = 1024;
+/// Rough inode/metadata cost per entry on other filesystems
+const INODE_BYTES: u64 = 256;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationKind {
+    SkippedDirs,
+    DeniedDirs,
+    FilesystemMetadata,
+    AllocationSlack,
+    SystemFiles,
+    ShadowCopies,
+}
+
+/// One explained share of the gap between volume usage and scanned bytes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReconciliationItem {
+    pub kind: ReconciliationKind,
+    pub label: String,
+    pub bytes: u64,
+    /// True when the size is an estimate rather than measured
+    pub estimated: bool,
+    pub paths: Vec<String>,
+}
+
+/// Where the "missing" space went: volume used bytes versus what the scan found
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpaceReconciliation {
+    pub scan_id: String,
+    pub root_path: String,
+    pub mount_point: String,
+    pub volume_used_bytes: u64,
+    pub scanned_bytes: u64,
+    /// False when the scan started below the volume root, so most of the gap is unscanned folders
+    pub covers_whole_volume: bool,
+    pub items: Vec<ReconciliationItem>,
+    /// Gap left after all items; negative when estimates overshoot
+    pub unexplained_bytes: i64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RootComponentKind {
+    /// Recycle Bin or trash folders
+    Trash,
+    ShadowCopies,
+    Hibernation,
+    Pagefile,
+    Swapfile,
+    /// What the scan found, less anything counted in another component
+    ScannedFiles,
+}
+
+/// One share of a volume's used space
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RootComponent {
+    pub kind: RootComponentKind,
+    pub bytes: u64,
+    pub paths: Vec<String>,
+}
+
+/// A volume's used space split into system components and scanned files
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RootBreakdown {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub used_bytes: u64,
+    /// The scan the file totals come from; None when the volume hasn't been scanned
+    pub scan_id: Option<String>,
+    /// False when that scan started below the volume root
+    pub scan_covers_volume: bool,
+    /// Largest first
+    pub components: Vec<RootComponent>,
+    /// Used bytes no component accounts for: metadata, unreadable or unscanned folders
+    pub unaccounted_bytes: u64,
+}
+
+/// Break down the used space of the volume holding `path`, reading file totals from
+/// `scan` when a scan of that volume is at hand
+pub fn root_breakdown(path: &Path, scan: Option<(&ScanResult, &ScanTree)>) -> Result<RootBreakdown, String> {
+    let volume = volume_space(path).ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    let mount_point = Path::new(&volume.mount_point);
+    let mut components = Vec::new();
+
+    if let Some((bytes, folders)) = trash_usage(&volume.mount_point) {
+        components.push(RootComponent {
+            kind: RootComponentKind::Trash,
+            bytes,
+            paths: folders.iter().map(|folder| folder.to_string_lossy().to_string()).collect(),
+        });
+    }
+    if let Some(shadow) = shadow_storage_for(&volume.mount_point) {
+        components.push(RootComponent {
+            kind: RootComponentKind::ShadowCopies,
+            bytes: shadow.used_bytes,
+            paths: Vec::new(),
+        });
+    }
+    for name in SYSTEM_FILES {
+        let path = mount_point.join(name);
+        let (Some(info), Some(bytes)) = (system_file_info(&path), system_file_size(&path)) else {
+            continue;
+        };
+        components.push(RootComponent {
+            kind: match info.kind {
+                SystemFileKind::Hibernation => RootComponentKind::Hibernation,
+                SystemFileKind::Pagefile => RootComponentKind::Pagefile,
+                SystemFileKind::Swapfile => RootComponentKind::Swapfile,
+            },
+            bytes,
+            paths: vec![path.to_string_lossy().to_string()],
+        });
+    }
+
+    let scanned = scan.and_then(|(result, tree)| {
+        let root = tree.root()?;
+        // Trash folders and system files the scan also counted belong to their own component
+        let overlap: u64 = components
+            .iter()
+            .flat_map(|component| component.paths.iter())
+            .filter_map(|path| tree.find_by_path(path))
+            .map(|node| node.size_bytes)
+            .sum();
+        Some(ScannedShare {
+            scan_id: result.scan_id.clone(),
+            covers_volume: Path::new(&root.path) == mount_point,
+            bytes: result.total_bytes.saturating_sub(overlap),
+            root_path: root.path.clone(),
+        })
+    });
+    Ok(assemble(volume, components, scanned))
+}
+
+struct ScannedShare {
+    scan_id: String,
+    covers_volume: bool,
+    bytes: u64,
+    root_path: String,
+}
+
+fn assemble(volume: VolumeSpace, mut components: Vec<RootComponent>, scanned: Option<ScannedShare>) -> RootBreakdown {
+    let used_bytes = volume.total_bytes.saturating_sub(volume.available_bytes);
+    let (scan_id, scan_covers_volume) = match scanned {
+        Some(share) => {
+            components.push(RootComponent {
+                kind: RootComponentKind::ScannedFiles,
+                bytes: share.bytes,
+                paths: vec![share.root_path],
+            });
+            (Some(share.scan_id), share.covers_volume)
+        }
+        None => (None, false),
+    };
+    components.sort_by_key(|component| std::cmp::Reverse(component.bytes));
+    let accounted: u64 = components.iter().map(|component| component.bytes).sum();
+    RootBreakdown {
+        mount_point: volume.mount_point,
+        file_system: volume.file_system,
+        total_bytes: volume.total_bytes,
+        available_bytes: volume.available_bytes,
+        used_bytes,
+        scan_id,
+        scan_covers_volume,
+        components,
+        unaccounted_bytes: used_bytes.saturating_sub(accounted),
+    }
+}
+
+pub fn explain_missing_space(result: &ScanResult, tree: &ScanTree) -> Result<SpaceReconciliation, String> {
+    let root_path = tree
+        .root()
+        .map(|node| node.path.clone())
+        .ok_or_else(|| "Scan tree has no root".to_string())?;
+    let root = Path::new(&root_path);
+    let volume = volume_space(root).ok_or_else(|| format!("No mounted volume contains {}", root_path))?;
+    let mount_point = Path::new(&volume.mount_point);
+    let volume_used_bytes = volume.total_bytes.saturating_sub(volume.available_bytes);
+
+    let mut items = Vec::new();
+
+    if !result.skipped_paths.is_empty() {
+        items.push(ReconciliationItem {
+            kind: ReconciliationKind::SkippedDirs,
+            label: "System folders skipped by the scanner".to_string(),
+            bytes: result
+                .skipped_paths
+                .iter()
+                .map(|p| calculate_dir_size(Path::new(p)).unwrap_or(0))
+                .sum(),
+            estimated: false,
+            paths: result.skipped_paths.clone(),
+        });
+    }
+
+    if !result.error_paths.is_empty() {
+        // Unreadable folders can't be sized; they fall into the unexplained remainder
+        items.push(ReconciliationItem {
+            kind: ReconciliationKind::DeniedDirs,
+            label: "Folders that could not be read".to_string(),
+            bytes: 0,
+            estimated: true,
+            paths: result.error_paths.clone(),
+        });
+    }
+
+    let entries = result.total_files + result.total_dirs;
+    let per_entry = if volume.file_system.eq_ignore_ascii_case("ntfs") {
+        NTFS_RECORD_BYTES
+    } else {
+        INODE_BYTES
+    };
+    items.push(ReconciliationItem {
+        kind: ReconciliationKind::FilesystemMetadata,
+        label: "File system metadata (MFT, inodes, directories)".to_string(),
+        bytes: entries * per_entry,
+        estimated: true,
+        paths: Vec::new(),
+    });
+
+    // On average each file wastes half of its last cluster
+    items.push(ReconciliationItem {
+        kind: ReconciliationKind::AllocationSlack,
+        label: "Unused space at the end of file clusters".to_string(),
+        bytes: result.total_files * (cluster_size(root) / 2),
+        estimated: true,
+        paths: Vec::new(),
+    });
+
+    let system_files: Vec<(PathBuf, u64)> = SYSTEM_FILES
+        .iter()
+        .map(|name| mount_point.join(name))
+        .filter(|path| tree.find_by_path(&path.to_string_lossy()).is_none())
+        .filter_map(|path| system_file_size(&path).map(|size| (path, size)))
+        .collect();
+    if !system_files.is_empty() {
+        items.push(ReconciliationItem {
+            kind: ReconciliationKind::SystemFiles,
+            label: "Paging, hibernation, and swap files".to_string(),
+            bytes: system_files.iter().map(|(_, size)| size).sum(),
+            estimated: false,
+            paths: system_files
+                .iter()
+                .map(|(path, _)| path.to_string_lossy().to_string())
+                .collect(),
+        });
+    }
+
+    if let Some(shadow) = shadow_storage_for(&volume.mount_point).filter(|s| s.used_bytes > 0) {
+        items.push(ReconciliationItem {
+            kind: ReconciliationKind::ShadowCopies,
+            label: "System Restore and shadow copies".to_string(),
+            bytes: shadow.used_bytes,
+            estimated: false,
+            paths: Vec::new(),
+        });
+    }
+
+    let explained: u64 = items.iter().map(|item| item.bytes).sum();
+    let scanned_bytes = result.total_bytes;
+    Ok(SpaceReconciliation {
+        scan_id: result.scan_id.clone(),
+        covers_whole_volume: root == mount_point,
+        root_path,
+        mount_point: volume.mount_point,
+        volume_used_bytes,
+        scanned_bytes,
+        items,
+        unexplained_bytes: volume_used_bytes as i64 - scanned_bytes as i64 - explained as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakdown_adds_up_to_used_space() {
+        let volume = VolumeSpace {
+            mount_point: "C:\\".to_string(),
+            file_system: "NTFS".to_string(),
+            total_bytes: 1000,
+            available_bytes: 300,
+        };
+        let components = vec![
+            RootComponent {
+                kind: RootComponentKind::Trash,
+                bytes: 50,
+                paths: vec!["C:\\$Recycle.Bin".to_string()],
+            },
+            RootComponent {
+                kind: RootComponentKind::Hibernation,
+                bytes: 200,
+                paths: vec!["C:\\hiberfil.sys".to_string()],
+            },
+        ];
+        let scanned = ScannedShare {
+            scan_id: "s".to_string(),
+            covers_volume: true,
+            bytes: 400,
+            root_path: "C:\\".to_string(),
+        };
+        let breakdown = assemble(volume, components, Some(scanned));
+        assert_eq!(breakdown.used_bytes, 700);
+        assert_eq!(breakdown.unaccounted_bytes, 50);
+        let kinds: Vec<RootComponentKind> = breakdown.components.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![RootComponentKind::ScannedFiles, RootComponentKind::Hibernation, RootComponentKind::Trash]
+        );
+        assert_eq!(breakdown.scan_id.as_deref(), Some("s"));
+    }
+}
@@ -0,0 +1,457 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(target_os = "windows"))]
+use crate::scan::delete::calculate_dir_size;
+#[cfg(not(target_os = "windows"))]
+use crate::scan::known_folders::user_folders;
+use crate::scan::delete::DeleteMode;
+use crate::scan::volumes::volume_space;
+
+/// Folder created at a volume root to hold quarantined items
+pub const QUARANTINE_DIR: &str = "DiskSight Quarantine";
+
+/// Windows reserves this share of a volume for the Recycle Bin unless configured otherwise
+#[cfg(target_os = "windows")]
+const DEFAULT_BIN_PERCENT: u64 = 5;
+
+/// Recycle Bin limits of the volume holding a path
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TrashCapacity {
+    pub mount_point: String,
+    /// Largest total the bin may hold; None when the platform sets no limit
+    pub max_bytes: Option<u64>,
+    /// True when `max_bytes` is the Windows default rather than a configured value
+    pub max_is_default: bool,
+    pub used_bytes: Option<u64>,
+    /// "Don't move files to the Recycle Bin" is enabled for this volume
+    pub bypasses_trash: bool,
+}
+
+impl TrashCapacity {
+    /// Items larger than the whole bin are deleted permanently (sometimes without asking)
+    pub fn fits(&self, size_bytes: u64) -> bool {
+        !self.bypasses_trash && self.max_bytes.is_none_or(|max| size_bytes <= max)
+    }
+}
+
+/// Whether an item can be moved to the trash without being silently deleted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashFit {
+    pub path: String,
+    pub size_bytes: u64,
+    pub fits: bool,
+    pub capacity: TrashCapacity,
+}
+
+/// What to do when an item won't fit in the trash
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashFallback {
+    /// Delete permanently instead
+    Permanent,
+    /// Move into a quarantine folder on the same volume
+    Quarantine,
+    /// Leave the item in place and report an error
+    #[default]
+    Abort,
+}
+
+/// How to remove an item headed for the trash
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrashPlan {
+    Delete(DeleteMode),
+    Quarantine,
+    /// Leave it in place, for this reason
+    Refuse(String),
+}
+
+/// Send `path` to the trash when it fits, else do what `fallback` says. When the
+/// capacity can't be read the trash is tried anyway and reports its own failure.
+pub fn trash_plan(path: &Path, size_bytes: u64, fallback: TrashFallback) -> TrashPlan {
+    let fit = match trash_fit(path, size_bytes) {
+        Ok(fit) if !fit.fits => fit,
+        _ => return TrashPlan::Delete(DeleteMode::Trash),
+    };
+    match fallback {
+        TrashFallback::Permanent => TrashPlan::Delete(DeleteMode::Permanent),
+        TrashFallback::Quarantine => TrashPlan::Quarantine,
+        TrashFallback::Abort => TrashPlan::Refuse(format!(
+            "{} ({} bytes) won't fit in the Recycle Bin on {}; choose a permanent delete or quarantine instead",
+            path.display(),
+            size_bytes,
+            fit.capacity.mount_point
+        )),
+    }
+}
+
+pub fn trash_fit(path: &Path, size_bytes: u64) -> Result<TrashFit, String> {
+    let capacity = trash_capacity(path)?;
+    Ok(TrashFit {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        fits: capacity.fits(size_bytes),
+        capacity,
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn trash_capacity(path: &Path) -> Result<TrashCapacity, String> {
+    let volume = volume_space(path).ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    let guid = volume_guid(Path::new(&volume.mount_point));
+    let key = guid.map(|guid| {
+        format!(
+            "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\BitBucket\\Volume\\{}",
+            guid
+        )
+    });
+    let configured_mb = key.as_deref().and_then(|key| reg_dword(key, "MaxCapacity"));
+    let bypasses_trash = key.as_deref().and_then(|key| reg_dword(key, "NukeOnDelete")) == Some(1);
+    let max_bytes = match configured_mb {
+        Some(mb) => u64::from(mb) * 1024 * 1024,
+        None => volume.total_bytes / 100 * DEFAULT_BIN_PERCENT,
+    };
+    Ok(TrashCapacity {
+        used_bytes: recycle_bin_used(&volume.mount_point),
+        mount_point: volume.mount_point,
+        max_bytes: Some(max_bytes),
+        max_is_default: configured_mb.is_none(),
+        bypasses_trash,
+    })
+}
+
+/// Freedesktop and macOS trash folders have no size limit of their own
+#[cfg(not(target_os = "windows"))]
+pub fn trash_capacity(path: &Path) -> Result<TrashCapacity, String> {
+    let volume = volume_space(path).ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    Ok(TrashCapacity {
+        mount_point: volume.mount_point,
+        max_bytes: None,
+        max_is_default: false,
+        used_bytes: None,
+        bypasses_trash: false,
+    })
+}
+
+/// Bytes in the Recycle Bin of the volume mounted at `mount_point`, with its folder
+#[cfg(target_os = "windows")]
+pub fn trash_usage(mount_point: &str) -> Option<(u64, Vec<PathBuf>)> {
+    let used = recycle_bin_used(mount_point)?;
+    Some((used, vec![Path::new(mount_point).join("$Recycle.Bin")]))
+}
+
+/// Bytes in the trash folders on the volume mounted at `mount_point`: the per-volume
+/// `.Trash-<uid>` and `.Trashes` folders, and the user's own trash when it lives there
+#[cfg(not(target_os = "windows"))]
+pub fn trash_usage(mount_point: &str) -> Option<(u64, Vec<PathBuf>)> {
+    let mount = Path::new(mount_point);
+    let mut folders: Vec<PathBuf> = fs::read_dir(mount)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(".Trash"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some((home, _)) = user_folders() {
+        folders.push(home.join(".local/share/Trash"));
+        folders.push(home.join(".Trash"));
+    }
+    folders.retain(|folder| {
+        folder.is_dir() && volume_space(folder).is_some_and(|volume| volume.mount_point == mount_point)
+    });
+    folders.sort();
+    folders.dedup();
+    if folders.is_empty() {
+        return None;
+    }
+    let used = folders.iter().map(|folder| calculate_dir_size(folder).unwrap_or(0)).sum();
+    Some((used, folders))
+}
+
+/// "{guid}" of the volume mounted at `mount_point`
+#[cfg(target_os = "windows")]
+fn volume_guid(mount_point: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetVolumeNameForVolumeMountPointW;
+
+    let mut root: Vec<u16> = mount_point.as_os_str().encode_wide().collect();
+    if root.last() != Some(&(b'\\' as u16)) {
+        root.push(b'\\' as u16);
+    }
+    root.push(0);
+    let mut name = [0u16; 64];
+    let ok = unsafe { GetVolumeNameForVolumeMountPointW(root.as_ptr(), name.as_mut_ptr(), name.len() as u32) };
+    if ok == 0 {
+        return None;
+    }
+    let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+    let name = String::from_utf16_lossy(&name[..len]);
+    let start = name.find('{')?;
+    let end = name.find('}')?;
+    Some(name[start..=end].to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn reg_dword(key: &str, value: &str) -> Option<u32> {
+    let output = std::process::Command::new("reg")
+        .args(["query", key, "/v", value])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_reg_dword(&String::from_utf8_lossy(&output.stdout), value)
+}
+
+/// Parse a `reg query` line such as "    MaxCapacity    REG_DWORD    0x3e8"
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_reg_dword(output: &str, value: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if !fields.next()?.eq_ignore_ascii_case(value) || fields.next()? != "REG_DWORD" {
+            return None;
+        }
+        u32::from_str_radix(fields.next()?.trim_start_matches("0x"), 16).ok()
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn recycle_bin_used(mount_point: &str) -> Option<u64> {
+    use windows_sys::Win32::UI::Shell::{SHQueryRecycleBinW, SHQUERYRBINFO};
+
+    let root: Vec<u16> = mount_point.encode_utf16().chain(Some(0)).collect();
+    let mut info = SHQUERYRBINFO {
+        cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+        i64Size: 0,
+        i64NumItems: 0,
+    };
+    let hr = unsafe { SHQueryRecycleBinW(root.as_ptr(), &mut info) };
+    (hr >= 0).then(|| info.i64Size.max(0) as u64)
+}
+
+/// Move an item into the quarantine folder at the root of its own volume, so the move
+/// is a rename rather than a copy. Returns the new location.
+pub fn quarantine(path: &Path) -> Result<PathBuf, String> {
+    let target = quarantine_target(path)?;
+    move_path(path, &target)?;
+    Ok(target)
+}
+
+/// Where `quarantine` would move `path`, so the move can be journaled before it happens
+pub fn quarantine_target(path: &Path) -> Result<PathBuf, String> {
+    let volume = volume_space(path).ok_or_else(|| format!("No mounted volume contains {}", path.display()))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Cannot quarantine {}", path.display()))?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir = Path::new(&volume.mount_point).join(QUARANTINE_DIR).join(stamp.to_string());
+    Ok(dir.join(name))
+}
+
+/// Rename `from` to `to`, creating the destination's parent folders
+pub fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::rename(from, to).map_err(|e| e.to_string())
+}
+
+/// Put the most recently trashed item for each of `paths` back where it was. Returns
+/// the paths restored; the others were not in the trash (deleted permanently, or the
+/// trash was emptied since).
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn restore_from_trash(paths: &[String]) -> Result<Vec<String>, String> {
+    let wanted: std::collections::HashSet<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let mut newest: std::collections::HashMap<PathBuf, trash::TrashItem> = std::collections::HashMap::new();
+    for item in trash::os_limited::list().map_err(|e| e.to_string())? {
+        let original = item.original_path();
+        if !wanted.contains(&original) {
+            continue;
+        }
+        if newest.get(&original).is_none_or(|kept| kept.time_deleted < item.time_deleted) {
+            newest.insert(original, item);
+        }
+    }
+    let restored: Vec<String> = newest.keys().map(|path| path.to_string_lossy().to_string()).collect();
+    trash::os_limited::restore_all(newest.into_values()).map_err(|e| e.to_string())?;
+    Ok(restored)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn restore_from_trash(_paths: &[String]) -> Result<Vec<String>, String> {
+    Err("Restoring from the Trash is not supported on this platform".to_string())
+}
+
+/// One item sitting in the trash
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Platform identifier, passed back to restore or purge the item
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    /// Seconds since the Unix epoch; None when the trash didn't record it
+    pub deleted_at: Option<u64>,
+    pub size_bytes: u64,
+    pub is_dir: bool,
+    /// Volume the item was deleted from
+    pub mount_point: Option<String>,
+}
+
+/// The longest mount point `path` lies under
+fn mount_for<'a>(path: &Path, mounts: &'a [String]) -> Option<&'a str> {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(mount.as_str()))
+        .max_by_key(|mount| mount.len())
+        .map(String::as_str)
+}
+
+/// Items in the trash, newest first, optionally only those deleted from the volume
+/// mounted at `mount_point`
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn list_trash(mount_point: Option<&str>) -> Result<Vec<TrashEntry>, String> {
+    let mounts: Vec<String> = sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|disk| disk.mount_point().to_string_lossy().to_string())
+        .collect();
+    let mut entries: Vec<TrashEntry> = trash::os_limited::list()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|item| {
+            let mount = mount_for(&item.original_parent, &mounts);
+            if mount_point.is_some_and(|wanted| mount != Some(wanted)) {
+                return None;
+            }
+            Some(trash_entry(item, mount))
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    Ok(entries)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn list_trash(_mount_point: Option<&str>) -> Result<Vec<TrashEntry>, String> {
+    Err("Listing the Trash is not supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn trash_entry(item: &trash::TrashItem, mount_point: Option<&str>) -> TrashEntry {
+    let size = trash::os_limited::metadata(item).ok().map(|metadata| metadata.size);
+    TrashEntry {
+        id: item.id.to_string_lossy().to_string(),
+        name: item.name.to_string_lossy().to_string(),
+        original_path: item.original_path().to_string_lossy().to_string(),
+        deleted_at: u64::try_from(item.time_deleted).ok(),
+        // Folders report an entry count rather than bytes, so measure them where they sit
+        size_bytes: match size {
+            Some(trash::TrashItemSize::Bytes(bytes)) => bytes,
+            Some(trash::TrashItemSize::Entries(_)) => trashed_folder_size(item).unwrap_or(0),
+            None => 0,
+        },
+        is_dir: matches!(size, Some(trash::TrashItemSize::Entries(_))),
+        mount_point: mount_point.map(str::to_string),
+    }
+}
+
+/// Where the trash keeps a folder's contents: next to the .trashinfo file on Linux,
+/// and the parsing name itself on Windows
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn trashed_folder_size(item: &trash::TrashItem) -> Option<u64> {
+    let id = Path::new(&item.id);
+    let stored = if cfg!(target_os = "windows") {
+        id.to_path_buf()
+    } else {
+        let info_dir = id.parent()?;
+        info_dir.parent()?.join("files").join(id.file_stem()?)
+    };
+    Some(crate::scan::delete::measure_path(&stored).bytes)
+}
+
+/// The trash items with the given ids; ids no longer in the trash are left out
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn trash_items(ids: &[String]) -> Result<Vec<trash::TrashItem>, String> {
+    let wanted: std::collections::HashSet<&str> = ids.iter().map(String::as_str).collect();
+    Ok(trash::os_limited::list()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|item| wanted.contains(item.id.to_string_lossy().as_ref()))
+        .collect())
+}
+
+/// Put the chosen trash items back where they were. Returns their original paths.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn restore_trash_items(ids: &[String]) -> Result<Vec<String>, String> {
+    let items = trash_items(ids)?;
+    let restored = items
+        .iter()
+        .map(|item| item.original_path().to_string_lossy().to_string())
+        .collect();
+    trash::os_limited::restore_all(items).map_err(|e| e.to_string())?;
+    Ok(restored)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn restore_trash_items(_ids: &[String]) -> Result<Vec<String>, String> {
+    Err("Restoring from the Trash is not supported on this platform".to_string())
+}
+
+/// Permanently delete the chosen trash items. Returns what was purged; the platform
+/// APIs don't report which items failed, so an error covers them all.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn purge_trash_items(ids: &[String]) -> Result<Vec<TrashEntry>, String> {
+    let items = trash_items(ids)?;
+    let purged = items.iter().map(|item| trash_entry(item, None)).collect();
+    trash::os_limited::purge_all(&items).map_err(|e| e.to_string())?;
+    Ok(purged)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn purge_trash_items(_ids: &[String]) -> Result<Vec<TrashEntry>, String> {
+    Err("Purging the Trash is not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reg_query_output() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\BitBucket\\Volume\\{abc}\r\n    MaxCapacity    REG_DWORD    0x3e8\r\n\r\n";
+        assert_eq!(parse_reg_dword(output, "MaxCapacity"), Some(1000));
+        assert_eq!(parse_reg_dword(output, "NukeOnDelete"), None);
+    }
+
+    #[test]
+    fn trash_items_belong_to_the_deepest_mount() {
+        let mounts = vec!["/".to_string(), "/home".to_string(), "/media/usb".to_string()];
+        assert_eq!(mount_for(Path::new("/home/anna/Documents"), &mounts), Some("/home"));
+        assert_eq!(mount_for(Path::new("/media/usb/photos"), &mounts), Some("/media/usb"));
+        assert_eq!(mount_for(Path::new("/homework"), &mounts), Some("/"));
+        assert_eq!(mount_for(Path::new("relative"), &mounts), None);
+    }
+
+    #[test]
+    fn oversized_items_do_not_fit() {
+        let capacity = TrashCapacity {
+            mount_point: "C:\\".to_string(),
+            max_bytes: Some(100),
+            max_is_default: true,
+            used_bytes: Some(0),
+            bypasses_trash: false,
+        };
+        assert!(capacity.fits(100));
+        assert!(!capacity.fits(101));
+        assert!(!TrashCapacity { bypasses_trash: true, ..capacity }.fits(1));
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::scan::agent::format_bytes;
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::tree::ScanTree;
+
+const TOP_CHILDREN: usize = 10;
+const TOP_EXTENSIONS: usize = 10;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+/// Everything a node report shows, gathered once and rendered per format
+struct NodeFacts<'a> {
+    node: &'a TreeNode,
+    files: u64,
+    dirs: u64,
+    children: Vec<&'a TreeNode>,
+    /// (extension, bytes, files), largest first, with the tail folded into "other"
+    extensions: Vec<(String, u64, u64)>,
+}
+
+/// A clipboard-ready summary of a node: its size, largest children, and extension mix
+pub fn node_report(tree: &ScanTree, node_id: NodeId, format: ReportFormat) -> Result<String, String> {
+    let node = tree.get(node_id).ok_or_else(|| format!("Node {} not found", node_id))?;
+    let (files, dirs) = tree.subtree_counts(node_id).unwrap_or_default();
+    let mut children: Vec<&TreeNode> = node.children.iter().filter_map(|id| tree.get(*id)).collect();
+    children.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.name.cmp(&b.name)));
+    children.truncate(TOP_CHILDREN);
+    let facts = NodeFacts {
+        node,
+        files,
+        // The node itself isn't one of its own subfolders
+        dirs: dirs.saturating_sub(u64::from(node.kind == NodeKind::Dir)),
+        children,
+        extensions: extension_mix(tree, node_id),
+    };
+    Ok(match format {
+        ReportFormat::Text => render_text(&facts),
+        ReportFormat::Markdown => render_markdown(&facts),
+        ReportFormat::Csv => render_csv(&facts),
+    })
+}
+
+fn extension_mix(tree: &ScanTree, node_id: NodeId) -> Vec<(String, u64, u64)> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = tree.get(id) else {
+            continue;
+        };
+        match node.kind {
+            NodeKind::Dir => stack.extend(node.children.iter().copied()),
+            NodeKind::File => {
+                let ext = node.file_ext.clone().unwrap_or_else(|| "(none)".to_string());
+                let entry = totals.entry(ext).or_default();
+                entry.0 += node.size_bytes;
+                entry.1 += 1;
+            }
+        }
+    }
+    let mut mix: Vec<(String, u64, u64)> = totals.into_iter().map(|(ext, (bytes, n))| (ext, bytes, n)).collect();
+    mix.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if mix.len() > TOP_EXTENSIONS {
+        let (bytes, count) = mix
+            .drain(TOP_EXTENSIONS..)
+            .fold((0, 0), |(bytes, count), (_, b, n)| (bytes + b, count + n));
+        mix.push(("other".to_string(), bytes, count));
+    }
+    mix
+}
+
+fn share(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+fn display_name(node: &TreeNode) -> String {
+    match node.kind {
+        NodeKind::Dir => format!("{}/", node.name),
+        NodeKind::File => node.name.clone(),
+    }
+}
+
+fn render_text(facts: &NodeFacts) -> String {
+    let total = facts.node.size_bytes;
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", facts.node.path);
+    let _ = writeln!(
+        out,
+        "{} in {} files, {} folders",
+        format_bytes(total),
+        facts.files,
+        facts.dirs
+    );
+    if !facts.children.is_empty() {
+        let _ = writeln!(out, "\nLargest items:");
+        for child in &facts.children {
+            let _ = writeln!(
+                out,
+                "  {:>10}  {:>5.1}%  {}",
+                format_bytes(child.size_bytes),
+                share(child.size_bytes, total),
+                display_name(child)
+            );
+        }
+    }
+    if !facts.extensions.is_empty() {
+        let _ = writeln!(out, "\nBy extension:");
+        for (ext, bytes, count) in &facts.extensions {
+            let _ = writeln!(
+                out,
+                "  {:>10}  {:>5.1}%  {} ({} files)",
+                format_bytes(*bytes),
+                share(*bytes, total),
+                ext,
+                count
+            );
+        }
+    }
+    out
+}
+
+fn render_markdown(facts: &NodeFacts) -> String {
+    let total = facts.node.size_bytes;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "**{}**: {} in {} files, {} folders",
+        escape_markdown(&facts.node.path),
+        format_bytes(total),
+        facts.files,
+        facts.dirs
+    );
+    if !facts.children.is_empty() {
+        let _ = writeln!(out, "\n| Largest items | Size | Share |\n|---|---:|---:|");
+        for child in &facts.children {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {:.1}% |",
+                escape_markdown(&display_name(child)),
+                format_bytes(child.size_bytes),
+                share(child.size_bytes, total)
+            );
+        }
+    }
+    if !facts.extensions.is_empty() {
+        let _ = writeln!(out, "\n| Extension | Size | Files |\n|---|---:|---:|");
+        for (ext, bytes, count) in &facts.extensions {
+            let _ = writeln!(out, "| {} | {} | {} |", escape_markdown(ext), format_bytes(*bytes), count);
+        }
+    }
+    out
+}
+
+/// One table with a section column, so the snippet pastes straight into a spreadsheet.
+/// Sizes are raw bytes.
+fn render_csv(facts: &NodeFacts) -> String {
+    let total = facts.node.size_bytes;
+    let mut out = String::from("section,name,bytes,share_percent,files\n");
+    let _ = writeln!(
+        out,
+        "node,{},{},100.0,{}",
+        escape_csv(&facts.node.path),
+        total,
+        facts.files
+    );
+    for child in &facts.children {
+        let files = match child.kind {
+            NodeKind::File => "1".to_string(),
+            NodeKind::Dir => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            "child,{},{},{:.1},{}",
+            escape_csv(&display_name(child)),
+            child.size_bytes,
+            share(child.size_bytes, total),
+            files
+        );
+    }
+    for (ext, bytes, count) in &facts.extensions {
+        let _ = writeln!(
+            out,
+            "extension,{},{},{:.1},{}",
+            escape_csv(ext),
+            bytes,
+            share(*bytes, total),
+            count
+        );
+    }
+    out
+}
+
+fn escape_markdown(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace('*', "\\*").replace('_', "\\_")
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            file_ext: path.rsplit_once('.').map(|(_, ext)| ext.to_string()),
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn csv_report_lists_children_and_extensions() {
+        let nodes = vec![
+            node(1, None, "/data", NodeKind::Dir, 100, vec![2, 3]),
+            node(2, Some(1), "/data/photos", NodeKind::Dir, 75, vec![4]),
+            node(3, Some(1), "/data/a,b.txt", NodeKind::File, 25, vec![]),
+            node(4, Some(2), "/data/photos/img.jpg", NodeKind::File, 75, vec![]),
+        ];
+        let paths = nodes.iter().map(|n| (n.path.clone(), n.id)).collect();
+        let tree = ScanTree::new(1, nodes.into_iter().map(|n| (n.id, n)).collect(), paths);
+
+        let csv = node_report(&tree, 1, ReportFormat::Csv).expect("report");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "node,/data,100,100.0,2");
+        assert_eq!(lines[2], "child,photos/,75,75.0,");
+        assert_eq!(lines[3], "child,\"a,b.txt\",25,25.0,1");
+        assert_eq!(lines[4], "extension,jpg,75,75.0,1");
+        assert!(node_report(&tree, 9, ReportFormat::Text).is_err());
+    }
+}
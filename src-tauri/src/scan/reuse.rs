@@ -0,0 +1,373 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::analyzer::{AnalyzerSet, FileVisit};
+use crate::scan::engine::is_case_insensitive;
+use crate::scan::model::{
+    AnalyzerReport, NodeId, NodeKind, RootOverlap, ScanOptions, ScanResult, SkipGroup, TreeNode,
+};
+use crate::scan::roots;
+use crate::scan::state::AppState;
+use crate::scan::tree::{PathIndex, ScanTree};
+
+/// How old a covering scan may be before it is no longer offered for reuse
+pub const DEFAULT_REUSE_MAX_AGE_SECS: u64 = 60 * 60;
+/// Directories whose modification time is checked when validating a reused subtree;
+/// the largest are checked first
+const MAX_VALIDATED_DIRS: usize = 5000;
+
+/// Asks start_scan to answer from a fresh stored scan that covers the requested root
+/// instead of walking it again
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScanReuse {
+    /// Check directory modification times against the stored scan and walk anyway
+    /// when any changed since
+    #[serde(default)]
+    pub validate: bool,
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+}
+
+/// A stored scan whose tree contains a requested path
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CoveringScan {
+    pub scan_id: String,
+    pub root_path: String,
+    /// The node for the requested path in the stored tree
+    pub node_id: NodeId,
+    pub path: String,
+    pub total_bytes: u64,
+    pub scanned_at: u64,
+    pub age_secs: u64,
+}
+
+/// The newest stored scan, no older than `max_age_secs`, whose tree holds `path` and
+/// was walked with options that produce the same tree as `options`
+pub fn find_covering_scan(
+    state: &AppState,
+    path: &Path,
+    options: &ScanOptions,
+    max_age_secs: u64,
+) -> Option<CoveringScan> {
+    let path_str = path.to_string_lossy().to_string();
+    let held = state.scan_roots();
+    let candidates = roots::find_overlaps(
+        &path_str,
+        held.iter().map(|(id, root)| (id.as_str(), root.as_str())),
+        is_case_insensitive(path),
+    );
+    let now = now_millis();
+    candidates
+        .into_iter()
+        .filter(|overlap| matches!(overlap.relation, RootOverlap::Same | RootOverlap::Within))
+        .filter_map(|overlap| {
+            let result = state.get_result(&overlap.scan_id)?;
+            let age_secs = now.saturating_sub(result.started_at) / 1000;
+            if !result.complete || result.started_at == 0 || age_secs > max_age_secs || !options_compatible(&result.options, options) {
+                return None;
+            }
+            let node = state
+                .with_tree(&overlap.scan_id, |tree| {
+                    tree.find_by_path(&path_str).map(|node| (node.id, node.path.clone(), node.size_bytes))
+                })
+                .ok()
+                .flatten()?;
+            Some(CoveringScan {
+                scan_id: overlap.scan_id,
+                root_path: overlap.root_path,
+                node_id: node.0,
+                path: node.1,
+                total_bytes: node.2,
+                scanned_at: result.started_at,
+                age_secs,
+            })
+        })
+        .max_by_key(|covering| covering.scanned_at)
+}
+
+/// Whether a tree walked with `stored` has the same contents below any of its
+/// directories as a walk with `requested`
+fn options_compatible(stored: &ScanOptions, requested: &ScanOptions) -> bool {
+    stored.max_depth.is_none()
+        && requested.max_depth.is_none()
+        && stored.follow_symlinks == requested.follow_symlinks
+        && stored.one_file_system == requested.one_file_system
+        && stored.exclude_patterns == requested.exclude_patterns
+        // A tree built from a file list says nothing about what is on the disk
+        && stored.file_list.is_none()
+        && requested.file_list.is_none()
+}
+
+/// Copy the subtree below `node_id` into a tree of its own, rooted at that node
+pub fn extract_subtree(tree: &ScanTree, node_id: NodeId) -> Option<ScanTree> {
+    tree.get(node_id)?;
+    let mut nodes = HashMap::new();
+    let mut index = PathIndex::new(tree.case_insensitive());
+    let mut stack = vec![node_id];
+    while let Some(id) = stack.pop() {
+        let Some(node) = tree.get(id) else {
+            continue;
+        };
+        let mut node = node.clone();
+        if id == node_id {
+            node.parent = None;
+        }
+        stack.extend(node.children.iter().copied());
+        index.insert(&node.path, id);
+        nodes.insert(id, node);
+    }
+    Some(ScanTree::with_index(node_id, nodes, index))
+}
+
+/// Directories of `tree` modified on disk (or gone) since `since_millis`, checking the
+/// largest directories first and at most `MAX_VALIDATED_DIRS` of them. A directory's
+/// modification time moves when entries are added, removed, or renamed in it.
+pub fn changed_dirs(tree: &ScanTree, since_millis: u64) -> Vec<String> {
+    let mut dirs: Vec<&TreeNode> = tree.nodes().filter(|node| node.kind == NodeKind::Dir).collect();
+    dirs.sort_by_key(|node| std::cmp::Reverse(node.size_bytes));
+    dirs.into_iter()
+        .take(MAX_VALIDATED_DIRS)
+        .filter(|node| {
+            let modified = std::fs::metadata(&node.path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_millis() as u64);
+            modified.is_none_or(|modified| modified > since_millis)
+        })
+        .map(|node| node.path.clone())
+        .collect()
+}
+
+/// Build the result of a scan answered from `subtree`, replaying its files through the
+/// requested analyzers so every report covers just the subtree
+pub fn reused_result(scan_id: &str, source: &ScanResult, subtree: &ScanTree, options: &ScanOptions) -> ScanResult {
+    let mut analyzers = AnalyzerSet::from_configs(&options.analyzers);
+    let (mut total_files, mut total_dirs) = (0, 0);
+    // Depth-first with an explicit exit marker, so each directory completes after its
+    // descendants as it does during a walk
+    let mut stack: Vec<(NodeId, bool)> = vec![(subtree.root_id, false)];
+    while let Some((id, exiting)) = stack.pop() {
+        let Some(node) = subtree.get(id) else {
+            continue;
+        };
+        match node.kind {
+            NodeKind::File => {
+                total_files += 1;
+                let modified = node
+                    .modified
+                    .map(|secs| UNIX_EPOCH + std::time::Duration::from_secs(secs));
+                analyzers.on_file(&FileVisit {
+                    node,
+                    modified,
+                    metadata: None,
+                });
+            }
+            NodeKind::Dir if exiting => analyzers.on_dir_complete(node),
+            NodeKind::Dir => {
+                total_dirs += 1;
+                stack.push((id, true));
+                stack.extend(node.children.iter().map(|child| (*child, false)));
+            }
+        }
+    }
+
+    let mut result = ScanResult {
+        scan_id: scan_id.to_string(),
+        root_id: subtree.root_id,
+        total_bytes: subtree.root().map(|root| root.size_bytes).unwrap_or(0),
+        total_files,
+        total_dirs,
+        extension_stats: Vec::new(),
+        size_distribution: Vec::new(),
+        analyzer_reports: Vec::new(),
+        skipped_paths: within(&source.skipped_paths, subtree),
+        error_paths: within(&source.error_paths, subtree),
+        // Only the sampled paths are known per folder, so counts shrink to those
+        skipped: source
+            .skipped
+            .iter()
+            .filter_map(|group| {
+                let sample = within(&group.sample, subtree);
+                (!sample.is_empty()).then_some(SkipGroup {
+                    reason: group.reason,
+                    count: sample.len() as u64,
+                    sample,
+                })
+            })
+            .collect(),
+        error_groups: Vec::new(),
+        root_aliases: Vec::new(),
+        started_at: source.started_at,
+        options: options.clone(),
+        reused_from: Some(source.scan_id.clone()),
+        complete: true,
+        incomplete_dirs: Vec::new(),
+        unreadable_regions: source
+            .unreadable_regions
+            .iter()
+            .filter(|region| !within(std::slice::from_ref(&region.path), subtree).is_empty())
+            .cloned()
+            .collect(),
+        limit_actions: Vec::new(),
+        slow_dirs: Vec::new(),
+    };
+    for report in analyzers.finalize() {
+        match report {
+            AnalyzerReport::ExtensionStats { stats } => result.extension_stats = stats,
+            AnalyzerReport::SizeDistribution { buckets } => result.size_distribution = buckets,
+            other => result.analyzer_reports.push(other),
+        }
+    }
+    result
+}
+
+/// The paths that lie inside the subtree's root
+fn within(paths: &[String], subtree: &ScanTree) -> Vec<String> {
+    let Some(root) = subtree.root() else {
+        return Vec::new();
+    };
+    let root_key = if subtree.case_insensitive() { root.path.to_lowercase() } else { root.path.clone() };
+    paths
+        .iter()
+        .filter(|path| {
+            let key = if subtree.case_insensitive() { path.to_lowercase() } else { path.to_string() };
+            key.strip_prefix(root_key.trim_end_matches(['/', '\\']))
+                .is_some_and(|rest| rest.starts_with(['/', '\\']))
+        })
+        .cloned()
+        .collect()
+}
+
+/// What a resumed scan takes over from a canceled one: its tree, and the folders it
+/// read completely just below the ones it left unfinished. The new walk skips those
+/// folders and copies them from the old tree.
+#[derive(Debug)]
+pub struct ResumeSeed {
+    pub scan_id: String,
+    tree: ScanTree,
+    finished: PathIndex,
+}
+
+impl ResumeSeed {
+    pub fn new(scan_id: &str, result: &ScanResult, tree: ScanTree) -> Self {
+        let mut incomplete = PathIndex::new(tree.case_insensitive());
+        for path in &result.incomplete_dirs {
+            incomplete.insert(path, 0);
+        }
+        let mut finished = PathIndex::new(tree.case_insensitive());
+        for node in tree.nodes().filter(|node| node.kind == NodeKind::Dir) {
+            let parent_unfinished = node
+                .parent
+                .and_then(|parent| tree.get(parent))
+                .is_some_and(|parent| incomplete.contains(&parent.path));
+            if parent_unfinished && !incomplete.contains(&node.path) {
+                finished.insert(&node.path, node.id);
+            }
+        }
+        Self {
+            scan_id: scan_id.to_string(),
+            tree,
+            finished,
+        }
+    }
+
+    /// The old tree's node for `path` when it is a folder the new walk can skip
+    pub fn finished_dir(&self, path: &Path) -> Option<NodeId> {
+        self.finished.get(&path.to_string_lossy())
+    }
+
+    pub fn tree(&self) -> &ScanTree {
+        &self.tree
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        TreeNode {
+            id,
+            parent,
+            file_ext: (kind == NodeKind::File)
+                .then(|| name.rsplit_once('.').map(|(_, ext)| ext.to_string()))
+                .flatten(),
+            name,
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn answers_a_subfolder_from_the_covering_tree() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/c", NodeKind::Dir, 70, vec![2, 5]),
+                node(2, Some(1), "/c/users", NodeKind::Dir, 60, vec![3, 4]),
+                node(3, Some(2), "/c/users/a.txt", NodeKind::File, 40, vec![]),
+                node(4, Some(2), "/c/users/b.png", NodeKind::File, 20, vec![]),
+                node(5, Some(1), "/c/other.txt", NodeKind::File, 10, vec![]),
+            ],
+            false,
+        );
+        let subtree = extract_subtree(&tree, 2).expect("subtree");
+        assert_eq!(subtree.len(), 3);
+        assert_eq!(subtree.root().and_then(|root| root.parent), None);
+        assert!(subtree.find_by_path("/c/other.txt").is_none());
+
+        let source = ScanResult {
+            scan_id: "full".to_string(),
+            root_id: 1,
+            total_bytes: 70,
+            total_files: 3,
+            total_dirs: 2,
+            extension_stats: Vec::new(),
+            size_distribution: Vec::new(),
+            analyzer_reports: Vec::new(),
+            skipped_paths: Vec::new(),
+            error_paths: vec!["/c/users/locked".to_string(), "/c/usersx".to_string()],
+            skipped: Vec::new(),
+            error_groups: Vec::new(),
+            root_aliases: Vec::new(),
+            started_at: 5,
+            options: ScanOptions::default(),
+            reused_from: None,
+            complete: true,
+            incomplete_dirs: Vec::new(),
+            unreadable_regions: Vec::new(),
+            limit_actions: Vec::new(),
+            slow_dirs: Vec::new(),
+        };
+        let result = reused_result("sub", &source, &subtree, &ScanOptions::default());
+        assert_eq!((result.total_bytes, result.total_files, result.total_dirs), (60, 2, 1));
+        assert_eq!(result.error_paths, vec!["/c/users/locked".to_string()]);
+        assert_eq!(result.reused_from.as_deref(), Some("full"));
+        let mut exts: Vec<&str> = result.extension_stats.iter().map(|stat| stat.ext.as_str()).collect();
+        exts.sort_unstable();
+        assert_eq!(exts, vec!["png", "txt"]);
+
+        assert!(!options_compatible(
+            &ScanOptions {
+                max_depth: Some(3),
+                ..ScanOptions::default()
+            },
+            &ScanOptions::default()
+        ));
+    }
+}
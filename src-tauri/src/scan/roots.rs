@@ -0,0 +1,212 @@
+use std::path::{Path, PathBuf};
+
+use crate::scan::model::{OverlapDecision, RootOverlap, ScanOverlap};
+
+/// Drop the `\\?\` prefix canonicalize puts on Windows paths, turning `\\?\UNC\srv\share`
+/// into `\\srv\share` and `\\?\C:\dir` into `C:\dir`, so the same location always
+/// prints the same way
+pub fn strip_verbatim(path: &str) -> String {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else if let Some(local) = path.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Stored scans whose roots equal, contain, or lie inside `root`. Both sides are expected
+/// in canonical form; `case_insensitive` follows the filesystem holding `root`.
+pub fn find_overlaps<'a>(
+    root: &str,
+    existing: impl IntoIterator<Item = (&'a str, &'a str)>,
+    case_insensitive: bool,
+) -> Vec<ScanOverlap> {
+    let fold = |path: &str| {
+        let trimmed = path.trim_end_matches(['/', '\\']);
+        if case_insensitive {
+            trimmed.to_lowercase()
+        } else {
+            trimmed.to_string()
+        }
+    };
+    let requested = fold(root);
+    let mut overlaps: Vec<ScanOverlap> = existing
+        .into_iter()
+        .filter_map(|(scan_id, root_path)| {
+            let other = fold(root_path);
+            let relation = if other == requested {
+                RootOverlap::Same
+            } else if is_below(&other, &requested) {
+                RootOverlap::Contains
+            } else if is_below(&requested, &other) {
+                RootOverlap::Within
+            } else {
+                return None;
+            };
+            Some(ScanOverlap {
+                scan_id: scan_id.to_string(),
+                root_path: root_path.to_string(),
+                relation,
+            })
+        })
+        .collect();
+    overlaps.sort_by(|a, b| a.root_path.cmp(&b.root_path).then_with(|| a.scan_id.cmp(&b.scan_id)));
+    overlaps
+}
+
+/// Decide how a new scan runs beside `running`, its overlaps with scans still in
+/// progress. A root already being walked, or lying inside one, waits for that scan; a
+/// root containing running scans leaves their folders out.
+pub fn overlap_decision(running: Vec<ScanOverlap>) -> OverlapDecision {
+    if let Some(covering) = running
+        .iter()
+        .filter(|overlap| matches!(overlap.relation, RootOverlap::Same | RootOverlap::Within))
+        .min_by_key(|overlap| overlap.root_path.len())
+    {
+        return OverlapDecision::Queued {
+            behind: covering.scan_id.clone(),
+            root_path: covering.root_path.clone(),
+        };
+    }
+    let inside: Vec<ScanOverlap> = running
+        .into_iter()
+        .filter(|overlap| overlap.relation == RootOverlap::Contains)
+        .collect();
+    if inside.is_empty() {
+        return OverlapDecision::Run;
+    }
+    let mut excluded_paths: Vec<String> = inside.iter().map(|overlap| overlap.root_path.clone()).collect();
+    excluded_paths.sort();
+    excluded_paths.dedup();
+    OverlapDecision::CarvedOut {
+        scans: inside,
+        excluded_paths,
+    }
+}
+
+/// An exclude pattern, anchored at `root`, matching the folder `path` below it
+pub fn carve_out_pattern(root: &str, path: &str) -> Option<String> {
+    let root = root.trim_end_matches(['/', '\\']);
+    let relative = path.get(root.len()..)?.trim_matches(['/', '\\']);
+    if relative.is_empty() {
+        return None;
+    }
+    let escaped: String = relative
+        .chars()
+        .flat_map(|c| match c {
+            '\\' => vec!['/'],
+            '*' | '?' | '[' | ']' | '!' | '#' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect();
+    Some(format!("/{}/", escaped))
+}
+
+fn is_below(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.starts_with(['/', '\\']) || ancestor.is_empty())
+}
+
+/// Resolve a subst'd or mapped drive letter to the folder or share it points at, for
+/// when canonicalize can't open the path (a disconnected share, or a root the process
+/// can't read). Returns None for ordinary drive letters.
+#[cfg(target_os = "windows")]
+pub fn resolve_drive_mapping(path: &Path) -> Option<PathBuf> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::NetworkManagement::WNet::WNetGetConnectionW;
+    use windows_sys::Win32::Storage::FileSystem::QueryDosDeviceW;
+
+    let text = path.to_string_lossy();
+    let mut chars = text.chars();
+    let (Some(letter), Some(':')) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    if !letter.is_ascii_alphabetic() {
+        return None;
+    }
+    let rest = text[2..].trim_start_matches(['/', '\\']);
+    let drive: Vec<u16> = std::ffi::OsStr::new(&format!("{}:", letter))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut remote = vec![0u16; 1024];
+    let mut remote_len = remote.len() as u32;
+    // SAFETY: both buffers are null-terminated or sized as passed
+    let status = unsafe { WNetGetConnectionW(drive.as_ptr(), remote.as_mut_ptr(), &mut remote_len) };
+    if status == 0 {
+        let end = remote.iter().position(|c| *c == 0).unwrap_or(remote.len());
+        let share = PathBuf::from(String::from_utf16_lossy(&remote[..end]));
+        return Some(if rest.is_empty() { share } else { share.join(rest) });
+    }
+
+    let mut target = vec![0u16; 1024];
+    // SAFETY: the buffer length passed matches the allocation
+    let written = unsafe { QueryDosDeviceW(drive.as_ptr(), target.as_mut_ptr(), target.len() as u32) };
+    if written == 0 {
+        return None;
+    }
+    let end = target.iter().position(|c| *c == 0).unwrap_or(target.len());
+    let device = String::from_utf16_lossy(&target[..end]);
+    // subst targets read `\??\C:\folder`; real volumes read `\Device\HarddiskVolumeN`
+    let folder = device.strip_prefix(r"\??\")?;
+    let folder = PathBuf::from(strip_verbatim(folder));
+    Some(if rest.is_empty() { folder } else { folder.join(rest) })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn resolve_drive_mapping(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_verbatim_prefixes_and_finds_overlaps() {
+        assert_eq!(strip_verbatim(r"\\?\UNC\nas\media\films"), r"\\nas\media\films");
+        assert_eq!(strip_verbatim(r"\\?\C:\Users"), r"C:\Users");
+        assert_eq!(strip_verbatim("/home/me"), "/home/me");
+
+        let existing = [
+            ("a", r"\\nas\media"),
+            ("b", r"\\NAS\Media\Films\2019"),
+            ("c", r"\\nas\media\films"),
+            ("d", r"\\nas\media\filmsold"),
+        ];
+        let overlaps = find_overlaps(r"\\nas\media\Films\", existing, true);
+        let relations: Vec<(&str, RootOverlap)> =
+            overlaps.iter().map(|o| (o.scan_id.as_str(), o.relation)).collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("b", RootOverlap::Contains),
+                ("a", RootOverlap::Within),
+                ("c", RootOverlap::Same),
+            ]
+        );
+        assert!(find_overlaps("/data/Films", [("x", "/data/films")], false).is_empty());
+    }
+
+    #[test]
+    fn narrower_scans_queue_and_broader_scans_carve_out() {
+        let running = [("all", r"C:\"), ("users", r"C:\Users")];
+        match overlap_decision(find_overlaps(r"C:\Users\me", running, true)) {
+            OverlapDecision::Queued { behind, root_path } => {
+                assert_eq!((behind.as_str(), root_path.as_str()), ("all", r"C:\"))
+            }
+            other => panic!("unexpected decision {:?}", other),
+        }
+
+        let running = [("users", r"C:\Users"), ("games", r"D:\Games")];
+        match overlap_decision(find_overlaps(r"C:\", running, true)) {
+            OverlapDecision::CarvedOut { excluded_paths, .. } => assert_eq!(excluded_paths, vec![r"C:\Users"]),
+            other => panic!("unexpected decision {:?}", other),
+        }
+        assert_eq!(carve_out_pattern(r"C:\", r"C:\Users\[old]"), Some(r"/Users/\[old\]/".to_string()));
+        assert_eq!(carve_out_pattern("/data", "/data"), None);
+        assert!(matches!(overlap_decision(Vec::new()), OverlapDecision::Run));
+    }
+}
@@ -0,0 +1,509 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::scan::crashdumps::crash_dump_kind;
+use crate::scan::delete::SafetyLevel;
+use crate::scan::known_folders::{is_inside, named_known_folders};
+use crate::scan::state::AppState;
+
+pub const EVENT_RULES_RELOADED: &str = "rules://reloaded";
+/// Subfolder of the app data dir holding user rule packs
+pub const RULES_DIR: &str = "rules";
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Protected locations never get deeper than this many path components
+const TOP_LEVEL_DEPTH: usize = 3;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+const BUILTIN_PACK: &str = include_str!("../../rules/default.json");
+
+/// What a rule's pattern is compared against (always case-insensitive)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchType {
+    /// File extension without the leading dot
+    Extension,
+    /// Exact name of a file or folder
+    FileName,
+    /// Exact name of a folder
+    FolderName,
+    /// A folder within the first few components of a path, e.g. C:\Windows
+    TopLevelFolder,
+    /// Anything below a folder with this name, at any depth
+    InsideFolder,
+    /// Anything below a folder the OS names, wherever it is: `temp` for the temp folders
+    InsideKnownFolder,
+}
+
+/// Restricts a rule to files or to folders
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    File,
+    Folder,
+}
+
+/// Facts about an entry that conditional rules look at, read once per classification
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EntryFacts {
+    pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+}
+
+impl EntryFacts {
+    pub fn of(path: &Path) -> Self {
+        let metadata = fs::metadata(path).ok();
+        Self {
+            is_dir: metadata.as_ref().is_some_and(|m| m.is_dir()),
+            modified: metadata.and_then(|m| m.modified().ok()),
+        }
+    }
+
+    fn age(&self) -> Option<Duration> {
+        self.modified
+            .map(|modified| SystemTime::now().duration_since(modified).unwrap_or_default())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    Windows,
+    Macos,
+    Linux,
+}
+
+impl Platform {
+    fn is_current(self) -> bool {
+        match self {
+            Platform::Windows => cfg!(target_os = "windows"),
+            Platform::Macos => cfg!(target_os = "macos"),
+            Platform::Linux => cfg!(target_os = "linux"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub pattern: String,
+    #[serde(rename = "match")]
+    pub match_type: MatchType,
+    pub level: SafetyLevel,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Only applies on this platform; None applies everywhere
+    #[serde(default)]
+    pub platform: Option<Platform>,
+    /// Only applies in developer mode, ahead of the standard rules
+    #[serde(default)]
+    pub developer_mode: bool,
+    /// How to regenerate what the rule matches (for ReclaimableBuild rules)
+    #[serde(default)]
+    pub hint: Option<String>,
+    /// Only matches files or only folders
+    #[serde(default)]
+    pub entry: Option<EntryKind>,
+    /// Only matches entries last modified more than this many days ago
+    #[serde(default)]
+    pub older_than_days: Option<u32>,
+    /// Only matches entries modified within this many days
+    #[serde(default)]
+    pub newer_than_days: Option<u32>,
+}
+
+impl Rule {
+    fn matches(&self, path: &Path, entry: &EntryFacts) -> bool {
+        let kind_matches = match self.entry {
+            Some(EntryKind::File) => !entry.is_dir,
+            Some(EntryKind::Folder) => entry.is_dir,
+            None => true,
+        };
+        kind_matches && self.pattern_matches(path, entry.is_dir) && self.age_matches(entry)
+    }
+
+    fn pattern_matches(&self, path: &Path, is_dir: bool) -> bool {
+        match self.match_type {
+            MatchType::Extension => path
+                .extension()
+                .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case(&self.pattern)),
+            MatchType::FileName => file_name_is(path, &self.pattern),
+            MatchType::FolderName => is_dir && file_name_is(path, &self.pattern),
+            MatchType::TopLevelFolder => {
+                let path_str = path.to_string_lossy().to_lowercase();
+                let parts: Vec<&str> = path_str.split(['/', '\\']).collect();
+                parts.len() <= TOP_LEVEL_DEPTH && parts.iter().any(|p| p.eq_ignore_ascii_case(&self.pattern))
+            }
+            MatchType::InsideFolder => path
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| file_name_is(ancestor, &self.pattern)),
+            MatchType::InsideKnownFolder => named_known_folders(&self.pattern)
+                .is_some_and(|folders| folders.iter().any(|folder| is_inside(path, folder))),
+        }
+    }
+
+    /// Entries of unknown age never satisfy an age condition
+    fn age_matches(&self, entry: &EntryFacts) -> bool {
+        if self.older_than_days.is_none() && self.newer_than_days.is_none() {
+            return true;
+        }
+        let Some(age) = entry.age() else {
+            return false;
+        };
+        self.older_than_days.is_none_or(|days| age > days_to_duration(days))
+            && self.newer_than_days.is_none_or(|days| age < days_to_duration(days))
+    }
+}
+
+fn days_to_duration(days: u32) -> Duration {
+    Duration::from_secs(u64::from(days) * DAY_SECS)
+}
+
+fn file_name_is(path: &Path, pattern: &str) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().to_lowercase() == pattern.to_lowercase())
+}
+
+/// A JSON file of classification rules
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulePack {
+    pub name: String,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// Outcome of loading or validating one rule pack
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RulePackReport {
+    /// None for the pack shipped with the app
+    pub path: Option<String>,
+    pub name: Option<String>,
+    pub rule_count: usize,
+    pub errors: Vec<String>,
+}
+
+impl RulePackReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The rule that decided a path's safety level
+#[derive(Clone, Debug, PartialEq)]
+pub struct Classification {
+    pub level: SafetyLevel,
+    pub reason: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl From<&Rule> for Classification {
+    fn from(rule: &Rule) -> Self {
+        Self {
+            level: rule.level.clone(),
+            reason: rule.reason.clone(),
+            hint: rule.hint.clone(),
+        }
+    }
+}
+
+/// Rules from every loaded pack for the current platform, user packs ahead of the
+/// built-in one so they can override it
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn from_packs(packs: Vec<RulePack>) -> Self {
+        let rules = packs
+            .into_iter()
+            .flat_map(|pack| pack.rules)
+            .filter(|rule| rule.platform.is_none_or(Platform::is_current))
+            .collect();
+        Self { rules }
+    }
+
+    /// The rule set shipped with the app
+    pub fn builtin() -> Arc<RuleSet> {
+        static BUILTIN: OnceLock<Arc<RuleSet>> = OnceLock::new();
+        BUILTIN
+            .get_or_init(|| {
+                let pack: RulePack = serde_json::from_str(BUILTIN_PACK).expect("built-in rule pack is valid");
+                Arc::new(RuleSet::from_packs(vec![pack]))
+            })
+            .clone()
+    }
+
+    /// Load the user packs in `dir` (in file name order) ahead of the built-in pack.
+    /// Invalid packs are skipped and reported.
+    pub fn load(dir: Option<&Path>) -> (RuleSet, Vec<RulePackReport>) {
+        let mut packs = Vec::new();
+        let mut reports = Vec::new();
+        for path in dir.map(pack_files).unwrap_or_default() {
+            let (pack, report) = read_pack(&path);
+            if let Some(pack) = pack.filter(|_| report.is_valid()) {
+                packs.push(pack);
+            }
+            reports.push(report);
+        }
+        let builtin: RulePack = serde_json::from_str(BUILTIN_PACK).expect("built-in rule pack is valid");
+        reports.push(RulePackReport {
+            path: None,
+            name: Some(builtin.name.clone()),
+            rule_count: builtin.rules.len(),
+            errors: Vec::new(),
+        });
+        packs.push(builtin);
+        (RuleSet::from_packs(packs), reports)
+    }
+
+    /// The reason text of every rule that gives one
+    pub fn reasons(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().filter_map(|rule| rule.reason.as_deref())
+    }
+
+    /// The first protected-location rule matching `path`. Crash dumps are never
+    /// protected, even MEMORY.DMP right inside the Windows folder.
+    pub fn protected_match(&self, path: &Path) -> Option<&Rule> {
+        if crash_dump_kind(path).is_some() {
+            return None;
+        }
+        self.rules
+            .iter()
+            .find(|rule| rule.match_type == MatchType::TopLevelFolder && rule.pattern_matches(path, true))
+    }
+
+    /// Classify by rules alone. Protected locations win, then developer rules (in developer
+    /// mode), then location rules, file names, folder names, and extensions. Location rules
+    /// go first so conditions such as age can override what an extension alone suggests.
+    /// None when no rule matches.
+    pub fn classify(&self, path: &Path, entry: &EntryFacts, developer_mode: bool) -> Option<Classification> {
+        if let Some(rule) = self.protected_match(path) {
+            return Some(rule.into());
+        }
+        if developer_mode {
+            if let Some(rule) = self.rules.iter().find(|rule| rule.developer_mode && rule.matches(path, entry)) {
+                return Some(rule.into());
+            }
+        }
+        [MatchType::InsideKnownFolder, MatchType::InsideFolder, MatchType::FileName, MatchType::FolderName, MatchType::Extension]
+            .into_iter()
+            .find_map(|match_type| {
+                self.rules
+                    .iter()
+                    .find(|rule| !rule.developer_mode && rule.match_type == match_type && rule.matches(path, entry))
+            })
+            .map(Classification::from)
+    }
+}
+
+/// The active rules plus the developer mode toggle, as used for one classification pass
+#[derive(Clone)]
+pub struct Classifier {
+    pub rules: Arc<RuleSet>,
+    pub developer_mode: bool,
+}
+
+impl Classifier {
+    pub fn builtin(developer_mode: bool) -> Self {
+        Self {
+            rules: RuleSet::builtin(),
+            developer_mode,
+        }
+    }
+
+    pub fn classify(&self, path: &Path, entry: &EntryFacts) -> Option<Classification> {
+        self.rules.classify(path, entry, self.developer_mode)
+    }
+}
+
+/// `*.json` files in a rule pack dir, sorted by name
+pub fn pack_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn read_pack(path: &Path) -> (Option<RulePack>, RulePackReport) {
+    let mut report = RulePackReport {
+        path: Some(path.to_string_lossy().to_string()),
+        name: None,
+        rule_count: 0,
+        errors: Vec::new(),
+    };
+    let pack = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str::<RulePack>(&contents).map_err(|e| e.to_string()));
+    match pack {
+        Ok(pack) => {
+            report.name = Some(pack.name.clone());
+            report.rule_count = pack.rules.len();
+            report.errors = rule_errors(&pack);
+            (Some(pack), report)
+        }
+        Err(e) => {
+            report.errors.push(e);
+            (None, report)
+        }
+    }
+}
+
+/// Check a rule pack file without loading it
+pub fn validate_rule_pack(path: &Path) -> RulePackReport {
+    read_pack(path).1
+}
+
+/// Problems serde can't catch, such as patterns that could never match
+fn rule_errors(pack: &RulePack) -> Vec<String> {
+    let mut errors = Vec::new();
+    if pack.name.trim().is_empty() {
+        errors.push("Pack name is empty".to_string());
+    }
+    for (i, rule) in pack.rules.iter().enumerate() {
+        let pattern = rule.pattern.trim();
+        if pattern.is_empty() {
+            errors.push(format!("Rule {}: pattern is empty", i + 1));
+        } else if pattern.contains(['/', '\\']) {
+            errors.push(format!("Rule {}: pattern '{}' must be a single name, not a path", i + 1, pattern));
+        } else if rule.match_type == MatchType::InsideKnownFolder && named_known_folders(pattern).is_none() {
+            errors.push(format!("Rule {}: '{}' is not a known folder; use temp", i + 1, pattern));
+        } else if rule.match_type == MatchType::Extension && pattern.starts_with('.') {
+            errors.push(format!("Rule {}: write extension '{}' without the leading dot", i + 1, pattern));
+        }
+        if let (Some(older), Some(newer)) = (rule.older_than_days, rule.newer_than_days) {
+            if older >= newer {
+                errors.push(format!(
+                    "Rule {}: older_than_days {} and newer_than_days {} can never both hold",
+                    i + 1,
+                    older,
+                    newer
+                ));
+            }
+        }
+        if rule.hint.is_some() && rule.level != SafetyLevel::ReclaimableBuild {
+            errors.push(format!("Rule {}: hints only apply to reclaimable_build rules", i + 1));
+        }
+    }
+    errors
+}
+
+/// Names and modification times of the pack files, to notice edits
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    pack_files(dir)
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Reload the rules whenever a pack in the user rules dir is added, changed, or removed,
+/// and emit `rules://reloaded` with the pack reports
+pub fn spawn_rule_watcher(app_handle: AppHandle, state: AppState) {
+    let Some(dir) = state.rules_dir() else {
+        return;
+    };
+    thread::spawn(move || {
+        let mut last = fingerprint(&dir);
+        loop {
+            thread::sleep(RELOAD_CHECK_INTERVAL);
+            let current = fingerprint(&dir);
+            if current != last {
+                last = current;
+                let _ = app_handle.emit(EVENT_RULES_RELOADED, state.reload_rules());
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn builtin_pack_is_valid() {
+        let pack: RulePack = serde_json::from_str(BUILTIN_PACK).expect("parse");
+        assert!(rule_errors(&pack).is_empty());
+        let rules = RuleSet::builtin();
+        let level = |path: &str, is_dir| {
+            let entry = EntryFacts { is_dir, modified: None };
+            rules.classify(Path::new(path), &entry, false).map(|c| c.level)
+        };
+        assert_eq!(level("C:\\Windows", true), Some(SafetyLevel::Protected));
+        assert_eq!(level("/home/me/project/build.log", false), Some(SafetyLevel::AutoDelete));
+        assert_eq!(level("/home/me/report.pdf", false), Some(SafetyLevel::ConfirmRequired));
+        assert_eq!(level("/home/me/project/dist", false), None);
+    }
+
+    #[test]
+    fn user_packs_override_builtin_rules() {
+        let temp = tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("keep-logs.json"),
+            r#"{"name": "Keep logs", "rules": [{"pattern": "log", "match": "extension", "level": "confirm_required", "reason": "Audit logs"}]}"#,
+        )
+        .expect("write");
+        fs::write(temp.path().join("broken.json"), r#"{"name": "Broken", "rules": [{"pattern": ".tmp", "match": "extension", "level": "auto_delete"}]}"#)
+            .expect("write");
+
+        let (rules, reports) = RuleSet::load(Some(temp.path()));
+        assert_eq!(reports.len(), 3);
+        assert!(!reports[0].is_valid());
+        assert!(reports[1].is_valid());
+        let log = rules
+            .classify(Path::new("/var/app/server.log"), &EntryFacts::default(), false)
+            .expect("match");
+        assert_eq!(log.level, SafetyLevel::ConfirmRequired);
+        assert_eq!(log.reason.as_deref(), Some("Audit logs"));
+    }
+
+    #[test]
+    fn temp_files_are_auto_deleted_only_once_old() {
+        let rules = RuleSet::builtin();
+        let aged = |days: u64| EntryFacts {
+            is_dir: false,
+            modified: Some(SystemTime::now() - Duration::from_secs(days * DAY_SECS)),
+        };
+        let path = std::env::temp_dir().join("setup-1234").join("setup.exe");
+        let level = |entry: EntryFacts| rules.classify(&path, &entry, false).map(|c| c.level);
+        assert_eq!(level(aged(30)), Some(SafetyLevel::AutoDelete));
+        assert_eq!(level(aged(0)), Some(SafetyLevel::ConfirmRequired));
+        // Without a known age the extension decides
+        assert_eq!(level(EntryFacts::default()), Some(SafetyLevel::ConfirmRequired));
+        // A folder that is merely named temp is not the OS temp folder
+        let thesis = Path::new("/home/me/Documents/temp/thesis.docx");
+        assert_ne!(rules.classify(thesis, &aged(30), false).map(|c| c.level), Some(SafetyLevel::AutoDelete));
+
+        let dump = Path::new("/Windows/MEMORY.DMP");
+        assert!(rules.protected_match(dump).is_none());
+        assert_eq!(rules.classify(dump, &aged(30), false).map(|c| c.level), Some(SafetyLevel::AutoDelete));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let temp = tempdir().expect("tempdir");
+        let path = temp.path().join("typo.json");
+        fs::write(&path, r#"{"name": "Typo", "rules": [{"pattern": "x", "match": "extension", "levle": "protected"}]}"#)
+            .expect("write");
+        let report = validate_rule_pack(&path);
+        assert!(!report.is_valid());
+        assert!(report.name.is_none());
+    }
+}
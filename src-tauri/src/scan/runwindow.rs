@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Local times of day, as minutes after midnight, between which background work may
+/// start. A range whose end is before its start runs past midnight; equal ends mean
+/// the whole day.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start_minute: u16,
+    pub end_minute: u16,
+}
+
+impl TimeRange {
+    pub fn contains(&self, minute: u16) -> bool {
+        match self.start_minute.cmp(&self.end_minute) {
+            std::cmp::Ordering::Equal => true,
+            std::cmp::Ordering::Less => (self.start_minute..self.end_minute).contains(&minute),
+            std::cmp::Ordering::Greater => minute >= self.start_minute || minute < self.end_minute,
+        }
+    }
+}
+
+/// When scheduled scans and cleanup policies may start. Low-space warnings and anything
+/// started by hand are not held back.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunWindow {
+    #[serde(default)]
+    pub hours: Option<TimeRange>,
+    #[serde(default)]
+    pub require_ac_power: bool,
+    #[serde(default)]
+    pub skip_metered: bool,
+}
+
+/// Why background work is waiting
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowBlocker {
+    OutsideHours,
+    OnBattery,
+    MeteredConnection,
+}
+
+/// The state of the machine a window is checked against; None where it couldn't be
+/// read, which never holds work back
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunConditions {
+    pub minute_of_day: Option<u16>,
+    pub on_ac_power: Option<bool>,
+    pub metered: Option<bool>,
+}
+
+impl RunWindow {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.hours {
+            Some(hours) if hours.start_minute >= MINUTES_PER_DAY || hours.end_minute >= MINUTES_PER_DAY => {
+                Err("Window times must be minutes after midnight, below 1440".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the conditions this window cares about and report what keeps it closed
+    pub fn check(&self) -> Vec<WindowBlocker> {
+        let conditions = RunConditions {
+            minute_of_day: self.hours.and_then(|_| local_minute_of_day()),
+            on_ac_power: self.require_ac_power.then(on_ac_power).flatten(),
+            metered: self.skip_metered.then(is_metered).flatten(),
+        };
+        self.blockers(&conditions)
+    }
+
+    pub fn blockers(&self, conditions: &RunConditions) -> Vec<WindowBlocker> {
+        let mut blockers = Vec::new();
+        if let (Some(hours), Some(minute)) = (self.hours, conditions.minute_of_day) {
+            if !hours.contains(minute) {
+                blockers.push(WindowBlocker::OutsideHours);
+            }
+        }
+        if self.require_ac_power && conditions.on_ac_power == Some(false) {
+            blockers.push(WindowBlocker::OnBattery);
+        }
+        if self.skip_metered && conditions.metered == Some(true) {
+            blockers.push(WindowBlocker::MeteredConnection);
+        }
+        blockers
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn local_minute_of_day() -> Option<u16> {
+    use windows_sys::Win32::Foundation::SYSTEMTIME;
+    use windows_sys::Win32::System::SystemInformation::GetLocalTime;
+
+    let mut time: SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { GetLocalTime(&mut time) };
+    Some(time.wHour * 60 + time.wMinute)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn local_minute_of_day() -> Option<u16> {
+    let output = std::process::Command::new("date").arg("+%H:%M").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (hours, minutes) = text.trim().split_once(':')?;
+    Some(hours.parse::<u16>().ok()? * 60 + minutes.parse::<u16>().ok()?)
+}
+
+#[cfg(target_os = "windows")]
+fn on_ac_power() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // 0 = battery, 1 = AC, 255 = unknown
+    match status.ACLineStatus {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+/// A machine with no battery is always on mains power; otherwise any online mains or
+/// USB supply counts
+#[cfg(target_os = "linux")]
+fn on_ac_power() -> Option<bool> {
+    let mut has_battery = false;
+    let mut online = false;
+    for supply in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let read = |name: &str| std::fs::read_to_string(supply.path().join(name)).unwrap_or_default();
+        match read("type").trim() {
+            "Battery" => has_battery |= read("scope").trim() != "Device",
+            "Mains" | "USB" => online |= read("online").trim() == "1",
+            _ => {}
+        }
+    }
+    Some(online || !has_battery)
+}
+
+#[cfg(target_os = "macos")]
+fn on_ac_power() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let first = text.lines().next()?;
+    Some(first.contains("AC Power"))
+}
+
+#[cfg(all(not(target_os = "linux"), not(target_os = "windows"), not(target_os = "macos")))]
+fn on_ac_power() -> Option<bool> {
+    None
+}
+
+/// The cost type of the internet connection profile: Fixed and Variable are metered
+#[cfg(target_os = "windows")]
+fn is_metered() -> Option<bool> {
+    use std::process::Command;
+
+    let script = "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,\
+        ContentType=WindowsRuntime]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType";
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()
+        .ok()?;
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "Fixed" | "Variable" => Some(true),
+        "Unrestricted" => Some(false),
+        _ => None,
+    }
+}
+
+/// NetworkManager's overall Metered property: 1 yes, 2 no, 3 guessed yes, 4 guessed no
+#[cfg(target_os = "linux")]
+fn is_metered() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.trim().strip_prefix("u ")?.trim() {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+/// macOS has no command-line view of Low Data Mode, so connections count as unmetered
+#[cfg(all(not(target_os = "linux"), not(target_os = "windows")))]
+fn is_metered() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_blocks_outside_hours_on_battery_and_metered() {
+        let overnight = TimeRange {
+            start_minute: 2 * 60,
+            end_minute: 6 * 60,
+        };
+        assert!(overnight.contains(3 * 60) && !overnight.contains(6 * 60));
+        let wrapping = TimeRange {
+            start_minute: 22 * 60,
+            end_minute: 60,
+        };
+        assert!(wrapping.contains(23 * 60) && wrapping.contains(30) && !wrapping.contains(12 * 60));
+
+        let window = RunWindow {
+            hours: Some(overnight),
+            require_ac_power: true,
+            skip_metered: true,
+        };
+        let conditions = RunConditions {
+            minute_of_day: Some(12 * 60),
+            on_ac_power: Some(false),
+            metered: Some(true),
+        };
+        assert_eq!(
+            window.blockers(&conditions),
+            vec![WindowBlocker::OutsideHours, WindowBlocker::OnBattery, WindowBlocker::MeteredConnection]
+        );
+        assert!(window.blockers(&RunConditions::default()).is_empty());
+        assert_eq!(parse_nm_metered("u 4\n"), Some(false));
+        let invalid = TimeRange {
+            start_minute: MINUTES_PER_DAY,
+            end_minute: 0,
+        };
+        assert!(RunWindow { hours: Some(invalid), ..window }.validate().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long one stat or directory listing may take in safe mode before the entry is
+/// given up on
+pub const SAFE_MODE_IO_TIMEOUT: Duration = Duration::from_secs(3);
+/// Timed-out reads in one folder after which the rest of it is left unread
+const BLACKLIST_AFTER: u32 = 3;
+/// Regions kept per scan; a dying drive can fail far more reads than are worth listing
+const REGION_LIMIT: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnreadableReason {
+    /// The read did not return within the timeout
+    TimedOut,
+    /// The drive reported an I/O error
+    ReadError,
+    /// Left unread after repeated timeouts inside it
+    Blacklisted,
+}
+
+/// A file or folder a safe-mode scan could not read, for planning recovery
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnreadableRegion {
+    pub path: String,
+    pub is_dir: bool,
+    pub reason: UnreadableReason,
+    /// The error reported, for read errors
+    pub message: Option<String>,
+}
+
+enum Request {
+    Stat { path: PathBuf, follow: bool },
+    List(PathBuf),
+}
+
+/// Metadata for a stat; nothing for a listing
+type Reply = io::Result<Option<Metadata>>;
+
+fn execute(request: Request) -> Reply {
+    match request {
+        Request::Stat { path, follow: true } => fs::metadata(path).map(Some),
+        Request::Stat { path, follow: false } => fs::symlink_metadata(path).map(Some),
+        // Reading an entry touches the directory's data, not just its inode
+        Request::List(path) => fs::read_dir(path)
+            .and_then(|mut entries| entries.next().transpose())
+            .map(|_| None),
+    }
+}
+
+struct Worker {
+    requests: Sender<Request>,
+    replies: Receiver<Reply>,
+}
+
+impl Worker {
+    fn spawn() -> Option<Self> {
+        let (requests, request_rx) = mpsc::channel::<Request>();
+        let (reply_tx, replies) = mpsc::channel();
+        thread::Builder::new()
+            .name("safe-mode-io".to_string())
+            .spawn(move || {
+                for request in request_rx {
+                    if reply_tx.send(execute(request)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .ok()?;
+        Some(Self { requests, replies })
+    }
+}
+
+/// Runs a safe-mode scan's stats and listings on a helper thread, so a read stuck on a
+/// bad sector costs one timeout instead of hanging the walk. A stuck helper is abandoned
+/// (it exits if its read ever returns) and a fresh one takes over.
+pub struct SafeReader {
+    timeout: Duration,
+    worker: Option<Worker>,
+    timeouts: HashMap<PathBuf, u32>,
+    blacklist: Vec<PathBuf>,
+    regions: Vec<UnreadableRegion>,
+}
+
+impl SafeReader {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            worker: None,
+            timeouts: HashMap::new(),
+            blacklist: Vec::new(),
+            regions: Vec::new(),
+        }
+    }
+
+    /// Whether `path` lies in a folder that was given up on
+    pub fn is_blacklisted(&self, path: &Path) -> bool {
+        self.blacklist.iter().any(|dir| path.starts_with(dir))
+    }
+
+    /// Whether the directory lists in time and without a read error; one that doesn't
+    /// is recorded and should be left out of the walk
+    pub fn can_list(&mut self, path: &Path) -> bool {
+        if self.is_blacklisted(path) {
+            return false;
+        }
+        match self.run(Request::List(path.to_path_buf())) {
+            Some(Ok(_)) => true,
+            Some(Err(err)) if is_read_error(&err) => {
+                self.record(path, true, UnreadableReason::ReadError, Some(err.to_string()));
+                false
+            }
+            // Access denied and the like are reported by the walker as usual
+            Some(Err(_)) => true,
+            None => {
+                self.record(path, true, UnreadableReason::TimedOut, None);
+                self.blacklist.push(path.to_path_buf());
+                false
+            }
+        }
+    }
+
+    /// Metadata for a file, or None when it can't be read in time. Repeated timeouts
+    /// in one folder blacklist the folder.
+    pub fn stat(&mut self, path: &Path, follow: bool) -> Option<Metadata> {
+        if self.is_blacklisted(path) {
+            return None;
+        }
+        let request = Request::Stat {
+            path: path.to_path_buf(),
+            follow,
+        };
+        match self.run(request) {
+            Some(Ok(metadata)) => metadata,
+            Some(Err(err)) => {
+                if is_read_error(&err) {
+                    self.record(path, false, UnreadableReason::ReadError, Some(err.to_string()));
+                }
+                None
+            }
+            None => {
+                self.record(path, false, UnreadableReason::TimedOut, None);
+                if let Some(parent) = path.parent() {
+                    let count = self.timeouts.entry(parent.to_path_buf()).or_default();
+                    *count += 1;
+                    if *count == BLACKLIST_AFTER {
+                        self.blacklist.push(parent.to_path_buf());
+                        self.record(parent, true, UnreadableReason::Blacklisted, None);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Everything recorded so far
+    pub fn take_regions(&mut self) -> Vec<UnreadableRegion> {
+        std::mem::take(&mut self.regions)
+    }
+
+    /// The reply, or None if the read timed out
+    fn run(&mut self, request: Request) -> Option<Reply> {
+        if self.worker.is_none() {
+            self.worker = Worker::spawn();
+        }
+        // Without a helper thread the read runs inline, unguarded
+        let Some(worker) = &self.worker else {
+            return Some(execute(request));
+        };
+        if worker.requests.send(request).is_err() {
+            self.worker = None;
+            return Some(Err(io::Error::other("safe-mode reader stopped")));
+        }
+        match worker.replies.recv_timeout(self.timeout) {
+            Ok(reply) => Some(reply),
+            Err(RecvTimeoutError::Timeout) => {
+                self.worker = None;
+                None
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.worker = None;
+                Some(Err(io::Error::other("safe-mode reader stopped")))
+            }
+        }
+    }
+
+    fn record(&mut self, path: &Path, is_dir: bool, reason: UnreadableReason, message: Option<String>) {
+        if self.regions.len() < REGION_LIMIT {
+            self.regions.push(UnreadableRegion {
+                path: path.to_string_lossy().to_string(),
+                is_dir,
+                reason,
+                message,
+            });
+        }
+    }
+}
+
+/// Failures that point at the media rather than at permissions or a vanished entry
+fn is_read_error(err: &io::Error) -> bool {
+    !matches!(err.kind(), io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_pass_through_and_blacklisted_folders_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("disksight-safemode-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create dir");
+        let file = dir.join("a.bin");
+        fs::write(&file, [0u8; 42]).expect("write file");
+
+        let mut reader = SafeReader::new(SAFE_MODE_IO_TIMEOUT);
+        assert!(reader.can_list(&dir));
+        assert_eq!(reader.stat(&file, false).map(|metadata| metadata.len()), Some(42));
+        assert!(reader.stat(&dir.join("missing"), false).is_none());
+        assert!(reader.take_regions().is_empty());
+
+        reader.blacklist.push(dir.clone());
+        assert!(!reader.can_list(&dir.join("sub")));
+        assert!(reader.stat(&file, false).is_none());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
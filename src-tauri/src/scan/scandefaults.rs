@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::scan::baseline::is_descendant;
+use crate::scan::model::ScanOptions;
+
+/// Roots remembered at most; the least recently scanned are forgotten first
+const MAX_REMEMBERED_ROOTS: usize = 200;
+
+/// The options a root was last scanned with by hand
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RememberedOptions {
+    pub options: ScanOptions,
+    /// Mount point of the volume holding the root, when it could be found
+    #[serde(default)]
+    pub mount_point: Option<String>,
+    pub used_at: u64,
+}
+
+/// Remembered options by normalized root path
+pub type ScanDefaults = HashMap<String, RememberedOptions>;
+
+/// Where suggested options came from
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DefaultsSource {
+    /// The root itself was scanned before
+    Root,
+    /// The nearest scanned folder above the root
+    Ancestor { path: String },
+    /// The most recently scanned root on the same volume
+    Volume { path: String },
+    /// Nothing on the volume was scanned yet
+    Builtin,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DefaultOptions {
+    pub options: ScanOptions,
+    pub source: DefaultsSource,
+}
+
+/// Remember `options` for `root`, leaving out what only applies to one run
+pub fn remember(defaults: &mut ScanDefaults, root: &str, mount_point: Option<String>, options: &ScanOptions, at: u64) {
+    let options = ScanOptions {
+        size_hints: None,
+        resume: None,
+        file_list: None,
+        ..options.clone()
+    };
+    defaults.insert(
+        root.to_string(),
+        RememberedOptions {
+            options,
+            mount_point,
+            used_at: at,
+        },
+    );
+    while defaults.len() > MAX_REMEMBERED_ROOTS {
+        let Some(oldest) = defaults
+            .iter()
+            .min_by_key(|(_, remembered)| remembered.used_at)
+            .map(|(path, _)| path.clone())
+        else {
+            break;
+        };
+        defaults.remove(&oldest);
+    }
+}
+
+/// The options to offer for scanning `root`: its own last options, else those of the
+/// nearest scanned folder above it, else the latest on its volume, so a NAS mount and
+/// a local SSD each keep their own settings
+pub fn default_options(defaults: &ScanDefaults, root: &str, mount_point: Option<&str>) -> DefaultOptions {
+    if let Some(remembered) = defaults.get(root) {
+        return DefaultOptions {
+            options: remembered.options.clone(),
+            source: DefaultsSource::Root,
+        };
+    }
+    let ancestor = defaults
+        .iter()
+        .filter(|(path, _)| is_descendant(root, path))
+        .max_by_key(|(path, _)| path.len());
+    if let Some((path, remembered)) = ancestor {
+        return DefaultOptions {
+            options: remembered.options.clone(),
+            source: DefaultsSource::Ancestor { path: path.clone() },
+        };
+    }
+    let on_volume = mount_point.and_then(|mount_point| {
+        defaults
+            .iter()
+            .filter(|(_, remembered)| remembered.mount_point.as_deref() == Some(mount_point))
+            .max_by_key(|(_, remembered)| remembered.used_at)
+    });
+    match on_volume {
+        Some((path, remembered)) => DefaultOptions {
+            options: remembered.options.clone(),
+            source: DefaultsSource::Volume { path: path.clone() },
+        },
+        None => DefaultOptions {
+            options: ScanOptions::default(),
+            source: DefaultsSource::Builtin,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_from_root_to_ancestor_to_volume() {
+        let mut defaults = ScanDefaults::new();
+        let nas = ScanOptions {
+            follow_symlinks: true,
+            file_list: Some("1\t/mnt/nas/a".to_string()),
+            ..ScanOptions::default()
+        };
+        remember(&mut defaults, "/mnt/nas", Some("/mnt/nas".to_string()), &nas, 10);
+        let ssd = ScanOptions {
+            max_depth: Some(4),
+            ..ScanOptions::default()
+        };
+        remember(&mut defaults, "/home/me/src", Some("/".to_string()), &ssd, 20);
+
+        let own = default_options(&defaults, "/mnt/nas", Some("/mnt/nas"));
+        assert_eq!(own.source, DefaultsSource::Root);
+        assert!(own.options.follow_symlinks && own.options.file_list.is_none());
+        let below = default_options(&defaults, "/mnt/nas/photos", Some("/mnt/nas"));
+        assert_eq!(below.source, DefaultsSource::Ancestor { path: "/mnt/nas".to_string() });
+        let sibling = default_options(&defaults, "/var", Some("/"));
+        assert_eq!(sibling.source, DefaultsSource::Volume { path: "/home/me/src".to_string() });
+        assert_eq!(sibling.options.max_depth, Some(4));
+        assert_eq!(default_options(&defaults, "/media/usb", None).source, DefaultsSource::Builtin);
+    }
+}
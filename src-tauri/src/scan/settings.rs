@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::scan::agent::AgentSettings;
+use crate::scan::api::ApiSettings;
+use crate::scan::backend::ScanBackend;
+use crate::scan::baseline::DriftThresholds;
+use crate::scan::bookmarks::Bookmark;
+use crate::scan::notify::NotificationSettings;
+use crate::scan::policy::Policy;
+use crate::scan::recycle::TrashFallback;
+use crate::scan::scandefaults::ScanDefaults;
+use crate::scan::spill::SpillSettings;
+use crate::scan::throttle::DeleteThrottle;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Backend settings persisted as JSON in the app data dir
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Settings {
+    /// Default pacing for bulk deletes; operations may override it
+    #[serde(default)]
+    pub delete_throttle: DeleteThrottle,
+    /// What smart and bulk deletes do with items too large for the Recycle Bin
+    #[serde(default)]
+    pub trash_fallback: TrashFallback,
+    /// Classify build output as ReclaimableBuild and VCS/IDE folders as ConfirmRequired
+    #[serde(default)]
+    pub developer_mode: bool,
+    /// Tray, autostart, scheduled scan, and low-space monitoring
+    #[serde(default)]
+    pub agent: AgentSettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Local HTTP endpoint serving Prometheus metrics
+    #[serde(default)]
+    pub api: ApiSettings,
+    /// Scheduled cleanup rules
+    #[serde(default)]
+    pub policies: Vec<Policy>,
+    /// Folders tracked with quick rescans
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// When a scheduled scan counts as drifting from its root's baseline
+    #[serde(default)]
+    pub drift: DriftThresholds,
+    /// Moving large finished trees to disk to bound memory use
+    #[serde(default)]
+    pub spill: SpillSettings,
+    /// Backend for scans that ask for Auto, as picked by the last scan benchmark
+    #[serde(default)]
+    pub scan_backend: ScanBackend,
+    /// Options each root was last scanned with by hand, offered for its next scan
+    #[serde(default)]
+    pub scan_defaults: ScanDefaults,
+}
+
+/// Load settings from `dir`, falling back to defaults when missing or unreadable
+pub fn load_settings(dir: &Path) -> Settings {
+    fs::read_to_string(dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Write settings to `dir`, creating it if needed
+pub fn save_settings(dir: &Path, settings: &Settings) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), contents).map_err(|e| e.to_string())
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, ShaderCacheUsage};
+
+/// Folder sequences of GPU driver and game shader caches, with a readable source name.
+/// Everything below them is rebuilt by the driver or game on the next launch.
+const SHADER_CACHES: &[(&[&str], &str)] = &[
+    (&["nvidia", "dxcache"], "NVIDIA DXCache"),
+    (&["nvidia", "glcache"], "NVIDIA GLCache"),
+    (&["nvidia", "optixcache"], "NVIDIA OptiX cache"),
+    (&["nvidia corporation", "nv_cache"], "NVIDIA NV_Cache"),
+    (&[".nv", "glcache"], "NVIDIA GLCache"),
+    (&[".nv", "computecache"], "NVIDIA compute cache"),
+    (&["amd", "dxcache"], "AMD DxCache"),
+    (&["amd", "dxccache"], "AMD DxcCache"),
+    (&["amd", "glcache"], "AMD GLCache"),
+    (&["amd", "vkcache"], "AMD VkCache"),
+    (&["intel", "shadercache"], "Intel shader cache"),
+    (&["d3dscache"], "Direct3D shader cache"),
+    (&["mesa_shader_cache"], "Mesa shader cache"),
+    (&["mesa_shader_cache_db"], "Mesa shader cache"),
+    (&["radv_builtin_shaders64"], "RADV shader cache"),
+];
+
+/// Steam keeps one shader cache folder per game, named by its app id
+const STEAM_SHADER_CACHE: &[&str] = &["steamapps", "shadercache"];
+
+/// Sizes the shader caches GPU drivers and Steam build up, one entry per cache folder
+/// and per Steam game, since they quietly grow to tens of GB
+#[derive(Default)]
+pub struct ShaderCacheAnalyzer {
+    caches: HashMap<PathBuf, CacheTotals>,
+}
+
+struct CacheTotals {
+    source: String,
+    steam_app_id: Option<u32>,
+    bytes: u64,
+    files: u64,
+}
+
+impl Analyzer for ShaderCacheAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let Some(location) = locate(Path::new(&file.node.path)) else {
+            return;
+        };
+        let totals = self.caches.entry(location.folder).or_insert_with(|| CacheTotals {
+            source: location.source,
+            steam_app_id: location.steam_app_id,
+            bytes: 0,
+            files: 0,
+        });
+        totals.bytes += file.node.size_bytes;
+        totals.files += 1;
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut caches: Vec<ShaderCacheUsage> = self
+            .caches
+            .into_iter()
+            .map(|(folder, totals)| ShaderCacheUsage {
+                source: totals.source,
+                path: folder.to_string_lossy().to_string(),
+                steam_app_id: totals.steam_app_id,
+                bytes: totals.bytes,
+                files: totals.files,
+            })
+            .collect();
+        caches.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+        AnalyzerReport::ShaderCaches { caches }
+    }
+}
+
+struct Location {
+    source: String,
+    /// The cache folder, or the game's folder inside Steam's shader cache
+    folder: PathBuf,
+    steam_app_id: Option<u32>,
+}
+
+/// Which shader cache a file belongs to, if any
+fn locate(path: &Path) -> Option<Location> {
+    let components: Vec<Component> = path.components().collect();
+    let lower: Vec<String> = components
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    let find = |sequence: &[&str]| {
+        lower
+            .windows(sequence.len())
+            .rposition(|window| window.iter().zip(sequence.iter()).all(|(a, b)| a == b))
+            .map(|start| start + sequence.len())
+    };
+    // The file must sit below the game's folder, not loose in shadercache itself
+    if let Some(end) = find(STEAM_SHADER_CACHE).filter(|end| lower.len() > end + 1) {
+        let game = components[end].as_os_str().to_string_lossy().to_string();
+        return Some(Location {
+            steam_app_id: game.parse().ok(),
+            source: format!("Steam game {}", game),
+            folder: components[..=end].iter().collect(),
+        });
+    }
+    let (end, source) = SHADER_CACHES
+        .iter()
+        .filter_map(|(sequence, source)| find(sequence).map(|end| (end, *source)))
+        .filter(|(end, _)| lower.len() > *end)
+        .max_by_key(|(end, _)| *end)?;
+    Some(Location {
+        source: source.to_string(),
+        folder: components[..end].iter().collect(),
+        steam_app_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_files_to_shader_caches() {
+        let nvidia = locate(Path::new("C:/Users/me/AppData/Local/NVIDIA/DXCache/a1b2.nvph")).expect("nvidia");
+        assert_eq!(nvidia.source, "NVIDIA DXCache");
+        assert!(nvidia.folder.ends_with("NVIDIA/DXCache"));
+
+        let steam = locate(Path::new("D:/Steam/steamapps/shadercache/1091500/fozpipelinesv6/steam_pipeline_cache.foz"))
+            .expect("steam");
+        assert_eq!((steam.source.as_str(), steam.steam_app_id), ("Steam game 1091500", Some(1091500)));
+        assert!(steam.folder.ends_with("shadercache/1091500"));
+
+        let mesa = locate(Path::new("/home/me/.cache/mesa_shader_cache/3f/0a1b")).expect("mesa");
+        assert_eq!(mesa.source, "Mesa shader cache");
+
+        assert!(locate(Path::new("D:/Steam/steamapps/shadercache/loose.bin")).is_none());
+        assert!(locate(Path::new("C:/Users/me/AppData/Local/NVIDIA/DXCache")).is_none());
+        assert!(locate(Path::new("C:/Users/me/Documents/dxcache.txt")).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+/// vssadmin refuses shadow storage limits below 320 MB
+pub const MIN_SHADOW_STORAGE_BYTES: u64 = 320 * 1024 * 1024;
+
+/// Volume Shadow Copy (System Restore) storage reserved for one volume
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ShadowStorage {
+    /// Volume whose snapshots are stored, e.g. "C:"
+    pub volume: String,
+    /// Volume holding the snapshot data (usually the same volume)
+    pub storage_volume: String,
+    pub used_bytes: u64,
+    pub allocated_bytes: u64,
+    /// Configured limit; None when unbounded
+    pub max_bytes: Option<u64>,
+}
+
+/// Shadow storage associations on all volumes
+#[cfg(target_os = "windows")]
+pub fn list_shadow_storage() -> Result<Vec<ShadowStorage>, String> {
+    let output = vssadmin(&["list", "shadowstorage"])?;
+    Ok(parse_shadow_storage(&output))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn list_shadow_storage() -> Result<Vec<ShadowStorage>, String> {
+    Err("Volume Shadow Copy is only available on Windows".to_string())
+}
+
+/// Shadow storage reserved for the volume mounted at `mount_point`, if any
+pub fn shadow_storage_for(mount_point: &str) -> Option<ShadowStorage> {
+    let volume = volume_name(mount_point);
+    list_shadow_storage()
+        .ok()?
+        .into_iter()
+        .find(|storage| storage.volume.eq_ignore_ascii_case(&volume))
+}
+
+/// Change the maximum shadow storage of a volume. Shrinking below current usage makes
+/// Windows delete the oldest restore points. Requires an elevated process.
+#[cfg(target_os = "windows")]
+pub fn resize_shadow_storage(volume: &str, max_bytes: u64) -> Result<ShadowStorage, String> {
+    if max_bytes < MIN_SHADOW_STORAGE_BYTES {
+        return Err(format!("Shadow storage cannot be smaller than {} bytes", MIN_SHADOW_STORAGE_BYTES));
+    }
+    let volume = volume_name(volume);
+    let current = shadow_storage_for(&volume)
+        .ok_or_else(|| format!("No shadow storage is configured for {}", volume))?;
+    vssadmin(&[
+        "resize",
+        "shadowstorage",
+        &format!("/For={}", current.volume),
+        &format!("/On={}", current.storage_volume),
+        &format!("/MaxSize={}", max_bytes),
+    ])?;
+    shadow_storage_for(&volume).ok_or_else(|| format!("Shadow storage for {} disappeared after resizing", volume))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn resize_shadow_storage(_volume: &str, _max_bytes: u64) -> Result<ShadowStorage, String> {
+    Err("Volume Shadow Copy is only available on Windows".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn vssadmin(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("vssadmin")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        // vssadmin reports errors on stdout
+        return Err(stdout.lines().last().unwrap_or("vssadmin failed").trim().to_string());
+    }
+    Ok(stdout)
+}
+
+/// "C:\" and "c:" both become "C:"
+fn volume_name(mount_point: &str) -> String {
+    mount_point.trim_end_matches(['\\', '/']).to_uppercase()
+}
+
+/// Parse `vssadmin list shadowstorage`, one block per association:
+///
+/// ```text
+/// Shadow Copy Storage association
+///    For volume: (C:)\\?\Volume{...}\
+///    Shadow Copy Storage volume: (C:)\\?\Volume{...}\
+///    Used Shadow Copy Storage space: 2.95 GB (3%)
+///    Allocated Shadow Copy Storage space: 3.32 GB (3%)
+///    Maximum Shadow Copy Storage space: UNBOUNDED (100%)
+/// ```
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_shadow_storage(output: &str) -> Vec<ShadowStorage> {
+    let mut storages = Vec::new();
+    let mut current: Option<ShadowStorage> = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(": ") else {
+            continue;
+        };
+        let key = key.to_lowercase();
+        if key == "for volume" {
+            storages.extend(current.take());
+            current = Some(ShadowStorage {
+                volume: parse_volume(value),
+                storage_volume: String::new(),
+                used_bytes: 0,
+                allocated_bytes: 0,
+                max_bytes: None,
+            });
+            continue;
+        }
+        let Some(storage) = current.as_mut() else {
+            continue;
+        };
+        if key.ends_with("storage volume") {
+            storage.storage_volume = parse_volume(value);
+        } else if key.starts_with("used") {
+            storage.used_bytes = parse_size(value).unwrap_or(0);
+        } else if key.starts_with("allocated") {
+            storage.allocated_bytes = parse_size(value).unwrap_or(0);
+        } else if key.starts_with("maximum") {
+            storage.max_bytes = parse_size(value);
+        }
+    }
+    storages.extend(current);
+    storages
+}
+
+/// "(C:)\\?\Volume{guid}\" -> "C:", or the GUID path for volumes without a letter
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_volume(value: &str) -> String {
+    match value.strip_prefix('(').and_then(|rest| rest.split_once(')')) {
+        Some((letter, _)) if !letter.is_empty() => letter.to_uppercase(),
+        Some((_, guid)) => guid.trim().to_string(),
+        None => value.trim().to_string(),
+    }
+}
+
+/// "2.95 GB (3%)" -> bytes; None for "UNBOUNDED"
+pub(crate) fn parse_size(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let number: f64 = parts.next()?.replace(',', ".").parse().ok()?;
+    let multiplier: u64 = match parts.next()?.to_uppercase().as_str() {
+        "B" | "BYTES" => 1,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        "PB" => 1 << 50,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_vssadmin_listing() {
+        let output = "vssadmin 1.1 - Volume Shadow Copy Service administrative command-line tool\r\n\
+            (C) Copyright 2001-2013 Microsoft Corp.\r\n\r\n\
+            Shadow Copy Storage association\r\n\
+            \x20  For volume: (C:)\\\\?\\Volume{1111}\\\r\n\
+            \x20  Shadow Copy Storage volume: (C:)\\\\?\\Volume{1111}\\\r\n\
+            \x20  Used Shadow Copy Storage space: 2 GB (3%)\r\n\
+            \x20  Allocated Shadow Copy Storage space: 2.5 GB (3%)\r\n\
+            \x20  Maximum Shadow Copy Storage space: UNBOUNDED (100%)\r\n\r\n\
+            Shadow Copy Storage association\r\n\
+            \x20  For volume: ()\\\\?\\Volume{2222}\\\r\n\
+            \x20  Shadow Copy Storage volume: (D:)\\\\?\\Volume{3333}\\\r\n\
+            \x20  Used Shadow Copy Storage space: 0 bytes (0%)\r\n\
+            \x20  Allocated Shadow Copy Storage space: 0 bytes (0%)\r\n\
+            \x20  Maximum Shadow Copy Storage space: 512 MB (5%)\r\n";
+        let storages = parse_shadow_storage(output);
+        assert_eq!(storages.len(), 2);
+        assert_eq!(storages[0].volume, "C:");
+        assert_eq!(storages[0].used_bytes, 2 << 30);
+        assert_eq!(storages[0].allocated_bytes, 5 << 29);
+        assert_eq!(storages[0].max_bytes, None);
+        assert_eq!(storages[1].volume, "\\\\?\\Volume{2222}\\");
+        assert_eq!(storages[1].storage_volume, "D:");
+        assert_eq!(storages[1].max_bytes, Some(512 << 20));
+    }
+
+    #[test]
+    fn normalizes_volume_names() {
+        assert_eq!(volume_name("c:\\"), "C:");
+        assert_eq!(volume_name("D:"), "D:");
+    }
+}
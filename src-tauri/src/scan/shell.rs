@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::scan::tray::show_main_window;
+
+/// `DiskSight --scan <folder>` opens the app and scans the folder
+pub const SCAN_ARG: &str = "--scan";
+pub const EVENT_SCAN_REQUESTED: &str = "app://scan_requested";
+/// Verb key name shared by every registration below
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const VERB: &str = "DiskSight";
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const VERB_LABEL: &str = "Analyze with DiskSight";
+
+/// A scan asked for from the command line, waiting for the frontend to pick it up
+#[derive(Default)]
+pub struct LaunchRequest(Mutex<Option<String>>);
+
+impl LaunchRequest {
+    pub fn take(&self) -> Option<String> {
+        self.0.lock().ok().and_then(|mut guard| guard.take())
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScanRequestedPayload {
+    pub path: String,
+}
+
+/// The folder after `--scan`, given as `--scan <path>` or `--scan=<path>`
+pub fn scan_path_from_args<I, S>(args: I) -> Option<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        let path = if arg == SCAN_ARG {
+            args.next().map(|next| next.as_ref().to_string())
+        } else {
+            arg.strip_prefix(SCAN_ARG)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        };
+        if let Some(path) = path.map(|p| clean_path(&p)).filter(|p| !p.is_empty()) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Explorer quotes drive roots as `"C:\"`, which the argument parser reads as an escaped
+/// quote and hands over as `C:"`
+fn clean_path(path: &str) -> String {
+    let path = path.trim_matches('"');
+    if path.len() == 2 && path.ends_with(':') {
+        format!("{}\\", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Act on launch arguments, from this process or forwarded by a second launch: remember
+/// any requested scan, tell the frontend, and bring the window forward. Relative paths
+/// resolve against `cwd`, the launching process's working directory. The frontend takes
+/// the request on load in case the event arrives before it is listening.
+pub fn handle_launch_args<I, S>(app: &AppHandle, args: I, cwd: &Path)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let Some(path) = scan_path_from_args(args) else {
+        return;
+    };
+    let path = cwd.join(path).to_string_lossy().to_string();
+    if let Some(request) = app.try_state::<LaunchRequest>() {
+        if let Ok(mut guard) = request.0.lock() {
+            *guard = Some(path.clone());
+        }
+    }
+    show_main_window(app);
+    let _ = app.emit(EVENT_SCAN_REQUESTED, ScanRequestedPayload { path });
+}
+
+/// Registry keys under HKCU\Software\Classes for folders, folder backgrounds and drives,
+/// with the placeholder Explorer substitutes for each
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn verb_keys() -> [(String, &'static str); 3] {
+    [
+        (format!(r"HKCU\Software\Classes\Directory\shell\{}", VERB), "%1"),
+        (format!(r"HKCU\Software\Classes\Directory\Background\shell\{}", VERB), "%V"),
+        (format!(r"HKCU\Software\Classes\Drive\shell\{}", VERB), "%1"),
+    ]
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn verb_command(exe: &str, placeholder: &str) -> String {
+    format!("\"{}\" {} \"{}\"", exe, SCAN_ARG, placeholder)
+}
+
+/// Whether "Analyze with DiskSight" is in the Explorer folder context menu
+#[cfg(target_os = "windows")]
+pub fn is_context_menu_registered() -> bool {
+    use std::process::Command;
+
+    let (key, _) = &verb_keys()[0];
+    Command::new("reg")
+        .args(["query", key.as_str()])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Add the context-menu verb for the current user, pointing at this executable. No
+/// elevation is needed since everything lives under HKCU.
+#[cfg(target_os = "windows")]
+pub fn register_context_menu() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let exe = exe.to_string_lossy().to_string();
+    for (key, placeholder) in verb_keys() {
+        reg(&["add", key.as_str(), "/ve", "/d", VERB_LABEL, "/f"])?;
+        reg(&["add", key.as_str(), "/v", "Icon", "/d", exe.as_str(), "/f"])?;
+        let command_key = format!(r"{}\command", key);
+        let command = verb_command(&exe, placeholder);
+        reg(&["add", command_key.as_str(), "/ve", "/d", command.as_str(), "/f"])?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn unregister_context_menu() -> Result<(), String> {
+    for (key, _) in verb_keys() {
+        // Deleting a key that is already gone is not an error here
+        let _ = reg(&["delete", key.as_str(), "/f"]);
+    }
+    if is_context_menu_registered() {
+        return Err("Could not remove the Explorer context menu entry".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<(), String> {
+    use std::process::Command;
+
+    let output = Command::new("reg").args(args).output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_context_menu_registered() -> bool {
+    false
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn register_context_menu() -> Result<(), String> {
+    Err("Explorer integration is only available on Windows".to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn unregister_context_menu() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_scan_path_from_launch_args() {
+        assert_eq!(
+            scan_path_from_args([r"C:\DiskSight.exe", "--scan", r"D:\Projects"]),
+            Some(r"D:\Projects".to_string())
+        );
+        assert_eq!(
+            scan_path_from_args(["disksight", "--minimized", "--scan=/home/anna"]),
+            Some("/home/anna".to_string())
+        );
+        assert_eq!(
+            scan_path_from_args([r"C:\DiskSight.exe", "--scan", "E:\""]),
+            Some(r"E:\".to_string())
+        );
+        assert_eq!(scan_path_from_args([r"C:\DiskSight.exe", "--scan"]), None);
+        assert_eq!(scan_path_from_args(["disksight", "--minimized"]), None);
+    }
+
+    #[test]
+    fn verb_command_quotes_exe_and_target() {
+        assert_eq!(
+            verb_command(r"C:\Program Files\DiskSight\DiskSight.exe", "%V"),
+            r#""C:\Program Files\DiskSight\DiskSight.exe" --scan "%V""#
+        );
+    }
+}
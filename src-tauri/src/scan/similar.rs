@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::scan::hashcache::{file_stamp, HashCache, HashKind};
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::tree::ScanTree;
+
+/// Results returned when the caller sets no limit
+pub const DEFAULT_SIMILAR_LIMIT: usize = 200;
+/// Bigram overlap (Dice coefficient) at which two names count as alike
+const NAME_SIMILARITY: f64 = 0.7;
+/// Name parts that mark versions and copies rather than what a file is
+const NOISE_WORDS: &[&str] = &[
+    "copy", "final", "export", "exported", "render", "rendered", "edit", "edited", "draft", "new", "old", "backup",
+];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarReason {
+    /// Both files have a cached hash and the hashes match
+    SameContent,
+    SameSize,
+    /// The names match once copy and version markers are set aside
+    SimilarName,
+    /// Same extension in a folder of the same name, like the other renders of a project
+    SameExtensionAndFolder,
+}
+
+impl SimilarReason {
+    fn weight(self) -> f64 {
+        match self {
+            SimilarReason::SameContent => 1.0,
+            SimilarReason::SimilarName => 0.6,
+            SimilarReason::SameSize => 0.4,
+            SimilarReason::SameExtensionAndFolder => 0.2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimilarFile {
+    pub id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified: Option<u64>,
+    pub reasons: Vec<SimilarReason>,
+    /// Higher is more alike; the sum of the reasons' weights
+    pub score: f64,
+}
+
+/// Files of the scan that look like copies or siblings of the file `node_id`, most
+/// alike first. Content is compared only through hashes already cached, so nothing is
+/// read from disk beyond a stat of same-size files.
+pub fn find_similar(
+    tree: &ScanTree,
+    node_id: NodeId,
+    cache: &mut HashCache,
+    limit: usize,
+) -> Result<Vec<SimilarFile>, String> {
+    let target = tree.get(node_id).ok_or_else(|| format!("Unknown node: {}", node_id))?;
+    if target.kind != NodeKind::File {
+        return Err(format!("{} is not a file", target.path));
+    }
+    let target_name = name_bigrams(&target.name);
+    let target_folder = parent_name(tree, target);
+    let target_hash = cached_full_hash(&target.path, cache);
+
+    let mut found: Vec<SimilarFile> = tree
+        .nodes()
+        .filter(|node| node.kind == NodeKind::File && node.id != target.id && !node.informational)
+        .filter_map(|node| {
+            let mut reasons = Vec::new();
+            if node.size_bytes == target.size_bytes && node.size_bytes > 0 {
+                if target_hash.is_some() && cached_full_hash(&node.path, cache) == target_hash {
+                    reasons.push(SimilarReason::SameContent);
+                }
+                reasons.push(SimilarReason::SameSize);
+            }
+            if dice(&target_name, &name_bigrams(&node.name)) >= NAME_SIMILARITY {
+                reasons.push(SimilarReason::SimilarName);
+            }
+            if node.file_ext.is_some()
+                && node.file_ext == target.file_ext
+                && target_folder.is_some()
+                && parent_name(tree, node) == target_folder
+            {
+                reasons.push(SimilarReason::SameExtensionAndFolder);
+            }
+            if reasons.is_empty() {
+                return None;
+            }
+            Some(SimilarFile {
+                id: node.id,
+                path: node.path.clone(),
+                size_bytes: node.size_bytes,
+                modified: node.modified,
+                score: reasons.iter().map(|reason| reason.weight()).sum(),
+                reasons,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    found.truncate(limit);
+    Ok(found)
+}
+
+fn cached_full_hash(path: &str, cache: &mut HashCache) -> Option<String> {
+    let stamp = file_stamp(Path::new(path))?;
+    cache.get(path, stamp, HashKind::Full)
+}
+
+fn parent_name(tree: &ScanTree, node: &TreeNode) -> Option<String> {
+    node.parent
+        .and_then(|id| tree.get(id))
+        .map(|parent| parent.name.to_lowercase())
+}
+
+/// Letter pairs of a file name with the extension, numbers, copy markers such as
+/// "(2)" or "- Copy", and version tags such as "v3" left out
+fn name_bigrams(name: &str) -> HashSet<(char, char)> {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let words: Vec<&str> = stem.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    let is_noise = |word: &&str| {
+        NOISE_WORDS.contains(word)
+            || word.chars().all(|c| c.is_ascii_digit())
+            || (word.len() > 1 && word.starts_with('v') && word[1..].chars().all(|c| c.is_ascii_digit()))
+    };
+    let mut kept: String = words.iter().filter(|word| !is_noise(word)).copied().collect();
+    if kept.is_empty() {
+        kept = words.concat();
+    }
+    let chars: Vec<char> = kept.chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+fn dice(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64) -> TreeNode {
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        TreeNode {
+            id,
+            parent,
+            file_ext: Path::new(&name).extension().map(|ext| ext.to_string_lossy().to_lowercase()),
+            name,
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            modified: None,
+            informational: false,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_copies_renders_and_siblings() {
+        let tree = ScanTree::from_nodes(
+            1,
+            vec![
+                node(1, None, "/v", NodeKind::Dir, 0),
+                node(2, Some(1), "/v/renders", NodeKind::Dir, 0),
+                node(3, Some(2), "/v/renders/Wedding Video Final (2).mp4", NodeKind::File, 900),
+                node(4, Some(1), "/v/wedding_video_export_v3.mov", NodeKind::File, 700),
+                node(5, Some(1), "/v/backup.bin", NodeKind::File, 900),
+                node(6, Some(2), "/v/renders/teaser.mp4", NodeKind::File, 50),
+                node(7, Some(1), "/v/notes.txt", NodeKind::File, 3),
+            ],
+            false,
+        );
+        let found = find_similar(&tree, 3, &mut HashCache::default(), DEFAULT_SIMILAR_LIMIT).expect("similar");
+        let reasons: Vec<(NodeId, Vec<SimilarReason>)> =
+            found.iter().map(|file| (file.id, file.reasons.clone())).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (4, vec![SimilarReason::SimilarName]),
+                (5, vec![SimilarReason::SameSize]),
+                (6, vec![SimilarReason::SameExtensionAndFolder]),
+            ]
+        );
+        assert!(find_similar(&tree, 2, &mut HashCache::default(), 10).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::scan::model::{SkipGroup, SkipReason};
+
+/// Paths kept per reason, enough to show what was left out without growing unbounded
+const SAMPLE_LIMIT: usize = 20;
+
+/// Running count of what a scan left out, grouped by reason
+#[derive(Default)]
+pub struct SkipTally {
+    groups: Vec<SkipGroup>,
+    changed: bool,
+}
+
+impl SkipTally {
+    pub fn record(&mut self, reason: SkipReason, path: &Path) {
+        let index = match self.groups.iter().position(|group| group.reason == reason) {
+            Some(index) => index,
+            None => {
+                self.groups.push(SkipGroup {
+                    reason,
+                    count: 0,
+                    sample: Vec::new(),
+                });
+                self.groups.len() - 1
+            }
+        };
+        let group = &mut self.groups[index];
+        group.count += 1;
+        if group.sample.len() < SAMPLE_LIMIT {
+            group.sample.push(path.to_string_lossy().to_string());
+        }
+        self.changed = true;
+    }
+
+    /// The totals so far if anything was recorded since the last call
+    pub fn take_update(&mut self) -> Option<Vec<SkipGroup>> {
+        std::mem::take(&mut self.changed).then(|| self.groups.clone())
+    }
+
+    pub fn groups(&self) -> Vec<SkipGroup> {
+        self.groups.clone()
+    }
+}
+
+/// Build a matcher for the scan's exclude patterns, which use .gitignore syntax relative
+/// to the scan root
+pub fn exclude_matcher(root: &Path, patterns: &[String]) -> Result<Option<Gitignore>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|e| format!("Invalid exclude pattern '{}': {}", pattern, e))?;
+    }
+    builder.build().map(Some).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_skips_by_reason_with_bounded_samples() {
+        let mut tally = SkipTally::default();
+        for i in 0..25 {
+            tally.record(SkipReason::ExcludedPattern, Path::new(&format!("/data/cache{}", i)));
+        }
+        tally.record(SkipReason::SystemSkip, Path::new("C:/Windows"));
+
+        let groups = tally.take_update().expect("changed");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].count, 25);
+        assert_eq!(groups[0].sample.len(), SAMPLE_LIMIT);
+        assert_eq!(groups[1].reason, SkipReason::SystemSkip);
+        assert!(tally.take_update().is_none());
+    }
+
+    #[test]
+    fn exclude_patterns_use_gitignore_syntax() {
+        let root = Path::new("/data");
+        let matcher = exclude_matcher(root, &["*.iso".to_string(), "/build/".to_string()])
+            .expect("valid")
+            .expect("matcher");
+        assert!(matcher.matched(root.join("images/disk.iso"), false).is_ignore());
+        assert!(matcher.matched(root.join("build"), true).is_ignore());
+        assert!(!matcher.matched(root.join("src/build"), true).is_ignore());
+        assert!(exclude_matcher(root, &[]).expect("valid").is_none());
+    }
+}
@@ -0,0 +1,308 @@
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+
+use crate::scan::model::{NodeId, TreeNode};
+use crate::scan::tree::ScanTree;
+
+pub const SPILL_DIR: &str = "spill";
+const SPILL_EXTENSION: &str = "nodes";
+const TABLE_MAGIC: &[u8; 8] = b"DSNODES1";
+/// Magic, root id, flags, node count, id index offset, path index offset
+const HEADER_LEN: usize = 6 * 8;
+/// Node id, record offset, record length
+const ID_ENTRY_LEN: usize = 3 * 8;
+/// Path hash, node id
+const PATH_ENTRY_LEN: usize = 2 * 8;
+const FLAG_CASE_INSENSITIVE: u64 = 1;
+
+/// When finished scans move their node table to disk
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpillSettings {
+    #[serde(default = "default_spill_enabled")]
+    pub enabled: bool,
+    /// Estimated in-memory size of a tree above which it is spilled
+    #[serde(default = "default_spill_threshold")]
+    pub threshold_bytes: u64,
+}
+
+impl Default for SpillSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_spill_enabled(),
+            threshold_bytes: default_spill_threshold(),
+        }
+    }
+}
+
+fn default_spill_enabled() -> bool {
+    true
+}
+
+fn default_spill_threshold() -> u64 {
+    1024 * 1024 * 1024
+}
+
+/// The node table of a spilled tree, mapped read-only. Nodes are JSON records looked
+/// up through two sorted indexes, by id and by path hash, so nothing is read until a
+/// query asks for it. The file is deleted once the last tree using it is dropped.
+pub struct NodeTable {
+    map: ManuallyDrop<Mmap>,
+    path: PathBuf,
+    root_id: NodeId,
+    case_insensitive: bool,
+    count: usize,
+    ids_at: usize,
+    paths_at: usize,
+}
+
+impl NodeTable {
+    /// Map a table written by `write_table`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        // SAFETY: spill files are private to the app, written in full before they are
+        // renamed into place, and never changed afterwards
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())?;
+        if map.len() < HEADER_LEN || &map[..8] != TABLE_MAGIC {
+            return Err(format!("Not a spilled node table: {}", path.display()));
+        }
+        let mut table = Self {
+            map: ManuallyDrop::new(map),
+            path: path.to_path_buf(),
+            root_id: 0,
+            case_insensitive: false,
+            count: 0,
+            ids_at: 0,
+            paths_at: 0,
+        };
+        table.root_id = table.read_u64(8);
+        table.case_insensitive = table.read_u64(16) & FLAG_CASE_INSENSITIVE != 0;
+        table.count = table.read_u64(24) as usize;
+        table.ids_at = table.read_u64(32) as usize;
+        table.paths_at = table.read_u64(40) as usize;
+        let ids_end = table.count.checked_mul(ID_ENTRY_LEN).and_then(|len| len.checked_add(table.ids_at));
+        let paths_end = table.count.checked_mul(PATH_ENTRY_LEN).and_then(|len| len.checked_add(table.paths_at));
+        if ids_end.is_none_or(|end| end > table.map.len()) || paths_end.is_none_or(|end| end > table.map.len()) {
+            return Err(format!("Spilled node table is truncated: {}", path.display()));
+        }
+        Ok(table)
+    }
+
+    pub fn root_id(&self) -> NodeId {
+        self.root_id
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.count
+    }
+
+    /// Size of the file on disk
+    pub fn file_bytes(&self) -> u64 {
+        self.map.len() as u64
+    }
+
+    /// Every node id in the table, in id order
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.count).map(|index| self.read_u64(self.ids_at + index * ID_ENTRY_LEN))
+    }
+
+    /// Decode the node with `id`
+    pub fn get(&self, id: NodeId) -> Option<TreeNode> {
+        let index = self.search(self.ids_at, ID_ENTRY_LEN, id)?;
+        let entry = self.ids_at + index * ID_ENTRY_LEN;
+        if self.read_u64(entry) != id {
+            return None;
+        }
+        let start = self.read_u64(entry + 8) as usize;
+        let end = start.checked_add(self.read_u64(entry + 16) as usize)?;
+        serde_json::from_slice(self.map.get(start..end)?).ok()
+    }
+
+    /// Ids of the nodes whose path key hashes like `key`; callers compare the paths
+    pub fn ids_for_path(&self, key: &str) -> impl Iterator<Item = NodeId> + '_ {
+        let hash = path_hash(key);
+        let first = self.search(self.paths_at, PATH_ENTRY_LEN, hash).unwrap_or(self.count);
+        (first..self.count)
+            .map(|index| self.paths_at + index * PATH_ENTRY_LEN)
+            .take_while(move |entry| self.read_u64(*entry) == hash)
+            .map(|entry| self.read_u64(entry + 8))
+    }
+
+    /// Index of the first entry of a sorted index whose key is at least `key`
+    fn search(&self, at: usize, entry_len: usize, key: u64) -> Option<usize> {
+        let (mut low, mut high) = (0, self.count);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.read_u64(at + mid * entry_len) < key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low < self.count).then_some(low)
+    }
+
+    fn read_u64(&self, at: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.map[at..at + 8]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+impl Drop for NodeTable {
+    fn drop(&mut self) {
+        // SAFETY: the map is dropped once, here; Windows won't delete a mapped file
+        unsafe { ManuallyDrop::drop(&mut self.map) };
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl std::fmt::Debug for NodeTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeTable")
+            .field("path", &self.path)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+/// Where a scan's tree is spilled: the app data dir when there is one, else temp
+pub fn spill_path(data_dir: Option<&Path>, scan_id: &str) -> PathBuf {
+    let dir = data_dir
+        .map(|dir| dir.join(SPILL_DIR))
+        .unwrap_or_else(|| std::env::temp_dir().join("disksight-spill"));
+    dir.join(format!("{}.{}", scan_id, SPILL_EXTENSION))
+}
+
+/// Write a tree's nodes as a table `NodeTable` can map. Returns the size of the file.
+pub fn write_table(path: &Path, tree: &ScanTree) -> Result<u64, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp = path.with_extension("tmp");
+    let written = write_table_file(&temp, tree);
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())?;
+    fs::metadata(path).map(|metadata| metadata.len()).map_err(|e| e.to_string())
+}
+
+fn write_table_file(path: &Path, tree: &ScanTree) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    let io = |e: std::io::Error| e.to_string();
+    writer.write_all(&[0u8; HEADER_LEN]).map_err(io)?;
+
+    let mut offset = HEADER_LEN as u64;
+    let mut ids = Vec::with_capacity(tree.len());
+    let mut paths = Vec::with_capacity(tree.len());
+    for node in tree.nodes() {
+        let record = serde_json::to_vec(node).map_err(|e| e.to_string())?;
+        writer.write_all(&record).map_err(io)?;
+        ids.push([node.id, offset, record.len() as u64]);
+        paths.push([path_hash(&tree.path_key(&node.path)), node.id]);
+        offset += record.len() as u64;
+    }
+    ids.sort_unstable();
+    paths.sort_unstable();
+    let ids_at = offset;
+    for value in ids.iter().flatten() {
+        writer.write_all(&value.to_le_bytes()).map_err(io)?;
+    }
+    let paths_at = ids_at + (ids.len() * ID_ENTRY_LEN) as u64;
+    for value in paths.iter().flatten() {
+        writer.write_all(&value.to_le_bytes()).map_err(io)?;
+    }
+
+    let flags = if tree.case_insensitive() { FLAG_CASE_INSENSITIVE } else { 0 };
+    writer.seek(SeekFrom::Start(0)).map_err(io)?;
+    writer.write_all(TABLE_MAGIC).map_err(io)?;
+    for value in [tree.root_id, flags, ids.len() as u64, ids_at, paths_at] {
+        writer.write_all(&value.to_le_bytes()).map_err(io)?;
+    }
+    let file = writer.into_inner().map_err(|e| e.to_string())?;
+    file.sync_all().map_err(io)
+}
+
+/// FNV-1a, stable across runs so the path index can be written once and searched later
+fn path_hash(key: &str) -> u64 {
+    key.bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Remove trees spilled by earlier sessions; their results are gone from memory
+pub fn clear_spill_dir(data_dir: &Path) {
+    let Ok(entries) = fs::read_dir(data_dir.join(SPILL_DIR)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(SPILL_EXTENSION) || name.ends_with(".tmp") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::NodeKind;
+    use tempfile::tempdir;
+
+    #[test]
+    fn node_tables_find_nodes_by_id_and_path() {
+        let nodes = vec![
+            TreeNode {
+                id: 1,
+                parent: None,
+                name: "r".to_string(),
+                path: "/r".to_string(),
+                kind: NodeKind::Dir,
+                size_bytes: 5,
+                file_ext: None,
+                modified: None,
+                informational: false,
+                children: vec![2],
+            },
+            TreeNode {
+                id: 2,
+                parent: Some(1),
+                name: "a.txt".to_string(),
+                path: "/r/a.txt".to_string(),
+                kind: NodeKind::File,
+                size_bytes: 5,
+                file_ext: Some("txt".to_string()),
+                modified: Some(7),
+                informational: false,
+                children: Vec::new(),
+            },
+        ];
+        let tree = ScanTree::from_nodes(1, nodes, true);
+        let temp = tempdir().expect("tempdir");
+        let path = spill_path(Some(temp.path()), "scan");
+        assert!(write_table(&path, &tree).expect("write") > 0);
+
+        let table = NodeTable::open(&path).expect("open");
+        assert_eq!((table.root_id(), table.node_count(), table.case_insensitive()), (1, 2, true));
+        assert_eq!(table.ids().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(table.get(2).map(|node| node.modified), Some(Some(7)));
+        assert!(table.get(3).is_none());
+        assert_eq!(table.ids_for_path("/r/a.txt").collect::<Vec<_>>(), vec![2]);
+        assert_eq!(table.ids_for_path("/r/b.txt").count(), 0);
+        drop(table);
+        assert!(!path.exists());
+
+        write_table(&path, &tree).expect("rewrite");
+        clear_spill_dir(temp.path());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,166 @@
+use serde::Serialize;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::scan::model::{NodeId, NodeKind};
+use crate::scan::state::AppState;
+use crate::scan::tree::ScanTree;
+
+pub const EVENT_SCAN_STALE: &str = "scan://stale";
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Directories this many levels below the root are spot-checked
+const STAMP_DEPTH: usize = 2;
+const MAX_STAMPS: usize = 512;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StalePayload {
+    pub scan_id: String,
+    /// Directories whose contents changed on disk since the scan
+    pub paths: Vec<String>,
+    pub node_ids: Vec<NodeId>,
+}
+
+struct DirStamp {
+    id: NodeId,
+    path: String,
+    modified: Option<SystemTime>,
+}
+
+/// Modification times of the top levels of a scanned tree. A directory's mtime changes
+/// when entries are added, removed, or renamed directly inside it, so this notices most
+/// outside deletions without rewalking the tree.
+pub struct DirStamps {
+    stamps: Vec<DirStamp>,
+}
+
+impl DirStamps {
+    pub fn capture(tree: &ScanTree) -> Self {
+        let mut stamps = Vec::new();
+        let mut level: Vec<NodeId> = tree.root().map(|root| vec![root.id]).unwrap_or_default();
+        for depth in 0..=STAMP_DEPTH {
+            let mut next = Vec::new();
+            for id in level {
+                let Some(node) = tree.get(id) else {
+                    continue;
+                };
+                if stamps.len() >= MAX_STAMPS {
+                    break;
+                }
+                stamps.push(DirStamp {
+                    id,
+                    path: node.path.clone(),
+                    modified: modified(&node.path),
+                });
+                if depth < STAMP_DEPTH {
+                    next.extend(
+                        node.children
+                            .iter()
+                            .filter(|child| tree.get(**child).is_some_and(|c| c.kind == NodeKind::Dir)),
+                    );
+                }
+            }
+            level = next;
+        }
+        Self { stamps }
+    }
+
+    /// Approximate heap bytes held by the stamps
+    pub fn heap_bytes(&self) -> u64 {
+        self.stamps
+            .iter()
+            .map(|stamp| (std::mem::size_of::<DirStamp>() + stamp.path.capacity()) as u64)
+            .sum()
+    }
+
+    /// Directories that changed since the last check. Their stamps are updated so each
+    /// change is reported once.
+    pub fn take_changed(&mut self) -> Vec<(NodeId, String)> {
+        let mut changed = Vec::new();
+        for stamp in self.stamps.iter_mut() {
+            let current = modified(&stamp.path);
+            if current != stamp.modified {
+                stamp.modified = current;
+                changed.push((stamp.id, stamp.path.clone()));
+            }
+        }
+        changed
+    }
+
+    /// Re-read the stamps of directories directly containing `paths`, after the app
+    /// changed them itself and already patched the tree
+    pub fn refresh_parents(&mut self, paths: &[String]) {
+        for stamp in self.stamps.iter_mut() {
+            let is_parent = paths
+                .iter()
+                .any(|path| Path::new(path).parent() == Some(Path::new(&stamp.path)));
+            if is_parent {
+                stamp.modified = modified(&stamp.path);
+            }
+        }
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Periodically spot-check finished scans and emit `scan://stale` for changed subtrees
+pub fn spawn_stale_watcher(app_handle: AppHandle, state: AppState) {
+    thread::spawn(move || loop {
+        thread::sleep(STALE_CHECK_INTERVAL);
+        for (scan_id, changed) in state.take_stale_dirs() {
+            let payload = StalePayload {
+                scan_id,
+                node_ids: changed.iter().map(|(id, _)| *id).collect(),
+                paths: changed.into_iter().map(|(_, path)| path).collect(),
+            };
+            let _ = app_handle.emit(EVENT_SCAN_STALE, payload);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::tempdir;
+
+    use crate::scan::model::TreeNode;
+
+    #[test]
+    fn detects_entries_removed_outside_the_app() {
+        let temp = tempdir().expect("tempdir");
+        let sub = temp.path().join("sub");
+        fs::create_dir(&sub).expect("mkdir");
+        fs::write(sub.join("a.bin"), b"data").expect("write");
+
+        let dir = |id, parent, path: &Path, children| TreeNode {
+            id,
+            parent,
+            name: String::new(),
+            path: path.to_string_lossy().to_string(),
+            kind: NodeKind::Dir,
+            size_bytes: 0,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        };
+        let nodes = HashMap::from([(1, dir(1, None, temp.path(), vec![2])), (2, dir(2, Some(1), &sub, vec![]))]);
+        let index = nodes.values().map(|n| (n.path.clone(), n.id)).collect();
+        let mut stamps = DirStamps::capture(&ScanTree::new(1, nodes, index));
+        assert!(stamps.take_changed().is_empty());
+
+        // Back-date the stamp instead of sleeping past the filesystem's mtime granularity
+        stamps.stamps[1].modified = Some(SystemTime::UNIX_EPOCH);
+        fs::remove_file(sub.join("a.bin")).expect("remove");
+        let changed = stamps.take_changed();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].0, 2);
+        assert!(stamps.take_changed().is_empty());
+    }
+}
@@ -1,13 +1,51 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{atomic::AtomicBool, Arc, Mutex};
 use std::time::SystemTime;
 
-use crate::scan::model::ScanResult;
+use crate::scan::agent::LastScan;
+use crate::scan::confirm::{ConfirmationStore, ConfirmationToken};
+use crate::scan::duplicates::DuplicateGroup;
+use crate::scan::delete::SafetyLevel;
+use crate::scan::deletetime::DeleteCalibration;
+use crate::scan::events::ScanEmitter;
+use crate::scan::extstats::ExtensionStatsTable;
+use crate::scan::hashcache::{hash_cache_file_bytes, load_hash_cache, save_hash_cache, HashCache, HashCacheStats};
+use crate::scan::hooks::{load_hooks, HookConfig};
+use crate::scan::audit::{append_entries, read_entries, AuditEntry, AuditRange};
+use crate::scan::journal::{load_journal, mark_interrupted, save_journal, Journal};
+use crate::scan::memory::{self, ActiveScanMemory, CacheMemory, MemoryUsage, ScanMemory};
+use crate::scan::model::{NodeId, NodeKind, ScanResult};
+use crate::scan::rules::{Classifier, RulePackReport, RuleSet, RULES_DIR};
+use crate::scan::settings::{load_settings, save_settings, Settings};
+use crate::scan::spill::{self, NodeTable};
+use crate::scan::stale::DirStamps;
+use crate::scan::tags::{load_tags, save_tags, TagStore};
+use crate::scan::tree::ScanTree;
 
 #[derive(Clone)]
 pub struct AppState {
     active_scans: Arc<Mutex<HashMap<String, ScanState>>>,
     results: Arc<Mutex<HashMap<String, ScanResult>>>,
+    trees: Arc<Mutex<HashMap<String, ScanTree>>>,
+    stamps: Arc<Mutex<HashMap<String, DirStamps>>>,
+    duplicate_groups: Arc<Mutex<HashMap<String, DuplicateGroup>>>,
+    data_dir: Option<PathBuf>,
+    settings: Arc<Mutex<Settings>>,
+    audit_lock: Arc<Mutex<()>>,
+    journal: Arc<Mutex<Journal>>,
+    confirmations: Arc<Mutex<ConfirmationStore>>,
+    rules: Arc<Mutex<Arc<RuleSet>>>,
+    last_scan: Arc<Mutex<Option<LastScan>>>,
+    /// Unix millis each held scan finished at, by scan id
+    finished_at: Arc<Mutex<HashMap<String, u64>>>,
+    tags: Arc<Mutex<TagStore>>,
+    /// Stop flags of running size watches, by watch id
+    watches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// File hashes for duplicate search, loaded from the data dir on first use
+    hash_cache: Arc<Mutex<Option<HashCache>>>,
+    /// Delete timings measured per volume, by mount point
+    delete_calibrations: Arc<Mutex<HashMap<String, DeleteCalibration>>>,
 }
 
 impl AppState {
@@ -15,9 +53,197 @@ impl AppState {
         Self {
             active_scans: Arc::new(Mutex::new(HashMap::new())),
             results: Arc::new(Mutex::new(HashMap::new())),
+            trees: Arc::new(Mutex::new(HashMap::new())),
+            stamps: Arc::new(Mutex::new(HashMap::new())),
+            duplicate_groups: Arc::new(Mutex::new(HashMap::new())),
+            data_dir: None,
+            settings: Arc::new(Mutex::new(Settings::default())),
+            audit_lock: Arc::new(Mutex::new(())),
+            journal: Arc::new(Mutex::new(Journal::default())),
+            confirmations: Arc::new(Mutex::new(ConfirmationStore::default())),
+            rules: Arc::new(Mutex::new(RuleSet::builtin())),
+            last_scan: Arc::new(Mutex::new(None)),
+            finished_at: Arc::new(Mutex::new(HashMap::new())),
+            tags: Arc::new(Mutex::new(TagStore::default())),
+            watches: Arc::new(Mutex::new(HashMap::new())),
+            hash_cache: Arc::new(Mutex::new(None)),
+            delete_calibrations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Create state backed by the app data dir, loading persisted settings and user
+    /// rule packs from it
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        let settings = load_settings(&data_dir);
+        let mut journal = load_journal(&data_dir);
+        if mark_interrupted(&mut journal) {
+            let _ = save_journal(&data_dir, &journal);
+        }
+        let tags = load_tags(&data_dir);
+        let (rules, _) = RuleSet::load(Some(&data_dir.join(RULES_DIR)));
+        spill::clear_spill_dir(&data_dir);
+        Self {
+            data_dir: Some(data_dir),
+            settings: Arc::new(Mutex::new(settings)),
+            journal: Arc::new(Mutex::new(journal)),
+            rules: Arc::new(Mutex::new(Arc::new(rules))),
+            tags: Arc::new(Mutex::new(tags)),
+            ..Self::new()
+        }
+    }
+
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data_dir.as_deref()
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings
+            .lock()
+            .map(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
+    /// Apply a change to the settings and persist them (when a data dir is configured)
+    pub fn update_settings<F: FnOnce(&mut Settings)>(&self, update: F) -> Result<Settings, String> {
+        let mut guard = self.settings.lock().map_err(|e| e.to_string())?;
+        update(&mut guard);
+        if let Some(dir) = &self.data_dir {
+            save_settings(dir, &guard)?;
+        }
+        Ok(guard.clone())
+    }
+
+    /// The delete hooks, read from the hooks file on every use so edits apply at once
+    pub fn hooks(&self) -> HookConfig {
+        self.data_dir.as_deref().map(load_hooks).unwrap_or_default()
+    }
+
+    /// Folder holding user rule packs
+    pub fn rules_dir(&self) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(RULES_DIR))
+    }
+
+    /// The active rule set combined with the developer mode setting
+    pub fn classifier(&self) -> Classifier {
+        let rules = self
+            .rules
+            .lock()
+            .map(|guard| guard.clone())
+            .unwrap_or_else(|_| RuleSet::builtin());
+        Classifier {
+            rules,
+            developer_mode: self.settings().developer_mode,
+        }
+    }
+
+    /// Re-read the user rule packs and swap them in, reporting on every pack
+    pub fn reload_rules(&self) -> Vec<RulePackReport> {
+        let (rules, reports) = RuleSet::load(self.rules_dir().as_deref());
+        if let Ok(mut guard) = self.rules.lock() {
+            *guard = Arc::new(rules);
+        }
+        reports
+    }
+
+    /// Append to the audit log; a no-op without a data dir
+    pub fn record_audit(&self, entries: &[AuditEntry]) {
+        let Some(dir) = &self.data_dir else {
+            return;
+        };
+        if let Ok(_guard) = self.audit_lock.lock() {
+            let _ = append_entries(dir, entries);
+        }
+    }
+
+    /// Apply a change to the operation journal and persist it (when a data dir is configured)
+    pub fn update_journal<R, F: FnOnce(&mut Journal) -> R>(&self, update: F) -> Result<R, String> {
+        let mut guard = self.journal.lock().map_err(|e| e.to_string())?;
+        let result = update(&mut guard);
+        if let Some(dir) = &self.data_dir {
+            save_journal(dir, &guard)?;
+        }
+        Ok(result)
+    }
+
+    /// Apply a change to the path annotations and persist them (when a data dir is configured)
+    pub fn update_tags<R, F: FnOnce(&mut TagStore) -> R>(&self, update: F) -> Result<R, String> {
+        let mut guard = self.tags.lock().map_err(|e| e.to_string())?;
+        let result = update(&mut guard);
+        if let Some(dir) = &self.data_dir {
+            save_tags(dir, &guard)?;
+        }
+        Ok(result)
+    }
+
+    pub fn with_tags<R, F: FnOnce(&TagStore) -> R>(&self, query: F) -> Result<R, String> {
+        let guard = self.tags.lock().map_err(|e| e.to_string())?;
+        Ok(query(&guard))
+    }
+
+    /// Use the file hash cache, loading it on first use, and persist any change (when a
+    /// data dir is configured)
+    pub fn update_hash_cache<R, F: FnOnce(&mut HashCache) -> R>(&self, update: F) -> Result<R, String> {
+        let mut guard = self.hash_cache.lock().map_err(|e| e.to_string())?;
+        let cache = guard.get_or_insert_with(|| self.data_dir.as_deref().map(load_hash_cache).unwrap_or_default());
+        let result = update(cache);
+        if let Some(dir) = &self.data_dir {
+            save_hash_cache(dir, cache)?;
+        }
+        Ok(result)
+    }
+
+    pub fn hash_cache_stats(&self) -> Result<HashCacheStats, String> {
+        let file_bytes = self.data_dir.as_deref().map(hash_cache_file_bytes).unwrap_or(0);
+        self.update_hash_cache(|cache| cache.stats(file_bytes))
+    }
+
+    pub fn update_delete_calibrations<R, F: FnOnce(&mut HashMap<String, DeleteCalibration>) -> R>(
+        &self,
+        update: F,
+    ) -> Result<R, String> {
+        let mut guard = self.delete_calibrations.lock().map_err(|e| e.to_string())?;
+        Ok(update(&mut guard))
+    }
+
+    pub fn issue_confirmation(&self, paths: Vec<String>) -> Result<ConfirmationToken, String> {
+        let mut guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        Ok(guard.issue(paths))
+    }
+
+    pub fn validate_confirmation(&self, token: &str, path: &str) -> Result<(), String> {
+        let guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        guard.validate(token, path)
+    }
+
+    /// Validate a confirmation and use it up, so it can't be replayed
+    pub fn take_confirmation(&self, token: &str, path: &str) -> Result<(), String> {
+        let mut guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        guard.take(token, path)
+    }
+
+    pub fn issue_delete_confirmation(&self, items: Vec<(String, SafetyLevel)>) -> Result<ConfirmationToken, String> {
+        let mut guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        Ok(guard.issue_classified(items))
+    }
+
+    pub fn check_delete_confirmation(&self, token: &str, path: &str, safety: &SafetyLevel) -> Result<(), String> {
+        let guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        guard.validate_classified(token, path, safety)
+    }
+
+    pub fn consume_delete_confirmation(&self, token: &str, path: &str, safety: &SafetyLevel) -> Result<(), String> {
+        let mut guard = self.confirmations.lock().map_err(|e| e.to_string())?;
+        guard.consume(token, path, safety)
+    }
+
+    pub fn read_audit(&self, range: &AuditRange) -> Result<Vec<AuditEntry>, String> {
+        let Some(dir) = &self.data_dir else {
+            return Ok(Vec::new());
+        };
+        let _guard = self.audit_lock.lock().map_err(|e| e.to_string())?;
+        read_entries(dir, range)
+    }
+
     pub fn insert_scan(&self, scan_id: String, scan_state: ScanState) {
         if let Ok(mut guard) = self.active_scans.lock() {
             guard.insert(scan_id, scan_state);
@@ -31,6 +257,24 @@ impl AppState {
             .and_then(|guard| guard.get(scan_id).map(|s| s.cancel_flag.clone()))
     }
 
+    /// Register a size watch, returning the flag that stops it
+    pub fn add_watch(&self, watch_id: &str) -> Arc<AtomicBool> {
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Ok(mut guard) = self.watches.lock() {
+            guard.insert(watch_id.to_string(), stop.clone());
+        }
+        stop
+    }
+
+    /// Stop a size watch; false if no such watch is running
+    pub fn stop_watch(&self, watch_id: &str) -> bool {
+        let stop = self.watches.lock().ok().and_then(|mut guard| guard.remove(watch_id));
+        if let Some(stop) = &stop {
+            stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+        stop.is_some()
+    }
+
     pub fn cancel_scan(&self, scan_id: &str) -> bool {
         if let Ok(guard) = self.active_scans.lock() {
             if let Some(state) = guard.get(scan_id) {
@@ -41,15 +285,83 @@ impl AppState {
         false
     }
 
-    pub fn finish_scan(&self, scan_id: &str, result: ScanResult) {
+    pub fn finish_scan(&self, scan_id: &str, result: ScanResult, tree: ScanTree) {
+        let finished_at = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        // A canceled scan's partial result is kept but isn't the last scan
+        let last_scan = if result.complete { self.last_scan.lock().ok() } else { None };
+        if let Some(mut guard) = last_scan {
+            *guard = Some(LastScan {
+                scan_id: scan_id.to_string(),
+                root_path: tree.root().map(|root| root.path.clone()).unwrap_or_default(),
+                total_bytes: result.total_bytes,
+                finished_at,
+            });
+        }
+        if let Ok(mut guard) = self.finished_at.lock() {
+            guard.insert(scan_id.to_string(), finished_at);
+        }
         if let Ok(mut guard) = self.results.lock() {
             guard.insert(scan_id.to_string(), result);
         }
+        if let Ok(mut guard) = self.stamps.lock() {
+            guard.insert(scan_id.to_string(), DirStamps::capture(&tree));
+        }
+        let tree = self.spill_if_large(scan_id, tree);
+        if let Ok(mut guard) = self.trees.lock() {
+            guard.insert(scan_id.to_string(), tree);
+        }
         if let Ok(mut guard) = self.active_scans.lock() {
             guard.remove(scan_id);
         }
     }
 
+    pub fn last_scan(&self) -> Option<LastScan> {
+        self.last_scan.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    /// Running scans that report to a window, as (scan id, started at, emitter)
+    pub fn active_scan_emitters(&self) -> Vec<(String, SystemTime, ScanEmitter)> {
+        self.active_scans
+            .lock()
+            .map(|guard| {
+                guard
+                    .iter()
+                    .filter_map(|(id, scan)| Some((id.clone(), scan.started_at, scan.emitter.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Canonical roots of the running scans, by scan id
+    pub fn active_scan_roots(&self) -> Vec<(String, String)> {
+        self.active_scans
+            .lock()
+            .map(|guard| {
+                guard
+                    .iter()
+                    .filter_map(|(id, scan)| Some((id.clone(), scan.root.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn is_scan_active(&self, scan_id: &str) -> bool {
+        self.active_scans
+            .lock()
+            .map(|guard| guard.contains_key(scan_id))
+            .unwrap_or(false)
+    }
+
+    pub fn has_active_scans(&self) -> bool {
+        self.active_scans
+            .lock()
+            .map(|guard| !guard.is_empty())
+            .unwrap_or(false)
+    }
+
     pub fn remove_scan(&self, scan_id: &str) {
         if let Ok(mut guard) = self.active_scans.lock() {
             guard.remove(scan_id);
@@ -62,11 +374,267 @@ impl AppState {
             .ok()
             .and_then(|guard| guard.get(scan_id).cloned())
     }
+
+    /// Move a finished tree's nodes to a mapped table on disk when the tree is larger
+    /// than the spill threshold. A tree that can't be spilled stays in memory.
+    fn spill_if_large(&self, scan_id: &str, tree: ScanTree) -> ScanTree {
+        let settings = self.settings().spill;
+        if !settings.enabled || tree.heap_bytes() <= settings.threshold_bytes {
+            return tree;
+        }
+        let path = spill::spill_path(self.data_dir(), scan_id);
+        match spill::write_table(&path, &tree).and_then(|_| NodeTable::open(&path)) {
+            Ok(table) => ScanTree::mapped(table),
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);
+                tree
+            }
+        }
+    }
+
+    /// Drop everything kept for a finished scan. Returns false if nothing was stored.
+    pub fn release_scan(&self, scan_id: &str) -> bool {
+        let mut found = false;
+        if let Ok(mut guard) = self.results.lock() {
+            found |= guard.remove(scan_id).is_some();
+        }
+        if let Ok(mut guard) = self.trees.lock() {
+            found |= guard.remove(scan_id).is_some();
+        }
+        if let Ok(mut guard) = self.stamps.lock() {
+            guard.remove(scan_id);
+        }
+        if let Ok(mut guard) = self.finished_at.lock() {
+            guard.remove(scan_id);
+        }
+        if let Ok(mut guard) = self.duplicate_groups.lock() {
+            guard.retain(|_, group| group.scan_id != scan_id);
+        }
+        found
+    }
+
+    /// Root paths of the finished scans held, in memory or spilled, by scan id
+    pub fn scan_roots(&self) -> Vec<(String, String)> {
+        self.trees
+            .lock()
+            .map(|guard| {
+                guard
+                    .iter()
+                    .filter_map(|(scan_id, tree)| Some((scan_id.clone(), tree.root()?.path.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The newest finished scan held of each root, newest first
+    pub fn latest_scans(&self) -> Vec<LastScan> {
+        let finished_at = self.finished_at.lock().map(|guard| guard.clone()).unwrap_or_default();
+        let mut latest: HashMap<String, LastScan> = HashMap::new();
+        for (scan_id, root_path) in self.scan_roots() {
+            let scan = LastScan {
+                finished_at: finished_at.get(&scan_id).copied().unwrap_or(0),
+                total_bytes: self.get_result(&scan_id).map(|result| result.total_bytes).unwrap_or(0),
+                scan_id,
+                root_path: root_path.clone(),
+            };
+            if latest.get(&root_path).is_none_or(|held| held.finished_at < scan.finished_at) {
+                latest.insert(root_path, scan);
+            }
+        }
+        let mut scans: Vec<LastScan> = latest.into_values().collect();
+        scans.sort_by_key(|scan| std::cmp::Reverse(scan.finished_at));
+        scans
+    }
+
+    /// Estimated memory held per stored scan, per running scan, and by shared caches
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut scans: HashMap<String, ScanMemory> = HashMap::new();
+        if let Ok(guard) = self.trees.lock() {
+            for (scan_id, tree) in guard.iter() {
+                let scan = scan_memory(&mut scans, scan_id);
+                scan.root_path = tree.root().map(|root| root.path.clone()).unwrap_or_default();
+                scan.node_count = tree.len();
+                scan.tree_bytes = tree.heap_bytes();
+                scan.spilled_bytes = tree.spilled_bytes();
+            }
+        }
+        if let Ok(guard) = self.results.lock() {
+            for (scan_id, result) in guard.iter() {
+                scan_memory(&mut scans, scan_id).result_bytes = memory::result_bytes(result);
+            }
+        }
+        if let Ok(guard) = self.stamps.lock() {
+            for (scan_id, stamps) in guard.iter() {
+                scan_memory(&mut scans, scan_id).cache_bytes += stamps.heap_bytes();
+            }
+        }
+        if let Ok(guard) = self.duplicate_groups.lock() {
+            for group in guard.values() {
+                let bytes = serde_json::to_vec(group).map(|json| json.len() as u64).unwrap_or(0);
+                scan_memory(&mut scans, &group.scan_id).cache_bytes += bytes;
+            }
+        }
+        let mut scans: Vec<ScanMemory> = scans
+            .into_values()
+            .map(|mut scan| {
+                scan.total_bytes = scan.tree_bytes + scan.result_bytes + scan.cache_bytes;
+                scan
+            })
+            .collect();
+        scans.sort_by_key(|scan| std::cmp::Reverse(scan.total_bytes));
+
+        let active_scans: Vec<ActiveScanMemory> = self
+            .active_scan_emitters()
+            .into_iter()
+            .map(|(scan_id, _, emitter)| {
+                let visited_entries = emitter.pulse().visited();
+                ActiveScanMemory {
+                    scan_id,
+                    visited_entries,
+                    estimated_bytes: memory::active_scan_bytes(visited_entries),
+                }
+            })
+            .collect();
+
+        let mut caches = Vec::new();
+        if let Ok(guard) = self.tags.lock() {
+            let bytes = serde_json::to_vec(&*guard).map(|json| json.len() as u64).unwrap_or(0);
+            caches.push(CacheMemory { name: "tags".to_string(), bytes });
+        }
+        if let Ok(guard) = self.journal.lock() {
+            let bytes = serde_json::to_vec(&*guard).map(|json| json.len() as u64).unwrap_or(0);
+            caches.push(CacheMemory { name: "journal".to_string(), bytes });
+        }
+
+        let tracked_bytes = scans.iter().map(|scan| scan.total_bytes).sum::<u64>()
+            + active_scans.iter().map(|scan| scan.estimated_bytes).sum::<u64>()
+            + caches.iter().map(|cache| cache.bytes).sum::<u64>();
+        MemoryUsage {
+            process_bytes: memory::process_resident_bytes(),
+            scans,
+            active_scans,
+            caches,
+            tracked_bytes,
+        }
+    }
+
+    /// Run a read-only query against the stored tree of a finished scan
+    pub fn with_tree<R, F: FnOnce(&ScanTree) -> R>(&self, scan_id: &str, query: F) -> Result<R, String> {
+        let mut guard = self.trees.lock().map_err(|e| e.to_string())?;
+        let tree = guard
+            .get_mut(scan_id)
+            .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+        let result = query(tree);
+        tree.trim_cache();
+        Ok(result)
+    }
+
+    /// Replace the remembered duplicate groups of a scan
+    pub fn store_duplicate_groups(&self, scan_id: &str, groups: &[DuplicateGroup]) {
+        if let Ok(mut guard) = self.duplicate_groups.lock() {
+            guard.retain(|_, group| group.scan_id != scan_id);
+            for group in groups {
+                guard.insert(group.id.clone(), group.clone());
+            }
+        }
+    }
+
+    pub fn get_duplicate_group(&self, group_id: &str) -> Option<DuplicateGroup> {
+        self.duplicate_groups
+            .lock()
+            .ok()
+            .and_then(|guard| guard.get(group_id).cloned())
+    }
+
+    /// Drop deleted files from a group, forgetting it once fewer than two copies remain
+    pub fn remove_duplicate_files(&self, group_id: &str, removed: &[NodeId]) {
+        if let Ok(mut guard) = self.duplicate_groups.lock() {
+            if let Some(group) = guard.get_mut(group_id) {
+                group.files.retain(|f| !removed.contains(&f.id));
+                if group.files.len() < 2 {
+                    guard.remove(group_id);
+                }
+            }
+        }
+    }
+
+    /// Apply a change to the stored tree of a finished scan
+    pub fn update_tree<R, F: FnOnce(&mut ScanTree) -> R>(&self, scan_id: &str, update: F) -> Result<R, String> {
+        let mut guard = self.trees.lock().map_err(|e| e.to_string())?;
+        let tree = guard
+            .get_mut(scan_id)
+            .ok_or_else(|| format!("No finished scan with id {}", scan_id))?;
+        let result = update(tree);
+        tree.trim_cache();
+        Ok(result)
+    }
+
+    /// Remove deleted nodes from a finished scan's tree and subtract their files from
+    /// the stored extension stats. Returns the bytes removed.
+    pub fn prune_nodes(&self, scan_id: &str, ids: &[NodeId]) -> Result<u64, String> {
+        let removed = self.update_tree(scan_id, |tree| {
+            ids.iter()
+                .filter_map(|id| tree.take_subtree(*id))
+                .flatten()
+                .collect::<Vec<_>>()
+        })?;
+        if let Ok(mut guard) = self.results.lock() {
+            if let Some(result) = guard.get_mut(scan_id) {
+                let mut stats = ExtensionStatsTable::from_stats(&result.extension_stats);
+                stats.remove_nodes(&removed);
+                result.extension_stats = stats.into_stats();
+            }
+        }
+        Ok(removed
+            .iter()
+            .filter(|node| node.kind == NodeKind::File)
+            .map(|node| node.size_bytes)
+            .sum())
+    }
+
+    /// Spot-check finished scans for directories changed on disk, per scan id
+    pub fn take_stale_dirs(&self) -> Vec<(String, Vec<(NodeId, String)>)> {
+        let Ok(mut guard) = self.stamps.lock() else {
+            return Vec::new();
+        };
+        guard
+            .iter_mut()
+            .map(|(scan_id, stamps)| (scan_id.clone(), stamps.take_changed()))
+            .filter(|(_, changed)| !changed.is_empty())
+            .collect()
+    }
+
+    /// Accept changes the app made itself under `paths` so they aren't reported as stale
+    pub fn refresh_stamps(&self, scan_id: &str, paths: &[String]) {
+        if let Ok(mut guard) = self.stamps.lock() {
+            if let Some(stamps) = guard.get_mut(scan_id) {
+                stamps.refresh_parents(paths);
+            }
+        }
+    }
+}
+
+/// The memory entry of a scan, created empty on first use
+fn scan_memory<'a>(scans: &'a mut HashMap<String, ScanMemory>, scan_id: &str) -> &'a mut ScanMemory {
+    scans.entry(scan_id.to_string()).or_insert_with(|| ScanMemory {
+        scan_id: scan_id.to_string(),
+        root_path: String::new(),
+        node_count: 0,
+        tree_bytes: 0,
+        result_bytes: 0,
+        cache_bytes: 0,
+        spilled_bytes: None,
+        total_bytes: 0,
+    })
 }
 
 pub struct ScanState {
     pub cancel_flag: Arc<AtomicBool>,
     pub started_at: SystemTime,
+    /// Where the scan reports to, for the watchdog's heartbeats
+    pub emitter: Option<ScanEmitter>,
+    /// Canonical root being walked, for spotting overlapping scans
+    pub root: Option<String>,
 }
 
 impl ScanState {
@@ -74,6 +642,114 @@ impl ScanState {
         Self {
             cancel_flag: Arc::new(AtomicBool::new(false)),
             started_at: SystemTime::now(),
+            emitter: None,
+            root: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::TreeNode;
+    use tempfile::tempdir;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
         }
     }
+
+    fn sample_tree() -> ScanTree {
+        let nodes = vec![
+            node(1, None, "/r", NodeKind::Dir, 10, vec![2, 4]),
+            node(2, Some(1), "/r/a", NodeKind::Dir, 7, vec![3]),
+            node(3, Some(2), "/r/a/x", NodeKind::File, 7, vec![]),
+            node(4, Some(1), "/r/y", NodeKind::File, 3, vec![]),
+        ];
+        ScanTree::from_nodes(1, nodes, false)
+    }
+
+    /// State that spills every finished tree
+    fn spilling_state(data_dir: PathBuf) -> AppState {
+        let state = AppState::with_data_dir(data_dir);
+        state
+            .update_settings(|settings| settings.spill.threshold_bytes = 0)
+            .expect("settings");
+        state
+    }
+
+    fn store_tree(state: &AppState, scan_id: &str, tree: ScanTree) {
+        let tree = state.spill_if_large(scan_id, tree);
+        state.trees.lock().expect("trees").insert(scan_id.to_string(), tree);
+    }
+
+    #[test]
+    fn spilled_trees_evict_decoded_nodes_and_reload_them_from_disk() {
+        let temp = tempdir().expect("tempdir");
+        let state = spilling_state(temp.path().to_path_buf());
+        store_tree(&state, "s1", sample_tree());
+        let table = spill::spill_path(state.data_dir(), "s1");
+        assert!(table.exists());
+
+        let held = |state: &AppState| state.with_tree("s1", ScanTree::heap_bytes).expect("heap");
+        let idle = held(&state);
+        let found = state.with_tree("s1", |tree| {
+            // Decoded nodes stay in memory for the length of the query
+            let size = tree.find_by_path("/r/a/x").map(|n| n.size_bytes);
+            (size, tree.heap_bytes() > idle)
+        });
+        assert_eq!(found, Ok((Some(7), true)));
+        // and files are dropped from the cache afterwards, directories kept hot
+        assert_eq!(held(&state), idle);
+        let listed = state.with_tree("s1", |tree| {
+            let root = tree.root().expect("root");
+            root.children.iter().filter_map(|id| tree.get(*id)).map(|n| n.size_bytes).sum::<u64>()
+        });
+        assert_eq!(listed, Ok(10));
+        let hot = held(&state);
+        assert!(hot > idle);
+        assert_eq!(state.with_tree("s1", |tree| tree.nodes().count()), Ok(4));
+        assert_eq!(held(&state), hot);
+
+        let removed = state.update_tree("s1", |tree| tree.remove_subtree(2)).expect("update");
+        assert_eq!(removed, Some(7));
+        let after = state.with_tree("s1", |tree| {
+            (tree.len(), tree.root().map(|r| r.size_bytes), tree.get(3).is_none())
+        });
+        assert_eq!(after, Ok((2, Some(3), true)));
+
+        let usage = state.memory_usage();
+        let scan = usage.scans.iter().find(|scan| scan.scan_id == "s1").expect("scan memory");
+        assert!(scan.spilled_bytes.is_some_and(|bytes| bytes > 0));
+        assert_eq!((scan.root_path.as_str(), scan.node_count), ("/r", 2));
+
+        assert!(state.release_scan("s1"));
+        assert!(!table.exists());
+    }
+
+    #[test]
+    fn trees_that_fail_to_spill_stay_in_memory() {
+        let temp = tempdir().expect("tempdir");
+        let state = spilling_state(temp.path().to_path_buf());
+        // A file where the spill folder belongs makes every write fail
+        std::fs::write(temp.path().join(spill::SPILL_DIR), b"").expect("block spill dir");
+        store_tree(&state, "s1", sample_tree());
+
+        let queried = state.with_tree("s1", |tree| {
+            (tree.spilled_bytes(), tree.len(), tree.find_by_path("/r/y").is_some())
+        });
+        assert_eq!(queried, Ok((None, 4, true)));
+        assert_eq!(state.update_tree("s1", |tree| tree.remove_subtree(4)), Ok(Some(3)));
+        assert_eq!(state.scan_roots(), vec![("s1".to_string(), "/r".to_string())]);
+    }
 }
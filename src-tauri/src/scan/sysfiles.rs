@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Paging, hibernation, and swap files that live at a volume root
+#[cfg(target_os = "windows")]
+pub const SYSTEM_FILES: &[&str] = &["pagefile.sys", "hiberfil.sys", "swapfile.sys"];
+#[cfg(not(target_os = "windows"))]
+pub const SYSTEM_FILES: &[&str] = &["swapfile", "swap.img"];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemFileKind {
+    Pagefile,
+    Hibernation,
+    Swapfile,
+}
+
+/// Ways to reclaim the space without deleting the file directly
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemFileGuidance {
+    /// `powercfg /hibernate off` removes hiberfil.sys
+    HibernationCanBeDisabled,
+    /// `powercfg /h /type reduced` keeps Fast Startup with a smaller file
+    HibernationCanBeReduced,
+    /// Virtual memory settings allow a fixed, smaller size
+    SizeCanBeLimited,
+    /// The paging file can be placed on another drive
+    CanMoveToAnotherDrive,
+}
+
+/// Details shown for a paging, hibernation, or swap file
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SystemFileInfo {
+    pub kind: SystemFileKind,
+    pub guidance: Vec<SystemFileGuidance>,
+}
+
+/// Recognize a system file by name, but only at the root of a volume
+pub fn system_file_info(path: &Path) -> Option<SystemFileInfo> {
+    let parent = path.parent()?;
+    if parent.parent().is_some() {
+        return None;
+    }
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if !SYSTEM_FILES.contains(&name.as_str()) {
+        return None;
+    }
+    let (kind, guidance) = match name.as_str() {
+        "hiberfil.sys" => (
+            SystemFileKind::Hibernation,
+            vec![
+                SystemFileGuidance::HibernationCanBeDisabled,
+                SystemFileGuidance::HibernationCanBeReduced,
+            ],
+        ),
+        "pagefile.sys" => (
+            SystemFileKind::Pagefile,
+            vec![
+                SystemFileGuidance::SizeCanBeLimited,
+                SystemFileGuidance::CanMoveToAnotherDrive,
+            ],
+        ),
+        "swapfile.sys" => (SystemFileKind::Swapfile, Vec::new()),
+        _ => (SystemFileKind::Swapfile, vec![SystemFileGuidance::SizeCanBeLimited]),
+    };
+    Some(SystemFileInfo { kind, guidance })
+}
+
+pub fn is_system_file(path: &Path) -> bool {
+    system_file_info(path).is_some()
+}
+
+/// Size of a system file such as pagefile.sys. These are held open exclusively by the
+/// kernel, so plain metadata calls fail; the directory listing still reports the size.
+#[cfg(target_os = "windows")]
+pub fn system_file_size(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::{FindClose, FindFirstFileW, WIN32_FIND_DATAW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data: WIN32_FIND_DATAW = unsafe { std::mem::zeroed() };
+    let handle = unsafe { FindFirstFileW(wide.as_ptr(), &mut data) };
+    if handle == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    unsafe { FindClose(handle) };
+    Some((u64::from(data.nFileSizeHigh) << 32) | u64::from(data.nFileSizeLow))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn system_file_size(path: &Path) -> Option<u64> {
+    std::fs::symlink_metadata(path)
+        .ok()
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_system_files_only_at_volume_root() {
+        let name = SYSTEM_FILES[0];
+        let root = if cfg!(target_os = "windows") { "C:\\" } else { "/" };
+        let at_root = Path::new(root).join(name);
+        assert!(is_system_file(&at_root));
+        assert!(!system_file_info(&at_root).expect("info").guidance.is_empty());
+        assert!(!is_system_file(&Path::new(root).join("backup").join(name)));
+        assert!(!is_system_file(&Path::new(root).join("notes.txt")));
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scan::model::NodeId;
+use crate::scan::tree::ScanTree;
+
+const TAGS_FILE: &str = "tags.json";
+
+/// Tags and a note the user attached to a path
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Annotation {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// Annotations keyed by path, kept across scans and sessions
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TagStore {
+    #[serde(default)]
+    pub entries: BTreeMap<String, Annotation>,
+}
+
+/// An annotated path found in a scan tree
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaggedNode {
+    pub node_id: NodeId,
+    pub path: String,
+    pub size_bytes: u64,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+}
+
+impl TagStore {
+    /// Replace the annotation for `path`. Tags are trimmed and deduplicated; with no
+    /// tags and no note the entry is removed.
+    pub fn set(&mut self, path: &str, tags: Vec<String>, note: Option<String>) -> Option<Annotation> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+            if !cleaned.iter().any(|seen| seen.eq_ignore_ascii_case(tag)) {
+                cleaned.push(tag.to_string());
+            }
+        }
+        let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+        if cleaned.is_empty() && note.is_none() {
+            self.entries.remove(path);
+            return None;
+        }
+        let annotation = Annotation {
+            tags: cleaned,
+            note,
+            updated_at: now_millis(),
+        };
+        self.entries.insert(path.to_string(), annotation.clone());
+        Some(annotation)
+    }
+
+    /// Annotations for every path present in `tree`
+    pub fn for_tree(&self, tree: &ScanTree) -> Vec<TaggedNode> {
+        self.entries
+            .iter()
+            .filter_map(|(path, annotation)| {
+                let node = tree.find_by_path(path)?;
+                Some(TaggedNode {
+                    node_id: node.id,
+                    path: path.clone(),
+                    size_bytes: node.size_bytes,
+                    tags: annotation.tags.clone(),
+                    note: annotation.note.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn load_tags(dir: &Path) -> TagStore {
+    fs::read_to_string(dir.join(TAGS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the store, writing to a temp file first so a crash can't truncate it
+pub fn save_tags(dir: &Path, store: &TagStore) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let contents = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", TAGS_FILE));
+    fs::write(&temp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(TAGS_FILE)).map_err(|e| e.to_string())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_tags_and_removes_empty_annotations() {
+        let mut store = TagStore::default();
+        let annotation = store
+            .set(
+                "/data/old",
+                vec![" archive to NAS ".to_string(), "Archive to NAS".to_string(), "".to_string()],
+                Some("  ".to_string()),
+            )
+            .expect("annotation");
+        assert_eq!(annotation.tags, vec!["archive to NAS".to_string()]);
+        assert_eq!(annotation.note, None);
+
+        assert!(store.set("/data/old", Vec::new(), None).is_none());
+        assert!(store.entries.is_empty());
+    }
+}
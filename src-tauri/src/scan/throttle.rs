@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Rate limits for bulk deletes so they don't saturate the disk; None means unlimited
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeleteThrottle {
+    #[serde(default)]
+    pub max_files_per_sec: Option<u32>,
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl DeleteThrottle {
+    pub fn is_limited(&self) -> bool {
+        self.max_files_per_sec.is_some_and(|n| n > 0) || self.max_bytes_per_sec.is_some_and(|n| n > 0)
+    }
+}
+
+/// Paces a delete loop to stay under a `DeleteThrottle`
+pub struct Throttle {
+    limits: DeleteThrottle,
+    started: Instant,
+    files: u64,
+    bytes: u64,
+}
+
+impl Throttle {
+    pub fn new(limits: DeleteThrottle) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            files: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(DeleteThrottle::default())
+    }
+
+    /// Account for deleted entries, sleeping long enough to stay under the limits
+    pub fn record(&mut self, files: u64, bytes: u64) {
+        if !self.limits.is_limited() {
+            return;
+        }
+        self.files += files;
+        self.bytes += bytes;
+        let wait = self.required_elapsed().saturating_sub(self.started.elapsed());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Minimum time the work recorded so far may take
+    fn required_elapsed(&self) -> Duration {
+        let by_files = self
+            .limits
+            .max_files_per_sec
+            .filter(|n| *n > 0)
+            .map(|n| self.files as f64 / f64::from(n))
+            .unwrap_or(0.0);
+        let by_bytes = self
+            .limits
+            .max_bytes_per_sec
+            .filter(|n| *n > 0)
+            .map(|n| self.bytes as f64 / n as f64)
+            .unwrap_or(0.0);
+        Duration::from_secs_f64(by_files.max(by_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paces_by_the_tighter_limit() {
+        let mut throttle = Throttle::new(DeleteThrottle {
+            max_files_per_sec: Some(100),
+            max_bytes_per_sec: Some(1_000),
+        });
+        throttle.files = 50;
+        throttle.bytes = 2_000;
+        assert_eq!(throttle.required_elapsed(), Duration::from_secs(2));
+        throttle.bytes = 0;
+        assert_eq!(throttle.required_elapsed(), Duration::from_millis(500));
+
+        let mut unlimited = Throttle::unlimited();
+        unlimited.record(1_000_000, u64::MAX);
+        assert_eq!(unlimited.required_elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Mutex;
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Window, WindowEvent};
+
+use crate::scan::agent::{format_bytes, run_scheduled_scan, AgentStatus};
+use crate::scan::state::AppState;
+
+pub const TRAY_ID: &str = "disksight";
+const MAIN_WINDOW: &str = "main";
+const MENU_OPEN: &str = "open";
+const MENU_SCAN: &str = "scan_now";
+const MENU_QUIT: &str = "quit";
+
+/// Menu entries whose text the agent keeps up to date; None while there is no tray icon
+#[derive(Default)]
+pub struct TrayState(Mutex<Option<TrayStats>>);
+
+struct TrayStats {
+    last_scan: MenuItem,
+    freed: MenuItem,
+    space: MenuItem,
+}
+
+/// Show the tray icon, if it isn't shown already
+pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
+    if app.tray_by_id(TRAY_ID).is_some() {
+        return Ok(());
+    }
+    let last_scan = MenuItem::with_id(app, "last_scan", "No scan yet", false, None::<&str>)?;
+    let freed = MenuItem::with_id(app, "freed", "Nothing freed yet", false, None::<&str>)?;
+    let space = MenuItem::with_id(app, "space", "Checking free space", false, None::<&str>)?;
+    let open = MenuItem::with_id(app, MENU_OPEN, "Open DiskSight", true, None::<&str>)?;
+    let scan = MenuItem::with_id(app, MENU_SCAN, "Run scheduled scan now", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit DiskSight", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &last_scan,
+            &freed,
+            &space,
+            &PredefinedMenuItem::separator(app)?,
+            &open,
+            &scan,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("DiskSight")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    if let Ok(mut guard) = app.state::<TrayState>().0.lock() {
+        *guard = Some(TrayStats { last_scan, freed, space });
+    }
+    Ok(())
+}
+
+pub fn remove_tray(app: &AppHandle) {
+    app.remove_tray_by_id(TRAY_ID);
+    if let Ok(mut guard) = app.state::<TrayState>().0.lock() {
+        *guard = None;
+    }
+}
+
+/// Update the stats lines of the tray menu
+pub fn refresh(app: &AppHandle, status: &AgentStatus) {
+    let Some(tray) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let Ok(guard) = tray.0.lock() else {
+        return;
+    };
+    let Some(stats) = guard.as_ref() else {
+        return;
+    };
+    let last_scan = match &status.last_scan {
+        Some(scan) => format!("Last scan: {} ({})", scan.root_path, format_bytes(scan.total_bytes)),
+        None => "No scan yet".to_string(),
+    };
+    let freed = match status.freed_since_last_scan {
+        0 => "Nothing freed since the last scan".to_string(),
+        bytes => format!("Last scan freed {}", format_bytes(bytes)),
+    };
+    let space = match &status.lowest_free {
+        Some((mount, percent)) => format!("Lowest free space: {} {}%", mount, percent),
+        None => "No fixed volumes found".to_string(),
+    };
+    let _ = stats.last_scan.set_text(last_scan);
+    let _ = stats.freed.set_text(freed);
+    let _ = stats.space.set_text(space);
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_OPEN => show_main_window(app),
+        MENU_SCAN => {
+            run_scheduled_scan(app, &app.state::<AppState>());
+        }
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub fn hide_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.hide();
+    }
+}
+
+/// Closing the window hides it instead while the tray agent is enabled
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.state::<AppState>().settings().agent.run_in_tray {
+            let _ = window.hide();
+            api.prevent_close();
+        }
+    }
+}
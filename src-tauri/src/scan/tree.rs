@@ -0,0 +1,475 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::scan::model::{NodeId, NodeKind, TreeNode};
+use crate::scan::spill::NodeTable;
+
+/// Path -> node lookup whose keys follow the scanned filesystem's case rules, so a path
+/// reached with different casing on NTFS or APFS finds the same node
+#[derive(Clone, Debug, Default)]
+pub struct PathIndex {
+    map: HashMap<String, NodeId>,
+    case_insensitive: bool,
+}
+
+impl PathIndex {
+    pub fn new(case_insensitive: bool) -> Self {
+        Self {
+            map: HashMap::new(),
+            case_insensitive,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize, case_insensitive: bool) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+            case_insensitive,
+        }
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    fn key<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        if self.case_insensitive {
+            Cow::Owned(path.to_lowercase())
+        } else {
+            Cow::Borrowed(path)
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<NodeId> {
+        self.map.get(self.key(path).as_ref()).copied()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.map.contains_key(self.key(path).as_ref())
+    }
+
+    pub fn insert(&mut self, path: &str, id: NodeId) {
+        let key = self.key(path).into_owned();
+        self.map.insert(key, id);
+    }
+
+    pub fn remove(&mut self, path: &str) -> Option<NodeId> {
+        let key = self.key(path);
+        self.map.remove(key.as_ref())
+    }
+
+    fn heap_bytes(&self) -> u64 {
+        self.map
+            .keys()
+            .map(|key| (std::mem::size_of::<(String, NodeId)>() + key.capacity()) as u64)
+            .sum()
+    }
+}
+
+impl From<HashMap<String, NodeId>> for PathIndex {
+    /// An exact-match index, for trees built without knowing the filesystem
+    fn from(map: HashMap<String, NodeId>) -> Self {
+        Self {
+            map,
+            case_insensitive: false,
+        }
+    }
+}
+
+/// Directories a spilled tree keeps decoded between queries, most recently used first
+const HOT_DIRECTORIES: usize = 4096;
+
+/// The node tree of a finished scan, kept so later commands can query and update it.
+/// A spilled tree reads its nodes from a mapped node table as queries reach them and
+/// holds only the nodes changed since, plus a cache trimmed back to the hot
+/// directories after every query.
+#[derive(Clone, Debug, Default)]
+pub struct ScanTree {
+    pub root_id: NodeId,
+    /// Every node, or for a spilled tree those changed since it was spilled
+    nodes: HashMap<NodeId, TreeNode>,
+    /// Empty for a spilled tree, whose table has its own path index
+    path_index: PathIndex,
+    mapped: Option<MappedNodes>,
+}
+
+/// The on-disk side of a spilled tree
+#[derive(Debug)]
+struct MappedNodes {
+    table: Arc<NodeTable>,
+    /// Nodes removed since the tree was spilled
+    removed: HashSet<NodeId>,
+    /// Nodes decoded from the table, boxed so a reference handed out through `&self`
+    /// stays valid until the cache is trimmed through `&mut self`
+    cache: Mutex<HashMap<NodeId, CachedNode>>,
+    uses: AtomicU64,
+}
+
+#[derive(Debug)]
+struct CachedNode {
+    node: Box<TreeNode>,
+    last_used: u64,
+}
+
+impl Clone for MappedNodes {
+    /// Clones share the table and start with an empty cache
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            removed: self.removed.clone(),
+            cache: Mutex::new(HashMap::new()),
+            uses: AtomicU64::new(0),
+        }
+    }
+}
+
+impl MappedNodes {
+    fn get(&self, id: NodeId) -> Option<&TreeNode> {
+        if self.removed.contains(&id) {
+            return None;
+        }
+        let last_used = self.uses.fetch_add(1, Ordering::Relaxed);
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let node: *const TreeNode = match cache.entry(id) {
+            Entry::Occupied(mut cached) => {
+                cached.get_mut().last_used = last_used;
+                &*cached.get().node
+            }
+            Entry::Vacant(vacant) => {
+                let node = Box::new(self.table.get(id)?);
+                &*vacant.insert(CachedNode { node, last_used }).node
+            }
+        };
+        // SAFETY: the box outlives this borrow of self; cached nodes are only dropped
+        // by `trim` and `take`, which need &mut self, or with the tree
+        Some(unsafe { &*node })
+    }
+
+    /// Take a node out of the cache, or decode it, for an update through &mut self
+    fn take(&mut self, id: NodeId) -> Option<TreeNode> {
+        if self.removed.contains(&id) {
+            return None;
+        }
+        let cache = self.cache.get_mut().unwrap_or_else(PoisonError::into_inner);
+        cache.remove(&id).map(|cached| *cached.node).or_else(|| self.table.get(id))
+    }
+
+    /// Drop every cached file and all but the most recently used directories
+    fn trim(&mut self, keep_dirs: usize) {
+        let cache = self.cache.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let mut dirs: Vec<(u64, NodeId)> = cache
+            .iter()
+            .filter(|(_, cached)| cached.node.kind == NodeKind::Dir)
+            .map(|(id, cached)| (cached.last_used, *id))
+            .collect();
+        dirs.sort_unstable_by(|a, b| b.cmp(a));
+        let hot: HashSet<NodeId> = dirs.into_iter().take(keep_dirs).map(|(_, id)| id).collect();
+        cache.retain(|id, _| hot.contains(id));
+    }
+
+    fn cached_bytes(&self) -> u64 {
+        let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.values().map(|cached| node_heap_bytes(&cached.node)).sum()
+    }
+}
+
+impl ScanTree {
+    pub fn new(
+        root_id: NodeId,
+        nodes: HashMap<NodeId, TreeNode>,
+        path_index: HashMap<String, NodeId>,
+    ) -> Self {
+        Self::with_index(root_id, nodes, path_index.into())
+    }
+
+    pub fn with_index(root_id: NodeId, nodes: HashMap<NodeId, TreeNode>, path_index: PathIndex) -> Self {
+        Self {
+            root_id,
+            nodes,
+            path_index,
+            mapped: None,
+        }
+    }
+
+    /// Rebuild a tree, with its path index, from a flat list of nodes
+    pub fn from_nodes(root_id: NodeId, nodes: Vec<TreeNode>, case_insensitive: bool) -> Self {
+        let mut path_index = PathIndex::with_capacity(nodes.len(), case_insensitive);
+        for node in &nodes {
+            path_index.insert(&node.path, node.id);
+        }
+        let nodes = nodes.into_iter().map(|node| (node.id, node)).collect();
+        Self::with_index(root_id, nodes, path_index)
+    }
+
+    /// A tree read from a spilled node table
+    pub fn mapped(table: NodeTable) -> Self {
+        Self {
+            root_id: table.root_id(),
+            nodes: HashMap::new(),
+            path_index: PathIndex::new(table.case_insensitive()),
+            mapped: Some(MappedNodes {
+                table: Arc::new(table),
+                removed: HashSet::new(),
+                cache: Mutex::new(HashMap::new()),
+                uses: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Whether path lookups ignore case, as on the filesystem that was scanned
+    pub fn case_insensitive(&self) -> bool {
+        self.path_index.case_insensitive()
+    }
+
+    /// The key a path is indexed under
+    pub fn path_key<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        self.path_index.key(path)
+    }
+
+    /// Size of the node table on disk, for a spilled tree
+    pub fn spilled_bytes(&self) -> Option<u64> {
+        self.mapped.as_ref().map(|mapped| mapped.table.file_bytes())
+    }
+
+    pub fn root(&self) -> Option<&TreeNode> {
+        self.get(self.root_id)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&TreeNode> {
+        match (self.nodes.get(&id), &self.mapped) {
+            (Some(node), _) => Some(node),
+            (None, Some(mapped)) => mapped.get(id),
+            (None, None) => None,
+        }
+    }
+
+    pub fn find_by_path(&self, path: &str) -> Option<&TreeNode> {
+        let Some(mapped) = &self.mapped else {
+            return self.path_index.get(path).and_then(|id| self.nodes.get(&id));
+        };
+        let key = self.path_key(path);
+        mapped
+            .table
+            .ids_for_path(&key)
+            .filter_map(|id| self.get(id))
+            .find(|node| self.path_key(&node.path) == key)
+    }
+
+    /// Every node. A spilled tree decodes them all, and keeps them until the query ends.
+    pub fn nodes(&self) -> impl Iterator<Item = &TreeNode> {
+        let held = self.mapped.is_none().then(|| self.nodes.values());
+        let spilled = self
+            .mapped
+            .iter()
+            .flat_map(move |mapped| mapped.table.ids().filter_map(move |id| self.get(id)));
+        held.into_iter().flatten().chain(spilled)
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.mapped {
+            Some(mapped) => mapped.table.node_count() - mapped.removed.len(),
+            None => self.nodes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate heap bytes held by the nodes and the path index, counting each map
+    /// entry plus the strings and child lists it owns. For a spilled tree, only what is
+    /// held in memory: changed and cached nodes.
+    pub fn heap_bytes(&self) -> u64 {
+        let nodes: u64 = self.nodes.values().map(node_heap_bytes).sum();
+        let mapped = self.mapped.as_ref().map_or(0, |mapped| {
+            mapped.cached_bytes() + (mapped.removed.len() * std::mem::size_of::<NodeId>()) as u64
+        });
+        nodes + mapped + self.path_index.heap_bytes()
+    }
+
+    /// Let a spilled tree drop what the last query decoded, keeping the hot directories
+    pub fn trim_cache(&mut self) {
+        if let Some(mapped) = &mut self.mapped {
+            mapped.trim(HOT_DIRECTORIES);
+        }
+    }
+
+    /// Files and directories in a node's subtree (a directory counts itself)
+    pub fn subtree_counts(&self, id: NodeId) -> Option<(u64, u64)> {
+        self.get(id)?;
+        let (mut files, mut dirs) = (0, 0);
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            let Some(node) = self.get(current) else {
+                continue;
+            };
+            match node.kind {
+                NodeKind::File => files += 1,
+                NodeKind::Dir => dirs += 1,
+            }
+            stack.extend(node.children.iter().copied());
+        }
+        Some((files, dirs))
+    }
+
+    /// Files below every node of the subtree at `id` (a file counts itself), in one pass
+    pub fn file_counts(&self, id: NodeId) -> HashMap<NodeId, u64> {
+        let mut counts = HashMap::new();
+        // Each directory is seen twice: entering, then leaving once its children are counted
+        let mut stack = vec![(id, false)];
+        while let Some((current, leaving)) = stack.pop() {
+            let Some(node) = self.get(current) else {
+                continue;
+            };
+            match node.kind {
+                NodeKind::File => {
+                    counts.insert(current, 1);
+                }
+                NodeKind::Dir if leaving => {
+                    let files = node.children.iter().filter_map(|child| counts.get(child)).sum();
+                    counts.insert(current, files);
+                }
+                NodeKind::Dir => {
+                    stack.push((current, true));
+                    stack.extend(node.children.iter().map(|child| (*child, false)));
+                }
+            }
+        }
+        counts
+    }
+
+    /// Remove a node and all its descendants, subtracting its size from every ancestor.
+    /// Returns the number of bytes removed, or None if the node is unknown or the root.
+    pub fn remove_subtree(&mut self, id: NodeId) -> Option<u64> {
+        let size = self.get(id)?.size_bytes;
+        self.take_subtree(id).map(|_| size)
+    }
+
+    /// `remove_subtree`, handing back the removed nodes so totals kept beside the tree
+    /// can be adjusted
+    pub fn take_subtree(&mut self, id: NodeId) -> Option<Vec<TreeNode>> {
+        if id == self.root_id {
+            return None;
+        }
+        let node = self.get(id)?;
+        let size = node.size_bytes;
+        let parent_id = node.parent;
+
+        let mut removed_nodes = Vec::new();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            if let Some(mut removed) = self.remove_node(current) {
+                stack.append(&mut removed.children);
+                removed_nodes.push(removed);
+            }
+        }
+
+        if let Some(parent) = parent_id.and_then(|pid| self.node_mut(pid)) {
+            parent.children.retain(|child| *child != id);
+        }
+        let mut ancestor = parent_id;
+        while let Some(aid) = ancestor {
+            let Some(node) = self.node_mut(aid) else {
+                break;
+            };
+            node.size_bytes = node.size_bytes.saturating_sub(size);
+            ancestor = node.parent;
+        }
+        Some(removed_nodes)
+    }
+
+    fn remove_node(&mut self, id: NodeId) -> Option<TreeNode> {
+        let Some(mapped) = &mut self.mapped else {
+            let removed = self.nodes.remove(&id)?;
+            self.path_index.remove(&removed.path);
+            return Some(removed);
+        };
+        let removed = self.nodes.remove(&id).or_else(|| mapped.take(id))?;
+        mapped.removed.insert(id);
+        Some(removed)
+    }
+
+    /// A node to change in place; a spilled tree keeps it in memory from then on
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut TreeNode> {
+        if let Some(mapped) = &mut self.mapped {
+            if let Entry::Vacant(vacant) = self.nodes.entry(id) {
+                vacant.insert(mapped.take(id)?);
+            }
+        }
+        self.nodes.get_mut(&id)
+    }
+}
+
+fn node_heap_bytes(node: &TreeNode) -> u64 {
+    let entry = std::mem::size_of::<(NodeId, TreeNode)>();
+    let owned = node.name.capacity()
+        + node.path.capacity()
+        + node.file_ext.as_ref().map_or(0, String::capacity)
+        + node.children.capacity() * std::mem::size_of::<NodeId>();
+    (entry + owned) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::model::NodeKind;
+
+    fn node(id: NodeId, parent: Option<NodeId>, path: &str, kind: NodeKind, size: u64, children: Vec<NodeId>) -> TreeNode {
+        TreeNode {
+            id,
+            parent,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind,
+            size_bytes: size,
+            file_ext: None,
+            modified: None,
+            informational: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn removing_a_subtree_updates_ancestors() {
+        let nodes = vec![
+            node(1, None, "/r", NodeKind::Dir, 10, vec![2, 4]),
+            node(2, Some(1), "/r/a", NodeKind::Dir, 7, vec![3]),
+            node(3, Some(2), "/r/a/x", NodeKind::File, 7, vec![]),
+            node(4, Some(1), "/r/y", NodeKind::File, 3, vec![]),
+        ];
+        let path_index = nodes.iter().map(|n| (n.path.clone(), n.id)).collect();
+        let mut tree = ScanTree::new(1, nodes.into_iter().map(|n| (n.id, n)).collect(), path_index);
+
+        assert_eq!(tree.subtree_counts(1), Some((2, 2)));
+        assert_eq!(tree.subtree_counts(2), Some((1, 1)));
+        let heap_before = tree.heap_bytes();
+        assert_eq!(tree.remove_subtree(2), Some(7));
+        assert!(tree.heap_bytes() < heap_before);
+        assert_eq!(tree.root().map(|r| r.size_bytes), Some(3));
+        assert_eq!(tree.root().map(|r| r.children.clone()), Some(vec![4]));
+        assert!(tree.find_by_path("/r/a/x").is_none());
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.remove_subtree(1), None);
+    }
+
+    #[test]
+    fn case_insensitive_trees_find_mixed_case_paths() {
+        let nodes = vec![
+            node(1, None, "C:\\Users", NodeKind::Dir, 5, vec![2]),
+            node(2, Some(1), "C:\\Users\\Me\\Photo.JPG", NodeKind::File, 5, vec![]),
+        ];
+        let insensitive = ScanTree::from_nodes(1, nodes.clone(), true);
+        assert_eq!(insensitive.find_by_path("c:\\users\\me\\photo.jpg").map(|n| n.id), Some(2));
+        assert_eq!(insensitive.find_by_path("C:\\USERS").map(|n| n.id), Some(1));
+
+        let mut index = PathIndex::new(true);
+        index.insert("/Data/Photos", 7);
+        assert!(index.contains("/data/photos"));
+        assert_eq!(index.remove("/DATA/PHOTOS"), Some(7));
+
+        let sensitive = ScanTree::from_nodes(1, nodes, false);
+        assert!(sensitive.find_by_path("c:\\users").is_none());
+    }
+}
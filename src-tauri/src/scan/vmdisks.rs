@@ -0,0 +1,206 @@
+use std::fs::{File, Metadata};
+use std::io::Read;
+use std::path::Path;
+
+use crate::scan::analyzer::{Analyzer, FileVisit};
+use crate::scan::model::{AnalyzerReport, VmDiskImage};
+
+/// Formats recognized by extension; the header decides the virtual size
+const VM_DISK_EXTENSIONS: &[&str] = &["vhdx", "vhd", "vmdk", "qcow2", "vdi"];
+/// Enough to cover every header field read below (the VDI disk size ends at 0x178)
+const HEADER_BYTES: usize = 512;
+
+/// Finds virtual machine disk images and reports their virtual capacity, file length,
+/// and space actually allocated, with the hypervisor that likely owns them and how to
+/// compact them
+#[derive(Default)]
+pub struct VmDiskAnalyzer {
+    found: Vec<VmDiskImage>,
+}
+
+impl Analyzer for VmDiskAnalyzer {
+    fn on_file(&mut self, file: &FileVisit<'_>) {
+        let node = file.node;
+        let Some(format) = node.file_ext.as_deref().filter(|ext| VM_DISK_EXTENSIONS.contains(ext)) else {
+            return;
+        };
+        let path = Path::new(&node.path);
+        let hypervisor = hypervisor(&node.path, format);
+        self.found.push(VmDiskImage {
+            id: node.id,
+            path: node.path.clone(),
+            format: format.to_string(),
+            hypervisor: hypervisor.to_string(),
+            file_bytes: node.size_bytes,
+            allocated_bytes: allocated_bytes(path, file.metadata).unwrap_or(node.size_bytes),
+            virtual_bytes: read_header(path).and_then(|header| virtual_size(format, &header)),
+            modified: node.modified,
+            compactable: matches!(format, "vhd" | "vhdx") && cfg!(target_os = "windows"),
+            compaction_hint: compaction_hint(hypervisor).to_string(),
+        });
+    }
+
+    fn finalize(self: Box<Self>) -> AnalyzerReport {
+        let mut disks = self.found;
+        disks.sort_by_key(|disk| std::cmp::Reverse(disk.allocated_bytes));
+        AnalyzerReport::VmDisks { disks }
+    }
+}
+
+/// Best guess at the program that owns an image, from its location and format
+fn hypervisor(path: &str, format: &str) -> &'static str {
+    let lower = path.to_lowercase().replace('\\', "/");
+    if lower.contains("docker") {
+        "Docker Desktop"
+    } else if lower.ends_with("/ext4.vhdx") || lower.contains("/wsl/") {
+        "WSL"
+    } else if lower.contains("/.android/avd/") {
+        "Android Emulator"
+    } else if lower.contains("virtualbox vms") || format == "vdi" {
+        "VirtualBox"
+    } else if format == "vmdk" {
+        "VMware"
+    } else if format == "qcow2" {
+        "QEMU"
+    } else {
+        "Hyper-V"
+    }
+}
+
+fn compaction_hint(hypervisor: &str) -> &'static str {
+    match hypervisor {
+        "WSL" => {
+            "Run `wsl --shutdown`, then compact the disk from an elevated PowerShell with \
+             `Optimize-VHD -Mode Full`. Newer WSL releases can keep it sparse with \
+             `wsl --manage <distro> --set-sparse true`."
+        }
+        "Docker Desktop" => {
+            "Run `docker system prune -a` and quit Docker Desktop before compacting; \
+             Troubleshoot > Clean / Purge data shrinks it the most."
+        }
+        "Android Emulator" => "Wipe the device's data from the Device Manager in Android Studio.",
+        "VirtualBox" => {
+            "Zero the free space inside the guest, power it off, then run \
+             `VBoxManage modifymedium disk <path> --compact`."
+        }
+        "VMware" => {
+            "Power the VM off and use Settings > Hard Disk > Compact, or run \
+             `vmware-vdiskmanager -k <path>`."
+        }
+        "QEMU" => "Shut the guest down and rewrite the image with `qemu-img convert -O qcow2 <path> <new path>`.",
+        _ => "Shut the VM down, then run `Optimize-VHD -Path <path> -Mode Full` from an elevated PowerShell.",
+    }
+}
+
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    File::open(path)
+        .ok()?
+        .take(HEADER_BYTES as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
+/// Disk capacity the guest sees, from the image header. VHDX keeps it in a metadata
+/// region that takes several reads to locate, so it is left unknown.
+fn virtual_size(format: &str, header: &[u8]) -> Option<u64> {
+    let u64_le = |offset: usize| Some(u64::from_le_bytes(header.get(offset..offset + 8)?.try_into().ok()?));
+    let u64_be = |offset: usize| Some(u64::from_be_bytes(header.get(offset..offset + 8)?.try_into().ok()?));
+    match format {
+        // Dynamic VHDs start with a copy of the footer; fixed ones only have it at the end
+        "vhd" if header.starts_with(b"conectix") => u64_be(48),
+        // Monolithic sparse extent header; capacity is in 512-byte sectors
+        "vmdk" if header.starts_with(b"KDMV") => u64_le(12).map(|sectors| sectors * 512),
+        "qcow2" if header.starts_with(b"QFI\xFB") => u64_be(24),
+        "vdi" if header.get(0x40..0x44) == Some(&[0x7F, 0x10, 0xDA, 0xBE][..]) => u64_le(0x170),
+        _ => None,
+    }
+}
+
+/// Bytes the image occupies on disk, which is less than its length when it is sparse
+/// or compressed
+#[cfg(unix)]
+fn allocated_bytes(_path: &Path, metadata: Option<&Metadata>) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    metadata.map(|metadata| metadata.blocks() * 512)
+}
+
+#[cfg(target_os = "windows")]
+fn allocated_bytes(path: &Path, _metadata: Option<&Metadata>) -> Option<u64> {
+    Some(crate::scan::compress::allocated_size(path))
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn allocated_bytes(_path: &Path, _metadata: Option<&Metadata>) -> Option<u64> {
+    None
+}
+
+/// Compact a detached VHD/VHDX with Optimize-VHD, returning its size before and after.
+/// Needs the Hyper-V PowerShell module and an elevated process.
+#[cfg(target_os = "windows")]
+pub fn compact_vm_disk(path: &Path) -> Result<crate::scan::compress::CompressionResult, String> {
+    use std::process::Command;
+
+    let format = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if format != "vhd" && format != "vhdx" {
+        return Err("Only VHD and VHDX images can be compacted from here".to_string());
+    }
+    if !crate::scan::acl::is_elevated() {
+        return Err("Compacting a virtual disk requires running DiskSight as administrator".to_string());
+    }
+    let path_str = path.to_string_lossy().to_string();
+    let before = path.metadata().map_err(|e| e.to_string())?.len();
+    let script = format!(
+        "Optimize-VHD -Path '{}' -Mode Full -ErrorAction Stop",
+        path_str.replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("Optimize-VHD failed");
+        return Err(message.trim().to_string());
+    }
+    let after = path.metadata().map_err(|e| e.to_string())?.len();
+    Ok(crate::scan::compress::CompressionResult {
+        path: path_str,
+        before_bytes: before,
+        after_bytes: after,
+        saved_bytes: before.saturating_sub(after),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn compact_vm_disk(_path: &Path) -> Result<crate::scan::compress::CompressionResult, String> {
+    Err("Compacting virtual disks is only supported on Windows; see the compaction hint".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_virtual_size_and_owner() {
+        let mut qcow2 = b"QFI\xFB\x00\x00\x00\x03".to_vec();
+        qcow2.resize(24, 0);
+        qcow2.extend_from_slice(&(20u64 << 30).to_be_bytes());
+        assert_eq!(virtual_size("qcow2", &qcow2), Some(20 << 30));
+
+        let mut vmdk = b"KDMV".to_vec();
+        vmdk.resize(12, 0);
+        vmdk.extend_from_slice(&2048u64.to_le_bytes());
+        assert_eq!(virtual_size("vmdk", &vmdk), Some(1 << 20));
+        assert_eq!(virtual_size("vmdk", b"# Disk DescriptorFile"), None);
+
+        let wsl = "C:\\Users\\me\\AppData\\Local\\Packages\\CanonicalGroup.Ubuntu\\LocalState\\ext4.vhdx";
+        assert_eq!(hypervisor(wsl, "vhdx"), "WSL");
+        assert_eq!(hypervisor("/home/me/VirtualBox VMs/win/win.vmdk", "vmdk"), "VirtualBox");
+        assert_eq!(hypervisor("D:/VMs/build.vhdx", "vhdx"), "Hyper-V");
+    }
+}
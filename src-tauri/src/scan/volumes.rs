@@ -0,0 +1,327 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use sysinfo::{Disk, DiskKind, DiskRefreshKind, Disks};
+use tauri::{AppHandle, Emitter};
+
+use crate::scan::cow::CowFilesystem;
+use crate::scan::engine::is_case_insensitive;
+use crate::scan::model::RootEntry;
+
+// Filesystem types that are always backed by a remote server
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb", "smbfs", "smb2", "smb3", "afpfs", "webdav", "davfs",
+    "fuse.sshfs", "sshfs", "9p", "ncpfs", "fuse.rclone",
+];
+
+pub const EVENT_ROOTS_CHANGED: &str = "roots://changed";
+const ROOT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_CLUSTER_SIZE: u64 = 4096;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RootsChangedPayload {
+    pub roots: Vec<RootEntry>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+pub fn list_root_entries() -> Vec<RootEntry> {
+    let disks = Disks::new_with_refreshed_list();
+    disks.list().iter().map(root_entry).collect()
+}
+
+/// Poll the mounted volumes in the background and emit `roots://changed` when a
+/// volume is mounted or unmounted. Only mount points are compared, so polling stays
+/// cheap; full root details are gathered only after a change.
+pub fn spawn_root_watcher(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut known = mount_points();
+        loop {
+            thread::sleep(ROOT_POLL_INTERVAL);
+            let current = mount_points();
+            if current == known {
+                continue;
+            }
+            let payload = RootsChangedPayload {
+                roots: list_root_entries(),
+                added: current.difference(&known).cloned().collect(),
+                removed: known.difference(&current).cloned().collect(),
+            };
+            let _ = app_handle.emit(EVENT_ROOTS_CHANGED, payload);
+            known = current;
+        }
+    });
+}
+
+/// Size and free space of the mounted volume holding a path
+#[derive(Clone, Debug)]
+pub struct VolumeSpace {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+pub fn volume_space(path: &Path) -> Option<VolumeSpace> {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| VolumeSpace {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+        })
+}
+
+/// What the volume holding a scan root supports, so features such as dedupe,
+/// compression suggestions, and slack estimates can adapt without probing it again.
+/// The feature flags follow from the filesystem type; unknown types report none.
+#[derive(Clone, Debug, Serialize)]
+pub struct VolumeCapabilities {
+    pub mount_point: String,
+    pub file_system: String,
+    pub case_sensitive: bool,
+    pub hardlinks: bool,
+    /// Copies can share data blocks (reflinks, clonefile, or block cloning)
+    pub reflinks: bool,
+    /// Files can be compressed transparently by the filesystem
+    pub compression: bool,
+    pub cluster_size: u64,
+}
+
+pub fn volume_capabilities(root: &Path) -> Option<VolumeCapabilities> {
+    let volume = volume_space(root)?;
+    let (hardlinks, reflinks, compression) = filesystem_features(&volume.file_system);
+    Some(VolumeCapabilities {
+        mount_point: volume.mount_point,
+        file_system: volume.file_system,
+        case_sensitive: !is_case_insensitive(root),
+        hardlinks,
+        reflinks,
+        compression,
+        cluster_size: cluster_size(root),
+    })
+}
+
+/// (hardlinks, reflinks, compression) for a filesystem type
+fn filesystem_features(file_system: &str) -> (bool, bool, bool) {
+    match file_system.to_lowercase().as_str() {
+        "ntfs" => (true, false, true),
+        "refs" => (true, true, false),
+        "btrfs" | "zfs" | "apfs" | "bcachefs" => (true, true, true),
+        "xfs" => (true, true, false),
+        "f2fs" => (true, false, true),
+        "ext2" | "ext3" | "ext4" | "hfs" | "hfs+" | "jfs" | "nfs" | "nfs4" | "cifs" | "smb3" => (true, false, false),
+        _ => (false, false, false),
+    }
+}
+
+/// Allocation unit of the volume holding `path`
+#[cfg(target_os = "windows")]
+pub fn cluster_size(path: &Path) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceW;
+
+    let Some(root) = path.ancestors().last() else {
+        return DEFAULT_CLUSTER_SIZE;
+    };
+    let wide: Vec<u16> = root.as_os_str().encode_wide().chain(Some(0)).collect();
+    let (mut sectors, mut bytes, mut free, mut total) = (0u32, 0u32, 0u32, 0u32);
+    let ok = unsafe { GetDiskFreeSpaceW(wide.as_ptr(), &mut sectors, &mut bytes, &mut free, &mut total) };
+    if ok == 0 {
+        return DEFAULT_CLUSTER_SIZE;
+    }
+    u64::from(sectors) * u64::from(bytes)
+}
+
+#[cfg(unix)]
+pub fn cluster_size(path: &Path) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    path.metadata().map(|m| m.blksize()).unwrap_or(DEFAULT_CLUSTER_SIZE)
+}
+
+#[cfg(all(not(unix), not(target_os = "windows")))]
+pub fn cluster_size(_path: &Path) -> u64 {
+    DEFAULT_CLUSTER_SIZE
+}
+
+fn mount_points() -> BTreeSet<String> {
+    let disks = Disks::new_with_refreshed_list_specifics(DiskRefreshKind::nothing());
+    disks
+        .list()
+        .iter()
+        .map(|disk| disk.mount_point().to_string_lossy().to_string())
+        .collect()
+}
+
+fn root_entry(disk: &Disk) -> RootEntry {
+    let file_system = disk.file_system().to_string_lossy().to_string();
+    let name = disk.name().to_string_lossy().to_string();
+    let path = disk.mount_point().to_string_lossy().to_string();
+    let (label, device) = label_and_device(&name, &path);
+    RootEntry {
+        is_network: is_network_volume(disk.mount_point(), &file_system),
+        copy_on_write: CowFilesystem::detect(&file_system).is_some(),
+        encrypted: encryption_status(disk.mount_point(), &device),
+        disk_kind: match disk.kind() {
+            DiskKind::HDD => "hdd",
+            DiskKind::SSD => "ssd",
+            DiskKind::Unknown(_) => "unknown",
+        }
+        .to_string(),
+        name,
+        path,
+        total_bytes: disk.total_space(),
+        available_bytes: disk.available_space(),
+        file_system,
+        is_removable: disk.is_removable(),
+        is_read_only: disk.is_read_only(),
+        label,
+        device,
+    }
+}
+
+/// sysinfo reports the volume label as the name on Windows and the device path elsewhere
+#[cfg(target_os = "windows")]
+fn label_and_device(name: &str, mount_point: &str) -> (Option<String>, String) {
+    let label = (!name.is_empty()).then(|| name.to_string());
+    (label, mount_point.trim_end_matches('\\').to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn label_and_device(name: &str, _mount_point: &str) -> (Option<String>, String) {
+    (label_for_device(name), name.to_string())
+}
+
+/// Look up a label from the /dev/disk/by-label symlinks
+#[cfg(target_os = "linux")]
+fn label_for_device(device: &str) -> Option<String> {
+    let device = std::fs::canonicalize(device).ok()?;
+    std::fs::read_dir("/dev/disk/by-label")
+        .ok()?
+        .flatten()
+        .find(|entry| std::fs::canonicalize(entry.path()).ok().as_ref() == Some(&device))
+        .map(|entry| unescape_udev(&entry.file_name().to_string_lossy()))
+}
+
+#[cfg(all(not(target_os = "linux"), not(target_os = "windows")))]
+fn label_for_device(_device: &str) -> Option<String> {
+    None
+}
+
+/// udev escapes spaces and other special characters in link names as \xNN
+#[cfg(target_os = "linux")]
+fn unescape_udev(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(index) = rest.find("\\x") {
+        out.push_str(&rest[..index]);
+        let hex = rest.get(index + 2..index + 4);
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            None => {
+                out.push_str("\\x");
+                rest = &rest[index + 2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(target_os = "windows")]
+fn is_network_volume(mount_point: &Path, file_system: &str) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+    use windows_sys::Win32::System::WindowsProgramming::DRIVE_REMOTE;
+
+    let wide: Vec<u16> = mount_point.as_os_str().encode_wide().chain(Some(0)).collect();
+    unsafe { GetDriveTypeW(wide.as_ptr()) == DRIVE_REMOTE } || is_network_filesystem(file_system)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_network_volume(_mount_point: &Path, file_system: &str) -> bool {
+    is_network_filesystem(file_system)
+}
+
+fn is_network_filesystem(file_system: &str) -> bool {
+    let fs = file_system.to_lowercase();
+    NETWORK_FILESYSTEMS.contains(&fs.as_str())
+}
+
+/// BitLocker protection via the shell property system (works without elevation)
+#[cfg(target_os = "windows")]
+fn encryption_status(mount_point: &Path, _device: &str) -> Option<bool> {
+    use std::process::Command;
+
+    let drive = mount_point.to_string_lossy().trim_end_matches('\\').to_string();
+    let script = format!(
+        "(New-Object -ComObject Shell.Application).NameSpace('{}').Self.ExtendedProperty('System.Volume.BitLockerProtection')",
+        drive
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .output()
+        .ok()?;
+    // 1 = on, 3 = encrypting, 5 = on but suspended; 2 = off; empty = not supported
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "1" | "3" | "5" => Some(true),
+        "2" => Some(false),
+        _ => None,
+    }
+}
+
+/// dm-crypt/LUKS volumes are device-mapper targets whose uuid starts with CRYPT-
+#[cfg(target_os = "linux")]
+fn encryption_status(_mount_point: &Path, device: &str) -> Option<bool> {
+    let resolved = std::fs::canonicalize(device).ok()?;
+    let name = resolved.file_name()?.to_string_lossy().to_string();
+    if !name.starts_with("dm-") {
+        return Some(false);
+    }
+    let uuid = std::fs::read_to_string(format!("/sys/block/{}/dm/uuid", name)).ok()?;
+    Some(uuid.starts_with("CRYPT-"))
+}
+
+#[cfg(all(not(target_os = "linux"), not(target_os = "windows")))]
+fn encryption_status(_mount_point: &Path, _device: &str) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_network_filesystems() {
+        assert!(is_network_filesystem("NFS4"));
+        assert!(is_network_filesystem("cifs"));
+        assert!(!is_network_filesystem("ext4"));
+    }
+
+    #[test]
+    fn filesystem_types_map_to_features() {
+        assert_eq!(filesystem_features("NTFS"), (true, false, true));
+        assert_eq!(filesystem_features("btrfs"), (true, true, true));
+        assert_eq!(filesystem_features("exfat"), (false, false, false));
+        let capabilities = volume_capabilities(&std::env::temp_dir());
+        assert!(capabilities.is_none_or(|capabilities| capabilities.cluster_size > 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn unescapes_udev_labels() {
+        assert_eq!(unescape_udev("My\\x20Passport"), "My Passport");
+        assert_eq!(unescape_udev("plain"), "plain");
+    }
+}
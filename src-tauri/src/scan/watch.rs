@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::scan::delete::{measure_path, PathTotals};
+
+pub const EVENT_SIZE_CHANGED: &str = "watch://size-changed";
+/// Shortest re-measure interval; a large folder takes a while to total
+pub const MIN_WATCH_INTERVAL_SECS: u64 = 1;
+/// How often a sleeping watch checks whether it was stopped
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+const HOUR_SECS: f64 = 60.0 * 60.0;
+
+/// A watch as started, with the first measurement
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeWatch {
+    pub watch_id: String,
+    pub path: String,
+    pub interval_secs: u64,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// Sent when a re-measure finds a watched folder changed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SizeChangedPayload {
+    pub watch_id: String,
+    pub path: String,
+    pub bytes: u64,
+    pub files: u64,
+    pub delta_bytes: i64,
+    pub delta_files: i64,
+    /// The change scaled to an hour, from the time since the previous measurement
+    pub bytes_per_hour: f64,
+    /// False once the folder is gone; the watch ends with this event
+    pub exists: bool,
+    pub measured_at: u64,
+}
+
+/// What changed between two measurements, or None when nothing did
+fn size_change(previous: &PathTotals, current: &PathTotals, elapsed: Duration) -> Option<(i64, i64, f64)> {
+    let delta_bytes = current.bytes as i64 - previous.bytes as i64;
+    let delta_files = current.files as i64 - previous.files as i64;
+    if delta_bytes == 0 && delta_files == 0 {
+        return None;
+    }
+    let secs = elapsed.as_secs_f64().max(1.0);
+    Some((delta_bytes, delta_files, delta_bytes as f64 * HOUR_SECS / secs))
+}
+
+/// Re-measure `path` every `interval` until `stop` is set or the path disappears,
+/// handing each change to `on_change`. `initial` is the measurement the watch started
+/// from.
+pub fn run_watch(
+    watch_id: &str,
+    path: &Path,
+    interval: Duration,
+    initial: PathTotals,
+    stop: &AtomicBool,
+    mut on_change: impl FnMut(SizeChangedPayload),
+) {
+    let mut previous = initial;
+    let mut measured = Instant::now();
+    loop {
+        let wake = Instant::now() + interval;
+        while Instant::now() < wake {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(STOP_CHECK_INTERVAL.min(wake.saturating_duration_since(Instant::now())));
+        }
+        let exists = path.exists();
+        let current = if exists { measure_path(path) } else { PathTotals::default() };
+        let elapsed = measured.elapsed();
+        measured = Instant::now();
+        if let Some((delta_bytes, delta_files, bytes_per_hour)) = size_change(&previous, &current, elapsed) {
+            on_change(SizeChangedPayload {
+                watch_id: watch_id.to_string(),
+                path: path.to_string_lossy().to_string(),
+                bytes: current.bytes,
+                files: current.files,
+                delta_bytes,
+                delta_files,
+                bytes_per_hour,
+                exists,
+                measured_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+            });
+        }
+        if !exists {
+            return;
+        }
+        previous = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_deltas_and_hourly_rate_only_on_change() {
+        let totals = |bytes, files| PathTotals { bytes, files, folders: 1 };
+        assert_eq!(size_change(&totals(100, 2), &totals(100, 2), Duration::from_secs(60)), None);
+        assert_eq!(
+            size_change(&totals(100, 2), &totals(700, 5), Duration::from_secs(60)),
+            Some((600, 3, 36_000.0))
+        );
+        let (delta_bytes, _, rate) =
+            size_change(&totals(500, 2), &totals(200, 2), Duration::from_secs(3600)).expect("change");
+        assert_eq!((delta_bytes, rate), (-300, -300.0));
+    }
+}
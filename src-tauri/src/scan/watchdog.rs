@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::scan::events::{emit_heartbeat, emit_stalled, HeartbeatPayload, StalledPayload};
+use crate::scan::state::AppState;
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// A scan that visits nothing for this long is reported as stalled
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(3 * 60);
+
+/// Counters the engine bumps as it walks, cheap enough to update for every entry
+#[derive(Default)]
+pub struct ScanPulse {
+    visited: AtomicU64,
+    finalizing: AtomicBool,
+}
+
+impl ScanPulse {
+    pub fn advance(&self, visited_entries: u64) {
+        self.visited.store(visited_entries, Ordering::Relaxed);
+    }
+
+    pub fn set_finalizing(&self) {
+        self.finalizing.store(true, Ordering::Relaxed);
+    }
+
+    pub fn visited(&self) -> u64 {
+        self.visited.load(Ordering::Relaxed)
+    }
+
+    pub fn phase(&self) -> &'static str {
+        if self.finalizing.load(Ordering::Relaxed) {
+            "finalizing"
+        } else {
+            "walking"
+        }
+    }
+}
+
+/// What the watchdog last saw of one scan
+struct Liveness {
+    visited: u64,
+    phase: &'static str,
+    changed_at: Instant,
+    reported: bool,
+}
+
+impl Liveness {
+    fn new(visited: u64, phase: &'static str, now: Instant) -> Self {
+        Self {
+            visited,
+            phase,
+            changed_at: now,
+            reported: false,
+        }
+    }
+
+    /// Record the latest counters and return how long the scan has gone without progress
+    fn observe(&mut self, visited: u64, phase: &'static str, now: Instant) -> Duration {
+        if visited != self.visited || phase != self.phase {
+            *self = Self::new(visited, phase, now);
+        }
+        now.duration_since(self.changed_at)
+    }
+}
+
+/// Emit a heartbeat for every running scan and report scans that stop making progress,
+/// once per stall
+pub fn spawn_watchdog(state: AppState) {
+    thread::spawn(move || {
+        let mut seen: HashMap<String, Liveness> = HashMap::new();
+        loop {
+            thread::sleep(HEARTBEAT_INTERVAL);
+            let scans = state.active_scan_emitters();
+            seen.retain(|id, _| scans.iter().any(|(scan_id, _, _)| scan_id == id));
+            let now = Instant::now();
+            for (scan_id, started_at, emitter) in scans {
+                let visited = emitter.pulse().visited();
+                let phase = emitter.pulse().phase();
+                let liveness = seen
+                    .entry(scan_id.clone())
+                    .or_insert_with(|| Liveness::new(visited, phase, now));
+                let idle_ms = liveness.observe(visited, phase, now).as_millis() as u64;
+                emit_heartbeat(
+                    &emitter,
+                    HeartbeatPayload {
+                        scan_id: scan_id.clone(),
+                        phase: phase.to_string(),
+                        visited_entries: visited,
+                        elapsed_ms: started_at.elapsed().unwrap_or_default().as_millis() as u64,
+                        idle_ms,
+                    },
+                );
+                if idle_ms >= STALL_TIMEOUT.as_millis() as u64 && !liveness.reported {
+                    liveness.reported = true;
+                    emit_stalled(
+                        &emitter,
+                        StalledPayload {
+                            scan_id,
+                            phase: phase.to_string(),
+                            visited_entries: visited,
+                            idle_ms,
+                            message: format!("The scan has made no progress for {} minutes", idle_ms / 60_000),
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_time_resets_on_progress_or_phase_change() {
+        let start = Instant::now();
+        let mut liveness = Liveness::new(10, "walking", start);
+        let later = start + Duration::from_secs(60);
+        assert_eq!(liveness.observe(10, "walking", later), Duration::from_secs(60));
+        liveness.reported = true;
+        assert_eq!(liveness.observe(11, "walking", later), Duration::ZERO);
+        assert!(!liveness.reported);
+        let much_later = later + Duration::from_secs(30);
+        assert_eq!(liveness.observe(11, "finalizing", much_later), Duration::ZERO);
+    }
+}
@@ -0,0 +1,441 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scan::delete::{measure_path, SafetyLevel};
+
+/// Cached .msi and .msp packages, below the Windows folder
+pub const INSTALLER_FOLDER: &str = "Installer";
+/// Staged driver packages, one folder per package, below the Windows folder
+pub const DRIVER_REPOSITORY: &str = "System32/DriverStore/FileRepository";
+pub const FONTS_FOLDER: &str = "Fonts";
+/// Bitmap fonts (.fon) are loaded through other registry keys, so only outline fonts
+/// are checked against the font registrations
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const FONT_EXTENSIONS: &[&str] = &["ttf", "ttc", "otf"];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreItemKind {
+    /// An .msi or .msp in the Installer cache that no installed product or patch
+    /// refers to
+    OrphanedInstaller,
+    /// An older version of a third-party driver package that has a newer one staged
+    SupersededDriver,
+    /// A font file no font registration points to, so Windows never loads it
+    UnregisteredFont,
+}
+
+/// How a store item is removed
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StoreCleanup {
+    DeleteFile,
+    /// `pnputil /delete-driver`, which refuses while a device still uses the package
+    RemoveDriverPackage { published_name: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoreItem {
+    pub path: String,
+    pub kind: StoreItemKind,
+    pub size_bytes: u64,
+    /// The driver package name and version, or the file name
+    pub label: String,
+    pub level: SafetyLevel,
+    /// What could go wrong after removing it
+    pub caveat: String,
+    pub cleanup: StoreCleanup,
+}
+
+/// The Installer cache, driver store, and Fonts folder, which the scanner skips along
+/// with the rest of the Windows folder
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WindowsStoresReport {
+    pub installer_cache_bytes: u64,
+    pub driver_store_bytes: u64,
+    pub fonts_bytes: u64,
+    /// Largest first
+    pub items: Vec<StoreItem>,
+    pub reclaimable_bytes: u64,
+    /// Sections that could not be checked, such as an unreadable registry key
+    pub errors: Vec<String>,
+}
+
+/// What happened to one item of a cleanup
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoreCleanupResult {
+    pub path: String,
+    pub freed_bytes: u64,
+    pub error: Option<String>,
+}
+
+/// One staged driver package as `pnputil /enum-drivers` lists it
+#[derive(Clone, Debug, PartialEq)]
+struct DriverPackage {
+    published_name: String,
+    original_name: String,
+    /// As `DriverVer` spells it: "03/15/2023,31.0.15.3161"
+    version: String,
+}
+
+/// Look for orphaned installer packages, superseded drivers, and unregistered fonts.
+/// Needs an elevated process to read the Installer cache.
+#[cfg(target_os = "windows")]
+pub fn analyze_windows_stores() -> Result<WindowsStoresReport, String> {
+    let windows = std::env::var_os("SystemRoot").ok_or_else(|| "The Windows folder could not be found".to_string())?;
+    let windows = Path::new(&windows);
+    let mut report = WindowsStoresReport::default();
+
+    let installer = windows.join(INSTALLER_FOLDER);
+    let packages = files_with_extensions(&installer, &["msi", "msp"]);
+    report.installer_cache_bytes = measure_path(&installer).bytes;
+    match reg_sz_values(r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Installer\UserData", "LocalPackage", true) {
+        Ok(referenced) => report.items.extend(orphaned_installers(&packages, &referenced)),
+        Err(e) => report.errors.push(format!("Installed products could not be read: {}", e)),
+    }
+
+    let repository = windows.join(DRIVER_REPOSITORY);
+    report.driver_store_bytes = measure_path(&repository).bytes;
+    match enum_drivers() {
+        Ok(staged) => report.items.extend(superseded_drivers(&repository, &staged)),
+        Err(e) => report.errors.push(format!("Driver packages could not be listed: {}", e)),
+    }
+
+    let fonts = windows.join(FONTS_FOLDER);
+    let font_files = files_with_extensions(&fonts, FONT_EXTENSIONS);
+    report.fonts_bytes = measure_path(&fonts).bytes;
+    let key = r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion\Fonts";
+    match reg_sz_values(key, "", false) {
+        Ok(registered) => report.items.extend(unregistered_fonts(&font_files, &registered)),
+        Err(e) => report.errors.push(format!("Font registrations could not be read: {}", e)),
+    }
+
+    report.items.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.path.cmp(&b.path)));
+    report.reclaimable_bytes = report.items.iter().map(|item| item.size_bytes).sum();
+    Ok(report)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn analyze_windows_stores() -> Result<WindowsStoresReport, String> {
+    Err("The Installer cache, driver store, and Fonts folder only exist on Windows".to_string())
+}
+
+/// Remove one item found by `analyze_windows_stores`. Returns the bytes freed.
+#[cfg(target_os = "windows")]
+pub fn remove_store_item(item: &StoreItem) -> Result<u64, String> {
+    match &item.cleanup {
+        StoreCleanup::DeleteFile => fs::remove_file(&item.path).map_err(|e| e.to_string())?,
+        StoreCleanup::RemoveDriverPackage { published_name } => {
+            // Without /force, pnputil leaves packages a device is using
+            let output = std::process::Command::new("pnputil")
+                .args(["/delete-driver", published_name.as_str()])
+                .output()
+                .map_err(|e| e.to_string())?;
+            if !output.status.success() {
+                let message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if message.is_empty() {
+                    return Err(format!("pnputil could not remove {}", published_name));
+                }
+                return Err(message);
+            }
+        }
+    }
+    Ok(item.size_bytes)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn remove_store_item(_item: &StoreItem) -> Result<u64, String> {
+    Err("The Installer cache, driver store, and Fonts folder only exist on Windows".to_string())
+}
+
+/// Files directly inside `dir` with one of `extensions`, with their sizes
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn files_with_extensions(dir: &Path, extensions: &[&str]) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry.path().extension().is_some_and(|ext| {
+                let ext = ext.to_string_lossy().to_lowercase();
+                extensions.contains(&ext.as_str())
+            })
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| (entry.path(), metadata.len()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn reg_sz_values(key: &str, value: &str, recursive: bool) -> Result<Vec<String>, String> {
+    let mut args = vec!["query", key];
+    if recursive {
+        args.push("/s");
+    }
+    if !value.is_empty() {
+        args.extend(["/v", value]);
+    }
+    let output = std::process::Command::new("reg")
+        .args(&args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(parse_reg_sz(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The data of every string value in `reg query` output, such as
+/// "    LocalPackage    REG_SZ    C:\Windows\Installer\1a2b3c.msi"
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_reg_sz(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, data) = line.split_once("    REG_SZ    ").or_else(|| line.split_once("    REG_EXPAND_SZ    "))?;
+            Some(data.trim().to_string()).filter(|data| !data.is_empty())
+        })
+        .collect()
+}
+
+/// Cached packages whose path no product or patch registration names
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn orphaned_installers(packages: &[(PathBuf, u64)], referenced: &[String]) -> Vec<StoreItem> {
+    let referenced: HashSet<String> = referenced.iter().map(|path| path.to_lowercase()).collect();
+    packages
+        .iter()
+        .filter(|(path, _)| !referenced.contains(&path.to_string_lossy().to_lowercase()))
+        .map(|(path, size)| StoreItem {
+            path: path.to_string_lossy().to_string(),
+            kind: StoreItemKind::OrphanedInstaller,
+            size_bytes: *size,
+            label: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            level: SafetyLevel::ConfirmRequired,
+            caveat: "A product with a damaged registration may still need it to repair or uninstall".to_string(),
+            cleanup: StoreCleanup::DeleteFile,
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn enum_drivers() -> Result<Vec<DriverPackage>, String> {
+    let output = std::process::Command::new("pnputil")
+        .arg("/enum-drivers")
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    Ok(parse_enum_drivers(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the blocks `pnputil /enum-drivers` prints:
+///
+/// ```text
+/// Published Name:     oem12.inf
+/// Original Name:      nvlddmkm.inf
+/// Driver Version:     03/15/2023 31.0.15.3161
+/// ```
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_enum_drivers(output: &str) -> Vec<DriverPackage> {
+    let mut packages = Vec::new();
+    let mut current: HashMap<&str, String> = HashMap::new();
+    for line in output.lines().chain(Some("")) {
+        let Some((key, value)) = line.split_once(':') else {
+            if let (Some(published), Some(original), Some(version)) = (
+                current.remove("published name"),
+                current.remove("original name"),
+                current.remove("driver version"),
+            ) {
+                packages.push(DriverPackage {
+                    published_name: published,
+                    original_name: original.to_lowercase(),
+                    version: version.replacen(' ', ",", 1),
+                });
+            }
+            current.clear();
+            continue;
+        };
+        let key = match key.trim().to_lowercase().as_str() {
+            "published name" => "published name",
+            "original name" => "original name",
+            "driver version" => "driver version",
+            _ => continue,
+        };
+        current.insert(key, value.trim().to_string());
+    }
+    packages
+}
+
+/// `DriverVer` from an .inf file: "DriverVer = 03/15/2023,31.0.15.3161 ; comment"
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_driver_ver(inf: &str) -> Option<String> {
+    inf.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("DriverVer") {
+            return None;
+        }
+        let value = value.split(';').next()?;
+        Some(value.split(',').map(str::trim).collect::<Vec<_>>().join(","))
+    })
+}
+
+/// Order driver versions by date, then by version number
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn version_key(version: &str) -> (u32, u32, u32, Vec<u32>) {
+    let (date, number) = version.split_once(',').unwrap_or((version, ""));
+    let mut date = date.split('/').map(|part| part.parse().unwrap_or(0));
+    let (month, day, year) = (date.next().unwrap_or(0), date.next().unwrap_or(0), date.next().unwrap_or(0));
+    (year, month, day, number.split('.').map(|part| part.parse().unwrap_or(0)).collect())
+}
+
+/// Driver store folders ("nvlddmkm.inf_amd64_1a2b3c") of third-party packages that a
+/// newer version of the same .inf supersedes. Inbox drivers never show up in
+/// `pnputil /enum-drivers`, so they are never offered.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn superseded_drivers(repository: &Path, staged: &[DriverPackage]) -> Vec<StoreItem> {
+    let Ok(entries) = fs::read_dir(repository) else {
+        return Vec::new();
+    };
+    let mut by_inf: HashMap<String, Vec<(PathBuf, &DriverPackage)>> = HashMap::new();
+    for entry in entries.flatten() {
+        let folder = entry.path();
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        let Some(inf_name) = name.find(".inf_").map(|end| name[..end + 4].to_string()) else {
+            continue;
+        };
+        let Some(version) = fs::read(folder.join(&inf_name))
+            .ok()
+            .and_then(|bytes| parse_driver_ver(&decode_inf(&bytes)))
+        else {
+            continue;
+        };
+        if let Some(package) = staged
+            .iter()
+            .find(|package| package.original_name == inf_name && package.version == version)
+        {
+            by_inf.entry(inf_name).or_default().push((folder, package));
+        }
+    }
+    by_inf
+        .into_values()
+        .filter(|folders| folders.len() > 1)
+        .flat_map(|mut folders| {
+            folders.sort_by_key(|(_, package)| version_key(&package.version));
+            folders.pop();
+            folders
+        })
+        .map(|(folder, package)| StoreItem {
+            path: folder.to_string_lossy().to_string(),
+            kind: StoreItemKind::SupersededDriver,
+            size_bytes: measure_path(&folder).bytes,
+            label: format!("{} {}", package.original_name, package.version),
+            level: SafetyLevel::ConfirmRequired,
+            caveat: "Rolling the device back to this driver version will no longer be possible".to_string(),
+            cleanup: StoreCleanup::RemoveDriverPackage {
+                published_name: package.published_name.clone(),
+            },
+        })
+        .collect()
+}
+
+/// .inf files are ANSI or UTF-16 with a byte order mark
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn decode_inf(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Font files that no registration names, by file name or full path
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn unregistered_fonts(fonts: &[(PathBuf, u64)], registered: &[String]) -> Vec<StoreItem> {
+    let registered: HashSet<String> = registered
+        .iter()
+        .filter_map(|data| Path::new(data).file_name().map(|name| name.to_string_lossy().to_lowercase()))
+        .collect();
+    fonts
+        .iter()
+        .filter(|(path, _)| {
+            path.file_name()
+                .is_some_and(|name| !registered.contains(&name.to_string_lossy().to_lowercase()))
+        })
+        .map(|(path, size)| StoreItem {
+            path: path.to_string_lossy().to_string(),
+            kind: StoreItemKind::UnregisteredFont,
+            size_bytes: *size,
+            label: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            level: SafetyLevel::ConfirmRequired,
+            caveat: "An application that loads the file directly would lose the font".to_string(),
+            cleanup: StoreCleanup::DeleteFile,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_orphaned_installers_and_unregistered_fonts() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\...\\Products\\00002109\\InstallProperties\r\n\
+            \x20   LocalPackage    REG_SZ    C:\\Windows\\Installer\\1a2b3c.msi\r\n\r\n\
+            End of search: 1 match(es) found.\r\n";
+        let referenced = parse_reg_sz(output);
+        assert_eq!(referenced, vec!["C:\\Windows\\Installer\\1a2b3c.msi".to_string()]);
+
+        let packages = vec![
+            (PathBuf::from("C:\\Windows\\Installer\\1A2B3C.msi"), 10),
+            (PathBuf::from("C:\\Windows\\Installer\\9f8e7d.msp"), 20),
+        ];
+        let orphans = orphaned_installers(&packages, &referenced);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!((orphans[0].size_bytes, orphans[0].cleanup.clone()), (20, StoreCleanup::DeleteFile));
+
+        let fonts = vec![(PathBuf::from("/Fonts/arial.ttf"), 5), (PathBuf::from("/Fonts/leftover.otf"), 7)];
+        let unused = unregistered_fonts(&fonts, &["ARIAL.TTF".to_string()]);
+        assert_eq!(unused.iter().map(|item| item.label.as_str()).collect::<Vec<_>>(), vec!["leftover.otf"]);
+    }
+
+    #[test]
+    fn keeps_only_the_newest_staged_driver_version() {
+        let output = "Microsoft PnP Utility\r\n\r\n\
+            Published Name:     oem12.inf\r\nOriginal Name:      widget.inf\r\n\
+            Provider Name:      Widgets Inc.\r\nDriver Version:     03/15/2023 2.0.0.1\r\n\r\n\
+            Published Name:     oem7.inf\r\nOriginal Name:      Widget.inf\r\n\
+            Driver Version:     11/02/2021 1.9.0.0\r\n";
+        let staged = parse_enum_drivers(output);
+        assert_eq!(staged.len(), 2);
+        assert_eq!(staged[1].version, "11/02/2021,1.9.0.0");
+
+        let repository = tempfile::tempdir().expect("temp dir");
+        let packages = [
+            ("widget.inf_amd64_aaa", "03/15/2023, 2.0.0.1"),
+            ("widget.inf_amd64_bbb", "11/02/2021,1.9.0.0"),
+        ];
+        for (folder, version) in packages {
+            let dir = repository.path().join(folder);
+            fs::create_dir_all(&dir).expect("create package");
+            let inf = format!("[Version]\r\nDriverVer = {} ; built\r\n", version);
+            fs::write(dir.join("widget.inf"), inf).expect("write inf");
+        }
+        let superseded = superseded_drivers(repository.path(), &staged);
+        assert_eq!(superseded.len(), 1);
+        assert!(superseded[0].path.ends_with("widget.inf_amd64_bbb"));
+        assert_eq!(
+            superseded[0].cleanup,
+            StoreCleanup::RemoveDriverPackage {
+                published_name: "oem7.inf".to_string()
+            }
+        );
+    }
+}
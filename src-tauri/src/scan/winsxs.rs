@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::scan::shadow::parse_size;
+
+pub const EVENT_CLEANUP_PROGRESS: &str = "winsxs://progress";
+pub const EVENT_CLEANUP_FINISHED: &str = "winsxs://finished";
+/// What confirmation tokens for a component store cleanup are bound to
+pub const COMPONENT_STORE: &str = "WinSxS";
+
+/// Only one DISM servicing operation can run at a time
+static CLEANUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// The Windows component store (WinSxS) as reported by `Dism /AnalyzeComponentStore`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ComponentStoreAnalysis {
+    /// What Explorer shows, counting hard links into C:\Windows again
+    pub explorer_size_bytes: u64,
+    pub actual_size_bytes: u64,
+    /// Hard-linked into the running system; never reclaimable
+    pub shared_with_windows_bytes: u64,
+    /// Superseded component versions kept for uninstalling updates
+    pub backups_and_disabled_bytes: u64,
+    pub cache_and_temp_bytes: u64,
+    /// Backups plus cache, the most a cleanup can free
+    pub reclaimable_bytes: u64,
+    pub reclaimable_packages: u32,
+    pub cleanup_recommended: bool,
+    /// As DISM prints it, e.g. "2024-01-15 03:12:45"; None if never cleaned up
+    pub last_cleanup: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupProgressPayload {
+    pub percent: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupFinishedPayload {
+    pub success: bool,
+    pub error: Option<String>,
+    pub before: Option<ComponentStoreAnalysis>,
+    pub after: Option<ComponentStoreAnalysis>,
+    pub freed_bytes: u64,
+}
+
+/// Analyze the component store. Takes a minute or two and requires an elevated process.
+#[cfg(target_os = "windows")]
+pub fn analyze_component_store() -> Result<ComponentStoreAnalysis, String> {
+    let output = std::process::Command::new("dism")
+        .args(["/Online", "/Cleanup-Image", "/AnalyzeComponentStore", "/English"])
+        .output()
+        .map_err(|e| e.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        return Err(dism_error(&stdout));
+    }
+    parse_analysis(&stdout)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn analyze_component_store() -> Result<ComponentStoreAnalysis, String> {
+    Err("The component store only exists on Windows".to_string())
+}
+
+/// Claim the single cleanup slot; false if a cleanup is already running
+pub fn begin_cleanup() -> bool {
+    CLEANUP_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+pub fn end_cleanup() {
+    CLEANUP_RUNNING.store(false, Ordering::SeqCst);
+}
+
+/// Run `Dism /StartComponentCleanup`, reporting its percentage as it goes. Removes
+/// superseded components, so installed updates can no longer be uninstalled.
+#[cfg(target_os = "windows")]
+pub fn start_component_cleanup(mut on_progress: impl FnMut(f32)) -> Result<(), String> {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("dism")
+        .args(["/Online", "/Cleanup-Image", "/StartComponentCleanup", "/English"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let mut stdout = child.stdout.take().ok_or("DISM output is unavailable")?;
+    // DISM redraws its progress bar with carriage returns, so read raw chunks
+    let mut output = String::new();
+    let mut buffer = [0u8; 512];
+    loop {
+        let read = stdout.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        output.push_str(&String::from_utf8_lossy(&buffer[..read]));
+        if let Some(percent) = output.rsplit(['\r', '\n']).find_map(parse_progress) {
+            on_progress(percent);
+        }
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(dism_error(&output));
+    }
+    on_progress(100.0);
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_component_cleanup(_on_progress: impl FnMut(f32)) -> Result<(), String> {
+    Err("The component store only exists on Windows".to_string())
+}
+
+/// DISM prints "Error: 740" followed by a description on stdout
+#[cfg(target_os = "windows")]
+fn dism_error(output: &str) -> String {
+    let error: Vec<&str> = output
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("Error"))
+        .filter(|line| !line.is_empty())
+        .take(2)
+        .collect();
+    if error.is_empty() {
+        "DISM failed".to_string()
+    } else {
+        error.join(": ")
+    }
+}
+
+/// Parse the English report of `Dism /AnalyzeComponentStore`:
+///
+/// ```text
+/// Windows Explorer Reported Size of Component Store : 8.21 GB
+/// Actual Size of Component Store : 7.94 GB
+///     Shared with Windows : 5.76 GB
+///     Backups and Disabled Features : 1.91 GB
+///     Cache and Temporary Data : 270.23 MB
+/// Date of Last Cleanup : 2024-01-15 03:12:45
+/// Number of Reclaimable Packages : 4
+/// Component Store Cleanup Recommended : Yes
+/// ```
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_analysis(output: &str) -> Result<ComponentStoreAnalysis, String> {
+    let mut analysis = ComponentStoreAnalysis::default();
+    let mut found = false;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        let size = || parse_size(value).unwrap_or(0);
+        match key.as_str() {
+            "windows explorer reported size of component store" => analysis.explorer_size_bytes = size(),
+            "actual size of component store" => {
+                analysis.actual_size_bytes = size();
+                found = true;
+            }
+            "shared with windows" => analysis.shared_with_windows_bytes = size(),
+            "backups and disabled features" => analysis.backups_and_disabled_bytes = size(),
+            "cache and temporary data" => analysis.cache_and_temp_bytes = size(),
+            "date of last cleanup" => {
+                analysis.last_cleanup = Some(value.to_string()).filter(|date| !date.is_empty());
+            }
+            "number of reclaimable packages" => analysis.reclaimable_packages = value.parse().unwrap_or(0),
+            "component store cleanup recommended" => analysis.cleanup_recommended = value.eq_ignore_ascii_case("yes"),
+            _ => {}
+        }
+    }
+    if !found {
+        return Err("DISM did not report the component store size".to_string());
+    }
+    analysis.reclaimable_bytes = analysis.backups_and_disabled_bytes + analysis.cache_and_temp_bytes;
+    Ok(analysis)
+}
+
+/// "[=====      10.0%             ]" -> 10.0
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_progress(line: &str) -> Option<f32> {
+    let line = line.trim();
+    if !line.starts_with('[') {
+        return None;
+    }
+    let end = line.find('%')?;
+    let start = line[..end].rfind(|c: char| !(c.is_ascii_digit() || c == '.'))? + 1;
+    line[start..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_component_store_report() {
+        let output = "Deployment Image Servicing and Management tool\r\nVersion: 10.0.19041.844\r\n\r\n\
+            [===========================100.0%==========================]\r\n\r\n\
+            Component Store (WinSxS) information:\r\n\r\n\
+            Windows Explorer Reported Size of Component Store : 8.21 GB\r\n\r\n\
+            Actual Size of Component Store : 7.94 GB\r\n\r\n\
+            \x20   Shared with Windows : 5.76 GB\r\n\
+            \x20   Backups and Disabled Features : 1.5 GB\r\n\
+            \x20   Cache and Temporary Data : 512 MB\r\n\r\n\
+            Date of Last Cleanup : 2024-01-15 03:12:45\r\n\r\n\
+            Number of Reclaimable Packages : 4\r\n\
+            Component Store Cleanup Recommended : Yes\r\n";
+        let analysis = parse_analysis(output).expect("analysis");
+        assert_eq!(analysis.backups_and_disabled_bytes, 3 << 29);
+        assert_eq!(analysis.reclaimable_bytes, (3 << 29) + (512 << 20));
+        assert_eq!(analysis.reclaimable_packages, 4);
+        assert!(analysis.cleanup_recommended);
+        assert_eq!(analysis.last_cleanup.as_deref(), Some("2024-01-15 03:12:45"));
+        assert!(parse_analysis("Error: 740\r\nElevated permissions are required").is_err());
+
+        assert_eq!(parse_progress("[=====                      10.0%                          ]"), Some(10.0));
+        assert_eq!(parse_progress("[===========================100.0%==========================]"), Some(100.0));
+        assert_eq!(parse_progress("Image Version: 10.0"), None);
+    }
+}
@@ -1,9 +1,11 @@
 import { useState, useEffect, useMemo } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import "./App.css";
 import type {
   RootEntry,
+  RootsChangedPayload,
   ScanHandle,
   ScanOptions,
   ProgressPayload,
@@ -14,6 +16,11 @@ import type {
   AppView,
   AppSettings,
   ScanProgressPayload,
+  ScanRequestedPayload,
+  StalledPayload,
+  SkipGroup,
+  SkippedPayload,
+  RootLostPayload,
 } from "./types";
 import { DEFAULT_SETTINGS } from "./constants";
 
@@ -31,6 +38,23 @@ interface TreeNodeMap {
   [id: number]: TreeNodeDelta & { children: NodeId[] };
 }
 
+// Stand-in root entry for a folder opened from the Explorer context menu
+const folderEntry = (path: string): RootEntry => ({
+  name: path.split(/[\\/]/).filter(Boolean).pop() || path,
+  path,
+  total_bytes: 0,
+  available_bytes: 0,
+  file_system: "",
+  copy_on_write: false,
+  is_removable: false,
+  is_network: false,
+  is_read_only: false,
+  label: null,
+  device: "",
+  disk_kind: "unknown",
+  encrypted: null,
+});
+
 function App() {
   // App state
   const [currentView, setCurrentView] = useState<AppView>("HOME");
@@ -49,7 +73,9 @@ function App() {
   const [treeNodes, setTreeNodes] = useState<TreeNodeMap>({});
   const [errorMessage, setErrorMessage] = useState<string | null>(null);
   const [scanErrors, setScanErrors] = useState<string[]>([]);
+  const [skipped, setSkipped] = useState<SkipGroup[]>([]);
   const [loadingDrives, setLoadingDrives] = useState(true);
+  const [requestedScanPath, setRequestedScanPath] = useState<string | null>(null);
 
   // Theme management
   useEffect(() => {
@@ -83,16 +109,18 @@ function App() {
   // Set up event listeners
   useEffect(() => {
     const unlisteners: UnlistenFn[] = [];
+    // Scan events are sent to the window that started the scan
+    const appWindow = getCurrentWebviewWindow();
 
     const setupListeners = async () => {
       unlisteners.push(
-        await listen<ProgressPayload>("scan://progress", (event) => {
+        await appWindow.listen<ProgressPayload>("scan://progress", (event) => {
           setProgress(event.payload);
         })
       );
 
       unlisteners.push(
-        await listen<{ nodes: TreeNodeDelta[] }>("scan://partial-tree", (event) => {
+        await appWindow.listen<{ nodes: TreeNodeDelta[] }>("scan://partial-tree", (event) => {
           setTreeNodes((prev) => {
             const updated = { ...prev };
             for (const node of event.payload.nodes) {
@@ -118,7 +146,7 @@ function App() {
       );
 
       unlisteners.push(
-        await listen<FinishedPayload>("scan://finished", (event) => {
+        await appWindow.listen<FinishedPayload>("scan://finished", (event) => {
           setScanStatus("finished");
           setSummary(event.payload.summary);
           setRootNodeId(event.payload.root_node_id);
@@ -127,7 +155,7 @@ function App() {
       );
 
       unlisteners.push(
-        await listen<{ message: string; path?: string }>("scan://error", (event) => {
+        await appWindow.listen<{ message: string; path?: string }>("scan://error", (event) => {
           // Track non-fatal errors (permission denied, access errors) without stopping scan
           const msg = event.payload.message;
           const isPermissionError = msg.includes('Access is denied') || 
@@ -148,11 +176,50 @@ function App() {
       );
 
       unlisteners.push(
-        await listen("scan://canceled", () => {
+        await appWindow.listen<SkippedPayload>("scan://skipped", (event) => {
+          setSkipped(event.payload.groups);
+        })
+      );
+
+      unlisteners.push(
+        await appWindow.listen<StalledPayload>("scan://stalled", (event) => {
+          // The scan is still running; let the user decide whether to cancel
+          setErrorMessage(event.payload.message);
+        })
+      );
+
+      unlisteners.push(
+        await appWindow.listen<RootLostPayload>("scan://root_lost", (event) => {
+          setErrorMessage(event.payload.message);
+          setScanStatus("error");
+          setCurrentView("HOME");
+          loadDrives();
+        })
+      );
+
+      unlisteners.push(
+        await appWindow.listen("scan://canceled", () => {
           setScanStatus("canceled");
           setCurrentView("HOME");
         })
       );
+
+      unlisteners.push(
+        await listen<RootsChangedPayload>("roots://changed", (event) => {
+          setDrives(event.payload.roots);
+        })
+      );
+
+      unlisteners.push(
+        await listen<ScanRequestedPayload>("app://scan_requested", async () => {
+          const path = await invoke<string | null>("take_scan_request");
+          if (path) setRequestedScanPath(path);
+        })
+      );
+
+      // A folder passed at launch may have been requested before we were listening
+      const launchPath = await invoke<string | null>("take_scan_request");
+      if (launchPath) setRequestedScanPath(launchPath);
     };
 
     setupListeners();
@@ -187,6 +254,7 @@ function App() {
     setTreeNodes({});
     setErrorMessage(null);
     setScanErrors([]);
+    setSkipped([]);
     setRootNodeId(null);
     setCurrentView("SCANNING");
 
@@ -210,6 +278,14 @@ function App() {
     }
   };
 
+  // Scan folders requested from the Explorer context menu or the command line
+  useEffect(() => {
+    if (!requestedScanPath) return;
+    setRequestedScanPath(null);
+    const drive = drives.find((d) => d.path === requestedScanPath);
+    startScan(drive ?? folderEntry(requestedScanPath));
+  }, [requestedScanPath]);
+
   const cancelScan = async () => {
     if (!currentScanId) return;
     try {
@@ -266,6 +342,8 @@ function App() {
             targetPath={selectedDrive?.path || ""}
             rootPath={selectedDrive?.path}
             scanErrors={scanErrors}
+            skipped={skipped}
+            errorMessage={errorMessage}
             totalDriveBytes={selectedDrive?.total_bytes}
           />
         );
@@ -275,6 +353,7 @@ function App() {
             summary={summary}
             treeNodes={treeNodes}
             rootNodeId={rootNodeId}
+            scanId={currentScanId}
             rootPath={selectedDrive?.path}
             onExtensionClick={handleExtensionClick}
             onNewScan={handleNewScan}
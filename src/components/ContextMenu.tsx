@@ -10,19 +10,23 @@ interface ContextMenuProps {
   y: number;
   onClose: () => void;
   onOpenInExplorer: (path: string) => void;
-  onDelete: (path: string, toTrash: boolean) => void;
+  onDelete: (path: string, toTrash: boolean, confirmationToken: string | null) => void;
   onCopyPath: (path: string) => void;
   onDeleteComplete?: (path: string, bytesFreed: number) => void;
+  scanId?: string | null;
+  onCopyReport?: (report: string) => void;
 }
 
 const SAFETY_COLORS: Record<SafetyLevel, string> = {
   AutoDelete: 'text-green-400',
+  ReclaimableBuild: 'text-sky-400',
   ConfirmRequired: 'text-amber-400',
   Protected: 'text-red-400',
 };
 
 const SAFETY_LABELS: Record<SafetyLevel, string> = {
   AutoDelete: 'Safe to delete',
+  ReclaimableBuild: 'Regenerable build output',
   ConfirmRequired: 'Needs confirmation',
   Protected: 'Protected file',
 };
@@ -36,6 +40,8 @@ const ContextMenu: React.FC<ContextMenuProps> = ({
   onDelete,
   onCopyPath,
   onDeleteComplete,
+  scanId,
+  onCopyReport,
 }) => {
   const menuRef = useRef<HTMLDivElement>(null);
   const [fileInfo, setFileInfo] = useState<FileInfo | null>(null);
@@ -60,6 +66,7 @@ const ContextMenu: React.FC<ContextMenuProps> = ({
           is_dir: node.kind === 'dir',
           modified_days_ago: null,
           extension: node.file_ext,
+          junk: { score: 0, reasons: [] },
         });
       } finally {
         setLoading(false);
@@ -111,9 +118,12 @@ const ContextMenu: React.FC<ContextMenuProps> = ({
   const performDelete = async () => {
     setIsDeleting(true);
     try {
+      // ConfirmRequired paths need the one-time token that came with the file details
+      const confirmationToken = fileInfo?.confirmation?.token ?? null;
+
       const result = await invoke<{ success: boolean; bytes_freed: number; errors: string[] }>('smart_delete', {
         path: node.path,
-        force: true, // User confirmed or it's auto-delete
+        confirmationToken,
       });
 
       if (result.success) {
@@ -153,6 +163,19 @@ const ContextMenu: React.FC<ContextMenuProps> = ({
         onClose();
       },
     },
+    ...(scanId && onCopyReport ? [{
+      icon: 'summarize',
+      label: 'Copy Report',
+      onClick: async () => {
+        try {
+          const report = await invoke<string>('get_node_report', { scanId, nodeId: node.id, format: 'markdown' });
+          onCopyReport(report);
+        } catch (err) {
+          console.error('Failed to build report:', err);
+        }
+        onClose();
+      },
+    }] : []),
     { type: 'separator' as const },
     {
       icon: 'auto_delete',
@@ -170,23 +193,25 @@ const ContextMenu: React.FC<ContextMenuProps> = ({
       icon: 'delete',
       label: 'Move to Trash',
       onClick: () => {
-        onDelete(node.path, true);
+        onDelete(node.path, true, fileInfo?.confirmation?.token ?? null);
         onClose();
       },
       danger: true,
       secondary: true,
+      disabled: loading || fileInfo?.safety_level === 'Protected',
     },
     {
       icon: 'delete_forever',
       label: 'Delete Permanently',
       onClick: () => {
         if (confirm(`Permanently delete "${node.name}"?\n\nThis action cannot be undone.`)) {
-          onDelete(node.path, false);
+          onDelete(node.path, false, fileInfo?.confirmation?.token ?? null);
           onClose();
         }
       },
       danger: true,
       secondary: true,
+      disabled: loading || fileInfo?.safety_level === 'Protected',
     },
   ];
 
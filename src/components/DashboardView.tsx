@@ -1,5 +1,5 @@
 import React, { useState, useCallback, useRef, useEffect } from 'react';
-import type { ScanSummary, TreeNodeDelta, NodeId } from '../types';
+import type { ScanSummary, TreeNodeDelta, NodeId, DeleteResult } from '../types';
 import { FORMAT_SIZE } from '../constants';
 import { invoke } from '@tauri-apps/api/core';
 import Treemap from './Treemap';
@@ -22,6 +22,7 @@ interface DashboardViewProps {
   rootPath?: string | null;
   treeNodes: TreeNodeMap;
   rootNodeId: NodeId | null;
+  scanId?: string | null;
 }
 
 type ViewMode = 'treemap' | 'tree' | 'table';
@@ -36,6 +37,7 @@ const DashboardView: React.FC<DashboardViewProps> = ({
   rootPath, 
   treeNodes, 
   rootNodeId,
+  scanId,
 }) => {
   // State
   const [viewMode, setViewMode] = useState<ViewMode>('treemap');
@@ -94,11 +96,12 @@ const DashboardView: React.FC<DashboardViewProps> = ({
     }
   }, []);
 
-  const handleDelete = useCallback(async (path: string, toTrash: boolean) => {
+  const handleDelete = useCallback(async (path: string, toTrash: boolean, confirmationToken: string | null) => {
     try {
-      await invoke('delete_path', { path, toTrash });
+      const result = await invoke<DeleteResult>('delete_path', { path, toTrash, confirmationToken });
+      const freed = FORMAT_SIZE(result.bytes_freed);
       setNotification({ 
-        message: toTrash ? 'Moved to Trash' : 'Deleted permanently', 
+        message: toTrash ? `Moved to Trash (${freed})` : `Deleted permanently, ${freed} freed`, 
         type: 'success' 
       });
       // Trigger rescan to update the tree
@@ -155,6 +158,11 @@ const DashboardView: React.FC<DashboardViewProps> = ({
           onOpenInExplorer={handleOpenInExplorer}
           onDelete={handleDelete}
           onCopyPath={handleCopyPath}
+          scanId={scanId}
+          onCopyReport={(report) => {
+            navigator.clipboard.writeText(report);
+            setNotification({ message: 'Report copied to clipboard', type: 'success' });
+          }}
         />
       )}
 
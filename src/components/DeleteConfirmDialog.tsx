@@ -16,6 +16,12 @@ const SAFETY_CONFIG: Record<SafetyLevel, { color: string; icon: string; label: s
     label: 'Safe to Delete',
     description: 'This is a temporary/cache file that can be safely deleted.',
   },
+  ReclaimableBuild: {
+    color: 'text-sky-400',
+    icon: 'build',
+    label: 'Regenerable Build Output',
+    description: 'This folder is build output and can be regenerated from the project.',
+  },
   ConfirmRequired: {
     color: 'text-amber-400',
     icon: 'warning',
@@ -76,6 +82,9 @@ const DeleteConfirmDialog: React.FC<DeleteConfirmDialogProps> = ({
             <div>
               <h3 className="text-lg font-semibold text-white">{config.label}</h3>
               <p className="text-sm text-slate-400">{config.description}</p>
+              {fileInfo.regenerate_hint && (
+                <p className="text-xs text-slate-500 mt-1">{fileInfo.regenerate_hint}</p>
+              )}
             </div>
           </div>
         </div>
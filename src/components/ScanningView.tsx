@@ -1,5 +1,11 @@
 import React, { useState, useEffect } from 'react';
-import type { ScanProgressPayload } from '../types';
+import type { ScanProgressPayload, SkipGroup, SkipReason } from '../types';
+
+const SKIP_LABELS: Record<SkipReason, string> = {
+  system_skip: 'system folders',
+  excluded_pattern: 'excluded',
+  permission: 'unreadable',
+};
 
 interface ScanningViewProps {
   progress: ScanProgressPayload | null;
@@ -7,11 +13,12 @@ interface ScanningViewProps {
   rootPath?: string | null;
   errorMessage?: string | null;
   scanErrors?: string[];
+  skipped?: SkipGroup[];
   totalDriveBytes?: number;
   onCancel: () => void;
 }
 
-const ScanningView: React.FC<ScanningViewProps> = ({ progress, targetPath: _targetPath, rootPath, errorMessage, scanErrors = [], totalDriveBytes, onCancel }) => {
+const ScanningView: React.FC<ScanningViewProps> = ({ progress, targetPath: _targetPath, rootPath, errorMessage, scanErrors = [], skipped = [], totalDriveBytes, onCancel }) => {
   const objects = progress?.visited_entries ?? 0;
   const visitedBytes = progress?.visited_bytes_approx ?? 0;
   const volumeGb = visitedBytes / (1024 * 1024 * 1024);
@@ -26,6 +33,10 @@ const ScanningView: React.FC<ScanningViewProps> = ({ progress, targetPath: _targ
   const calculateProgress = () => {
     if (phase === 'finalizing') return 99;
     if (phase === 'initializing' || objects === 0) return 0;
+    // Two-phase scans know how many files the sizing pass has left
+    if (phase === 'sizing' && progress?.files_total) {
+      return Math.min(95, Math.max(1, ((progress.files_sized ?? 0) / progress.files_total) * 95));
+    }
     
     // If we have drive size, calculate based on bytes scanned
     if (totalDriveBytes && totalDriveBytes > 0) {
@@ -134,6 +145,18 @@ const ScanningView: React.FC<ScanningViewProps> = ({ progress, targetPath: _targ
           </div>
         )}
 
+        {skipped.length > 0 && (
+          <div
+            className="text-[10px] font-bold uppercase tracking-[0.2em] text-slate-400 mb-8"
+            title={skipped.flatMap((group) => group.sample).join('\n')}
+          >
+            Skipped:{' '}
+            <span className="font-mono normal-case tracking-normal text-slate-500">
+              {skipped.map((group) => `${group.count.toLocaleString()} ${SKIP_LABELS[group.reason]}`).join(', ')}
+            </span>
+          </div>
+        )}
+
         {errorMessage && (
           <div className="text-[10px] font-bold uppercase tracking-[0.2em] text-red-500 mb-8">
             Error: <span className="font-mono normal-case tracking-normal text-red-400">{errorMessage}</span>
@@ -7,8 +7,60 @@ export interface ScanOptions {
   one_file_system?: boolean;
   max_depth?: number | null;
   exclude_patterns?: string[];
+  analyzers?: AnalyzerConfig[];
+  two_phase?: boolean;
+  backend?: ScanBackend;
+  safe_mode?: boolean;
+  /** Path of a newline-delimited list of sizes and paths to build the tree from */
+  file_list?: string | null;
+  file_list_format?: FileListFormat;
+  limits?: ScanLimits;
+  /** Files of this size or more are only counted, per folder, as "<large files>" */
+  size_ceiling?: number | null;
+  /** Time each directory's listing to find what made the scan slow */
+  profile_dirs?: boolean;
 }
 
+/** Per-scan caps; null or absent means no cap */
+export interface ScanLimits {
+  max_threads?: number | null;
+  max_memory_bytes?: number | null;
+  max_open_handles?: number | null;
+}
+
+/** Where the options suggested for a root came from */
+export type DefaultsSource =
+  | { kind: "root" }
+  | { kind: "ancestor"; path: string }
+  | { kind: "volume"; path: string }
+  | { kind: "builtin" };
+
+export interface DefaultOptions {
+  options: ScanOptions;
+  source: DefaultsSource;
+}
+
+export type LimitAction =
+  | { kind: "reduced_threads"; threads: number }
+  | { kind: "aggregated_small_files"; after_entries: number; files: number; bytes: number };
+
+export type FileListFormat = 'plain' | 's3_inventory' | 'rclone_json';
+
+export type ScanBackend = 'auto' | 'walker' | 'attr_list_bulk' | 'io_uring' | 'parallel_stat';
+
+export type AnalyzerConfig =
+  | { kind: "top_files"; limit?: number }
+  | { kind: "age_histogram" }
+  | { kind: "category_stats" }
+  | { kind: "similar_images"; max_bytes?: number; max_distance?: number }
+  | { kind: "owner_usage" }
+  | { kind: "high_entropy"; min_bytes?: number }
+  | { kind: "executables" }
+  | { kind: "vm_disks" }
+  | { kind: "browser_profiles" }
+  | { kind: "app_data" }
+  | { kind: "shader_caches" };
+
 export type NodeKind = "file" | "dir";
 
 export interface TreeNode {
@@ -19,6 +71,7 @@ export interface TreeNode {
   kind: NodeKind;
   size_bytes: number;
   file_ext: string | null;
+  informational?: boolean;
   children: NodeId[];
 }
 
@@ -30,6 +83,7 @@ export interface TreeNodeDelta {
   kind: NodeKind;
   size_bytes: number;
   file_ext: string | null;
+  informational?: boolean;
 }
 
 export interface ExtensionStat {
@@ -38,6 +92,150 @@ export interface ExtensionStat {
   count: number;
 }
 
+export interface TopFileEntry {
+  id: NodeId;
+  path: string;
+  size_bytes: number;
+}
+
+export interface AgeBucket {
+  label: string;
+  max_age_secs: number | null;
+  bytes: number;
+  count: number;
+}
+
+export interface CategoryStat {
+  category: string;
+  bytes: number;
+  count: number;
+}
+
+export interface SelectionStats {
+  total_bytes: number;
+  total_files: number;
+  extensions: ExtensionStat[];
+  categories: CategoryStat[];
+  missing: string[];
+}
+
+export type AnalyzerReport =
+  | { kind: "extension_stats"; stats: ExtensionStat[] }
+  | { kind: "top_files"; files: TopFileEntry[] }
+  | { kind: "age_histogram"; buckets: AgeBucket[]; directories: DirectoryAgeHistogram[] }
+  | { kind: "category_stats"; categories: CategoryStat[] }
+  | { kind: "similar_images"; groups: SimilarImageGroup[] }
+  | { kind: "owner_usage"; owners: OwnerUsage[] }
+  | { kind: "size_distribution"; buckets: SizeBucket[] }
+  | { kind: "high_entropy"; files: HighEntropyFile[] }
+  | { kind: "executables"; files: ExecutableFile[] }
+  | { kind: "vm_disks"; disks: VmDiskImage[] }
+  | { kind: "browser_profiles"; profiles: BrowserProfileUsage[] }
+  | { kind: "app_data"; apps: AppDataUsage[] }
+  | { kind: "shader_caches"; caches: ShaderCacheUsage[] };
+
+export interface SignatureInfo {
+  signed: boolean;
+  status: string;
+  publisher: string | null;
+}
+
+export interface ExecutableFile {
+  id: NodeId;
+  path: string;
+  size_bytes: number;
+  ext: string;
+  modified: number | null;
+  in_temp: boolean;
+  signature: SignatureInfo | null;
+}
+
+export interface VmDiskImage {
+  id: NodeId;
+  path: string;
+  format: string;
+  hypervisor: string;
+  file_bytes: number;
+  allocated_bytes: number;
+  virtual_bytes: number | null;
+  modified: number | null;
+  compactable: boolean;
+  compaction_hint: string;
+}
+
+export type BrowserStorageKind =
+  | "cache"
+  | "service_worker"
+  | "indexed_db"
+  | "history"
+  | "site_data"
+  | "extensions"
+  | "other";
+
+export interface BrowserStorage {
+  kind: BrowserStorageKind;
+  bytes: number;
+  files: number;
+  safe_to_clear: boolean;
+  paths: string[];
+}
+
+export interface BrowserProfileUsage {
+  browser: string;
+  profile: string;
+  paths: string[];
+  total_bytes: number;
+  clearable_bytes: number;
+  storage: BrowserStorage[];
+}
+
+export interface AppDataUsage {
+  app: string;
+  paths: string[];
+  total_bytes: number;
+  files: number;
+  cache_bytes: number;
+  cache_fraction: number;
+}
+
+export interface ShaderCacheUsage {
+  source: string;
+  path: string;
+  steam_app_id: number | null;
+  bytes: number;
+  files: number;
+}
+
+export interface HighEntropyFile {
+  id: NodeId;
+  path: string;
+  size_bytes: number;
+  entropy: number;
+}
+
+export interface OwnerUsage {
+  owner_id: string;
+  name: string | null;
+  bytes: number;
+  files: number;
+}
+
+export interface SimilarImage {
+  id: NodeId;
+  path: string;
+  size_bytes: number;
+  width: number;
+  height: number;
+  modified: number | null;
+  thumbnail: string | null;
+}
+
+export interface SimilarImageGroup {
+  images: SimilarImage[];
+  keeper_id: NodeId;
+  reclaimable_bytes: number;
+}
+
 export interface ScanResult {
   scan_id: string;
   root_id: NodeId;
@@ -45,6 +243,45 @@ export interface ScanResult {
   total_files: number;
   total_dirs: number;
   extension_stats: ExtensionStat[];
+  analyzer_reports: AnalyzerReport[];
+  skipped_paths: string[];
+  error_paths: string[];
+  skipped: SkipGroup[];
+  root_aliases: string[];
+  error_groups: ErrorGroup[];
+  started_at: number;
+  options: ScanOptions;
+  reused_from: string | null;
+  complete: boolean;
+  incomplete_dirs: string[];
+  unreadable_regions: UnreadableRegion[];
+  limit_actions: LimitAction[];
+  /** Slowest directories first; empty unless the scan ran with profile_dirs */
+  slow_dirs: DirTiming[];
+}
+
+export interface DirTiming {
+  path: string;
+  millis: number;
+  entries: number;
+}
+
+export type UnreadableReason = "timed_out" | "read_error" | "blacklisted";
+
+/** A file or folder a safe-mode scan could not read */
+export interface UnreadableRegion {
+  path: string;
+  is_dir: boolean;
+  reason: UnreadableReason;
+  message: string | null;
+}
+
+export type SkipReason = 'system_skip' | 'excluded_pattern' | 'permission' | 'unreadable';
+
+export interface SkipGroup {
+  reason: SkipReason;
+  count: number;
+  sample: string[];
 }
 
 export interface ScanSummary {
@@ -52,10 +289,68 @@ export interface ScanSummary {
   total_files: number;
   total_dirs: number;
   extension_stats: ExtensionStat[];
+  size_distribution: SizeBucket[];
+}
+
+export interface SizeBucket {
+  label: string;
+  min_bytes: number;
+  max_bytes: number | null;
+  bytes: number;
+  count: number;
+}
+
+export type RootOverlap = 'same' | 'contains' | 'within';
+
+export interface ScanOverlap {
+  scan_id: string;
+  root_path: string;
+  relation: RootOverlap;
 }
 
 export interface ScanHandle {
   scan_id: string;
+  overlaps: ScanOverlap[];
+  reused_from: string | null;
+  decision: OverlapDecision;
+}
+
+/** What a new scan does about running scans of overlapping roots */
+export type OverlapDecision =
+  | { action: "run" }
+  | { action: "queued"; behind: string; root_path: string }
+  | { action: "carved_out"; scans: ScanOverlap[]; excluded_paths: string[] };
+
+export interface ScanReuse {
+  validate?: boolean;
+  max_age_secs?: number | null;
+}
+
+export interface CoveringScan {
+  scan_id: string;
+  root_path: string;
+  node_id: NodeId;
+  path: string;
+  total_bytes: number;
+  scanned_at: number;
+  age_secs: number;
+}
+
+export interface BackendTiming {
+  backend: ScanBackend;
+  entries: number;
+  files: number;
+  total_bytes: number;
+  wall_ms: number;
+  entries_per_sec: number;
+  error: string | null;
+}
+
+export interface ScanBenchmark {
+  path: string;
+  timings: BackendTiming[];
+  best: ScanBackend | null;
+  previous_default: ScanBackend;
 }
 
 export interface RootEntry {
@@ -63,6 +358,21 @@ export interface RootEntry {
   path: string;
   total_bytes: number;
   available_bytes: number;
+  file_system: string;
+  copy_on_write: boolean;
+  is_removable: boolean;
+  is_network: boolean;
+  is_read_only: boolean;
+  label: string | null;
+  device: string;
+  disk_kind: 'hdd' | 'ssd' | 'unknown';
+  encrypted: boolean | null;
+}
+
+export interface RootsChangedPayload {
+  roots: RootEntry[];
+  added: string[];
+  removed: string[];
 }
 
 // Event payloads
@@ -70,6 +380,19 @@ export interface StartedPayload {
   scan_id: string;
   root_path: string;
   started_at: number;
+  canonical_root: string | null;
+  overlaps: ScanOverlap[];
+  capabilities: VolumeCapabilities | null;
+}
+
+export interface VolumeCapabilities {
+  mount_point: string;
+  file_system: string;
+  case_sensitive: boolean;
+  hardlinks: boolean;
+  reflinks: boolean;
+  compression: boolean;
+  cluster_size: number;
 }
 
 export interface ProgressPayload {
@@ -78,6 +401,8 @@ export interface ProgressPayload {
   visited_bytes_approx: number;
   current_path: string;
   phase: string;
+  files_sized: number | null;
+  files_total: number | null;
 }
 
 export interface PartialTreePayload {
@@ -93,14 +418,77 @@ export interface FinishedPayload {
   finished_at: number;
 }
 
+/** A backend string as a code and params to translate, with its English rendering */
+export interface Message {
+  code: string;
+  params: Record<string, string>;
+  text: string;
+}
+
+export interface MessageTemplate {
+  code: string;
+  template: string;
+}
+
 export interface ErrorPayload {
   scan_id: string;
   message: string;
   path: string | null;
+  localized: Message;
+}
+
+export type ScanErrorKind = 'permission_denied' | 'not_found' | 'timed_out' | 'loop' | 'io';
+
+export interface ErrorGroup {
+  kind: ScanErrorKind;
+  parent: string;
+  count: number;
+  message: string;
+  sample: string[];
+}
+
+export interface ErrorRollupPayload {
+  scan_id: string;
+  groups: ErrorGroup[];
+  suppressed: number;
 }
 
 export interface CanceledPayload {
   scan_id: string;
+  partial_result: boolean;
+}
+
+export interface RootLostPayload {
+  scan_id: string;
+  root_path: string;
+  message: string;
+}
+
+export interface SkippedPayload {
+  scan_id: string;
+  groups: SkipGroup[];
+}
+
+export interface HeartbeatPayload {
+  scan_id: string;
+  phase: string;
+  visited_entries: number;
+  elapsed_ms: number;
+  idle_ms: number;
+}
+
+export interface StalledPayload {
+  scan_id: string;
+  phase: string;
+  visited_entries: number;
+  idle_ms: number;
+  message: string;
+}
+
+export interface StalePayload {
+  scan_id: string;
+  paths: string[];
+  node_ids: NodeId[];
 }
 
 // App-specific types
@@ -147,7 +535,7 @@ export interface TopFile {
 // SMART DELETE TYPES
 // ==========================================
 
-export type SafetyLevel = 'AutoDelete' | 'ConfirmRequired' | 'Protected';
+export type SafetyLevel = 'AutoDelete' | 'ReclaimableBuild' | 'ConfirmRequired' | 'Protected';
 
 export interface DeleteResult {
   success: boolean;
@@ -156,6 +544,62 @@ export interface DeleteResult {
   folders_deleted: number;
   errors: string[];
   was_auto_delete: boolean;
+  failures: DeleteFailure[];
+  quarantined_to?: string | null;
+}
+
+export interface PatternMatch {
+  id: number;
+  path: string;
+  kind: NodeKind;
+  size_bytes: number;
+  /** The strictest level of the node and everything below it */
+  safety: SafetyLevel;
+  /** The entry inside a matched folder that set `safety` */
+  held_by: string | null;
+}
+
+export interface PatternDelete {
+  pattern: string;
+  safety_floor: SafetyLevel;
+  dry_run: boolean;
+  matches: PatternMatch[];
+  matched_bytes: number;
+  skipped_count: number;
+  skipped_bytes: number;
+  /** On a dry run, a one-time token covering the ConfirmRequired matches */
+  confirmation: ConfirmationToken | null;
+  result: DeleteResult | null;
+}
+
+export interface LockHolder {
+  pid: number;
+  name: string;
+}
+
+export interface DeleteFailure {
+  path: string;
+  message: string;
+  locked: boolean;
+  lock_holders: LockHolder[];
+  localized: Message | null;
+  /** Left in place for being too large for the Recycle Bin */
+  wont_fit_trash?: boolean;
+}
+
+export type ErrorPolicy = 'fail_fast' | 'continue';
+
+export interface DeleteThrottle {
+  max_files_per_sec: number | null;
+  max_bytes_per_sec: number | null;
+}
+
+export interface ConfirmationToken {
+  token: string;
+  paths: string[];
+  expires_at: number;
+  /** Delete tokens: the safety level each path was previewed with */
+  safety?: SafetyLevel[];
 }
 
 export interface FileInfo {
@@ -166,6 +610,43 @@ export interface FileInfo {
   is_dir: boolean;
   modified_days_ago: number | null;
   extension: string | null;
+  junk: JunkScore;
+  system_file: SystemFileInfo | null;
+  safety_reason: string | null;
+  safety_reason_message: Message | null;
+  regenerate_hint: string | null;
+  /** One-time token for deleting a ConfirmRequired path */
+  confirmation: ConfirmationToken | null;
+}
+
+export type SystemFileKind = 'pagefile' | 'hibernation' | 'swapfile';
+
+export type SystemFileGuidance =
+  | 'hibernation_can_be_disabled'
+  | 'hibernation_can_be_reduced'
+  | 'size_can_be_limited'
+  | 'can_move_to_another_drive';
+
+export interface SystemFileInfo {
+  kind: SystemFileKind;
+  guidance: SystemFileGuidance[];
+}
+
+export interface JunkScore {
+  score: number;
+  reasons: string[];
+  reason_messages: Message[];
+}
+
+export interface JunkCandidate {
+  id: NodeId;
+  path: string;
+  name: string;
+  kind: NodeKind;
+  size_bytes: number;
+  score: number;
+  reasons: string[];
+  reason_messages: Message[];
 }
 
 export interface DeletedPayload {
@@ -179,3 +660,822 @@ export interface DeleteFailedPayload {
   reason: string;
 }
 
+
+// ==========================================
+// DUPLICATE TYPES
+// ==========================================
+
+export interface DuplicateFile {
+  id: NodeId;
+  path: string;
+  modified: number | null;
+}
+
+export interface DuplicateGroup {
+  id: string;
+  scan_id: string;
+  hash: string;
+  size_bytes: number;
+  files: DuplicateFile[];
+}
+
+export type KeepStrategy =
+  | { kind: 'keep_newest' }
+  | { kind: 'keep_oldest' }
+  | { kind: 'keep_in_path_prefix'; prefix: string }
+  | { kind: 'keep_list'; paths: string[] };
+
+export interface DuplicateResolution {
+  group_id: string;
+  kept: string[];
+  result: DeleteResult;
+}
+
+export type SimilarReason = 'same_content' | 'same_size' | 'similar_name' | 'same_extension_and_folder';
+
+/** A file that looks like another copy or render of a chosen file */
+export interface SimilarFile {
+  id: number;
+  path: string;
+  size_bytes: number;
+  modified: number | null;
+  reasons: SimilarReason[];
+  score: number;
+}
+
+/** File hashes duplicate search keeps between sessions */
+export interface HashCacheStats {
+  entries: number;
+  full_hashes: number;
+  covered_bytes: number;
+  file_bytes: number;
+  hits: number;
+  misses: number;
+}
+
+// ==========================================
+// ARCHIVE TYPES
+// ==========================================
+
+export type ArchiveFormat = 'zip' | 'seven_zip' | 'tar' | 'tar_gz' | 'iso' | 'wim';
+
+export interface ArchiveEntry {
+  name: string;
+  size_bytes: number;
+  compressed_bytes: number | null;
+  is_dir: boolean;
+}
+
+export interface ArchiveListing {
+  path: string;
+  format: ArchiveFormat;
+  archive_bytes: number;
+  uncompressed_bytes: number;
+  entries: ArchiveEntry[];
+}
+
+export interface ExtractResult {
+  extracted: string[];
+  bytes_written: number;
+  missing: string[];
+  conflicts: string[];
+}
+
+export type ArchivePhase = 'archiving' | 'verifying' | 'deleting';
+
+export interface ArchiveProgressPayload {
+  archive_path: string;
+  phase: ArchivePhase;
+  bytes_done: number;
+  bytes_total: number;
+}
+
+export interface ArchiveSummary {
+  files: number;
+  folders: number;
+  bytes: number;
+  archive_bytes: number;
+}
+
+export interface ArchiveDeleteResult {
+  archive_path: string;
+  summary: ArchiveSummary;
+  delete: DeleteResult;
+}
+
+// ==========================================
+// COMPRESSION TYPES
+// ==========================================
+
+export interface CompressibilityEstimate {
+  path: string;
+  size_bytes: number;
+  sampled_bytes: number;
+  compressed_sample_bytes: number;
+  ratio: number;
+  estimated_savings_bytes: number;
+}
+
+export interface CompressionResult {
+  path: string;
+  before_bytes: number;
+  after_bytes: number;
+  saved_bytes: number;
+}
+
+// ==========================================
+// COPY-ON-WRITE TYPES
+// ==========================================
+
+export interface SnapshotUsage {
+  name: string;
+  exclusive_bytes: number;
+}
+
+export interface CowUsage {
+  path: string;
+  mount_point: string;
+  filesystem: 'btrfs' | 'zfs';
+  logical_bytes: number | null;
+  referenced_bytes: number | null;
+  disk_bytes: number | null;
+  snapshot_bytes: number | null;
+  snapshots: SnapshotUsage[];
+}
+
+// ==========================================
+// SPACE RECONCILIATION TYPES
+// ==========================================
+
+export type ReconciliationKind =
+  | 'skipped_dirs'
+  | 'denied_dirs'
+  | 'filesystem_metadata'
+  | 'allocation_slack'
+  | 'system_files'
+  | 'shadow_copies';
+
+export interface ReconciliationItem {
+  kind: ReconciliationKind;
+  label: string;
+  bytes: number;
+  estimated: boolean;
+  paths: string[];
+}
+
+export interface SpaceReconciliation {
+  scan_id: string;
+  root_path: string;
+  mount_point: string;
+  volume_used_bytes: number;
+  scanned_bytes: number;
+  covers_whole_volume: boolean;
+  items: ReconciliationItem[];
+  unexplained_bytes: number;
+}
+
+export type RootComponentKind = 'trash' | 'shadow_copies' | 'hibernation' | 'pagefile' | 'swapfile' | 'scanned_files';
+
+export interface RootComponent {
+  kind: RootComponentKind;
+  bytes: number;
+  paths: string[];
+}
+
+export interface RootBreakdown {
+  mount_point: string;
+  file_system: string;
+  total_bytes: number;
+  available_bytes: number;
+  used_bytes: number;
+  scan_id: string | null;
+  scan_covers_volume: boolean;
+  components: RootComponent[];
+  unaccounted_bytes: number;
+}
+
+// ==========================================
+// SHADOW COPY TYPES
+// ==========================================
+
+export interface ShadowStorage {
+  volume: string;
+  storage_volume: string;
+  used_bytes: number;
+  allocated_bytes: number;
+  max_bytes: number | null;
+}
+
+// ==========================================
+// TRASH CAPACITY TYPES
+// ==========================================
+
+export interface TrashCapacity {
+  mount_point: string;
+  max_bytes: number | null;
+  max_is_default: boolean;
+  used_bytes: number | null;
+  bypasses_trash: boolean;
+}
+
+export interface TrashFit {
+  path: string;
+  size_bytes: number;
+  fits: boolean;
+  capacity: TrashCapacity;
+}
+
+export type TrashFallback = 'permanent' | 'quarantine' | 'abort';
+
+export interface TrashEntry {
+  id: string;
+  name: string;
+  original_path: string;
+  deleted_at: number | null;
+  size_bytes: number;
+  is_dir: boolean;
+  mount_point: string | null;
+}
+
+// ==========================================
+// RULE PACK TYPES
+// ==========================================
+
+export interface RulePackReport {
+  path: string | null;
+  name: string | null;
+  rule_count: number;
+  errors: string[];
+}
+
+export interface SettingsImport {
+  exported_at: number;
+  policies: number;
+  bookmarks: number;
+  rule_packs: RulePackReport[];
+}
+
+// ==========================================
+// BACKGROUND AGENT TYPES
+// ==========================================
+
+export interface ScheduledScan {
+  root_path: string;
+  every_hours: number;
+  options?: ScanOptions;
+  last_run_at?: number | null;
+}
+
+export interface AgentSettings {
+  run_in_tray: boolean;
+  autostart: boolean;
+  scheduled_scan: ScheduledScan | null;
+  low_space_percent: number | null;
+  run_window?: RunWindow | null;
+}
+
+/** Minutes after local midnight; an end before the start runs past midnight */
+export interface TimeRange {
+  start_minute: number;
+  end_minute: number;
+}
+
+export interface RunWindow {
+  hours?: TimeRange | null;
+  require_ac_power?: boolean;
+  skip_metered?: boolean;
+}
+
+export type WindowBlocker = 'outside_hours' | 'on_battery' | 'metered_connection';
+
+export interface LastScan {
+  scan_id: string;
+  root_path: string;
+  total_bytes: number;
+  finished_at: number;
+}
+
+export interface AgentStatus {
+  last_scan: LastScan | null;
+  freed_since_last_scan: number;
+  lowest_free: [string, number] | null;
+  window_blockers: WindowBlocker[];
+}
+
+export interface LowSpacePayload {
+  mount_point: string;
+  available_bytes: number;
+  total_bytes: number;
+  threshold_percent: number;
+}
+
+export interface NotificationSettings {
+  enabled: boolean;
+  scan_finished: boolean;
+  scheduled_scan: boolean;
+  background_delete: boolean;
+}
+
+// ==========================================
+// SHELL INTEGRATION TYPES
+// ==========================================
+
+export interface ScanRequestedPayload {
+  path: string;
+}
+
+// ==========================================
+// EXPORT TYPES
+// ==========================================
+
+export type ExportFormat = 'parquet';
+
+export interface ExportResult {
+  path: string;
+  format: ExportFormat;
+  rows: number;
+  bytes_written: number;
+  anonymized: Anonymization | null;
+}
+
+/** How names are hidden in an export meant for sharing */
+export type Anonymization = 'hash' | 'pseudonym';
+
+// ==========================================
+// LOCAL API TYPES
+// ==========================================
+
+export interface ApiSettings {
+  enabled: boolean;
+  port: number;
+}
+
+// ==========================================
+// REPORT TYPES
+// ==========================================
+
+export type ReportFormat = 'text' | 'markdown' | 'csv';
+
+// ==========================================
+// POLICY TYPES
+// ==========================================
+
+export type PolicyTarget =
+  | { kind: 'recycle_bin' }
+  | { kind: 'folder'; path: string };
+
+export type PolicyAction = 'trash' | 'delete';
+
+export interface Policy {
+  id: string;
+  name: string;
+  enabled: boolean;
+  target: PolicyTarget;
+  action: PolicyAction;
+  min_age_days: number | null;
+  min_size_bytes: number | null;
+  every_hours: number;
+  last_run_at: number | null;
+}
+
+export interface PolicyMatch {
+  path: string;
+  size_bytes: number;
+  age_days: number | null;
+}
+
+export interface PolicyRun {
+  policy_id: string;
+  policy_name: string;
+  started_at: number;
+  finished_at: number;
+  dry_run: boolean;
+  matched_count: number;
+  matched_bytes: number;
+  matches: PolicyMatch[];
+  removed_count: number;
+  freed_bytes: number;
+  errors: string[];
+}
+
+// ==========================================
+// BOOKMARK TYPES
+// ==========================================
+
+export interface Bookmark {
+  path: string;
+  label: string | null;
+  added_at: number;
+  last_bytes: number | null;
+  last_scanned_at: number | null;
+}
+
+export interface BookmarkChild {
+  name: string;
+  size_bytes: number;
+}
+
+export interface BookmarkSummary {
+  path: string;
+  label: string | null;
+  scanned_at: number;
+  total_bytes: number;
+  total_files: number;
+  total_dirs: number;
+  delta_bytes: number | null;
+  top_children: BookmarkChild[];
+  error: string | null;
+}
+
+// ==========================================
+// TAG TYPES
+// ==========================================
+
+export interface Annotation {
+  tags: string[];
+  note: string | null;
+  updated_at: number;
+}
+
+export interface TaggedNode {
+  node_id: NodeId;
+  path: string;
+  size_bytes: number;
+  tags: string[];
+  note: string | null;
+}
+
+// ==========================================
+// AGE HISTOGRAM TYPES
+// ==========================================
+
+export interface DirectoryAgeHistogram {
+  node_id: NodeId;
+  path: string;
+  buckets: AgeBucket[];
+}
+
+export interface AgeHistogramView {
+  node_id: NodeId;
+  buckets: AgeBucket[];
+  directories: DirectoryAgeHistogram[];
+}
+
+// ==========================================
+// BASELINE TYPES
+// ==========================================
+
+export interface BaselineInfo {
+  root_path: string;
+  scan_id: string;
+  created_at: number;
+  total_bytes: number;
+  dir_count: number;
+}
+
+export interface DriftThresholds {
+  min_growth_bytes: number;
+  min_growth_percent: number;
+}
+
+export interface DirectoryDrift {
+  path: string;
+  baseline_bytes: number;
+  current_bytes: number;
+  growth_bytes: number;
+}
+
+/** Payload of the 'baseline://drift' event */
+export interface DriftReport {
+  root_path: string;
+  scan_id: string;
+  baseline_scan_id: string;
+  baseline_created_at: number;
+  baseline_total_bytes: number;
+  current_total_bytes: number;
+  drifts: DirectoryDrift[];
+}
+
+// ==========================================
+// LARGE DOCUMENT TYPES
+// ==========================================
+
+export interface DocumentGroup {
+  folder_id: NodeId;
+  folder_path: string;
+  age: string;
+  total_bytes: number;
+  documents: JunkCandidate[];
+}
+
+// ==========================================
+// MEMORY TYPES
+// ==========================================
+
+export interface ScanMemory {
+  scan_id: string;
+  root_path: string;
+  node_count: number;
+  tree_bytes: number;
+  result_bytes: number;
+  cache_bytes: number;
+  spilled_bytes: number | null;
+  total_bytes: number;
+}
+
+export interface ActiveScanMemory {
+  scan_id: string;
+  visited_entries: number;
+  estimated_bytes: number;
+}
+
+export interface CacheMemory {
+  name: string;
+  bytes: number;
+}
+
+export interface SpillSettings {
+  enabled: boolean;
+  threshold_bytes: number;
+}
+
+export interface MemoryUsage {
+  process_bytes: number | null;
+  scans: ScanMemory[];
+  active_scans: ActiveScanMemory[];
+  caches: CacheMemory[];
+  tracked_bytes: number;
+}
+
+// ==========================================
+// QUERY TYPES
+// ==========================================
+
+/** Shared filter for search_nodes, get_top_files, and export_scan; unset fields match everything */
+export interface NodeFilter {
+  min_size?: number;
+  max_size?: number;
+  modified_after?: number;
+  modified_before?: number;
+  kinds?: NodeKind[];
+  extensions?: string[];
+  path_prefix?: string;
+  name_contains?: string;
+  min_depth?: number;
+  max_depth?: number;
+  safety_levels?: SafetyLevel[];
+}
+
+export interface NodeMatch {
+  id: NodeId;
+  path: string;
+  name: string;
+  kind: NodeKind;
+  size_bytes: number;
+  file_ext: string | null;
+  modified: number | null;
+  depth: number;
+  file_count: number | null;
+}
+
+export type Weight = 'bytes' | 'file_count';
+
+export type UserFolder =
+  | 'documents'
+  | 'pictures'
+  | 'videos'
+  | 'music'
+  | 'downloads'
+  | 'desktop'
+  | 'app_caches';
+
+export interface HomeCategoryUsage {
+  category: UserFolder;
+  path: string;
+  node_id: NodeId;
+  bytes: number;
+  files: number;
+  outside_home: boolean;
+}
+
+export interface HomeOverview {
+  scan_id: string;
+  home_path: string;
+  total_bytes: number;
+  total_files: number;
+  categories: HomeCategoryUsage[];
+  other_bytes: number;
+}
+
+export interface VolumeTrend {
+  mount_point: string;
+  total_bytes: number;
+  available_bytes: number;
+  week_delta_bytes: number | null;
+  days_until_full: number | null;
+}
+
+export interface CleanupSuggestion {
+  scan_id: string;
+  root_path: string;
+  candidates: number;
+  bytes: number;
+  top: JunkCandidate[];
+}
+
+export interface QuarantineUsage {
+  mount_point: string;
+  path: string;
+  totals: { bytes: number; files: number; folders: number };
+}
+
+export interface ScheduledJob {
+  kind: 'scan' | 'policy';
+  name: string;
+  id: string | null;
+  every_hours: number;
+  last_run_at: number | null;
+  next_run_at: number;
+}
+
+export interface DashboardSummary {
+  generated_at: number;
+  latest_scans: LastScan[];
+  freed_since_last_scan: number;
+  volumes: VolumeTrend[];
+  cleanup: CleanupSuggestion[];
+  quarantine: QuarantineUsage[];
+  scheduled: ScheduledJob[];
+  window_blockers: WindowBlocker[];
+}
+
+export interface ComponentStoreAnalysis {
+  explorer_size_bytes: number;
+  actual_size_bytes: number;
+  shared_with_windows_bytes: number;
+  backups_and_disabled_bytes: number;
+  cache_and_temp_bytes: number;
+  reclaimable_bytes: number;
+  reclaimable_packages: number;
+  cleanup_recommended: boolean;
+  last_cleanup: string | null;
+}
+
+export interface CleanupProgressPayload {
+  percent: number;
+}
+
+export interface CleanupFinishedPayload {
+  success: boolean;
+  error: string | null;
+  before: ComponentStoreAnalysis | null;
+  after: ComponentStoreAnalysis | null;
+  freed_bytes: number;
+}
+
+export type CrashDumpKind = 'system_dump' | 'minidump' | 'kernel_report' | 'error_report' | 'app_crash_dump';
+
+export interface CrashDump {
+  path: string;
+  kind: CrashDumpKind;
+  size_bytes: number;
+  files: number;
+  modified: number | null;
+  level: SafetyLevel;
+}
+
+export interface CrashDumpReport {
+  dumps: CrashDump[];
+  total_bytes: number;
+  auto_delete_bytes: number;
+}
+
+export type BlockReason = 'missing' | 'protected' | 'read_only' | 'access_denied' | 'locked';
+
+export interface BlockedEntry {
+  path: string;
+  reason: BlockReason;
+  message: string;
+}
+
+export interface FeasibilityReport {
+  checked_entries: number;
+  complete: boolean;
+  blocked: BlockedEntry[];
+  blocked_count: number;
+  failing_paths: string[];
+}
+
+export type CscGuidance = 'lower_cache_limit' | 'reset_cache' | 'disable_offline_files';
+
+/** Space held by Windows Offline Files, which scans never see */
+export interface OfflineFilesCache {
+  path: string;
+  bytes: number;
+  files: number;
+  complete: boolean;
+  guidance: CscGuidance[];
+}
+
+export type StoreItemKind = 'orphaned_installer' | 'superseded_driver' | 'unregistered_font';
+
+export type StoreCleanup =
+  | { type: 'delete_file' }
+  | { type: 'remove_driver_package'; published_name: string };
+
+export interface StoreItem {
+  path: string;
+  kind: StoreItemKind;
+  size_bytes: number;
+  label: string;
+  level: SafetyLevel;
+  caveat: string;
+  cleanup: StoreCleanup;
+}
+
+/** Installer cache, driver store, and Fonts folder, which scans never see */
+export interface WindowsStoresReport {
+  installer_cache_bytes: number;
+  driver_store_bytes: number;
+  fonts_bytes: number;
+  items: StoreItem[];
+  reclaimable_bytes: number;
+  errors: string[];
+}
+
+export interface StoreCleanupResult {
+  path: string;
+  freed_bytes: number;
+  error: string | null;
+}
+
+/** A folder being re-measured on an interval */
+export interface NodeWatch {
+  watch_id: string;
+  path: string;
+  interval_secs: number;
+  bytes: number;
+  files: number;
+}
+
+/** Payload of `watch://size-changed` */
+export interface SizeChangedPayload {
+  watch_id: string;
+  path: string;
+  bytes: number;
+  files: number;
+  delta_bytes: number;
+  delta_files: number;
+  bytes_per_hour: number;
+  exists: boolean;
+  measured_at: number;
+}
+
+export type DeleteMethod = 'trash' | 'permanent' | 'secure_wipe';
+
+export interface DeleteCalibration {
+  mount_point: string;
+  delete_secs_per_entry: number;
+  trash_secs_per_item: number;
+  write_bytes_per_sec: number;
+  measured_at: number;
+}
+
+export interface DeleteTimeEstimate {
+  method: DeleteMethod;
+  seconds: number;
+}
+
+export interface DeletePreview {
+  totals: { bytes: number; files: number; folders: number };
+  items: number;
+  estimates: DeleteTimeEstimate[];
+  wipe_passes: number;
+  calibrations: DeleteCalibration[];
+  errors: string[];
+  /** One-time token covering the ConfirmRequired paths */
+  confirmation: ConfirmationToken | null;
+}
+
+export type GrowthRange = 'day' | 'week';
+
+export interface DirGrowth {
+  path: string;
+  /** Negative when the directory shrank */
+  delta_bytes: number;
+  bytes: number;
+}
+
+export interface GrowthPeriod {
+  start: number;
+  end: number;
+  scans: number;
+  total_delta_bytes: number;
+  top_dirs: DirGrowth[];
+}
+
+export interface GrowthReport {
+  root_path: string;
+  range: GrowthRange;
+  periods: GrowthPeriod[];
+}